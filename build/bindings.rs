@@ -0,0 +1,171 @@
+use gl_generator;
+use gl_generator::generators::{self, Generator};
+use gl_generator::registry::{Cmd, Ns, Registry};
+
+use std::io::Write;
+
+/// Writes bindings whose functions can call `glGetError` after each call, and record each call
+/// with the `trace` module of glium before executing it if `trace` is true.
+///
+/// The bindings generated by `StructGenerator` are written in a `raw` module, and a `Gl` struct
+/// that wraps them replaces the original one.
+pub fn write_wrapped_bindings<W>(registry: &Registry, dest: &mut W, trace: bool)
+                                 where W: Write
+{
+    (writeln!(dest, "mod raw {{")).unwrap();
+    gl_generator::StructGenerator.write(registry, Ns::Gl, dest).unwrap();
+    (writeln!(dest, "}}")).unwrap();
+
+    (writeln!(dest, r#"
+        mod __gl_imports {{
+            extern crate gl_common;
+            extern crate libc;
+            pub use std::mem;
+            pub use std::marker::Send;
+        }}
+
+        pub use self::raw::*;
+
+        /// The OpenGL functions.
+        #[derive(Clone)]
+        pub struct Gl {{
+            raw: raw::Gl,
+
+            /// If true, `glGetError` is called after each function.
+            check_errors: ::std::cell::Cell<bool>,
+
+            /// The errors detected after each function, with the name of the function.
+            errors: ::std::cell::RefCell<Vec<(&'static str, types::GLenum)>>,
+
+            /// The first error detected after each function since the last call to
+            /// `take_unread_error`. Plays the role of the error flag that `glGetError` would
+            /// have returned if we hadn't called it.
+            unread_error: ::std::cell::Cell<Option<types::GLenum>>,
+        }}
+
+        impl ::std::ops::Deref for Gl {{
+            type Target = raw::Gl;
+
+            #[inline]
+            fn deref(&self) -> &raw::Gl {{
+                &self.raw
+            }}
+        }}
+
+        impl Gl {{
+            /// Loads each OpenGL symbol using a custom load function.
+            #[allow(dead_code)]
+            pub fn load_with<F>(loadfn: F) -> Gl
+                                where F: FnMut(&str) -> *const __gl_imports::libc::c_void
+            {{
+                Gl {{
+                    raw: raw::Gl::load_with(loadfn),
+                    check_errors: ::std::cell::Cell::new(false),
+                    errors: ::std::cell::RefCell::new(Vec::new()),
+                    unread_error: ::std::cell::Cell::new(None),
+                }}
+            }}
+
+            /// Enables or disables calling `glGetError` after each function.
+            #[doc(hidden)]
+            #[inline]
+            pub fn set_check_errors(&self, check: bool) {{
+                self.check_errors.set(check);
+            }}
+
+            /// Returns and clears the errors detected since the previous call, with the name of
+            /// the function that generated them.
+            #[doc(hidden)]
+            #[inline]
+            pub fn take_errors(&self) -> Vec<(&'static str, types::GLenum)> {{
+                ::std::mem::replace(&mut *self.errors.borrow_mut(), Vec::new())
+            }}
+
+            /// Returns and clears the first error detected after a function since the previous
+            /// call, even if it has already been returned by `take_errors`.
+            #[doc(hidden)]
+            #[inline]
+            pub fn take_unread_error(&self) -> Option<types::GLenum> {{
+                let error = self.unread_error.get();
+                self.unread_error.set(None);
+                error
+            }}
+
+            #[inline(never)]
+            unsafe fn check_error(&self, symbol: &'static str) {{
+                let error = self.raw.GetError();
+                if error != NO_ERROR {{
+                    self.errors.borrow_mut().push((symbol, error));
+                    if self.unread_error.get().is_none() {{
+                        self.unread_error.set(Some(error));
+                    }}
+                }}
+            }}
+    "#)).unwrap();
+
+    for cmd in registry.cmd_iter() {
+        let idents = generators::gen_parameters(cmd, true, false);
+        let symbol = generators::gen_symbol_name(&Ns::Gl, &cmd.proto.ident);
+
+        let record = if trace {
+            let (format, arguments) = gen_format_arguments(cmd);
+            format!(r#"
+                if ::trace::is_enabled() {{
+                    ::trace::record_call("{symbol}", format!("{format}"{arguments}));
+                }}
+            "#, symbol = symbol, format = format, arguments = arguments)
+        } else {
+            String::new()
+        };
+
+        // checking the errors after `glGetError` would discard the error that it returns
+        let check = if cmd.proto.ident == "GetError" {
+            String::new()
+        } else {
+            format!(r#"
+                if self.check_errors.get() {{
+                    self.check_error("{symbol}");
+                }}
+            "#, symbol = symbol)
+        };
+
+        (writeln!(dest, r#"
+            #[allow(non_snake_case)]
+            #[allow(unused_variables)]
+            #[allow(dead_code)]
+            #[inline]
+            pub unsafe fn {name}(&self, {params}) -> {return_type} {{
+                {record}
+                let result = self.raw.{name}({idents});
+                {check}
+                result
+            }}
+        "#, name = cmd.proto.ident,
+            params = generators::gen_parameters(cmd, true, true).join(", "),
+            return_type = generators::gen_return_type(cmd),
+            record = record, check = check, idents = idents.join(", "))).unwrap();
+    }
+
+    (writeln!(dest, "}}")).unwrap();
+}
+
+/// Returns the format string and the arguments to pass to `format!` in order to print the
+/// parameters of a command, separated by commas.
+///
+/// The arguments start with a comma, so that they can be appended to the format string.
+pub fn gen_format_arguments(cmd: &Cmd) -> (String, String) {
+    let idents = generators::gen_parameters(cmd, true, false);
+    let types = generators::gen_parameters(cmd, false, true);
+
+    // the callbacks don't implement `Debug`
+    let arguments = idents.iter().zip(types.iter()).map(|(ident, ty)| {
+        if ty.contains("GLDEBUGPROC") {
+            ", \"<callback>\"".to_string()
+        } else {
+            format!(", {}", ident)
+        }
+    }).collect::<Vec<_>>().concat();
+
+    let format = idents.iter().map(|_| "{:?}").collect::<Vec<_>>().join(", ");
+    (format, arguments)
+}
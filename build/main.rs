@@ -6,8 +6,9 @@ use std::fs::File;
 use std::io::BufReader;
 use std::io::Write;
 use std::path::Path;
-use gl_generator::generators::Generator;
 
+mod bindings;
+mod mock;
 mod textures;
 
 fn main() {
@@ -17,11 +18,17 @@ fn main() {
     textures::build_texture_file(&mut File::create(&dest.join("textures.rs")).unwrap());
 
 
+    let registry = build_gl_registry();
+
     let mut gl_bindings = File::create(&dest.join("gl_bindings.rs")).unwrap();
-    generate_gl_bindings(&mut gl_bindings);
+    generate_gl_bindings(&registry, &mut gl_bindings);
+
+    if env::var("CARGO_FEATURE_MOCK_BACKEND").is_ok() {
+        mock::write_mock_stubs(&registry, &mut File::create(&dest.join("mock_gl.rs")).unwrap());
+    }
 }
 
-fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
+fn build_gl_registry() -> gl_generator::registry::Registry {
     let gl_registry = {
         let reader = BufReader::new(khronos_api::GL_XML);
         let ns = gl_generator::registry::Ns::Gl;
@@ -46,8 +53,10 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_ARB_ES3_2_compatibility".to_string(),
                 "GL_ARB_framebuffer_sRGB".to_string(),
                 "GL_ARB_geometry_shader4".to_string(),
+                "GL_ARB_get_texture_sub_image".to_string(),
                 "GL_ARB_gpu_shader_fp64".to_string(),
                 "GL_ARB_invalidate_subdata".to_string(),
+                "GL_ARB_multi_bind".to_string(),
                 "GL_ARB_multi_draw_indirect".to_string(),
                 "GL_ARB_occlusion_query".to_string(),
                 "GL_ARB_pixel_buffer_object".to_string(),
@@ -55,11 +64,13 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_ARB_shader_image_load_store".to_string(),
                 "GL_ARB_shader_objects".to_string(),
                 "GL_ARB_texture_buffer_object".to_string(),
+                "GL_ARB_texture_filter_minmax".to_string(),
                 "GL_ARB_texture_float".to_string(),
                 "GL_ARB_texture_multisample".to_string(),
                 "GL_ARB_texture_rg".to_string(),
                 "GL_ARB_texture_rgb10_a2ui".to_string(),
                 "GL_ARB_transform_feedback3".to_string(),
+                "GL_ARB_vertex_attrib_binding".to_string(),
                 "GL_ARB_vertex_buffer_object".to_string(),
                 "GL_ARB_vertex_shader".to_string(),
                 "GL_ATI_draw_buffers".to_string(),
@@ -73,6 +84,7 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_EXT_gpu_shader4".to_string(),
                 "GL_EXT_packed_depth_stencil".to_string(),
                 "GL_EXT_provoking_vertex".to_string(),
+                "GL_EXT_shader_framebuffer_fetch_non_coherent".to_string(),
                 "GL_EXT_texture_array".to_string(),
                 "GL_EXT_texture_buffer_object".to_string(),
                 "GL_EXT_texture_compression_s3tc".to_string(),
@@ -84,6 +96,7 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_KHR_robustness".to_string(),
                 "GL_NVX_gpu_memory_info".to_string(),
                 "GL_NV_conditional_render".to_string(),
+                "GL_NV_vdpau_interop".to_string(),
                 "GL_NV_vertex_attrib_integer_64bit".to_string(),
             ],
             version: "4.5".to_string(),
@@ -112,12 +125,17 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_EXT_occlusion_query_boolean".to_string(),
                 "GL_EXT_primitive_bounding_box".to_string(),
                 "GL_EXT_robustness".to_string(),
+                "GL_EXT_shader_framebuffer_fetch_non_coherent".to_string(),
+                "GL_EXT_shader_pixel_local_storage".to_string(),
+                "GL_EXT_shader_pixel_local_storage2".to_string(),
+                "GL_EXT_texture_filter_minmax".to_string(),
                 "GL_KHR_debug".to_string(),
                 "GL_NV_copy_buffer".to_string(),
                 "GL_NV_framebuffer_multisample".to_string(),
                 "GL_NV_pixel_buffer_object".to_string(),
                 "GL_OES_depth_texture".to_string(),
                 "GL_OES_draw_elements_base_vertex".to_string(),
+                "GL_OES_EGL_image".to_string(),
                 "GL_OES_packed_depth_stencil".to_string(),
                 "GL_OES_primitive_bounding_box".to_string(),
                 "GL_OES_rgb8_rgba8".to_string(),
@@ -133,6 +151,12 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
         gl_generator::registry::Registry::from_xml(reader, ns, Some(filter))
     };
 
-    gl_generator::StructGenerator.write(&(gl_registry + gles_registry),
-                                        gl_generator::registry::Ns::Gl, dest).unwrap();
+    gl_registry + gles_registry
+}
+
+fn generate_gl_bindings<W>(registry: &gl_generator::registry::Registry, dest: &mut W)
+                           where W: Write
+{
+    let trace = env::var("CARGO_FEATURE_GL_TRACE").is_ok();
+    bindings::write_wrapped_bindings(registry, dest, trace);
 }
@@ -0,0 +1,61 @@
+use gl_generator::generators;
+use gl_generator::registry::{Ns, Registry};
+
+use std::io::Write;
+
+use bindings;
+
+/// Writes a stub for each OpenGL function, for the mock backend of glium.
+///
+/// Each stub records the call with `super::record_call`, sets the values pointed to by its
+/// output parameters to zero, and returns zero. The generated `get_stub` function returns the
+/// address of the stub whose symbol is given.
+pub fn write_mock_stubs<W>(registry: &Registry, dest: &mut W) where W: Write {
+    let mut symbols = Vec::new();
+
+    for cmd in registry.cmd_iter() {
+        let symbol = generators::gen_symbol_name(&Ns::Gl, &cmd.proto.ident);
+        let idents = generators::gen_parameters(cmd, true, false);
+        let types = generators::gen_parameters(cmd, false, true);
+        let (format, arguments) = bindings::gen_format_arguments(cmd);
+
+        // the pointers to `void` are buffers whose size is unknown
+        let outputs = idents.iter().zip(types.iter()).filter(|&(_, ty)| {
+            ty.starts_with("*mut ") && !ty.ends_with("c_void")
+        }).map(|(ident, _)| {
+            format!("if !{0}.is_null() {{ *{0} = __gl_imports::mem::zeroed(); }}\n", ident)
+        }).collect::<Vec<_>>().concat();
+
+        (writeln!(dest, r#"
+            #[allow(non_snake_case)]
+            #[allow(unused_variables)]
+            unsafe extern "system" fn {symbol}({params}) -> {return_type} {{
+                super::record_call("{symbol}", format!("{format}"{arguments}));
+                {outputs}
+                __gl_imports::mem::zeroed()
+            }}
+        "#, symbol = symbol,
+            params = generators::gen_parameters(cmd, true, true).join(", "),
+            return_type = generators::gen_return_type(cmd),
+            format = format, arguments = arguments, outputs = outputs)).unwrap();
+
+        symbols.push(symbol);
+    }
+
+    (writeln!(dest, r#"
+        /// Returns the address of the stub of an OpenGL function, or a null pointer if the
+        /// function is unknown.
+        pub fn get_stub(symbol: &str) -> *const __gl_imports::libc::c_void {{
+            match symbol {{
+    "#)).unwrap();
+
+    for symbol in symbols.iter() {
+        (writeln!(dest, "\"{0}\" => {0} as *const __gl_imports::libc::c_void,", symbol)).unwrap();
+    }
+
+    (writeln!(dest, r#"
+                _ => __gl_imports::ptr::null(),
+            }}
+        }}
+    "#)).unwrap();
+}
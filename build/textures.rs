@@ -313,7 +313,8 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
             use texture::pixel_buffer::PixelBuffer;
             use texture::{{TextureCreationError, Texture1dDataSource, Texture2dDataSource}};
             use texture::{{Texture3dDataSource, Texture2dDataSink, MipmapsOption, CompressedMipmapsOption, Texture}};
-            use texture::{{RawImage1d, RawImage2d, RawImage3d, CubeLayer}};
+            use texture::{{RawImage1d, RawImage2d, RawImage3d, CubeLayer, TextureUsage}};
+            use texture::{{PixelValue, ReadError}};
 
             use image_format::{{ClientFormatAny, TextureFormatRequest}};
             use image_format::{{UncompressedFloatFormat, UncompressedIntFormat}};
@@ -583,7 +584,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                               -> Result<{name}, TextureCreationError>
                               where T: {data_source_trait}<'a>, F: Facade
                 {{
-                    {name}::new_impl(facade, data, None, {mipmap_default})
+                    {name}::new_impl(facade, data, None, {mipmap_default}, TextureUsage::General)
                 }}
             ", data_source_trait = data_source_trait, param = param, name = name,
                mipmap_default = mipmap_default, gen_doc = gen_doc)).unwrap();
@@ -608,7 +609,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                                               -> Result<{name}, TextureCreationError>
                                               where T: {data_source_trait}<'a>, F: Facade
                 {{
-                    {name}::new_impl(facade, data, None, mipmaps)
+                    {name}::new_impl(facade, data, None, mipmaps, TextureUsage::General)
                 }}
             ", data_source_trait = data_source_trait, param = param, name = name,
                mipmaps = mipmaps_option_ty)).unwrap();
@@ -638,7 +639,8 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                     let data = Cow::Borrowed(data.as_ref());
                     let client_format = {client_format_any}(format);
                     Ok({name}(try!(any::new_texture(facade, {default_format}, Some((client_format, data)),
-                                                    mipmaps.into(), {dim_params_passing}))))
+                                                    mipmaps.into(), TextureUsage::General,
+                                                    {dim_params_passing}))))
                 }}
             ", dim_params = dimensions_parameters_input, dim_params_passing = dimensions_parameters_passing,
                param = param, client_format_any = client_format_any_ty, 
@@ -666,7 +668,21 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                                           -> Result<{name}, TextureCreationError>
                                           where T: {data_source_trait}<'a>, F: Facade
                 {{
-                    {name}::new_impl(facade, data, Some(format), mipmaps)
+                    {name}::new_impl(facade, data, Some(format), mipmaps, TextureUsage::General)
+                }}
+
+                /// Builds a new texture with a specific format and a hint about how it is going
+                /// to be used.
+                ///
+                /// See the documentation of `TextureUsage`.
+                #[inline]
+                pub fn with_format_and_usage<'a, F, T>(facade: &F, data: {param},
+                                                    format: {format}, mipmaps: {mipmaps},
+                                                    usage: TextureUsage)
+                                                    -> Result<{name}, TextureCreationError>
+                                                    where T: {data_source_trait}<'a>, F: Facade
+                {{
+                    {name}::new_impl(facade, data, Some(format), mipmaps, usage)
                 }}
             ", data_source_trait = data_source_trait, param = param,
                format = relevant_format, name = name,
@@ -688,7 +704,8 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
         (writeln!(dest, "
                 #[inline]
                 fn new_impl<'a, F, T>(facade: &F, data: {param},
-                                   format: Option<{relevant_format}>, mipmaps: {mipmaps})
+                                   format: Option<{relevant_format}>, mipmaps: {mipmaps},
+                                   usage: TextureUsage)
                                    -> Result<{name}, TextureCreationError>
                                    where T: {data_source_trait}<'a>, F: Facade
                 {{
@@ -734,7 +751,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
 
         // writing the constructor
         (write!(dest, "Ok({}(try!(any::new_texture(facade, format, \
-                       Some((client_format, data)), mipmaps.into(), {}", name, dimensions_parameters_passing)).unwrap();
+                       Some((client_format, data)), mipmaps.into(), usage, {}", name, dimensions_parameters_passing)).unwrap();
         (writeln!(dest, "))))")).unwrap();
 
         // end of "new" function block
@@ -760,7 +777,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                )).unwrap();
 
         // writing the constructor
-        (write!(dest, "any::new_texture::<_, u8>(facade, format, None, {mipmap}::NoMipmap.into(), {}).map(|t| {}(t))",
+        (write!(dest, "any::new_texture::<_, u8>(facade, format, None, {mipmap}::NoMipmap.into(), TextureUsage::General, {}).map(|t| {}(t))",
                 dimensions_parameters_passing, name, mipmap = mipmaps_option_ty)).unwrap();
 
         // closing function
@@ -782,7 +799,32 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                mipmaps = mipmaps_option_ty)).unwrap();
 
         // writing the constructor
-        (write!(dest, "let t = any::new_texture::<_, u8>(facade, format, None, mipmaps.into(), {});", dimensions_parameters_passing)).unwrap();
+        (write!(dest, "let t = any::new_texture::<_, u8>(facade, format, None, mipmaps.into(), TextureUsage::General, {});", dimensions_parameters_passing)).unwrap();
+        (writeln!(dest, "
+            t.map(|t| {}(t))", name)).unwrap();
+
+        // closing function
+        (writeln!(dest, "}}")).unwrap();
+    }
+
+    // writing the `empty_with_usage` function
+    if true {
+        // opening function
+        (writeln!(dest, "
+                /// Creates an empty texture with a specific format and a hint about how it is
+                /// going to be used.
+                ///
+                /// See the documentation of `TextureUsage`. The texture (and its mipmaps) will
+                /// contain undefined data.
+                #[inline]
+                pub fn empty_with_usage<F>(facade: &F, format: {format}, mipmaps: {mipmaps}, usage: TextureUsage, {dim_params}) -> Result<{name}, TextureCreationError> where F: Facade {{
+                    let format = format.to_texture_format();
+                    let format = TextureFormatRequest::Specific(format);
+            ", format = relevant_format, dim_params = dimensions_parameters_input, name = name,
+               mipmaps = mipmaps_option_ty)).unwrap();
+
+        // writing the constructor
+        (write!(dest, "let t = any::new_texture::<_, u8>(facade, format, None, mipmaps.into(), usage, {});", dimensions_parameters_passing)).unwrap();
         (writeln!(dest, "
             t.map(|t| {}(t))", name)).unwrap();
 
@@ -804,13 +846,36 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                mipmaps = mipmaps_option_ty)).unwrap();
 
         // writing the constructor
-        (write!(dest, "any::new_texture::<_, u8>(facade, format, None, mipmaps.into(), {})", dimensions_parameters_passing)).unwrap();
+        (write!(dest, "any::new_texture::<_, u8>(facade, format, None, mipmaps.into(), TextureUsage::General, {})", dimensions_parameters_passing)).unwrap();
         (writeln!(dest, ".map(|t| {}(t))", name)).unwrap();
 
         // closing function
         (writeln!(dest, "}}")).unwrap();
     }
 
+    // writing the `from_id` function
+    (writeln!(dest, "
+            /// Builds a texture from the id of an existing OpenGL texture, for example a texture
+            /// created by another library.
+            ///
+            /// If `owned` is true, the texture is destroyed when the object is dropped.
+            /// Otherwise it is left alive, and whoever created it is responsible for destroying
+            /// it after the object has been dropped.
+            ///
+            /// # Safety
+            ///
+            /// `id` must be a texture of this context whose type corresponds to `{name}`, whose
+            /// format is `format`, and whose dimensions and mipmaps match `ty` and `mipmaps`.
+            #[inline]
+            pub unsafe fn from_id<F>(facade: &F, format: {format}, id: gl::types::GLuint,
+                                     owned: bool, mipmaps: {mipmaps}, ty: Dimensions)
+                                     -> {name} where F: Facade
+            {{
+                let format = TextureFormatRequest::Specific(format.to_texture_format());
+                {name}(any::from_id(facade, format, id, owned, mipmaps.into(), ty))
+            }}
+        ", format = relevant_format, name = name, mipmaps = mipmaps_option_ty)).unwrap();
+
     // writing the `as_surface` function
     if (dimensions == TextureDimensions::Texture2d ||
         dimensions == TextureDimensions::Texture2dMultisample) && ty == TextureType::Regular
@@ -864,6 +929,22 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                 }}
             "#)).unwrap();
 
+        (write!(dest, r#"
+                /// Reads the content of the texture to RAM with a pixel type other than
+                /// `(u8, u8, u8, u8)`, for example `(f16, f16, f16, f16)`.
+                ///
+                /// Returns `FormatNotSupported` if the backend can't read pixels of this type.
+                #[inline]
+                pub fn read_if_supported<T, P>(&self) -> Result<T, ReadError>
+                                               where T: Texture2dDataSink<P>, P: PixelValue
+                {{
+                    let rect = Rect {{ left: 0, bottom: 0, width: self.get_width(),
+                                       height: self.get_height().unwrap_or(1) }};
+                    self.0.main_level().first_layer().into_image(None).unwrap()
+                          .raw_read_if_supported(&rect)
+                }}
+            "#)).unwrap();
+
         (write!(dest, r#"
                 /// Reads the content of the texture into a buffer in video memory.
                 ///
@@ -72,6 +72,7 @@ fn body(ecx: &mut base::ExtCtxt, span: codemap::Span,
                             Cow::Borrowed($ident_str),
                             offset,
                             <$elem_type as Attribute>::get_type(),
+                            false,
                         ));
                     })
 
@@ -0,0 +1,538 @@
+#![cfg(feature = "egl_headless")]
+/*!
+
+Headless backend that uses EGL directly, without any window or display server.
+
+This is useful for rendering on a server, or for running tests on a continuous integration
+machine that has no X11 server.
+
+# Features
+
+Only available if the 'egl_headless' feature is enabled. Requires `libEGL` and an implementation
+that supports the `EGL_KHR_surfaceless_context` extension, like Mesa. If the
+`EGL_MESA_platform_surfaceless` extension is available, it is used so that no display server
+is needed at all.
+
+# Sharing
+
+`EglHeadlessFacade::build_shared_backend` creates a new EGL context that shares its objects
+(buffers, textures, programs, etc.) with an existing one. The returned `EglHeadlessBackend` can
+be sent to another thread and turned into a facade there with `EglHeadlessFacade::from_backend`.
+
+Note that a glium object can only be used with the facade that has created it. Sharing only
+means that the OpenGL objects live in the same share group, for example to upload textures
+on a background thread and hand over their raw ids.
+
+# Usage
+
+Since there is no window, there is no default framebuffer. You must draw on textures or
+renderbuffers instead, for example with a `SimpleFrameBuffer`.
+
+```no_run
+# use glium::Surface;
+use glium::backend::egl_headless::EglHeadlessFacade;
+
+let facade = EglHeadlessFacade::new(1024, 768).unwrap();
+
+let texture = glium::texture::Texture2d::empty(&facade, 1024, 768).unwrap();
+texture.as_surface().clear_color(0.0, 0.0, 1.0, 1.0);
+
+let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+```
+
+*/
+use libc;
+
+use GliumCreationError;
+use SwapBuffersError;
+
+use context;
+use backend;
+use backend::Context;
+use backend::Backend;
+use native_fence::{NativeFenceError, NativeFences};
+
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::ptr;
+use std::rc::Rc;
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use libc;
+
+    pub type EGLBoolean = libc::c_uint;
+    pub type EGLenum = libc::c_uint;
+    pub type EGLint = i32;
+    pub type EGLDisplay = *mut libc::c_void;
+    pub type EGLConfig = *mut libc::c_void;
+    pub type EGLContext = *mut libc::c_void;
+    pub type EGLSurface = *mut libc::c_void;
+
+    pub const EGL_FALSE: EGLBoolean = 0;
+    pub const EGL_TRUE: EGLint = 1;
+    pub const EGL_NONE: EGLint = 0x3038;
+    pub const EGL_EXTENSIONS: EGLint = 0x3055;
+    pub const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+    pub const EGL_OPENGL_BIT: EGLint = 0x0008;
+    pub const EGL_OPENGL_ES2_BIT: EGLint = 0x0004;
+    pub const EGL_OPENGL_API: EGLenum = 0x30A2;
+    pub const EGL_OPENGL_ES_API: EGLenum = 0x30A0;
+    pub const EGL_CONTEXT_CLIENT_VERSION: EGLint = 0x3098;
+    pub const EGL_PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+    pub const EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT: EGLint = 0x30BF;
+    pub const EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT: EGLint = 0x3138;
+    pub const EGL_LOSE_CONTEXT_ON_RESET_EXT: EGLint = 0x31BF;
+
+    pub type PFNEGLGETPLATFORMDISPLAYEXTPROC =
+        extern "system" fn(EGLenum, *mut libc::c_void, *const EGLint) -> EGLDisplay;
+
+    #[link(name = "EGL")]
+    extern "system" {
+        pub fn eglGetDisplay(display_id: *mut libc::c_void) -> EGLDisplay;
+        pub fn eglInitialize(dpy: EGLDisplay, major: *mut EGLint, minor: *mut EGLint)
+                             -> EGLBoolean;
+        pub fn eglQueryString(dpy: EGLDisplay, name: EGLint) -> *const libc::c_char;
+        pub fn eglBindAPI(api: EGLenum) -> EGLBoolean;
+        pub fn eglChooseConfig(dpy: EGLDisplay, attrib_list: *const EGLint,
+                               configs: *mut EGLConfig, config_size: EGLint,
+                               num_config: *mut EGLint) -> EGLBoolean;
+        pub fn eglCreateContext(dpy: EGLDisplay, config: EGLConfig, share_context: EGLContext,
+                                attrib_list: *const EGLint) -> EGLContext;
+        pub fn eglDestroyContext(dpy: EGLDisplay, ctx: EGLContext) -> EGLBoolean;
+        pub fn eglMakeCurrent(dpy: EGLDisplay, draw: EGLSurface, read: EGLSurface,
+                              ctx: EGLContext) -> EGLBoolean;
+        pub fn eglGetCurrentContext() -> EGLContext;
+        pub fn eglGetProcAddress(procname: *const libc::c_char) -> *const libc::c_void;
+    }
+}
+
+/// Error that can happen while creating an EGL headless backend.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CreationError {
+    /// No EGL display is available.
+    NoDisplay,
+
+    /// The call to `eglInitialize` failed.
+    InitializationFailed,
+
+    /// The EGL implementation doesn't support `EGL_KHR_surfaceless_context`.
+    SurfacelessNotSupported,
+
+    /// No configuration supports OpenGL or OpenGL ES 2.
+    NoAvailableConfig,
+
+    /// The call to `eglCreateContext` failed.
+    ContextCreationFailed,
+
+    /// A robust context was requested, but the EGL implementation doesn't support
+    /// `EGL_EXT_create_context_robustness`.
+    RobustnessNotSupported,
+
+    /// Robust buffer access was requested, but the OpenGL implementation doesn't define the
+    /// behavior of out-of-bound buffer accesses.
+    RobustBufferAccessNotSupported,
+}
+
+impl fmt::Display for CreationError {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::NoDisplay => "No EGL display is available",
+            &CreationError::InitializationFailed => "Failed to initialize EGL",
+            &CreationError::SurfacelessNotSupported => "The EGL implementation doesn't support \
+                                                        surfaceless contexts",
+            &CreationError::NoAvailableConfig => "No EGL configuration supports OpenGL or \
+                                                  OpenGL ES 2",
+            &CreationError::ContextCreationFailed => "Failed to create the EGL context",
+            &CreationError::RobustnessNotSupported => "The EGL implementation doesn't support \
+                                                       robust contexts",
+            &CreationError::RobustBufferAccessNotSupported => "The OpenGL implementation doesn't \
+                                                               support robust buffer access",
+        }
+    }
+}
+
+/// An implementation of the `Backend` trait for a surfaceless EGL context.
+pub struct EglHeadlessBackend {
+    display: ffi::EGLDisplay,
+    context: ffi::EGLContext,
+    config: ffi::EGLConfig,
+    api: ffi::EGLenum,
+    robust: bool,
+    dimensions: (u32, u32),
+}
+
+// An EGL context can be made current on any thread, as long as it is not current on another one.
+// The backend is only made current by the `Context` that owns it, and `Context` isn't `Send`, so
+// a backend that can be moved to another thread is not current anywhere. The display is shared
+// by the whole process.
+unsafe impl Send for EglHeadlessBackend {}
+
+unsafe impl Backend for EglHeadlessBackend {
+    #[inline]
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+        let symbol = CString::new(symbol.as_bytes()).unwrap();
+        ffi::eglGetProcAddress(symbol.as_ptr())
+    }
+
+    #[inline]
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        unsafe { ffi::eglGetCurrentContext() == self.context }
+    }
+
+    #[inline]
+    unsafe fn make_current(&self) {
+        // if this fails, the context is not current and `Context` reports it as lost
+        ffi::eglMakeCurrent(self.display, ptr::null_mut(), ptr::null_mut(), self.context);
+    }
+}
+
+impl EglHeadlessBackend {
+    /// Builds a new backend.
+    ///
+    /// The dimensions are the ones returned by `get_framebuffer_dimensions`. As there is no
+    /// default framebuffer, they are only used as the default viewport.
+    #[inline]
+    pub fn new(width: u32, height: u32) -> Result<EglHeadlessBackend, CreationError> {
+        EglHeadlessBackend::new_impl(width, height, false)
+    }
+
+    /// Builds a new backend with a robust context.
+    ///
+    /// Out-of-bounds accesses from the GPU can't crash, and a GPU reset is reported with
+    /// `Context::is_context_lost` instead of resulting in undefined behavior.
+    #[inline]
+    pub fn new_robust(width: u32, height: u32) -> Result<EglHeadlessBackend, CreationError> {
+        EglHeadlessBackend::new_impl(width, height, true)
+    }
+
+    fn new_impl(width: u32, height: u32, robust: bool)
+                -> Result<EglHeadlessBackend, CreationError>
+    {
+        unsafe {
+            let display = get_display();
+            if display.is_null() {
+                return Err(CreationError::NoDisplay);
+            }
+
+            if ffi::eglInitialize(display, ptr::null_mut(), ptr::null_mut()) == ffi::EGL_FALSE {
+                return Err(CreationError::InitializationFailed);
+            }
+
+            let extensions = ffi::eglQueryString(display, ffi::EGL_EXTENSIONS);
+
+            if !has_extension(extensions, "EGL_KHR_surfaceless_context") {
+                return Err(CreationError::SurfacelessNotSupported);
+            }
+
+            if robust && !has_extension(extensions, "EGL_EXT_create_context_robustness") {
+                return Err(CreationError::RobustnessNotSupported);
+            }
+
+            // trying desktop OpenGL first, then OpenGL ES 2
+            let config = choose_config(display, ffi::EGL_OPENGL_API, ffi::EGL_OPENGL_BIT)
+                .map(|config| (ffi::EGL_OPENGL_API, config))
+                .or_else(|_| {
+                    choose_config(display, ffi::EGL_OPENGL_ES_API, ffi::EGL_OPENGL_ES2_BIT)
+                        .map(|config| (ffi::EGL_OPENGL_ES_API, config))
+                });
+            let (api, config) = try!(config);
+
+            let context = try!(create_context(display, api, config, robust, ptr::null_mut()));
+
+            Ok(EglHeadlessBackend {
+                display: display,
+                context: context,
+                config: config,
+                api: api,
+                robust: robust,
+                dimensions: (width, height),
+            })
+        }
+    }
+
+    /// Builds a new backend whose context shares its objects with this one.
+    ///
+    /// The new context uses the same display, API and configuration.
+    #[inline]
+    pub fn build_shared(&self, width: u32, height: u32)
+                        -> Result<EglHeadlessBackend, CreationError>
+    {
+        unsafe {
+            build_shared(self.display, self.api, self.config, self.robust, self.context,
+                         (width, height))
+        }
+    }
+}
+
+impl Drop for EglHeadlessBackend {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            if self.is_current() {
+                ffi::eglMakeCurrent(self.display, ptr::null_mut(), ptr::null_mut(),
+                                    ptr::null_mut());
+            }
+
+            ffi::eglDestroyContext(self.display, self.context);
+        }
+    }
+}
+
+/// Returns the surfaceless display if it is supported, or the default display otherwise.
+unsafe fn get_display() -> ffi::EGLDisplay {
+    // the client extensions are queried with `EGL_NO_DISPLAY`
+    let client_extensions = ffi::eglQueryString(ptr::null_mut(), ffi::EGL_EXTENSIONS);
+
+    if has_extension(client_extensions, "EGL_MESA_platform_surfaceless") &&
+       has_extension(client_extensions, "EGL_EXT_platform_base")
+    {
+        let name = CString::new("eglGetPlatformDisplayEXT").unwrap();
+        let get_platform_display = ffi::eglGetProcAddress(name.as_ptr());
+
+        if !get_platform_display.is_null() {
+            let get_platform_display: ffi::PFNEGLGETPLATFORMDISPLAYEXTPROC =
+                                                        mem::transmute(get_platform_display);
+            let display = get_platform_display(ffi::EGL_PLATFORM_SURFACELESS_MESA,
+                                               ptr::null_mut(), ptr::null());
+            if !display.is_null() {
+                return display;
+            }
+        }
+    }
+
+    ffi::eglGetDisplay(ptr::null_mut())
+}
+
+/// Returns true if the space-separated list of extensions contains `name`.
+unsafe fn has_extension(list: *const libc::c_char, name: &str) -> bool {
+    if list.is_null() {
+        return false;
+    }
+
+    let list = CStr::from_ptr(list);
+    String::from_utf8_lossy(list.to_bytes()).split(' ').any(|e| e == name)
+}
+
+/// Chooses a configuration for the given API.
+unsafe fn choose_config(display: ffi::EGLDisplay, api: ffi::EGLenum,
+                        renderable_type: ffi::EGLint)
+                        -> Result<ffi::EGLConfig, CreationError>
+{
+    if ffi::eglBindAPI(api) == ffi::EGL_FALSE {
+        return Err(CreationError::NoAvailableConfig);
+    }
+
+    let config_attributes = [ffi::EGL_RENDERABLE_TYPE, renderable_type, ffi::EGL_NONE];
+
+    let mut config: ffi::EGLConfig = ptr::null_mut();
+    let mut num_configs = 0;
+    if ffi::eglChooseConfig(display, config_attributes.as_ptr(), &mut config, 1,
+                            &mut num_configs) == ffi::EGL_FALSE || num_configs == 0
+    {
+        return Err(CreationError::NoAvailableConfig);
+    }
+
+    Ok(config)
+}
+
+/// Creates a context for the given API. If `share` is not null, the new context shares its
+/// objects with it.
+unsafe fn create_context(display: ffi::EGLDisplay, api: ffi::EGLenum, config: ffi::EGLConfig,
+                         robust: bool, share: ffi::EGLContext)
+                         -> Result<ffi::EGLContext, CreationError>
+{
+    if ffi::eglBindAPI(api) == ffi::EGL_FALSE {
+        return Err(CreationError::NoAvailableConfig);
+    }
+
+    let mut context_attributes = Vec::with_capacity(7);
+
+    if api == ffi::EGL_OPENGL_ES_API {
+        context_attributes.push(ffi::EGL_CONTEXT_CLIENT_VERSION);
+        context_attributes.push(2);
+    }
+
+    if robust {
+        context_attributes.push(ffi::EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT);
+        context_attributes.push(ffi::EGL_TRUE);
+        context_attributes.push(ffi::EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT);
+        context_attributes.push(ffi::EGL_LOSE_CONTEXT_ON_RESET_EXT);
+    }
+
+    context_attributes.push(ffi::EGL_NONE);
+
+    let context = ffi::eglCreateContext(display, config, share, context_attributes.as_ptr());
+    if context.is_null() {
+        return Err(CreationError::ContextCreationFailed);
+    }
+
+    Ok(context)
+}
+
+/// Builds a backend whose context shares its objects with `share`, with the same display, API
+/// and configuration.
+unsafe fn build_shared(display: ffi::EGLDisplay, api: ffi::EGLenum, config: ffi::EGLConfig,
+                       robust: bool, share: ffi::EGLContext, dimensions: (u32, u32))
+                       -> Result<EglHeadlessBackend, CreationError>
+{
+    let context = try!(create_context(display, api, config, robust, share));
+
+    Ok(EglHeadlessBackend {
+        display: display,
+        context: context,
+        config: config,
+        api: api,
+        robust: robust,
+        dimensions: dimensions,
+    })
+}
+
+/// Facade implementation for a surfaceless EGL context.
+#[derive(Clone)]
+pub struct EglHeadlessFacade {
+    context: Rc<context::Context>,
+
+    // the raw handles of the backend owned by `context`, which are valid as long as `context`
+    // is alive
+    display: ffi::EGLDisplay,
+    egl_context: ffi::EGLContext,
+    config: ffi::EGLConfig,
+    api: ffi::EGLenum,
+    robust: bool,
+}
+
+impl EglHeadlessFacade {
+    /// Builds a new headless facade.
+    ///
+    /// The dimensions are the ones of the default viewport.
+    pub fn new(width: u32, height: u32)
+               -> Result<EglHeadlessFacade, GliumCreationError<CreationError>>
+    {
+        let backend = try!(EglHeadlessBackend::new(width, height));
+        EglHeadlessFacade::from_backend(backend)
+    }
+
+    /// Builds a new headless facade with a robust context.
+    ///
+    /// See `EglHeadlessBackend::new_robust`.
+    pub fn new_robust(width: u32, height: u32)
+                      -> Result<EglHeadlessFacade, GliumCreationError<CreationError>>
+    {
+        let backend = try!(EglHeadlessBackend::new_robust(width, height));
+        EglHeadlessFacade::from_backend(backend)
+    }
+
+    /// Builds a new headless facade with a robust context, and checks that out-of-bound buffer
+    /// accesses from programs have a defined behavior.
+    ///
+    /// Returns `RobustBufferAccessNotSupported` if
+    /// `Context::has_robust_buffer_access_behavior` returns false for the context that has been
+    /// obtained, in which case the context is destroyed.
+    pub fn new_with_robust_buffer_access(width: u32, height: u32)
+                                         -> Result<EglHeadlessFacade,
+                                                   GliumCreationError<CreationError>>
+    {
+        let facade = try!(EglHeadlessFacade::new_robust(width, height));
+
+        if facade.context.has_robust_buffer_access_behavior() {
+            Ok(facade)
+        } else {
+            Err(GliumCreationError::BackendCreationError(
+                CreationError::RobustBufferAccessNotSupported))
+        }
+    }
+
+    /// Builds a facade from an existing backend, for example one returned by
+    /// `build_shared_backend`.
+    pub fn from_backend(backend: EglHeadlessBackend)
+                        -> Result<EglHeadlessFacade, GliumCreationError<CreationError>>
+    {
+        let display = backend.display;
+        let egl_context = backend.context;
+        let config = backend.config;
+        let api = backend.api;
+        let robust = backend.robust;
+
+        let context = try!(unsafe { context::Context::new(backend, true) });
+
+        Ok(EglHeadlessFacade {
+            context: context,
+            display: display,
+            egl_context: egl_context,
+            config: config,
+            api: api,
+            robust: robust,
+        })
+    }
+
+    /// Builds a new backend whose context shares its objects with this facade's context.
+    ///
+    /// The backend implements `Send`, so it can be moved to another thread and turned into a
+    /// facade with `from_backend`.
+    #[inline]
+    pub fn build_shared_backend(&self, width: u32, height: u32)
+                                -> Result<EglHeadlessBackend, CreationError>
+    {
+        unsafe {
+            build_shared(self.display, self.api, self.config, self.robust, self.egl_context,
+                         (width, height))
+        }
+    }
+
+    /// Loads the functions that exchange native fence file descriptors with other APIs and
+    /// processes, for the display of this facade.
+    ///
+    /// See the `native_fence` module.
+    pub fn native_fences(&self) -> Result<NativeFences, NativeFenceError> {
+        unsafe {
+            let extensions = ffi::eglQueryString(self.display, ffi::EGL_EXTENSIONS);
+            if extensions.is_null() {
+                return Err(NativeFenceError::NotSupported);
+            }
+
+            let extensions = String::from_utf8_lossy(CStr::from_ptr(extensions).to_bytes());
+
+            NativeFences::new(self, self.display as *const _, &extensions, |symbol| {
+                let symbol = CString::new(symbol.as_bytes()).unwrap();
+                ffi::eglGetProcAddress(symbol.as_ptr())
+            })
+        }
+    }
+}
+
+impl backend::Facade for EglHeadlessFacade {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+}
+
+impl Deref for EglHeadlessFacade {
+    type Target = Context;
+
+    #[inline]
+    fn deref(&self) -> &Context {
+        &self.context
+    }
+}
@@ -0,0 +1,232 @@
+/*!
+
+Backend built from closures, for windowing libraries that glium doesn't support directly.
+
+Instead of implementing the unsafe `Backend` trait, you can give closures that load the OpenGL
+functions, swap the buffers, etc. to a `GenericBackendBuilder`.
+
+# Example
+
+```no_run
+# use std::rc::Rc;
+# fn load(_: &str) -> *const u8 { std::ptr::null() }
+# fn swap() {}
+# let window_dimensions = Rc::new(std::cell::Cell::new((800, 600)));
+use glium::backend::generic::GenericBackendBuilder;
+
+let dimensions = window_dimensions.clone();
+
+let display = GenericBackendBuilder::new(|symbol| load(symbol) as *const _)
+                    .with_swap_buffers(|| { swap(); Ok(()) })
+                    .with_framebuffer_dimensions(move || dimensions.get())
+                    .build_glium()
+                    .unwrap();
+
+let mut frame = display.draw();
+// ...
+frame.finish().unwrap();
+```
+
+If you don't provide `with_is_current` and `with_make_current`, glium supposes that the OpenGL
+context is always current in the thread where the facade is used, and that no other OpenGL
+context is ever made current in this thread.
+
+# Native window handles
+
+This module doesn't create OpenGL contexts, and thus doesn't accept native window handles
+(like the ones of the `raw-window-handle` library). Creating a context on a native window is
+the job of the windowing library, for example with `SDL_GL_CreateContext` or `eglCreateContext`,
+after which the functions of this context can be passed to `GenericBackendBuilder`.
+
+*/
+use libc;
+
+use Frame;
+use GliumCreationError;
+use SwapBuffersError;
+
+use context;
+use backend;
+use backend::Context;
+use backend::Backend;
+
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// Builds a `GenericFacade`.
+pub struct GenericBackendBuilder {
+    get_proc_address: Box<Fn(&str) -> *const libc::c_void>,
+    swap_buffers: Box<Fn() -> Result<(), SwapBuffersError>>,
+    framebuffer_dimensions: Box<Fn() -> (u32, u32)>,
+    is_current: Option<Box<Fn() -> bool>>,
+    make_current: Option<Box<Fn()>>,
+}
+
+impl GenericBackendBuilder {
+    /// Starts building a backend.
+    ///
+    /// `get_proc_address` must return the address of the OpenGL function whose name is given,
+    /// or a null pointer if it is not supported.
+    pub fn new<F>(get_proc_address: F) -> GenericBackendBuilder
+                  where F: Fn(&str) -> *const libc::c_void + 'static
+    {
+        GenericBackendBuilder {
+            get_proc_address: Box::new(get_proc_address),
+            swap_buffers: Box::new(|| Ok(())),
+            framebuffer_dimensions: Box::new(|| (800, 600)),
+            is_current: None,
+            make_current: None,
+        }
+    }
+
+    /// Sets the function that swaps the buffers at the end of a frame. The default does nothing.
+    pub fn with_swap_buffers<F>(mut self, swap_buffers: F) -> GenericBackendBuilder
+                                where F: Fn() -> Result<(), SwapBuffersError> + 'static
+    {
+        self.swap_buffers = Box::new(swap_buffers);
+        self
+    }
+
+    /// Sets the function that returns the dimensions of the default framebuffer in pixels.
+    /// The default always returns `(800, 600)`.
+    pub fn with_framebuffer_dimensions<F>(mut self, dimensions: F) -> GenericBackendBuilder
+                                          where F: Fn() -> (u32, u32) + 'static
+    {
+        self.framebuffer_dimensions = Box::new(dimensions);
+        self
+    }
+
+    /// Sets the function that returns true if the OpenGL context is current in the thread.
+    pub fn with_is_current<F>(mut self, is_current: F) -> GenericBackendBuilder
+                              where F: Fn() -> bool + 'static
+    {
+        self.is_current = Some(Box::new(is_current));
+        self
+    }
+
+    /// Sets the function that makes the OpenGL context current in the thread.
+    pub fn with_make_current<F>(mut self, make_current: F) -> GenericBackendBuilder
+                                where F: Fn() + 'static
+    {
+        self.make_current = Some(Box::new(make_current));
+        self
+    }
+
+    /// Builds the backend without creating a `Context`.
+    #[inline]
+    pub fn build_backend(self) -> GenericBackend {
+        GenericBackend { inner: self }
+    }
+
+    /// Builds the facade.
+    ///
+    /// The OpenGL context must be current in the thread, and must be newly-created.
+    pub fn build_glium(self) -> Result<GenericFacade, GliumCreationError<GenericCreationError>> {
+        let check_current_context = self.is_current.is_some() && self.make_current.is_some();
+        let backend = self.build_backend();
+        let context = try!(unsafe { context::Context::new(backend, check_current_context) });
+
+        Ok(GenericFacade {
+            context: context,
+        })
+    }
+}
+
+/// Error that can happen while building a `GenericFacade`.
+///
+/// No such error exists at the moment. This type only exists because `GliumCreationError`
+/// requires one.
+pub enum GenericCreationError {}
+
+impl fmt::Debug for GenericCreationError {
+    #[inline]
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        match *self {}
+    }
+}
+
+impl fmt::Display for GenericCreationError {
+    #[inline]
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        match *self {}
+    }
+}
+
+impl ::std::error::Error for GenericCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {}
+    }
+}
+
+/// An implementation of the `Backend` trait that calls the closures of a
+/// `GenericBackendBuilder`.
+pub struct GenericBackend {
+    inner: GenericBackendBuilder,
+}
+
+unsafe impl Backend for GenericBackend {
+    #[inline]
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        (self.inner.swap_buffers)()
+    }
+
+    #[inline]
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+        (self.inner.get_proc_address)(symbol)
+    }
+
+    #[inline]
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        (self.inner.framebuffer_dimensions)()
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        match self.inner.is_current {
+            Some(ref is_current) => is_current(),
+            None => true,
+        }
+    }
+
+    #[inline]
+    unsafe fn make_current(&self) {
+        if let Some(ref make_current) = self.inner.make_current {
+            make_current();
+        }
+    }
+}
+
+/// Facade implementation for a `GenericBackend`.
+#[derive(Clone)]
+pub struct GenericFacade {
+    context: Rc<context::Context>,
+}
+
+impl GenericFacade {
+    /// Start drawing on the backbuffer.
+    ///
+    /// This function returns a `Frame`, which can be used to draw on it. When the `Frame` is
+    /// destroyed, the buffers are swapped.
+    #[inline]
+    pub fn draw(&self) -> Frame {
+        Frame::new(self.context.clone(), self.get_framebuffer_dimensions())
+    }
+}
+
+impl backend::Facade for GenericFacade {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+}
+
+impl Deref for GenericFacade {
+    type Target = Context;
+
+    #[inline]
+    fn deref(&self) -> &Context {
+        &self.context
+    }
+}
@@ -16,13 +16,17 @@ use DisplayBuild;
 use Frame;
 use GliumCreationError;
 use SwapBuffersError;
+use SwapInterval;
+use Rect;
 
 use context;
 use backend;
 use backend::Context;
 use backend::Backend;
+use backend::SurfaceBackend;
+use backend::DefaultFramebufferProperties;
 
-use std::cell::{RefCell, Ref};
+use std::cell::{Cell, RefCell, Ref};
 use std::rc::Rc;
 use std::ops::Deref;
 
@@ -129,6 +133,94 @@ impl GlutinFacade {
     pub fn draw(&self) -> Frame {
         Frame::new(self.context.clone(), self.get_framebuffer_dimensions())
     }
+
+    /// Builds a new window whose OpenGL context shares its objects (buffers, textures, programs,
+    /// etc.) with this one.
+    ///
+    /// Note that a glium object can only be used with the facade that has created it.
+    ///
+    /// # Panic
+    ///
+    /// Panics if this facade uses a headless context.
+    pub fn build_shared_window(&self, builder: glutin::WindowBuilder<'static>)
+                               -> Result<GlutinFacade, GliumCreationError<glutin::CreationError>>
+    {
+        let backend = {
+            let existing_window = Option::as_ref(&self.backend)
+                                         .expect("can't share with a headless display").borrow();
+            Rc::new(try!(existing_window.build_shared(builder)))
+        };
+
+        let context = try!(unsafe { context::Context::new(backend.clone(), true) });
+
+        Ok(GlutinFacade {
+            context: context,
+            backend: Rc::new(Some(RefCell::new(backend))),
+        })
+    }
+
+    /// Builds a new window on which the OpenGL context of this facade can draw. Use
+    /// `draw_on_surface` to draw on it.
+    ///
+    /// Contrary to `build_shared_window`, all the glium objects of this facade can be used with
+    /// the new window. The window must have the same pixel format as the window of this facade,
+    /// which is the case if the builders request the same attributes.
+    ///
+    /// Returns an error if the window system doesn't support this, for example on OS X.
+    ///
+    /// # Panic
+    ///
+    /// Panics if this facade uses a headless context.
+    pub fn build_surface(&self, builder: glutin::WindowBuilder<'static>)
+                         -> Result<Rc<GlutinSurfaceBackend>,
+                                   GliumCreationError<glutin::CreationError>>
+    {
+        let (window, binding) = {
+            let existing_window = Option::as_ref(&self.backend)
+                                         .expect("can't draw on a surface with a headless \
+                                                  display").borrow();
+            let window = try!(builder.build());
+
+            let binding = unsafe {
+                window.make_current().unwrap();
+                let binding = backend::surface_binding::SurfaceBinding::load_current(|symbol| {
+                    window.get_proc_address(symbol)
+                });
+                existing_window.make_current();
+                binding
+            };
+
+            (window, binding)
+        };
+
+        let binding = match binding {
+            Some(binding) => binding,
+            None => {
+                let err = "Drawing on another window is not supported by the window system";
+                let err = glutin::CreationError::OsError(err.to_string());
+                return Err(GliumCreationError::BackendCreationError(err));
+            },
+        };
+
+        Ok(Rc::new(GlutinSurfaceBackend {
+            window: window,
+            facade_window: self.backend.clone(),
+            binding: binding,
+            damage_swapper: Cell::new(None),
+        }))
+    }
+
+    /// Start drawing on a surface built with `build_surface`.
+    ///
+    /// When the `Frame` is destroyed, the buffers of the surface are swapped.
+    ///
+    /// # Panic
+    ///
+    /// Panics if another `Frame` that draws on a surface hasn't been finished yet.
+    #[inline]
+    pub fn draw_on_surface(&self, surface: &Rc<GlutinSurfaceBackend>) -> Frame {
+        Frame::new_on_surface(self.context.clone(), surface.clone())
+    }
 }
 
 impl Deref for GlutinFacade {
@@ -178,6 +270,124 @@ impl DisplayBuild for glutin::WindowBuilder<'static> {
     }
 }
 
+/// Properties of the default framebuffer to request when building a window.
+///
+/// Contrary to the corresponding methods of `glutin::WindowBuilder`, `build_glium` checks that
+/// the default framebuffer that has been obtained has the requested properties.
+///
+/// # Example
+///
+/// ```no_run
+/// use glium::backend::glutin_backend::FramebufferRequest;
+///
+/// let request = FramebufferRequest {
+///     color_bits: Some(10),
+///     depth_bits: Some(24),
+///     samples: Some(4),
+///     .. Default::default()
+/// };
+///
+/// let display = request.build_glium(glium::glutin::WindowBuilder::new()).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FramebufferRequest {
+    /// Minimum number of bits of each of the red, green and blue components. For example
+    /// `Some(10)` requests a backbuffer with 10 bits per channel.
+    pub color_bits: Option<u8>,
+
+    /// Minimum number of bits of the alpha component.
+    pub alpha_bits: Option<u8>,
+
+    /// Minimum number of bits of the depth buffer.
+    pub depth_bits: Option<u8>,
+
+    /// Minimum number of bits of the stencil buffer.
+    pub stencil_bits: Option<u8>,
+
+    /// Minimum number of samples for multisampling.
+    pub samples: Option<u16>,
+
+    /// If true, requires an sRGB-capable backbuffer.
+    pub srgb: bool,
+
+    /// If true, requires a backbuffer that contains floating-point values.
+    ///
+    /// Glutin can't request this property, so the request is only satisfied if the platform
+    /// returns such a backbuffer by itself.
+    pub floating_point: bool,
+}
+
+impl FramebufferRequest {
+    /// Adds the properties of this request to a window builder.
+    pub fn apply<'a>(&self, builder: glutin::WindowBuilder<'a>) -> glutin::WindowBuilder<'a> {
+        let mut builder = builder;
+
+        if self.color_bits.is_some() || self.alpha_bits.is_some() {
+            let color_bits = self.color_bits.unwrap_or(8);
+            builder = builder.with_pixel_format(color_bits.saturating_mul(3),
+                                                self.alpha_bits.unwrap_or(0));
+        }
+
+        if let Some(bits) = self.depth_bits {
+            builder = builder.with_depth_buffer(bits);
+        }
+
+        if let Some(bits) = self.stencil_bits {
+            builder = builder.with_stencil_buffer(bits);
+        }
+
+        if let Some(samples) = self.samples {
+            builder = builder.with_multisampling(samples);
+        }
+
+        if self.srgb {
+            builder = builder.with_srgb(Some(true));
+        }
+
+        builder
+    }
+
+    /// Returns true if a default framebuffer with the given properties satisfies this request.
+    pub fn is_satisfied_by(&self, properties: &DefaultFramebufferProperties) -> bool {
+        fn at_least(obtained: Option<u16>, requested: Option<u16>) -> bool {
+            match requested {
+                None => true,
+                Some(requested) => obtained.unwrap_or(0) >= requested,
+            }
+        }
+
+        let color_bits = self.color_bits.map(|b| b as u16);
+
+        at_least(properties.red_bits, color_bits) &&
+        at_least(properties.green_bits, color_bits) &&
+        at_least(properties.blue_bits, color_bits) &&
+        at_least(properties.alpha_bits, self.alpha_bits.map(|b| b as u16)) &&
+        at_least(properties.depth_bits, self.depth_bits.map(|b| b as u16)) &&
+        at_least(properties.stencil_bits, self.stencil_bits.map(|b| b as u16)) &&
+        at_least(properties.samples, self.samples) &&
+        (!self.srgb || properties.srgb) &&
+        (!self.floating_point || properties.floating_point)
+    }
+
+    /// Builds a window with this request applied, and checks that the default framebuffer
+    /// that has been obtained satisfies the request.
+    ///
+    /// Returns `FramebufferRequestNotSatisfied` if it doesn't, in which case the window is
+    /// destroyed.
+    pub fn build_glium(&self, builder: glutin::WindowBuilder<'static>)
+                       -> Result<GlutinFacade, GliumCreationError<glutin::CreationError>>
+    {
+        let display = try!(self.apply(builder).build_glium());
+        let properties = display.get_default_framebuffer_properties();
+
+        if self.is_satisfied_by(&properties) {
+            Ok(display)
+        } else {
+            Err(GliumCreationError::FramebufferRequestNotSatisfied(properties))
+        }
+    }
+}
+
 impl DisplayBuild for glutin::HeadlessRendererBuilder {
     type Facade = GlutinFacade;
     type Err = GliumCreationError<glutin::CreationError>;
@@ -214,6 +424,10 @@ impl DisplayBuild for glutin::HeadlessRendererBuilder {
 /// An implementation of the `Backend` trait for a glutin window.
 pub struct GlutinWindowBackend {
     window: glutin::Window,
+
+    // loaded the first time the buffers are swapped with damage rectangles, as this requires
+    // the context to be current
+    damage_swapper: Cell<Option<backend::swap_damage::DamageSwapper>>,
 }
 
 unsafe impl Backend for GlutinWindowBackend {
@@ -226,6 +440,29 @@ unsafe impl Backend for GlutinWindowBackend {
         }
     }
 
+    fn swap_buffers_with_damage(&self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        let swapper = match self.damage_swapper.get() {
+            Some(swapper) => swapper,
+            None => {
+                let swapper = unsafe {
+                    backend::swap_damage::DamageSwapper::load(|symbol| {
+                        self.window.get_proc_address(symbol)
+                    })
+                };
+
+                self.damage_swapper.set(Some(swapper));
+                swapper
+            },
+        };
+
+        let result = unsafe { swapper.swap(damage) };
+
+        match result {
+            Some(result) => result,
+            None => self.swap_buffers(),
+        }
+    }
+
     #[inline]
     unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
         self.window.get_proc_address(symbol)
@@ -247,6 +484,12 @@ unsafe impl Backend for GlutinWindowBackend {
     unsafe fn make_current(&self) {
         self.window.make_current().unwrap();
     }
+
+    #[inline]
+    unsafe fn set_swap_interval(&self, interval: SwapInterval) -> Result<(), ()> {
+        backend::swap_interval::set_swap_interval(|symbol| self.window.get_proc_address(symbol),
+                                                  interval)
+    }
 }
 
 #[allow(missing_docs)]
@@ -259,6 +502,7 @@ impl GlutinWindowBackend {
 
         Ok(GlutinWindowBackend {
             window: window,
+            damage_swapper: Cell::new(None),
         })
     }
 
@@ -284,8 +528,100 @@ impl GlutinWindowBackend {
 
         Ok(GlutinWindowBackend {
             window: window,
+            damage_swapper: Cell::new(None),
         })
     }
+
+    /// Builds a new backend whose context shares its objects with this one.
+    #[inline]
+    pub fn build_shared(&self, builder: glutin::WindowBuilder)
+                        -> Result<GlutinWindowBackend, GliumCreationError<glutin::CreationError>>
+    {
+        self.rebuild(builder)
+    }
+}
+
+/// An implementation of the `SurfaceBackend` trait for a glutin window, on which the context
+/// of a `GlutinFacade` draws. Built with `GlutinFacade::build_surface`.
+pub struct GlutinSurfaceBackend {
+    // the OpenGL context of this window is not used
+    window: glutin::Window,
+
+    // the window of the facade, which is replaced when the facade is rebuilt
+    facade_window: Rc<Option<RefCell<Rc<GlutinWindowBackend>>>>,
+
+    binding: backend::surface_binding::SurfaceBinding,
+
+    // loaded the first time the buffers are swapped with damage rectangles
+    damage_swapper: Cell<Option<backend::swap_damage::DamageSwapper>>,
+}
+
+unsafe impl SurfaceBackend for GlutinSurfaceBackend {
+    #[inline]
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        match self.window.swap_buffers() {
+            Ok(()) => Ok(()),
+            Err(glutin::ContextError::IoError(e)) => {
+                panic!("Error while swapping buffers: {:?}", e)
+            },
+            Err(glutin::ContextError::ContextLost) => Err(SwapBuffersError::ContextLost),
+        }
+    }
+
+    fn swap_buffers_with_damage(&self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        let swapper = match self.damage_swapper.get() {
+            Some(swapper) => swapper,
+            None => {
+                // the context of the facade is current with this window when swapping
+                let swapper = unsafe {
+                    backend::swap_damage::DamageSwapper::load(|symbol| {
+                        self.window.get_proc_address(symbol)
+                    })
+                };
+
+                self.damage_swapper.set(Some(swapper));
+                swapper
+            },
+        };
+
+        let result = unsafe { swapper.swap(damage) };
+
+        match result {
+            Some(result) => result,
+            None => self.swap_buffers(),
+        }
+    }
+
+    #[inline]
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        let (width, height) = self.window.get_inner_size().unwrap_or((800, 600));
+        let scale = self.window.hidpi_factor();
+        ((width as f32 * scale) as u32, (height as f32 * scale) as u32)
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        self.get_facade_window().is_current() && unsafe { self.binding.is_bound() }
+    }
+
+    unsafe fn make_current(&self) {
+        self.get_facade_window().make_current();
+        self.binding.bind_current_context().ok().expect("Failed to bind the OpenGL context to \
+                                                          the window");
+    }
+}
+
+#[allow(missing_docs)]
+impl GlutinSurfaceBackend {
+    #[inline]
+    pub fn get_window(&self) -> &glutin::Window {
+        &self.window
+    }
+
+    #[inline]
+    fn get_facade_window(&self) -> Ref<Rc<GlutinWindowBackend>> {
+        Option::as_ref(&self.facade_window).unwrap().borrow()
+    }
 }
 
 /// An implementation of the `Backend` trait for a glutin headless context.
@@ -0,0 +1,936 @@
+#![cfg(feature = "mock_backend")]
+/*!
+
+Backend that doesn't use any GPU or display, to unit test rendering code.
+
+A `MockBackendBuilder` pretends to be an OpenGL implementation of the given version that
+supports the given extensions. glium chooses its code paths according to them as it would with
+a real driver, which makes it possible to test the fallbacks of glium and of your application
+on a continuous integration machine.
+
+Each OpenGL function that glium calls is recorded with its arguments instead of being executed.
+The calls can be retrieved with `MockFacade::get_calls`.
+
+# What is emulated
+
+ - `glGetString` and `glGetStringi` return the version, vendor, renderer and extensions of the
+   builder.
+ - The limits returned by `glGetIntegerv` and the similar functions have reasonable default
+   values, which can be changed with `with_integer`. Unknown values are 0.
+ - The functions that create objects return new names.
+ - Buffers have a storage in memory, so that they can be written, mapped and read back.
+ - Shaders always compile, programs always link and have no active uniform or attribute,
+   fences are always signaled and queries are always available with a result of 0.
+
+The other functions don't do anything, and their output parameters are set to 0. Textures and
+renderbuffers don't have any content.
+
+# Features
+
+Only available if the 'mock_backend' feature is enabled.
+
+# Example
+
+```no_run
+use glium::backend::mock::MockBackendBuilder;
+use glium::{Api, Version};
+
+let display = MockBackendBuilder::new(Version(Api::Gl, 2, 1))
+                    .with_extensions(&["GL_ARB_vertex_array_object"])
+                    .build_glium()
+                    .unwrap();
+
+let buffer = glium::buffer::Buffer::new(&display, &[1.0f32, 2.0, 3.0][..],
+                                       glium::buffer::BufferType::ArrayBuffer,
+                                       glium::buffer::BufferMode::Default).unwrap();
+assert!(display.get_calls().iter().any(|call| call.function == "glGenBuffers"));
+```
+
+*/
+use libc;
+
+use Frame;
+use GliumCreationError;
+use SwapBuffersError;
+
+use context;
+use backend;
+use backend::Context;
+use backend::Backend;
+use gl;
+use gl::types::{GLenum, GLint, GLintptr, GLsizeiptr, GLuint};
+use version::{self, Api, Version};
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt;
+use std::ops::Deref;
+use std::ptr;
+use std::rc::Rc;
+
+/// The values returned by `glGetIntegerv` and the similar functions when they haven't been
+/// given with `with_integer`.
+const DEFAULT_INTEGERS: &'static [(GLenum, GLint)] = &[
+    (gl::DEPTH_BITS, 24),
+    (gl::STENCIL_BITS, 8),
+    (gl::MAX_3D_TEXTURE_SIZE, 2048),
+    (gl::MAX_ARRAY_TEXTURE_LAYERS, 2048),
+    (gl::MAX_ATOMIC_COUNTER_BUFFER_BINDINGS, 1),
+    (gl::MAX_COLOR_ATTACHMENTS, 8),
+    (gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, 48),
+    (gl::MAX_COMPUTE_WORK_GROUP_COUNT, 65535),
+    (gl::MAX_CUBE_MAP_TEXTURE_SIZE, 4096),
+    (gl::MAX_DRAW_BUFFERS, 8),
+    (gl::MAX_FRAMEBUFFER_HEIGHT, 16384),
+    (gl::MAX_FRAMEBUFFER_LAYERS, 2048),
+    (gl::MAX_FRAMEBUFFER_SAMPLES, 4),
+    (gl::MAX_FRAMEBUFFER_WIDTH, 16384),
+    (gl::MAX_PATCH_VERTICES, 32),
+    (gl::MAX_RENDERBUFFER_SIZE, 4096),
+    (gl::MAX_SAMPLES, 4),
+    (gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS, 8),
+    (gl::MAX_TEXTURE_BUFFER_SIZE, 65536),
+    (gl::MAX_TEXTURE_MAX_ANISOTROPY_EXT, 16),
+    (gl::MAX_TEXTURE_SIZE, 4096),
+    (gl::MAX_TRANSFORM_FEEDBACK_BUFFERS, 4),
+    (gl::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS, 4),
+    (gl::MAX_UNIFORM_BUFFER_BINDINGS, 36),
+    (gl::MAX_VERTEX_ATTRIBS, 16),
+    (gl::MAX_VIEWPORT_DIMS, 16384),
+    (gl::RESET_NOTIFICATION_STRATEGY, gl::NO_RESET_NOTIFICATION as GLint),
+];
+
+/// Returns the number of values written by `glGetIntegerv` and the similar functions for a state
+/// variable.
+fn values_count(name: GLenum) -> usize {
+    match name {
+        gl::BLEND_COLOR | gl::COLOR_CLEAR_VALUE | gl::COLOR_WRITEMASK | gl::SCISSOR_BOX |
+        gl::VIEWPORT => 4,
+        gl::ALIASED_LINE_WIDTH_RANGE | gl::ALIASED_POINT_SIZE_RANGE | gl::DEPTH_RANGE |
+        gl::MAX_VIEWPORT_DIMS | gl::POINT_SIZE_RANGE | gl::POLYGON_MODE |
+        gl::SMOOTH_LINE_WIDTH_RANGE => 2,
+        _ => 1,
+    }
+}
+
+/// A call to an OpenGL function recorded by the mock backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockCall {
+    /// Name of the function, for example `glDrawArrays`.
+    pub function: &'static str,
+
+    /// The arguments, separated by commas.
+    pub arguments: String,
+}
+
+impl fmt::Display for MockCall {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}({})", self.function, self.arguments)
+    }
+}
+
+/// Builds a `MockFacade`.
+pub struct MockBackendBuilder {
+    version: Version,
+    vendor: String,
+    renderer: String,
+    extensions: Vec<String>,
+    integers: HashMap<GLenum, GLint>,
+    dimensions: (u32, u32),
+}
+
+impl MockBackendBuilder {
+    /// Starts building a backend that pretends to support the given version of OpenGL or
+    /// OpenGL ES, without any extension.
+    pub fn new(version: Version) -> MockBackendBuilder {
+        MockBackendBuilder {
+            version: version,
+            vendor: "glium".to_string(),
+            renderer: "glium mock backend".to_string(),
+            extensions: Vec::new(),
+            integers: HashMap::new(),
+            dimensions: (800, 600),
+        }
+    }
+
+    /// Adds extensions to the list of supported extensions, for example
+    /// `"GL_ARB_direct_state_access"`.
+    pub fn with_extensions(mut self, extensions: &[&str]) -> MockBackendBuilder {
+        self.extensions.extend(extensions.iter().map(|e| e.to_string()));
+        self
+    }
+
+    /// Sets the value returned for `GL_VENDOR`. The default is `glium`.
+    pub fn with_vendor(mut self, vendor: &str) -> MockBackendBuilder {
+        self.vendor = vendor.to_string();
+        self
+    }
+
+    /// Sets the value returned for `GL_RENDERER`. The default is `glium mock backend`.
+    pub fn with_renderer(mut self, renderer: &str) -> MockBackendBuilder {
+        self.renderer = renderer.to_string();
+        self
+    }
+
+    /// Sets the value returned by `glGetIntegerv` for `name`, for example `gl::MAX_TEXTURE_SIZE`.
+    pub fn with_integer(mut self, name: GLenum, value: GLint) -> MockBackendBuilder {
+        self.integers.insert(name, value);
+        self
+    }
+
+    /// Sets the dimensions of the default framebuffer. The default is `(800, 600)`.
+    pub fn with_framebuffer_dimensions(mut self, width: u32, height: u32) -> MockBackendBuilder {
+        self.dimensions = (width, height);
+        self
+    }
+
+    /// Builds the backend without creating a `Context`.
+    ///
+    /// Returns an error if the vendor, the renderer or an extension can't be returned by
+    /// `glGetString`.
+    pub fn build_backend(self) -> Result<MockBackend, MockCreationError> {
+        for extension in self.extensions.iter() {
+            if extension.is_empty() || extension.contains(char::is_whitespace) {
+                return Err(MockCreationError::InvalidExtensionName(extension.clone()));
+            }
+        }
+
+        let vendor = try!(to_c_string(self.vendor));
+        let renderer = try!(to_c_string(self.renderer));
+        let extensions = try!(self.extensions.iter().cloned().map(to_c_string)
+                                             .collect::<Result<Vec<_>, _>>());
+
+        let version_string = match self.version {
+            Version(Api::Gl, major, minor) => format!("{}.{}.0", major, minor),
+            Version(Api::GlEs, major, minor) => format!("OpenGL ES {}.{}", major, minor),
+        };
+
+        let glsl_version = match self.version {
+            Version(Api::Gl, 1, _) | Version(Api::GlEs, 1, _) => String::new(),
+            Version(Api::Gl, _, _) => {
+                let Version(_, major, minor) = version::get_supported_glsl_version(&self.version);
+                format!("{}.{}0", major, minor)
+            },
+            Version(Api::GlEs, _, _) => {
+                let Version(_, major, minor) = version::get_supported_glsl_version(&self.version);
+                format!("OpenGL ES GLSL ES {}.{}0", major, minor)
+            },
+        };
+
+        let mut integers = DEFAULT_INTEGERS.iter().cloned().collect::<HashMap<_, _>>();
+        integers.insert(gl::MAJOR_VERSION, self.version.1 as GLint);
+        integers.insert(gl::MINOR_VERSION, self.version.2 as GLint);
+        integers.insert(gl::NUM_EXTENSIONS, self.extensions.len() as GLint);
+        integers.extend(self.integers.into_iter());
+
+        // the strings are generated by the builder and don't contain any nul character
+        let state = State {
+            version: CString::new(version_string).unwrap(),
+            glsl_version: CString::new(glsl_version).unwrap(),
+            vendor: vendor,
+            renderer: renderer,
+            extensions_string: CString::new(self.extensions.join(" ")).unwrap(),
+            extensions: extensions,
+            integers: integers,
+            dimensions: self.dimensions,
+            calls: RefCell::new(Vec::new()),
+            next_name: Cell::new(1),
+            bound_buffers: RefCell::new(HashMap::new()),
+            buffers: RefCell::new(HashMap::new()),
+        };
+
+        Ok(MockBackend { state: Rc::new(state) })
+    }
+
+    /// Builds the facade.
+    ///
+    /// Returns an error if the strings of the builder are invalid, or if glium doesn't support
+    /// the version and extensions of the builder.
+    pub fn build_glium(self) -> Result<MockFacade, GliumCreationError<MockCreationError>> {
+        let backend = try!(self.build_backend());
+        let state = backend.state.clone();
+        let context = try!(unsafe { context::Context::new(backend, true) });
+
+        Ok(MockFacade {
+            context: context,
+            state: state,
+        })
+    }
+}
+
+/// Error that can happen while building a `MockBackend` or a `MockFacade`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockCreationError {
+    /// The vendor, the renderer or an extension contains a nul character. Contains the string.
+    NulCharacter(String),
+
+    /// An extension name is empty or contains a whitespace, which would change the list of
+    /// extensions returned by `glGetString`. Contains the name.
+    InvalidExtensionName(String),
+}
+
+impl fmt::Display for MockCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", ::std::error::Error::description(self))
+    }
+}
+
+impl ::std::error::Error for MockCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match self {
+            &MockCreationError::NulCharacter(_) => "A string of the mock backend contains a nul \
+                                                    character",
+            &MockCreationError::InvalidExtensionName(_) => "An extension name is empty or \
+                                                            contains a whitespace",
+        }
+    }
+}
+
+/// Converts a string given to the builder to a `CString`.
+fn to_c_string(string: String) -> Result<CString, MockCreationError> {
+    CString::new(string.clone()).map_err(|_| MockCreationError::NulCharacter(string))
+}
+
+/// The fake implementation shared by the backend, the facade and the OpenGL functions.
+struct State {
+    version: CString,
+    glsl_version: CString,
+    vendor: CString,
+    renderer: CString,
+    extensions_string: CString,
+    extensions: Vec<CString>,
+    integers: HashMap<GLenum, GLint>,
+    dimensions: (u32, u32),
+    calls: RefCell<Vec<MockCall>>,
+
+    /// The next name returned by the functions that create objects. Names are unique among all
+    /// the kinds of objects.
+    next_name: Cell<GLuint>,
+
+    /// The buffer bound to each target.
+    bound_buffers: RefCell<HashMap<GLenum, GLuint>>,
+
+    /// The content of each buffer.
+    buffers: RefCell<HashMap<GLuint, Vec<u8>>>,
+}
+
+impl State {
+    #[inline]
+    fn get_integer(&self, name: GLenum) -> GLint {
+        self.integers.get(&name).cloned().unwrap_or(0)
+    }
+
+    /// Returns all the values of a state variable. The viewport and the scissor box cover the
+    /// whole default framebuffer, and the other variables repeat their value.
+    fn get_integers(&self, name: GLenum) -> Vec<GLint> {
+        match name {
+            gl::VIEWPORT | gl::SCISSOR_BOX => {
+                vec![0, 0, self.dimensions.0 as GLint, self.dimensions.1 as GLint]
+            },
+            _ => vec![self.get_integer(name); values_count(name)],
+        }
+    }
+
+    #[inline]
+    fn new_name(&self) -> GLuint {
+        let name = self.next_name.get();
+        self.next_name.set(name + 1);
+        name
+    }
+
+    #[inline]
+    fn get_bound_buffer(&self, target: GLenum) -> GLuint {
+        self.bound_buffers.borrow().get(&target).cloned().unwrap_or(0)
+    }
+
+    /// Replaces the content of a buffer with `size` bytes copied from `data`, or with zeroes if
+    /// `data` is null.
+    unsafe fn set_buffer_storage(&self, id: GLuint, size: GLsizeiptr, data: *const libc::c_void) {
+        if size < 0 {
+            return;
+        }
+
+        let mut content = vec![0u8; size as usize];
+        if !data.is_null() {
+            ptr::copy_nonoverlapping(data as *const u8, content.as_mut_ptr(), size as usize);
+        }
+
+        self.buffers.borrow_mut().insert(id, content);
+    }
+
+    /// Returns a pointer to a range of the content of a buffer, or a null pointer if the range
+    /// is out of bounds.
+    unsafe fn get_buffer_range(&self, id: GLuint, offset: GLintptr, size: GLsizeiptr)
+                               -> *mut libc::c_void
+    {
+        let mut buffers = self.buffers.borrow_mut();
+        let content = match buffers.get_mut(&id) {
+            Some(content) => content,
+            None => return ptr::null_mut(),
+        };
+
+        if offset < 0 || size < 0 || (offset + size) as usize > content.len() {
+            return ptr::null_mut();
+        }
+
+        content.as_mut_ptr().offset(offset as isize) as *mut libc::c_void
+    }
+
+    #[inline]
+    fn get_buffer_size(&self, id: GLuint) -> GLint {
+        self.buffers.borrow().get(&id).map(|c| c.len() as GLint).unwrap_or(0)
+    }
+}
+
+thread_local!(static CURRENT: RefCell<Option<Rc<State>>> = RefCell::new(None));
+
+/// Calls a function with the state of the mock context that is current in this thread.
+///
+/// Returns `None` if no mock context is current. The OpenGL functions then don't do anything
+/// and return 0 or a null pointer, like OpenGL does when no context is current.
+fn with_state<F, R>(f: F) -> Option<R> where F: FnOnce(&State) -> R {
+    let state = CURRENT.with(|current| current.borrow().clone());
+    state.map(|state| f(&state))
+}
+
+/// Records a call in the mock context that is current in this thread.
+fn record_call(function: &'static str, arguments: String) {
+    CURRENT.with(|current| {
+        if let Some(ref state) = *current.borrow() {
+            state.calls.borrow_mut().push(MockCall { function: function, arguments: arguments });
+        }
+    });
+}
+
+/// An implementation of the `Backend` trait that doesn't use any GPU.
+pub struct MockBackend {
+    state: Rc<State>,
+}
+
+unsafe impl Backend for MockBackend {
+    #[inline]
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+        let function = get_function(symbol);
+        if !function.is_null() {
+            return function;
+        }
+
+        stubs::get_stub(symbol)
+    }
+
+    #[inline]
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.state.dimensions
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        CURRENT.with(|current| {
+            match *current.borrow() {
+                Some(ref state) => &**state as *const State == &*self.state as *const State,
+                None => false,
+            }
+        })
+    }
+
+    #[inline]
+    unsafe fn make_current(&self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.state.clone()));
+    }
+}
+
+/// Facade implementation for a `MockBackend`.
+#[derive(Clone)]
+pub struct MockFacade {
+    context: Rc<context::Context>,
+    state: Rc<State>,
+}
+
+impl MockFacade {
+    /// Start drawing on the backbuffer.
+    ///
+    /// This function returns a `Frame`, which can be used to draw on it. When the `Frame` is
+    /// destroyed, the buffers are swapped.
+    #[inline]
+    pub fn draw(&self) -> Frame {
+        Frame::new(self.context.clone(), self.get_framebuffer_dimensions())
+    }
+
+    /// Returns the OpenGL calls that have been recorded since the context has been created or
+    /// since the last call to `clear_calls`, from the oldest to the most recent.
+    #[inline]
+    pub fn get_calls(&self) -> Vec<MockCall> {
+        self.state.calls.borrow().clone()
+    }
+
+    /// Forgets the calls that have been recorded.
+    #[inline]
+    pub fn clear_calls(&self) {
+        self.state.calls.borrow_mut().clear();
+    }
+}
+
+impl backend::Facade for MockFacade {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+}
+
+impl Deref for MockFacade {
+    type Target = Context;
+
+    #[inline]
+    fn deref(&self) -> &Context {
+        &self.context
+    }
+}
+
+/// The functions that don't only record the call.
+mod stubs {
+    use gl::types;
+
+    mod __gl_imports {
+        pub use libc;
+        pub use std::mem;
+        pub use std::ptr;
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/mock_gl.rs"));
+}
+
+/// Returns the address of the function of the mock backend that emulates an OpenGL function, or
+/// a null pointer if the function only records the call.
+fn get_function(symbol: &str) -> *const libc::c_void {
+    use self::functions::*;
+
+    macro_rules! functions {
+        ($($name:ident),+) => (
+            match symbol {
+                $(stringify!($name) => $name as *const libc::c_void,)+
+                _ => ptr::null(),
+            }
+        )
+    }
+
+    functions!(glGetString, glGetStringi, glGetIntegerv, glGetInteger64v, glGetBooleanv,
+               glGetFloatv, glGetIntegeri_v, glGenBuffers, glGenBuffersARB, glGenTextures,
+               glGenFramebuffers, glGenFramebuffersEXT, glGenRenderbuffers,
+               glGenRenderbuffersEXT, glGenQueries, glGenQueriesARB, glGenQueriesEXT,
+               glGenSamplers, glGenVertexArrays, glGenVertexArraysAPPLE, glGenVertexArraysOES,
+               glCreateBuffers, glCreateFramebuffers, glCreateRenderbuffers, glCreateSamplers,
+               glCreateVertexArrays, glCreateTextures, glCreateQueries, glCreateProgram,
+               glCreateShader, glCreateProgramObjectARB, glCreateShaderObjectARB, glGetShaderiv,
+               glGetProgramiv, glGetObjectParameterivARB, glGetQueryObjectiv,
+               glGetQueryObjectivEXT, glGetQueryObjectuiv, glGetQueryObjectuivARB,
+               glGetQueryObjectuivEXT, glGetQueryObjectui64v, glGetQueryObjectui64vEXT,
+               glCheckFramebufferStatus, glCheckFramebufferStatusEXT,
+               glCheckNamedFramebufferStatus, glCheckNamedFramebufferStatusEXT, glFenceSync, glFenceSyncAPPLE, glClientWaitSync, glClientWaitSyncAPPLE,
+               glBindBuffer, glBindBufferARB, glBindBufferBase, glBindBufferRange,
+               glBufferData, glBufferDataARB, glBufferStorage, glBufferStorageEXT,
+               glNamedBufferData, glNamedBufferDataEXT, glNamedBufferStorage,
+               glNamedBufferStorageEXT, glBufferSubData, glBufferSubDataARB,
+               glNamedBufferSubData, glNamedBufferSubDataEXT, glGetBufferSubData,
+               glGetBufferSubDataARB, glGetNamedBufferSubData, glGetBufferParameteriv,
+               glGetBufferParameterivARB, glGetNamedBufferParameteriv,
+               glGetNamedBufferParameterivEXT, glMapBuffer, glMapBufferARB, glMapBufferRange,
+               glMapNamedBufferRange, glUnmapBuffer, glUnmapBufferARB, glUnmapNamedBuffer,
+               glUnmapNamedBufferEXT, glCopyBufferSubData, glCopyNamedBufferSubData)
+}
+
+#[allow(non_snake_case)]
+mod functions {
+    use gl;
+    use gl::types::*;
+    use libc;
+
+    use std::ptr;
+
+    use super::{record_call, values_count, with_state};
+
+    pub unsafe extern "system" fn glGetString(name: GLenum) -> *const GLubyte {
+        record_call("glGetString", format!("{:?}", name));
+
+        with_state(|state| {
+            let value = match name {
+                gl::VENDOR => &state.vendor,
+                gl::RENDERER => &state.renderer,
+                gl::VERSION => &state.version,
+                gl::SHADING_LANGUAGE_VERSION => &state.glsl_version,
+                gl::EXTENSIONS => &state.extensions_string,
+                _ => return ptr::null(),
+            };
+
+            value.as_ptr() as *const GLubyte
+        }).unwrap_or(ptr::null())
+    }
+
+    pub unsafe extern "system" fn glGetStringi(name: GLenum, index: GLuint) -> *const GLubyte {
+        record_call("glGetStringi", format!("{:?}, {:?}", name, index));
+
+        with_state(|state| {
+            match (name, state.extensions.get(index as usize)) {
+                (gl::EXTENSIONS, Some(extension)) => extension.as_ptr() as *const GLubyte,
+                _ => ptr::null(),
+            }
+        }).unwrap_or(ptr::null())
+    }
+
+    /// Writes all the values of a state variable.
+    unsafe fn write_integer<T, F>(name: GLenum, data: *mut T, convert: F) where F: Fn(GLint) -> T {
+        let values = with_state(|state| state.get_integers(name))
+                        .unwrap_or_else(|| vec![0; values_count(name)]);
+
+        for (i, &value) in values.iter().enumerate() {
+            *data.offset(i as isize) = convert(value);
+        }
+    }
+
+    pub unsafe extern "system" fn glGetIntegerv(name: GLenum, data: *mut GLint) {
+        record_call("glGetIntegerv", format!("{:?}, {:?}", name, data));
+        write_integer(name, data, |v| v);
+    }
+
+    pub unsafe extern "system" fn glGetInteger64v(name: GLenum, data: *mut GLint64) {
+        record_call("glGetInteger64v", format!("{:?}, {:?}", name, data));
+        write_integer(name, data, |v| v as GLint64);
+    }
+
+    pub unsafe extern "system" fn glGetBooleanv(name: GLenum, data: *mut GLboolean) {
+        record_call("glGetBooleanv", format!("{:?}, {:?}", name, data));
+        write_integer(name, data, |v| if v != 0 { gl::TRUE } else { gl::FALSE });
+    }
+
+    pub unsafe extern "system" fn glGetFloatv(name: GLenum, data: *mut GLfloat) {
+        record_call("glGetFloatv", format!("{:?}, {:?}", name, data));
+        write_integer(name, data, |v| v as GLfloat);
+    }
+
+    pub unsafe extern "system" fn glGetIntegeri_v(name: GLenum, index: GLuint, data: *mut GLint) {
+        record_call("glGetIntegeri_v", format!("{:?}, {:?}, {:?}", name, index, data));
+        *data = with_state(|state| state.get_integer(name)).unwrap_or(0);
+    }
+
+    unsafe fn write_new_names(n: GLsizei, names: *mut GLuint) {
+        with_state(|state| {
+            for i in 0 .. n as isize {
+                *names.offset(i) = state.new_name();
+            }
+        });
+    }
+
+    macro_rules! gen_names {
+        ($($name:ident),+) => ($(
+            pub unsafe extern "system" fn $name(n: GLsizei, names: *mut GLuint) {
+                record_call(stringify!($name), format!("{:?}, {:?}", n, names));
+                write_new_names(n, names);
+            }
+        )+)
+    }
+
+    gen_names!(glGenBuffers, glGenBuffersARB, glGenTextures, glGenFramebuffers,
+               glGenFramebuffersEXT, glGenRenderbuffers, glGenRenderbuffersEXT, glGenQueries,
+               glGenQueriesARB, glGenQueriesEXT, glGenSamplers, glGenVertexArrays,
+               glGenVertexArraysAPPLE, glGenVertexArraysOES, glCreateBuffers,
+               glCreateFramebuffers, glCreateRenderbuffers, glCreateSamplers,
+               glCreateVertexArrays);
+
+    pub unsafe extern "system" fn glCreateTextures(target: GLenum, n: GLsizei,
+                                                   textures: *mut GLuint)
+    {
+        record_call("glCreateTextures", format!("{:?}, {:?}, {:?}", target, n, textures));
+        write_new_names(n, textures);
+    }
+
+    pub unsafe extern "system" fn glCreateQueries(target: GLenum, n: GLsizei, ids: *mut GLuint) {
+        record_call("glCreateQueries", format!("{:?}, {:?}, {:?}", target, n, ids));
+        write_new_names(n, ids);
+    }
+
+    pub unsafe extern "system" fn glCreateProgram() -> GLuint {
+        record_call("glCreateProgram", String::new());
+        with_state(|state| state.new_name()).unwrap_or(0)
+    }
+
+    pub unsafe extern "system" fn glCreateShader(ty: GLenum) -> GLuint {
+        record_call("glCreateShader", format!("{:?}", ty));
+        with_state(|state| state.new_name()).unwrap_or(0)
+    }
+
+    pub unsafe extern "system" fn glCreateProgramObjectARB() -> GLhandleARB {
+        record_call("glCreateProgramObjectARB", String::new());
+        with_state(|state| state.new_name() as usize as GLhandleARB).unwrap_or(0 as GLhandleARB)
+    }
+
+    pub unsafe extern "system" fn glCreateShaderObjectARB(ty: GLenum) -> GLhandleARB {
+        record_call("glCreateShaderObjectARB", format!("{:?}", ty));
+        with_state(|state| state.new_name() as usize as GLhandleARB).unwrap_or(0 as GLhandleARB)
+    }
+
+    // compilations, links and validations always succeed
+    macro_rules! object_parameters {
+        ($($name:ident($ty:ty)),+) => ($(
+            pub unsafe extern "system" fn $name(object: $ty, name: GLenum, params: *mut GLint) {
+                record_call(stringify!($name), format!("{:?}, {:?}, {:?}", object, name, params));
+
+                *params = match name {
+                    gl::COMPILE_STATUS | gl::LINK_STATUS | gl::VALIDATE_STATUS => 1,
+                    _ => 0,
+                };
+            }
+        )+)
+    }
+
+    object_parameters!(glGetShaderiv(GLuint), glGetProgramiv(GLuint),
+                       glGetObjectParameterivARB(GLhandleARB));
+
+    // the results are always available
+    macro_rules! query_objects {
+        ($($name:ident($ty:ty)),+) => ($(
+            pub unsafe extern "system" fn $name(id: GLuint, name: GLenum, params: *mut $ty) {
+                record_call(stringify!($name), format!("{:?}, {:?}, {:?}", id, name, params));
+
+                *params = match name {
+                    gl::QUERY_RESULT_AVAILABLE => 1,
+                    _ => 0,
+                };
+            }
+        )+)
+    }
+
+    query_objects!(glGetQueryObjectiv(GLint), glGetQueryObjectivEXT(GLint),
+                   glGetQueryObjectuiv(GLuint), glGetQueryObjectuivARB(GLuint),
+                   glGetQueryObjectuivEXT(GLuint), glGetQueryObjectui64v(GLuint64),
+                   glGetQueryObjectui64vEXT(GLuint64));
+
+    // framebuffers are always complete
+    macro_rules! framebuffer_status {
+        ($($name:ident),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLenum) -> GLenum {
+                record_call(stringify!($name), format!("{:?}", target));
+                gl::FRAMEBUFFER_COMPLETE
+            }
+        )+)
+    }
+
+    framebuffer_status!(glCheckFramebufferStatus, glCheckFramebufferStatusEXT);
+
+    macro_rules! named_framebuffer_status {
+        ($($name:ident),+) => ($(
+            pub unsafe extern "system" fn $name(framebuffer: GLuint, target: GLenum) -> GLenum {
+                record_call(stringify!($name), format!("{:?}, {:?}", framebuffer, target));
+                gl::FRAMEBUFFER_COMPLETE
+            }
+        )+)
+    }
+
+    named_framebuffer_status!(glCheckNamedFramebufferStatus, glCheckNamedFramebufferStatusEXT);
+
+    pub unsafe extern "system" fn glFenceSync(condition: GLenum, flags: GLbitfield) -> GLsync {
+        record_call("glFenceSync", format!("{:?}, {:?}", condition, flags));
+        1usize as GLsync
+    }
+
+    pub unsafe extern "system" fn glFenceSyncAPPLE(condition: GLenum, flags: GLbitfield)
+                                                   -> GLsync
+    {
+        record_call("glFenceSyncAPPLE", format!("{:?}, {:?}", condition, flags));
+        1usize as GLsync
+    }
+
+    pub unsafe extern "system" fn glClientWaitSync(sync: GLsync, flags: GLbitfield,
+                                                   timeout: GLuint64) -> GLenum
+    {
+        record_call("glClientWaitSync", format!("{:?}, {:?}, {:?}", sync, flags, timeout));
+        gl::ALREADY_SIGNALED
+    }
+
+    pub unsafe extern "system" fn glClientWaitSyncAPPLE(sync: GLsync, flags: GLbitfield,
+                                                        timeout: GLuint64) -> GLenum
+    {
+        record_call("glClientWaitSyncAPPLE", format!("{:?}, {:?}, {:?}", sync, flags, timeout));
+        gl::ALREADY_SIGNALED
+    }
+
+    macro_rules! bind_buffer {
+        ($($name:ident),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLenum, buffer: GLuint) {
+                record_call(stringify!($name), format!("{:?}, {:?}", target, buffer));
+                with_state(|state| state.bound_buffers.borrow_mut().insert(target, buffer));
+            }
+        )+)
+    }
+
+    bind_buffer!(glBindBuffer, glBindBufferARB);
+
+    pub unsafe extern "system" fn glBindBufferBase(target: GLenum, index: GLuint, buffer: GLuint) {
+        record_call("glBindBufferBase", format!("{:?}, {:?}, {:?}", target, index, buffer));
+        with_state(|state| state.bound_buffers.borrow_mut().insert(target, buffer));
+    }
+
+    pub unsafe extern "system" fn glBindBufferRange(target: GLenum, index: GLuint, buffer: GLuint,
+                                                    offset: GLintptr, size: GLsizeiptr)
+    {
+        record_call("glBindBufferRange", format!("{:?}, {:?}, {:?}, {:?}, {:?}", target, index,
+                                                 buffer, offset, size));
+        with_state(|state| state.bound_buffers.borrow_mut().insert(target, buffer));
+    }
+
+    // `$bound` is true for the functions that take a target, and false for the ones that take
+    // the name of the buffer
+    macro_rules! buffer_storage {
+        ($($name:ident($bound:expr)),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLuint, size: GLsizeiptr,
+                                                data: *const libc::c_void, flags: GLenum)
+            {
+                record_call(stringify!($name), format!("{:?}, {:?}, {:?}, {:?}", target, size,
+                                                       data, flags));
+
+                with_state(|state| {
+                    let id = if $bound { state.get_bound_buffer(target) } else { target };
+                    state.set_buffer_storage(id, size, data);
+                });
+            }
+        )+)
+    }
+
+    buffer_storage!(glBufferData(true), glBufferDataARB(true), glBufferStorage(true),
+                    glBufferStorageEXT(true), glNamedBufferData(false),
+                    glNamedBufferDataEXT(false), glNamedBufferStorage(false),
+                    glNamedBufferStorageEXT(false));
+
+    macro_rules! buffer_sub_data {
+        ($($name:ident($bound:expr, $write:expr)),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLuint, offset: GLintptr,
+                                                size: GLsizeiptr, data: *mut libc::c_void)
+            {
+                record_call(stringify!($name), format!("{:?}, {:?}, {:?}, {:?}", target, offset,
+                                                       size, data));
+
+                with_state(|state| {
+                    let id = if $bound { state.get_bound_buffer(target) } else { target };
+                    let range = state.get_buffer_range(id, offset, size);
+                    if range.is_null() {
+                        return;
+                    }
+
+                    if $write {
+                        ptr::copy(data as *const u8, range as *mut u8, size as usize);
+                    } else {
+                        ptr::copy(range as *const u8, data as *mut u8, size as usize);
+                    }
+                });
+            }
+        )+)
+    }
+
+    buffer_sub_data!(glBufferSubData(true, true), glBufferSubDataARB(true, true),
+                     glNamedBufferSubData(false, true), glNamedBufferSubDataEXT(false, true),
+                     glGetBufferSubData(true, false), glGetBufferSubDataARB(true, false),
+                     glGetNamedBufferSubData(false, false));
+
+    macro_rules! buffer_parameters {
+        ($($name:ident($bound:expr)),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLuint, name: GLenum, params: *mut GLint) {
+                record_call(stringify!($name), format!("{:?}, {:?}, {:?}", target, name, params));
+
+                *params = with_state(|state| {
+                    let id = if $bound { state.get_bound_buffer(target) } else { target };
+                    match name {
+                        gl::BUFFER_SIZE => state.get_buffer_size(id),
+                        _ => 0,
+                    }
+                }).unwrap_or(0);
+            }
+        )+)
+    }
+
+    buffer_parameters!(glGetBufferParameteriv(true), glGetBufferParameterivARB(true),
+                       glGetNamedBufferParameteriv(false), glGetNamedBufferParameterivEXT(false));
+
+    macro_rules! map_buffer {
+        ($($name:ident),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLenum, access: GLenum)
+                                                -> *mut libc::c_void
+            {
+                record_call(stringify!($name), format!("{:?}, {:?}", target, access));
+
+                with_state(|state| {
+                    let id = state.get_bound_buffer(target);
+                    state.get_buffer_range(id, 0, state.get_buffer_size(id) as GLsizeiptr)
+                }).unwrap_or(ptr::null_mut())
+            }
+        )+)
+    }
+
+    map_buffer!(glMapBuffer, glMapBufferARB);
+
+    pub unsafe extern "system" fn glMapBufferRange(target: GLenum, offset: GLintptr,
+                                                   length: GLsizeiptr, access: GLbitfield)
+                                                   -> *mut libc::c_void
+    {
+        record_call("glMapBufferRange", format!("{:?}, {:?}, {:?}, {:?}", target, offset, length,
+                                                access));
+        with_state(|state| {
+            state.get_buffer_range(state.get_bound_buffer(target), offset, length)
+        }).unwrap_or(ptr::null_mut())
+    }
+
+    pub unsafe extern "system" fn glMapNamedBufferRange(buffer: GLuint, offset: GLintptr,
+                                                        length: GLsizeiptr, access: GLbitfield)
+                                                        -> *mut libc::c_void
+    {
+        record_call("glMapNamedBufferRange", format!("{:?}, {:?}, {:?}, {:?}", buffer, offset,
+                                                     length, access));
+        with_state(|state| state.get_buffer_range(buffer, offset, length))
+            .unwrap_or(ptr::null_mut())
+    }
+
+    macro_rules! unmap_buffer {
+        ($($name:ident),+) => ($(
+            pub unsafe extern "system" fn $name(target: GLuint) -> GLboolean {
+                record_call(stringify!($name), format!("{:?}", target));
+                gl::TRUE
+            }
+        )+)
+    }
+
+    unmap_buffer!(glUnmapBuffer, glUnmapBufferARB, glUnmapNamedBuffer, glUnmapNamedBufferEXT);
+
+    macro_rules! copy_buffer {
+        ($($name:ident($bound:expr)),+) => ($(
+            pub unsafe extern "system" fn $name(read: GLuint, write: GLuint,
+                                                read_offset: GLintptr, write_offset: GLintptr,
+                                                size: GLsizeiptr)
+            {
+                record_call(stringify!($name), format!("{:?}, {:?}, {:?}, {:?}, {:?}", read,
+                                                       write, read_offset, write_offset, size));
+
+                with_state(|state| {
+                    let (read, write) = if $bound {
+                        (state.get_bound_buffer(read), state.get_bound_buffer(write))
+                    } else {
+                        (read, write)
+                    };
+
+                    let source = state.get_buffer_range(read, read_offset, size);
+                    let dest = state.get_buffer_range(write, write_offset, size);
+                    if !source.is_null() && !dest.is_null() {
+                        ptr::copy(source as *const u8, dest as *mut u8, size as usize);
+                    }
+                });
+            }
+        )+)
+    }
+
+    copy_buffer!(glCopyBufferSubData(true), glCopyNamedBufferSubData(false));
+}
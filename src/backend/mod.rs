@@ -11,6 +11,10 @@ There are three concepts in play:
  - The `Facade` trait. Calling functions like `VertexBuffer::new` requires passing an object
    that implements this trait. It is implemented on `Rc<Context>`.
 
+If your windowing library is not supported by glium, the `generic` module allows you to build
+a facade from a few closures instead of implementing `Backend`. The `mock` module provides a
+backend without any GPU, for unit tests.
+
 */
 use std::rc::Rc;
 use std::ops::Deref;
@@ -18,16 +22,31 @@ use std::ops::Deref;
 use libc;
 use CapabilitiesSource;
 use SwapBuffersError;
+use SwapInterval;
+use Rect;
 
 use context::Capabilities;
 use context::ExtensionsList;
 use version::Version;
 
 pub use context::Context;
+pub use context::DefaultFramebufferProperties;
+pub use context::Profile;
+pub use context::Quirks;
 pub use context::ReleaseBehavior;
+pub use context::StateCategories;
 
+#[cfg(feature = "egl_headless")]
+pub mod egl_headless;
+pub mod generic;
 #[cfg(feature = "glutin")]
 pub mod glutin_backend;
+#[cfg(feature = "mock_backend")]
+pub mod mock;
+
+mod surface_binding;
+mod swap_damage;
+mod swap_interval;
 
 /// Trait for types that can be used as a backend for a glium context.
 ///
@@ -37,6 +56,17 @@ pub unsafe trait Backend {
     /// Swaps buffers at the end of a frame.
     fn swap_buffers(&self) -> Result<(), SwapBuffersError>;
 
+    /// Swaps buffers at the end of a frame, and tells the window system that only the content
+    /// of the given rectangles has changed. The rectangles are relative to the bottom-left
+    /// corner of the window.
+    ///
+    /// The default implementation calls `swap_buffers`.
+    #[inline]
+    fn swap_buffers_with_damage(&self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        let _ = damage;
+        self.swap_buffers()
+    }
+
     /// Returns the address of an OpenGL function.
     ///
     /// Supposes that the context has been made current before this function is called.
@@ -49,7 +79,20 @@ pub unsafe trait Backend {
     fn is_current(&self) -> bool;
 
     /// Makes the OpenGL context the current context in the current thread.
+    ///
+    /// If `is_current` still returns false afterwards, the context is considered lost.
     unsafe fn make_current(&self);
+
+    /// Changes the swap interval of the window.
+    ///
+    /// Supposes that the context has been made current before this function is called.
+    /// Returns `Err` if the backend doesn't support this swap interval, which is what the
+    /// default implementation does.
+    #[inline]
+    unsafe fn set_swap_interval(&self, interval: SwapInterval) -> Result<(), ()> {
+        let _ = interval;
+        Err(())
+    }
 }
 
 unsafe impl<T> Backend for Rc<T> where T: Backend {
@@ -57,6 +100,10 @@ unsafe impl<T> Backend for Rc<T> where T: Backend {
         self.deref().swap_buffers()
     }
 
+    fn swap_buffers_with_damage(&self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        self.deref().swap_buffers_with_damage(damage)
+    }
+
     unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
         self.deref().get_proc_address(symbol)
     }
@@ -72,6 +119,47 @@ unsafe impl<T> Backend for Rc<T> where T: Backend {
     unsafe fn make_current(&self) {
         self.deref().make_current();
     }
+
+    unsafe fn set_swap_interval(&self, interval: SwapInterval) -> Result<(), ()> {
+        self.deref().set_swap_interval(interval)
+    }
+}
+
+/// Trait for additional surfaces, like windows or pbuffers, on which the OpenGL context of a
+/// `Backend` can draw.
+///
+/// This allows drawing on multiple windows with a single `Context`, and thus sharing all the
+/// resources between the windows. Use `Frame::new_on_surface` to draw on a surface.
+///
+/// The surface must be compatible with the OpenGL context of the backend, for example
+/// created with the same `EGLConfig` or the same GLX visual.
+///
+/// This trait is unsafe for the same reasons as `Backend`.
+pub unsafe trait SurfaceBackend {
+    /// Swaps the buffers of the surface at the end of a frame.
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError>;
+
+    /// Swaps the buffers of the surface, and tells the window system that only the content of
+    /// the given rectangles has changed. The rectangles are relative to the bottom-left corner
+    /// of the surface.
+    ///
+    /// The default implementation calls `swap_buffers`.
+    #[inline]
+    fn swap_buffers_with_damage(&self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        let _ = damage;
+        self.swap_buffers()
+    }
+
+    /// Returns the dimensions of the surface.
+    fn get_framebuffer_dimensions(&self) -> (u32, u32);
+
+    /// Returns true if the OpenGL context of the backend is the current one in the thread, and
+    /// if it is bound to this surface.
+    fn is_current(&self) -> bool;
+
+    /// Makes the OpenGL context of the backend current in the current thread, with this surface
+    /// as the default framebuffer.
+    unsafe fn make_current(&self);
 }
 
 /// Trait for types that provide a safe access for glium functions.
@@ -0,0 +1,243 @@
+//! Binds the current OpenGL context to the surface of another context with the functions of
+//! the window system, so that a single context can draw on multiple windows.
+
+use libc;
+
+/// Surface of a context, for example a window, to which another context can be bound.
+///
+/// The context must have been created with the same pixel format as the surface.
+#[derive(Copy, Clone)]
+pub enum SurfaceBinding {
+    #[cfg(target_os = "windows")]
+    Wgl {
+        dc: *mut libc::c_void,
+        get_current_context: extern "system" fn() -> *mut libc::c_void,
+        get_current_dc: extern "system" fn() -> *mut libc::c_void,
+        make_current: extern "system" fn(*mut libc::c_void, *mut libc::c_void) -> libc::c_int,
+    },
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    Glx {
+        drawable: libc::c_ulong,
+        get_current_display: extern "C" fn() -> *mut libc::c_void,
+        get_current_context: extern "C" fn() -> *mut libc::c_void,
+        get_current_drawable: extern "C" fn() -> libc::c_ulong,
+        make_current: extern "C" fn(*mut libc::c_void, libc::c_ulong, *mut libc::c_void)
+                                    -> libc::c_int,
+    },
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    Egl {
+        display: *mut libc::c_void,
+        surface: *mut libc::c_void,
+        get_current_context: extern "system" fn() -> *mut libc::c_void,
+        get_current_surface: extern "system" fn(i32) -> *mut libc::c_void,
+        make_current: extern "system" fn(*mut libc::c_void, *mut libc::c_void, *mut libc::c_void,
+                                         *mut libc::c_void) -> libc::c_uint,
+    },
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+const EGL_DRAW: i32 = 0x3059;
+
+impl SurfaceBinding {
+    /// Loads the surface that the current context draws on.
+    ///
+    /// Returns `None` if this is not supported by the window system, which is the case with CGL.
+    ///
+    /// *Safety*: the context must be current, and `get_proc_address` must load the functions of
+    /// the window system of this context.
+    #[cfg(target_os = "windows")]
+    pub unsafe fn load_current<F>(get_proc_address: F) -> Option<SurfaceBinding>
+                                  where F: Fn(&str) -> *const libc::c_void
+    {
+        use std::mem;
+
+        let get_current_context = get_proc_address("wglGetCurrentContext");
+        let get_current_dc = get_proc_address("wglGetCurrentDC");
+        let make_current = get_proc_address("wglMakeCurrent");
+        if get_current_context.is_null() || get_current_dc.is_null() || make_current.is_null() {
+            return None;
+        }
+
+        let get_current_dc: extern "system" fn() -> *mut libc::c_void =
+                                                                mem::transmute(get_current_dc);
+
+        let dc = get_current_dc();
+        if dc.is_null() {
+            return None;
+        }
+
+        Some(SurfaceBinding::Wgl {
+            dc: dc,
+            get_current_context: mem::transmute(get_current_context),
+            get_current_dc: get_current_dc,
+            make_current: mem::transmute(make_current),
+        })
+    }
+
+    /// Loads the surface that the current context draws on.
+    ///
+    /// Always returns `None` on this platform, as CGL doesn't allow this.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    #[inline]
+    pub unsafe fn load_current<F>(_: F) -> Option<SurfaceBinding>
+                                  where F: Fn(&str) -> *const libc::c_void
+    {
+        None
+    }
+
+    /// Loads the surface that the current context draws on.
+    ///
+    /// Returns `None` if this is not supported by the window system.
+    ///
+    /// *Safety*: the context must be current, and `get_proc_address` must load the functions of
+    /// the window system of this context.
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    pub unsafe fn load_current<F>(get_proc_address: F) -> Option<SurfaceBinding>
+                                  where F: Fn(&str) -> *const libc::c_void
+    {
+        // GLX is tried first, and if there is no current GLX drawable we try EGL
+        match load_current_glx(&get_proc_address) {
+            Some(binding) => Some(binding),
+            None => load_current_egl(&get_proc_address),
+        }
+    }
+
+    /// Binds the context that is current in this thread to this surface.
+    ///
+    /// Returns `Err` if there is no current context or if the window system refuses to bind it,
+    /// for example because the pixel formats don't match.
+    ///
+    /// *Safety*: the surface must still be alive.
+    pub unsafe fn bind_current_context(&self) -> Result<(), ()> {
+        match *self {
+            #[cfg(target_os = "windows")]
+            SurfaceBinding::Wgl { dc, get_current_context, make_current, .. } => {
+                let context = get_current_context();
+                if context.is_null() {
+                    return Err(());
+                }
+
+                if make_current(dc, context) != 0 { Ok(()) } else { Err(()) }
+            },
+
+            #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+            SurfaceBinding::Glx { drawable, get_current_display, get_current_context,
+                                  make_current, .. } =>
+            {
+                // drawables are shared between all the connections to the X server, while
+                // contexts are not, so we use the connection of the current context
+                let display = get_current_display();
+                let context = get_current_context();
+                if display.is_null() || context.is_null() {
+                    return Err(());
+                }
+
+                if make_current(display, drawable, context) != 0 { Ok(()) } else { Err(()) }
+            },
+
+            #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+            SurfaceBinding::Egl { display, surface, get_current_context, make_current, .. } => {
+                let context = get_current_context();
+                if context.is_null() {
+                    return Err(());
+                }
+
+                if make_current(display, surface, surface, context) != 0 {
+                    Ok(())
+                } else {
+                    Err(())
+                }
+            },
+        }
+    }
+
+    /// Returns true if the context that is current in this thread draws on this surface.
+    pub unsafe fn is_bound(&self) -> bool {
+        match *self {
+            #[cfg(target_os = "windows")]
+            SurfaceBinding::Wgl { dc, get_current_dc, .. } => get_current_dc() == dc,
+
+            #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+            SurfaceBinding::Glx { drawable, get_current_drawable, .. } => {
+                get_current_drawable() == drawable
+            },
+
+            #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+            SurfaceBinding::Egl { surface, get_current_surface, .. } => {
+                get_current_surface(EGL_DRAW) == surface
+            },
+        }
+    }
+}
+
+/// Returns `None` if GLX is not used by the current context.
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn load_current_glx<F>(get_proc_address: &F) -> Option<SurfaceBinding>
+                              where F: Fn(&str) -> *const libc::c_void
+{
+    use std::mem;
+
+    let get_current_display = get_proc_address("glXGetCurrentDisplay");
+    let get_current_context = get_proc_address("glXGetCurrentContext");
+    let get_current_drawable = get_proc_address("glXGetCurrentDrawable");
+    let make_current = get_proc_address("glXMakeCurrent");
+    if get_current_display.is_null() || get_current_context.is_null() ||
+       get_current_drawable.is_null() || make_current.is_null()
+    {
+        return None;
+    }
+
+    let get_current_drawable: extern "C" fn() -> libc::c_ulong =
+                                                            mem::transmute(get_current_drawable);
+
+    let drawable = get_current_drawable();
+    if drawable == 0 {
+        return None;
+    }
+
+    Some(SurfaceBinding::Glx {
+        drawable: drawable,
+        get_current_display: mem::transmute(get_current_display),
+        get_current_context: mem::transmute(get_current_context),
+        get_current_drawable: get_current_drawable,
+        make_current: mem::transmute(make_current),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn load_current_egl<F>(get_proc_address: &F) -> Option<SurfaceBinding>
+                              where F: Fn(&str) -> *const libc::c_void
+{
+    use std::mem;
+
+    let get_current_display = get_proc_address("eglGetCurrentDisplay");
+    let get_current_context = get_proc_address("eglGetCurrentContext");
+    let get_current_surface = get_proc_address("eglGetCurrentSurface");
+    let make_current = get_proc_address("eglMakeCurrent");
+    if get_current_display.is_null() || get_current_context.is_null() ||
+       get_current_surface.is_null() || make_current.is_null()
+    {
+        return None;
+    }
+
+    let get_current_display: extern "system" fn() -> *mut libc::c_void =
+                                                            mem::transmute(get_current_display);
+    let get_current_surface: extern "system" fn(i32) -> *mut libc::c_void =
+                                                            mem::transmute(get_current_surface);
+
+    let display = get_current_display();
+    let surface = get_current_surface(EGL_DRAW);
+    if display.is_null() || surface.is_null() {
+        return None;
+    }
+
+    Some(SurfaceBinding::Egl {
+        display: display,
+        surface: surface,
+        get_current_context: mem::transmute(get_current_context),
+        get_current_surface: get_current_surface,
+        make_current: mem::transmute(make_current),
+    })
+}
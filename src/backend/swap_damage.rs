@@ -0,0 +1,213 @@
+//! Swaps the buffers of the current context with damage rectangles, using the functions of
+//! the window system, for backends that don't provide a way to do so.
+
+use libc;
+
+use Rect;
+use SwapBuffersError;
+
+/// Functions of the window system that swap the buffers of a context with damage rectangles.
+///
+/// The functions, the display and the surface are loaded once with `load`, instead of being
+/// queried again at each swap.
+#[derive(Copy, Clone)]
+pub enum DamageSwapper {
+    /// Uses `glXCopySubBufferMESA` to copy the damaged rectangles to the front buffer.
+    ///
+    /// Contrary to a swap, the copy is not synchronized with the vertical refresh of the
+    /// screen and the content of the back buffer is kept.
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    Glx {
+        display: *mut libc::c_void,
+        drawable: libc::c_ulong,
+        copy_sub_buffer: extern "C" fn(*mut libc::c_void, libc::c_ulong, libc::c_int,
+                                       libc::c_int, libc::c_int, libc::c_int),
+    },
+
+    /// Uses `eglSwapBuffersWithDamageKHR` or `eglSwapBuffersWithDamageEXT`.
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    Egl {
+        display: *mut libc::c_void,
+        surface: *mut libc::c_void,
+        swap: extern "system" fn(*mut libc::c_void, *mut libc::c_void, *const i32, i32)
+                                 -> libc::c_uint,
+        get_error: extern "system" fn() -> i32,
+    },
+
+    /// Swapping with damage rectangles is not supported.
+    Unsupported,
+}
+
+impl DamageSwapper {
+    /// Loads the functions of GLX or EGL depending on which one is used by the current
+    /// context.
+    ///
+    /// *Safety*: the context must be current, and `get_proc_address` must load the functions of
+    /// the window system of this context.
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+    pub unsafe fn load<F>(get_proc_address: F) -> DamageSwapper
+                          where F: Fn(&str) -> *const libc::c_void
+    {
+        // GLX is tried first, and if there is no current GLX display we try EGL
+        match load_glx(&get_proc_address) {
+            Some(swapper) => swapper,
+            None => load_egl(&get_proc_address),
+        }
+    }
+
+    /// Always returns `Unsupported` on this platform, as no window system function is available.
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+    #[inline]
+    pub unsafe fn load<F>(_: F) -> DamageSwapper where F: Fn(&str) -> *const libc::c_void {
+        DamageSwapper::Unsupported
+    }
+
+    /// Swaps the buffers of the context, or copies the damaged rectangles to the front buffer
+    /// with GLX.
+    ///
+    /// Returns `None` if this is not supported, in which case the buffers have not been swapped.
+    ///
+    /// *Safety*: the context that was current when calling `load` must be current.
+    pub unsafe fn swap(&self, damage: &[Rect]) -> Option<Result<(), SwapBuffersError>> {
+        match self {
+            #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+            &DamageSwapper::Glx { display, drawable, copy_sub_buffer } => {
+                // GLX expects `x, y, width, height` with the origin at the bottom-left,
+                // like `Rect`
+                for rect in damage {
+                    copy_sub_buffer(display, drawable, rect.left as libc::c_int,
+                                    rect.bottom as libc::c_int, rect.width as libc::c_int,
+                                    rect.height as libc::c_int);
+                }
+
+                Some(Ok(()))
+            },
+
+            #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+            &DamageSwapper::Egl { display, surface, swap, get_error } => {
+                const EGL_CONTEXT_LOST: i32 = 0x300E;
+
+                // EGL expects `x, y, width, height` with the origin at the bottom-left,
+                // like `Rect`
+                let rects = damage.iter().flat_map(|r| {
+                    vec![r.left as i32, r.bottom as i32, r.width as i32, r.height as i32]
+                        .into_iter()
+                }).collect::<Vec<_>>();
+
+                if swap(display, surface, rects.as_ptr(), damage.len() as i32) != 0 {
+                    return Some(Ok(()));
+                }
+
+                match get_error() {
+                    EGL_CONTEXT_LOST => Some(Err(SwapBuffersError::ContextLost)),
+                    err => Some(Err(SwapBuffersError::WindowSystemError(err as u32))),
+                }
+            },
+
+            &DamageSwapper::Unsupported => None,
+        }
+    }
+}
+
+/// Returns `None` if GLX is not used by the current context.
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn load_glx<F>(get_proc_address: &F) -> Option<DamageSwapper>
+                      where F: Fn(&str) -> *const libc::c_void
+{
+    use std::mem;
+    use super::swap_interval::has_extension;
+
+    const GLX_EXTENSIONS: libc::c_int = 3;
+
+    let get_current_display = get_proc_address("glXGetCurrentDisplay");
+    let get_current_drawable = get_proc_address("glXGetCurrentDrawable");
+    let get_client_string = get_proc_address("glXGetClientString");
+    if get_current_display.is_null() || get_current_drawable.is_null() ||
+       get_client_string.is_null()
+    {
+        return None;
+    }
+
+    let get_current_display: extern "C" fn() -> *mut libc::c_void =
+                                                            mem::transmute(get_current_display);
+    let get_current_drawable: extern "C" fn() -> libc::c_ulong =
+                                                            mem::transmute(get_current_drawable);
+    let get_client_string: extern "C" fn(*mut libc::c_void, libc::c_int) -> *const libc::c_char =
+                                                            mem::transmute(get_client_string);
+
+    let display = get_current_display();
+    if display.is_null() {
+        return None;
+    }
+
+    let drawable = get_current_drawable();
+    let extensions = get_client_string(display, GLX_EXTENSIONS);
+    if drawable == 0 || !has_extension(extensions, "GLX_MESA_copy_sub_buffer") {
+        return Some(DamageSwapper::Unsupported);
+    }
+
+    let copy_sub_buffer = get_proc_address("glXCopySubBufferMESA");
+    if copy_sub_buffer.is_null() {
+        return Some(DamageSwapper::Unsupported);
+    }
+
+    Some(DamageSwapper::Glx {
+        display: display,
+        drawable: drawable,
+        copy_sub_buffer: mem::transmute(copy_sub_buffer),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn load_egl<F>(get_proc_address: &F) -> DamageSwapper
+                      where F: Fn(&str) -> *const libc::c_void
+{
+    use std::mem;
+    use super::swap_interval::has_extension;
+
+    const EGL_EXTENSIONS: i32 = 0x3055;
+    const EGL_DRAW: i32 = 0x3059;
+
+    let get_current_display = get_proc_address("eglGetCurrentDisplay");
+    let get_current_surface = get_proc_address("eglGetCurrentSurface");
+    let query_string = get_proc_address("eglQueryString");
+    let get_error = get_proc_address("eglGetError");
+    if get_current_display.is_null() || get_current_surface.is_null() ||
+       query_string.is_null() || get_error.is_null()
+    {
+        return DamageSwapper::Unsupported;
+    }
+
+    let get_current_display: extern "system" fn() -> *mut libc::c_void =
+                                                            mem::transmute(get_current_display);
+    let get_current_surface: extern "system" fn(i32) -> *mut libc::c_void =
+                                                            mem::transmute(get_current_surface);
+    let query_string: extern "system" fn(*mut libc::c_void, i32) -> *const libc::c_char =
+                                                            mem::transmute(query_string);
+
+    let display = get_current_display();
+    let surface = get_current_surface(EGL_DRAW);
+    if display.is_null() || surface.is_null() {
+        return DamageSwapper::Unsupported;
+    }
+
+    let extensions = query_string(display, EGL_EXTENSIONS);
+    let swap = if has_extension(extensions, "EGL_KHR_swap_buffers_with_damage") {
+        get_proc_address("eglSwapBuffersWithDamageKHR")
+    } else if has_extension(extensions, "EGL_EXT_swap_buffers_with_damage") {
+        get_proc_address("eglSwapBuffersWithDamageEXT")
+    } else {
+        return DamageSwapper::Unsupported;
+    };
+
+    if swap.is_null() {
+        return DamageSwapper::Unsupported;
+    }
+
+    DamageSwapper::Egl {
+        display: display,
+        surface: surface,
+        swap: mem::transmute(swap),
+        get_error: mem::transmute(get_error),
+    }
+}
@@ -0,0 +1,216 @@
+//! Changes the swap interval of the current context with the functions of the window system,
+//! for backends that don't provide a way to do so.
+
+use libc;
+
+use SwapInterval;
+
+use std::ffi::CStr;
+use std::mem;
+
+/// Changes the swap interval of the current context, using `get_proc_address` to load the
+/// functions of WGL, GLX, EGL or CGL depending on the platform.
+///
+/// Returns `Err` if the swap interval is not supported.
+///
+/// *Safety*: the context must be current, and `get_proc_address` must load the functions of
+/// the window system of this context.
+pub unsafe fn set_swap_interval<F>(get_proc_address: F, interval: SwapInterval)
+                                   -> Result<(), ()>
+                                   where F: Fn(&str) -> *const libc::c_void
+{
+    let value = match interval {
+        SwapInterval::Immediate => 0,
+        SwapInterval::Sync(n) => n as libc::c_int,
+        SwapInterval::Adaptive => -1,
+    };
+
+    platform_set_swap_interval(&get_proc_address, value)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn platform_set_swap_interval<F>(get_proc_address: &F, value: libc::c_int)
+                                        -> Result<(), ()>
+                                        where F: Fn(&str) -> *const libc::c_void
+{
+    let swap_interval = get_proc_address("wglSwapIntervalEXT");
+    if swap_interval.is_null() {
+        return Err(());
+    }
+
+    if value < 0 {
+        let get_extensions = get_proc_address("wglGetExtensionsStringEXT");
+        if get_extensions.is_null() {
+            return Err(());
+        }
+
+        let get_extensions: extern "system" fn() -> *const libc::c_char =
+                                                                mem::transmute(get_extensions);
+        if !has_extension(get_extensions(), "WGL_EXT_swap_control_tear") {
+            return Err(());
+        }
+    }
+
+    let swap_interval: extern "system" fn(libc::c_int) -> libc::c_int =
+                                                                mem::transmute(swap_interval);
+    if swap_interval(value) != 0 { Ok(()) } else { Err(()) }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+unsafe fn platform_set_swap_interval<F>(get_proc_address: &F, value: libc::c_int)
+                                        -> Result<(), ()>
+                                        where F: Fn(&str) -> *const libc::c_void
+{
+    const KCGLCP_SWAP_INTERVAL: libc::c_int = 222;
+
+    if value < 0 {
+        return Err(());
+    }
+
+    let get_current_context = get_proc_address("CGLGetCurrentContext");
+    let set_parameter = get_proc_address("CGLSetParameter");
+    if get_current_context.is_null() || set_parameter.is_null() {
+        return Err(());
+    }
+
+    let get_current_context: extern "C" fn() -> *mut libc::c_void =
+                                                            mem::transmute(get_current_context);
+    let set_parameter: extern "C" fn(*mut libc::c_void, libc::c_int,
+                                     *const libc::c_int) -> libc::c_int =
+                                                            mem::transmute(set_parameter);
+
+    let context = get_current_context();
+    if context.is_null() {
+        return Err(());
+    }
+
+    if set_parameter(context, KCGLCP_SWAP_INTERVAL, &value) == 0 { Ok(()) } else { Err(()) }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn platform_set_swap_interval<F>(get_proc_address: &F, value: libc::c_int)
+                                        -> Result<(), ()>
+                                        where F: Fn(&str) -> *const libc::c_void
+{
+    // GLX is tried first, and if there is no current GLX display we try EGL
+    match glx_set_swap_interval(get_proc_address, value) {
+        Some(result) => result,
+        None => egl_set_swap_interval(get_proc_address, value),
+    }
+}
+
+/// Returns `None` if GLX is not used by the current context.
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn glx_set_swap_interval<F>(get_proc_address: &F, value: libc::c_int)
+                                   -> Option<Result<(), ()>>
+                                   where F: Fn(&str) -> *const libc::c_void
+{
+    const GLX_EXTENSIONS: libc::c_int = 3;
+
+    let get_current_display = get_proc_address("glXGetCurrentDisplay");
+    let get_current_drawable = get_proc_address("glXGetCurrentDrawable");
+    let get_client_string = get_proc_address("glXGetClientString");
+    if get_current_display.is_null() || get_current_drawable.is_null() ||
+       get_client_string.is_null()
+    {
+        return None;
+    }
+
+    let get_current_display: extern "C" fn() -> *mut libc::c_void =
+                                                            mem::transmute(get_current_display);
+    let get_current_drawable: extern "C" fn() -> libc::c_ulong =
+                                                            mem::transmute(get_current_drawable);
+    let get_client_string: extern "C" fn(*mut libc::c_void, libc::c_int) -> *const libc::c_char =
+                                                            mem::transmute(get_client_string);
+
+    let display = get_current_display();
+    if display.is_null() {
+        return None;
+    }
+
+    let extensions = get_client_string(display, GLX_EXTENSIONS);
+
+    if has_extension(extensions, "GLX_EXT_swap_control") {
+        if value < 0 && !has_extension(extensions, "GLX_EXT_swap_control_tear") {
+            return Some(Err(()));
+        }
+
+        let swap_interval = get_proc_address("glXSwapIntervalEXT");
+        if swap_interval.is_null() {
+            return Some(Err(()));
+        }
+
+        let swap_interval: extern "C" fn(*mut libc::c_void, libc::c_ulong, libc::c_int) =
+                                                                mem::transmute(swap_interval);
+        swap_interval(display, get_current_drawable(), value);
+        return Some(Ok(()));
+    }
+
+    if value < 0 {
+        return Some(Err(()));
+    }
+
+    if has_extension(extensions, "GLX_MESA_swap_control") {
+        let swap_interval = get_proc_address("glXSwapIntervalMESA");
+        if swap_interval.is_null() {
+            return Some(Err(()));
+        }
+
+        let swap_interval: extern "C" fn(libc::c_uint) -> libc::c_int =
+                                                                mem::transmute(swap_interval);
+        return Some(if swap_interval(value as libc::c_uint) == 0 { Ok(()) } else { Err(()) });
+    }
+
+    // `glXSwapIntervalSGI` doesn't accept 0
+    if value > 0 && has_extension(extensions, "GLX_SGI_swap_control") {
+        let swap_interval = get_proc_address("glXSwapIntervalSGI");
+        if swap_interval.is_null() {
+            return Some(Err(()));
+        }
+
+        let swap_interval: extern "C" fn(libc::c_int) -> libc::c_int =
+                                                                mem::transmute(swap_interval);
+        return Some(if swap_interval(value) == 0 { Ok(()) } else { Err(()) });
+    }
+
+    Some(Err(()))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
+unsafe fn egl_set_swap_interval<F>(get_proc_address: &F, value: libc::c_int) -> Result<(), ()>
+                                   where F: Fn(&str) -> *const libc::c_void
+{
+    // EGL doesn't support adaptive vsync
+    if value < 0 {
+        return Err(());
+    }
+
+    let get_current_display = get_proc_address("eglGetCurrentDisplay");
+    let swap_interval = get_proc_address("eglSwapInterval");
+    if get_current_display.is_null() || swap_interval.is_null() {
+        return Err(());
+    }
+
+    let get_current_display: extern "system" fn() -> *mut libc::c_void =
+                                                            mem::transmute(get_current_display);
+    let swap_interval: extern "system" fn(*mut libc::c_void, i32) -> libc::c_uint =
+                                                            mem::transmute(swap_interval);
+
+    let display = get_current_display();
+    if display.is_null() {
+        return Err(());
+    }
+
+    if swap_interval(display, value) != 0 { Ok(()) } else { Err(()) }
+}
+
+/// Returns true if the space-separated list of extensions contains `name`.
+#[allow(dead_code)]
+pub unsafe fn has_extension(list: *const libc::c_char, name: &str) -> bool {
+    if list.is_null() {
+        return false;
+    }
+
+    let list = CStr::from_ptr(list);
+    String::from_utf8_lossy(list.to_bytes()).split(' ').any(|e| e == name)
+}
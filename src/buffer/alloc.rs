@@ -6,8 +6,9 @@ use CapabilitiesSource;
 use ContextExt;
 use gl;
 use libc;
-use std::{fmt, mem, ptr};
-use std::cell::Cell;
+use std::{fmt, mem, ptr, slice};
+use std::error::Error;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::ops::{Deref, DerefMut, Range};
 use GlObject;
@@ -29,6 +30,21 @@ pub enum ReadError {
     ContextLost,
 }
 
+impl fmt::Display for ReadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for ReadError {
+    fn description(&self) -> &str {
+        match self {
+            &ReadError::NotSupported => "The backend doesn't support reading from a buffer",
+            &ReadError::ContextLost => "The context has been lost",
+        }
+    }
+}
+
 /// Error that can happen when copying data between buffers.
 #[derive(Debug, Copy, Clone)]
 pub enum CopyError {
@@ -36,6 +52,20 @@ pub enum CopyError {
     NotSupported,
 }
 
+impl fmt::Display for CopyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for CopyError {
+    fn description(&self) -> &str {
+        match self {
+            &CopyError::NotSupported => "The backend doesn't support copying between buffers",
+        }
+    }
+}
+
 /// A buffer in the graphics card's memory.
 pub struct Alloc {
     context: Rc<Context>,
@@ -66,6 +96,13 @@ pub struct Alloc {
 
     /// ID of the draw call where the buffer was last written as an SSBO.
     latest_shader_write: Cell<u64>,
+
+    /// If true, the uploads are stored in `pending_writes` instead of being sent immediately.
+    write_coalescing: Cell<bool>,
+
+    /// Data that has been written but not sent to OpenGL yet, with its offset in bytes. The
+    /// ranges are sorted, and they neither overlap nor touch each other.
+    pending_writes: RefCell<Vec<(usize, Vec<u8>)>>,
 }
 
 impl Alloc {
@@ -93,6 +130,8 @@ impl Alloc {
             creation_mode: mode,
             mapped: Cell::new(false),
             latest_shader_write: Cell::new(0),
+            write_coalescing: Cell::new(false),
+            pending_writes: RefCell::new(Vec::new()),
         })
     }
 
@@ -116,6 +155,8 @@ impl Alloc {
             creation_mode: mode,
             mapped: Cell::new(false),
             latest_shader_write: Cell::new(0),
+            write_coalescing: Cell::new(false),
+            pending_writes: RefCell::new(Vec::new()),
         })
     }
 
@@ -144,13 +185,82 @@ impl Alloc {
         Ok(self)
     }
 
-    /// Asserts that the buffer is not mapped and available for operations.
+    /// Sets whether the uploads should be kept in memory and sent all at once when the buffer
+    /// is used, instead of being sent immediately. Disabling it sends the pending uploads.
+    ///
+    /// Has no effect on persistent-mapped buffers.
+    pub fn set_write_coalescing(&self, enabled: bool) {
+        self.write_coalescing.set(enabled);
+
+        if !enabled {
+            let mut ctxt = self.context.make_current();
+            self.flush_pending_writes(&mut ctxt);
+        }
+    }
+
+    /// Returns true if the uploads are kept in memory until the buffer is used.
+    #[inline]
+    pub fn uses_write_coalescing(&self) -> bool {
+        self.write_coalescing.get()
+    }
+
+    /// Asserts that the buffer is not mapped and available for operations, and sends the
+    /// pending writes to OpenGL.
     /// No-op for persistent mapping.
     fn assert_unmapped(&self, ctxt: &mut CommandContext) {
         if self.mapped.get() {
             unsafe { unmap_buffer(ctxt, self.id, self.ty) };
             self.mapped.set(false);
         }
+
+        self.flush_pending_writes(ctxt);
+    }
+
+    /// Sends the writes that have been delayed by the write coalescing to OpenGL.
+    pub fn flush_pending_writes(&self, ctxt: &mut CommandContext) {
+        if self.pending_writes.borrow().is_empty() {
+            return;
+        }
+
+        let writes = mem::replace(&mut *self.pending_writes.borrow_mut(), Vec::new());
+        for (offset, data) in writes.into_iter() {
+            unsafe { self.upload_impl(ctxt, offset, &data[..]); }
+        }
+    }
+
+    /// Stores data in `pending_writes`, merging it with the writes that it overlaps or touches.
+    fn queue_write(&self, offset: usize, data: &[u8]) {
+        if data.len() == 0 {
+            return;
+        }
+
+        let mut pending = self.pending_writes.borrow_mut();
+        let end = offset + data.len();
+
+        // the writes to merge with the new one are contiguous in the list, since it is sorted
+        let first = pending.iter().position(|&(o, ref d)| o + d.len() >= offset)
+                           .unwrap_or(pending.len());
+        let last = pending[first ..].iter().position(|&(o, _)| o > end)
+                                   .map(|p| first + p).unwrap_or(pending.len());
+
+        if first == last {
+            pending.insert(first, (offset, data.to_vec()));
+            return;
+        }
+
+        let merged_start = if pending[first].0 < offset { pending[first].0 } else { offset };
+        let merged_end = {
+            let &(o, ref d) = &pending[last - 1];
+            if o + d.len() > end { o + d.len() } else { end }
+        };
+
+        let mut merged = vec![0; merged_end - merged_start];
+        for (o, d) in pending.drain(first .. last) {
+            merged[o - merged_start .. o - merged_start + d.len()].copy_from_slice(&d);
+        }
+        merged[offset - merged_start .. end - merged_start].copy_from_slice(data);
+
+        pending.insert(first, (merged_start, merged));
     }
 
     /// Ensures that the buffer isn't used by the transform feedback process.
@@ -374,11 +484,31 @@ impl Alloc {
         assert!(offset_bytes + mem::size_of_val(data) <= self.size);
 
         if self.persistent_mapping.is_some() {
+            self.context.make_current()
+                .record_statistics(|s| s.buffer_upload_bytes += mem::size_of_val(data) as u64);
+
             let mapping = Mapping { mapping: self.map_shared(offset_bytes .. offset_bytes + mem::size_of_val(data), false, true) };
             ptr::copy_nonoverlapping(data.to_void_ptr() as *const u8, <D as Content>::to_void_ptr(&mapping) as *mut u8, mem::size_of_val(data));
 
-        } else if self.immutable {
+        } else if self.write_coalescing.get() {
+            let bytes = slice::from_raw_parts(data.to_void_ptr() as *const u8,
+                                              mem::size_of_val(data));
+            self.queue_write(offset_bytes, bytes);
+
+        } else {
             let mut ctxt = self.context.make_current();
+            self.flush_pending_writes(&mut ctxt);
+            self.upload_impl(&mut ctxt, offset_bytes, data);
+        }
+    }
+
+    /// Sends data to a buffer that isn't persistent-mapped, ignoring the write coalescing.
+    unsafe fn upload_impl<D: ?Sized>(&self, mut ctxt: &mut CommandContext, offset_bytes: usize,
+                                     data: &D) where D: Content
+    {
+        ctxt.record_statistics(|s| s.buffer_upload_bytes += mem::size_of_val(data) as u64);
+
+        if self.immutable {
             self.barrier_for_buffer_update(&mut ctxt);
 
             self.assert_unmapped(&mut ctxt);
@@ -393,7 +523,6 @@ impl Alloc {
         } else {
             assert!(offset_bytes < self.size);
 
-            let mut ctxt = self.context.make_current();
             self.barrier_for_buffer_update(&mut ctxt);
 
             let invalidate_all = offset_bytes == 0 && mem::size_of_val(data) == self.size;
@@ -462,7 +591,7 @@ impl Alloc {
                                                          size as gl::types::GLsizeiptr) };
             }
 
-        } else if !self.immutable {
+        } else if !self.immutable && !self.context.get_quirks().no_buffer_orphaning {
             if is_whole_buffer {
                 let flags = match self.creation_mode {
                     BufferMode::Default | BufferMode::Immutable => gl::STATIC_DRAW,
@@ -709,6 +838,9 @@ impl Alloc {
 
     /// Reads the content of the buffer.
     ///
+    /// OpenGL ES doesn't have `glGetBufferSubData`, so with OpenGL ES 3.0 the range is mapped
+    /// instead. Returns `NotSupported` with older versions of OpenGL ES.
+    ///
     /// # Panic
     ///
     /// Panicks if out of range.
@@ -759,6 +891,20 @@ impl Alloc {
                                                 size_to_read as gl::types::GLsizeiptr,
                                                 output as *mut _ as *mut libc::c_void);
 
+                } else if ctxt.version >= &Version(Api::GlEs, 3, 0) && !self.immutable {
+                    if size_to_read != 0 {
+                        let ptr = map_buffer(&mut ctxt, self.id, self.ty, range.clone(), true,
+                                             false);
+                        let ptr = match ptr {
+                            Some(ptr) if !ptr.is_null() => ptr,
+                            _ => return Err(ReadError::NotSupported),
+                        };
+
+                        ptr::copy_nonoverlapping(ptr as *const u8, output as *mut D as *mut u8,
+                                                 size_to_read);
+                        unmap_buffer(&mut ctxt, self.id, self.ty);
+                    }
+
                 } else if ctxt.version >= &Version(Api::GlEs, 1, 0) {
                     return Err(ReadError::NotSupported);
 
@@ -790,6 +936,8 @@ impl Alloc {
         assert!(dest_offset + range.end - range.start <= target.size);
 
         let mut ctxt = self.context.make_current();
+        self.flush_pending_writes(&mut ctxt);
+        target.flush_pending_writes(&mut ctxt);
 
         unsafe {
             copy_buffer(&mut ctxt, self.id, range.start, target.id, dest_offset,
@@ -807,11 +955,14 @@ impl fmt::Debug for Alloc {
 impl Drop for Alloc {
     fn drop(&mut self) {
         unsafe {
+            // the pending writes would be useless
+            self.pending_writes.borrow_mut().clear();
+
             let mut ctxt = self.context.make_current();
             self.assert_unmapped(&mut ctxt);
             self.assert_not_transform_feedback(&mut ctxt);
             VertexAttributesSystem::purge_buffer(&mut ctxt, self.id);
-            destroy_buffer(&mut ctxt, self.id);
+            ctxt.destroy_object(self.id, destroy_buffer);
         }
     }
 }
@@ -1065,8 +1216,14 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         return Err(BufferCreationError::BufferTypeNotSupported);
     }
 
+    // the size is passed as a `GLsizeiptr`, which is signed
+    if size > ::std::isize::MAX as usize {
+        return Err(BufferCreationError::OutOfMemory);
+    }
+
     if let Some(data) = data {
         assert!(mem::size_of_val(data) == size);
+        ctxt.record_statistics(|s| s.buffer_upload_bytes += size as u64);
     }
 
     // creating the id of the buffer
@@ -1218,9 +1375,10 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
                 unreachable!();
             };
 
+            // the mapping can fail if there is not enough address space
             if ptr.is_null() {
-                let error = ::get_gl_error(ctxt);
-                panic!("glMapBufferRange returned null (error: {:?})", error);
+                destroy_buffer(&mut ctxt, id);
+                return Err(BufferCreationError::OutOfMemory);
             }
 
             Some(ptr)
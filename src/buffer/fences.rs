@@ -2,17 +2,26 @@
 
 This module handles the fences of a buffer.
 
+A sync object can't be reused once it has been signaled, so instead of creating one fence per
+buffer and per operation, the same fence is shared between all the buffers that are accessed
+by an operation (see `Inserter::insert_all`), and between the ranges of a buffer that are split.
+The fence is destroyed when it is no longer referenced by any buffer.
+
 */
 use smallvec::SmallVec;
 use std::cell::RefCell;
 use std::ops::Range;
+use std::rc::Rc;
 
 use context::CommandContext;
 use sync::{self, LinearSyncFence};
 
+/// A fence that can be shared between multiple ranges and buffers.
+type SharedFence = Rc<LinearSyncFence>;
+
 /// Contains a list of fences.
 pub struct Fences {
-    fences: RefCell<SmallVec<[(Range<usize>, LinearSyncFence); 16]>>,
+    fences: RefCell<SmallVec<[(Range<usize>, SharedFence); 16]>>,
 }
 
 impl Fences {
@@ -41,7 +50,8 @@ impl Fences {
             if (existing.0.start >= range.start && existing.0.start < range.end) ||
                (existing.0.end > range.start && existing.0.end < range.end)
             {
-                unsafe { sync::wait_linear_sync_fence_and_drop(existing.1, ctxt) };
+                unsafe { sync::wait_linear_sync_fence(&existing.1, ctxt) };
+                release(ctxt, existing.1);
             } else {
                 new_fences.push(existing);
             }
@@ -54,11 +64,18 @@ impl Fences {
     pub fn clean(&mut self, ctxt: &mut CommandContext) {
         let mut fences = self.fences.borrow_mut();
         for (_, sync) in fences.into_iter() {
-            unsafe { sync::destroy_linear_sync_fence(ctxt, sync) };
+            release(ctxt, sync);
         }
     }
 }
 
+/// Removes a reference to a fence, and destroys the fence if it was the last one.
+fn release(ctxt: &mut CommandContext, fence: SharedFence) {
+    if let Ok(fence) = Rc::try_unwrap(fence) {
+        unsafe { sync::destroy_linear_sync_fence(ctxt, fence) };
+    }
+}
+
 /// Allows inserting a fence in the list.
 pub struct Inserter<'a> {
     fences: &'a Fences,
@@ -67,7 +84,34 @@ pub struct Inserter<'a> {
 
 impl<'a> Inserter<'a> {
     /// Inserts a new fence.
+    #[inline]
     pub fn insert(self, ctxt: &mut CommandContext) {
+        Inserter::insert_all(ctxt, Some(self));
+    }
+
+    /// Inserts a single fence in the lists of all the inserters.
+    ///
+    /// This should be used when multiple buffers are accessed by the same command, in order to
+    /// avoid creating one fence per buffer.
+    pub fn insert_all<I>(ctxt: &mut CommandContext, inserters: I)
+                         where I: IntoIterator<Item = Inserter<'a>>
+    {
+        let mut inserters = inserters.into_iter().peekable();
+        if inserters.peek().is_none() {
+            return;
+        }
+
+        let fence = Rc::new(unsafe { sync::new_linear_sync_fence(ctxt).unwrap() });
+
+        for inserter in inserters {
+            inserter.insert_shared(ctxt, &fence);
+        }
+
+        release(ctxt, fence);
+    }
+
+    /// Inserts an existing fence.
+    fn insert_shared(self, ctxt: &mut CommandContext, fence: &SharedFence) {
         let mut new_fences = SmallVec::new();
 
         let mut written = false;
@@ -77,34 +121,30 @@ impl<'a> Inserter<'a> {
             if existing.0.start < self.range.start && existing.0.end <= self.range.start {
                 new_fences.push(existing);
 
-            } else if existing.0.start < self.range.start && existing.0.end >= self.range.end {
-                // we are stuck here, because we can't duplicate a fence
-                // so instead we just extend the new fence to the existing one
-                let new_fence = unsafe { sync::new_linear_sync_fence(ctxt).unwrap() };
-                new_fences.push((existing.0.start .. self.range.start, existing.1));
-                new_fences.push((self.range.start .. existing.0.end, new_fence));
+            } else if existing.0.start < self.range.start && existing.0.end > self.range.end {
+                // the existing fence is split in two parts that share the same fence
+                new_fences.push((existing.0.start .. self.range.start, existing.1.clone()));
+                new_fences.push((self.range.clone(), fence.clone()));
+                new_fences.push((self.range.end .. existing.0.end, existing.1));
                 written = true;
 
             } else if existing.0.start < self.range.start && existing.0.end >= self.range.start {
                 new_fences.push((existing.0.start .. self.range.start, existing.1));
                 if !written {
-                    let new_fence = unsafe { sync::new_linear_sync_fence(ctxt).unwrap() };
-                    new_fences.push((self.range.clone(), new_fence));
+                    new_fences.push((self.range.clone(), fence.clone()));
                     written = true;
                 }
 
             } else if existing.0.start >= self.range.start && existing.0.end <= self.range.end {
-                unsafe { sync::destroy_linear_sync_fence(ctxt, existing.1) };
+                release(ctxt, existing.1);
                 if !written {
-                    let new_fence = unsafe { sync::new_linear_sync_fence(ctxt).unwrap() };
-                    new_fences.push((self.range.clone(), new_fence));
+                    new_fences.push((self.range.clone(), fence.clone()));
                     written = true;
                 }
 
             } else if existing.0.start >= self.range.end {
                 if !written {
-                    let new_fence = unsafe { sync::new_linear_sync_fence(ctxt).unwrap() };
-                    new_fences.push((self.range.clone(), new_fence));
+                    new_fences.push((self.range.clone(), fence.clone()));
                     written = true;
                 }
 
@@ -112,8 +152,7 @@ impl<'a> Inserter<'a> {
 
             } else {
                 if !written {
-                    let new_fence = unsafe { sync::new_linear_sync_fence(ctxt).unwrap() };
-                    new_fences.push((self.range.clone(), new_fence));
+                    new_fences.push((self.range.clone(), fence.clone()));
                     written = true;
                 }
 
@@ -122,8 +161,7 @@ impl<'a> Inserter<'a> {
         }
 
         if !written {
-            let new_fence = unsafe { sync::new_linear_sync_fence(ctxt).unwrap() };
-            new_fences.push((self.range, new_fence));
+            new_fences.push((self.range, fence.clone()));
         }
 
         *existing_fences = new_fences;
@@ -90,6 +90,40 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         self.alloc.as_ref().unwrap().uses_persistent_mapping()
     }
 
+    /// Sets whether the writes to this buffer and to its slices should be coalesced.
+    ///
+    /// When enabled, `write` copies the data in memory instead of sending it immediately. Writes
+    /// that overlap or touch each other are merged, and they are all sent to OpenGL the next
+    /// time the buffer is used, for example by a draw call. This reduces the number of calls to
+    /// `glBufferSubData` when a lot of small writes are made to the same buffer, like in UI
+    /// renderers.
+    ///
+    /// Disabled by default. Disabling it sends the pending writes. Has no effect on
+    /// persistent-mapped buffers.
+    ///
+    /// The pending writes are not automatically sent when the buffer is only accessed through a
+    /// `BufferTexture`. Call `flush_writes` before drawing in this situation.
+    #[inline]
+    pub fn set_write_coalescing(&self, enabled: bool) {
+        self.alloc.as_ref().unwrap().set_write_coalescing(enabled);
+    }
+
+    /// Returns true if the writes to this buffer are coalesced. See `set_write_coalescing`.
+    #[inline]
+    pub fn uses_write_coalescing(&self) -> bool {
+        self.alloc.as_ref().unwrap().uses_write_coalescing()
+    }
+
+    /// Sends the writes that have been delayed by the write coalescing to OpenGL.
+    ///
+    /// This is done automatically when the buffer is used, so you usually don't need to call
+    /// this function.
+    pub fn flush_writes(&self) {
+        let alloc = self.alloc.as_ref().unwrap();
+        let mut ctxt = alloc.get_context().make_current();
+        alloc.flush_pending_writes(&mut ctxt);
+    }
+
     /// Uploads some data in this buffer.
     ///
     /// # Implementation
@@ -331,7 +365,12 @@ impl<T> Buffer<[T]> where [T]: Content, T: Copy {
     pub fn empty_array<F>(facade: &F, ty: BufferType, len: usize, mode: BufferMode)
                           -> Result<Buffer<[T]>, BufferCreationError> where F: Facade
     {
-        Alloc::empty(facade, ty, len * mem::size_of::<T>(), mode)
+        let size = match len.checked_mul(mem::size_of::<T>()) {
+            Some(size) => size,
+            None => return Err(BufferCreationError::OutOfMemory),
+        };
+
+        Alloc::empty(facade, ty, size, mode)
             .map(|buffer| {
                 Buffer {
                     alloc: Some(buffer),
@@ -1027,6 +1066,17 @@ impl BufferAny {
         }
     }
 
+    /// Builds a slice of this subbuffer. Returns `None` if out of range.
+    ///
+    /// The range is expressed in number of elements, as returned by `get_elements_size`.
+    ///
+    /// This method builds an object that represents a slice of the buffer. No actual operation
+    /// OpenGL is performed.
+    #[inline]
+    pub fn slice(&self, range: Range<usize>) -> Option<BufferAnySlice> {
+        self.as_slice_any().slice(range)
+    }
+
     /// Returns the size in bytes of each element in the buffer.
     // TODO: clumbsy, remove this function
     #[inline]
@@ -1040,6 +1090,28 @@ impl BufferAny {
     pub fn get_elements_count(&self) -> usize {
         self.size / self.elements_size
     }
+
+    /// UNSTABLE. This function can be removed at any moment without any further notice.
+    ///
+    /// Changes the size in bytes of each element in the buffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `elements_size` is 0 or if the size of the buffer is not a multiple
+    /// of `elements_size`.
+    ///
+    /// # Unsafety
+    ///
+    /// The functions that read the buffer or that use it as a source of vertices, indices or
+    /// commands rely on the size of the elements. The content of the buffer must be valid for
+    /// elements of `elements_size` bytes, and the type that the buffer is later turned into
+    /// must have this size.
+    #[inline]
+    pub unsafe fn set_elements_size(&mut self, elements_size: usize) {
+        assert!(elements_size != 0);
+        assert!(self.size % elements_size == 0);
+        self.elements_size = elements_size;
+    }
     
     /// Returns the context corresponding to this buffer.
     #[inline]
@@ -1061,6 +1133,22 @@ impl BufferAny {
         self.alloc.invalidate(0, self.size);
     }
 
+    /// UNSTABLE. This function can be removed at any moment without any further notice.
+    ///
+    /// Writes some data at `offset_bytes` bytes from the start of the buffer, without checking
+    /// that it matches the content of the buffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the data doesn't fit in the buffer.
+    pub fn write_at<D: ?Sized>(&self, offset_bytes: usize, data: &D) where D: Content {
+        let end = offset_bytes + mem::size_of_val(data);
+        assert!(end <= self.size);
+
+        self.fence.wait(&mut self.alloc.get_context().make_current(), offset_bytes .. end);
+        unsafe { self.alloc.upload(offset_bytes, data); }
+    }
+
     /// UNSTABLE. This function can be removed at any moment without any further notice.
     ///
     /// Considers that the buffer is filled with elements of type `T` and reads them.
@@ -1221,6 +1309,40 @@ impl<'a> BufferAnySlice<'a> {
     pub fn invalidate(&self) {
         self.alloc.invalidate(self.bytes_start, self.get_size());
     }
+
+    /// UNSTABLE. This function can be removed at any moment without any further notice.
+    ///
+    /// Considers that the slice is filled with elements of type `T` and reads them.
+    ///
+    /// # Panic
+    ///
+    /// Panicks if the size of the slice is not a multiple of the size of the data.
+    ///
+    #[inline]
+    pub unsafe fn read<T: ?Sized>(&self) -> Result<T::Owned, ReadError> where T: Content {
+        self.fence.wait(&mut self.alloc.get_context().make_current(),
+                        self.bytes_start .. self.bytes_end);
+        self.alloc.read::<T>(self.bytes_start .. self.bytes_end)
+    }
+
+    /// Builds a slice of this slice. Returns `None` if out of range.
+    ///
+    /// The range is expressed in number of elements, as returned by `get_elements_size`.
+    #[inline]
+    pub fn slice(&self, range: Range<usize>) -> Option<BufferAnySlice<'a>> {
+        let len = self.get_elements_count();
+        if range.start > range.end || range.end > len {
+            return None;
+        }
+
+        Some(BufferAnySlice {
+            alloc: self.alloc,
+            bytes_start: self.bytes_start + range.start * self.elements_size,
+            bytes_end: self.bytes_start + range.end * self.elements_size,
+            elements_size: self.elements_size,
+            fence: self.fence,
+        })
+    }
 }
 
 impl<'a> fmt::Debug for BufferAnySlice<'a> {
@@ -0,0 +1,276 @@
+/*!
+Records commands from multiple threads and executes them later on the thread that owns the
+OpenGL context.
+
+Glium objects can't be sent between threads, so the commands don't contain them directly.
+Instead the thread that owns the context stores its objects in a `Resources` and gives their
+`ResourceId` to the other threads. Commands are plain data of a type that you define, and
+which usually contains some of these ids.
+
+An id only corresponds to an object of the `Resources` that returned it, even if another
+`Resources` contains objects of the same type.
+
+Each thread records its commands in a `CommandEncoder`, then submits it to a `CommandQueue`.
+The queue executes the commands in the order of the keys that were given when creating the
+encoders, and in the order of recording within an encoder. This order doesn't depend on which
+thread finishes first.
+
+# Example
+
+```no_run
+use std::thread;
+use glium::command_encoder::{CommandQueue, Resources, ResourceId};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let vertex_buffer: glium::vertex::VertexBufferAny = unsafe { ::std::mem::uninitialized() };
+
+enum Command {
+    Draw { mesh: ResourceId, position: [f32; 3] },
+}
+
+let mut meshes = Resources::new();
+let mesh = meshes.insert(vertex_buffer);
+
+let queue = CommandQueue::new();
+
+let threads = (0 .. 4).map(|n| {
+    let queue = queue.clone();
+    thread::spawn(move || {
+        let mut encoder = queue.encoder(n);
+        encoder.push(Command::Draw { mesh: mesh, position: [n as f32, 0.0, 0.0] });
+        queue.submit(encoder);
+    })
+}).collect::<Vec<_>>();
+
+for thread in threads { thread.join().unwrap(); }
+
+// on the thread that owns the context
+queue.execute(|command| -> Result<(), glium::command_encoder::InvalidResourceId> {
+    match command {
+        Command::Draw { mesh, position } => {
+            let mesh = try!(meshes.get(mesh));
+            // draw `mesh` at `position`
+        },
+    }
+    Ok(())
+}).unwrap();
+```
+
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+/// Used to identify each `Resources`, so that the ids of a `Resources` can't be used with
+/// another one.
+static NEXT_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Identifier of an object stored in a `Resources`.
+///
+/// Contrary to glium objects, ids can be sent between threads.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceId {
+    /// The id of the `Resources` that returned this id.
+    resources: usize,
+    index: u64,
+}
+
+/// Error that happens when a `ResourceId` doesn't correspond to any object, either because the
+/// object has been removed or because the id was returned by another `Resources`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidResourceId(pub ResourceId);
+
+impl fmt::Display for InvalidResourceId {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for InvalidResourceId {
+    fn description(&self) -> &str {
+        "The resource id doesn't correspond to any object"
+    }
+}
+
+/// Objects that can be referenced by commands recorded on other threads.
+pub struct Resources<T> {
+    /// Unique id of this `Resources`, which is stored in the `ResourceId`s that it returns.
+    id: usize,
+    objects: HashMap<u64, T>,
+    next_index: u64,
+}
+
+impl<T> Resources<T> {
+    /// Builds an empty list of resources.
+    #[inline]
+    pub fn new() -> Resources<T> {
+        Resources {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            objects: HashMap::new(),
+            next_index: 0,
+        }
+    }
+
+    /// Stores an object and returns its id.
+    ///
+    /// Ids are never reused, even after the object has been removed.
+    pub fn insert(&mut self, object: T) -> ResourceId {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.objects.insert(index, object);
+        ResourceId { resources: self.id, index: index }
+    }
+
+    /// Returns the object corresponding to an id.
+    ///
+    /// Returns an error if the id was returned by another `Resources`.
+    #[inline]
+    pub fn get(&self, id: ResourceId) -> Result<&T, InvalidResourceId> {
+        if id.resources != self.id {
+            return Err(InvalidResourceId(id));
+        }
+
+        self.objects.get(&id.index).ok_or(InvalidResourceId(id))
+    }
+
+    /// Removes an object and returns it.
+    ///
+    /// Returns an error if the id was returned by another `Resources`.
+    #[inline]
+    pub fn remove(&mut self, id: ResourceId) -> Result<T, InvalidResourceId> {
+        if id.resources != self.id {
+            return Err(InvalidResourceId(id));
+        }
+
+        self.objects.remove(&id.index).ok_or(InvalidResourceId(id))
+    }
+
+    /// Returns true if the id corresponds to an object of this `Resources`.
+    #[inline]
+    pub fn contains(&self, id: ResourceId) -> bool {
+        id.resources == self.id && self.objects.contains_key(&id.index)
+    }
+
+    /// Returns the number of objects.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+}
+
+impl<T> Default for Resources<T> {
+    #[inline]
+    fn default() -> Resources<T> {
+        Resources::new()
+    }
+}
+
+/// List of commands recorded by a thread.
+pub struct CommandEncoder<C> {
+    order: u64,
+    commands: Vec<C>,
+}
+
+impl<C> CommandEncoder<C> {
+    /// Adds a command at the end of the encoder.
+    #[inline]
+    pub fn push(&mut self, command: C) {
+        self.commands.push(command);
+    }
+
+    /// Returns the number of commands in the encoder.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if the encoder doesn't contain any command.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns the key that determines when the commands of this encoder are executed.
+    #[inline]
+    pub fn get_order(&self) -> u64 {
+        self.order
+    }
+}
+
+/// Encoders that have been submitted and whose commands haven't been executed yet.
+///
+/// Cloning a queue returns a handle to the same queue.
+pub struct CommandQueue<C> {
+    submitted: Arc<Mutex<Vec<CommandEncoder<C>>>>,
+}
+
+impl<C> CommandQueue<C> where C: Send {
+    /// Builds an empty queue.
+    #[inline]
+    pub fn new() -> CommandQueue<C> {
+        CommandQueue {
+            submitted: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Builds a new encoder.
+    ///
+    /// The commands of encoders with a lower `order` are executed before the commands of
+    /// encoders with a higher `order`. Encoders with the same `order` are executed in the order
+    /// of submission.
+    #[inline]
+    pub fn encoder(&self, order: u64) -> CommandEncoder<C> {
+        CommandEncoder {
+            order: order,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Submits an encoder. Its commands are executed during the next call to `execute`.
+    #[inline]
+    pub fn submit(&self, encoder: CommandEncoder<C>) {
+        self.submitted.lock().unwrap().push(encoder);
+    }
+
+    /// Executes all the commands that have been submitted, in order, and removes them from the
+    /// queue. This is usually called from the thread that owns the OpenGL context.
+    ///
+    /// If `executor` returns an error, the remaining commands are discarded and the error is
+    /// returned.
+    pub fn execute<F, E>(&self, mut executor: F) -> Result<(), E>
+                         where F: FnMut(C) -> Result<(), E>
+    {
+        let mut encoders = {
+            let mut submitted = self.submitted.lock().unwrap();
+            ::std::mem::replace(&mut *submitted, Vec::new())
+        };
+
+        // the sort is stable, so the order of submission is kept for equal keys
+        encoders.sort_by(|a, b| a.order.cmp(&b.order));
+
+        for encoder in encoders.into_iter() {
+            for command in encoder.commands.into_iter() {
+                try!(executor(command));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> Clone for CommandQueue<C> {
+    #[inline]
+    fn clone(&self) -> CommandQueue<C> {
+        CommandQueue {
+            submitted: self.submitted.clone(),
+        }
+    }
+}
+
+impl<C> Default for CommandQueue<C> where C: Send {
+    #[inline]
+    fn default() -> CommandQueue<C> {
+        CommandQueue::new()
+    }
+}
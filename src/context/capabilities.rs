@@ -1,8 +1,8 @@
 use context::ExtensionsList;
+use context::Quirks;
 use version::Version;
 use version::Api;
 use std::cmp;
-use std::ffi::CStr;
 use std::mem;
 use gl;
 
@@ -18,6 +18,11 @@ pub struct Capabilities {
     /// True if out-of-bound access on the GPU side can't result in crashes.
     pub robustness: bool,
 
+    /// True if out-of-bound reads of buffers from the GPU side return zero or a value from
+    /// within the buffer, and out-of-bound writes are discarded. Requires a robust context and
+    /// `GL_ARB_robust_buffer_access_behavior` or equivalent.
+    pub robust_buffer_access_behavior: bool,
+
     /// True if it is possible for the OpenGL context to be lost.
     pub can_lose_context: bool,
 
@@ -36,6 +41,24 @@ pub struct Capabilities {
     /// Number of bits in the default framebuffer's stencil buffer
     pub stencil_bits: Option<u16>,
 
+    /// Number of samples of the default framebuffer, or `None` if it is not multisampled.
+    pub samples: Option<u16>,
+
+    /// Number of bits of the red, green, blue and alpha components of the default framebuffer.
+    pub color_bits: (Option<u16>, Option<u16>, Option<u16>, Option<u16>),
+
+    /// True if the color buffer of the default framebuffer contains floating-point values.
+    pub floating_point_color: bool,
+
+    /// The profile of the context, or `None` if the context doesn't have any.
+    pub profile: Option<Profile>,
+
+    /// True if the context is forward-compatible, ie. deprecated functions are not available.
+    pub forward_compatible: bool,
+
+    /// True if the context is a debug context.
+    pub debug: bool,
+
     /// Maximum number of textures that can be bound to a program.
     ///
     /// `glActiveTexture` must be between `GL_TEXTURE0` and `GL_TEXTURE0` + this value - 1.
@@ -87,6 +110,40 @@ pub struct Capabilities {
 
     /// Maximum samples of an empty framebuffer. `None` if not supported.
     pub max_framebuffer_samples: Option<gl::types::GLint>,
+
+    /// Maximum width and height of a 1D or 2D texture.
+    pub max_texture_size: gl::types::GLint,
+
+    /// Maximum width, height and depth of a 3D texture. `None` if 3D textures are not supported.
+    pub max_3d_texture_size: Option<gl::types::GLint>,
+
+    /// Maximum width and height of a cubemap. `None` if cubemaps are not supported.
+    pub max_cube_map_texture_size: Option<gl::types::GLint>,
+
+    /// Maximum number of layers of an array texture. `None` if array textures are not
+    /// supported.
+    pub max_array_texture_layers: Option<gl::types::GLint>,
+
+    /// Maximum width and height of a render buffer. `0` if render buffers are not supported.
+    pub max_renderbuffer_size: gl::types::GLint,
+
+    /// Number of bytes of pixel local storage per pixel that a fragment shader can use without
+    /// performance penalty. `None` if pixel local storage is not supported.
+    pub max_shader_pixel_local_storage_fast_size: Option<gl::types::GLint>,
+
+    /// Maximum number of bytes of pixel local storage per pixel that a fragment shader can use.
+    /// `None` if pixel local storage is not supported.
+    pub max_shader_pixel_local_storage_size: Option<gl::types::GLint>,
+
+    /// Number of bytes per pixel that a fragment shader can use for its color outputs and its
+    /// pixel local storage combined without performance penalty. `None` if
+    /// `GL_EXT_shader_pixel_local_storage2` is not supported.
+    pub max_shader_combined_local_storage_fast_size: Option<gl::types::GLint>,
+
+    /// Maximum number of bytes per pixel that a fragment shader can use for its color outputs and
+    /// its pixel local storage combined. `None` if `GL_EXT_shader_pixel_local_storage2` is not
+    /// supported.
+    pub max_shader_combined_local_storage_size: Option<gl::types::GLint>,
 }
 
 /// Defines what happens when you change the current context.
@@ -99,6 +156,47 @@ pub enum ReleaseBehavior {
     Flush,
 }
 
+/// Properties of the default framebuffer that have been obtained when creating the context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DefaultFramebufferProperties {
+    /// Number of bits of the red component, or `None` if there is no red component.
+    pub red_bits: Option<u16>,
+
+    /// Number of bits of the green component, or `None` if there is no green component.
+    pub green_bits: Option<u16>,
+
+    /// Number of bits of the blue component, or `None` if there is no blue component.
+    pub blue_bits: Option<u16>,
+
+    /// Number of bits of the alpha component, or `None` if there is no alpha component.
+    pub alpha_bits: Option<u16>,
+
+    /// True if the color buffer contains floating-point values.
+    pub floating_point: bool,
+
+    /// True if the color buffer is sRGB-capable.
+    pub srgb: bool,
+
+    /// Number of bits of the depth buffer, or `None` if there is no depth buffer.
+    pub depth_bits: Option<u16>,
+
+    /// Number of bits of the stencil buffer, or `None` if there is no stencil buffer.
+    pub stencil_bits: Option<u16>,
+
+    /// Number of samples, or `None` if the framebuffer is not multisampled.
+    pub samples: Option<u16>,
+}
+
+/// Profile of an OpenGL context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// Only the functions of the core profile are available.
+    Core,
+
+    /// The functions that have been removed from the core profile are available.
+    Compatibility,
+}
+
 /// Loads the capabilities.
 ///
 /// *Safety*: the OpenGL context corresponding to `gl` must be current in the thread.
@@ -108,39 +206,21 @@ pub enum ReleaseBehavior {
 /// Can panic if the version number or extensions list don't match the backend, leading to
 /// unloaded functions being called.
 ///
-pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &ExtensionsList)
-                               -> Capabilities
+pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &ExtensionsList,
+                               quirks: &Quirks) -> Capabilities
 {
-    // getting the value of `GL_RENDERER`
-    let renderer = {
-        let s = gl.GetString(gl::RENDERER);
-        assert!(!s.is_null());
-        String::from_utf8(CStr::from_ptr(s as *const i8).to_bytes().to_vec()).ok()
-                                    .expect("glGetString(GL_RENDERER) returned a non-UTF8 string")
-    };
-
     Capabilities {
         supported_glsl_versions: {
             get_supported_glsl(gl, version, extensions)
         },
 
-        robustness: if version >= &Version(Api::Gl, 4, 5) || version >= &Version(Api::GlEs, 3, 2) ||
-                       (version >= &Version(Api::Gl, 3, 0) && extensions.gl_arb_robustness)
-        {
-            // TODO: there seems to be no way to query `GL_CONTEXT_FLAGS` before OpenGL 3.0, even
-            //       if `GL_ARB_robustness` is there
-            let mut val = mem::uninitialized();
-            gl.GetIntegerv(gl::CONTEXT_FLAGS, &mut val);
-            let val = val as gl::types::GLenum;
-            (val & gl::CONTEXT_FLAG_ROBUST_ACCESS_BIT) != 0
-
-        } else if extensions.gl_khr_robustness || extensions.gl_ext_robustness {
-            let mut val = mem::uninitialized();
-            gl.GetBooleanv(gl::CONTEXT_ROBUST_ACCESS, &mut val);
-            val != 0
+        robustness: is_robust(gl, version, extensions),
 
-        } else {
-            false
+        robust_buffer_access_behavior: {
+            (version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 2) ||
+             extensions.gl_arb_robust_buffer_access_behavior ||
+             extensions.gl_khr_robust_buffer_access_behavior) &&
+            is_robust(gl, version, extensions)
         },
 
         can_lose_context: if version >= &Version(Api::Gl, 4, 5) || extensions.gl_khr_robustness ||
@@ -275,13 +355,107 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             }
         },
 
+        samples: {
+            let mut value = 0;
+            gl.GetIntegerv(gl::SAMPLES, &mut value);
+
+            match value {
+                0 => None,
+                v => Some(v as u16),
+            }
+        },
+
+        color_bits: (
+            get_default_framebuffer_color_size(gl, version, extensions,
+                                               gl::FRAMEBUFFER_ATTACHMENT_RED_SIZE, gl::RED_BITS),
+            get_default_framebuffer_color_size(gl, version, extensions,
+                                               gl::FRAMEBUFFER_ATTACHMENT_GREEN_SIZE,
+                                               gl::GREEN_BITS),
+            get_default_framebuffer_color_size(gl, version, extensions,
+                                               gl::FRAMEBUFFER_ATTACHMENT_BLUE_SIZE,
+                                               gl::BLUE_BITS),
+            get_default_framebuffer_color_size(gl, version, extensions,
+                                               gl::FRAMEBUFFER_ATTACHMENT_ALPHA_SIZE,
+                                               gl::ALPHA_BITS),
+        ),
+
+        floating_point_color: {
+            // see the comments about `depth_bits`
+            let attachment = if version >= &Version(Api::Gl, 3, 0) &&
+                                !extensions.gl_arb_compatibility
+            {
+                Some(gl::BACK_LEFT)
+            } else if version >= &Version(Api::GlEs, 3, 0) {
+                Some(gl::BACK)
+            } else {
+                None
+            };
+
+            if let Some(attachment) = attachment {
+                // headless, surfaceless and single-buffered contexts don't have a back buffer
+                let mut ty = gl::NONE as gl::types::GLint;
+                gl.GetFramebufferAttachmentParameteriv(gl::FRAMEBUFFER, attachment,
+                                                       gl::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE,
+                                                       &mut ty);
+
+                if ty as gl::types::GLenum == gl::NONE {
+                    false
+                } else {
+                    let mut value = 0;
+                    let param = gl::FRAMEBUFFER_ATTACHMENT_COMPONENT_TYPE;
+                    gl.GetFramebufferAttachmentParameteriv(gl::FRAMEBUFFER, attachment, param,
+                                                           &mut value);
+                    value as gl::types::GLenum == gl::FLOAT
+                }
+            } else {
+                false
+            }
+        },
+
+        profile: if version >= &Version(Api::Gl, 3, 2) {
+            let mut value = mem::uninitialized();
+            gl.GetIntegerv(gl::CONTEXT_PROFILE_MASK, &mut value);
+            let value = value as gl::types::GLenum;
+
+            if (value & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT) != 0 {
+                Some(Profile::Compatibility)
+            } else if (value & gl::CONTEXT_CORE_PROFILE_BIT) != 0 {
+                Some(Profile::Core)
+            } else {
+                None
+            }
+
+        } else {
+            None
+        },
+
+        forward_compatible: if version >= &Version(Api::Gl, 3, 0) {
+            let mut value = mem::uninitialized();
+            gl.GetIntegerv(gl::CONTEXT_FLAGS, &mut value);
+            let value = value as gl::types::GLenum;
+            (value & gl::CONTEXT_FLAG_FORWARD_COMPATIBLE_BIT) != 0
+
+        } else {
+            false
+        },
+
+        debug: if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 2) ||
+                  (version >= &Version(Api::Gl, 3, 0) && extensions.gl_khr_debug)
+        {
+            let mut value = mem::uninitialized();
+            gl.GetIntegerv(gl::CONTEXT_FLAGS, &mut value);
+            let value = value as gl::types::GLenum;
+            (value & gl::CONTEXT_FLAG_DEBUG_BIT) != 0
+
+        } else {
+            false
+        },
+
         max_combined_texture_image_units: {
             let mut val = 2;
             gl.GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut val);
 
-            // WORK-AROUND (issue #1181)
-            // Some Radeon drivers crash if you use texture units 32 or more.
-            if renderer.contains("Radeon") {
+            if quirks.limit_texture_units {
                 val = cmp::min(val, 32);
             }
 
@@ -472,6 +646,171 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
                 None
             }
         },
+
+        max_texture_size: {
+            let mut val = 64;
+            gl.GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut val);
+            val
+        },
+
+        max_3d_texture_size: {
+            if version >= &Version(Api::Gl, 1, 2) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_oes_texture_3d
+            {
+                let mut val = 16;
+                gl.GetIntegerv(gl::MAX_3D_TEXTURE_SIZE, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+
+        max_cube_map_texture_size: {
+            if version >= &Version(Api::Gl, 1, 3) || version >= &Version(Api::GlEs, 2, 0) ||
+               extensions.gl_arb_texture_cube_map || extensions.gl_ext_texture_cube_map
+            {
+                let mut val = 16;
+                gl.GetIntegerv(gl::MAX_CUBE_MAP_TEXTURE_SIZE, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+
+        max_array_texture_layers: {
+            if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_ext_texture_array
+            {
+                let mut val = 64;
+                gl.GetIntegerv(gl::MAX_ARRAY_TEXTURE_LAYERS, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+
+        max_renderbuffer_size: {
+            if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 2, 0) ||
+               extensions.gl_arb_framebuffer_object
+            {
+                let mut val = 1;
+                gl.GetIntegerv(gl::MAX_RENDERBUFFER_SIZE, &mut val);
+                val
+            } else if extensions.gl_ext_framebuffer_object {
+                let mut val = 1;
+                gl.GetIntegerv(gl::MAX_RENDERBUFFER_SIZE_EXT, &mut val);
+                val
+            } else {
+                0
+            }
+        },
+
+        max_shader_pixel_local_storage_fast_size: {
+            if extensions.gl_ext_shader_pixel_local_storage ||
+               extensions.gl_ext_shader_pixel_local_storage2
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_SHADER_PIXEL_LOCAL_STORAGE_FAST_SIZE_EXT, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+
+        max_shader_pixel_local_storage_size: {
+            if extensions.gl_ext_shader_pixel_local_storage ||
+               extensions.gl_ext_shader_pixel_local_storage2
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_SHADER_PIXEL_LOCAL_STORAGE_SIZE_EXT, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+
+        max_shader_combined_local_storage_fast_size: {
+            if extensions.gl_ext_shader_pixel_local_storage2 {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_SHADER_COMBINED_LOCAL_STORAGE_FAST_SIZE_EXT, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+
+        max_shader_combined_local_storage_size: {
+            if extensions.gl_ext_shader_pixel_local_storage2 {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_SHADER_COMBINED_LOCAL_STORAGE_SIZE_EXT, &mut val);
+                Some(val)
+
+            } else {
+                None
+            }
+        },
+    }
+}
+
+/// Returns true if the context has been created with robust access.
+unsafe fn is_robust(gl: &gl::Gl, version: &Version, extensions: &ExtensionsList) -> bool {
+    if version >= &Version(Api::Gl, 4, 5) || version >= &Version(Api::GlEs, 3, 2) ||
+       (version >= &Version(Api::Gl, 3, 0) && extensions.gl_arb_robustness)
+    {
+        // TODO: there seems to be no way to query `GL_CONTEXT_FLAGS` before OpenGL 3.0, even
+        //       if `GL_ARB_robustness` is there
+        let mut val = mem::uninitialized();
+        gl.GetIntegerv(gl::CONTEXT_FLAGS, &mut val);
+        let val = val as gl::types::GLenum;
+        (val & gl::CONTEXT_FLAG_ROBUST_ACCESS_BIT) != 0
+
+    } else if extensions.gl_khr_robustness || extensions.gl_ext_robustness {
+        let mut val = mem::uninitialized();
+        gl.GetBooleanv(gl::CONTEXT_ROBUST_ACCESS, &mut val);
+        val != 0
+
+    } else {
+        false
+    }
+}
+
+/// Returns the number of bits of a component of the color buffer of the default framebuffer.
+///
+/// `param` is the value to pass to `glGetFramebufferAttachmentParameteriv`, and `legacy` the
+/// value to pass to `glGetIntegerv` if the former can't be used.
+unsafe fn get_default_framebuffer_color_size(gl: &gl::Gl, version: &Version,
+                                             extensions: &ExtensionsList,
+                                             param: gl::types::GLenum,
+                                             legacy: gl::types::GLenum) -> Option<u16>
+{
+    let mut value = mem::uninitialized();
+
+    // see the comments about `depth_bits`
+    if version >= &Version(Api::Gl, 3, 0) && !extensions.gl_arb_compatibility {
+        let mut ty = mem::uninitialized();
+        gl.GetFramebufferAttachmentParameteriv(gl::FRAMEBUFFER, gl::BACK_LEFT,
+                                               gl::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE, &mut ty);
+
+        if ty as gl::types::GLenum == gl::NONE {
+            value = 0;
+        } else {
+            gl.GetFramebufferAttachmentParameteriv(gl::FRAMEBUFFER, gl::BACK_LEFT, param,
+                                                   &mut value);
+        }
+
+    } else {
+        gl.GetIntegerv(legacy, &mut value);
+    }
+
+    match value {
+        0 => None,
+        v => Some(v as u16),
     }
 }
 
@@ -0,0 +1,278 @@
+//! Reads back the state of the OpenGL context and compares it with the `GlState`.
+//!
+//! This is used to detect code that doesn't go through glium and that modifies the state of the
+//! context without restoring it. Only the states that can be queried without modifying the
+//! context are checked.
+
+use gl;
+use Handle;
+
+use context::CommandContext;
+use debug::StateMismatch;
+use version::Api;
+use version::Version;
+
+use std::fmt::Debug;
+
+/// Compares the state cache with the actual state of the context and returns the list of
+/// differences.
+pub unsafe fn compare(ctxt: &mut CommandContext) -> Vec<StateMismatch> {
+    let mut mismatches = Vec::new();
+
+    compare_program(ctxt, &mut mismatches);
+    compare_buffers(ctxt, &mut mismatches);
+    compare_framebuffers(ctxt, &mut mismatches);
+    compare_capabilities(ctxt, &mut mismatches);
+    compare_blending(ctxt, &mut mismatches);
+    compare_depth_stencil(ctxt, &mut mismatches);
+    compare_viewport_scissor(ctxt, &mut mismatches);
+    compare_misc(ctxt, &mut mismatches);
+
+    mismatches
+}
+
+/// Adds an entry to `mismatches` if the two values are different.
+fn check<T>(mismatches: &mut Vec<StateMismatch>, name: &'static str, cached: T, actual: T)
+            where T: PartialEq + Debug
+{
+    if cached != actual {
+        mismatches.push(StateMismatch {
+            name: name,
+            cached: format!("{:?}", cached),
+            actual: format!("{:?}", actual),
+        });
+    }
+}
+
+unsafe fn get_integer(ctxt: &CommandContext, pname: gl::types::GLenum) -> gl::types::GLint {
+    let mut value = 0;
+    ctxt.gl.GetIntegerv(pname, &mut value);
+    value
+}
+
+unsafe fn get_enum(ctxt: &CommandContext, pname: gl::types::GLenum) -> gl::types::GLenum {
+    get_integer(ctxt, pname) as gl::types::GLenum
+}
+
+unsafe fn get_boolean(ctxt: &CommandContext, pname: gl::types::GLenum) -> bool {
+    let mut value = 0;
+    ctxt.gl.GetBooleanv(pname, &mut value);
+    value != 0
+}
+
+unsafe fn is_enabled(ctxt: &CommandContext, cap: gl::types::GLenum) -> bool {
+    ctxt.gl.IsEnabled(cap) != 0
+}
+
+/// Compares the current program and vertex array object.
+unsafe fn compare_program(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    if let Handle::Id(id) = ctxt.state.program {
+        if ctxt.version >= &Version(Api::Gl, 2, 0) ||
+           ctxt.version >= &Version(Api::GlEs, 2, 0)
+        {
+            check(mismatches, "GL_CURRENT_PROGRAM", id,
+                  get_integer(ctxt, gl::CURRENT_PROGRAM) as gl::types::GLuint);
+        }
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+       ctxt.extensions.gl_arb_vertex_array_object
+    {
+        check(mismatches, "GL_VERTEX_ARRAY_BINDING", ctxt.state.vertex_array,
+              get_integer(ctxt, gl::VERTEX_ARRAY_BINDING) as gl::types::GLuint);
+    }
+}
+
+/// Compares the non-indexed buffer bind points.
+unsafe fn compare_buffers(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    check(mismatches, "GL_ARRAY_BUFFER_BINDING", ctxt.state.array_buffer_binding,
+          get_integer(ctxt, gl::ARRAY_BUFFER_BINDING) as gl::types::GLuint);
+
+    if ctxt.version >= &Version(Api::Gl, 2, 1) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+       ctxt.extensions.gl_arb_pixel_buffer_object
+    {
+        check(mismatches, "GL_PIXEL_PACK_BUFFER_BINDING", ctxt.state.pixel_pack_buffer_binding,
+              get_integer(ctxt, gl::PIXEL_PACK_BUFFER_BINDING) as gl::types::GLuint);
+        check(mismatches, "GL_PIXEL_UNPACK_BUFFER_BINDING",
+              ctxt.state.pixel_unpack_buffer_binding,
+              get_integer(ctxt, gl::PIXEL_UNPACK_BUFFER_BINDING) as gl::types::GLuint);
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 1) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+       ctxt.extensions.gl_arb_uniform_buffer_object
+    {
+        check(mismatches, "GL_UNIFORM_BUFFER_BINDING", ctxt.state.uniform_buffer_binding,
+              get_integer(ctxt, gl::UNIFORM_BUFFER_BINDING) as gl::types::GLuint);
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 1) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+       ctxt.extensions.gl_arb_copy_buffer
+    {
+        check(mismatches, "GL_COPY_READ_BUFFER_BINDING", ctxt.state.copy_read_buffer_binding,
+              get_integer(ctxt, gl::COPY_READ_BUFFER_BINDING) as gl::types::GLuint);
+        check(mismatches, "GL_COPY_WRITE_BUFFER_BINDING", ctxt.state.copy_write_buffer_binding,
+              get_integer(ctxt, gl::COPY_WRITE_BUFFER_BINDING) as gl::types::GLuint);
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 4, 0) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+       ctxt.extensions.gl_arb_multi_draw_indirect
+    {
+        check(mismatches, "GL_DRAW_INDIRECT_BUFFER_BINDING",
+              ctxt.state.draw_indirect_buffer_binding,
+              get_integer(ctxt, gl::DRAW_INDIRECT_BUFFER_BINDING) as gl::types::GLuint);
+    }
+}
+
+/// Compares the read and draw framebuffers.
+unsafe fn compare_framebuffers(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+       ctxt.extensions.gl_arb_framebuffer_object
+    {
+        check(mismatches, "GL_DRAW_FRAMEBUFFER_BINDING", ctxt.state.draw_framebuffer,
+              get_integer(ctxt, gl::DRAW_FRAMEBUFFER_BINDING) as gl::types::GLuint);
+        check(mismatches, "GL_READ_FRAMEBUFFER_BINDING", ctxt.state.read_framebuffer,
+              get_integer(ctxt, gl::READ_FRAMEBUFFER_BINDING) as gl::types::GLuint);
+
+    } else if ctxt.version >= &Version(Api::GlEs, 2, 0) {
+        check(mismatches, "GL_FRAMEBUFFER_BINDING", ctxt.state.draw_framebuffer,
+              get_integer(ctxt, gl::FRAMEBUFFER_BINDING) as gl::types::GLuint);
+    }
+}
+
+/// Compares the capabilities that are enabled with `glEnable`.
+unsafe fn compare_capabilities(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    check(mismatches, "GL_BLEND", ctxt.state.enabled_blend, is_enabled(ctxt, gl::BLEND));
+    check(mismatches, "GL_CULL_FACE", ctxt.state.enabled_cull_face,
+          is_enabled(ctxt, gl::CULL_FACE));
+    check(mismatches, "GL_DEPTH_TEST", ctxt.state.enabled_depth_test,
+          is_enabled(ctxt, gl::DEPTH_TEST));
+    check(mismatches, "GL_DITHER", ctxt.state.enabled_dither, is_enabled(ctxt, gl::DITHER));
+    check(mismatches, "GL_POLYGON_OFFSET_FILL", ctxt.state.enabled_polygon_offset_fill,
+          is_enabled(ctxt, gl::POLYGON_OFFSET_FILL));
+    check(mismatches, "GL_SAMPLE_ALPHA_TO_COVERAGE", ctxt.state.enabled_sample_alpha_to_coverage,
+          is_enabled(ctxt, gl::SAMPLE_ALPHA_TO_COVERAGE));
+    check(mismatches, "GL_SAMPLE_COVERAGE", ctxt.state.enabled_sample_coverage,
+          is_enabled(ctxt, gl::SAMPLE_COVERAGE));
+    check(mismatches, "GL_SCISSOR_TEST", ctxt.state.enabled_scissor_test,
+          is_enabled(ctxt, gl::SCISSOR_TEST));
+    check(mismatches, "GL_STENCIL_TEST", ctxt.state.enabled_stencil_test,
+          is_enabled(ctxt, gl::STENCIL_TEST));
+
+    if ctxt.version >= &Version(Api::Gl, 1, 0) {
+        check(mismatches, "GL_MULTISAMPLE", ctxt.state.enabled_multisample,
+              is_enabled(ctxt, gl::MULTISAMPLE));
+        check(mismatches, "GL_POLYGON_OFFSET_LINE", ctxt.state.enabled_polygon_offset_line,
+              is_enabled(ctxt, gl::POLYGON_OFFSET_LINE));
+        check(mismatches, "GL_POLYGON_OFFSET_POINT", ctxt.state.enabled_polygon_offset_point,
+              is_enabled(ctxt, gl::POLYGON_OFFSET_POINT));
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_srgb {
+        check(mismatches, "GL_FRAMEBUFFER_SRGB", ctxt.state.enabled_framebuffer_srgb,
+              is_enabled(ctxt, gl::FRAMEBUFFER_SRGB));
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        check(mismatches, "GL_RASTERIZER_DISCARD", ctxt.state.enabled_rasterizer_discard,
+              is_enabled(ctxt, gl::RASTERIZER_DISCARD));
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 2) {
+        check(mismatches, "GL_PROGRAM_POINT_SIZE", ctxt.state.enabled_program_point_size,
+              is_enabled(ctxt, gl::PROGRAM_POINT_SIZE));
+    }
+
+    if ctxt.extensions.gl_ext_shader_pixel_local_storage ||
+       ctxt.extensions.gl_ext_shader_pixel_local_storage2
+    {
+        check(mismatches, "GL_SHADER_PIXEL_LOCAL_STORAGE_EXT",
+              ctxt.state.enabled_shader_pixel_local_storage,
+              is_enabled(ctxt, gl::SHADER_PIXEL_LOCAL_STORAGE_EXT));
+    }
+}
+
+/// Compares the blending equations and functions.
+///
+/// The blending color isn't compared, as OpenGL may have clamped it.
+unsafe fn compare_blending(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    check(mismatches, "GL_BLEND_EQUATION_RGB", ctxt.state.blend_equation.0,
+          get_enum(ctxt, gl::BLEND_EQUATION_RGB));
+    check(mismatches, "GL_BLEND_EQUATION_ALPHA", ctxt.state.blend_equation.1,
+          get_enum(ctxt, gl::BLEND_EQUATION_ALPHA));
+    check(mismatches, "GL_BLEND_SRC_RGB", ctxt.state.blend_func.0,
+          get_enum(ctxt, gl::BLEND_SRC_RGB));
+    check(mismatches, "GL_BLEND_DST_RGB", ctxt.state.blend_func.1,
+          get_enum(ctxt, gl::BLEND_DST_RGB));
+    check(mismatches, "GL_BLEND_SRC_ALPHA", ctxt.state.blend_func.2,
+          get_enum(ctxt, gl::BLEND_SRC_ALPHA));
+    check(mismatches, "GL_BLEND_DST_ALPHA", ctxt.state.blend_func.3,
+          get_enum(ctxt, gl::BLEND_DST_ALPHA));
+}
+
+/// Compares the depth function and mask, and the stencil functions and operations.
+///
+/// The stencil masks aren't compared, as some implementations clamp them when they are
+/// returned as signed integers.
+unsafe fn compare_depth_stencil(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    check(mismatches, "GL_DEPTH_FUNC", ctxt.state.depth_func, get_enum(ctxt, gl::DEPTH_FUNC));
+    check(mismatches, "GL_DEPTH_WRITEMASK", ctxt.state.depth_mask,
+          get_boolean(ctxt, gl::DEPTH_WRITEMASK));
+
+    let (func, reference, _) = ctxt.state.stencil_func_front;
+    check(mismatches, "GL_STENCIL_FUNC", func, get_enum(ctxt, gl::STENCIL_FUNC));
+    check(mismatches, "GL_STENCIL_REF", reference, get_integer(ctxt, gl::STENCIL_REF));
+
+    let (func, reference, _) = ctxt.state.stencil_func_back;
+    check(mismatches, "GL_STENCIL_BACK_FUNC", func, get_enum(ctxt, gl::STENCIL_BACK_FUNC));
+    check(mismatches, "GL_STENCIL_BACK_REF", reference, get_integer(ctxt, gl::STENCIL_BACK_REF));
+
+    let (sfail, dpfail, dppass) = ctxt.state.stencil_op_front;
+    check(mismatches, "GL_STENCIL_FAIL", sfail, get_enum(ctxt, gl::STENCIL_FAIL));
+    check(mismatches, "GL_STENCIL_PASS_DEPTH_FAIL", dpfail,
+          get_enum(ctxt, gl::STENCIL_PASS_DEPTH_FAIL));
+    check(mismatches, "GL_STENCIL_PASS_DEPTH_PASS", dppass,
+          get_enum(ctxt, gl::STENCIL_PASS_DEPTH_PASS));
+
+    let (sfail, dpfail, dppass) = ctxt.state.stencil_op_back;
+    check(mismatches, "GL_STENCIL_BACK_FAIL", sfail, get_enum(ctxt, gl::STENCIL_BACK_FAIL));
+    check(mismatches, "GL_STENCIL_BACK_PASS_DEPTH_FAIL", dpfail,
+          get_enum(ctxt, gl::STENCIL_BACK_PASS_DEPTH_FAIL));
+    check(mismatches, "GL_STENCIL_BACK_PASS_DEPTH_PASS", dppass,
+          get_enum(ctxt, gl::STENCIL_BACK_PASS_DEPTH_PASS));
+}
+
+/// Compares the viewport and the scissor box, if they are known.
+unsafe fn compare_viewport_scissor(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    if let Some((x, y, width, height)) = ctxt.state.viewport {
+        let mut actual = [0; 4];
+        ctxt.gl.GetIntegerv(gl::VIEWPORT, actual.as_mut_ptr());
+        check(mismatches, "GL_VIEWPORT", [x, y, width, height], actual);
+    }
+
+    if let Some((x, y, width, height)) = ctxt.state.scissor {
+        let mut actual = [0; 4];
+        ctxt.gl.GetIntegerv(gl::SCISSOR_BOX, actual.as_mut_ptr());
+        check(mismatches, "GL_SCISSOR_BOX", [x, y, width, height], actual);
+    }
+}
+
+/// Compares the color mask, the culled face, the pixel store alignments and the active
+/// texture unit.
+unsafe fn compare_misc(ctxt: &CommandContext, mismatches: &mut Vec<StateMismatch>) {
+    let mut actual = [0; 4];
+    ctxt.gl.GetBooleanv(gl::COLOR_WRITEMASK, actual.as_mut_ptr());
+    let (r, g, b, a) = ctxt.state.color_mask;
+    check(mismatches, "GL_COLOR_WRITEMASK", [r != 0, g != 0, b != 0, a != 0],
+          [actual[0] != 0, actual[1] != 0, actual[2] != 0, actual[3] != 0]);
+
+    check(mismatches, "GL_CULL_FACE_MODE", ctxt.state.cull_face,
+          get_enum(ctxt, gl::CULL_FACE_MODE));
+
+    check(mismatches, "GL_UNPACK_ALIGNMENT", ctxt.state.pixel_store_unpack_alignment,
+          get_integer(ctxt, gl::UNPACK_ALIGNMENT));
+    check(mismatches, "GL_PACK_ALIGNMENT", ctxt.state.pixel_store_pack_alignment,
+          get_integer(ctxt, gl::PACK_ALIGNMENT));
+
+    check(mismatches, "GL_ACTIVE_TEXTURE", ctxt.state.active_texture,
+          get_enum(ctxt, gl::ACTIVE_TEXTURE).wrapping_sub(gl::TEXTURE0));
+}
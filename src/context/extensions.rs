@@ -44,6 +44,29 @@ macro_rules! extensions {
 
             extensions
         }
+
+        /// Returns true if `name` corresponds to an extension known by glium.
+        pub fn is_known_extension(name: &str) -> bool {
+            match name {
+                $(
+                    $string => true,
+                )+
+                _ => false
+            }
+        }
+
+        impl ExtensionsList {
+            /// Marks an extension as unsupported. Returns `false` if the name doesn't correspond
+            /// to any extension known by glium.
+            pub fn disable(&mut self, name: &str) -> bool {
+                match name {
+                    $(
+                        $string => { self.$field = false; true },
+                    )+
+                    _ => false
+                }
+            }
+        }
     }
 }
 
@@ -75,7 +98,9 @@ extensions! {
     "GL_ARB_framebuffer_sRGB" => gl_arb_framebuffer_srgb,
     "GL_ARB_geometry_shader4" => gl_arb_geometry_shader4,
     "GL_ARB_get_program_binary" => gl_arb_get_programy_binary,
+    "GL_ARB_get_texture_sub_image" => gl_arb_get_texture_sub_image,
     "GL_ARB_gpu_shader_fp64" => gl_arb_gpu_shader_fp64,
+    "GL_ARB_half_float_pixel" => gl_arb_half_float_pixel,
     "GL_ARB_instanced_arrays" => gl_arb_instanced_arrays,
     "GL_ARB_invalidate_subdata" => gl_arb_invalidate_subdata,
     "GL_ARB_occlusion_query" => gl_arb_occlusion_query,
@@ -84,6 +109,7 @@ extensions! {
     "GL_ARB_program_interface_query" => gl_arb_program_interface_query,
     "GL_ARB_query_buffer_object" => gl_arb_query_buffer_object,
     "GL_ARB_map_buffer_range" => gl_arb_map_buffer_range,
+    "GL_ARB_multi_bind" => gl_arb_multi_bind,
     "GL_ARB_multi_draw_indirect" => gl_arb_multi_draw_indirect,
     "GL_ARB_provoking_vertex" => gl_arb_provoking_vertex,
     "GL_ARB_robustness" => gl_arb_robustness,
@@ -99,6 +125,7 @@ extensions! {
     "GL_ARB_texture_compression_bptc" => gl_arb_texture_compression_bptc,
     "GL_ARB_texture_cube_map" => gl_arb_texture_cube_map,
     "GL_ARB_texture_cube_map_array" => gl_arb_texture_cube_map_array,
+    "GL_ARB_texture_filter_minmax" => gl_arb_texture_filter_minmax,
     "GL_ARB_texture_float" => gl_arb_texture_float,
     "GL_ARB_texture_multisample" => gl_arb_texture_multisample,
     "GL_ARB_texture_non_power_of_two" => gl_arb_texture_non_power_of_two,
@@ -109,13 +136,16 @@ extensions! {
     "GL_ARB_timer_query" => gl_arb_timer_query,
     "GL_ARB_transform_feedback3" => gl_arb_transform_feedback3,
     "GL_ARB_uniform_buffer_object" => gl_arb_uniform_buffer_object,
+    "GL_ARB_vertex_array_bgra" => gl_arb_vertex_array_bgra,
     "GL_ARB_vertex_array_object" => gl_arb_vertex_array_object,
+    "GL_ARB_vertex_attrib_binding" => gl_arb_vertex_attrib_binding,
     "GL_ARB_vertex_buffer_object" => gl_arb_vertex_buffer_object,
     "GL_ARB_vertex_half_float" => gl_arb_vertex_half_float,
     "GL_ARB_vertex_shader" => gl_arb_vertex_shader,
     "GL_ARB_vertex_type_10f_11f_11f_rev" => gl_arb_vertex_type_10f_11f_11f_rev,
     "GL_ARB_vertex_type_2_10_10_10_rev" => gl_arb_vertex_type_2_10_10_10_rev,
     "GL_ARM_rgba8" => gl_arm_rgba8,
+    "GL_ARM_shader_framebuffer_fetch" => gl_arm_shader_framebuffer_fetch,
     "GL_ATI_meminfo" => gl_ati_meminfo,
     "GL_ATI_draw_buffers" => gl_ati_draw_buffers,
     "GL_ATI_texture_float" => gl_ati_texture_float,
@@ -140,19 +170,26 @@ extensions! {
     "GL_EXT_provoking_vertex" => gl_ext_provoking_vertex,
     "GL_EXT_robustness" => gl_ext_robustness,
     "GL_EXT_sRGB_write_control" => gl_ext_srgb_write_control,
+    "GL_EXT_shader_framebuffer_fetch" => gl_ext_shader_framebuffer_fetch,
+    "GL_EXT_shader_framebuffer_fetch_non_coherent" => gl_ext_shader_framebuffer_fetch_non_coherent,
+    "GL_EXT_shader_pixel_local_storage" => gl_ext_shader_pixel_local_storage,
+    "GL_EXT_shader_pixel_local_storage2" => gl_ext_shader_pixel_local_storage2,
     "GL_EXT_texture3D" => gl_ext_texture3d,
     "GL_EXT_texture_array" => gl_ext_texture_array,
+    "GL_EXT_texture_border_clamp" => gl_ext_texture_border_clamp,
     "GL_EXT_texture_buffer" => gl_ext_texture_buffer,
     "GL_EXT_texture_buffer_object" => gl_ext_texture_buffer_object,
     "GL_EXT_texture_compression_s3tc" => gl_ext_texture_compression_s3tc,
     "GL_EXT_texture_cube_map" => gl_ext_texture_cube_map,
     "GL_EXT_texture_cube_map_array" => gl_ext_texture_cube_map_array,
     "GL_EXT_texture_filter_anisotropic" => gl_ext_texture_filter_anisotropic,
+    "GL_EXT_texture_filter_minmax" => gl_ext_texture_filter_minmax,
     "GL_EXT_texture_integer" => gl_ext_texture_integer,
     "GL_EXT_texture_shared_exponent" => gl_ext_texture_shared_exponent,
     "GL_EXT_texture_snorm" => gl_ext_texture_snorm,
     "GL_EXT_texture_sRGB" => gl_ext_texture_srgb,
     "GL_EXT_transform_feedback" => gl_ext_transform_feedback,
+    "GL_EXT_vertex_array_bgra" => gl_ext_vertex_array_bgra,
     "GL_GREMEDY_string_marker" => gl_gremedy_string_marker,
     "GL_KHR_debug" => gl_khr_debug,
     "GL_KHR_context_flush_control" => gl_khr_context_flush_control,
@@ -165,11 +202,14 @@ extensions! {
     "GL_NV_framebuffer_multisample" => gl_nv_framebuffer_multisample,
     "GL_NV_half_float" => gl_nv_half_float,
     "GL_NV_pixel_buffer_object" => gl_nv_pixel_buffer_object,
+    "GL_NV_shader_framebuffer_fetch" => gl_nv_shader_framebuffer_fetch,
     "GL_NV_texture_array" => gl_nv_texture_array,
+    "GL_NV_vdpau_interop" => gl_nv_vdpau_interop,
     "GL_NV_vertex_attrib_integer_64bit" => gl_nv_vertex_attrib_integer_64bit,
     "GL_NVX_gpu_memory_info" => gl_nvx_gpu_memory_info,
     "GL_OES_depth_texture" => gl_oes_depth_texture,
     "GL_OES_draw_elements_base_vertex" => gl_oes_draw_elements_base_vertex,
+    "GL_OES_EGL_image" => gl_oes_egl_image,
     "GL_OES_element_index_uint" => gl_oes_element_index_uint,
     "GL_OES_fixed_point" => gl_oes_fixed_point,
     "GL_OES_geometry_shader" => gl_oes_geometry_shader,
@@ -180,6 +220,7 @@ extensions! {
     "GL_OES_stencil4" => gl_oes_stencil4,
     "GL_OES_tessellation_shader" => gl_oes_tessellation_shader,
     "GL_OES_texture_3D" => gl_oes_texture_3d,
+    "GL_OES_texture_border_clamp" => gl_oes_texture_border_clamp,
     "GL_OES_texture_buffer" => gl_oes_texture_buffer,
     "GL_OES_texture_cube_map_array" => gl_oes_texture_cube_map_array,
     "GL_OES_texture_stencil8" => gl_oes_texture_stencil8,
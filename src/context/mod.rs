@@ -4,11 +4,12 @@ use gl;
 use libc;
 use backtrace;
 
+use std::any::Any;
 use std::env;
 use std::mem;
 use std::ptr;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::cell::{Cell, RefCell, RefMut};
 use std::marker::PhantomData;
 use std::ffi::CStr;
@@ -16,26 +17,39 @@ use std::rc::Rc;
 
 use GliumCreationError;
 use SwapBuffersError;
+use SwapInterval;
+use Rect;
 use CapabilitiesSource;
 use ContextExt;
 use backend::Backend;
+use backend::SurfaceBackend;
+use debug;
+use draw_parameters;
 use version;
 use version::Api;
 use version::Version;
 
 use fbo;
 use ops;
+use ring_buffer;
 use sampler_object;
+use sync;
 use texture;
 use uniforms;
 use vertex_array_object;
 
-pub use self::capabilities::{ReleaseBehavior, Capabilities};
+pub use self::capabilities::{ReleaseBehavior, Capabilities, Profile};
+pub use self::capabilities::DefaultFramebufferProperties;
 pub use self::extensions::ExtensionsList;
+pub use self::quirks::Quirks;
+pub use self::restore::StateCategories;
 pub use self::state::GlState;
 
 mod capabilities;
+mod check;
 mod extensions;
+mod quirks;
+mod restore;
 mod state;
 
 /// Stores the state and information required for glium to execute commands. Most public glium
@@ -82,6 +96,9 @@ pub struct Context {
     /// We maintain a list of samplers for each possible behavior.
     samplers: RefCell<HashMap<uniforms::SamplerBehavior, sampler_object::SamplerObject>>,
 
+    /// Buffer used to upload indices that are stored in RAM. Created on first use.
+    ring_buffer: RefCell<Option<ring_buffer::RingBuffer>>,
+
     /// List of texture handles that are resident. We need to call `MakeTextureHandleResidentARB`
     /// when rebuilding the context.
     resident_texture_handles: RefCell<Vec<gl::types::GLuint64>>,
@@ -89,8 +106,61 @@ pub struct Context {
     /// List of images handles that are resident. We need to call `MakeImageHandleResidentARB`
     /// when rebuilding the context.
     resident_image_handles: RefCell<Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
+
+    /// Functions to call when the context loss is detected.
+    context_lost_callbacks: RefCell<Vec<Box<FnMut()>>>,
+
+    /// Callback registered by the user with `set_debug_callback`.
+    debug_callback: RefCell<Option<debug::DebugCallback>>,
+
+    /// True if `callback_wrapper` has been registered as the debug callback of the backend.
+    debug_callback_installed: Cell<bool>,
+
+    /// When to call `glGetError`.
+    error_check_policy: Cell<debug::ErrorCheckPolicy>,
+
+    /// When to compare the state cache with the actual state.
+    state_check_policy: Cell<debug::StateCheckPolicy>,
+
+    /// Counters of the current frame, or `None` if statistics are disabled.
+    statistics: Cell<Option<debug::FrameStatistics>>,
+
+    /// Surface that the default framebuffer currently refers to, if it is not the one of
+    /// the backend.
+    current_surface: RefCell<Option<Rc<SurfaceBackend>>>,
+
+    /// Features that draw calls are allowed to emulate.
+    emulation_options: Cell<draw_parameters::EmulationOptions>,
+
+    /// Maximum number of frames that the GPU can be late on, or `None` if there's no limit.
+    frame_latency_limit: Cell<Option<u32>>,
+
+    /// Fences inserted at the end of the frames that may still be in the commands queue,
+    /// from the oldest to the most recent.
+    frame_fences: RefCell<VecDeque<sync::LinearSyncFence>>,
+
+    /// If true, the destruction of objects is delayed until the GPU has finished the frame
+    /// during which they have been dropped.
+    deferred_destruction: Cell<bool>,
+
+    /// Objects that have been dropped during the current frame.
+    pending_destructions: RefCell<Vec<DeferredObject>>,
+
+    /// Objects that have been dropped during the previous frames, with the fence inserted at
+    /// the end of these frames, from the oldest to the most recent.
+    deferred_destructions: RefCell<VecDeque<(sync::LinearSyncFence, Vec<DeferredObject>)>>,
+
+    /// Objects that must be kept alive until their function returns true. Checked at the end
+    /// of each frame.
+    kept_alive: RefCell<Vec<(Box<Any>, Box<Fn() -> bool>)>>,
+
+    /// Workarounds for the bugs of the driver.
+    quirks: Cell<Quirks>,
 }
 
+/// An object whose destruction has been delayed, and the function that destroys it.
+pub type DeferredObject = (gl::types::GLuint, unsafe fn(&mut CommandContext, gl::types::GLuint));
+
 /// This struct is a guard that is returned when you want to access the OpenGL backend.
 pub struct CommandContext<'a> {
     /// Source of OpenGL function pointers.
@@ -109,6 +179,9 @@ pub struct CommandContext<'a> {
     /// Capabilities of the backend.
     pub capabilities: &'a Capabilities,
 
+    /// Workarounds enabled for the backend.
+    pub quirks: Quirks,
+
     /// Whether or not errors triggered by ARB_debug_output (and similar extensions) should be
     /// reported to the user (by panicking).
     pub report_debug_output_errors: &'a Cell<bool>,
@@ -122,12 +195,29 @@ pub struct CommandContext<'a> {
     /// The list of samplers.
     pub samplers: RefMut<'a, HashMap<uniforms::SamplerBehavior, sampler_object::SamplerObject>>,
 
+    /// The buffer used to upload indices that are stored in RAM.
+    pub ring_buffer: RefMut<'a, Option<ring_buffer::RingBuffer>>,
+
     /// List of texture handles that need to be made resident.
     pub resident_texture_handles: RefMut<'a, Vec<gl::types::GLuint64>>,
 
     /// List of image handles and their access that need to be made resident.
     pub resident_image_handles: RefMut<'a, Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
 
+    /// When to call `glGetError`.
+    pub error_check_policy: debug::ErrorCheckPolicy,
+
+    /// Callback registered by the user with `set_debug_callback`, which receives the OpenGL
+    /// errors detected by the error check policy.
+    pub debug_callback: &'a RefCell<Option<debug::DebugCallback>>,
+
+    /// Counters of the current frame, or `None` if statistics are disabled.
+    pub statistics: &'a Cell<Option<debug::FrameStatistics>>,
+
+    /// Objects dropped during the current frame, or `None` if deferred destruction is
+    /// disabled.
+    pub pending_destructions: Option<RefMut<'a, Vec<DeferredObject>>>,
+
     /// This marker is here to prevent `CommandContext` from implementing `Send`
     // TODO: use this when possible
     //impl<'a, 'b> !Send for CommandContext<'a, 'b> {}
@@ -147,24 +237,75 @@ impl Context {
     /// The OpenGL context must be newly-created. If you make modifications to the context before
     /// passing it to this function, glium's state cache may mismatch the actual one.
     ///
+    /// The extensions listed in the `GLIUM_DISABLE_EXTENSIONS` environment variable, separated
+    /// with commas, are considered as unsupported. See `new_with_disabled_extensions`. Unlike
+    /// with `new_with_disabled_extensions`, the names that don't correspond to any extension
+    /// known by glium are ignored after sending a warning to the debug callback.
+    ///
     pub unsafe fn new<B, E>(backend: B, check_current_context: bool)
                             -> Result<Rc<Context>, GliumCreationError<E>>
                             where B: Backend + 'static
+    {
+        let disabled = env::var("GLIUM_DISABLE_EXTENSIONS").unwrap_or(String::new());
+        let (disabled, unknown): (Vec<_>, Vec<_>) =
+            disabled.split(',').map(|e| e.trim()).filter(|e| !e.is_empty())
+                    .partition(|e| extensions::is_known_extension(e));
+
+        let context = try!(Context::new_with_disabled_extensions(backend, check_current_context,
+                                                                 &disabled));
+
+        for extension in unknown {
+            context.report_message(debug::MessageType::Other, debug::Severity::Medium,
+                                   &format!("unknown extension `{}` in GLIUM_DISABLE_EXTENSIONS",
+                                            extension));
+        }
+
+        Ok(context)
+    }
+
+    /// Builds a new context, pretending that the extensions of `disabled_extensions` are not
+    /// supported by the backend.
+    ///
+    /// This is useful to test the fallback paths of glium and of your application. For example
+    /// passing `&["GL_ARB_direct_state_access"]` makes glium use the functions that bind
+    /// objects instead.
+    ///
+    /// Note that this has no effect on a functionality that is part of the core version of
+    /// the backend. Returns `UnknownExtension` if a name doesn't correspond to any extension
+    /// known by glium, for example because it is misspelled.
+    ///
+    /// See `new` for the other parameters.
+    pub unsafe fn new_with_disabled_extensions<B, E>(backend: B, check_current_context: bool,
+                                                     disabled_extensions: &[&str])
+                                                     -> Result<Rc<Context>, GliumCreationError<E>>
+                                                     where B: Backend + 'static
     {
         backend.make_current();
 
         let gl = gl::Gl::load_with(|symbol| backend.get_proc_address(symbol));
         let gl_state: RefCell<GlState> = RefCell::new(Default::default());
         let version = version::get_gl_version(&gl);
-        let extensions = extensions::get_extensions(&gl, &version);
-        let capabilities = capabilities::get_capabilities(&gl, &version, &extensions);
+        let quirks = quirks::get_quirks(&gl);
+        let mut extensions = extensions::get_extensions(&gl, &version);
+        for extension in disabled_extensions {
+            if !extensions.disable(extension) {
+                return Err(GliumCreationError::UnknownExtension(extension.to_string()));
+            }
+        }
+        if quirks.avoid_direct_state_access_extensions {
+            extensions.disable("GL_ARB_direct_state_access");
+            extensions.disable("GL_EXT_direct_state_access");
+        }
+        let capabilities = capabilities::get_capabilities(&gl, &version, &extensions, &quirks);
         let report_debug_output_errors = Cell::new(true);
 
         let vertex_array_objects = vertex_array_object::VertexAttributesSystem::new();
         let framebuffer_objects = fbo::FramebuffersContainer::new();
         let samplers = RefCell::new(HashMap::with_capacity(16));
+        let ring_buffer = RefCell::new(None);
         let resident_texture_handles = RefCell::new(Vec::new());
         let resident_image_handles = RefCell::new(Vec::new());
+        let debug_callback = RefCell::new(None);
 
         // checking whether the backend supports glium
         // TODO: do this more properly
@@ -175,12 +316,18 @@ impl Context {
                 version: &version,
                 extensions: &extensions,
                 capabilities: &capabilities,
+                quirks: quirks,
                 report_debug_output_errors: &report_debug_output_errors,
                 vertex_array_objects: &vertex_array_objects,
                 framebuffer_objects: &framebuffer_objects,
                 samplers: samplers.borrow_mut(),
+                ring_buffer: ring_buffer.borrow_mut(),
                 resident_texture_handles: resident_texture_handles.borrow_mut(),
                 resident_image_handles: resident_image_handles.borrow_mut(),
+                error_check_policy: debug::ErrorCheckPolicy::Never,
+                debug_callback: &debug_callback,
+                statistics: &Cell::new(None),
+                pending_destructions: None,
                 marker: PhantomData,
             };
 
@@ -199,11 +346,29 @@ impl Context {
             framebuffer_objects: Some(framebuffer_objects),
             vertex_array_objects: vertex_array_objects,
             samplers: samplers,
+            ring_buffer: ring_buffer,
             resident_texture_handles: resident_texture_handles,
             resident_image_handles: resident_image_handles,
+            context_lost_callbacks: RefCell::new(Vec::new()),
+            debug_callback: debug_callback,
+            debug_callback_installed: Cell::new(false),
+            error_check_policy: Cell::new(debug::ErrorCheckPolicy::Never),
+            state_check_policy: Cell::new(debug::StateCheckPolicy::Never),
+            statistics: Cell::new(None),
+            current_surface: RefCell::new(None),
+            emulation_options: Cell::new(Default::default()),
+            frame_latency_limit: Cell::new(None),
+            frame_fences: RefCell::new(VecDeque::new()),
+            deferred_destruction: Cell::new(false),
+            pending_destructions: RefCell::new(Vec::new()),
+            deferred_destructions: RefCell::new(VecDeque::new()),
+            kept_alive: RefCell::new(Vec::new()),
+            quirks: Cell::new(quirks),
         });
 
         init_debug_callback(&context);
+        context.set_error_check_policy(error_check_policy_from_env(&context));
+        context.state_check_policy.set(state_check_policy_from_env(&context));
 
         // making sure that an error wasn't triggered during initialization
         {
@@ -263,28 +428,251 @@ impl Context {
     }
 
     /// Swaps the buffers in the backend.
+    ///
+    /// If the context is robust and can be lost, the reset status is checked before swapping.
+    /// Otherwise it is only checked if swapping fails.
+    #[inline]
     pub fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
-        let mut state = self.state.borrow_mut();
-        if state.lost_context {
+        self.swap_buffers_impl(None, None)
+    }
+
+    /// Swaps the buffers in the backend, and tells the window system that only the content
+    /// of the given rectangles has changed since the previous frame.
+    ///
+    /// The compositor can use this information to avoid recomposing the whole window. If the
+    /// backend doesn't support this, the buffers are swapped normally.
+    #[inline]
+    pub fn swap_buffers_with_damage(&self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        self.swap_buffers_impl(None, Some(damage))
+    }
+
+    /// Swaps the buffers of `surface`, or of the backend if it is `None`.
+    fn swap_buffers_impl(&self, surface: Option<&SurfaceBackend>, damage: Option<&[Rect]>)
+                         -> Result<(), SwapBuffersError>
+    {
+        if self.is_reset_status_polled() && self.is_context_lost() {
             return Err(SwapBuffersError::ContextLost);
         }
 
-        let backend = self.backend.borrow();
+        if self.state.borrow().lost_context {
+            return Err(SwapBuffersError::ContextLost);
+        }
 
-        if self.check_current_context {
-            if !backend.is_current() {
-                unsafe { backend.make_current() };
+        trace_operation!("swap_buffers");
+        self.check_state_cache_before_swap();
+
+        // marking the end of the frame, so that we can later wait for the GPU to reach it
+        if self.frame_latency_limit.get().is_some() {
+            let mut ctxt = self.make_current();
+            if let Ok(fence) = unsafe { sync::new_linear_sync_fence(&mut ctxt) } {
+                self.frame_fences.borrow_mut().push_back(fence);
             }
         }
 
+        self.release_kept_alive();
+        self.process_deferred_destructions();
+
         // swapping
-        let err = backend.swap_buffers();
-        if let Err(SwapBuffersError::ContextLost) = err {
-            state.lost_context = true;
+        let err = if let Some(surface) = surface {
+            if !surface.is_current() {
+                unsafe { surface.make_current() };
+            }
+
+            match damage {
+                Some(damage) => surface.swap_buffers_with_damage(damage),
+                None => surface.swap_buffers(),
+            }
+
+        } else {
+            let backend = self.backend.borrow();
+
+            if self.check_current_context {
+                if !backend.is_current() {
+                    unsafe { backend.make_current() };
+                }
+            }
+
+            match damage {
+                Some(damage) => backend.swap_buffers_with_damage(damage),
+                None => backend.swap_buffers(),
+            }
+        };
+
+        match err {
+            Err(SwapBuffersError::ContextLost) => {
+                self.state.borrow_mut().lost_context = true;
+                self.call_context_lost_callbacks();
+            },
+            // a failed swap can be caused by a reset that the backend doesn't report as such
+            Err(_) if self.capabilities.can_lose_context => {
+                self.is_context_lost();
+            },
+            _ => ()
         }
+
+        self.limit_frame_latency();
+
         err
     }
 
+    /// Returns the maximum number of frames that the GPU can be late on the CPU, or `None` if
+    /// there is no limit.
+    #[inline]
+    pub fn get_frame_latency_limit(&self) -> Option<u32> {
+        self.frame_latency_limit.get()
+    }
+
+    /// Limits the number of frames that the GPU can be late on the CPU.
+    ///
+    /// OpenGL drivers usually allow the application to submit several frames in advance, which
+    /// increases the delay between the moment when you handle inputs and the moment when the
+    /// result is displayed. With a limit of `n`, swapping the buffers at the end of the frame
+    /// `N - 1` blocks until the GPU has finished the frame `N - n`, before you can start the
+    /// frame `N`.
+    ///
+    /// A limit of `Some(1)` means that the GPU has always finished everything when a frame
+    /// starts, and `Some(2)` allows the GPU to work on the previous frame while the CPU
+    /// prepares the next one. `None`, the default, lets the driver decide.
+    ///
+    /// Has no effect if the backend doesn't support fences.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the limit is `Some(0)`.
+    pub fn set_frame_latency_limit(&self, limit: Option<u32>) {
+        assert!(limit != Some(0), "The frame latency limit must be at least 1");
+        self.frame_latency_limit.set(limit);
+        self.limit_frame_latency();
+    }
+
+    /// Waits for the oldest frame fences until the limit is respected.
+    fn limit_frame_latency(&self) {
+        let mut fences = self.frame_fences.borrow_mut();
+        if fences.is_empty() {
+            return;
+        }
+
+        let limit = self.frame_latency_limit.get().unwrap_or(0) as usize;
+
+        let mut ctxt = self.make_current();
+        while fences.len() >= limit && !fences.is_empty() {
+            let fence = fences.pop_front().unwrap();
+
+            // if the limit has been removed or the context is lost, we don't wait
+            if limit == 0 || ctxt.state.lost_context {
+                unsafe { sync::destroy_linear_sync_fence(&mut ctxt, fence) };
+            } else {
+                unsafe { sync::wait_linear_sync_fence_and_drop(fence, &mut ctxt) };
+            }
+        }
+    }
+
+    /// Returns true if the destruction of buffers, textures and render buffers is delayed until
+    /// the GPU has finished using them.
+    #[inline]
+    pub fn is_deferred_destruction_enabled(&self) -> bool {
+        self.deferred_destruction.get()
+    }
+
+    /// Enables or disables the deferred destruction of buffers, textures and render buffers.
+    ///
+    /// Dropping one of these objects normally deletes the OpenGL object immediately, and some
+    /// drivers block until the GPU has finished the commands that use it. When deferred
+    /// destruction is enabled, the objects are queued instead. Swapping the buffers inserts a
+    /// fence at the end of the frame, and the objects are deleted during a later swap, once the
+    /// GPU has reached this fence.
+    ///
+    /// If the backend doesn't support fences, the objects are deleted when swapping the
+    /// buffers. The objects that are already queued are still deleted after deferred destruction
+    /// has been disabled.
+    ///
+    /// Deferred destruction is disabled by default.
+    #[inline]
+    pub fn set_deferred_destruction_enabled(&self, enabled: bool) {
+        self.deferred_destruction.set(enabled);
+    }
+
+    /// Returns the number of objects whose destruction has been delayed and that haven't been
+    /// deleted yet.
+    pub fn get_deferred_destructions_count(&self) -> usize {
+        let deferred = self.deferred_destructions.borrow();
+        let deferred = deferred.iter().map(|&(_, ref objects)| objects.len()).fold(0, |a, b| a + b);
+        self.pending_destructions.borrow().len() + deferred
+    }
+
+    /// Drops the objects given to `keep_alive_until` whose function returns true.
+    fn release_kept_alive(&self) {
+        let released = {
+            let mut kept_alive = self.kept_alive.borrow_mut();
+            let objects = mem::replace(&mut *kept_alive, Vec::new());
+            let (released, kept): (Vec<_>, Vec<_>) = objects.into_iter()
+                                                            .partition(|&(_, ref done)| done());
+            *kept_alive = kept;
+            released
+        };
+
+        // the objects can access the context when they are dropped
+        drop(released);
+    }
+
+    /// Inserts a fence after the objects that have been dropped during the current frame, and
+    /// deletes the objects of the previous frames whose fence has been signaled.
+    fn process_deferred_destructions(&self) {
+        let mut deferred = self.deferred_destructions.borrow_mut();
+        let mut ctxt = self.make_current();
+
+        let pending = match ctxt.pending_destructions {
+            Some(ref mut pending) => mem::replace(&mut **pending, Vec::new()),
+            None => mem::replace(&mut *self.pending_destructions.borrow_mut(), Vec::new()),
+        };
+
+        if !pending.is_empty() {
+            match unsafe { sync::new_linear_sync_fence(&mut ctxt) } {
+                Ok(fence) => deferred.push_back((fence, pending)),
+                Err(_) => {
+                    for (id, destroy) in pending {
+                        unsafe { destroy(&mut ctxt, id) };
+                    }
+                },
+            }
+        }
+
+        loop {
+            let signaled = match deferred.front() {
+                Some(&(ref fence, _)) => unsafe {
+                    sync::is_linear_sync_fence_signaled(fence, &mut ctxt)
+                },
+                None => false,
+            };
+
+            if !signaled {
+                break;
+            }
+
+            let (fence, objects) = deferred.pop_front().unwrap();
+            unsafe { sync::destroy_linear_sync_fence(&mut ctxt, fence) };
+
+            for (id, destroy) in objects {
+                unsafe { destroy(&mut ctxt, id) };
+            }
+        }
+    }
+
+    /// Changes the swap interval, for example to enable or disable vsync.
+    ///
+    /// Returns `Err` if the backend doesn't support changing the swap interval, or doesn't
+    /// support this value. For example `SwapInterval::Adaptive` requires the
+    /// `EXT_swap_control_tear` extension of the window system.
+    pub fn set_swap_interval(&self, interval: SwapInterval) -> Result<(), ()> {
+        let backend = self.backend.borrow();
+
+        if !backend.is_current() {
+            unsafe { backend.make_current() };
+        }
+
+        unsafe { backend.set_swap_interval(interval) }
+    }
+
     /// DEPRECATED. Use `get_opengl_version` instead.
     #[inline]
     pub fn get_version(&self) -> &Version {
@@ -318,43 +706,152 @@ impl Context {
         self.capabilities().robustness
     }
 
+    /// Returns true if out-of-bound buffer accesses from programs have a defined behavior: reads
+    /// return zero or a value from within the buffer, and writes are discarded.
+    ///
+    /// This requires a robust context, see `is_robust`, and the
+    /// `GL_ARB_robust_buffer_access_behavior` extension or equivalent. Applications that render
+    /// untrusted geometry can rely on it instead of validating every index.
+    #[inline]
+    pub fn has_robust_buffer_access_behavior(&self) -> bool {
+        self.capabilities().robust_buffer_access_behavior
+    }
+
+    /// Returns the profile of the context, or `None` if the context doesn't have any.
+    ///
+    /// Only OpenGL 3.2 and above have profiles.
+    #[inline]
+    pub fn get_opengl_profile(&self) -> Option<Profile> {
+        self.capabilities().profile
+    }
+
+    /// Returns true if the context is forward-compatible, in which case the functions that have
+    /// been deprecated are not available.
+    #[inline]
+    pub fn is_forward_compatible(&self) -> bool {
+        self.capabilities().forward_compatible
+    }
+
+    /// Returns true if the context has been created with the debug flag.
+    #[inline]
+    pub fn is_debug(&self) -> bool {
+        self.capabilities().debug
+    }
+
+    /// Returns true if the default framebuffer is sRGB-capable.
+    #[inline]
+    pub fn is_default_framebuffer_srgb(&self) -> bool {
+        self.capabilities().srgb
+    }
+
+    /// Returns the number of samples of the default framebuffer, or `None` if it is not
+    /// multisampled.
+    #[inline]
+    pub fn get_default_framebuffer_samples(&self) -> Option<u16> {
+        self.capabilities().samples
+    }
+
+    /// Returns the properties of the default framebuffer that have been obtained when creating
+    /// the context.
+    ///
+    /// These can differ from the properties that have been requested.
+    pub fn get_default_framebuffer_properties(&self) -> DefaultFramebufferProperties {
+        let capabilities = self.capabilities();
+
+        DefaultFramebufferProperties {
+            red_bits: capabilities.color_bits.0,
+            green_bits: capabilities.color_bits.1,
+            blue_bits: capabilities.color_bits.2,
+            alpha_bits: capabilities.color_bits.3,
+            floating_point: capabilities.floating_point_color,
+            srgb: capabilities.srgb,
+            depth_bits: capabilities.depth_bits,
+            stencil_bits: capabilities.stencil_bits,
+            samples: capabilities.samples,
+        }
+    }
+
     /// Returns true if a context loss is possible.
     #[inline]
     pub fn is_context_loss_possible(&self) -> bool {
         self.capabilities().can_lose_context
     }
 
+    /// Returns true if the reset status is queried each time the buffers are swapped.
+    ///
+    /// `glGetGraphicsResetStatus` is a round-trip to the driver, so it is only called at each
+    /// frame for robust contexts, whose reset notification strategy is `LOSE_CONTEXT_ON_RESET`.
+    /// Other contexts only query it after an error, or when `is_context_lost` is called.
+    #[inline]
+    fn is_reset_status_polled(&self) -> bool {
+        self.capabilities.robustness && self.capabilities.can_lose_context
+    }
+
     /// Returns true if the context has been lost and needs to be recreated.
     ///
     /// # Implementation
     ///
-    /// If it has been determined that the context has been lost before, then the function
-    /// immediatly returns true. Otherwise, calls `glGetGraphicsResetStatus`. If this function
-    /// is not available, returns false.
+    /// If it has been determined that the context has been lost before, for example because the
+    /// backend has failed to make it current, then the function immediatly returns true.
+    /// Otherwise, calls `glGetGraphicsResetStatus`. If this function is not available, returns
+    /// false.
     pub fn is_context_lost(&self) -> bool {
         if self.state.borrow().lost_context {
             return true;
         }
 
-        let mut ctxt = self.make_current();
+        let lost = {
+            let mut ctxt = self.make_current();
 
-        let lost = if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                      ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                      ctxt.extensions.gl_khr_robustness
-        {
-            unsafe { ctxt.gl.GetGraphicsResetStatus() != gl::NO_ERROR }
-        } else if ctxt.extensions.gl_ext_robustness {
-            unsafe { ctxt.gl.GetGraphicsResetStatusEXT() != gl::NO_ERROR }
-        } else if ctxt.extensions.gl_arb_robustness {
-            unsafe { ctxt.gl.GetGraphicsResetStatusARB() != gl::NO_ERROR }
-        } else {
-            false
+            let lost = if ctxt.version >= &Version(Api::Gl, 4, 5) ||
+                          ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+                          ctxt.extensions.gl_khr_robustness
+            {
+                unsafe { ctxt.gl.GetGraphicsResetStatus() != gl::NO_ERROR }
+            } else if ctxt.extensions.gl_ext_robustness {
+                unsafe { ctxt.gl.GetGraphicsResetStatusEXT() != gl::NO_ERROR }
+            } else if ctxt.extensions.gl_arb_robustness {
+                unsafe { ctxt.gl.GetGraphicsResetStatusARB() != gl::NO_ERROR }
+            } else {
+                false
+            };
+
+            if lost { ctxt.state.lost_context = true; }
+            lost
         };
 
-        if lost { ctxt.state.lost_context = true; }
+        if lost {
+            self.call_context_lost_callbacks();
+        }
+
         lost
     }
 
+    /// Registers a function that is called once glium detects that the context has been lost.
+    ///
+    /// The context loss is detected when calling `is_context_lost` or when swapping buffers.
+    /// After a context loss, all the objects associated to this context (textures, buffers,
+    /// programs, etc.) are invalid, draw commands return `DrawError::ContextLost` and reads
+    /// from buffers and textures return `ReadError::ContextLost`. The callback is the right
+    /// place to schedule the creation of a new display and of all the resources of the
+    /// application.
+    ///
+    /// The callbacks are called only once, when the loss is detected, and are then removed.
+    /// A callback registered after the loss has been detected is never called.
+    ///
+    /// The callback must not call any function of this context.
+    pub fn on_context_lost<F>(&self, callback: F) where F: FnMut() + 'static {
+        self.context_lost_callbacks.borrow_mut().push(Box::new(callback));
+    }
+
+    /// Calls and removes all the functions registered with `on_context_lost`.
+    fn call_context_lost_callbacks(&self) {
+        let callbacks = mem::replace(&mut *self.context_lost_callbacks.borrow_mut(), Vec::new());
+        for mut callback in callbacks {
+            callback();
+        }
+    }
+
     /// Returns the behavior when the current OpenGL context is changed.
     ///
     /// The most common value is `Flush`. In order to get `None` you must explicitely request it
@@ -453,7 +950,7 @@ impl Context {
     /// component needs to directly manipulate OpenGL state.
     ///
     /// **If `action` manipulates any OpenGL state, it must be restored before `action`
-    /// completes.**
+    /// completes.** Use `with_foreign_gl` if this is not possible.
     #[inline]
     pub unsafe fn exec_in_context<'a, T, F>(&self, action: F) -> T
                                             where T: Send + 'static,
@@ -463,6 +960,61 @@ impl Context {
         action()
     }
 
+    /// Executes a closure that uses OpenGL without going through glium, for example a library
+    /// written in C that draws a user interface.
+    ///
+    /// Contrary to `exec_in_context`, `action` is allowed to leave the OpenGL state modified.
+    /// After `action` returns, glium sends back to OpenGL the state that it expects (bound
+    /// objects, blending, depth and stencil states, viewport, etc.) or forgets the states that
+    /// can't be restored so that they are set again the next time they are needed.
+    ///
+    /// # Safety
+    ///
+    /// `action` must not delete objects that belong to glium, and must not start or end
+    /// queries, conditional rendering or transform feedback. It can make another context
+    /// current, in which case glium's context is made current again after `action` returns.
+    pub unsafe fn with_foreign_gl<T, F>(&self, action: F) -> T where F: FnOnce() -> T {
+        {
+            let mut ctxt = self.make_current();
+            // the element array buffer of the current vertex array object can be modified
+            vertex_array_object::VertexAttributesSystem
+                ::hijack_current_element_array_buffer(&mut ctxt);
+        }
+
+        let result = action();
+
+        let mut ctxt = self.make_current();
+        restore::restore(&mut ctxt, &StateCategories::all());
+        result
+    }
+
+    /// Executes a closure that calls OpenGL functions directly.
+    ///
+    /// `touched` contains the categories of states that `action` may modify. After `action`
+    /// returns, glium restores the states of these categories and only these ones. This is
+    /// cheaper than `with_foreign_gl`, which restores everything.
+    ///
+    /// # Safety
+    ///
+    /// `action` must not modify states that are not part of `touched`, must not delete
+    /// objects that belong to glium, and must not start or end queries, conditional rendering
+    /// or transform feedback. The context must still be current when `action` returns.
+    pub unsafe fn exec_with_raw_gl<T, F>(&self, touched: StateCategories, action: F) -> T
+                                         where F: FnOnce(&gl::Gl) -> T
+    {
+        let mut ctxt = self.make_current();
+
+        if touched.vertex_array || touched.buffers {
+            // the element array buffer of the current vertex array object can be modified
+            vertex_array_object::VertexAttributesSystem
+                ::hijack_current_element_array_buffer(&mut ctxt);
+        }
+
+        let result = action(ctxt.gl);
+        restore::restore(&mut ctxt, &touched);
+        result
+    }
+
     /// Asserts that there are no OpenGL errors pending.
     ///
     /// This function should be used in tests.
@@ -538,6 +1090,207 @@ impl Context {
         }
     }
 
+    /// Sets the function that receives the debug messages of the OpenGL implementation.
+    ///
+    /// This replaces the previous callback, if any. The callback is called synchronously from
+    /// inside glium's functions and must not call any function of this context.
+    ///
+    /// Returns `Err` if the backend supports neither `GL_KHR_debug` nor `GL_ARB_debug_output`.
+    pub fn set_debug_callback<F>(&self, callback: F) -> Result<(), ()>
+                                 where F: FnMut(debug::Source, debug::MessageType,
+                                                debug::Severity, u32, &str) + 'static
+    {
+        if !self.is_debug_output_supported() {
+            return Err(());
+        }
+
+        *self.debug_callback.borrow_mut() = Some(Box::new(callback));
+        install_debug_callback(self);
+        Ok(())
+    }
+
+    /// Removes the function set with `set_debug_callback`.
+    #[inline]
+    pub fn remove_debug_callback(&self) {
+        *self.debug_callback.borrow_mut() = None;
+    }
+
+    /// Enables or disables the debug messages that match the given source, type and severity.
+    /// `None` matches all the values.
+    ///
+    /// Returns `Err` if the backend supports neither `GL_KHR_debug` nor `GL_ARB_debug_output`.
+    pub fn set_debug_messages_enabled(&self, source: Option<debug::Source>,
+                                      ty: Option<debug::MessageType>,
+                                      severity: Option<debug::Severity>, enabled: bool)
+                                      -> Result<(), ()>
+    {
+        if !self.is_debug_output_supported() {
+            return Err(());
+        }
+
+        let mut ctxt = self.make_current();
+        unsafe {
+            debug_message_control(&mut ctxt, source.map(|s| s as gl::types::GLenum),
+                                  ty.map(|t| t as gl::types::GLenum),
+                                  severity.map(|s| s as gl::types::GLenum), &[], enabled);
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables the debug messages with the given identifiers.
+    ///
+    /// Identifiers are only meaningful for a given source and type, which is why they must
+    /// be specified.
+    ///
+    /// Returns `Err` if the backend supports neither `GL_KHR_debug` nor `GL_ARB_debug_output`.
+    pub fn set_debug_message_ids_enabled(&self, source: debug::Source, ty: debug::MessageType,
+                                         ids: &[u32], enabled: bool) -> Result<(), ()>
+    {
+        if !self.is_debug_output_supported() {
+            return Err(());
+        }
+
+        let mut ctxt = self.make_current();
+        unsafe {
+            debug_message_control(&mut ctxt, Some(source as gl::types::GLenum),
+                                  Some(ty as gl::types::GLenum), None, ids, enabled);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the current policy for checking OpenGL errors.
+    #[inline]
+    pub fn get_error_check_policy(&self) -> debug::ErrorCheckPolicy {
+        self.error_check_policy.get()
+    }
+
+    /// Changes when glium checks for OpenGL errors.
+    ///
+    /// The errors are reported to the callback set with `set_debug_callback`, or printed on
+    /// stdout if there is none. See `ErrorCheckPolicy`.
+    pub fn set_error_check_policy(&self, policy: debug::ErrorCheckPolicy) {
+        self.error_check_policy.set(policy);
+        self.gl.set_check_errors(policy == debug::ErrorCheckPolicy::EveryCall);
+
+        // discarding the errors of the previous policy that haven't been reported yet
+        if policy != debug::ErrorCheckPolicy::EveryCall {
+            self.gl.take_errors();
+        }
+    }
+
+    /// Returns the current policy for comparing the state cache with the actual state.
+    #[inline]
+    pub fn get_state_check_policy(&self) -> debug::StateCheckPolicy {
+        self.state_check_policy.get()
+    }
+
+    /// Changes when glium compares its state cache with the actual state of the context.
+    #[inline]
+    pub fn set_state_check_policy(&self, policy: debug::StateCheckPolicy) {
+        self.state_check_policy.set(policy);
+    }
+
+    /// Reads back the main states of the OpenGL context and compares them with glium's cache.
+    ///
+    /// Returns the list of states whose actual value differs from the cached one. A difference
+    /// means that some code that doesn't go through glium has modified the state without
+    /// restoring it. See `with_foreign_gl` and `exec_with_raw_gl`.
+    ///
+    /// This is slow, as it forces a synchronization with the driver.
+    pub fn check_state_cache(&self) -> Vec<debug::StateMismatch> {
+        let mut ctxt = self.make_current();
+        unsafe { check::compare(&mut ctxt) }
+    }
+
+    /// If the policy is `EverySwap`, reports the states that don't match the cache to the
+    /// debug callback, or prints them if there is no callback.
+    fn check_state_cache_before_swap(&self) {
+        if self.state_check_policy.get() != debug::StateCheckPolicy::EverySwap {
+            return;
+        }
+
+        for mismatch in self.check_state_cache() {
+            let message = format!("glium's state cache doesn't match the state of the OpenGL \
+                                   context: {}", mismatch);
+            self.report_message(debug::MessageType::Error, debug::Severity::High, &message);
+        }
+    }
+
+    /// Sends a message from glium to the debug callback, or prints it if there is no callback.
+    fn report_message(&self, ty: debug::MessageType, severity: debug::Severity, message: &str) {
+        match *self.debug_callback.borrow_mut() {
+            Some(ref mut callback) => {
+                callback(debug::Source::Application, ty, severity, 0, message);
+            },
+            None => println!("glium: {}", message),
+        }
+    }
+
+    /// Enables or disables the counters of `debug::FrameStatistics`.
+    ///
+    /// Statistics are disabled by default. Enabling them resets the counters.
+    #[inline]
+    pub fn set_statistics_enabled(&self, enabled: bool) {
+        self.statistics.set(if enabled { Some(Default::default()) } else { None });
+    }
+
+    /// Returns the counters since the start of the current frame, or `None` if statistics are
+    /// disabled.
+    #[inline]
+    pub fn get_statistics(&self) -> Option<debug::FrameStatistics> {
+        self.statistics.get()
+    }
+
+    /// Sets all the counters to zero, if statistics are enabled. This is done automatically
+    /// when a `Frame` is created.
+    #[inline]
+    pub fn reset_statistics(&self) {
+        if self.statistics.get().is_some() {
+            self.statistics.set(Some(Default::default()));
+        }
+    }
+
+    /// Returns the features that glium is allowed to emulate when drawing.
+    #[inline]
+    pub fn get_emulation_options(&self) -> draw_parameters::EmulationOptions {
+        self.emulation_options.get()
+    }
+
+    /// Changes the features that glium is allowed to emulate when they are not supported by
+    /// the backend.
+    ///
+    /// See the documentation of `EmulationOptions` for more infos.
+    #[inline]
+    pub fn set_emulation_options(&self, options: draw_parameters::EmulationOptions) {
+        self.emulation_options.set(options);
+    }
+
+    /// Returns the workarounds for the bugs of the driver that are enabled.
+    #[inline]
+    pub fn get_quirks(&self) -> Quirks {
+        self.quirks.get()
+    }
+
+    /// Changes the workarounds for the bugs of the driver.
+    ///
+    /// See the documentation of `Quirks` for more infos. The workarounds that are only taken
+    /// into account when the context is created keep their original effect. In particular,
+    /// changing `avoid_direct_state_access_extensions` or `limit_texture_units` has no effect,
+    /// since they are applied once when the context is created.
+    #[inline]
+    pub fn set_quirks(&self, quirks: Quirks) {
+        self.quirks.set(quirks);
+    }
+
+    /// Returns true if the backend supports the debug output functions.
+    #[inline]
+    fn is_debug_output_supported(&self) -> bool {
+        self.version >= Version(Api::Gl, 4, 5) || self.version >= Version(Api::GlEs, 3, 2) ||
+        self.extensions.gl_khr_debug || self.extensions.gl_arb_debug_output
+    }
+
     /// Same as `insert_debug_marker`, except that if you don't compile with `debug_assertions`
     /// it is a no-op and returns `Ok`.
     #[inline]
@@ -558,10 +1311,24 @@ impl ContextExt for Context {
 
     fn make_current(&self) -> CommandContext {
         if self.check_current_context {
-            let backend = self.backend.borrow();
-            if !backend.is_current() {
-                unsafe { backend.make_current() };
-                debug_assert!(backend.is_current());
+            // `make_current` can't return an error, so a failure is reported as a loss of the
+            // context
+            if let Some(ref surface) = *self.current_surface.borrow() {
+                if !surface.is_current() {
+                    unsafe { surface.make_current() };
+                    if !surface.is_current() {
+                        self.state.borrow_mut().lost_context = true;
+                    }
+                }
+
+            } else {
+                let backend = self.backend.borrow();
+                if !backend.is_current() {
+                    unsafe { backend.make_current() };
+                    if !backend.is_current() {
+                        self.state.borrow_mut().lost_context = true;
+                    }
+                }
             }
         }
 
@@ -571,12 +1338,22 @@ impl ContextExt for Context {
             version: &self.version,
             extensions: &self.extensions,
             capabilities: &self.capabilities,
+            quirks: self.quirks.get(),
             report_debug_output_errors: &self.report_debug_output_errors,
             vertex_array_objects: &self.vertex_array_objects,
             framebuffer_objects: self.framebuffer_objects.as_ref().unwrap(),
             samplers: self.samplers.borrow_mut(),
+            ring_buffer: self.ring_buffer.borrow_mut(),
             resident_texture_handles: self.resident_texture_handles.borrow_mut(),
             resident_image_handles: self.resident_image_handles.borrow_mut(),
+            error_check_policy: self.error_check_policy.get(),
+            debug_callback: &self.debug_callback,
+            statistics: &self.statistics,
+            pending_destructions: if self.deferred_destruction.get() {
+                Some(self.pending_destructions.borrow_mut())
+            } else {
+                None
+            },
             marker: PhantomData,
         }
     }
@@ -585,6 +1362,106 @@ impl ContextExt for Context {
     fn capabilities(&self) -> &Capabilities {
         &self.capabilities
     }
+
+    fn set_current_surface(&self, surface: Option<Rc<SurfaceBackend>>) {
+        let mut current_surface = self.current_surface.borrow_mut();
+
+        unsafe {
+            match surface {
+                Some(ref surface) => {
+                    assert!(current_surface.is_none(), "A `Frame` is already drawing on \
+                                                        another surface");
+                    surface.make_current();
+                },
+                None => self.backend.borrow().make_current(),
+            }
+        }
+
+        *current_surface = surface;
+    }
+
+    #[inline]
+    fn swap_surface_buffers(&self, surface: &SurfaceBackend, damage: Option<&[Rect]>)
+                            -> Result<(), SwapBuffersError>
+    {
+        self.swap_buffers_impl(Some(surface), damage)
+    }
+
+    #[inline]
+    fn keep_alive_until(&self, object: Box<Any>, done: Box<Fn() -> bool>) {
+        self.kept_alive.borrow_mut().push((object, done));
+    }
+}
+
+impl<'a> CommandContext<'a> {
+    /// Reports the OpenGL errors if the policy is `EveryDraw` or `EveryCall`.
+    ///
+    /// Must be called after each draw command.
+    #[inline]
+    pub fn check_draw_error(&mut self) {
+        match self.error_check_policy {
+            debug::ErrorCheckPolicy::Never => (),
+            debug::ErrorCheckPolicy::EveryDraw => {
+                let error = unsafe { self.gl.GetError() };
+                if error != gl::NO_ERROR {
+                    self.report_error(error, &format!("{} has been generated by a draw command",
+                                                      ::gl_error_to_str(error)));
+                }
+            },
+            debug::ErrorCheckPolicy::EveryCall => self.report_recorded_errors(),
+        }
+    }
+
+    /// Destroys an object with `destroy`, or queues it if deferred destruction is enabled.
+    #[inline]
+    pub unsafe fn destroy_object(&mut self, id: gl::types::GLuint,
+                                 destroy: unsafe fn(&mut CommandContext, gl::types::GLuint))
+    {
+        if let Some(ref mut pending) = self.pending_destructions {
+            pending.push((id, destroy));
+            return;
+        }
+
+        destroy(self, id);
+    }
+
+    /// Updates the counters of the current frame, if statistics are enabled.
+    #[inline]
+    pub fn record_statistics<F>(&self, update: F) where F: FnOnce(&mut debug::FrameStatistics) {
+        if let Some(mut statistics) = self.statistics.get() {
+            update(&mut statistics);
+            self.statistics.set(Some(statistics));
+        }
+    }
+
+    /// Reports the errors that the OpenGL functions have generated since the last report, when
+    /// the policy is `EveryCall`.
+    fn report_recorded_errors(&self) {
+        for (function, error) in self.gl.take_errors() {
+            self.report_error(error, &format!("{} has been generated by {}",
+                                              ::gl_error_to_str(error), function));
+        }
+    }
+
+    /// Sends an OpenGL error to the debug callback, or prints it if there is no callback.
+    fn report_error(&self, error: gl::types::GLenum, message: &str) {
+        match *self.debug_callback.borrow_mut() {
+            Some(ref mut callback) => {
+                callback(debug::Source::Api, debug::MessageType::Error, debug::Severity::High,
+                         error, message);
+            },
+            None => println!("glium: OpenGL error: {}", message),
+        }
+    }
+}
+
+impl<'a> Drop for CommandContext<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.error_check_policy == debug::ErrorCheckPolicy::EveryCall {
+            self.report_recorded_errors();
+        }
+    }
 }
 
 impl CapabilitiesSource for Context {
@@ -626,8 +1503,13 @@ impl Drop for Context {
                 vertex_array_objects: &self.vertex_array_objects,
                 framebuffer_objects: self.framebuffer_objects.as_ref().unwrap(),
                 samplers: self.samplers.borrow_mut(),
+                ring_buffer: self.ring_buffer.borrow_mut(),
                 resident_texture_handles: self.resident_texture_handles.borrow_mut(),
                 resident_image_handles: self.resident_image_handles.borrow_mut(),
+                error_check_policy: self.error_check_policy.get(),
+                debug_callback: &self.debug_callback,
+                statistics: &self.statistics,
+                pending_destructions: None,
                 marker: PhantomData,
             };
 
@@ -638,6 +1520,25 @@ impl Drop for Context {
                 s.destroy(&mut ctxt);
             }
 
+            if let Some(ring_buffer) = ctxt.ring_buffer.take() {
+                ring_buffer.destroy(&mut ctxt);
+            }
+
+            for fence in self.frame_fences.borrow_mut().drain(..) {
+                sync::destroy_linear_sync_fence(&mut ctxt, fence);
+            }
+
+            for (id, destroy) in self.pending_destructions.borrow_mut().drain(..) {
+                destroy(&mut ctxt, id);
+            }
+
+            for (fence, objects) in self.deferred_destructions.borrow_mut().drain(..) {
+                sync::destroy_linear_sync_fence(&mut ctxt, fence);
+                for (id, destroy) in objects {
+                    destroy(&mut ctxt, id);
+                }
+            }
+
             // disabling callback
             if ctxt.state.enabled_debug_output != Some(false) {
                 if ctxt.version >= &Version(Api::Gl, 4,5) || ctxt.extensions.gl_khr_debug {
@@ -721,6 +1622,48 @@ fn init_debug_callback(context: &Rc<Context>) {
         return;
     }
 
+    install_debug_callback(context);
+}
+
+/// Reads the initial error check policy from the `GLIUM_ERROR_CHECK` environment variable.
+fn error_check_policy_from_env(context: &Context) -> debug::ErrorCheckPolicy {
+    match env::var("GLIUM_ERROR_CHECK") {
+        Ok(ref value) if value == "all" => debug::ErrorCheckPolicy::EveryCall,
+        Ok(ref value) if value == "draw" => debug::ErrorCheckPolicy::EveryDraw,
+        Ok(ref value) if value == "never" => debug::ErrorCheckPolicy::Never,
+        Ok(value) => {
+            context.report_message(debug::MessageType::Other, debug::Severity::Medium,
+                                   &format!("unknown value `{}` for GLIUM_ERROR_CHECK, expected \
+                                             `never`, `draw` or `all`. OpenGL errors won't be \
+                                             checked.", value));
+            debug::ErrorCheckPolicy::Never
+        },
+        Err(_) => debug::ErrorCheckPolicy::Never,
+    }
+}
+
+/// Reads the initial state check policy from the `GLIUM_STATE_CHECK` environment variable.
+fn state_check_policy_from_env(context: &Context) -> debug::StateCheckPolicy {
+    match env::var("GLIUM_STATE_CHECK") {
+        Ok(ref value) if value == "swap" => debug::StateCheckPolicy::EverySwap,
+        Ok(ref value) if value == "never" => debug::StateCheckPolicy::Never,
+        Ok(value) => {
+            context.report_message(debug::MessageType::Other, debug::Severity::Medium,
+                                   &format!("unknown value `{}` for GLIUM_STATE_CHECK, expected \
+                                             `never` or `swap`. The state cache won't be \
+                                             checked.", value));
+            debug::StateCheckPolicy::Never
+        },
+        Err(_) => debug::StateCheckPolicy::Never,
+    }
+}
+
+/// Registers glium's debug callback in the backend, if it is not already registered.
+fn install_debug_callback(context: &Context) {
+    if context.debug_callback_installed.get() {
+        return;
+    }
+
     // this is the C callback
     extern "system" fn callback_wrapper(source: gl::types::GLenum, ty: gl::types::GLenum,
                                         id: gl::types::GLuint, severity: gl::types::GLenum,
@@ -731,7 +1674,15 @@ fn init_debug_callback(context: &Rc<Context>) {
         let user_param = user_param as *const Context;
         let user_param: &Context = unsafe { mem::transmute(user_param) };
 
-        if (severity == gl::DEBUG_SEVERITY_HIGH || severity == gl::DEBUG_SEVERITY_MEDIUM) &&
+        if let Some(ref mut callback) = *user_param.debug_callback.borrow_mut() {
+            let message = unsafe { CStr::from_ptr(message) };
+            let message = String::from_utf8_lossy(message.to_bytes());
+            callback(debug_source(source), debug_message_type(ty), debug_severity(severity),
+                     id, &message);
+        }
+
+        if cfg!(debug_assertions) &&
+           (severity == gl::DEBUG_SEVERITY_HIGH || severity == gl::DEBUG_SEVERITY_MEDIUM) &&
            (ty == gl::DEBUG_TYPE_ERROR || ty == gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR ||
             ty == gl::DEBUG_TYPE_PORTABILITY || ty == gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR)
         {
@@ -777,7 +1728,7 @@ fn init_debug_callback(context: &Rc<Context>) {
 
     struct ContextRawPtr(*const Context);
     unsafe impl Send for ContextRawPtr {}
-    let context_raw_ptr = ContextRawPtr(&**context);
+    let context_raw_ptr = ContextRawPtr(context);
 
     unsafe {
         let mut ctxt = context.make_current();
@@ -825,6 +1776,72 @@ fn init_debug_callback(context: &Rc<Context>) {
 
                 ctxt.state.enabled_debug_output = Some(true);
             }
+
+            context.debug_callback_installed.set(true);
         }
     }
 }
+
+/// Calls `glDebugMessageControl` or its equivalent. `None` corresponds to `GL_DONT_CARE`.
+unsafe fn debug_message_control(ctxt: &mut CommandContext, source: Option<gl::types::GLenum>,
+                                ty: Option<gl::types::GLenum>,
+                                severity: Option<gl::types::GLenum>, ids: &[u32], enabled: bool)
+{
+    let source = source.unwrap_or(gl::DONT_CARE);
+    let ty = ty.unwrap_or(gl::DONT_CARE);
+    let severity = severity.unwrap_or(gl::DONT_CARE);
+    let enabled = if enabled { gl::TRUE } else { gl::FALSE };
+    let ids_ptr = if ids.is_empty() { ptr::null() } else { ids.as_ptr() };
+
+    if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+       (ctxt.version >= &Version(Api::Gl, 1, 0) && ctxt.extensions.gl_khr_debug)
+    {
+        ctxt.gl.DebugMessageControl(source, ty, severity, ids.len() as gl::types::GLsizei,
+                                    ids_ptr, enabled);
+
+    } else if ctxt.version >= &Version(Api::GlEs, 2, 0) && ctxt.extensions.gl_khr_debug {
+        ctxt.gl.DebugMessageControlKHR(source, ty, severity, ids.len() as gl::types::GLsizei,
+                                       ids_ptr, enabled);
+
+    } else if ctxt.extensions.gl_arb_debug_output {
+        ctxt.gl.DebugMessageControlARB(source, ty, severity, ids.len() as gl::types::GLsizei,
+                                       ids_ptr, enabled);
+
+    } else {
+        unreachable!();
+    }
+}
+
+fn debug_source(value: gl::types::GLenum) -> debug::Source {
+    match value {
+        gl::DEBUG_SOURCE_API => debug::Source::Api,
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => debug::Source::WindowSystem,
+        gl::DEBUG_SOURCE_SHADER_COMPILER => debug::Source::ShaderCompiler,
+        gl::DEBUG_SOURCE_THIRD_PARTY => debug::Source::ThirdParty,
+        gl::DEBUG_SOURCE_APPLICATION => debug::Source::Application,
+        _ => debug::Source::OtherSource,
+    }
+}
+
+fn debug_message_type(value: gl::types::GLenum) -> debug::MessageType {
+    match value {
+        gl::DEBUG_TYPE_ERROR => debug::MessageType::Error,
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => debug::MessageType::DeprecatedBehavior,
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => debug::MessageType::UndefinedBehavior,
+        gl::DEBUG_TYPE_PORTABILITY => debug::MessageType::Portability,
+        gl::DEBUG_TYPE_PERFORMANCE => debug::MessageType::Performance,
+        gl::DEBUG_TYPE_MARKER => debug::MessageType::Marker,
+        gl::DEBUG_TYPE_PUSH_GROUP => debug::MessageType::PushGroup,
+        gl::DEBUG_TYPE_POP_GROUP => debug::MessageType::PopGroup,
+        _ => debug::MessageType::Other,
+    }
+}
+
+fn debug_severity(value: gl::types::GLenum) -> debug::Severity {
+    match value {
+        gl::DEBUG_SEVERITY_HIGH => debug::Severity::High,
+        gl::DEBUG_SEVERITY_MEDIUM => debug::Severity::Medium,
+        gl::DEBUG_SEVERITY_LOW => debug::Severity::Low,
+        _ => debug::Severity::Notification,
+    }
+}
@@ -0,0 +1,149 @@
+//! Detects the drivers that need workarounds.
+//!
+//! The `GL_VENDOR`, `GL_RENDERER` and `GL_VERSION` strings of the context are compared with
+//! a table of drivers that are known to be buggy. The workarounds can then be changed with the
+//! `GLIUM_QUIRKS` environment variable.
+
+use gl;
+
+use std::env;
+use std::ffi::CStr;
+
+/// Workarounds for the bugs of specific drivers.
+///
+/// When a context is created, glium compares the `GL_VENDOR`, `GL_RENDERER` and `GL_VERSION`
+/// strings of the driver with a table of drivers that are known to be buggy, and enables the
+/// workarounds that they need.
+///
+/// The `GLIUM_QUIRKS` environment variable overrides the table. It contains names of fields
+/// separated with commas, and a name prefixed with `-` disables the workaround. For example
+/// `GLIUM_QUIRKS=no_buffer_orphaning,-limit_texture_units`.
+///
+/// Use `Context::get_quirks` to know which workarounds are enabled, and `Context::set_quirks`
+/// to change them. Some workarounds are only taken into account when the context is created,
+/// and changing them afterwards has no effect. For example the direct state access extensions
+/// are disabled once by `Context::new`, so they stay enabled or disabled whatever the value
+/// given to `set_quirks`. Use the `GLIUM_QUIRKS` environment variable to change them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Quirks {
+    /// If true, the `GL_ARB_direct_state_access` and `GL_EXT_direct_state_access` extensions
+    /// are considered as unsupported, and objects are bound before being modified instead.
+    ///
+    /// The functions of OpenGL 4.5 are still used if the context supports this version. Only
+    /// taken into account when the context is created.
+    pub avoid_direct_state_access_extensions: bool,
+
+    /// If true, at most 32 texture units are used, since using more crashes some drivers.
+    ///
+    /// Only taken into account when the context is created.
+    pub limit_texture_units: bool,
+
+    /// If true, invalidating a buffer when `glInvalidateBufferData` isn't available doesn't
+    /// orphan its storage with `glBufferData`. Invalidating the buffer then does nothing.
+    ///
+    /// The buffer that glium uses to upload the indices stored in RAM then waits on sync fences
+    /// before overwriting its previous content, instead of orphaning it.
+    pub no_buffer_orphaning: bool,
+}
+
+impl Quirks {
+    /// Returns the workarounds that the table of glium enables for a driver, given its
+    /// `GL_VENDOR`, `GL_RENDERER` and `GL_VERSION` strings.
+    pub fn detect(vendor: &str, renderer: &str, version: &str) -> Quirks {
+        let mut quirks = Quirks::default();
+
+        for driver in KNOWN_DRIVERS.iter() {
+            if vendor.contains(driver.vendor) && renderer.contains(driver.renderer) &&
+               version.contains(driver.version)
+            {
+                for name in driver.quirks.iter() {
+                    quirks.set(name, true);
+                }
+            }
+        }
+
+        quirks
+    }
+
+    /// Enables or disables a workaround given the name of its field, for example
+    /// `"no_buffer_orphaning"`.
+    ///
+    /// Returns `false` if the name doesn't correspond to any workaround.
+    pub fn set(&mut self, name: &str, value: bool) -> bool {
+        match name {
+            "avoid_direct_state_access_extensions" => {
+                self.avoid_direct_state_access_extensions = value
+            },
+            "limit_texture_units" => self.limit_texture_units = value,
+            "no_buffer_orphaning" => self.no_buffer_orphaning = value,
+            _ => return false,
+        };
+
+        true
+    }
+}
+
+/// A driver that needs workarounds. The strings must be contained in the corresponding
+/// `glGetString` values, and an empty string matches any driver.
+struct KnownDriver {
+    vendor: &'static str,
+    renderer: &'static str,
+    version: &'static str,
+    quirks: &'static [&'static str],
+}
+
+const KNOWN_DRIVERS: &'static [KnownDriver] = &[
+    // some Radeon drivers crash if you use texture units 32 or more (issue #1181)
+    KnownDriver {
+        vendor: "",
+        renderer: "Radeon",
+        version: "",
+        quirks: &["limit_texture_units"],
+    },
+
+    // the Windows drivers of Intel, whose version string contains "Build", return wrong
+    // values from some of the functions of the direct state access extensions
+    KnownDriver {
+        vendor: "Intel",
+        renderer: "",
+        version: "Build",
+        quirks: &["avoid_direct_state_access_extensions"],
+    },
+
+    // Mali drivers stall when the storage of a buffer that is still in use is orphaned
+    KnownDriver {
+        vendor: "ARM",
+        renderer: "Mali",
+        version: "",
+        quirks: &["no_buffer_orphaning"],
+    },
+];
+
+/// Returns the workarounds for the driver of the current context, after applying the
+/// `GLIUM_QUIRKS` environment variable.
+///
+/// # Unsafe
+///
+/// The functions must belong to the current context.
+pub unsafe fn get_quirks(gl: &gl::Gl) -> Quirks {
+    let mut quirks = Quirks::detect(&get_string(gl, gl::VENDOR), &get_string(gl, gl::RENDERER),
+                                    &get_string(gl, gl::VERSION));
+
+    if let Ok(value) = env::var("GLIUM_QUIRKS") {
+        for name in value.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            if name.starts_with('-') {
+                quirks.set(&name[1..], false);
+            } else {
+                quirks.set(name, true);
+            }
+        }
+    }
+
+    quirks
+}
+
+unsafe fn get_string(gl: &gl::Gl, name: gl::types::GLenum) -> String {
+    let s = gl.GetString(name);
+    assert!(!s.is_null());
+    String::from_utf8_lossy(CStr::from_ptr(s as *const i8).to_bytes()).into_owned()
+}
@@ -0,0 +1,473 @@
+//! Sends the state that is stored in the `GlState` back to OpenGL.
+//!
+//! This is used after some code that doesn't go through glium has modified the state of the
+//! OpenGL context. Each function restores one category of states, as described by
+//! `StateCategories`.
+
+use gl;
+use Handle;
+
+use context::CommandContext;
+use version::Api;
+use version::Version;
+
+/// Categories of OpenGL states that glium keeps track of.
+///
+/// Used to tell glium which states have been modified by code that called OpenGL directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct StateCategories {
+    /// The current program and `GL_PROGRAM_POINT_SIZE`.
+    pub program: bool,
+
+    /// The current vertex array object.
+    pub vertex_array: bool,
+
+    /// The buffers bound to the indexed and non-indexed bind points.
+    pub buffers: bool,
+
+    /// The read and draw framebuffers, the read buffer of the default framebuffer and
+    /// `GL_FRAMEBUFFER_SRGB`.
+    pub framebuffers: bool,
+
+    /// `GL_BLEND` and the blending equations, functions and color.
+    pub blending: bool,
+
+    /// `GL_DEPTH_TEST`, the depth function, mask and range, and depth clamping.
+    pub depth: bool,
+
+    /// `GL_STENCIL_TEST` and the stencil functions, masks and operations.
+    pub stencil: bool,
+
+    /// The viewport, the scissor box and `GL_SCISSOR_TEST`.
+    pub viewport_scissor: bool,
+
+    /// The color mask and the clear color, depth and stencil values.
+    pub clear_values: bool,
+
+    /// Culling, polygon mode, line width, point size, smoothing, multisampling, dithering,
+    /// polygon offset, sample coverage, rasterizer discard, provoking vertex and the number of
+    /// vertices per patch.
+    pub rasterization: bool,
+
+    /// The pack and unpack alignments.
+    pub pixel_store: bool,
+
+    /// The active texture unit, and the textures and samplers bound to each unit.
+    pub textures: bool,
+}
+
+impl StateCategories {
+    /// Returns a `StateCategories` where all the categories are set.
+    #[inline]
+    pub fn all() -> StateCategories {
+        StateCategories {
+            program: true,
+            vertex_array: true,
+            buffers: true,
+            framebuffers: true,
+            blending: true,
+            depth: true,
+            stencil: true,
+            viewport_scissor: true,
+            clear_values: true,
+            rasterization: true,
+            pixel_store: true,
+            textures: true,
+        }
+    }
+
+    /// Returns a `StateCategories` where none of the categories are set.
+    #[inline]
+    pub fn none() -> StateCategories {
+        Default::default()
+    }
+}
+
+/// Restores the states of the given categories.
+pub unsafe fn restore(ctxt: &mut CommandContext, categories: &StateCategories) {
+    if categories.program { restore_program(ctxt); }
+    if categories.vertex_array { restore_vertex_array(ctxt); }
+    if categories.buffers { restore_buffers(ctxt); }
+    if categories.framebuffers { restore_framebuffers(ctxt); }
+    if categories.blending { restore_blending(ctxt); }
+    if categories.depth { restore_depth(ctxt); }
+    if categories.stencil { restore_stencil(ctxt); }
+    if categories.viewport_scissor { restore_viewport_scissor(ctxt); }
+    if categories.clear_values { restore_clear_values(ctxt); }
+    if categories.rasterization { restore_rasterization(ctxt); }
+    if categories.pixel_store { restore_pixel_store(ctxt); }
+    if categories.textures { restore_textures(ctxt); }
+}
+
+/// Enables or disables a capability.
+unsafe fn set_enabled(ctxt: &mut CommandContext, cap: gl::types::GLenum, value: bool) {
+    if value {
+        ctxt.gl.Enable(cap);
+    } else {
+        ctxt.gl.Disable(cap);
+    }
+}
+
+/// Restores the current program.
+unsafe fn restore_program(ctxt: &mut CommandContext) {
+    match ctxt.state.program {
+        Handle::Id(id) => ctxt.gl.UseProgram(id),
+        Handle::Handle(id) => ctxt.gl.UseProgramObjectARB(id),
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 2) {
+        let value = ctxt.state.enabled_program_point_size;
+        set_enabled(ctxt, gl::PROGRAM_POINT_SIZE, value);
+    }
+}
+
+/// Restores the current vertex array object.
+///
+/// The binding of `GL_ELEMENT_ARRAY_BUFFER` is not cached and is part of the state of the
+/// vertex array object, so it's not restored.
+unsafe fn restore_vertex_array(ctxt: &mut CommandContext) {
+    let id = ctxt.state.vertex_array;
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+        ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+        ctxt.extensions.gl_arb_vertex_array_object
+    {
+        ctxt.gl.BindVertexArray(id);
+    } else if ctxt.extensions.gl_oes_vertex_array_object {
+        ctxt.gl.BindVertexArrayOES(id);
+    } else if ctxt.extensions.gl_apple_vertex_array_object {
+        ctxt.gl.BindVertexArrayAPPLE(id);
+    }
+}
+
+/// Restores the non-indexed buffer bind points.
+///
+/// The state of the indexed bind points is forgotten instead, which forces glium to bind its
+/// buffers again the next time they are used.
+unsafe fn restore_buffers(ctxt: &mut CommandContext) {
+    macro_rules! restore {
+        ($bind_point:expr, $state_var:ident, $supported:expr) => (
+            if $supported {
+                let id = ctxt.state.$state_var;
+
+                if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                   ctxt.version >= &Version(Api::GlEs, 2, 0)
+                {
+                    ctxt.gl.BindBuffer($bind_point, id);
+                } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                    ctxt.gl.BindBufferARB($bind_point, id);
+                }
+            }
+        );
+    }
+
+    restore!(gl::ARRAY_BUFFER, array_buffer_binding, true);
+
+    restore!(gl::PIXEL_PACK_BUFFER, pixel_pack_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 2, 1) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+             ctxt.extensions.gl_arb_pixel_buffer_object ||
+             ctxt.extensions.gl_nv_pixel_buffer_object);
+
+    restore!(gl::PIXEL_UNPACK_BUFFER, pixel_unpack_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 2, 1) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+             ctxt.extensions.gl_arb_pixel_buffer_object ||
+             ctxt.extensions.gl_nv_pixel_buffer_object);
+
+    restore!(gl::UNIFORM_BUFFER, uniform_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 3, 1) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+             ctxt.extensions.gl_arb_uniform_buffer_object);
+
+    restore!(gl::COPY_READ_BUFFER, copy_read_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 3, 1) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+             ctxt.extensions.gl_arb_copy_buffer || ctxt.extensions.gl_nv_copy_buffer);
+
+    restore!(gl::COPY_WRITE_BUFFER, copy_write_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 3, 1) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+             ctxt.extensions.gl_arb_copy_buffer || ctxt.extensions.gl_nv_copy_buffer);
+
+    restore!(gl::DISPATCH_INDIRECT_BUFFER, dispatch_indirect_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 4, 3) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+             ctxt.extensions.gl_arb_compute_shader);
+
+    restore!(gl::DRAW_INDIRECT_BUFFER, draw_indirect_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 4, 0) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+             ctxt.extensions.gl_arb_multi_draw_indirect ||
+             ctxt.extensions.gl_ext_multi_draw_indirect);
+
+    restore!(gl::QUERY_BUFFER, query_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 4, 4) ||
+             ctxt.extensions.gl_arb_query_buffer_object ||
+             ctxt.extensions.gl_amd_query_buffer_object);
+
+    restore!(gl::TEXTURE_BUFFER, texture_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 3, 0) ||
+             ctxt.extensions.gl_arb_texture_buffer_object ||
+             ctxt.extensions.gl_ext_texture_buffer_object ||
+             ctxt.extensions.gl_ext_texture_buffer || ctxt.extensions.gl_oes_texture_buffer);
+
+    restore!(gl::ATOMIC_COUNTER_BUFFER, atomic_counter_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 4, 2) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 1));
+
+    restore!(gl::SHADER_STORAGE_BUFFER, shader_storage_buffer_binding,
+             ctxt.version >= &Version(Api::Gl, 4, 3) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+             ctxt.extensions.gl_arb_shader_storage_buffer_object);
+
+    for binding in ctxt.state.indexed_uniform_buffer_bindings.iter_mut() {
+        *binding = Default::default();
+    }
+    for binding in ctxt.state.indexed_atomic_counter_buffer_bindings.iter_mut() {
+        *binding = Default::default();
+    }
+    for binding in ctxt.state.indexed_shader_storage_buffer_bindings.iter_mut() {
+        *binding = Default::default();
+    }
+    for binding in ctxt.state.indexed_transform_feedback_buffer_bindings.iter_mut() {
+        *binding = Default::default();
+    }
+}
+
+/// Restores the read and draw framebuffers and `GL_FRAMEBUFFER_SRGB`.
+///
+/// The content of the pixel local storage can't be restored, so it is disabled instead.
+unsafe fn restore_framebuffers(ctxt: &mut CommandContext) {
+    if ctxt.extensions.gl_ext_shader_pixel_local_storage ||
+       ctxt.extensions.gl_ext_shader_pixel_local_storage2
+    {
+        ctxt.gl.Disable(gl::SHADER_PIXEL_LOCAL_STORAGE_EXT);
+        ctxt.state.enabled_shader_pixel_local_storage = false;
+    }
+
+    let draw = ctxt.state.draw_framebuffer;
+    let read = ctxt.state.read_framebuffer;
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_object {
+        ctxt.gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, draw);
+        ctxt.gl.BindFramebuffer(gl::READ_FRAMEBUFFER, read);
+    } else if ctxt.version >= &Version(Api::GlEs, 2, 0) {
+        ctxt.gl.BindFramebuffer(gl::FRAMEBUFFER, draw);
+    } else if ctxt.extensions.gl_ext_framebuffer_object {
+        ctxt.gl.BindFramebufferEXT(gl::FRAMEBUFFER_EXT, draw);
+    }
+
+    // the read buffer of the default framebuffer is set again the next time it is used
+    ctxt.state.default_framebuffer_read = None;
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_srgb ||
+       ctxt.extensions.gl_ext_framebuffer_srgb || ctxt.extensions.gl_ext_srgb_write_control
+    {
+        let value = ctxt.state.enabled_framebuffer_srgb;
+        set_enabled(ctxt, gl::FRAMEBUFFER_SRGB, value);
+    }
+}
+
+/// Restores the blending equations, functions and color.
+unsafe fn restore_blending(ctxt: &mut CommandContext) {
+    let value = ctxt.state.enabled_blend;
+    set_enabled(ctxt, gl::BLEND, value);
+
+    let (color_eq, alpha_eq) = ctxt.state.blend_equation;
+    ctxt.gl.BlendEquationSeparate(color_eq, alpha_eq);
+
+    let (src_rgb, dst_rgb, src_alpha, dst_alpha) = ctxt.state.blend_func;
+    ctxt.gl.BlendFuncSeparate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+
+    let (r, g, b, a) = ctxt.state.blend_color;
+    ctxt.gl.BlendColor(r, g, b, a);
+}
+
+/// Restores the depth test, depth mask, depth range and depth clamping.
+unsafe fn restore_depth(ctxt: &mut CommandContext) {
+    let value = ctxt.state.enabled_depth_test;
+    set_enabled(ctxt, gl::DEPTH_TEST, value);
+
+    let func = ctxt.state.depth_func;
+    ctxt.gl.DepthFunc(func);
+
+    let mask = ctxt.state.depth_mask;
+    ctxt.gl.DepthMask(if mask { gl::TRUE } else { gl::FALSE });
+
+    let (near, far) = ctxt.state.depth_range;
+    if ctxt.version >= &Version(Api::Gl, 1, 0) {
+        ctxt.gl.DepthRange(near as f64, far as f64);
+    } else {
+        ctxt.gl.DepthRangef(near, far);
+    }
+
+    let near = ctxt.state.enabled_depth_clamp_near;
+    let far = ctxt.state.enabled_depth_clamp_far;
+
+    if ctxt.extensions.gl_amd_depth_clamp_separate {
+        set_enabled(ctxt, gl::DEPTH_CLAMP_NEAR_AMD, near);
+        set_enabled(ctxt, gl::DEPTH_CLAMP_FAR_AMD, far);
+    } else if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_depth_clamp ||
+              ctxt.extensions.gl_nv_depth_clamp
+    {
+        set_enabled(ctxt, gl::DEPTH_CLAMP, near && far);
+    }
+}
+
+/// Restores the stencil test, functions, masks and operations.
+unsafe fn restore_stencil(ctxt: &mut CommandContext) {
+    let value = ctxt.state.enabled_stencil_test;
+    set_enabled(ctxt, gl::STENCIL_TEST, value);
+
+    let (func, reference, mask) = ctxt.state.stencil_func_front;
+    ctxt.gl.StencilFuncSeparate(gl::FRONT, func, reference, mask);
+    let (func, reference, mask) = ctxt.state.stencil_func_back;
+    ctxt.gl.StencilFuncSeparate(gl::BACK, func, reference, mask);
+
+    let mask = ctxt.state.stencil_mask_front;
+    ctxt.gl.StencilMaskSeparate(gl::FRONT, mask);
+    let mask = ctxt.state.stencil_mask_back;
+    ctxt.gl.StencilMaskSeparate(gl::BACK, mask);
+
+    let (sfail, dpfail, dppass) = ctxt.state.stencil_op_front;
+    ctxt.gl.StencilOpSeparate(gl::FRONT, sfail, dpfail, dppass);
+    let (sfail, dpfail, dppass) = ctxt.state.stencil_op_back;
+    ctxt.gl.StencilOpSeparate(gl::BACK, sfail, dpfail, dppass);
+}
+
+/// Restores the viewport, the scissor box and the scissor test.
+///
+/// If the viewport or the scissor box have never been set by glium, they are forgotten
+/// instead.
+unsafe fn restore_viewport_scissor(ctxt: &mut CommandContext) {
+    let value = ctxt.state.enabled_scissor_test;
+    set_enabled(ctxt, gl::SCISSOR_TEST, value);
+
+    if let Some((x, y, width, height)) = ctxt.state.viewport {
+        ctxt.gl.Viewport(x, y, width, height);
+    }
+
+    if let Some((x, y, width, height)) = ctxt.state.scissor {
+        ctxt.gl.Scissor(x, y, width, height);
+    }
+}
+
+/// Restores the color mask and the values used when clearing.
+unsafe fn restore_clear_values(ctxt: &mut CommandContext) {
+    let (r, g, b, a) = ctxt.state.color_mask;
+    ctxt.gl.ColorMask(r, g, b, a);
+
+    let (r, g, b, a) = ctxt.state.clear_color;
+    ctxt.gl.ClearColor(r, g, b, a);
+
+    let depth = ctxt.state.clear_depth;
+    if ctxt.version >= &Version(Api::Gl, 1, 0) {
+        ctxt.gl.ClearDepth(depth as gl::types::GLclampd);
+    } else {
+        ctxt.gl.ClearDepthf(depth);
+    }
+
+    let stencil = ctxt.state.clear_stencil;
+    ctxt.gl.ClearStencil(stencil);
+}
+
+/// Restores the states related to the rasterization of primitives: culling, polygon mode,
+/// polygon offset, line width, point size, smoothing, multisampling, dithering, rasterizer
+/// discard, provoking vertex and the number of vertices per patch.
+unsafe fn restore_rasterization(ctxt: &mut CommandContext) {
+    let value = ctxt.state.enabled_cull_face;
+    set_enabled(ctxt, gl::CULL_FACE, value);
+    let cull_face = ctxt.state.cull_face;
+    ctxt.gl.CullFace(cull_face);
+
+    let value = ctxt.state.enabled_dither;
+    set_enabled(ctxt, gl::DITHER, value);
+    let value = ctxt.state.enabled_polygon_offset_fill;
+    set_enabled(ctxt, gl::POLYGON_OFFSET_FILL, value);
+    let (factor, units) = ctxt.state.polygon_offset;
+    ctxt.gl.PolygonOffset(factor, units);
+    let value = ctxt.state.enabled_sample_alpha_to_coverage;
+    set_enabled(ctxt, gl::SAMPLE_ALPHA_TO_COVERAGE, value);
+    let value = ctxt.state.enabled_sample_coverage;
+    set_enabled(ctxt, gl::SAMPLE_COVERAGE, value);
+
+    let line_width = ctxt.state.line_width;
+    ctxt.gl.LineWidth(line_width);
+
+    if ctxt.version >= &Version(Api::Gl, 1, 0) {
+        let value = ctxt.state.enabled_multisample;
+        set_enabled(ctxt, gl::MULTISAMPLE, value);
+
+        let point_size = ctxt.state.point_size;
+        ctxt.gl.PointSize(point_size);
+
+        let polygon_mode = ctxt.state.polygon_mode;
+        ctxt.gl.PolygonMode(gl::FRONT_AND_BACK, polygon_mode);
+
+        let value = ctxt.state.enabled_polygon_offset_line;
+        set_enabled(ctxt, gl::POLYGON_OFFSET_LINE, value);
+        let value = ctxt.state.enabled_polygon_offset_point;
+        set_enabled(ctxt, gl::POLYGON_OFFSET_POINT, value);
+
+        let value = ctxt.state.enabled_line_smooth;
+        set_enabled(ctxt, gl::LINE_SMOOTH, value);
+        let value = ctxt.state.enabled_polygon_smooth;
+        set_enabled(ctxt, gl::POLYGON_SMOOTH, value);
+
+        let (line_hint, polygon_hint) = ctxt.state.smooth;
+        ctxt.gl.Hint(gl::LINE_SMOOTH_HINT, line_hint);
+        ctxt.gl.Hint(gl::POLYGON_SMOOTH_HINT, polygon_hint);
+    }
+
+    let value = ctxt.state.enabled_rasterizer_discard;
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        set_enabled(ctxt, gl::RASTERIZER_DISCARD, value);
+    } else if ctxt.extensions.gl_ext_transform_feedback {
+        set_enabled(ctxt, gl::RASTERIZER_DISCARD_EXT, value);
+    }
+
+    let provoking_vertex = ctxt.state.provoking_vertex;
+    if ctxt.version >= &Version(Api::Gl, 3, 2) || ctxt.extensions.gl_arb_provoking_vertex {
+        ctxt.gl.ProvokingVertex(provoking_vertex);
+    } else if ctxt.extensions.gl_ext_provoking_vertex {
+        ctxt.gl.ProvokingVertexEXT(provoking_vertex);
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 4, 0) || ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+       ctxt.extensions.gl_arb_tessellation_shader || ctxt.extensions.gl_oes_tessellation_shader
+    {
+        let vertices = ctxt.state.patch_patch_vertices;
+        ctxt.gl.PatchParameteri(gl::PATCH_VERTICES, vertices);
+    }
+}
+
+/// Restores the pack and unpack alignments.
+unsafe fn restore_pixel_store(ctxt: &mut CommandContext) {
+    let alignment = ctxt.state.pixel_store_unpack_alignment;
+    ctxt.gl.PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+
+    let alignment = ctxt.state.pixel_store_pack_alignment;
+    ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, alignment);
+}
+
+/// Restores the active texture unit and the samplers.
+///
+/// The bind point of the textures is not cached, so the textures bound to each unit are
+/// forgotten instead, which forces glium to bind them again the next time they are used.
+unsafe fn restore_textures(ctxt: &mut CommandContext) {
+    let samplers_supported = ctxt.version >= &Version(Api::Gl, 3, 3) ||
+                             ctxt.extensions.gl_arb_sampler_objects;
+
+    for unit in 0 .. ctxt.state.texture_units.len() {
+        ctxt.state.texture_units[unit].texture = 0;
+
+        if samplers_supported {
+            let sampler = ctxt.state.texture_units[unit].sampler;
+            ctxt.gl.BindSampler(unit as gl::types::GLuint, sampler);
+        }
+    }
+
+    let active_texture = ctxt.state.active_texture;
+    ctxt.gl.ActiveTexture(gl::TEXTURE0 + active_texture);
+}
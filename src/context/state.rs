@@ -45,6 +45,12 @@ pub struct GlState {
     /// Whether GL_POLYGON_OFFSET_FILL is enabled
     pub enabled_polygon_offset_fill: bool,
 
+    /// Whether GL_POLYGON_OFFSET_LINE is enabled
+    pub enabled_polygon_offset_line: bool,
+
+    /// Whether GL_POLYGON_OFFSET_POINT is enabled
+    pub enabled_polygon_offset_point: bool,
+
     /// Whether GL_RASTERIZER_DISCARD is enabled
     pub enabled_rasterizer_discard: bool,
 
@@ -57,6 +63,9 @@ pub struct GlState {
     /// Whether GL_SCISSOR_TEST is enabled
     pub enabled_scissor_test: bool,
 
+    /// Whether GL_SHADER_PIXEL_LOCAL_STORAGE_EXT is enabled
+    pub enabled_shader_pixel_local_storage: bool,
+
     /// Whether GL_STENCIL_TEST is enabled
     pub enabled_stencil_test: bool,
 
@@ -208,6 +217,9 @@ pub struct GlState {
     /// The latest value passed to `glPolygonMode`.
     pub polygon_mode: gl::types::GLenum,
 
+    /// The latest values passed to `glPolygonOffset`, as `(factor, units)`.
+    pub polygon_offset: (gl::types::GLfloat, gl::types::GLfloat),
+
     /// The latest value passed to `glHint` for smoothing.
     pub smooth: (gl::types::GLenum, gl::types::GLenum),
 
@@ -263,6 +275,13 @@ pub struct GlState {
     /// The latest value passed to `glPrimitiveBoundingBox`.
     pub primitive_bounding_box: (f32, f32, f32, f32, f32, f32, f32, f32),
 
+    /// The id of the `PipelineState` whose draw parameters have been entirely synchronized by
+    /// the latest draw call, and the dimensions of the target of that draw call.
+    ///
+    /// Must be reset to `None` by any code that modifies the states controlled by the draw
+    /// parameters.
+    pub pipeline_state: Option<(usize, (u32, u32))>,
+
     /// Current draw call ID.
     /// We maintain a counter that is incremented at each draw call.
     pub next_draw_call_id: u64,
@@ -366,14 +385,17 @@ impl Default for GlState {
             enabled_depth_test: false,
             enabled_depth_clamp_near: false,
             enabled_depth_clamp_far: false,
-            enabled_dither: false,
+            enabled_dither: true,
             enabled_framebuffer_srgb: false,
             enabled_multisample: true,
             enabled_polygon_offset_fill: false,
+            enabled_polygon_offset_line: false,
+            enabled_polygon_offset_point: false,
             enabled_rasterizer_discard: false,
             enabled_sample_alpha_to_coverage: false,
             enabled_sample_coverage: false,
             enabled_scissor_test: false,
+            enabled_shader_pixel_local_storage: false,
             enabled_stencil_test: false,
             enabled_line_smooth: false,
             enabled_polygon_smooth: false,
@@ -423,6 +445,7 @@ impl Default for GlState {
             point_size: 1.0,
             cull_face: gl::BACK,
             polygon_mode: gl::FILL,
+            polygon_offset: (0.0, 0.0),
             smooth: (gl::DONT_CARE, gl::DONT_CARE),
             provoking_vertex: gl::LAST_VERTEX_CONVENTION,
             pixel_store_unpack_alignment: 4,
@@ -440,6 +463,7 @@ impl Default for GlState {
             transform_feedback_enabled: None,
             transform_feedback_paused: false,
             primitive_bounding_box: (-1.0, -1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0),
+            pipeline_state: None,
 
             next_draw_call_id: 1,
             latest_memory_barrier_vertex_attrib_array: 1,
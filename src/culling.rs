@@ -0,0 +1,475 @@
+/*!
+Culls objects on the GPU and draws the ones that are visible with a single multidraw call.
+
+Each object is described by a bounding sphere (an `ObjectBounds`) and by the command that draws
+it (a `DrawCommandIndices`). A compute shader tests each sphere against the frustum of a
+view-projection matrix, and writes the commands of the visible objects at the start of a
+`DrawCommandsIndicesBuffer`. The commands of the culled objects are written at the end of the
+same buffer with an `instance_count` of `0`, so that the whole buffer can be passed to `draw`.
+
+The buffer is not compacted: it always contains one command per object, and the multidraw
+call still goes through the commands of the culled objects, which draw nothing. Culling saves
+the vertex processing of these objects, not the submission of their commands.
+
+The bounds and the commands are stored in buffers, which means that they can be updated without
+going through the CPU, for example by another compute shader.
+
+Culling on the GPU requires compute shaders, shader storage buffers and multidraw indirect
+commands, which are all available with OpenGL 4.3.
+
+# Example
+
+```no_run
+# #[macro_use] extern crate glium;
+# fn main() {
+use glium::Surface;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+use glium::culling::{CullingBuffers, GpuCuller, ObjectBounds};
+use glium::index::{DrawCommandIndices, DrawCommandsIndicesBuffer};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let vertex_buffer: glium::VertexBuffer<u8> = unsafe { ::std::mem::uninitialized() };
+# let index_buffer: glium::IndexBuffer<u16> = unsafe { ::std::mem::uninitialized() };
+# let program: glium::Program = unsafe { ::std::mem::uninitialized() };
+# let bounds: Vec<ObjectBounds> = Vec::new();
+# let commands: Vec<DrawCommandIndices> = Vec::new();
+# let view_projection = [[0.0; 4]; 4];
+
+let culler = GpuCuller::new(&display).unwrap();
+
+let objects = Buffer::new(&display, &bounds[..], BufferType::ShaderStorageBuffer,
+                          BufferMode::Default).unwrap();
+let commands = Buffer::new(&display, &commands[..], BufferType::ShaderStorageBuffer,
+                           BufferMode::Default).unwrap();
+let output = DrawCommandsIndicesBuffer::empty(&display, bounds.len()).unwrap();
+
+let buffers = CullingBuffers { objects: &objects, commands: &commands, output: &output };
+
+let mut target = display.draw();
+culler.cull_and_draw(&mut target, &vertex_buffer, &index_buffer, buffers, view_projection,
+                     &program, &uniform!{ matrix: view_projection }, &Default::default())
+      .unwrap();
+target.finish().unwrap();
+# }
+```
+
+# Custom visibility test
+
+`GpuCuller::with_visibility_function` builds a culler that uses your own visibility test
+instead of the frustum test. The test is a GLSL function with the following signature, which
+returns true if the object is visible:
+
+```glsl
+bool is_visible(vec3 center, float radius);
+```
+
+The function can declare its own uniforms, and their values are passed to
+`cull_with_uniforms`.
+
+*/
+use std::error::Error;
+use std::fmt;
+
+use backend::Facade;
+use buffer::{Buffer, BufferCreationError, BufferMode, BufferType, ReadError};
+use index::{DrawCommandIndices, DrawCommandsIndicesBuffer, Index, IndexBuffer};
+use program::{BlockLayout, ComputeShader, ProgramCreationError, UniformBlock as BlockInfo};
+use uniforms::{AsUniformValue, EmptyUniforms, LayoutMismatchError, UniformBlock};
+use uniforms::{UniformValue, Uniforms};
+use vertex::MultiVerticesSource;
+use version::{Api, Version};
+use CapabilitiesSource;
+use DrawError;
+use DrawParameters;
+use Program;
+use Surface;
+
+/// Number of objects that are processed by each work group.
+const WORK_GROUP_SIZE: usize = 64;
+
+/// Bounding sphere of an object.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ObjectBounds {
+    /// Center of the sphere.
+    pub center: [f32; 3],
+    /// Radius of the sphere.
+    pub radius: f32,
+}
+
+implement_uniform_block!(ObjectBounds, center, radius);
+
+/// Content of the buffer that counts the visible and the culled objects.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct Counters {
+    visible: u32,
+    culled: u32,
+}
+
+implement_uniform_block!(Counters, visible, culled);
+
+/// The buffers used by a culling pass.
+///
+/// The three buffers must have the same length. The command at index `i` of `commands` draws
+/// the object whose bounds are at index `i` of `objects`.
+#[derive(Copy, Clone)]
+pub struct CullingBuffers<'a> {
+    /// The bounding spheres of the objects.
+    pub objects: &'a Buffer<[ObjectBounds]>,
+
+    /// The commands that draw the objects.
+    pub commands: &'a Buffer<[DrawCommandIndices]>,
+
+    /// The buffer where the commands are written after culling.
+    pub output: &'a DrawCommandsIndicesBuffer,
+}
+
+/// Error that can happen when creating a `GpuCuller`.
+#[derive(Clone, Debug)]
+pub enum CreationError {
+    /// Culling on the GPU is not supported by the backend.
+    NotSupported,
+
+    /// Error while compiling the compute shader.
+    ProgramCreationError(ProgramCreationError),
+
+    /// Error while creating the buffer of the counters.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CreationError::ProgramCreationError(ref err) => err.fmt(fmt),
+            &CreationError::BufferCreationError(ref err) => err.fmt(fmt),
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::NotSupported => "Culling on the GPU is not supported by the backend",
+            &CreationError::ProgramCreationError(_) => "Error while compiling the compute shader",
+            &CreationError::BufferCreationError(_) => "Error while creating the buffer of the \
+                                                       counters",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::ProgramCreationError(ref err) => Some(err),
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProgramCreationError> for CreationError {
+    #[inline]
+    fn from(err: ProgramCreationError) -> CreationError {
+        CreationError::ProgramCreationError(err)
+    }
+}
+
+impl From<BufferCreationError> for CreationError {
+    #[inline]
+    fn from(err: BufferCreationError) -> CreationError {
+        CreationError::BufferCreationError(err)
+    }
+}
+
+/// Error that can happen when culling or drawing objects.
+#[derive(Clone, Debug)]
+pub enum CullingError {
+    /// The buffers of a `CullingBuffers` don't have the same length.
+    LengthMismatch,
+
+    /// There are more objects than the number of work groups that can be dispatched.
+    TooManyObjects,
+
+    /// Error while drawing the visible objects.
+    DrawError(DrawError),
+}
+
+impl fmt::Display for CullingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CullingError::DrawError(ref err) => err.fmt(fmt),
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for CullingError {
+    fn description(&self) -> &str {
+        match self {
+            &CullingError::LengthMismatch => "The buffers of the objects, of the commands and \
+                                              of the output don't have the same length",
+            &CullingError::TooManyObjects => "There are more objects than the number of work \
+                                              groups that can be dispatched",
+            &CullingError::DrawError(_) => "Error while drawing the visible objects",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CullingError::DrawError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<DrawError> for CullingError {
+    #[inline]
+    fn from(err: DrawError) -> CullingError {
+        CullingError::DrawError(err)
+    }
+}
+
+/// Culls objects with a compute shader.
+///
+/// See the documentation of the module.
+pub struct GpuCuller {
+    program: ComputeShader,
+    counters: Buffer<Counters>,
+}
+
+impl GpuCuller {
+    /// Returns true if the backend supports culling on the GPU.
+    pub fn is_supported<C>(caps: &C) -> bool where C: CapabilitiesSource {
+        ComputeShader::is_supported(caps) &&
+        caps.get_capabilities().max_indexed_shader_storage_buffer >= 4 &&
+        (caps.get_version() >= &Version(Api::Gl, 4, 3) ||
+         caps.get_extensions().gl_arb_multi_draw_indirect)
+    }
+
+    /// Builds a culler that tests the bounding spheres against the frustum of a
+    /// view-projection matrix.
+    #[inline]
+    pub fn new<F>(facade: &F) -> Result<GpuCuller, CreationError> where F: Facade {
+        GpuCuller::with_visibility_function(facade, FRUSTUM_TEST)
+    }
+
+    /// Builds a culler that uses a custom visibility test.
+    ///
+    /// `function` is the GLSL source code of a function named `is_visible`. See the
+    /// documentation of the module.
+    pub fn with_visibility_function<F>(facade: &F, function: &str)
+                                       -> Result<GpuCuller, CreationError> where F: Facade
+    {
+        if !GpuCuller::is_supported(facade) {
+            return Err(CreationError::NotSupported);
+        }
+
+        let source = format!("{}\n{}\n{}", HEADER, function, MAIN);
+        let program = try!(ComputeShader::from_source(facade, &source));
+
+        let counters = try!(Buffer::new(facade, &Counters { visible: 0, culled: 0 },
+                                        BufferType::ShaderStorageBuffer, BufferMode::Dynamic));
+
+        Ok(GpuCuller {
+            program: program,
+            counters: counters,
+        })
+    }
+
+    /// Tests each object against the frustum of `view_projection`, and writes the commands in
+    /// the output buffer.
+    ///
+    /// The commands of the visible objects are written at the start of the output buffer, in
+    /// no particular order, and the commands of the culled objects are written after them
+    /// with an `instance_count` of `0`.
+    #[inline]
+    pub fn cull(&self, buffers: CullingBuffers, view_projection: [[f32; 4]; 4])
+                -> Result<(), CullingError>
+    {
+        self.cull_with_uniforms(buffers, view_projection, &EmptyUniforms)
+    }
+
+    /// Same as `cull`, but passes additional uniforms to the visibility function.
+    pub fn cull_with_uniforms<U>(&self, buffers: CullingBuffers, view_projection: [[f32; 4]; 4],
+                                 uniforms: &U) -> Result<(), CullingError> where U: Uniforms
+    {
+        let len = buffers.objects.len();
+        if buffers.commands.len() != len || buffers.output.len() != len {
+            return Err(CullingError::LengthMismatch);
+        }
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        let groups = (len + WORK_GROUP_SIZE - 1) / WORK_GROUP_SIZE;
+        let max_groups = buffers.objects.get_context().get_capabilities()
+                                .max_compute_work_group_count.0 as usize;
+        if groups > max_groups {
+            return Err(CullingError::TooManyObjects);
+        }
+
+        self.counters.write(&Counters { visible: 0, culled: 0 });
+
+        let uniforms = CullingUniforms {
+            objects: ArrayBlock(buffers.objects),
+            commands: ArrayBlock(buffers.commands),
+            output: ArrayBlock(buffers.output),
+            counters: &self.counters,
+            objects_count: len as u32,
+            view_projection: view_projection,
+            rest: uniforms,
+        };
+
+        self.program.execute(uniforms, groups as u32, 1, 1);
+        Ok(())
+    }
+
+    /// Culls the objects, then draws the visible ones with a single multidraw call.
+    ///
+    /// The multidraw call contains the commands of all the objects, but the ones of the culled
+    /// objects have an `instance_count` of `0`.
+    pub fn cull_and_draw<'a, S, V, I, U>(&self, surface: &mut S, vertices: V,
+                                         indices: &IndexBuffer<I>, buffers: CullingBuffers,
+                                         view_projection: [[f32; 4]; 4], program: &Program,
+                                         uniforms: &U, draw_parameters: &DrawParameters)
+                                         -> Result<(), CullingError>
+                                         where S: Surface, V: MultiVerticesSource<'a>,
+                                               I: Index, U: Uniforms
+    {
+        try!(self.cull(buffers, view_projection));
+        try!(surface.draw(vertices, buffers.output.with_index_buffer(indices), program,
+                          uniforms, draw_parameters));
+        Ok(())
+    }
+
+    /// Reads the number of objects that were visible during the latest culling pass.
+    ///
+    /// This waits until the GPU has finished culling, so you should only call it for debugging
+    /// or statistics.
+    #[inline]
+    pub fn read_visible_count(&self) -> Result<u32, ReadError> {
+        self.counters.read().map(|counters| counters.visible)
+    }
+}
+
+/// Binds a buffer of `T`s to a block whose only member is an unsized array of `T`.
+struct ArrayBlock<'a, T: 'a>(&'a Buffer<[T]>);
+
+impl<'a, T> AsUniformValue for ArrayBlock<'a, T> where T: UniformBlock + Copy {
+    fn as_uniform_value(&self) -> UniformValue {
+        fn f<T>(block: &BlockInfo) -> Result<(), LayoutMismatchError> where T: UniformBlock {
+            match block.layout {
+                BlockLayout::Struct { ref members } if members.len() == 1 => {
+                    <[T] as UniformBlock>::matches(&members[0].1, 0)
+                },
+                ref layout => Err(LayoutMismatchError::LayoutMismatch {
+                    expected: layout.clone(),
+                    obtained: BlockLayout::Struct {
+                        members: vec![("<array>".to_owned(),
+                                       <[T] as UniformBlock>::build_layout(0))],
+                    },
+                }),
+            }
+        }
+
+        UniformValue::Block(self.0.as_slice_any(), f::<T>)
+    }
+}
+
+/// The uniforms of the compute shader, followed by the uniforms of the user.
+struct CullingUniforms<'a, U: 'a> {
+    objects: ArrayBlock<'a, ObjectBounds>,
+    commands: ArrayBlock<'a, DrawCommandIndices>,
+    output: ArrayBlock<'a, DrawCommandIndices>,
+    counters: &'a Buffer<Counters>,
+    objects_count: u32,
+    view_projection: [[f32; 4]; 4],
+    rest: &'a U,
+}
+
+impl<'a, U> Uniforms for CullingUniforms<'a, U> where U: Uniforms {
+    fn visit_values<'b, F: FnMut(&str, UniformValue<'b>)>(&'b self, mut output: F) {
+        output("CullingObjects", self.objects.as_uniform_value());
+        output("CullingCommands", self.commands.as_uniform_value());
+        output("CullingOutput", self.output.as_uniform_value());
+        output("CullingCounters", self.counters.as_uniform_value());
+        output("objects_count", self.objects_count.as_uniform_value());
+        output("view_projection", self.view_projection.as_uniform_value());
+        self.rest.visit_values(output);
+    }
+}
+
+const HEADER: &'static str = "
+    #version 430
+
+    layout(local_size_x = 64) in;
+
+    struct ObjectBounds {
+        vec3 center;
+        float radius;
+    };
+
+    struct DrawCommand {
+        uint count;
+        uint instance_count;
+        uint first_index;
+        uint base_vertex;
+        uint base_instance;
+    };
+
+    layout(std430) buffer CullingObjects {
+        ObjectBounds objects[];
+    };
+
+    layout(std430) buffer CullingCommands {
+        DrawCommand commands[];
+    };
+
+    layout(std430) buffer CullingOutput {
+        DrawCommand output_commands[];
+    };
+
+    layout(std430) buffer CullingCounters {
+        uint visible;
+        uint culled;
+    };
+
+    uniform uint objects_count;
+    uniform mat4 view_projection;
+";
+
+const FRUSTUM_TEST: &'static str = "
+    bool is_visible(vec3 center, float radius) {
+        mat4 rows = transpose(view_projection);
+
+        vec4 planes[6] = vec4[6](rows[3] + rows[0], rows[3] - rows[0],
+                                 rows[3] + rows[1], rows[3] - rows[1],
+                                 rows[3] + rows[2], rows[3] - rows[2]);
+
+        for (int i = 0; i < 6; ++i) {
+            if (dot(planes[i].xyz, center) + planes[i].w < -radius * length(planes[i].xyz)) {
+                return false;
+            }
+        }
+
+        return true;
+    }
+";
+
+const MAIN: &'static str = "
+    void main() {
+        uint id = gl_GlobalInvocationID.x;
+        if (id >= objects_count) {
+            return;
+        }
+
+        DrawCommand command = commands[id];
+
+        if (is_visible(objects[id].center, objects[id].radius)) {
+            output_commands[atomicAdd(visible, 1u)] = command;
+        } else {
+            command.instance_count = 0u;
+            output_commands[objects_count - 1u - atomicAdd(culled, 1u)] = command;
+        }
+    }
+";
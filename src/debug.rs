@@ -1,5 +1,37 @@
 /*!
 
+Debugging utilities.
+
+# Error checking
+
+By default glium doesn't call `glGetError`. See `ErrorCheckPolicy` for how to change this.
+
+# State cache checking
+
+glium keeps a cache of the state of the OpenGL context. If some code that doesn't go through
+glium modifies the state without restoring it, the cache no longer matches the actual state.
+`Context::check_state_cache` reads back the main states and returns the list of differences.
+See also `StateCheckPolicy` to do this automatically.
+
+# Debug messages
+
+If the backend supports `GL_KHR_debug` or `GL_ARB_debug_output`, you can register a callback
+that receives the messages emitted by the OpenGL implementation with
+`Context::set_debug_callback`. Messages can be filtered with
+`Context::set_debug_messages_enabled` and muted individually with
+`Context::set_debug_message_ids_enabled`.
+
+```no_run
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+use glium::debug::Severity;
+
+display.set_debug_callback(|source, ty, severity, id, message| {
+    println!("{:?} {:?} {:?} #{}: {}", source, ty, severity, id, message);
+}).unwrap();
+
+// ignoring notifications
+display.set_debug_messages_enabled(None, None, Some(Severity::Notification), false).unwrap();
+```
 
 */
 
@@ -9,6 +41,8 @@ use ContextExt;
 use version::Api;
 use version::Version;
 use gl;
+use std::error::Error;
+use std::fmt;
 use std::rc::Rc;
 use std::mem;
 
@@ -32,7 +66,7 @@ pub enum Severity {
 }
 
 /// Source of a debug message.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Source {
     /// Calls to the OpenGL API.
@@ -57,7 +91,7 @@ pub enum Source {
 }
 
 /// Type of a debug message.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum MessageType {
     /// An error, typically from the API
@@ -80,6 +114,108 @@ pub enum MessageType {
     Other = gl::DEBUG_TYPE_OTHER,
 }
 
+/// Policy for checking OpenGL errors with `glGetError`.
+///
+/// Calling `glGetError` can force a synchronization with the driver, which is why errors are
+/// not checked by default. The policy can be changed with `Context::set_error_check_policy`,
+/// or when the context is created with the `GLIUM_ERROR_CHECK` environment variable, whose
+/// value can be `never`, `draw` or `all`.
+///
+/// The errors are sent to the callback set with `Context::set_debug_callback`, with the `Api`
+/// source, the `Error` type, the `High` severity, and the value returned by `glGetError` as
+/// id. If there is no callback, they are printed on stdout. glium doesn't panic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCheckPolicy {
+    /// Never checks for errors. This is the default.
+    Never,
+
+    /// Checks for errors after each draw command.
+    EveryDraw,
+
+    /// Checks for errors after each OpenGL function called by glium, including the ones
+    /// called by `Context::exec_with_raw_gl`. The message contains the name of the function
+    /// that generated the error. The errors are reported when glium's function returns, and
+    /// the first of them stays pending for `Context::assert_no_error`.
+    EveryCall,
+}
+
+/// Policy for comparing glium's state cache with the actual state of the OpenGL context.
+///
+/// Reading back the state is slow, so this is meant to be used while debugging. The policy can
+/// be changed with `Context::set_state_check_policy`, or when the context is created with the
+/// `GLIUM_STATE_CHECK` environment variable, whose value can be `never` or `swap`.
+///
+/// The differences are sent to the callback set with `Context::set_debug_callback`, with the
+/// `Application` source, the `Error` type, the `High` severity, and `0` as id. If there is no
+/// callback, they are printed on stdout. glium doesn't panic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateCheckPolicy {
+    /// Never compares the states. This is the default.
+    Never,
+
+    /// Compares the states each time the buffers are swapped.
+    EverySwap,
+}
+
+/// Counters of the operations that have been executed since the start of the frame.
+///
+/// Statistics are disabled by default, and can be enabled with
+/// `Context::set_statistics_enabled`. They are then reset each time a `Frame` is created, and
+/// can be obtained with `Frame::statistics`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStatistics {
+    /// Number of draw commands.
+    pub draw_calls: u64,
+
+    /// Number of times the current program has been changed.
+    pub program_binds: u64,
+
+    /// Number of times a texture has been bound to a texture unit.
+    pub texture_binds: u64,
+
+    /// Number of times the read or draw framebuffer has been changed.
+    pub framebuffer_binds: u64,
+
+    /// Number of bytes sent to buffers when creating or writing them.
+    pub buffer_upload_bytes: u64,
+
+    /// Number of bytes sent to textures when creating or writing them.
+    pub texture_upload_bytes: u64,
+}
+
+/// A state whose value in glium's cache is different from its actual value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateMismatch {
+    /// Name of the state, for example `GL_BLEND`.
+    pub name: &'static str,
+
+    /// Value of the state in glium's cache.
+    pub cached: String,
+
+    /// Value of the state returned by OpenGL.
+    pub actual: String,
+}
+
+impl fmt::Display for StateMismatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{} is {} but glium expected {}", self.name, self.actual, self.cached)
+    }
+}
+
+impl Error for StateMismatch {
+    fn description(&self) -> &str {
+        "The value of a state in glium's cache is different from its actual value"
+    }
+}
+
+/// Function that receives the debug messages of the OpenGL implementation.
+///
+/// The parameters are the source, type, severity, identifier and content of the message.
+///
+/// The callback is called synchronously from inside glium's functions. It must not call
+/// any function of the context.
+pub type DebugCallback = Box<FnMut(Source, MessageType, Severity, u32, &str)>;
+
 /// Allows you to obtain the timestamp inside the OpenGL commands queue.
 ///
 /// When you call functions in glium, they are not instantly executed. Instead they are
@@ -0,0 +1,519 @@
+/*!
+Draws textures into a corner of a surface, in order to inspect intermediate render targets.
+
+The `DebugOverlay` owns the programs and the vertex buffer that it needs, so it should be
+created once and reused.
+
+Color textures are drawn as they are. Depth textures are drawn in grayscale, optionally after
+being linearized with the near and far planes of the perspective projection that was used to
+render them. Integral and unsigned textures are drawn with a palette of 8 colors, picked with
+the value of the red component modulo 8. Layers of array textures and individual mipmap levels
+can be selected as well.
+
+Drawing integral, unsigned and array textures, and selecting a mipmap level, require GLSL 1.40.
+
+# Example
+
+```no_run
+use glium::Surface;
+use glium::debug_overlay::{Corner, DebugOverlay, OverlayOptions, OverlaySource};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let shadow_map: glium::texture::DepthTexture2d = unsafe { ::std::mem::uninitialized() };
+
+let overlay = DebugOverlay::new(&display).unwrap();
+
+let mut target = display.draw();
+// ... draw the scene ...
+overlay.draw(&mut target, OverlaySource::Depth { texture: &shadow_map, linearize: None },
+             &OverlayOptions { corner: Corner::TopRight, .. Default::default() }).unwrap();
+target.finish().unwrap();
+```
+
+*/
+use std::error::Error;
+use std::fmt;
+
+use backend::Facade;
+use index::{NoIndices, PrimitiveType};
+use program::{Program, ProgramCreationError};
+use texture::{DepthTexture2d, IntegralTexture2d, Texture2d, Texture2dArray, UnsignedTexture2d};
+use uniforms::{MagnifySamplerFilter, MinifySamplerFilter};
+use vertex::{BufferCreationError, VertexBuffer};
+use version::{Api, Version};
+use DrawError;
+use Surface;
+
+/// Corner of the surface in which a texture is drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Corner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+/// Texture to draw, and how to interpret its content.
+#[derive(Copy, Clone)]
+pub enum OverlaySource<'a> {
+    /// A floating-point color texture.
+    Color(&'a Texture2d),
+
+    /// One layer of a floating-point array texture.
+    ColorLayer(&'a Texture2dArray, u32),
+
+    /// A depth texture.
+    Depth {
+        /// The texture.
+        texture: &'a DepthTexture2d,
+        /// If `Some`, contains the distances of the near and far planes of the perspective
+        /// projection. The depth is converted to a linear distance between these planes.
+        linearize: Option<(f32, f32)>,
+    },
+
+    /// An integral texture, drawn with a palette.
+    Integral(&'a IntegralTexture2d),
+
+    /// An unsigned texture, drawn with a palette.
+    Unsigned(&'a UnsignedTexture2d),
+}
+
+/// Where and how a texture is drawn.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OverlayOptions {
+    /// The corner of the surface in which the texture is drawn.
+    ///
+    /// The default value is `Corner::BottomLeft`.
+    pub corner: Corner,
+
+    /// The size of the overlay, as a fraction of the dimensions of the surface.
+    ///
+    /// The default value is `0.25`.
+    pub size: f32,
+
+    /// The mipmap level to draw.
+    ///
+    /// The default value is `0`.
+    pub level: u32,
+}
+
+impl Default for OverlayOptions {
+    #[inline]
+    fn default() -> OverlayOptions {
+        OverlayOptions {
+            corner: Corner::BottomLeft,
+            size: 0.25,
+            level: 0,
+        }
+    }
+}
+
+/// Error that can happen when creating a `DebugOverlay`.
+#[derive(Clone, Debug)]
+pub enum CreationError {
+    /// Error while compiling one of the programs.
+    ProgramCreationError(ProgramCreationError),
+
+    /// Error while creating the vertex buffer.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CreationError::ProgramCreationError(ref err) => err.fmt(fmt),
+            &CreationError::BufferCreationError(ref err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::ProgramCreationError(_) => "Error while compiling one of the programs",
+            &CreationError::BufferCreationError(_) => "Error while creating the vertex buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::ProgramCreationError(ref err) => Some(err),
+            &CreationError::BufferCreationError(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<ProgramCreationError> for CreationError {
+    #[inline]
+    fn from(err: ProgramCreationError) -> CreationError {
+        CreationError::ProgramCreationError(err)
+    }
+}
+
+impl From<BufferCreationError> for CreationError {
+    #[inline]
+    fn from(err: BufferCreationError) -> CreationError {
+        CreationError::BufferCreationError(err)
+    }
+}
+
+/// Error that can happen when drawing an overlay.
+#[derive(Clone, Debug)]
+pub enum OverlayError {
+    /// Drawing this kind of texture requires GLSL 1.40, which is not supported by the backend.
+    SourceNotSupported,
+
+    /// Error while drawing the overlay.
+    DrawError(DrawError),
+}
+
+impl fmt::Display for OverlayError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &OverlayError::DrawError(ref err) => err.fmt(fmt),
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for OverlayError {
+    fn description(&self) -> &str {
+        match self {
+            &OverlayError::SourceNotSupported => "Drawing this kind of texture is not supported \
+                                                  by the backend",
+            &OverlayError::DrawError(_) => "Error while drawing the overlay",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &OverlayError::DrawError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<DrawError> for OverlayError {
+    #[inline]
+    fn from(err: DrawError) -> OverlayError {
+        OverlayError::DrawError(err)
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+/// Draws textures into a corner of a surface.
+///
+/// See the documentation of the module.
+pub struct DebugOverlay {
+    vertex_buffer: VertexBuffer<Vertex>,
+    color: Program,
+    depth: Program,
+
+    /// These programs require GLSL 1.40.
+    color_layer: Option<Program>,
+    integral: Option<Program>,
+    unsigned: Option<Program>,
+
+    /// True if the programs can select a mipmap level.
+    supports_level: bool,
+}
+
+impl DebugOverlay {
+    /// Builds the programs and the vertex buffer of the overlay.
+    pub fn new<F>(facade: &F) -> Result<DebugOverlay, CreationError> where F: Facade {
+        let context = facade.get_context();
+
+        let vertex_buffer = try!(VertexBuffer::new(facade, &[
+            Vertex { position: [0.0, 0.0] },
+            Vertex { position: [1.0, 0.0] },
+            Vertex { position: [0.0, 1.0] },
+            Vertex { position: [1.0, 1.0] },
+        ]));
+
+        if !context.is_glsl_version_supported(&Version(Api::Gl, 1, 40)) {
+            return Ok(DebugOverlay {
+                vertex_buffer: vertex_buffer,
+                color: try!(Program::from_source(facade, VERTEX_110, COLOR_110, None)),
+                depth: try!(Program::from_source(facade, VERTEX_110, DEPTH_110, None)),
+                color_layer: None,
+                integral: None,
+                unsigned: None,
+                supports_level: false,
+            });
+        }
+
+        Ok(DebugOverlay {
+            vertex_buffer: vertex_buffer,
+            color: try!(Program::from_source(facade, VERTEX_140, COLOR_140, None)),
+            depth: try!(Program::from_source(facade, VERTEX_140, DEPTH_140, None)),
+            color_layer: Some(try!(Program::from_source(facade, VERTEX_140, COLOR_LAYER_140,
+                                                        None))),
+            integral: Some(try!(Program::from_source(facade, VERTEX_140, INTEGRAL_140, None))),
+            unsigned: Some(try!(Program::from_source(facade, VERTEX_140, UNSIGNED_140, None))),
+            supports_level: true,
+        })
+    }
+
+    /// Draws a texture into a corner of a surface.
+    ///
+    /// Returns `SourceNotSupported` if the source is not supported by the backend, or if a
+    /// mipmap level other than 0 is requested and the backend can't select it.
+    pub fn draw<S>(&self, surface: &mut S, source: OverlaySource, options: &OverlayOptions)
+                   -> Result<(), OverlayError> where S: Surface
+    {
+        if options.level != 0 && !self.supports_level {
+            return Err(OverlayError::SourceNotSupported);
+        }
+
+        let size = options.size * 2.0;
+        let (left, bottom) = match options.corner {
+            Corner::TopLeft => (-1.0, 1.0 - size),
+            Corner::TopRight => (1.0 - size, 1.0 - size),
+            Corner::BottomLeft => (-1.0, -1.0),
+            Corner::BottomRight => (1.0 - size, -1.0),
+        };
+
+        let rect = [left, bottom, size, size];
+        let level = options.level as f32;
+        let indices = NoIndices(PrimitiveType::TriangleStrip);
+        let params = Default::default();
+
+        match source {
+            OverlaySource::Color(texture) => {
+                let uniforms = uniform! {
+                    rect: rect,
+                    level: level,
+                    tex: texture.sampled().minify_filter(MinifySamplerFilter::NearestMipmapNearest)
+                                          .magnify_filter(MagnifySamplerFilter::Nearest),
+                };
+
+                try!(surface.draw(&self.vertex_buffer, &indices, &self.color, &uniforms,
+                                  &params));
+            },
+
+            OverlaySource::ColorLayer(texture, layer) => {
+                let program = match self.color_layer {
+                    Some(ref p) => p,
+                    None => return Err(OverlayError::SourceNotSupported),
+                };
+
+                let uniforms = uniform! {
+                    rect: rect,
+                    level: level,
+                    layer: layer as f32,
+                    tex: texture.sampled().minify_filter(MinifySamplerFilter::NearestMipmapNearest)
+                                          .magnify_filter(MagnifySamplerFilter::Nearest),
+                };
+
+                try!(surface.draw(&self.vertex_buffer, &indices, program, &uniforms, &params));
+            },
+
+            OverlaySource::Depth { texture, linearize } => {
+                let uniforms = uniform! {
+                    rect: rect,
+                    level: level,
+                    linearize: linearize.is_some(),
+                    range: match linearize { Some((n, f)) => [n, f], None => [0.0, 1.0] },
+                    tex: texture.sampled().minify_filter(MinifySamplerFilter::NearestMipmapNearest)
+                                          .magnify_filter(MagnifySamplerFilter::Nearest),
+                };
+
+                try!(surface.draw(&self.vertex_buffer, &indices, &self.depth, &uniforms,
+                                  &params));
+            },
+
+            OverlaySource::Integral(texture) => {
+                let program = match self.integral {
+                    Some(ref p) => p,
+                    None => return Err(OverlayError::SourceNotSupported),
+                };
+
+                let uniforms = uniform! {
+                    rect: rect,
+                    level: level,
+                    tex: texture.sampled().minify_filter(MinifySamplerFilter::NearestMipmapNearest)
+                                          .magnify_filter(MagnifySamplerFilter::Nearest),
+                };
+
+                try!(surface.draw(&self.vertex_buffer, &indices, program, &uniforms, &params));
+            },
+
+            OverlaySource::Unsigned(texture) => {
+                let program = match self.unsigned {
+                    Some(ref p) => p,
+                    None => return Err(OverlayError::SourceNotSupported),
+                };
+
+                let uniforms = uniform! {
+                    rect: rect,
+                    level: level,
+                    tex: texture.sampled().minify_filter(MinifySamplerFilter::NearestMipmapNearest)
+                                          .magnify_filter(MagnifySamplerFilter::Nearest),
+                };
+
+                try!(surface.draw(&self.vertex_buffer, &indices, program, &uniforms, &params));
+            },
+        }
+
+        Ok(())
+    }
+}
+
+const VERTEX_110: &'static str = "
+    #version 110
+
+    uniform vec4 rect;
+
+    attribute vec2 position;
+    varying vec2 v_tex_coords;
+
+    void main() {
+        gl_Position = vec4(rect.xy + position * rect.zw, 0.0, 1.0);
+        v_tex_coords = position;
+    }
+";
+
+const COLOR_110: &'static str = "
+    #version 110
+
+    uniform sampler2D tex;
+    varying vec2 v_tex_coords;
+
+    void main() {
+        gl_FragColor = vec4(texture2D(tex, v_tex_coords).rgb, 1.0);
+    }
+";
+
+const DEPTH_110: &'static str = "
+    #version 110
+
+    uniform sampler2D tex;
+    uniform bool linearize;
+    uniform vec2 range;
+    varying vec2 v_tex_coords;
+
+    void main() {
+        float depth = texture2D(tex, v_tex_coords).r;
+
+        if (linearize) {
+            float z = depth * 2.0 - 1.0;
+            float distance = 2.0 * range.x * range.y /
+                             (range.y + range.x - z * (range.y - range.x));
+            depth = (distance - range.x) / (range.y - range.x);
+        }
+
+        gl_FragColor = vec4(vec3(depth), 1.0);
+    }
+";
+
+const VERTEX_140: &'static str = "
+    #version 140
+
+    uniform vec4 rect;
+
+    in vec2 position;
+    out vec2 v_tex_coords;
+
+    void main() {
+        gl_Position = vec4(rect.xy + position * rect.zw, 0.0, 1.0);
+        v_tex_coords = position;
+    }
+";
+
+const COLOR_140: &'static str = "
+    #version 140
+
+    uniform sampler2D tex;
+    uniform float level;
+    in vec2 v_tex_coords;
+    out vec4 color;
+
+    void main() {
+        color = vec4(textureLod(tex, v_tex_coords, level).rgb, 1.0);
+    }
+";
+
+const COLOR_LAYER_140: &'static str = "
+    #version 140
+
+    uniform sampler2DArray tex;
+    uniform float level;
+    uniform float layer;
+    in vec2 v_tex_coords;
+    out vec4 color;
+
+    void main() {
+        color = vec4(textureLod(tex, vec3(v_tex_coords, layer), level).rgb, 1.0);
+    }
+";
+
+const DEPTH_140: &'static str = "
+    #version 140
+
+    uniform sampler2D tex;
+    uniform float level;
+    uniform bool linearize;
+    uniform vec2 range;
+    in vec2 v_tex_coords;
+    out vec4 color;
+
+    void main() {
+        float depth = textureLod(tex, v_tex_coords, level).r;
+
+        if (linearize) {
+            float z = depth * 2.0 - 1.0;
+            float distance = 2.0 * range.x * range.y /
+                             (range.y + range.x - z * (range.y - range.x));
+            depth = (distance - range.x) / (range.y - range.x);
+        }
+
+        color = vec4(vec3(depth), 1.0);
+    }
+";
+
+const INTEGRAL_140: &'static str = "
+    #version 140
+
+    uniform isampler2D tex;
+    uniform float level;
+    in vec2 v_tex_coords;
+    out vec4 color;
+
+    const vec3 palette[8] = vec3[8](
+        vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0),
+        vec3(1.0, 1.0, 0.0), vec3(1.0, 0.0, 1.0), vec3(0.0, 1.0, 1.0), vec3(1.0, 1.0, 1.0)
+    );
+
+    void main() {
+        int value = textureLod(tex, v_tex_coords, level).r;
+        color = vec4(palette[abs(value) % 8], 1.0);
+    }
+";
+
+const UNSIGNED_140: &'static str = "
+    #version 140
+
+    uniform usampler2D tex;
+    uniform float level;
+    in vec2 v_tex_coords;
+    out vec4 color;
+
+    const vec3 palette[8] = vec3[8](
+        vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0),
+        vec3(1.0, 1.0, 0.0), vec3(1.0, 0.0, 1.0), vec3(0.0, 1.0, 1.0), vec3(1.0, 1.0, 1.0)
+    );
+
+    void main() {
+        uint value = textureLod(tex, v_tex_coords, level).r;
+        color = vec4(palette[int(value % 8u)], 1.0);
+    }
+";
@@ -74,6 +74,7 @@ use std::ops::Range;
 pub use self::query::{QueryCreationError};
 pub use self::query::{SamplesPassedQuery, TimeElapsedQuery, PrimitivesGeneratedQuery};
 pub use self::query::{AnySamplesPassedQuery, TransformFeedbackPrimitivesWrittenQuery};
+pub use self::query::TimestampQuery;
 
 mod query;
 
@@ -569,6 +570,46 @@ impl ToGlEnum for PolygonMode {
     }
 }
 
+/// Offsets the depth values of the polygons that are drawn.
+///
+/// The offset that is added to the depth of each sample is `factor * dz + units * r`, where `dz`
+/// is the slope of the depth of the polygon and `r` is the smallest value that is guaranteed to
+/// produce a difference in the depth buffer. This is mostly used to avoid "shadow acne" when
+/// rendering shadow maps, or to draw decals and wireframes on top of existing polygons.
+///
+/// The offset can be enabled separately for each `PolygonMode`. OpenGL ES only supports
+/// offsetting filled polygons, and `point` and `line` are ignored there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PolygonOffset {
+    /// Scale factor of the slope of the depth of the polygon.
+    pub factor: f32,
+
+    /// Multiplied by the smallest difference between two depth values.
+    pub units: f32,
+
+    /// Whether the offset is applied when the polygon mode is `Point`.
+    pub point: bool,
+
+    /// Whether the offset is applied when the polygon mode is `Line`.
+    pub line: bool,
+
+    /// Whether the offset is applied when the polygon mode is `Fill`.
+    pub fill: bool,
+}
+
+impl Default for PolygonOffset {
+    #[inline]
+    fn default() -> PolygonOffset {
+        PolygonOffset {
+            factor: 0.0,
+            units: 0.0,
+            point: false,
+            line: false,
+            fill: false,
+        }
+    }
+}
+
 /// Specifies a hint for the smoothing.
 ///
 /// Note that this is just a hint and the driver may disregard it.
@@ -607,6 +648,43 @@ pub enum ProvokingVertex {
     FirstVertex,
 }
 
+/// Features that glium is allowed to emulate when the backend doesn't support them.
+///
+/// Some draw parameters are not supported by OpenGL ES (and thus ANGLE), which only allows
+/// glium to return an error. Each field of this struct allows glium to instead emulate the
+/// corresponding feature by rewriting the list of indices before drawing.
+///
+/// Index buffers and multidraw commands are read back from video memory in order to be
+/// rewritten, which costs a round-trip to the GPU at each draw call. The usual error is
+/// returned in the following situations:
+///
+/// - The indices are in an index buffer or a multidraw buffer and the backend is OpenGL ES 2,
+///   which can't read buffers back.
+/// - A multidraw command draws more than one instance or doesn't start at instance 0, or the
+///   draw call uses per-instance vertices sources together with multidraw commands.
+///
+/// `SamplerWrapFunction::ClampToBorder` is not part of the emulated features. It is only
+/// available with OpenGL ES 3.2 or the `GL_EXT_texture_border_clamp` or
+/// `GL_OES_texture_border_clamp` extensions, as emulating it would require modifying the
+/// shaders of the program.
+///
+/// Use `Context::set_emulation_options` to change them. Everything is disabled by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct EmulationOptions {
+    /// If true, `PolygonMode::Line` and `PolygonMode::Point` are emulated when `glPolygonMode`
+    /// is not available by drawing the edges or the vertices of each triangle instead.
+    ///
+    /// Note that `line_width` and `point_size` apply to the emulated primitives, and that
+    /// face culling is not applied to them.
+    pub polygon_mode: bool,
+
+    /// If true, `ProvokingVertex::FirstVertex` is emulated when it is not supported by
+    /// rotating the indices of each primitive so that the first vertex comes last.
+    ///
+    /// The winding order of triangles is preserved.
+    pub provoking_vertex: bool,
+}
+
 /// Represents the parameters to use when drawing.
 ///
 /// Example:
@@ -795,9 +873,15 @@ pub struct DrawParameters<'a> {
 
     /// How to render polygons. The default value is `Fill`.
     ///
-    /// See the documentation of `PolygonMode` for more infos.
+    /// See the documentation of `PolygonMode` for more infos. OpenGL ES only supports `Fill`,
+    /// unless emulation is enabled with `Context::set_emulation_options`.
     pub polygon_mode: PolygonMode,
 
+    /// Offset applied to the depth values of polygons. The default value disables the offset.
+    ///
+    /// See the documentation of `PolygonOffset` for more infos.
+    pub polygon_offset: PolygonOffset,
+
     /// Whether multisample antialiasing (MSAA) should be used. Default value is `true`.
     ///
     /// Note that you will need to set the appropriate option when creating the window.
@@ -872,7 +956,8 @@ pub struct DrawParameters<'a> {
     ///
     /// The default value is `LastVertex`, as this is the default in OpenGL. Any other value can
     /// potentially trigger a `ProvokingVertexNotSupported` error. Most notably OpenGL ES doesn't
-    /// support anything else but `LastVertex`.
+    /// support anything else but `LastVertex`, unless emulation is enabled with
+    /// `Context::set_emulation_options`.
     pub provoking_vertex: ProvokingVertex,
 
     /// Hint for the GPU of the bounding box of the geometry.
@@ -889,6 +974,20 @@ pub struct DrawParameters<'a> {
     /// Since this is purely an optimization, this parameter is ignored if the backend doesn't
     /// support it.
     pub primitive_bounding_box: (Range<f32>, Range<f32>, Range<f32>, Range<f32>),
+
+    /// If true, the fragment shader can use the pixel local storage of the
+    /// `GL_EXT_shader_pixel_local_storage` extension. This storage is kept in the on-chip memory
+    /// of tile-based GPUs, which allows for example to implement deferred shading without
+    /// writing the G-buffer to memory.
+    ///
+    /// The content of the pixel local storage is kept between successive draw calls on the same
+    /// framebuffer that have this parameter set to true. It is discarded when a draw call sets
+    /// this parameter to false, or when the framebuffer is cleared, blitted or read, or when
+    /// another framebuffer is used.
+    ///
+    /// The default value is `false`. Setting this parameter to true can trigger a
+    /// `PixelLocalStorageNotSupported` error. See `framebuffer::is_pixel_local_storage_supported`.
+    pub pixel_local_storage: bool,
 }
 
 /// Condition whether to render or not.
@@ -958,6 +1057,7 @@ impl<'a> Default for DrawParameters<'a> {
             point_size: None,
             backface_culling: BackfaceCullingMode::CullingDisabled,
             polygon_mode: PolygonMode::Fill,
+            polygon_offset: Default::default(),
             multisampling: true,
             dithering: true,
             viewport: None,
@@ -972,6 +1072,7 @@ impl<'a> Default for DrawParameters<'a> {
             smooth: None,
             provoking_vertex: ProvokingVertex::LastVertex,
             primitive_bounding_box: (-1.0 .. 1.0, -1.0 .. 1.0, -1.0 .. 1.0, -1.0 .. 1.0),
+            pixel_local_storage: false,
         }
     }
 }
@@ -1,3 +1,4 @@
+use std::error::Error;
 use backend::Facade;
 use context::Context;
 use context::CommandContext;
@@ -65,6 +66,20 @@ pub enum QueryCreationError {
     NotSupported,
 }
 
+impl fmt::Display for QueryCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for QueryCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &QueryCreationError::NotSupported => "The query type is not supported by the backend",
+        }
+    }
+}
+
 /// Error that can happen when writing the value of a query to a buffer.
 #[derive(Copy, Clone, Debug)]
 pub enum ToBufferError {
@@ -72,6 +87,21 @@ pub enum ToBufferError {
     NotSupported,
 }
 
+impl fmt::Display for ToBufferError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for ToBufferError {
+    fn description(&self) -> &str {
+        match self {
+            &ToBufferError::NotSupported => "Writing the result of a query to a buffer is not \
+                                             supported",
+        }
+    }
+}
+
 impl RawQuery {
     /// Builds a new query. Returns `None` if the backend doesn't support this type.
     pub fn new<F>(facade: &F, ty: QueryType) -> Result<RawQuery, QueryCreationError>
@@ -80,8 +110,6 @@ impl RawQuery {
         let context = facade.get_context().clone();
         let ctxt = facade.get_context().make_current();
 
-        // FIXME: handle Timestamp separately
-
         let id = unsafe {
             let mut id = mem::uninitialized();
 
@@ -89,7 +117,7 @@ impl RawQuery {
                 match ty {
                     QueryType::AnySamplesPassed | QueryType::SamplesPassed |
                     QueryType::PrimitivesGenerated | QueryType::TimeElapsed |
-                    QueryType::TransformFeedbackPrimitivesWritten => (),
+                    QueryType::Timestamp | QueryType::TransformFeedbackPrimitivesWritten => (),
                     QueryType::AnySamplesPassedConservative if
                             ctxt.extensions.gl_arb_es3_compatibility ||
                             ctxt.version >= &Version(Api:: Gl, 4, 3) => (),
@@ -111,6 +139,7 @@ impl RawQuery {
                     QueryType::AnySamplesPassed if ctxt.extensions.gl_arb_occlusion_query2 => (),
                     QueryType::AnySamplesPassedConservative if ctxt.extensions.gl_arb_es3_compatibility => (),
                     QueryType::TimeElapsed if ctxt.extensions.gl_arb_timer_query => (),
+                    QueryType::Timestamp if ctxt.extensions.gl_arb_timer_query => (),
 
                     _ => return Err(QueryCreationError::NotSupported)
                 };
@@ -125,6 +154,7 @@ impl RawQuery {
                     QueryType::PrimitivesGenerated if ctxt.extensions.gl_ext_transform_feedback => (),
                     QueryType::TransformFeedbackPrimitivesWritten if ctxt.extensions.gl_ext_transform_feedback => (),
                     QueryType::TimeElapsed if ctxt.extensions.gl_arb_timer_query => (),
+                    QueryType::Timestamp if ctxt.extensions.gl_arb_timer_query => (),
                     _ => return Err(QueryCreationError::NotSupported)
                 };
 
@@ -296,7 +326,7 @@ impl RawQuery {
     unsafe fn raw_get_u64(&self, ctxt: &mut CommandContext, target: *mut gl::types::GLuint64)
                           -> Result<(), ()>
     {
-        if ctxt.version >= &Version(Api::Gl, 3, 3) {
+        if ctxt.version >= &Version(Api::Gl, 3, 3) || ctxt.extensions.gl_arb_timer_query {
             ctxt.gl.GetQueryObjectui64v(self.id, gl::QUERY_RESULT, target);
             Ok(())
 
@@ -313,6 +343,17 @@ impl RawQuery {
         self.get_u32() != 0
     }
 
+    /// Records the time when the GPU reaches this point of the commands queue.
+    ///
+    /// The query must be a `Timestamp` query.
+    pub fn query_counter(&self) {
+        debug_assert!(match self.ty { QueryType::Timestamp => true, _ => false });
+
+        let ctxt = self.context.make_current();
+        unsafe { ctxt.gl.QueryCounter(self.id, gl::TIMESTAMP); }
+        self.has_been_used.set(true);
+    }
+
     /// If the query is active, unactivates it.
     fn deactivate(&self, ctxt: &mut CommandContext) {
         if ctxt.state.samples_passed_query == self.id {
@@ -819,6 +860,50 @@ impl TimeElapsedQuery {
 
 impl_helper!(TimeElapsedQuery, u32, get_u32);
 
+/// A query that records the time, in nanoseconds, when the GPU reaches a certain point of the
+/// commands queue.
+///
+/// Contrary to the other queries, a `TimestampQuery` is not passed to the draw parameters.
+/// Instead you call `record` between two commands. The difference between two timestamps gives
+/// you the time that the GPU has spent executing the commands between them.
+#[derive(Debug)]
+pub struct TimestampQuery {
+    query: RawQuery,
+}
+
+impl TimestampQuery {
+    /// Builds a new query.
+    #[inline]
+    pub fn new<F>(facade: &F) -> Result<TimestampQuery, QueryCreationError> where F: Facade {
+        RawQuery::new(facade, QueryType::Timestamp).map(|q| TimestampQuery { query: q })
+    }
+
+    /// Records the time when the GPU reaches this point of the commands queue.
+    ///
+    /// This function doesn't block. Calling it again replaces the previous value.
+    #[inline]
+    pub fn record(&self) {
+        self.query.query_counter();
+    }
+
+    /// Queries the counter to see if the result is already available.
+    ///
+    /// Returns false if `record` has never been called.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.query.is_ready()
+    }
+
+    /// Returns the recorded time in nanoseconds. Blocks until it is available.
+    ///
+    /// This function doesn't block if `is_ready` would return true. Returns 0 if `record` has
+    /// never been called.
+    #[inline]
+    pub fn get(&self) -> u64 {
+        self.query.get_u64()
+    }
+}
+
 /// A query type that allows you to know whether any sample has been written to the output during
 /// the operations executed with this query.
 ///
@@ -49,6 +49,8 @@ If a layered image is attached to one attachment, then all attachments must be l
   with an array depth texture) (GL_FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS when false).
 
 */
+use std::fmt;
+use std::error::Error;
 use std::collections::HashMap;
 use std::cmp;
 use std::mem;
@@ -63,8 +65,10 @@ use GlObject;
 use TextureExt;
 
 use texture::CubeLayer;
+use texture::Dimensions;
 use texture::TextureAnyImage;
 use texture::TextureAnyMipmap;
+use texture::TextureUsage;
 use framebuffer::RenderBufferAny;
 
 use gl;
@@ -83,6 +87,28 @@ pub fn is_dimensions_mismatch_supported<C>(context: &C) -> bool where C: Capabil
     context.get_extensions().gl_arb_framebuffer_object
 }
 
+/// Returns true if the backend supports layered framebuffers, where all the layers of a texture
+/// are attached at once.
+pub fn is_layered_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_version() >= &Version(Api::Gl, 3, 2) ||
+    context.get_extensions().gl_arb_direct_state_access ||
+    (context.get_extensions().gl_ext_direct_state_access &&
+     context.get_extensions().gl_ext_geometry_shader4)
+}
+
+/// Returns true if the backend supports pixel local storage, which is enabled with the
+/// `pixel_local_storage` member of the draw parameters.
+pub fn is_pixel_local_storage_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_extensions().gl_ext_shader_pixel_local_storage ||
+    context.get_extensions().gl_ext_shader_pixel_local_storage2
+}
+
+/// Returns true if the backend supports choosing the size of the pixel local storage of a
+/// framebuffer object and clearing the pixel local storage.
+pub fn is_pixel_local_storage2_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_extensions().gl_ext_shader_pixel_local_storage2
+}
+
 /// Represents the attachments to use for an OpenGL framebuffer.
 #[derive(Clone)]
 pub enum FramebufferAttachments<'a> {
@@ -115,6 +141,15 @@ pub enum RegularAttachment<'a> {
 #[derive(Copy, Clone)]
 pub struct LayeredAttachment<'a>(TextureAnyMipmap<'a>);
 
+impl<'a> LayeredAttachment<'a> {
+    /// Builds a layered attachment from all the layers of a mipmap of an array, cubemap or 3D
+    /// texture.
+    #[inline]
+    pub fn new(texture: TextureAnyMipmap<'a>) -> LayeredAttachment<'a> {
+        LayeredAttachment(texture)
+    }
+}
+
 /// Depth and/or stencil attachment to use.
 #[derive(Copy, Clone)]
 pub enum DepthStencilAttachments<T> {
@@ -209,6 +244,13 @@ impl<'a> FramebufferAttachments<'a> {
     {
         // TODO: make sure that all attachments are layered
 
+        if !is_layered_supported(context) {
+            return Err(ValidationError::LayeredFramebuffersNotSupported);
+        }
+
+        // the number of layers is the minimum between the number of layers of all attachments
+        let mut layers: Option<u32> = None;
+
         macro_rules! handle_tex {
             ($tex:ident, $dim:ident, $samples:ident, $num_bits:ident) => ({
                 $num_bits = Some($tex.get_texture().get_internal_format()
@@ -220,6 +262,10 @@ impl<'a> FramebufferAttachments<'a> {
                 // TODO: check that internal format is renderable
                 let context = $tex.get_texture().get_context();
 
+                if $tex.get_texture().get_usage() == TextureUsage::Sampled {
+                    return Err(ValidationError::IncompatibleTextureUsage);
+                }
+
                 match &mut $samples {
                     &mut Some(samples) => {
                         if samples != $tex.get_samples().unwrap_or(0) {
@@ -250,6 +296,10 @@ impl<'a> FramebufferAttachments<'a> {
                     },
                 }
 
+                let tex_layers = get_layers_count($tex.get_texture().get_texture_type(),
+                                                  $tex.get_level());
+                layers = Some(layers.map_or(tex_layers, |l| cmp::min(l, tex_layers)));
+
                 RawAttachment::Texture {
                     texture: $tex.get_texture().get_id(),
                     bind_point: $tex.get_texture().get_bind_point(),
@@ -325,7 +375,7 @@ impl<'a> FramebufferAttachments<'a> {
         Ok(ValidatedAttachments {
             raw: raw_attachments,
             dimensions: dimensions,
-            layers: None,       // FIXME: count layers
+            layers: layers,
             depth_buffer_bits: depth_bits,
             stencil_buffer_bits: stencil_bits,
             marker: PhantomData,
@@ -348,6 +398,10 @@ impl<'a> FramebufferAttachments<'a> {
                 // TODO: check that internal format is renderable
                 let context = $tex.get_texture().get_context();
 
+                if $tex.get_texture().get_usage() == TextureUsage::Sampled {
+                    return Err(ValidationError::IncompatibleTextureUsage);
+                }
+
                 match &mut $samples {
                     &mut Some(samples) => {
                         if samples != $tex.get_samples().unwrap_or(0) {
@@ -569,6 +623,9 @@ pub enum ValidationError {
     /// All attachments must have the same number of samples.
     SamplesCountMismatch,
 
+    /// The backend doesn't support attaching all the layers of a texture at once.
+    LayeredFramebuffersNotSupported,
+
     /// Backends only support a certain number of color attachments.
     TooManyColorAttachments {
         /// Maximum number of attachments.
@@ -576,6 +633,56 @@ pub enum ValidationError {
         /// Number of attachments that were given.
         obtained: usize,
     },
+
+    /// One of the attachments is a texture that has been created with `TextureUsage::Sampled`.
+    IncompatibleTextureUsage,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &ValidationError::TooManyColorAttachments { maximum, obtained } => {
+                write!(fmt, "Tried to use {} color attachments, but the backend only supports \
+                             {}", obtained, maximum)
+            },
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn description(&self) -> &str {
+        match self {
+            &ValidationError::EmptyFramebufferObjectsNotSupported => "Empty framebuffer objects \
+                                                                      are not supported",
+            &ValidationError::EmptyFramebufferUnsupportedDimensions => {
+                "The requested characteristics of the empty framebuffer object are out of range"
+            },
+            &ValidationError::DimensionsMismatchNotSupported => "The backend doesn't support \
+                                                                 attachments with various \
+                                                                 dimensions",
+            &ValidationError::SamplesCountMismatch => "All attachments must have the same number \
+                                                       of samples",
+            &ValidationError::LayeredFramebuffersNotSupported => "The backend doesn't support \
+                                                                  layered framebuffers",
+            &ValidationError::TooManyColorAttachments { .. } => "Too many color attachments",
+            &ValidationError::IncompatibleTextureUsage => "One of the attachments is a texture \
+                                                           that can only be sampled",
+        }
+    }
+}
+
+/// Returns the number of layers that a layered attachment of a mipmap of this texture would have.
+fn get_layers_count(ty: Dimensions, level: u32) -> u32 {
+    match ty {
+        Dimensions::Texture1dArray { array_size, .. } => array_size,
+        Dimensions::Texture2dArray { array_size, .. } => array_size,
+        Dimensions::Texture2dMultisampleArray { array_size, .. } => array_size,
+        Dimensions::Texture3d { depth, .. } => cmp::max(1, depth >> level),
+        Dimensions::Cubemap { .. } => 6,
+        Dimensions::CubemapArray { array_size, .. } => 6 * array_size,
+        _ => 1,
+    }
 }
 
 /// Data structure stored in the hashmap.
@@ -738,6 +845,7 @@ impl FramebuffersContainer {
     pub fn bind_default_framebuffer_for_reading(ctxt: &mut CommandContext,
                                                 read_buffer: gl::types::GLenum)
     {
+        end_pixel_local_storage(ctxt);
         unsafe { bind_framebuffer(ctxt, 0, false, true) };
         unsafe { ctxt.gl.ReadBuffer(read_buffer) };     // TODO: cache
     }
@@ -765,6 +873,7 @@ impl FramebuffersContainer {
         }).validate(ctxt).unwrap();
 
         let framebuffer = FramebuffersContainer::get_framebuffer_for_drawing(ctxt, Some(&attachments));
+        end_pixel_local_storage(ctxt);
         bind_framebuffer(ctxt, framebuffer, false, true);
         ctxt.gl.ReadBuffer(gl::COLOR_ATTACHMENT0);     // TODO: cache
     }
@@ -847,67 +956,62 @@ impl FrameBufferObject {
         };
 
         // framebuffer parameters
-        // TODO: DSA
         if let Some(width) = attachments.default_width {
-            unsafe { bind_framebuffer(&mut ctxt, id, true, false) };       // TODO: remove once DSA is used
             if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
                ctxt.extensions.gl_arb_framebuffer_no_attachments
             {
                 unsafe {
-                    ctxt.gl.FramebufferParameteri(gl::DRAW_FRAMEBUFFER, gl::FRAMEBUFFER_DEFAULT_WIDTH,
-                                                  width as gl::types::GLint);
+                    set_framebuffer_parameter(&mut ctxt, id, gl::FRAMEBUFFER_DEFAULT_WIDTH,
+                                              width as gl::types::GLint);
                 }
             } else {
                 unreachable!();
             }
         }
         if let Some(height) = attachments.default_height {
-            unsafe { bind_framebuffer(&mut ctxt, id, true, false) };       // TODO: remove once DSA is used
             if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
                ctxt.extensions.gl_arb_framebuffer_no_attachments
             {
                 unsafe {
-                    ctxt.gl.FramebufferParameteri(gl::DRAW_FRAMEBUFFER, gl::FRAMEBUFFER_DEFAULT_HEIGHT,
-                                                  height as gl::types::GLint);
+                    set_framebuffer_parameter(&mut ctxt, id, gl::FRAMEBUFFER_DEFAULT_HEIGHT,
+                                              height as gl::types::GLint);
                 }
             } else {
                 unreachable!();
             }
         }
         if let Some(layers) = attachments.default_layers {
-            unsafe { bind_framebuffer(&mut ctxt, id, true, false) };       // TODO: remove once DSA is used
             if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 2) ||
                ctxt.extensions.gl_arb_framebuffer_no_attachments
             {
                 unsafe {
-                    ctxt.gl.FramebufferParameteri(gl::DRAW_FRAMEBUFFER, gl::FRAMEBUFFER_DEFAULT_LAYERS,
-                                                  layers as gl::types::GLint);
+                    set_framebuffer_parameter(&mut ctxt, id, gl::FRAMEBUFFER_DEFAULT_LAYERS,
+                                              layers as gl::types::GLint);
                 }
             } else {
                 unreachable!();
             }
         }
         if let Some(samples) = attachments.default_samples {
-            unsafe { bind_framebuffer(&mut ctxt, id, true, false) };       // TODO: remove once DSA is used
             if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
                ctxt.extensions.gl_arb_framebuffer_no_attachments
             {
                 unsafe {
-                    ctxt.gl.FramebufferParameteri(gl::DRAW_FRAMEBUFFER, gl::FRAMEBUFFER_DEFAULT_SAMPLES,
-                                                  samples as gl::types::GLint);
+                    set_framebuffer_parameter(&mut ctxt, id, gl::FRAMEBUFFER_DEFAULT_SAMPLES,
+                                              samples as gl::types::GLint);
                 }
             } else {
                 unreachable!();
             }
         }
         if let Some(samples_fixed) = attachments.default_samples_fixed {
-            unsafe { bind_framebuffer(&mut ctxt, id, true, false) };       // TODO: remove once DSA is used
             if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
                ctxt.extensions.gl_arb_framebuffer_no_attachments
             {
                 unsafe {
-                    ctxt.gl.FramebufferParameteri(gl::DRAW_FRAMEBUFFER, gl::FRAMEBUFFER_DEFAULT_FIXED_SAMPLE_LOCATIONS,
-                                                  if samples_fixed { 1 } else { 0 });
+                    set_framebuffer_parameter(&mut ctxt, id,
+                                              gl::FRAMEBUFFER_DEFAULT_FIXED_SAMPLE_LOCATIONS,
+                                              if samples_fixed { 1 } else { 0 });
                 }
             } else {
                 unreachable!();
@@ -968,6 +1072,10 @@ impl FrameBufferObject {
                                            raw_attachments.as_ptr());
                 }
 
+            } else if raw_attachments.is_empty() {
+                // OpenGL ES 2 doesn't require the draw buffer to be attached, so framebuffers
+                // with only a depth or stencil attachment work without calling `glDrawBuffers`
+
             } else {
                 // OpenGL ES 2 and OpenGL 1 don't support calling `glDrawBuffers`
                 panic!("Using more than one attachment is not supported by the backend");
@@ -1024,6 +1132,13 @@ impl GlObject for FrameBufferObject {
 pub unsafe fn bind_framebuffer(ctxt: &mut CommandContext, fbo_id: gl::types::GLuint,
                                draw: bool, read: bool)
 {
+    let previous = (ctxt.state.draw_framebuffer, ctxt.state.read_framebuffer);
+
+    // binding another framebuffer is an error when pixel local storage is enabled
+    if (draw && previous.0 != fbo_id) || (read && previous.1 != fbo_id) {
+        end_pixel_local_storage(ctxt);
+    }
+
     if draw && read {
         if ctxt.state.draw_framebuffer != fbo_id || ctxt.state.read_framebuffer != fbo_id {
             if ctxt.version >= &Version(Api::Gl, 3, 0) ||
@@ -1084,6 +1199,77 @@ pub unsafe fn bind_framebuffer(ctxt: &mut CommandContext, fbo_id: gl::types::GLu
         }
 
     }
+
+    if (ctxt.state.draw_framebuffer, ctxt.state.read_framebuffer) != previous {
+        ctxt.record_statistics(|s| s.framebuffer_binds += 1);
+    }
+}
+
+/// Disables `GL_SHADER_PIXEL_LOCAL_STORAGE_EXT` if it is enabled, which discards the content of
+/// the pixel local storage.
+///
+/// Must be called before any operation other than a draw call is performed on the current
+/// framebuffer.
+pub fn end_pixel_local_storage(ctxt: &mut CommandContext) {
+    if ctxt.state.enabled_shader_pixel_local_storage {
+        unsafe { ctxt.gl.Disable(gl::SHADER_PIXEL_LOCAL_STORAGE_EXT); }
+        ctxt.state.enabled_shader_pixel_local_storage = false;
+    }
+}
+
+/// Sets the number of bytes per pixel of the pixel local storage of the framebuffer object that
+/// corresponds to some attachments.
+///
+/// The backend must support `GL_EXT_shader_pixel_local_storage2`.
+pub fn set_pixel_local_storage_size(ctxt: &mut CommandContext,
+                                    attachments: &ValidatedAttachments, size: u32)
+{
+    let fbo_id = FramebuffersContainer::get_framebuffer_for_drawing(ctxt, Some(attachments));
+
+    // changing the size is an error when pixel local storage is enabled
+    end_pixel_local_storage(ctxt);
+
+    unsafe {
+        bind_framebuffer(ctxt, fbo_id, true, false);
+        ctxt.gl.FramebufferPixelLocalStorageSizeEXT(gl::DRAW_FRAMEBUFFER,
+                                                    size as gl::types::GLsizei);
+    }
+}
+
+/// Enables pixel local storage on the framebuffer object that corresponds to some attachments,
+/// and sets the 32-bits words of the storage starting at `offset` to `values`.
+///
+/// The backend must support `GL_EXT_shader_pixel_local_storage2`.
+pub fn clear_pixel_local_storage(ctxt: &mut CommandContext, attachments: &ValidatedAttachments,
+                                 offset: u32, values: &[u32])
+{
+    let fbo_id = FramebuffersContainer::get_framebuffer_for_drawing(ctxt, Some(attachments));
+
+    unsafe {
+        bind_framebuffer(ctxt, fbo_id, true, false);
+
+        if !ctxt.state.enabled_shader_pixel_local_storage {
+            ctxt.gl.Enable(gl::SHADER_PIXEL_LOCAL_STORAGE_EXT);
+            ctxt.state.enabled_shader_pixel_local_storage = true;
+        }
+
+        ctxt.gl.ClearPixelLocalStorageuiEXT(offset as gl::types::GLsizei,
+                                            values.len() as gl::types::GLsizei,
+                                            values.as_ptr());
+    }
+}
+
+/// Changes a parameter of a framebuffer object, with DSA if it is supported.
+unsafe fn set_framebuffer_parameter(ctxt: &mut CommandContext, id: gl::types::GLuint,
+                                    pname: gl::types::GLenum, value: gl::types::GLint)
+{
+    if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+        ctxt.gl.NamedFramebufferParameteri(id, pname, value);
+
+    } else {
+        bind_framebuffer(ctxt, id, true, false);
+        ctxt.gl.FramebufferParameteri(gl::DRAW_FRAMEBUFFER, pname, value);
+    }
 }
 
 /// Attaches something to a framebuffer object.
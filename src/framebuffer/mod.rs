@@ -60,15 +60,33 @@ Not yet supported
 
 # Layered framebuffers
 
-Not yet supported
+A layered framebuffer draws on all the layers of an array, cubemap or 3D texture at once, and a
+geometry shader chooses the layer of each primitive by writing to `gl_Layer`. Only framebuffers
+with a single depth attachment can be layered for the moment, see
+`SimpleFrameBuffer::depth_only_layered`.
+
+# Pixel local storage
+
+On the tile-based GPUs of mobile devices, the `GL_EXT_shader_pixel_local_storage` extension gives
+fragment shaders access to per-pixel storage that stays in the on-chip memory of the GPU. Several
+draw calls can read and write this storage, which allows for example deferred shading without
+writing a G-buffer to memory. Use `is_pixel_local_storage_supported` to know if it is available,
+and set the `pixel_local_storage` member of the draw parameters to use it.
+
+With `GL_EXT_shader_pixel_local_storage2`, the size of the storage of a `SimpleFrameBuffer` must
+first be set with `set_pixel_local_storage_size`, and the storage can be initialized with
+`clear_pixel_local_storage`.
 
 */
 use std::rc::Rc;
 use std::ops::Deref;
+use std::fmt;
+use std::error::Error;
 use smallvec::SmallVec;
 
 use texture::Texture2d;
 use texture::TextureAnyImage;
+use texture::TextureAnyMipmap;
 use TextureExt;
 
 use backend::Facade;
@@ -92,12 +110,15 @@ use {fbo, gl};
 
 pub use self::render_buffer::{RenderBuffer, RenderBufferAny, DepthRenderBuffer};
 pub use self::render_buffer::{StencilRenderBuffer, DepthStencilRenderBuffer};
-pub use fbo::is_dimensions_mismatch_supported;
+pub use self::render_buffer::CreationError as RenderBufferCreationError;
+pub use self::render_buffer::is_renderbuffer_supported;
+pub use fbo::{is_dimensions_mismatch_supported, is_layered_supported};
+pub use fbo::{is_pixel_local_storage_supported, is_pixel_local_storage2_supported};
 pub use fbo::ValidationError;
 
 mod render_buffer;
 
-/// A framebuffer which has only one color attachment.
+/// A framebuffer which has at most one color attachment.
 pub struct SimpleFrameBuffer<'a> {
     context: Rc<Context>,
     attachments: fbo::ValidatedAttachments<'a>,
@@ -110,7 +131,7 @@ impl<'a> SimpleFrameBuffer<'a> {
     pub fn new<F, C>(facade: &F, color: C) -> Result<SimpleFrameBuffer<'a>, ValidationError>
                      where C: ToColorAttachment<'a>, F: Facade
     {
-        SimpleFrameBuffer::new_impl(facade, color.to_color_attachment(), None, None, None)
+        SimpleFrameBuffer::new_impl(facade, Some(color.to_color_attachment()), None, None, None)
     }
 
     /// Creates a `SimpleFrameBuffer` with a single color attachment and a depth
@@ -121,7 +142,7 @@ impl<'a> SimpleFrameBuffer<'a> {
                                       where C: ToColorAttachment<'a>,
                                             D: ToDepthAttachment<'a>, F: Facade
     {
-        SimpleFrameBuffer::new_impl(facade, color.to_color_attachment(),
+        SimpleFrameBuffer::new_impl(facade, Some(color.to_color_attachment()),
                                     Some(depth.to_depth_attachment()), None, None)
     }
 
@@ -136,7 +157,7 @@ impl<'a> SimpleFrameBuffer<'a> {
                                                            D: ToDepthAttachment<'a>,
                                                            S: ToStencilAttachment<'a>, F: Facade
     {
-        SimpleFrameBuffer::new_impl(facade, color.to_color_attachment(),
+        SimpleFrameBuffer::new_impl(facade, Some(color.to_color_attachment()),
                                     Some(depth.to_depth_attachment()),
                                     Some(stencil.to_stencil_attachment()), None)
     }
@@ -149,7 +170,7 @@ impl<'a> SimpleFrameBuffer<'a> {
                                         where C: ToColorAttachment<'a>, S: ToStencilAttachment<'a>,
                                               F: Facade
     {
-        SimpleFrameBuffer::new_impl(facade, color.to_color_attachment(), None,
+        SimpleFrameBuffer::new_impl(facade, Some(color.to_color_attachment()), None,
                                     Some(stencil.to_stencil_attachment()), None)
     }
 
@@ -160,20 +181,92 @@ impl<'a> SimpleFrameBuffer<'a> {
                                               where C: ToColorAttachment<'a>,
                                                     D: ToDepthStencilAttachment<'a>, F: Facade
     {
-        SimpleFrameBuffer::new_impl(facade, color.to_color_attachment(), None, None,
+        SimpleFrameBuffer::new_impl(facade, Some(color.to_color_attachment()), None, None,
                                     Some(depthstencil.to_depth_stencil_attachment()))
     }
 
 
-    fn new_impl<F>(facade: &F, color: ColorAttachment<'a>, depth: Option<DepthAttachment<'a>>,
+    /// Creates a `SimpleFrameBuffer` with a depth buffer and no color attachment.
+    ///
+    /// This is mostly useful to render shadow maps.
+    #[inline]
+    pub fn depth_only<F, D>(facade: &F, depth: D)
+                            -> Result<SimpleFrameBuffer<'a>, ValidationError>
+                            where D: ToDepthAttachment<'a>, F: Facade
+    {
+        SimpleFrameBuffer::new_impl(facade, None, Some(depth.to_depth_attachment()), None, None)
+    }
+
+    /// Creates a layered `SimpleFrameBuffer` whose depth buffer is made of all the layers of a
+    /// mipmap of an array, cubemap or 3D depth texture. There is no color attachment.
+    ///
+    /// A geometry shader then chooses the layer of each primitive with `gl_Layer`. See
+    /// `framebuffer::is_layered_supported` to know if the backend supports layered framebuffers.
+    pub fn depth_only_layered<F>(facade: &F, depth: TextureAnyMipmap<'a>)
+                                 -> Result<SimpleFrameBuffer<'a>, ValidationError>
+                                 where F: Facade
+    {
+        let attachments = fbo::FramebufferAttachments::Layered(fbo::FramebufferSpecificAttachments {
+            colors: SmallVec::new(),
+            depth_stencil: fbo::DepthStencilAttachments::DepthAttachment(
+                fbo::LayeredAttachment::new(depth)
+            ),
+        });
+
+        let attachments = try!(attachments.validate(facade));
+
+        Ok(SimpleFrameBuffer {
+            context: facade.get_context().clone(),
+            attachments: attachments,
+        })
+    }
+
+    /// Sets the number of bytes per pixel of the pixel local storage that the fragment shaders
+    /// can use when drawing on this framebuffer. `size` must be a multiple of `4`.
+    ///
+    /// With `GL_EXT_shader_pixel_local_storage2`, a framebuffer object must be given a size
+    /// before draw calls can enable pixel local storage on it. The size is kept by the
+    /// framebuffer object that glium shares between all the framebuffers with the same
+    /// attachments.
+    pub fn set_pixel_local_storage_size(&self, size: u32)
+                                        -> Result<(), PixelLocalStorageNotSupportedError>
+    {
+        if !is_pixel_local_storage2_supported(&self.context) {
+            return Err(PixelLocalStorageNotSupportedError);
+        }
+
+        let mut ctxt = self.context.make_current();
+        fbo::set_pixel_local_storage_size(&mut ctxt, &self.attachments, size);
+        Ok(())
+    }
+
+    /// Enables pixel local storage on this framebuffer and sets the 32-bits words of the storage
+    /// of every pixel starting at `offset` to `values`.
+    ///
+    /// The content is then kept by the next draw calls that have the `pixel_local_storage`
+    /// parameter set to true.
+    pub fn clear_pixel_local_storage(&self, offset: u32, values: &[u32])
+                                     -> Result<(), PixelLocalStorageNotSupportedError>
+    {
+        if !is_pixel_local_storage2_supported(&self.context) {
+            return Err(PixelLocalStorageNotSupportedError);
+        }
+
+        let mut ctxt = self.context.make_current();
+        fbo::clear_pixel_local_storage(&mut ctxt, &self.attachments, offset, values);
+        Ok(())
+    }
+
+    fn new_impl<F>(facade: &F, color: Option<ColorAttachment<'a>>,
+                   depth: Option<DepthAttachment<'a>>,
                    stencil: Option<StencilAttachment<'a>>,
                    depthstencil: Option<DepthStencilAttachment<'a>>)
                    -> Result<SimpleFrameBuffer<'a>, ValidationError> where F: Facade
     {
-        let color = match color {
+        let color = color.map(|color| match color {
             ColorAttachment::Texture(tex) => fbo::RegularAttachment::Texture(tex),
             ColorAttachment::RenderBuffer(buffer) => fbo::RegularAttachment::RenderBuffer(buffer),
-        };
+        });
 
         let depth = depth.map(|depth| match depth {
             DepthAttachment::Texture(tex) => fbo::RegularAttachment::Texture(tex),
@@ -191,7 +284,7 @@ impl<'a> SimpleFrameBuffer<'a> {
         });
 
         let attachments = fbo::FramebufferAttachments::Regular(fbo::FramebufferSpecificAttachments {
-            colors: { let mut v = SmallVec::new(); v.push((0, color)); v },
+            colors: color.into_iter().map(|color| (0, color)).collect(),
             depth_stencil: if let (Some(depth), Some(stencil)) = (depth, stencil) {
                 fbo::DepthStencilAttachments::DepthAndStencilAttachments(depth, stencil)
             } else if let Some(depth) = depth {
@@ -262,7 +355,19 @@ impl<'a> Surface for SimpleFrameBuffer<'a> {
         }
 
         ops::draw(&self.context, Some(&self.attachments), vb,
-                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions())
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(), None)
+    }
+
+    fn draw_with_pipeline<'b, 'v, V, I, U>(&mut self, vb: V, ib: I,
+        pipeline: &::pipeline::PipelineState, uniforms: &U) -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
+        V: ::vertex::MultiVerticesSource<'v>
+    {
+        let draw_parameters = pipeline.get_draw_parameters();
+        try!(::check_depth_buffer(&*self, draw_parameters));
+
+        ops::draw(&self.context, Some(&self.attachments), vb, ib.into(), pipeline.get_program(),
+                  uniforms, draw_parameters, self.get_dimensions(), Some(pipeline.get_id()))
     }
 
     #[inline]
@@ -478,7 +583,20 @@ impl<'a> Surface for MultiOutputFrameBuffer<'a> {
         }
 
         ops::draw(&self.context, Some(&self.build_attachments(program)), vb,
-                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions())
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(), None)
+    }
+
+    fn draw_with_pipeline<'i, 'v, V, I, U>(&mut self, vb: V, ib: I,
+        pipeline: &::pipeline::PipelineState, uniforms: &U) -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'i>>, U: ::uniforms::Uniforms,
+        V: ::vertex::MultiVerticesSource<'v>
+    {
+        let draw_parameters = pipeline.get_draw_parameters();
+        try!(::check_depth_buffer(&*self, draw_parameters));
+
+        let attachments = self.build_attachments(pipeline.get_program());
+        ops::draw(&self.context, Some(&attachments), vb, ib.into(), pipeline.get_program(),
+                  uniforms, draw_parameters, self.get_dimensions(), Some(pipeline.get_id()))
     }
 
     #[inline]
@@ -647,7 +765,19 @@ impl Surface for EmptyFrameBuffer {
         }
 
         ops::draw(&self.context, Some(&self.attachments), vb,
-                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions())
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(), None)
+    }
+
+    fn draw_with_pipeline<'b, 'v, V, I, U>(&mut self, vb: V, ib: I,
+        pipeline: &::pipeline::PipelineState, uniforms: &U) -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
+        V: ::vertex::MultiVerticesSource<'v>
+    {
+        let draw_parameters = pipeline.get_draw_parameters();
+        try!(::check_depth_buffer(&*self, draw_parameters));
+
+        ops::draw(&self.context, Some(&self.attachments), vb, ib.into(), pipeline.get_program(),
+                  uniforms, draw_parameters, self.get_dimensions(), Some(pipeline.get_id()))
     }
 
     #[inline]
@@ -750,3 +880,19 @@ pub trait ToDepthStencilAttachment<'a> {
     /// Builds the `DepthStencilAttachment`.
     fn to_depth_stencil_attachment(self) -> DepthStencilAttachment<'a>;
 }
+
+/// Pixel local storage is not supported by the backend.
+#[derive(Debug, Copy, Clone)]
+pub struct PixelLocalStorageNotSupportedError;
+
+impl fmt::Display for PixelLocalStorageNotSupportedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for PixelLocalStorageNotSupportedError {
+    fn description(&self) -> &str {
+        "Pixel local storage is not supported by the backend"
+    }
+}
@@ -9,6 +9,8 @@ the data of the render buffer.
 */
 use std::rc::Rc;
 use std::ops::{Deref, DerefMut};
+use std::error::Error;
+use std::fmt;
 use std::mem;
 
 use framebuffer::{ColorAttachment, ToColorAttachment};
@@ -17,17 +19,70 @@ use framebuffer::{StencilAttachment, ToStencilAttachment};
 use framebuffer::{DepthStencilAttachment, ToDepthStencilAttachment};
 use texture::{UncompressedFloatFormat, DepthFormat, StencilFormat, DepthStencilFormat};
 
-use image_format;
+use image_format::{self, FormatNotSupportedError};
 
 use gl;
 use GlObject;
 use fbo::FramebuffersContainer;
 use backend::Facade;
 use context::Context;
+use context::CommandContext;
+use CapabilitiesSource;
 use ContextExt;
 use version::Version;
 use version::Api;
 
+/// Error that can happen while creating a render buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CreationError {
+    /// The requested format is not supported by the backend.
+    FormatNotSupported,
+
+    /// The requested dimensions exceed the maximum size of a render buffer.
+    DimensionsNotSupported,
+
+    /// Render buffers are not supported by the backend.
+    NotSupported,
+
+    /// Multisample render buffers are not supported by the backend.
+    MultisamplingNotSupported,
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::FormatNotSupported => "The requested format is not supported by the \
+                                                   backend",
+            &CreationError::DimensionsNotSupported => "The requested dimensions exceed the \
+                                                       maximum size of a render buffer",
+            &CreationError::NotSupported => "Render buffers are not supported by the backend",
+            &CreationError::MultisamplingNotSupported => "Multisample render buffers are not \
+                                                          supported by the backend",
+        }
+    }
+}
+
+impl From<FormatNotSupportedError> for CreationError {
+    #[inline]
+    fn from(_: FormatNotSupportedError) -> CreationError {
+        CreationError::FormatNotSupported
+    }
+}
+
+/// Returns true if the backend supports render buffers.
+pub fn is_renderbuffer_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_version() >= &Version(Api::Gl, 3, 0) ||
+    context.get_version() >= &Version(Api::GlEs, 2, 0) ||
+    context.get_extensions().gl_arb_framebuffer_object ||
+    context.get_extensions().gl_ext_framebuffer_object
+}
+
 /// A render buffer is similar to a texture, but is optimized for usage as a draw target.
 ///
 /// Contrary to a texture, you can't sample or modify the content of the `RenderBuffer`.
@@ -38,14 +93,14 @@ pub struct RenderBuffer {
 impl RenderBuffer {
     /// Builds a new render buffer.
     pub fn new<F>(facade: &F, format: UncompressedFloatFormat, width: u32, height: u32)
-                  -> RenderBuffer where F: Facade
+                  -> Result<RenderBuffer, CreationError> where F: Facade
     {
         let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::UncompressedFloat(format));
-        let format = image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer).unwrap();
+        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer));
 
-        RenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
-        }
+        Ok(RenderBuffer {
+            buffer: try!(RenderBufferAny::new(facade, format, width, height, None))
+        })
     }
 }
 
@@ -91,14 +146,14 @@ pub struct DepthRenderBuffer {
 impl DepthRenderBuffer {
     /// Builds a new render buffer.
     pub fn new<F>(facade: &F, format: DepthFormat, width: u32, height: u32)
-                  -> DepthRenderBuffer where F: Facade
+                  -> Result<DepthRenderBuffer, CreationError> where F: Facade
     {
         let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::DepthFormat(format));
-        let format = image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer).unwrap();
+        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer));
 
-        DepthRenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
-        }
+        Ok(DepthRenderBuffer {
+            buffer: try!(RenderBufferAny::new(facade, format, width, height, None))
+        })
     }
 }
 
@@ -143,14 +198,14 @@ pub struct StencilRenderBuffer {
 impl StencilRenderBuffer {
     /// Builds a new render buffer.
     pub fn new<F>(facade: &F, format: StencilFormat, width: u32, height: u32)
-                  -> StencilRenderBuffer where F: Facade
+                  -> Result<StencilRenderBuffer, CreationError> where F: Facade
     {
         let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::StencilFormat(format));
-        let format = image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer).unwrap();
+        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer));
 
-        StencilRenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
-        }
+        Ok(StencilRenderBuffer {
+            buffer: try!(RenderBufferAny::new(facade, format, width, height, None))
+        })
     }
 }
 
@@ -196,14 +251,14 @@ pub struct DepthStencilRenderBuffer {
 impl DepthStencilRenderBuffer {
     /// Builds a new render buffer.
     pub fn new<F>(facade: &F, format: DepthStencilFormat, width: u32, height: u32)
-                  -> DepthStencilRenderBuffer where F: Facade
+                  -> Result<DepthStencilRenderBuffer, CreationError> where F: Facade
     {
         let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::DepthStencilFormat(format));
-        let format = image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer).unwrap();
+        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), None, format, image_format::RequestType::Renderbuffer));
 
-        DepthStencilRenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
-        }
+        Ok(DepthStencilRenderBuffer {
+            buffer: try!(RenderBufferAny::new(facade, format, width, height, None))
+        })
     }
 }
 
@@ -251,10 +306,38 @@ pub struct RenderBufferAny {
 impl RenderBufferAny {
     /// Builds a new render buffer.
     fn new<F>(facade: &F, format: gl::types::GLenum, width: u32, height: u32, samples: Option<u32>)
-              -> RenderBufferAny where F: Facade
+              -> Result<RenderBufferAny, CreationError> where F: Facade
     {
+        if !is_renderbuffer_supported(facade) {
+            return Err(CreationError::NotSupported);
+        }
+
+        let max_size = facade.get_context().get_capabilities().max_renderbuffer_size as u32;
+
+        if width > max_size || height > max_size {
+            return Err(CreationError::DimensionsNotSupported);
+        }
+
+        // checking multisampling, so that the branches below cover all the remaining cases
+        if samples.is_some() {
+            let context = facade.get_context();
+            let version = context.get_version();
+            let extensions = context.get_extensions();
+
+            if !(version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+                 extensions.gl_arb_direct_state_access ||
+                 extensions.gl_apple_framebuffer_multisample ||
+                 extensions.gl_angle_framebuffer_multisample ||
+                 extensions.gl_ext_multisampled_render_to_texture ||
+                 extensions.gl_nv_framebuffer_multisample ||
+                 (extensions.gl_ext_framebuffer_object &&
+                  extensions.gl_ext_framebuffer_multisample))
+            {
+                return Err(CreationError::MultisamplingNotSupported);
+            }
+        }
+
         unsafe {
-            // TODO: check that dimensions don't exceed GL_MAX_RENDERBUFFER_SIZE
             // FIXME: gles2 only supports very few formats
             let mut ctxt = facade.get_context().make_current();
             let mut id = mem::uninitialized();
@@ -362,13 +445,13 @@ impl RenderBufferAny {
                 unreachable!();
             }
 
-            RenderBufferAny {
+            Ok(RenderBufferAny {
                 context: facade.get_context().clone(),
                 id: id,
                 width: width,
                 height: height,
                 samples: samples,
-            }
+            })
         }
     }
 
@@ -400,26 +483,24 @@ impl Drop for RenderBufferAny {
             // removing FBOs which contain this buffer
             FramebuffersContainer::purge_renderbuffer(&mut ctxt, self.id);
 
-            if ctxt.version >= &Version(Api::Gl, 3, 0) ||
-               ctxt.version >= &Version(Api::GlEs, 2, 0)
-            {
-                if ctxt.state.renderbuffer == self.id {
-                    ctxt.state.renderbuffer = 0;
-                }
-
-                ctxt.gl.DeleteRenderbuffers(1, [ self.id ].as_ptr());
-
-            } else if ctxt.extensions.gl_ext_framebuffer_object {
-                if ctxt.state.renderbuffer == self.id {
-                    ctxt.state.renderbuffer = 0;
-                }
+            ctxt.destroy_object(self.id, destroy_renderbuffer);
+        }
+    }
+}
 
-                ctxt.gl.DeleteRenderbuffersEXT(1, [ self.id ].as_ptr());
+/// Resets the binding of a render buffer and destroys it.
+unsafe fn destroy_renderbuffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    if ctxt.state.renderbuffer == id {
+        ctxt.state.renderbuffer = 0;
+    }
 
-            } else {
-                unreachable!();
-            }
-        }
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 2, 0) {
+        ctxt.gl.DeleteRenderbuffers(1, [ id ].as_ptr());
+    } else if ctxt.extensions.gl_ext_framebuffer_object {
+        ctxt.gl.DeleteRenderbuffersEXT(1, [ id ].as_ptr());
+    } else {
+        // render buffers can't be created if none of the above is supported
+        unreachable!();
     }
 }
 
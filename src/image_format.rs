@@ -2,6 +2,8 @@
 This private module handles the various image formats in OpenGL.
 
 */
+use std::fmt;
+use std::error::Error;
 use gl;
 use context::Context;
 
@@ -12,6 +14,18 @@ use version::{Api, Version};
 #[derive(Copy, Clone, Debug)]
 pub struct FormatNotSupportedError;
 
+impl fmt::Display for FormatNotSupportedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for FormatNotSupportedError {
+    fn description(&self) -> &str {
+        "The requested format is not supported by the backend"
+    }
+}
+
 /// Texture format request.
 #[derive(Copy, Clone, Debug)]
 pub enum TextureFormatRequest {
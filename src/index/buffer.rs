@@ -1,4 +1,6 @@
-use buffer::{Buffer, BufferSlice, BufferAny, BufferType};
+use std::fmt;
+use std::error::Error;
+use buffer::{Buffer, BufferSlice, BufferAny, BufferAnySlice, BufferType};
 use buffer::{BufferMode, BufferCreationError};
 use gl;
 use BufferExt;
@@ -26,6 +28,34 @@ pub enum CreationError {
     BufferCreationError(BufferCreationError),
 }
 
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => err.fmt(fmt),
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::IndexTypeNotSupported => "The type of index is not supported by the \
+                                                      backend",
+            &CreationError::PrimitiveTypeNotSupported => "The type of primitives is not \
+                                                          supported by the backend",
+            &CreationError::BufferCreationError(_) => "An error happened while creating the buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<BufferCreationError> for CreationError {
     #[inline]
     fn from(err: BufferCreationError) -> CreationError {
@@ -305,6 +335,18 @@ impl IndexBufferAny {
     pub fn get_indices_type(&self) -> IndexType {
         self.data_type
     }
+
+    /// Returns `None` if out of range.
+    #[inline]
+    pub fn slice(&self, range: Range<usize>) -> Option<IndexBufferAnySlice> {
+        self.buffer.slice(range).map(|b| {
+            IndexBufferAnySlice {
+                buffer: b,
+                primitives: self.primitives,
+                data_type: self.data_type,
+            }
+        })
+    }
 }
 
 impl Deref for IndexBufferAny {
@@ -346,3 +388,68 @@ impl<'a> From<&'a IndexBufferAny> for IndicesSource<'a> {
         }
     }
 }
+
+/// Slice of an `IndexBufferAny`.
+#[derive(Debug, Copy, Clone)]
+pub struct IndexBufferAnySlice<'a> {
+    buffer: BufferAnySlice<'a>,
+    primitives: PrimitiveType,
+    data_type: IndexType,
+}
+
+impl<'a> IndexBufferAnySlice<'a> {
+    /// Returns the type of primitives associated with this index buffer.
+    #[inline]
+    pub fn get_primitives_type(&self) -> PrimitiveType {
+        self.primitives
+    }
+
+    /// Returns the data type of the indices inside this index buffer.
+    #[inline]
+    pub fn get_indices_type(&self) -> IndexType {
+        self.data_type
+    }
+
+    /// Returns `None` if out of range.
+    #[inline]
+    pub fn slice(&self, range: Range<usize>) -> Option<IndexBufferAnySlice<'a>> {
+        self.buffer.slice(range).map(|b| {
+            IndexBufferAnySlice {
+                buffer: b,
+                primitives: self.primitives,
+                data_type: self.data_type,
+            }
+        })
+    }
+}
+
+impl<'a> Deref for IndexBufferAnySlice<'a> {
+    type Target = BufferAnySlice<'a>;
+
+    #[inline]
+    fn deref(&self) -> &BufferAnySlice<'a> {
+        &self.buffer
+    }
+}
+
+impl<'a> From<IndexBufferAnySlice<'a>> for IndicesSource<'a> {
+    #[inline]
+    fn from(buf: IndexBufferAnySlice<'a>) -> IndicesSource<'a> {
+        IndicesSource::IndexBuffer {
+            buffer: buf.buffer,
+            data_type: buf.data_type,
+            primitives: buf.primitives,
+        }
+    }
+}
+
+impl<'a, 'r> From<&'r IndexBufferAnySlice<'a>> for IndicesSource<'a> {
+    #[inline]
+    fn from(buf: &'r IndexBufferAnySlice<'a>) -> IndicesSource<'a> {
+        IndicesSource::IndexBuffer {
+            buffer: buf.buffer,
+            data_type: buf.data_type,
+            primitives: buf.primitives,
+        }
+    }
+}
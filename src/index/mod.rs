@@ -16,12 +16,27 @@ There are eleven types of primitives, each one with a corresponding struct:
  - `TriangleFan`
  - `Patches`
 
-There are two ways to specify the indices that must be used:
+There are three ways to specify the indices that must be used:
 
  - Passing a reference to an `IndexBuffer`, which contains a list of indices.
+ - `ClientIndices`, which contains a reference to a list of indices stored in RAM. The indices
+   are uploaded by glium each time you draw.
  - `NoIndices`, in which case the vertices will be used in the order in which they are in the
    vertex buffer.
 
+You can also pass a slice of an index buffer, obtained with `IndexBuffer::slice` or
+`IndexBufferAny::slice`. This allows you to store the indices of multiple meshes in the same
+buffer and to draw them separately:
+
+```no_run
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+let indices = glium::IndexBuffer::new(&display, glium::index::PrimitiveType::TrianglesList,
+                                      &[0u16, 1, 2, 2, 1, 3]).unwrap();
+
+let first_mesh = indices.slice(0 .. 3).unwrap();
+let second_mesh = indices.slice(3 .. 6).unwrap();
+```
+
 ## Multidraw indirect
 
 In addition to indices, you can also use **multidraw indirect** rendering.
@@ -36,13 +51,18 @@ use version::Api;
 use version::Version;
 
 use std::mem;
+use std::slice;
 
 use buffer::BufferAnySlice;
 
-pub use self::buffer::{IndexBuffer, IndexBufferSlice, IndexBufferAny};
+pub use self::buffer::{IndexBuffer, IndexBufferSlice, IndexBufferAny, IndexBufferAnySlice};
 pub use self::buffer::CreationError as BufferCreationError;
 pub use self::multidraw::{DrawCommandsNoIndicesBuffer, DrawCommandNoIndices};
 pub use self::multidraw::{DrawCommandsIndicesBuffer, DrawCommandIndices};
+pub use self::multidraw::{DrawCommandNoIndicesBuilder, DrawCommandIndicesBuilder};
+pub use self::multidraw::DrawCommandError;
+
+pub mod optimize;
 
 mod buffer;
 mod multidraw;
@@ -80,6 +100,16 @@ pub enum IndicesSource<'a> {
         primitives: PrimitiveType,
     },
 
+    /// Use indices stored in RAM. They are uploaded by glium before drawing.
+    ClientData {
+        /// The raw data of the indices.
+        data: &'a [u8],
+        /// Type of indices in the data.
+        data_type: IndexType,
+        /// Type of primitives contained in the vertex source.
+        primitives: PrimitiveType,
+    },
+
     /// Don't use indices. Assemble primitives by using the order in which the vertices are in
     /// the vertices source.
     NoIndices {
@@ -96,6 +126,7 @@ impl<'a> IndicesSource<'a> {
             &IndicesSource::IndexBuffer { primitives, .. } => primitives,
             &IndicesSource::MultidrawArray { primitives, .. } => primitives,
             &IndicesSource::MultidrawElement { primitives, .. } => primitives,
+            &IndicesSource::ClientData { primitives, .. } => primitives,
             &IndicesSource::NoIndices { primitives } => primitives,
         }
     }
@@ -202,6 +233,47 @@ impl<'a, 'b> From<&'b NoIndices> for IndicesSource<'a> {
     }
 }
 
+/// A list of indices stored in RAM that can be used as an indices source.
+///
+/// The indices are uploaded to video memory each time you draw, through a buffer managed by
+/// glium. This is useful for small lists of indices that change every frame, like debug lines
+/// or user interfaces, as you don't have to create a new `IndexBuffer` each time. For large or
+/// static lists of indices, you should use an `IndexBuffer` instead.
+///
+/// # Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+/// let indices = [0u16, 1, 2, 2, 1, 3];
+/// let indices = glium::index::ClientIndices(glium::index::PrimitiveType::TrianglesList,
+///                                           &indices);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct ClientIndices<'a, T: 'a>(pub PrimitiveType, pub &'a [T]) where T: Index;
+
+impl<'a, T> From<ClientIndices<'a, T>> for IndicesSource<'a> where T: Index {
+    #[inline]
+    fn from(indices: ClientIndices<'a, T>) -> IndicesSource<'a> {
+        let data = unsafe {
+            slice::from_raw_parts(indices.1.as_ptr() as *const u8,
+                                  indices.1.len() * mem::size_of::<T>())
+        };
+
+        IndicesSource::ClientData {
+            data: data,
+            data_type: <T as Index>::get_type(),
+            primitives: indices.0,
+        }
+    }
+}
+
+impl<'a, 'b, T> From<&'b ClientIndices<'a, T>> for IndicesSource<'a> where T: Index {
+    #[inline]
+    fn from(indices: &'b ClientIndices<'a, T>) -> IndicesSource<'a> {
+        (*indices).into()
+    }
+}
+
 /// Type of the indices in an index source.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]    // GLenum
@@ -2,8 +2,9 @@
 //!
 use libc;
 
-use std::ops::Deref;
-use std::ops::DerefMut;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut, Range};
 
 use backend::Facade;
 use buffer::{BufferCreationError, BufferType, BufferMode, Buffer};
@@ -45,6 +46,257 @@ pub struct DrawCommandIndices {
 implement_uniform_block!(DrawCommandIndices, count, instance_count, first_index,
                          base_vertex, base_instance);
 
+/// Error that can happen when validating a draw command.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DrawCommandError {
+    /// The range of vertices or indices is invalid or goes past the end of the source.
+    ElementsOutOfRange,
+
+    /// The range of instances is invalid or goes past the end of the per-instance source.
+    InstancesOutOfRange,
+}
+
+impl fmt::Display for DrawCommandError {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for DrawCommandError {
+    fn description(&self) -> &str {
+        match self {
+            &DrawCommandError::ElementsOutOfRange => "The range of vertices or indices of the \
+                                                      command is out of range",
+            &DrawCommandError::InstancesOutOfRange => "The range of instances of the command is \
+                                                       out of range",
+        }
+    }
+}
+
+/// Checks whether `start .. start + count` is within `0 .. len`.
+#[inline]
+fn check_range(start: libc::c_uint, count: libc::c_uint, len: usize) -> bool {
+    (start as usize).checked_add(count as usize).map(|end| end <= len).unwrap_or(false)
+}
+
+impl DrawCommandNoIndices {
+    /// Checks that this command doesn't read outside of the vertices source.
+    ///
+    /// `vertices_count` is the number of vertices in the vertices source, and `instances_count`
+    /// the number of elements in the per-instance source if there is one.
+    ///
+    /// Out-of-range commands in a multidraw indirect buffer are not detected by glium when
+    /// drawing and can crash the driver, so you are encouraged to call this function on
+    /// commands that are written from the CPU.
+    pub fn validate(&self, vertices_count: usize, instances_count: Option<usize>)
+                    -> Result<(), DrawCommandError>
+    {
+        if !check_range(self.first_index, self.count, vertices_count) {
+            return Err(DrawCommandError::ElementsOutOfRange);
+        }
+
+        if let Some(instances_count) = instances_count {
+            if !check_range(self.base_instance, self.instance_count, instances_count) {
+                return Err(DrawCommandError::InstancesOutOfRange);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl DrawCommandIndices {
+    /// Checks that this command doesn't read outside of the index buffer.
+    ///
+    /// `indices_count` is the number of indices in the index buffer, and `instances_count`
+    /// the number of elements in the per-instance source if there is one.
+    ///
+    /// Out-of-range commands in a multidraw indirect buffer are not detected by glium when
+    /// drawing and can crash the driver, so you are encouraged to call this function on
+    /// commands that are written from the CPU.
+    pub fn validate(&self, indices_count: usize, instances_count: Option<usize>)
+                    -> Result<(), DrawCommandError>
+    {
+        if !check_range(self.first_index, self.count, indices_count) {
+            return Err(DrawCommandError::ElementsOutOfRange);
+        }
+
+        if let Some(instances_count) = instances_count {
+            if !check_range(self.base_instance, self.instance_count, instances_count) {
+                return Err(DrawCommandError::InstancesOutOfRange);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a `DrawCommandNoIndices` and checks it against the length of the vertices source.
+///
+/// # Example
+///
+/// ```
+/// use glium::index::DrawCommandNoIndicesBuilder;
+///
+/// let command = DrawCommandNoIndicesBuilder::new(12).vertices(6 .. 12).build().unwrap();
+/// assert_eq!(command.first_index, 6);
+/// assert_eq!(command.count, 6);
+///
+/// assert!(DrawCommandNoIndicesBuilder::new(12).vertices(6 .. 13).build().is_err());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct DrawCommandNoIndicesBuilder {
+    vertices_count: usize,
+    instances_count: Option<usize>,
+    vertices: Range<usize>,
+    instances: Range<usize>,
+}
+
+impl DrawCommandNoIndicesBuilder {
+    /// Starts building a command that draws all the vertices of a vertices source containing
+    /// `vertices_count` vertices, with one instance.
+    #[inline]
+    pub fn new(vertices_count: usize) -> DrawCommandNoIndicesBuilder {
+        DrawCommandNoIndicesBuilder {
+            vertices_count: vertices_count,
+            instances_count: None,
+            vertices: 0 .. vertices_count,
+            instances: 0 .. 1,
+        }
+    }
+
+    /// Sets the range of vertices to draw.
+    #[inline]
+    pub fn vertices(mut self, range: Range<usize>) -> DrawCommandNoIndicesBuilder {
+        self.vertices = range;
+        self
+    }
+
+    /// Sets the range of instances to draw.
+    #[inline]
+    pub fn instances(mut self, range: Range<usize>) -> DrawCommandNoIndicesBuilder {
+        self.instances = range;
+        self
+    }
+
+    /// Sets the number of elements in the per-instance source, so that the range of instances
+    /// is checked as well.
+    #[inline]
+    pub fn instances_count(mut self, count: usize) -> DrawCommandNoIndicesBuilder {
+        self.instances_count = Some(count);
+        self
+    }
+
+    /// Checks the parameters and builds the command.
+    pub fn build(self) -> Result<DrawCommandNoIndices, DrawCommandError> {
+        if self.vertices.start > self.vertices.end {
+            return Err(DrawCommandError::ElementsOutOfRange);
+        }
+
+        if self.instances.start > self.instances.end {
+            return Err(DrawCommandError::InstancesOutOfRange);
+        }
+
+        let command = DrawCommandNoIndices {
+            count: (self.vertices.end - self.vertices.start) as libc::c_uint,
+            instance_count: (self.instances.end - self.instances.start) as libc::c_uint,
+            first_index: self.vertices.start as libc::c_uint,
+            base_instance: self.instances.start as libc::c_uint,
+        };
+
+        try!(command.validate(self.vertices_count, self.instances_count));
+        Ok(command)
+    }
+}
+
+/// Builds a `DrawCommandIndices` and checks it against the length of the index buffer.
+///
+/// # Example
+///
+/// ```
+/// use glium::index::DrawCommandIndicesBuilder;
+///
+/// let command = DrawCommandIndicesBuilder::new(36).indices(0 .. 18).base_vertex(4)
+///                                                 .build().unwrap();
+/// assert_eq!(command.count, 18);
+/// assert_eq!(command.base_vertex, 4);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct DrawCommandIndicesBuilder {
+    indices_count: usize,
+    instances_count: Option<usize>,
+    indices: Range<usize>,
+    instances: Range<usize>,
+    base_vertex: u32,
+}
+
+impl DrawCommandIndicesBuilder {
+    /// Starts building a command that draws all the indices of an index buffer containing
+    /// `indices_count` indices, with one instance.
+    #[inline]
+    pub fn new(indices_count: usize) -> DrawCommandIndicesBuilder {
+        DrawCommandIndicesBuilder {
+            indices_count: indices_count,
+            instances_count: None,
+            indices: 0 .. indices_count,
+            instances: 0 .. 1,
+            base_vertex: 0,
+        }
+    }
+
+    /// Sets the range of indices to use in the index buffer.
+    #[inline]
+    pub fn indices(mut self, range: Range<usize>) -> DrawCommandIndicesBuilder {
+        self.indices = range;
+        self
+    }
+
+    /// Sets the value to add to each index.
+    #[inline]
+    pub fn base_vertex(mut self, base_vertex: u32) -> DrawCommandIndicesBuilder {
+        self.base_vertex = base_vertex;
+        self
+    }
+
+    /// Sets the range of instances to draw.
+    #[inline]
+    pub fn instances(mut self, range: Range<usize>) -> DrawCommandIndicesBuilder {
+        self.instances = range;
+        self
+    }
+
+    /// Sets the number of elements in the per-instance source, so that the range of instances
+    /// is checked as well.
+    #[inline]
+    pub fn instances_count(mut self, count: usize) -> DrawCommandIndicesBuilder {
+        self.instances_count = Some(count);
+        self
+    }
+
+    /// Checks the parameters and builds the command.
+    pub fn build(self) -> Result<DrawCommandIndices, DrawCommandError> {
+        if self.indices.start > self.indices.end {
+            return Err(DrawCommandError::ElementsOutOfRange);
+        }
+
+        if self.instances.start > self.instances.end {
+            return Err(DrawCommandError::InstancesOutOfRange);
+        }
+
+        let command = DrawCommandIndices {
+            count: (self.indices.end - self.indices.start) as libc::c_uint,
+            instance_count: (self.instances.end - self.instances.start) as libc::c_uint,
+            first_index: self.indices.start as libc::c_uint,
+            base_vertex: self.base_vertex as libc::c_uint,
+            base_instance: self.instances.start as libc::c_uint,
+        };
+
+        try!(command.validate(self.indices_count, self.instances_count));
+        Ok(command)
+    }
+}
+
 /// A buffer containing a list of draw commands.
 pub struct DrawCommandsNoIndicesBuffer {
     buffer: Buffer<[DrawCommandNoIndices]>,
@@ -0,0 +1,317 @@
+/*!
+Utilities to optimize lists of indices on the CPU before uploading them in an `IndexBuffer`.
+
+ - `to_u16` converts a list of `u32` indices to `u16`s when all the values fit, which halves
+   the size of the index buffer. `build_compact` does the same thing and directly builds an
+   `IndexBufferAny`.
+ - `optimize_vertex_cache` re-orders the triangles of a list of triangles so that the vertices
+   that have just been processed by the GPU are reused as much as possible.
+ - `triangles_to_strip` converts a list of triangles into a triangle strip.
+
+*/
+use std::collections::HashMap;
+
+use backend::Facade;
+use index::{Index, IndexBuffer, IndexBufferAny, PrimitiveType};
+use index::BufferCreationError;
+
+/// Converts a list of `u32` indices to `u16`s.
+///
+/// Returns `None` if one of the indices doesn't fit in a `u16`.
+pub fn to_u16(indices: &[u32]) -> Option<Vec<u16>> {
+    if indices.iter().any(|&i| i > 0xffff) {
+        return None;
+    }
+
+    Some(indices.iter().map(|&i| i as u16).collect())
+}
+
+/// Builds an index buffer from a list of `u32` indices. The buffer contains `u16`s if all the
+/// indices fit, and `u32`s otherwise.
+pub fn build_compact<F>(facade: &F, primitives: PrimitiveType, indices: &[u32])
+                        -> Result<IndexBufferAny, BufferCreationError> where F: Facade
+{
+    match to_u16(indices) {
+        Some(indices) => Ok(try!(IndexBuffer::new(facade, primitives, &indices)).into()),
+        None => Ok(try!(IndexBuffer::new(facade, primitives, indices)).into()),
+    }
+}
+
+/// Size of the simulated post-transform cache used by `optimize_vertex_cache`.
+const CACHE_SIZE: usize = 32;
+
+/// Re-orders the triangles of a list of triangles in order to make a better use of the
+/// post-transform vertex cache of the GPU.
+///
+/// The order of the vertices within each triangle is preserved, so that the winding of each
+/// triangle is not modified.
+///
+/// This function implements Tom Forsyth's "Linear-Speed Vertex Cache Optimisation" algorithm.
+///
+/// # Panic
+///
+/// Panics if the number of indices is not a multiple of three.
+pub fn optimize_vertex_cache<T>(indices: &[T]) -> Vec<T> where T: Index + Into<u32> {
+    assert!(indices.len() % 3 == 0);
+
+    let triangles_count = indices.len() / 3;
+    let vertices_count = indices.iter().map(|&i| value(i) + 1).max().unwrap_or(0);
+
+    // for each vertex, the list of triangles that use it and haven't been added yet
+    let mut vertex_triangles: Vec<Vec<usize>> = vec![Vec::new(); vertices_count];
+    for (triangle, chunk) in indices.chunks(3).enumerate() {
+        for &index in chunk {
+            vertex_triangles[value(index)].push(triangle);
+        }
+    }
+
+    let mut cache_position: Vec<Option<usize>> = vec![None; vertices_count];
+    let mut vertex_scores: Vec<f32> = (0 .. vertices_count).map(|v| {
+        vertex_score(None, vertex_triangles[v].len())
+    }).collect();
+
+    let mut triangle_added = vec![false; triangles_count];
+    let mut triangle_scores: Vec<f32> = indices.chunks(3).map(|chunk| {
+        chunk.iter().map(|&i| vertex_scores[value(i)]).fold(0.0, |a, b| a + b)
+    }).collect();
+
+    let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut output = Vec::with_capacity(indices.len());
+
+    // index used to look for the next triangle when no triangle of the cache is available
+    let mut next_unadded = 0;
+
+    for _ in 0 .. triangles_count {
+        // choosing the triangle with the best score among the ones that use a vertex in
+        // the cache
+        let mut best: Option<usize> = None;
+        for &vertex in cache.iter() {
+            for &triangle in vertex_triangles[vertex].iter() {
+                if best.map(|b| triangle_scores[triangle] > triangle_scores[b]).unwrap_or(true) {
+                    best = Some(triangle);
+                }
+            }
+        }
+
+        let best = match best {
+            Some(b) => b,
+            None => {
+                while triangle_added[next_unadded] {
+                    next_unadded += 1;
+                }
+                next_unadded
+            }
+        };
+
+        // adding the triangle to the output
+        triangle_added[best] = true;
+        let vertices = &indices[best * 3 .. best * 3 + 3];
+        output.extend(vertices.iter().cloned());
+
+        for &vertex in vertices {
+            let vertex = value(vertex);
+            vertex_triangles[vertex].retain(|&t| t != best);
+        }
+
+        // updating the simulated cache
+        let mut new_cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+        for vertex in vertices.iter().map(|&v| value(v)).chain(cache.iter().cloned()) {
+            if !new_cache.contains(&vertex) {
+                new_cache.push(vertex);
+            }
+        }
+
+        for &evicted in new_cache.iter().skip(CACHE_SIZE) {
+            cache_position[evicted] = None;
+        }
+        new_cache.truncate(CACHE_SIZE);
+
+        for (position, &vertex) in new_cache.iter().enumerate() {
+            cache_position[vertex] = Some(position);
+        }
+
+        // updating the scores of the vertices that were in the cache before and after
+        for &vertex in cache.iter().chain(new_cache.iter()) {
+            vertex_scores[vertex] = vertex_score(cache_position[vertex],
+                                                 vertex_triangles[vertex].len());
+        }
+
+        for &vertex in cache.iter().chain(new_cache.iter()) {
+            for &triangle in vertex_triangles[vertex].iter() {
+                triangle_scores[triangle] = indices[triangle * 3 .. triangle * 3 + 3].iter()
+                    .map(|&i| vertex_scores[value(i)]).fold(0.0, |a, b| a + b);
+            }
+        }
+
+        cache = new_cache;
+    }
+
+    output
+}
+
+/// Returns the value of an index.
+#[inline]
+fn value<T>(index: T) -> usize where T: Into<u32> {
+    let index: u32 = index.into();
+    index as usize
+}
+
+/// Computes the score of a vertex for `optimize_vertex_cache`.
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+    if remaining_triangles == 0 {
+        return -1.0;
+    }
+
+    let cache_score = match cache_position {
+        None => 0.0,
+        // the three vertices of the latest triangle have a fixed score, so that the algorithm
+        // doesn't favor using the last triangle's vertices
+        Some(position) if position < 3 => 0.75,
+        Some(position) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(1.5)
+        },
+    };
+
+    // vertices with few remaining triangles get a bonus, so that lone triangles are not left
+    // behind
+    let valence_score = 2.0 * (remaining_triangles as f32).powf(-0.5);
+
+    cache_score + valence_score
+}
+
+/// Converts a list of triangles into a triangle strip.
+///
+/// The triangles are grouped in strips, which are joined with degenerate triangles. The winding
+/// of each triangle is preserved.
+///
+/// # Panic
+///
+/// Panics if the number of indices is not a multiple of three.
+pub fn triangles_to_strip<T>(indices: &[T]) -> Vec<T> where T: Index + Into<u32> {
+    assert!(indices.len() % 3 == 0);
+
+    let triangles_count = indices.len() / 3;
+
+    // for each directed edge, the list of triangles that contain it and their third vertex
+    let mut edges: HashMap<(u32, u32), Vec<(usize, T)>> = HashMap::new();
+    for (triangle, chunk) in indices.chunks(3).enumerate() {
+        for n in 0 .. 3 {
+            let key = (value(chunk[n]) as u32, value(chunk[(n + 1) % 3]) as u32);
+            edges.entry(key).or_insert_with(Vec::new).push((triangle, chunk[(n + 2) % 3]));
+        }
+    }
+
+    let mut triangle_used = vec![false; triangles_count];
+    let mut output: Vec<T> = Vec::with_capacity(indices.len());
+
+    for first in 0 .. triangles_count {
+        if triangle_used[first] {
+            continue;
+        }
+
+        triangle_used[first] = true;
+        let mut strip: Vec<T> = indices[first * 3 .. first * 3 + 3].to_vec();
+
+        // extending the strip as long as there is an unused triangle that shares the latest
+        // edge, in the direction required by the winding of the next triangle
+        loop {
+            let len = strip.len();
+            let (a, b) = (value(strip[len - 2]) as u32, value(strip[len - 1]) as u32);
+            let key = if (len - 2) % 2 == 0 { (a, b) } else { (b, a) };
+
+            let next = edges.get(&key).and_then(|list| {
+                list.iter().find(|&&(t, _)| !triangle_used[t]).map(|&(t, v)| (t, v))
+            });
+
+            match next {
+                Some((triangle, vertex)) => {
+                    triangle_used[triangle] = true;
+                    strip.push(vertex);
+                },
+                None => break
+            }
+        }
+
+        // joining with the previous strip with degenerate triangles, while making sure that the
+        // new strip starts at an even position so that its winding is preserved
+        if let Some(&last) = output.last() {
+            output.push(last);
+            output.push(strip[0]);
+            if output.len() % 2 != 0 {
+                output.push(strip[0]);
+            }
+        }
+
+        output.extend(strip.into_iter());
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_u16, optimize_vertex_cache, triangles_to_strip};
+
+    /// Returns the list of non-degenerate triangles of a strip, with their winding.
+    fn strip_triangles(strip: &[u16]) -> Vec<[u16; 3]> {
+        let mut result = Vec::new();
+        for n in 0 .. strip.len().saturating_sub(2) {
+            let (a, b, c) = (strip[n], strip[n + 1], strip[n + 2]);
+            if a == b || b == c || a == c {
+                continue;
+            }
+            result.push(if n % 2 == 0 { [a, b, c] } else { [b, a, c] });
+        }
+        result
+    }
+
+    /// Rotates a triangle so that its smallest index is first, without changing its winding.
+    fn normalize(t: [u16; 3]) -> [u16; 3] {
+        if t[0] <= t[1] && t[0] <= t[2] {
+            t
+        } else if t[1] <= t[0] && t[1] <= t[2] {
+            [t[1], t[2], t[0]]
+        } else {
+            [t[2], t[0], t[1]]
+        }
+    }
+
+    fn sorted_triangles(list: &[[u16; 3]]) -> Vec<[u16; 3]> {
+        let mut list: Vec<_> = list.iter().map(|&t| normalize(t)).collect();
+        list.sort();
+        list
+    }
+
+    #[test]
+    fn u32_to_u16() {
+        assert_eq!(to_u16(&[0, 5, 65535]), Some(vec![0, 5, 65535]));
+        assert_eq!(to_u16(&[0, 65536]), None);
+    }
+
+    #[test]
+    fn vertex_cache_keeps_triangles() {
+        let indices = [0u16, 1, 2, 5, 6, 7, 2, 1, 3, 3, 1, 4, 7, 6, 8];
+        let optimized = optimize_vertex_cache(&indices);
+
+        let before: Vec<_> = indices.chunks(3).map(|c| [c[0], c[1], c[2]]).collect();
+        let after: Vec<_> = optimized.chunks(3).map(|c| [c[0], c[1], c[2]]).collect();
+        assert_eq!(sorted_triangles(&before), sorted_triangles(&after));
+    }
+
+    #[test]
+    fn strip_keeps_triangles() {
+        // a quad split in two triangles, plus an isolated triangle
+        let indices = [0u16, 1, 2, 2, 1, 3, 4, 5, 6];
+        let strip = triangles_to_strip(&indices);
+
+        let before: Vec<_> = indices.chunks(3).map(|c| [c[0], c[1], c[2]]).collect();
+        assert_eq!(sorted_triangles(&before), sorted_triangles(&strip_triangles(&strip)));
+    }
+
+    #[test]
+    fn strip_quad() {
+        let strip = triangles_to_strip(&[0u16, 1, 2, 2, 1, 3]);
+        assert_eq!(strip, vec![0, 1, 2, 3]);
+    }
+}
@@ -83,6 +83,16 @@ result to the user.
    uniform buffer for the name of the block when drawing.
  - **Vertex array objects**: VAOs are automatically managed by glium if the backend supports them.
 
+# Environment variables
+
+The following environment variables are read when a context is created:
+
+ - `GLIUM_DISABLE_EXTENSIONS`: a list of OpenGL extensions separated with commas, that are
+   considered as unsupported by the backend. See `Context::new_with_disabled_extensions`.
+ - `GLIUM_ERROR_CHECK`: when to check for OpenGL errors. See `debug::ErrorCheckPolicy`.
+ - `GLIUM_STATE_CHECK`: when to compare the state cache with the actual state of the
+   context. See `debug::StateCheckPolicy`.
+
 */
 #![warn(missing_docs)]
 
@@ -95,6 +105,8 @@ extern crate lazy_static;
 
 #[cfg(feature = "cgmath")]
 extern crate cgmath;
+#[cfg(feature = "half")]
+extern crate half;
 #[cfg(feature = "image")]
 extern crate image;
 extern crate libc;
@@ -116,6 +128,7 @@ pub use sync::{LinearSyncFence, SyncFence};
 pub use texture::{Texture, Texture2d};
 pub use version::{Api, Version, get_supported_glsl_version};
 
+use std::any::Any;
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::thread;
@@ -128,27 +141,46 @@ mod macros;
 
 pub mod backend;
 pub mod buffer;
+pub mod command_encoder;
+pub mod culling;
 pub mod debug;
+pub mod debug_overlay;
 pub mod draw_parameters;
 pub mod framebuffer;
 pub mod index;
+pub mod native_fence;
 pub mod pixel_buffer;
+pub mod pipeline;
+pub mod profiler;
 pub mod program;
+pub mod render_graph;
+pub mod resource_queue;
+pub mod screenshot;
+pub mod shadow_map;
 pub mod uniforms;
+pub mod upload_scheduler;
 pub mod vertex;
 pub mod texture;
+#[cfg(feature = "gl_trace")]
+pub mod trace;
+#[cfg(feature = "xr")]
+pub mod xr;
 
 mod context;
 mod fbo;
 mod image_format;
 mod ops;
+mod ring_buffer;
 mod sampler_object;
 mod sync;
 mod utils;
 mod version;
 mod vertex_array_object;
 
-mod gl {
+/// The raw OpenGL bindings used by glium.
+///
+/// See `Context::exec_with_raw_gl`.
+pub mod gl {
     include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
 }
 
@@ -272,6 +304,18 @@ trait ContextExt {
 
     /// Returns the capabilities of the backend.
     fn capabilities(&self) -> &context::Capabilities;
+
+    /// Changes the surface that the default framebuffer refers to, and makes the context
+    /// current with it. `None` means the surface of the backend.
+    fn set_current_surface(&self, surface: Option<Rc<backend::SurfaceBackend>>);
+
+    /// Swaps the buffers of a surface, with optional damage rectangles.
+    fn swap_surface_buffers(&self, surface: &backend::SurfaceBackend, damage: Option<&[Rect]>)
+                            -> Result<(), SwapBuffersError>;
+
+    /// Keeps `object` alive until `done` returns true. `done` is called at the end of each
+    /// frame.
+    fn keep_alive_until(&self, object: Box<Any>, done: Box<Fn() -> bool>);
 }
 
 /// Internal trait for programs.
@@ -295,6 +339,9 @@ trait ProgramExt {
 
     fn get_uniform(&self, name: &str) -> Option<&program::Uniform>;
 
+    /// Returns the label of the program, for the error messages.
+    fn get_label(&self) -> Option<String>;
+
     fn get_uniform_blocks(&self) -> &HashMap<String, program::UniformBlock>;
 
     fn get_shader_storage_blocks(&self) -> &HashMap<String, program::UniformBlock>;
@@ -726,6 +773,24 @@ pub trait Surface {
         V: vertex::MultiVerticesSource<'b>, I: Into<index::IndicesSource<'a>>,
         U: uniforms::Uniforms;
 
+    /// Draws with a pipeline state.
+    ///
+    /// This is the same as `draw`, except that the program and the draw parameters come from
+    /// the pipeline state. The states that are controlled by the draw parameters are only
+    /// synchronized if the previous draw call didn't use the same pipeline state. See the
+    /// `pipeline` module.
+    ///
+    /// The default implementation calls `draw` with the program and the draw parameters of the
+    /// pipeline state, and synchronizes the states at each draw call.
+    fn draw_with_pipeline<'a, 'b, V, I, U>(&mut self, vertex_buffer: V, index_buffer: I,
+        pipeline: &pipeline::PipelineState, uniforms: &U) -> Result<(), DrawError> where
+        V: vertex::MultiVerticesSource<'b>, I: Into<index::IndicesSource<'a>>,
+        U: uniforms::Uniforms
+    {
+        self.draw(vertex_buffer, index_buffer, pipeline.get_program(), uniforms,
+                  pipeline.get_draw_parameters())
+    }
+
     /// Blits from the default framebuffer.
     fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
                        filter: uniforms::MagnifySamplerFilter);
@@ -790,13 +855,27 @@ pub enum DrawError {
 
     /// The type of a vertex attribute in the vertices source doesn't match what the
     /// program requires.
-    AttributeTypeMismatch,
+    AttributeTypeMismatch {
+        /// Name of the attribute.
+        name: String,
+        /// The type expected by the program.
+        expected: vertex::AttributeType,
+        /// The type found in the vertices source.
+        obtained: vertex::AttributeType,
+        /// Label of the program, if it has one.
+        program: Option<String>,
+    },
 
     /// One of the attributes required by the program is missing from the vertex format.
     ///
     /// Note that it is perfectly valid to have an attribute in the vertex format that is
     /// not used by the program.
-    AttributeMissing,
+    AttributeMissing {
+        /// Name of the missing attribute.
+        name: String,
+        /// Label of the program, if it has one.
+        program: Option<String>,
+    },
 
     /// The viewport's dimensions are not supported by the backend.
     ViewportTooLarge,
@@ -810,18 +889,27 @@ pub enum DrawError {
         name: String,
         /// The expected type.
         expected: uniforms::UniformType,
+        /// The type of the value that has been given, or `None` if it doesn't correspond to
+        /// any GLSL type.
+        obtained: Option<uniforms::UniformType>,
+        /// Label of the program, if it has one.
+        program: Option<String>,
     },
 
     /// Tried to bind a uniform buffer to a single uniform value.
     UniformBufferToValue {
         /// Name of the uniform you are trying to bind.
         name: String,
+        /// Label of the program, if it has one.
+        program: Option<String>,
     },
 
     /// Tried to bind a single uniform value to a uniform block.
     UniformValueToBlock {
         /// Name of the uniform you are trying to bind.
         name: String,
+        /// Label of the program, if it has one.
+        program: Option<String>,
     },
 
     /// The layout of the content of the uniform buffer does not match the layout of the block.
@@ -830,6 +918,8 @@ pub enum DrawError {
         name: String,
         /// The error giving more details about the mismatch.
         err: uniforms::LayoutMismatchError,
+        /// Label of the program, if it has one.
+        program: Option<String>,
     },
 
     /// The number of vertices per patch that has been requested is not supported.
@@ -844,6 +934,13 @@ pub enum DrawError {
     /// Trying to use a sampler, but they are not supported by the backend.
     SamplersNotSupported,
 
+    /// Trying to use a sampler with the `Min` or `Max` reduction mode, but this is not
+    /// supported by the backend.
+    SamplerReductionModeNotSupported,
+
+    /// Trying to use a sampler with a wrap function that is not supported by the backend.
+    SamplerWrapFunctionNotSupported,
+
     /// When you use instancing, all vertices sources must have the same size.
     InstancesCountMismatch,
 
@@ -870,6 +967,17 @@ pub enum DrawError {
 
     /// One of the blending parameters is not supported by the backend.
     BlendingParameterNotSupported,
+
+    /// The context has been lost. Nothing is drawn.
+    ContextLost,
+
+    /// The requested polygon mode is not supported by the backend, and couldn't be emulated.
+    ///
+    /// See `EmulationOptions` in the `draw_parameters` module.
+    PolygonModeNotSupported,
+
+    /// Pixel local storage has been requested but is not supported by the backend.
+    PixelLocalStorageNotSupported,
 }
 
 impl std::fmt::Display for DrawError {
@@ -877,28 +985,43 @@ impl std::fmt::Display for DrawError {
         match self {
             &DrawError::NoDepthBuffer => write!(fmt, "A depth function has been requested but no \
                                                       depth buffer is available."),
-            &DrawError::AttributeTypeMismatch => write!(fmt, "The type of a vertex attribute in \
-                                                              the vertices source doesn't match \
-                                                              what the program requires."),
-            &DrawError::AttributeMissing => write!(fmt, "One of the attributes required by the \
-                                                         program is missing from the vertex \
-                                                         format."),
+            &DrawError::AttributeTypeMismatch { ref name, ref expected, ref obtained,
+                                                ref program } => {
+                write!(fmt, "The type of the vertex attribute `{}` in the vertices source doesn't \
+                             match what the program{} requires. Program expected {:?}, got {:?}.",
+                       name, ProgramLabel(program), expected, obtained)
+            },
+            &DrawError::AttributeMissing { ref name, ref program } => {
+                write!(fmt, "The attribute `{}` required by the program{} is missing from the \
+                             vertex format.", name, ProgramLabel(program))
+            },
             &DrawError::ViewportTooLarge => write!(fmt, "The viewport's dimensions are not \
                                                          supported by the backend."),
             &DrawError::InvalidDepthRange => write!(fmt, "The depth range is outside of the \
                                                           `(0, 1)` range."),
-            &DrawError::UniformTypeMismatch { ref name, ref expected } => {
-                write!(fmt, "The type of a uniform doesn't match what the program requires.")
+            &DrawError::UniformTypeMismatch { ref name, ref expected, ref obtained,
+                                              ref program } => {
+                try!(write!(fmt, "The type of the uniform `{}` doesn't match what the program{} \
+                                  requires. Program expected {:?}, ", name, ProgramLabel(program),
+                            expected));
+
+                match obtained {
+                    &Some(ref obtained) => write!(fmt, "got {:?}.", obtained),
+                    &None => write!(fmt, "got a value that can't be used with a single uniform."),
+                }
+            },
+            &DrawError::UniformBufferToValue { ref name, ref program } => {
+                write!(fmt, "Tried to bind a uniform buffer to the single uniform value `{}` of \
+                             the program{}.", name, ProgramLabel(program))
             },
-            &DrawError::UniformBufferToValue { ref name } => write!(fmt, "Tried to bind a uniform \
-                                                                          buffer to a single \
-                                                                          uniform value."),
-            &DrawError::UniformValueToBlock { ref name } => {
-                write!(fmt, "Tried to bind a single uniform value to a uniform block.")
+            &DrawError::UniformValueToBlock { ref name, ref program } => {
+                write!(fmt, "Tried to bind a single uniform value to the uniform block `{}` of \
+                             the program{}.", name, ProgramLabel(program))
             },
-            &DrawError::UniformBlockLayoutMismatch { ref name, .. } => {
+            &DrawError::UniformBlockLayoutMismatch { ref name, ref err, ref program } => {
                 write!(fmt, "The layout of the content of the uniform buffer does not match \
-                             the layout of the block.")
+                             the layout of the block `{}` of the program{}: {}.", name,
+                       ProgramLabel(program), err)
             },
             &DrawError::UnsupportedVerticesPerPatch => write!(fmt, "The number of vertices per \
                                                                     patch that has been requested \
@@ -911,6 +1034,14 @@ impl std::fmt::Display for DrawError {
                                                                    without submitting patches."),
             &DrawError::SamplersNotSupported => write!(fmt, "Trying to use a sampler, but they are \
                                                              not supported by the backend."),
+            &DrawError::SamplerReductionModeNotSupported => write!(fmt, "The reduction mode of a \
+                                                                         sampler is not \
+                                                                         supported by the \
+                                                                         backend."),
+            &DrawError::SamplerWrapFunctionNotSupported => write!(fmt, "The wrap function of a \
+                                                                        sampler is not \
+                                                                        supported by the \
+                                                                        backend."),
             &DrawError::InstancesCountMismatch => write!(fmt, "When you use instancing, all \
                                                                vertices sources must have the \
                                                                same size"),
@@ -927,12 +1058,95 @@ impl std::fmt::Display for DrawError {
                                                                     vertex, but this is not \
                                                                     supported by the backend."),
             &DrawError::RasterizerDiscardNotSupported => write!(fmt, "Discarding rasterizer \
-                                                                      output is not supported by
+                                                                      output is not supported by \
                                                                       the backend."),
             &DrawError::DepthClampNotSupported => write!(fmt, "The depth clamp mode is not \
                                                                supported by the backend."),
-            &DrawError::BlendingParameterNotSupported => write!(fmt, "One the blending parameters is not \
+            &DrawError::BlendingParameterNotSupported => write!(fmt, "One of the blending \
+                                                                      parameters is not \
                                                                       supported by the backend."),
+            &DrawError::ContextLost => write!(fmt, "The context has been lost."),
+            &DrawError::PolygonModeNotSupported => write!(fmt, "The requested polygon mode is \
+                                                                not supported by the backend."),
+            &DrawError::PixelLocalStorageNotSupported => write!(fmt, "Pixel local storage is not \
+                                                                      supported by the \
+                                                                      backend."),
+        }
+    }
+}
+
+/// Formats the label of a program, if any, for the error messages.
+struct ProgramLabel<'a>(&'a Option<String>);
+
+impl<'a> std::fmt::Display for ProgramLabel<'a> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self.0 {
+            &Some(ref label) => write!(fmt, " `{}`", label),
+            &None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for DrawError {
+    fn description(&self) -> &str {
+        match self {
+            &DrawError::NoDepthBuffer => "A depth function has been requested but no depth \
+                                          buffer is available",
+            &DrawError::AttributeTypeMismatch { .. } => "The type of a vertex attribute in the \
+                                                         vertices source doesn't match what the \
+                                                         program requires",
+            &DrawError::AttributeMissing { .. } => "One of the attributes required by the \
+                                                    program is missing from the vertex format",
+            &DrawError::ViewportTooLarge => "The viewport's dimensions are not supported by the \
+                                             backend",
+            &DrawError::InvalidDepthRange => "The depth range is outside of the `(0, 1)` range",
+            &DrawError::UniformTypeMismatch { .. } => "The type of a uniform doesn't match what \
+                                                       the program requires",
+            &DrawError::UniformBufferToValue { .. } => "Tried to bind a uniform buffer to a \
+                                                        single uniform value",
+            &DrawError::UniformValueToBlock { .. } => "Tried to bind a single uniform value to a \
+                                                       uniform block",
+            &DrawError::UniformBlockLayoutMismatch { .. } => "The layout of the content of the \
+                                                              uniform buffer does not match the \
+                                                              layout of the block",
+            &DrawError::UnsupportedVerticesPerPatch => "The number of vertices per patch that \
+                                                        has been requested is not supported",
+            &DrawError::TessellationNotSupported => "Tessellation is not supported by the backend",
+            &DrawError::TessellationWithoutPatches => "Using a program which contains \
+                                                       tessellation shaders, but without \
+                                                       submitting patches",
+            &DrawError::SamplersNotSupported => "Samplers are not supported by the backend",
+            &DrawError::SamplerReductionModeNotSupported => "The reduction mode of a sampler is \
+                                                             not supported by the backend",
+            &DrawError::SamplerWrapFunctionNotSupported => "The wrap function of a sampler is \
+                                                            not supported by the backend",
+            &DrawError::InstancesCountMismatch => "All the per-instance vertices sources must \
+                                                   have the same size",
+            &DrawError::VerticesSourcesLengthMismatch => "All the vertices sources must have the \
+                                                          same size",
+            &DrawError::TransformFeedbackNotSupported => "Not drawing primitives is not \
+                                                          supported by the backend",
+            &DrawError::WrongQueryOperation => "Wrong query operation",
+            &DrawError::SmoothingNotSupported => "Smoothing is not supported by the backend",
+            &DrawError::ProvokingVertexNotSupported => "The requested provoking vertex is not \
+                                                        supported by the backend",
+            &DrawError::RasterizerDiscardNotSupported => "Discarding the rasterizer output is \
+                                                          not supported by the backend",
+            &DrawError::DepthClampNotSupported => "Depth clamping is not supported by the backend",
+            &DrawError::BlendingParameterNotSupported => "One of the blending parameters is not \
+                                                          supported by the backend",
+            &DrawError::ContextLost => "The context has been lost",
+            &DrawError::PolygonModeNotSupported => "The requested polygon mode is not supported \
+                                                    by the backend",
+            &DrawError::PixelLocalStorageNotSupported => "Pixel local storage is not supported by \
+                                                          the backend",
+        }
+    }
+
+    fn cause(&self) -> Option<&std::error::Error> {
+        match self {
+            &DrawError::UniformBlockLayoutMismatch { ref err, .. } => Some(err),
+            _ => None,
         }
     }
 }
@@ -956,6 +1170,42 @@ pub enum SwapBuffersError {
     /// This error can be returned when `set_finish()` is called multiple times, or `finish()` is
     /// called after `set_finish()`.
     AlreadySwapped,
+    /// The window system failed to swap the buffers and returned the given error code.
+    WindowSystemError(u32),
+}
+
+impl std::fmt::Display for SwapBuffersError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        use std::error::Error;
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl std::error::Error for SwapBuffersError {
+    fn description(&self) -> &str {
+        match self {
+            &SwapBuffersError::ContextLost => "The OpenGL context has been lost and needs to be \
+                                               recreated",
+            &SwapBuffersError::AlreadySwapped => "The buffers have already been swapped",
+            &SwapBuffersError::WindowSystemError(_) => "The window system failed to swap the \
+                                                        buffers",
+        }
+    }
+}
+
+/// Describes how the buffers swaps are synchronized with the vertical refresh of the screen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwapInterval {
+    /// Buffers are swapped immediately, which can produce tearing.
+    Immediate,
+
+    /// Buffers are swapped after the given number of vertical refreshes. `Sync(1)` is the
+    /// usual vsync.
+    Sync(u32),
+
+    /// Same as `Sync(1)`, except that buffers are swapped immediately if the frame is late.
+    /// This reduces stuttering when the framerate drops below the refresh rate.
+    Adaptive,
 }
 
 /// Implementation of `Surface`, targeting the default framebuffer.
@@ -967,19 +1217,54 @@ pub struct Frame {
     context: Rc<Context>,
     dimensions: (u32, u32),
     destroyed: bool,        // TODO: use a linear type instead.
+    surface: Option<Rc<backend::SurfaceBackend>>,
 }
 
 impl Frame {
     /// Builds a new `Frame`. Use the `draw` function on `Display` instead of this function.
     #[inline]
     pub fn new(context: Rc<Context>, dimensions: (u32, u32)) -> Frame {
+        context.reset_statistics();
+
+        Frame {
+            context: context,
+            dimensions: dimensions,
+            destroyed: false,
+            surface: None,
+        }
+    }
+
+    /// Builds a new `Frame` that draws on an additional surface of the context, for example
+    /// another window.
+    ///
+    /// All the resources of the context (textures, buffers, programs, etc.) can be used. Until
+    /// the frame is finished, the default framebuffer of the context refers to the surface.
+    ///
+    /// # Panic
+    ///
+    /// Panics if another `Frame` that draws on a surface hasn't been finished yet.
+    pub fn new_on_surface(context: Rc<Context>, surface: Rc<backend::SurfaceBackend>) -> Frame {
+        let dimensions = surface.get_framebuffer_dimensions();
+        context.set_current_surface(Some(surface.clone()));
+        context.reset_statistics();
+
         Frame {
             context: context,
             dimensions: dimensions,
             destroyed: false,
+            surface: Some(surface),
         }
     }
 
+    /// Returns the counters of the operations that have been executed since this frame has been
+    /// created, or `None` if statistics are disabled.
+    ///
+    /// See `Context::set_statistics_enabled`.
+    #[inline]
+    pub fn statistics(&self) -> Option<debug::FrameStatistics> {
+        self.context.get_statistics()
+    }
+
     /// Stop drawing, swap the buffers, and consume the Frame.
     ///
     /// See the documentation of `SwapBuffersError` about what is being returned.
@@ -999,8 +1284,45 @@ impl Frame {
         }
 
         self.destroyed = true;
+
+        if let Some(surface) = self.surface.take() {
+            let result = self.context.swap_surface_buffers(&*surface, None);
+            self.context.set_current_surface(None);
+            return result;
+        }
+
         self.context.swap_buffers()
     }
+
+    /// Stop drawing, swap the buffers, and consume the Frame. Tells the window system that
+    /// only the content of the given rectangles has changed since the previous frame.
+    ///
+    /// This can save power when only a small part of the window is redrawn, for example in
+    /// editors. If the backend doesn't support this, the buffers are swapped normally.
+    ///
+    /// See the documentation of `SwapBuffersError` about what is being returned.
+    #[inline]
+    pub fn finish_with_damage(mut self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        self.set_finish_with_damage(damage)
+    }
+
+    /// Same as `set_finish`, but with damage rectangles. See `finish_with_damage`.
+    #[inline]
+    pub fn set_finish_with_damage(&mut self, damage: &[Rect]) -> Result<(), SwapBuffersError> {
+        if self.destroyed {
+            return Err(SwapBuffersError::AlreadySwapped);
+        }
+
+        self.destroyed = true;
+
+        if let Some(surface) = self.surface.take() {
+            let result = self.context.swap_surface_buffers(&*surface, Some(damage));
+            self.context.set_current_surface(None);
+            return result;
+        }
+
+        self.context.swap_buffers_with_damage(damage)
+    }
 }
 
 impl Surface for Frame {
@@ -1049,7 +1371,23 @@ impl Surface for Frame {
         }
 
         ops::draw(&self.context, None, vertex_buffer, index_buffer.into(), program,
-                  uniforms, draw_parameters, (self.dimensions.0 as u32, self.dimensions.1 as u32))
+                  uniforms, draw_parameters, (self.dimensions.0 as u32, self.dimensions.1 as u32),
+                  None)
+    }
+
+    fn draw_with_pipeline<'a, 'b, V, I, U>(&mut self, vertex_buffer: V, index_buffer: I,
+                                           pipeline: &pipeline::PipelineState, uniforms: &U)
+                                           -> Result<(), DrawError>
+                                           where I: Into<index::IndicesSource<'a>>,
+                                           U: uniforms::Uniforms,
+                                           V: vertex::MultiVerticesSource<'b>
+    {
+        let draw_parameters = pipeline.get_draw_parameters();
+        try!(check_depth_buffer(&*self, draw_parameters));
+
+        ops::draw(&self.context, None, vertex_buffer, index_buffer.into(),
+                  pipeline.get_program(), uniforms, draw_parameters,
+                  (self.dimensions.0 as u32, self.dimensions.1 as u32), Some(pipeline.get_id()))
     }
 
     #[inline]
@@ -1135,6 +1473,14 @@ pub enum GliumCreationError<T> {
 
     /// The OpenGL implementation is too old.
     IncompatibleOpenGl(String),
+
+    /// The default framebuffer doesn't have the properties that have been requested. Contains
+    /// the properties that have been obtained.
+    FramebufferRequestNotSatisfied(backend::DefaultFramebufferProperties),
+
+    /// An extension that should be considered as unsupported isn't known by glium. Contains
+    /// its name.
+    UnknownExtension(String),
 }
 
 impl<T> std::fmt::Display for GliumCreationError<T> where T: std::error::Error {
@@ -1149,7 +1495,13 @@ impl<T> std::error::Error for GliumCreationError<T> where T: std::error::Error {
     fn description(&self) -> &str {
         match self {
             &GliumCreationError::BackendCreationError(_) => "Error while creating the backend",
-            &GliumCreationError::IncompatibleOpenGl(_) => "The OpenGL implementation is too old to work with glium",
+            &GliumCreationError::IncompatibleOpenGl(_) => "The OpenGL implementation is too old \
+                                                           to work with glium",
+            &GliumCreationError::FramebufferRequestNotSatisfied(_) => {
+                "The default framebuffer doesn't have the requested properties"
+            },
+            &GliumCreationError::UnknownExtension(_) => "An extension to disable isn't known \
+                                                         by glium",
         }
     }
 
@@ -1158,6 +1510,8 @@ impl<T> std::error::Error for GliumCreationError<T> where T: std::error::Error {
         match self {
             &GliumCreationError::BackendCreationError(ref err) => Some(err as &std::error::Error),
             &GliumCreationError::IncompatibleOpenGl(_) => None,
+            &GliumCreationError::FramebufferRequestNotSatisfied(_) => None,
+            &GliumCreationError::UnknownExtension(_) => None,
         }
     }
 }
@@ -1169,19 +1523,45 @@ impl<T> std::convert::From<T> for GliumCreationError<T> {
     }
 }
 
+/// Returns `NoDepthBuffer` if the draw parameters use the depth buffer and the surface doesn't
+/// have one.
+fn check_depth_buffer<S>(surface: &S, draw_parameters: &DrawParameters) -> Result<(), DrawError>
+                         where S: Surface
+{
+    if !surface.has_depth_buffer() && (draw_parameters.depth_test.requires_depth_buffer() ||
+                                       draw_parameters.depth_write)
+    {
+        return Err(DrawError::NoDepthBuffer);
+    }
+
+    Ok(())
+}
+
 #[allow(dead_code)]
 #[inline]
 fn get_gl_error(ctxt: &mut context::CommandContext) -> Option<&'static str> {
+    // with the `EveryCall` policy, the OpenGL functions have already called `glGetError`
+    if let Some(error) = ctxt.gl.take_unread_error() {
+        return Some(gl_error_to_str(error));
+    }
+
     match unsafe { ctxt.gl.GetError() } {
         gl::NO_ERROR => None,
-        gl::INVALID_ENUM => Some("GL_INVALID_ENUM"),
-        gl::INVALID_VALUE => Some("GL_INVALID_VALUE"),
-        gl::INVALID_OPERATION => Some("GL_INVALID_OPERATION"),
-        gl::INVALID_FRAMEBUFFER_OPERATION => Some("GL_INVALID_FRAMEBUFFER_OPERATION"),
-        gl::OUT_OF_MEMORY => Some("GL_OUT_OF_MEMORY"),
-        gl::STACK_UNDERFLOW => Some("GL_STACK_UNDERFLOW"),
-        gl::STACK_OVERFLOW => Some("GL_STACK_OVERFLOW"),
-        gl::CONTEXT_LOST => Some("GL_CONTEXT_LOST"),
-        _ => Some("Unknown glGetError return value")
+        error => Some(gl_error_to_str(error)),
+    }
+}
+
+/// Returns the name of an error returned by `glGetError`.
+fn gl_error_to_str(error: gl::types::GLenum) -> &'static str {
+    match error {
+        gl::INVALID_ENUM => "GL_INVALID_ENUM",
+        gl::INVALID_VALUE => "GL_INVALID_VALUE",
+        gl::INVALID_OPERATION => "GL_INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+        gl::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+        gl::CONTEXT_LOST => "GL_CONTEXT_LOST",
+        _ => "Unknown glGetError return value"
     }
 }
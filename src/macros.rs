@@ -34,6 +34,17 @@ macro_rules! assert_no_gl_error {
     }
 }
 
+/// Records a marker at the start of a glium operation if the `gl_trace` feature is enabled.
+#[cfg(feature = "gl_trace")]
+macro_rules! trace_operation {
+    ($name:expr) => (::trace::marker($name));
+}
+
+#[cfg(not(feature = "gl_trace"))]
+macro_rules! trace_operation {
+    ($name:expr) => (());
+}
+
 /// Returns an implementation-defined type which implements the `Uniform` trait.
 ///
 /// ## Example
@@ -77,6 +88,10 @@ macro_rules! uniform {
 ///
 /// The parameters must be the name of the struct and the names of its fields.
 ///
+/// A field can be followed by `normalize(true)` to indicate that its integer values must be
+/// turned into floats between `0.0` and `1.0` (or `-1.0` and `1.0` for signed types) before
+/// being passed to the vertex shader.
+///
 /// ## Example
 ///
 /// ```
@@ -93,9 +108,25 @@ macro_rules! uniform {
 /// # }
 /// ```
 ///
+/// With normalization:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate glium;
+/// # fn main() {
+/// #[derive(Copy, Clone)]
+/// struct Vertex {
+///     position: [f32; 3],
+///     color: [u8; 4],
+/// }
+///
+/// implement_vertex!(Vertex, position, color normalize(true));
+/// # }
+/// ```
+///
 #[macro_export]
 macro_rules! implement_vertex {
-    ($struct_name:ident, $($field_name:ident),+) => (
+    ($struct_name:ident, $($field_name:ident $(normalize($should_normalize:expr))*),+) => (
         impl $crate::vertex::Vertex for $struct_name {
             #[inline]
             fn build_bindings() -> $crate::vertex::VertexFormat {
@@ -122,6 +153,14 @@ macro_rules! implement_vertex {
                                 let dummy: &$struct_name = unsafe { ::std::mem::transmute(0usize) };
                                 attr_type_of_val(&dummy.$field_name)
                             },
+                            {
+                                #[allow(unused_mut)]
+                                let mut normalize = false;
+                                $(
+                                    normalize = $should_normalize;
+                                )*
+                                normalize
+                            },
                         )
                     ),+
                 ])
@@ -129,8 +168,8 @@ macro_rules! implement_vertex {
         }
     );
 
-    ($struct_name:ident, $($field_name:ident),+,) => (
-        implement_vertex!($struct_name, $($field_name),+);
+    ($struct_name:ident, $($field_name:ident $(normalize($should_normalize:expr))*),+,) => (
+        implement_vertex!($struct_name, $($field_name $(normalize($should_normalize))*),+);
     );
 }
 
@@ -572,6 +611,25 @@ mod tests {
         implement_vertex!(Foo, pos,);
     }
 
+    #[test]
+    fn normalize_impl_vertex() {
+        use vertex::Vertex;
+
+        #[derive(Copy, Clone)]
+        struct Foo {
+            pos: [f32; 2],
+            color: [u8; 4],
+            id: u16,
+        }
+
+        implement_vertex!(Foo, pos, color normalize(true), id normalize(false));
+
+        let bindings = <Foo as Vertex>::build_bindings();
+        assert_eq!(bindings[0].3, false);
+        assert_eq!(bindings[1].3, true);
+        assert_eq!(bindings[2].3, false);
+    }
+
     #[test]
     fn assert_no_error_macro() {
         struct Dummy;
@@ -0,0 +1,253 @@
+/*!
+Exchanges fences with other APIs and processes as native fence file descriptors.
+
+The `EGL_ANDROID_native_fence_sync` extension turns a fence of the OpenGL command stream into a
+file descriptor (a sync file on Linux) that is signaled when the commands before it are
+finished. This is how frames are synchronized with SurfaceFlinger, media codecs, Vulkan or other
+processes on Android and on recent Linux drivers, without waiting for the GPU with `glFinish`.
+
+ - `NativeFences::export` inserts a fence after the commands that have been submitted so far,
+   and returns a file descriptor that is signaled once they are finished.
+ - `NativeFences::import` makes the GPU wait until an external file descriptor is signaled
+   before executing the next commands. The CPU isn't blocked. This requires the
+   `EGL_KHR_wait_sync` extension.
+
+The EGL functions are loaded with a function that behaves like `eglGetProcAddress`, and the
+display must be the `EGLDisplay` of the context. With the EGL headless backend,
+`EglHeadlessFacade::native_fences` does this for you.
+
+# Example
+
+```no_run
+use glium::native_fence::NativeFences;
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let egl_display: *const u8 = ::std::ptr::null();
+# let egl_extensions = "";
+# fn get_proc_address(_: &str) -> *const u8 { ::std::ptr::null() }
+# let codec_fence = 0;
+
+let fences = unsafe {
+    NativeFences::new(&display, egl_display as *const _, egl_extensions,
+                      |symbol| get_proc_address(symbol) as *const _)
+}.unwrap();
+
+// the next draw commands wait until the decoder has written the frame
+unsafe { fences.import(codec_fence) }.unwrap();
+
+// ... draw ...
+
+// handed to the compositor, which closes it once the frame has been displayed
+let fd = fences.export().unwrap();
+```
+
+*/
+use backend::Facade;
+use context::Context;
+use ContextExt;
+
+use libc;
+
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use libc;
+
+    pub type EGLBoolean = libc::c_uint;
+    pub type EGLenum = libc::c_uint;
+    pub type EGLint = i32;
+    pub type EGLDisplay = *const libc::c_void;
+    pub type EGLSyncKHR = *const libc::c_void;
+
+    pub const EGL_FALSE: EGLint = 0;
+    pub const EGL_NONE: EGLint = 0x3038;
+    pub const EGL_SYNC_NATIVE_FENCE_ANDROID: EGLenum = 0x3144;
+    pub const EGL_SYNC_NATIVE_FENCE_FD_ANDROID: EGLint = 0x3145;
+    pub const EGL_NO_NATIVE_FENCE_FD_ANDROID: EGLint = -1;
+
+    pub type PFNEGLCREATESYNCKHRPROC =
+        extern "system" fn(EGLDisplay, EGLenum, *const EGLint) -> EGLSyncKHR;
+    pub type PFNEGLDESTROYSYNCKHRPROC = extern "system" fn(EGLDisplay, EGLSyncKHR) -> EGLBoolean;
+    pub type PFNEGLDUPNATIVEFENCEFDANDROIDPROC =
+        extern "system" fn(EGLDisplay, EGLSyncKHR) -> EGLint;
+    pub type PFNEGLWAITSYNCKHRPROC = extern "system" fn(EGLDisplay, EGLSyncKHR, EGLint) -> EGLint;
+}
+
+/// Error that can happen when exchanging native fences.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NativeFenceError {
+    /// The display doesn't support the extension required by this operation.
+    NotSupported,
+
+    /// The implementation failed to create a native fence.
+    ExportFailed,
+
+    /// The implementation refused the file descriptor. It is still owned by the caller.
+    InvalidFence,
+
+    /// The implementation failed to wait for the fence. The file descriptor has already been
+    /// closed.
+    WaitFailed,
+}
+
+impl fmt::Display for NativeFenceError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for NativeFenceError {
+    #[inline]
+    fn description(&self) -> &str {
+        use self::NativeFenceError::*;
+        match *self {
+            NotSupported => "The display doesn't support native fences",
+            ExportFailed => "Failed to create a native fence",
+            InvalidFence => "The native fence file descriptor was refused",
+            WaitFailed => "Failed to wait for the native fence",
+        }
+    }
+}
+
+/// The EGL functions that exchange native fences, for the display of a context.
+///
+/// See the documentation of the module.
+pub struct NativeFences {
+    context: Rc<Context>,
+    display: ffi::EGLDisplay,
+    create_sync: ffi::PFNEGLCREATESYNCKHRPROC,
+    destroy_sync: ffi::PFNEGLDESTROYSYNCKHRPROC,
+    dup_native_fence_fd: ffi::PFNEGLDUPNATIVEFENCEFDANDROIDPROC,
+    wait_sync: Option<ffi::PFNEGLWAITSYNCKHRPROC>,
+}
+
+impl NativeFences {
+    /// Loads the functions that exchange native fences.
+    ///
+    /// `egl_extensions` is the list returned by `eglQueryString(display, EGL_EXTENSIONS)`, and
+    /// `get_proc_address` must behave like `eglGetProcAddress`.
+    ///
+    /// Returns `NotSupported` if the display doesn't support `EGL_ANDROID_native_fence_sync`.
+    ///
+    /// # Safety
+    ///
+    /// `display` must be the `EGLDisplay` of the context of `facade`, and must stay valid as
+    /// long as the `NativeFences` is alive.
+    pub unsafe fn new<F, L>(facade: &F, display: *const libc::c_void, egl_extensions: &str,
+                            mut get_proc_address: L)
+                            -> Result<NativeFences, NativeFenceError>
+                            where F: Facade, L: FnMut(&str) -> *const libc::c_void
+    {
+        let has_extension = |name: &str| egl_extensions.split(' ').any(|e| e == name);
+
+        if !has_extension("EGL_ANDROID_native_fence_sync") ||
+           !(has_extension("EGL_KHR_fence_sync") || has_extension("EGL_KHR_reusable_sync"))
+        {
+            return Err(NativeFenceError::NotSupported);
+        }
+
+        let create_sync = get_proc_address("eglCreateSyncKHR");
+        let destroy_sync = get_proc_address("eglDestroySyncKHR");
+        let dup_native_fence_fd = get_proc_address("eglDupNativeFenceFDANDROID");
+
+        if create_sync.is_null() || destroy_sync.is_null() || dup_native_fence_fd.is_null() {
+            return Err(NativeFenceError::NotSupported);
+        }
+
+        let wait_sync = if has_extension("EGL_KHR_wait_sync") {
+            let wait_sync = get_proc_address("eglWaitSyncKHR");
+            if wait_sync.is_null() { None } else { Some(mem::transmute(wait_sync)) }
+        } else {
+            None
+        };
+
+        Ok(NativeFences {
+            context: facade.get_context().clone(),
+            display: display,
+            create_sync: mem::transmute(create_sync),
+            destroy_sync: mem::transmute(destroy_sync),
+            dup_native_fence_fd: mem::transmute(dup_native_fence_fd),
+            wait_sync: wait_sync,
+        })
+    }
+
+    /// Returns true if `import` is supported.
+    #[inline]
+    pub fn is_import_supported(&self) -> bool {
+        self.wait_sync.is_some()
+    }
+
+    /// Inserts a fence after the commands that have been submitted so far, and returns a file
+    /// descriptor that is signaled once they are finished.
+    ///
+    /// The commands are flushed. The caller owns the file descriptor and must close it, usually
+    /// by handing it to the API or the process that waits for it.
+    pub fn export(&self) -> Result<libc::c_int, NativeFenceError> {
+        let ctxt = self.context.make_current();
+
+        let attributes = [
+            ffi::EGL_SYNC_NATIVE_FENCE_FD_ANDROID, ffi::EGL_NO_NATIVE_FENCE_FD_ANDROID,
+            ffi::EGL_NONE
+        ];
+
+        unsafe {
+            let sync = (self.create_sync)(self.display, ffi::EGL_SYNC_NATIVE_FENCE_ANDROID,
+                                          attributes.as_ptr());
+            if sync.is_null() {
+                return Err(NativeFenceError::ExportFailed);
+            }
+
+            // the file descriptor is only created once the fence has been flushed
+            ctxt.gl.Flush();
+
+            let fd = (self.dup_native_fence_fd)(self.display, sync);
+            (self.destroy_sync)(self.display, sync);
+
+            if fd == ffi::EGL_NO_NATIVE_FENCE_FD_ANDROID {
+                Err(NativeFenceError::ExportFailed)
+            } else {
+                Ok(fd as libc::c_int)
+            }
+        }
+    }
+
+    /// Makes the GPU wait until the fence is signaled before executing the commands that are
+    /// submitted after this call. Doesn't block.
+    ///
+    /// The file descriptor is closed by the implementation, unless `NotSupported` or
+    /// `InvalidFence` is returned.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be a native fence file descriptor, for example a sync file, and must not be
+    /// used by the caller after this call succeeds.
+    pub unsafe fn import(&self, fd: libc::c_int) -> Result<(), NativeFenceError> {
+        let wait_sync = match self.wait_sync {
+            Some(f) => f,
+            None => return Err(NativeFenceError::NotSupported),
+        };
+
+        // the wait is inserted in the command stream of the current context
+        let _ctxt = self.context.make_current();
+
+        let attributes = [ffi::EGL_SYNC_NATIVE_FENCE_FD_ANDROID, fd as ffi::EGLint, ffi::EGL_NONE];
+        let sync = (self.create_sync)(self.display, ffi::EGL_SYNC_NATIVE_FENCE_ANDROID,
+                                      attributes.as_ptr());
+        if sync.is_null() {
+            return Err(NativeFenceError::InvalidFence);
+        }
+
+        let result = wait_sync(self.display, sync, 0);
+        (self.destroy_sync)(self.display, sync);
+
+        if result == ffi::EGL_FALSE {
+            Err(NativeFenceError::WaitFailed)
+        } else {
+            Ok(())
+        }
+    }
+}
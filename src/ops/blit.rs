@@ -4,7 +4,7 @@ use Rect;
 use context::Context;
 use ContextExt;
 
-use fbo::FramebuffersContainer;
+use fbo::{self, FramebuffersContainer};
 use fbo::ValidatedAttachments;
 
 use gl;
@@ -15,6 +15,8 @@ pub fn blit(context: &Context, source: Option<&ValidatedAttachments>,
             target: Option<&ValidatedAttachments>, mask: gl::types::GLbitfield,
             src_rect: &Rect, target_rect: &BlitTarget, filter: gl::types::GLenum)
 {
+    trace_operation!("blit");
+
     unsafe {
         let mut ctxt = context.make_current();
 
@@ -22,10 +24,14 @@ pub fn blit(context: &Context, source: Option<&ValidatedAttachments>,
         let source = FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, source);
         let target = FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, target);
 
+        // blitting is an error when pixel local storage is enabled
+        fbo::end_pixel_local_storage(&mut ctxt);
+
         // scissor testing influences blitting
         if ctxt.state.enabled_scissor_test {
             ctxt.gl.Disable(gl::SCISSOR_TEST);
             ctxt.state.enabled_scissor_test = false;
+            ctxt.state.pipeline_state = None;
         }
 
         // trying to do a named blit if possible
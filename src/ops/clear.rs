@@ -18,12 +18,18 @@ pub fn clear(context: &Context, framebuffer: Option<&ValidatedAttachments>,
              rect: Option<&Rect>, color: Option<(f32, f32, f32, f32)>, depth: Option<f32>,
              stencil: Option<i32>)
 {
+    trace_operation!("clear");
+
     unsafe {
         let mut ctxt = context.make_current();
 
         let fbo_id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
+        fbo::end_pixel_local_storage(&mut ctxt);
         fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false);
 
+        // the masks and the scissor box are modified below
+        ctxt.state.pipeline_state = None;
+
         if ctxt.state.enabled_rasterizer_discard {
             ctxt.gl.Disable(gl::RASTERIZER_DISCARD);
             ctxt.state.enabled_rasterizer_discard = false;
@@ -19,11 +19,15 @@ use {Program, ToGlEnum};
 use index::{self, IndicesSource, PrimitiveType};
 use vertex::{MultiVerticesSource, VerticesSource, TransformFeedbackSession};
 use vertex_array_object::VertexAttributesSystem;
+use ring_buffer::RingBuffer;
+use buffer::Inserter;
+use super::emulation;
+use GlObject;
 
 use draw_parameters::DrawParameters;
 use draw_parameters::{Blend, BlendingFunction, BackfaceCullingMode,
     LinearBlendingFactor};
-use draw_parameters::{DepthTest, DepthClamp, PolygonMode, StencilTest};
+use draw_parameters::{DepthTest, DepthClamp, PolygonMode, PolygonOffset, StencilTest};
 use draw_parameters::{SamplesQueryParam, TransformFeedbackPrimitivesWrittenQuery};
 use draw_parameters::{PrimitivesGeneratedQuery, TimeElapsedQuery, ConditionalRendering};
 use draw_parameters::{Smooth, ProvokingVertex};
@@ -35,13 +39,21 @@ use version::Version;
 use version::Api;
 
 /// Draws everything.
+///
+/// `pipeline` is the id of the `PipelineState` that contains `program` and `draw_parameters`,
+/// if any. The draw parameters of a pipeline state have already been validated, and they are
+/// not synchronized again if the same pipeline state was used by the previous draw call.
 pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachments>,
                       vertex_buffers: V, indices: IndicesSource,
                       program: &Program, uniforms: &U, draw_parameters: &DrawParameters,
-                      dimensions: (u32, u32)) -> Result<(), DrawError>
+                      dimensions: (u32, u32), pipeline: Option<usize>) -> Result<(), DrawError>
                       where U: Uniforms, V: MultiVerticesSource<'a>
 {
-    try!(draw_parameters::validate(context, draw_parameters));
+    trace_operation!("draw");
+
+    if pipeline.is_none() {
+        try!(draw_parameters::validate(context, draw_parameters));
+    }
 
     // this contains the list of fences that will need to be fulfilled after the draw command
     // has started
@@ -77,15 +89,77 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
         },
     };
 
+    // the vertices sources are also needed in order to emulate draw parameters
+    let vertex_buffers = vertex_buffers.iter().collect::<Vec<_>>();
+
+    // emulating the draw parameters that the backend doesn't support, if any
+    let emulated = {
+        let vertices_count = match indices {
+            IndicesSource::NoIndices { .. } => vertices_sources_count(&vertex_buffers),
+            _ => None
+        };
+
+        let instanced = vertex_buffers.iter().any(|src| match src {
+            &VerticesSource::VertexBuffer(_, _, per_instance) => per_instance,
+            &VerticesSource::Marker { per_instance, .. } => per_instance,
+        });
+
+        try!(emulation::emulate(context, &indices, vertices_count, instanced, draw_parameters))
+    };
+
+    let indices = match emulated {
+        Some(ref emulated) => IndicesSource::ClientData {
+            data: &emulated.data,
+            data_type: emulated.data_type,
+            primitives: emulated.primitives,
+        },
+        None => indices,
+    };
+
+    // `glPolygonMode` doesn't exist on OpenGL ES, and the polygon mode has either been emulated
+    // or doesn't apply to the primitives being drawn
+    let polygon_mode = if emulation::is_polygon_mode_supported(context) {
+        draw_parameters.polygon_mode
+    } else {
+        PolygonMode::Fill
+    };
+
+    let provoking_vertex = match emulated {
+        Some(ref emulated) if emulated.provoking_vertex_emulated => ProvokingVertex::LastVertex,
+        _ => draw_parameters.provoking_vertex,
+    };
+
     // starting the state changes
     let mut ctxt = context.make_current();
 
+    if ctxt.state.lost_context {
+        return Err(DrawError::ContextLost);
+    }
+
+    // uploading the indices stored in RAM, if any
+    // contains the ID of the buffer and the offset of the indices within it
+    let client_indices = match indices {
+        IndicesSource::ClientData { data, data_type, .. } => {
+            let mut ring_buffer = match ctxt.ring_buffer.take() {
+                Some(buffer) => buffer,
+                None => RingBuffer::new(&mut ctxt, 16384),
+            };
+
+            let offset = ring_buffer.upload(&mut ctxt, data, data_type.get_size());
+            let id = ring_buffer.get_id();
+            *ctxt.ring_buffer = Some(ring_buffer);
+            Some((id, offset))
+        },
+        _ => None
+    };
+
     // handling vertices source
     let (vertices_count, instances_count, base_vertex) = {
         let index_buffer = match indices {
             IndicesSource::IndexBuffer { buffer, .. } => Some(buffer),
             IndicesSource::MultidrawArray { .. } => None,
             IndicesSource::MultidrawElement { indices, .. } => Some(indices),
+            IndicesSource::ClientData { .. } => None,
             IndicesSource::NoIndices { .. } => None,
         };
 
@@ -101,14 +175,18 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
         };
 
         // object that is used to build the bindings
-        let mut binder = VertexAttributesSystem::start(&mut ctxt, program, index_buffer,
-                                                       use_base_vertex);
+        let mut binder = match client_indices {
+            Some((id, _)) => VertexAttributesSystem::start_with_raw_indices(&mut ctxt, program, id,
+                                                                            use_base_vertex),
+            None => VertexAttributesSystem::start(&mut ctxt, program, index_buffer,
+                                                  use_base_vertex),
+        };
         // number of vertices in the vertices sources, or `None` if there is a mismatch
         let mut vertices_count: Option<usize> = None;
         // number of instances to draw
         let mut instances_count: Option<usize> = None;
 
-        for src in vertex_buffers.iter() {
+        for src in vertex_buffers.into_iter() {
             match src {
                 VerticesSource::VertexBuffer(buffer, format, per_instance) => {
                     // TODO: assert!(buffer.get_elements_size() == total_size(format));
@@ -165,7 +243,7 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
             }
         }
 
-        (vertices_count, instances_count, binder.bind().unwrap_or(0))
+        (vertices_count, instances_count, try!(binder.bind()).unwrap_or(0))
     };
 
     // binding the FBO to draw upon
@@ -180,19 +258,31 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
 
     // sync-ing draw_parameters
     unsafe {
-        try!(sync_depth(&mut ctxt, draw_parameters.depth_test, draw_parameters.depth_write,
-                        draw_parameters.depth_range, draw_parameters.depth_clamp));
-        sync_stencil(&mut ctxt, &draw_parameters);
-        try!(sync_blending(&mut ctxt, draw_parameters.blend));
-        sync_color_mask(&mut ctxt, draw_parameters.color_mask);
-        sync_line_width(&mut ctxt, draw_parameters.line_width);
-        sync_point_size(&mut ctxt, draw_parameters.point_size);
-        sync_polygon_mode(&mut ctxt, draw_parameters.backface_culling, draw_parameters.polygon_mode);
-        sync_multisampling(&mut ctxt, draw_parameters.multisampling);
-        sync_dithering(&mut ctxt, draw_parameters.dithering);
-        sync_viewport_scissor(&mut ctxt, draw_parameters.viewport, draw_parameters.scissor,
-                              dimensions);
-        try!(sync_rasterizer_discard(&mut ctxt, draw_parameters.draw_primitives));
+        // the states that only depend on the draw parameters and on the dimensions of the target
+        // are already correct if the previous draw call used the same pipeline state
+        let pipeline_state = pipeline.map(|id| (id, dimensions));
+        if pipeline_state.is_none() || ctxt.state.pipeline_state != pipeline_state {
+            ctxt.state.pipeline_state = None;
+
+            try!(sync_depth(&mut ctxt, draw_parameters.depth_test, draw_parameters.depth_write,
+                            draw_parameters.depth_range, draw_parameters.depth_clamp));
+            sync_stencil(&mut ctxt, &draw_parameters);
+            try!(sync_blending(&mut ctxt, draw_parameters.blend));
+            sync_color_mask(&mut ctxt, draw_parameters.color_mask);
+            sync_line_width(&mut ctxt, draw_parameters.line_width);
+            sync_point_size(&mut ctxt, draw_parameters.point_size);
+            sync_polygon_mode(&mut ctxt, draw_parameters.backface_culling, polygon_mode);
+            sync_polygon_offset(&mut ctxt, draw_parameters.polygon_offset);
+            sync_multisampling(&mut ctxt, draw_parameters.multisampling);
+            sync_dithering(&mut ctxt, draw_parameters.dithering);
+            sync_viewport_scissor(&mut ctxt, draw_parameters.viewport, draw_parameters.scissor,
+                                  dimensions);
+            try!(sync_rasterizer_discard(&mut ctxt, draw_parameters.draw_primitives));
+            sync_primitive_bounding_box(&mut ctxt, &draw_parameters.primitive_bounding_box);
+
+            ctxt.state.pipeline_state = pipeline_state;
+        }
+
         sync_vertices_per_patch(&mut ctxt, vertices_per_patch);
         try!(sync_queries(&mut ctxt, draw_parameters.samples_passed_query,
                           draw_parameters.time_elapsed_query,
@@ -200,8 +290,8 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                           draw_parameters.transform_feedback_primitives_written_query));
         sync_conditional_render(&mut ctxt, draw_parameters.condition);
         try!(sync_smooth(&mut ctxt, draw_parameters.smooth, indices.get_primitives_type()));
-        try!(sync_provoking_vertex(&mut ctxt, draw_parameters.provoking_vertex));
-        sync_primitive_bounding_box(&mut ctxt, &draw_parameters.primitive_bounding_box);
+        try!(sync_provoking_vertex(&mut ctxt, provoking_vertex));
+        try!(sync_pixel_local_storage(&mut ctxt, draw_parameters.pixel_local_storage));
 
         // TODO: make sure that the program is the right one
         // TODO: changing the current transform feedback requires pausing/unbinding before changing the program
@@ -212,6 +302,17 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
         }
     }
 
+    // without coherent framebuffer fetch, the result of the previous draw calls is only
+    // visible to the fragment shader after a barrier
+    if program.uses_framebuffer_fetch() &&
+       ctxt.extensions.gl_ext_shader_framebuffer_fetch_non_coherent &&
+       !ctxt.extensions.gl_ext_shader_framebuffer_fetch &&
+       !ctxt.extensions.gl_arm_shader_framebuffer_fetch &&
+       !ctxt.extensions.gl_nv_shader_framebuffer_fetch
+    {
+        unsafe { ctxt.gl.FramebufferFetchBarrierEXT(); }
+    }
+
     // drawing
     // TODO: make this code more readable
     {
@@ -225,74 +326,19 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                 }
 
                 unsafe {
-                    if let Some(instances_count) = instances_count {
-                        if base_vertex != 0 {
-                            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
-                               ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                               ctxt.extensions.gl_arb_draw_elements_base_vertex
-                            {
-                                ctxt.gl.DrawElementsInstancedBaseVertex(primitives.to_glenum(),
-                                                                     buffer.get_elements_count() as
-                                                                        gl::types::GLsizei,
-                                                                        data_type.to_glenum(),
-                                                                        ptr as *const libc::c_void,
-                                                                        instances_count as
-                                                                        gl::types::GLsizei,
-                                                                        base_vertex);
-
-                            } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
-                                ctxt.gl.DrawElementsInstancedBaseVertexOES(primitives.to_glenum(),
-                                                                     buffer.get_elements_count() as
-                                                                           gl::types::GLsizei,
-                                                                           data_type.to_glenum(),
-                                                                        ptr as *const libc::c_void,
-                                                                           instances_count as
-                                                                           gl::types::GLsizei,
-                                                                           base_vertex);
-                            } else {
-                                unreachable!();
-                            }
-
-                        } else {
-                            ctxt.gl.DrawElementsInstanced(primitives.to_glenum(),
-                                                          buffer.get_elements_count() as
-                                                          gl::types::GLsizei,
-                                                          data_type.to_glenum(),
-                                                          ptr as *const libc::c_void,
-                                                          instances_count as gl::types::GLsizei);
-                        }
+                    draw_elements(&mut ctxt, primitives, buffer.get_elements_count(), data_type,
+                                  ptr, instances_count, base_vertex);
+                }
+            },
 
-                    } else {
-                        if base_vertex != 0 {
-                            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
-                               ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                               ctxt.extensions.gl_arb_draw_elements_base_vertex
-                            {
-                                ctxt.gl.DrawElementsBaseVertex(primitives.to_glenum(),
-                                                               buffer.get_elements_count() as
-                                                               gl::types::GLsizei,
-                                                               data_type.to_glenum(),
-                                                               ptr as *const libc::c_void,
-                                                               base_vertex);
-
-                            } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
-                                ctxt.gl.DrawElementsBaseVertexOES(primitives.to_glenum(),
-                                                                  buffer.get_elements_count() as
-                                                                  gl::types::GLsizei,
-                                                                  data_type.to_glenum(),
-                                                                  ptr as *const libc::c_void,
-                                                                  base_vertex);
-                            } else {
-                                unreachable!();
-                            }
-
-                        } else {
-                            ctxt.gl.DrawElements(primitives.to_glenum(),
-                                                 buffer.get_elements_count() as gl::types::GLsizei,
-                                                 data_type.to_glenum(),
-                                                 ptr as *const libc::c_void);
-                        }
-                    }
+            &IndicesSource::ClientData { data, data_type, primitives } => {
+                let offset = client_indices.unwrap().1;
+                let ptr: *const u8 = ptr::null_mut();
+                let ptr = unsafe { ptr.offset(offset as isize) };
+
+                unsafe {
+                    draw_elements(&mut ctxt, primitives, data.len() / data_type.get_size(),
+                                  data_type, ptr, instances_count, base_vertex);
                 }
             },
 
@@ -356,16 +402,78 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
         };
     };
 
+    ctxt.check_draw_error();
     ctxt.state.next_draw_call_id += 1;
+    ctxt.record_statistics(|s| s.draw_calls += 1);
 
     // fulfilling the fences
-    for fence in fences.into_iter() {
-        fence.insert(&mut ctxt);
-    }
+    Inserter::insert_all(&mut ctxt, fences);
 
     Ok(())
 }
 
+/// Calls the `glDrawElements*` function that corresponds to the parameters.
+///
+/// `ptr` is the offset of the indices within the buffer currently binded to
+/// `GL_ELEMENT_ARRAY_BUFFER`.
+unsafe fn draw_elements(ctxt: &mut context::CommandContext, primitives: PrimitiveType,
+                        count: usize, data_type: index::IndexType, ptr: *const u8,
+                        instances_count: Option<usize>, base_vertex: gl::types::GLint)
+{
+    let count = count as gl::types::GLsizei;
+
+    if let Some(instances_count) = instances_count {
+        let instances_count = instances_count as gl::types::GLsizei;
+
+        if base_vertex != 0 {
+            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+               ctxt.extensions.gl_arb_draw_elements_base_vertex
+            {
+                ctxt.gl.DrawElementsInstancedBaseVertex(primitives.to_glenum(), count,
+                                                        data_type.to_glenum(),
+                                                        ptr as *const libc::c_void,
+                                                        instances_count, base_vertex);
+
+            } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
+                ctxt.gl.DrawElementsInstancedBaseVertexOES(primitives.to_glenum(), count,
+                                                           data_type.to_glenum(),
+                                                           ptr as *const libc::c_void,
+                                                           instances_count, base_vertex);
+            } else {
+                unreachable!();
+            }
+
+        } else {
+            ctxt.gl.DrawElementsInstanced(primitives.to_glenum(), count, data_type.to_glenum(),
+                                          ptr as *const libc::c_void, instances_count);
+        }
+
+    } else {
+        if base_vertex != 0 {
+            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+               ctxt.extensions.gl_arb_draw_elements_base_vertex
+            {
+                ctxt.gl.DrawElementsBaseVertex(primitives.to_glenum(), count,
+                                               data_type.to_glenum(),
+                                               ptr as *const libc::c_void, base_vertex);
+
+            } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
+                ctxt.gl.DrawElementsBaseVertexOES(primitives.to_glenum(), count,
+                                                  data_type.to_glenum(),
+                                                  ptr as *const libc::c_void, base_vertex);
+            } else {
+                unreachable!();
+            }
+
+        } else {
+            ctxt.gl.DrawElements(primitives.to_glenum(), count, data_type.to_glenum(),
+                                 ptr as *const libc::c_void);
+        }
+    }
+}
+
 fn sync_depth(ctxt: &mut context::CommandContext, depth_test: DepthTest, depth_write: bool,
               depth_range: (f32, f32), depth_clamp: DepthClamp) -> Result<(), DrawError>
 {
@@ -708,6 +816,27 @@ fn sync_point_size(ctxt: &mut context::CommandContext, point_size: Option<f32>)
     }
 }
 
+/// Returns the number of vertices of the per-vertex sources, or `None` if they don't have the
+/// same length.
+fn vertices_sources_count(sources: &[VerticesSource]) -> Option<usize> {
+    let mut count = None;
+
+    for src in sources.iter() {
+        let len = match src {
+            &VerticesSource::VertexBuffer(ref buffer, _, false) => buffer.get_elements_count(),
+            &VerticesSource::Marker { len, per_instance: false } => len,
+            _ => continue
+        };
+
+        match count {
+            Some(c) if c != len => return None,
+            _ => count = Some(len)
+        }
+    }
+
+    count
+}
+
 fn sync_polygon_mode(ctxt: &mut context::CommandContext, backface_culling: BackfaceCullingMode,
                      polygon_mode: PolygonMode)
 {
@@ -753,6 +882,38 @@ fn sync_polygon_mode(ctxt: &mut context::CommandContext, backface_culling: Backf
     }
 }
 
+fn sync_polygon_offset(ctxt: &mut context::CommandContext, offset: PolygonOffset) {
+    let (factor, units) = ctxt.state.polygon_offset;
+
+    if (offset.point || offset.line || offset.fill) &&
+       (factor != offset.factor || units != offset.units)
+    {
+        unsafe { ctxt.gl.PolygonOffset(offset.factor, offset.units) };
+        ctxt.state.polygon_offset = (offset.factor, offset.units);
+    }
+
+    macro_rules! set {
+        ($value:expr, $state:ident, $cap:expr) => (
+            if ctxt.state.$state != $value {
+                if $value {
+                    unsafe { ctxt.gl.Enable($cap) };
+                } else {
+                    unsafe { ctxt.gl.Disable($cap) };
+                }
+                ctxt.state.$state = $value;
+            }
+        );
+    }
+
+    set!(offset.fill, enabled_polygon_offset_fill, gl::POLYGON_OFFSET_FILL);
+
+    // OpenGL ES only supports offsetting filled polygons
+    if ctxt.version >= &Version(Api::Gl, 1, 0) {
+        set!(offset.line, enabled_polygon_offset_line, gl::POLYGON_OFFSET_LINE);
+        set!(offset.point, enabled_polygon_offset_point, gl::POLYGON_OFFSET_POINT);
+    }
+}
+
 fn sync_multisampling(ctxt: &mut context::CommandContext, multisampling: bool) {
     if ctxt.state.enabled_multisample != multisampling {
         unsafe {
@@ -1069,3 +1230,26 @@ fn sync_primitive_bounding_box(ctxt: &mut context::CommandContext,
         ctxt.state.primitive_bounding_box = value;
     }
 }
+
+fn sync_pixel_local_storage(ctxt: &mut context::CommandContext, enabled: bool)
+                            -> Result<(), DrawError>
+{
+    if ctxt.state.enabled_shader_pixel_local_storage == enabled {
+        return Ok(());
+    }
+
+    if !ctxt.extensions.gl_ext_shader_pixel_local_storage &&
+       !ctxt.extensions.gl_ext_shader_pixel_local_storage2
+    {
+        return Err(DrawError::PixelLocalStorageNotSupported);
+    }
+
+    if enabled {
+        unsafe { ctxt.gl.Enable(gl::SHADER_PIXEL_LOCAL_STORAGE_EXT); }
+    } else {
+        unsafe { ctxt.gl.Disable(gl::SHADER_PIXEL_LOCAL_STORAGE_EXT); }
+    }
+
+    ctxt.state.enabled_shader_pixel_local_storage = enabled;
+    Ok(())
+}
@@ -0,0 +1,361 @@
+//! Emulation of the draw parameters that the backend doesn't support, by rewriting the list of
+//! indices before drawing.
+
+use std::mem;
+
+use DrawError;
+
+use context::Context;
+use draw_parameters::{DrawParameters, PolygonMode, ProvokingVertex};
+use index::{DrawCommandIndices, DrawCommandNoIndices};
+use index::{IndexType, IndicesSource, PrimitiveType};
+use version::Api;
+use version::Version;
+
+/// Indices to draw instead of the ones given by the user.
+pub struct EmulatedIndices {
+    /// Raw data of the indices.
+    pub data: Vec<u8>,
+    /// Type of indices in `data`.
+    pub data_type: IndexType,
+    /// Type of primitives contained in `data`.
+    pub primitives: PrimitiveType,
+    /// True if the polygon mode has been emulated, in which case `Fill` must be used.
+    pub polygon_mode_emulated: bool,
+    /// True if the provoking vertex has been emulated, in which case `LastVertex` must be used.
+    pub provoking_vertex_emulated: bool,
+}
+
+/// Returns the indices to use instead of `indices` if some of the draw parameters need to be
+/// emulated, or `None` if the backend supports everything.
+///
+/// `vertices_count` is the number of vertices in the vertices sources, and is only used if
+/// there is no index. `instanced` must be true if some of the vertices sources are per-instance.
+///
+/// Index buffers and draw commands are read back from video memory, which requires either
+/// desktop OpenGL or OpenGL ES 3.0.
+pub fn emulate(context: &Context, indices: &IndicesSource, vertices_count: Option<usize>,
+               instanced: bool, params: &DrawParameters)
+               -> Result<Option<EmulatedIndices>, DrawError>
+{
+    let primitives = indices.get_primitives_type();
+
+    let emulate_polygon_mode = params.polygon_mode != PolygonMode::Fill &&
+                               is_triangles(primitives) &&
+                               !is_polygon_mode_supported(context);
+    let emulate_provoking_vertex = params.provoking_vertex == ProvokingVertex::FirstVertex &&
+                                   !is_provoking_vertex_supported(context);
+
+    if !emulate_polygon_mode && !emulate_provoking_vertex {
+        return Ok(None);
+    }
+
+    let options = context.get_emulation_options();
+    if emulate_polygon_mode && !options.polygon_mode {
+        return Err(DrawError::PolygonModeNotSupported);
+    }
+    if emulate_provoking_vertex && !options.provoking_vertex {
+        return Err(DrawError::ProvokingVertexNotSupported);
+    }
+
+    // the error to return if we can't emulate
+    let error = if emulate_polygon_mode {
+        DrawError::PolygonModeNotSupported
+    } else {
+        DrawError::ProvokingVertexNotSupported
+    };
+
+    // the list of indices of each draw command, and the type of indices to output
+    let (draws, data_type) = match indices {
+        &IndicesSource::ClientData { data, data_type, .. } => {
+            (vec![read_indices(data, data_type)], data_type)
+        },
+
+        &IndicesSource::IndexBuffer { ref buffer, data_type, .. } => {
+            let data = match unsafe { buffer.read::<[u8]>() } {
+                Ok(data) => data,
+                Err(_) => return Err(error),
+            };
+
+            (vec![read_indices(&data, data_type)], data_type)
+        },
+
+        &IndicesSource::NoIndices { .. } => {
+            let count = match vertices_count {
+                Some(c) => c,
+                None => return Err(DrawError::VerticesSourcesLengthMismatch),
+            };
+
+            let data_type = if count <= 65536 { IndexType::U16 } else { IndexType::U32 };
+            (vec![(0 .. count as u32).collect()], data_type)
+        },
+
+        &IndicesSource::MultidrawArray { ref buffer, .. } => {
+            let commands = match unsafe { buffer.read::<[DrawCommandNoIndices]>() } {
+                Ok(commands) => commands,
+                Err(_) => return Err(error),
+            };
+
+            let mut draws = Vec::with_capacity(commands.len());
+            for command in commands.iter() {
+                if !try!(is_single_instance(command.instance_count, command.base_instance,
+                                            instanced, &error))
+                {
+                    continue;
+                }
+
+                let first = command.first_index as u32;
+                draws.push((first .. first + command.count as u32).collect());
+            }
+
+            let data_type = smallest_index_type(&draws);
+            (draws, data_type)
+        },
+
+        &IndicesSource::MultidrawElement { ref commands, ref indices, data_type, .. } => {
+            let commands = match unsafe { commands.read::<[DrawCommandIndices]>() } {
+                Ok(commands) => commands,
+                Err(_) => return Err(error),
+            };
+
+            let data = match unsafe { indices.read::<[u8]>() } {
+                Ok(data) => data,
+                Err(_) => return Err(error),
+            };
+            let data = read_indices(&data, data_type);
+
+            // the `first_index` of the commands is relative to the start of the buffer, while
+            // `data` only contains the slice
+            let offset = indices.get_offset_bytes() / data_type.get_size();
+
+            let mut draws = Vec::with_capacity(commands.len());
+            for command in commands.iter() {
+                if !try!(is_single_instance(command.instance_count, command.base_instance,
+                                            instanced, &error))
+                {
+                    continue;
+                }
+
+                let start = match (command.first_index as usize).checked_sub(offset) {
+                    Some(start) => start,
+                    None => return Err(error),
+                };
+                let end = start + command.count as usize;
+                if end > data.len() {
+                    return Err(error);
+                }
+
+                let base_vertex = command.base_vertex as u32;
+                draws.push(data[start .. end].iter().map(|&i| i + base_vertex).collect());
+            }
+
+            let data_type = if data_type == IndexType::U32 {
+                IndexType::U32
+            } else {
+                smallest_index_type(&draws)
+            };
+            (draws, data_type)
+        },
+    };
+
+    // converting to a list of primitives, where each primitive starts with the vertex that
+    // OpenGL considers to be the "first" one if we emulate the provoking vertex, and ends with
+    // the "last" one otherwise
+    let output_primitives = match primitives {
+        PrimitiveType::Points => PrimitiveType::Points,
+        PrimitiveType::LinesList | PrimitiveType::LineStrip |
+        PrimitiveType::LineLoop => PrimitiveType::LinesList,
+        PrimitiveType::TrianglesList | PrimitiveType::TriangleStrip |
+        PrimitiveType::TriangleFan => PrimitiveType::TrianglesList,
+        _ => return Err(error),
+    };
+
+    let mut output = Vec::new();
+    for source in draws.iter() {
+        match output_primitives {
+            PrimitiveType::Points => output.extend(source.iter().cloned()),
+            PrimitiveType::LinesList => output.extend(lines_list(source, primitives).into_iter()),
+            PrimitiveType::TrianglesList => {
+                let list = triangles_list(source, primitives, emulate_provoking_vertex);
+                output.extend(list.into_iter());
+            },
+            _ => unreachable!()
+        }
+    }
+
+    let output_primitives = if emulate_polygon_mode {
+        match params.polygon_mode {
+            PolygonMode::Line => {
+                output = output.chunks(3).flat_map(|t| {
+                    vec![t[0], t[1], t[1], t[2], t[2], t[0]].into_iter()
+                }).collect();
+                PrimitiveType::LinesList
+            },
+            PolygonMode::Point => PrimitiveType::Points,
+            PolygonMode::Fill => unreachable!(),
+        }
+
+    } else {
+        // moving the first vertex of each primitive to the end, while preserving the winding
+        // order of triangles
+        match output_primitives {
+            PrimitiveType::LinesList => for line in output.chunks_mut(2) {
+                line.swap(0, 1);
+            },
+            PrimitiveType::TrianglesList => for triangle in output.chunks_mut(3) {
+                let first = triangle[0];
+                triangle[0] = triangle[1];
+                triangle[1] = triangle[2];
+                triangle[2] = first;
+            },
+            _ => ()
+        }
+
+        output_primitives
+    };
+
+    Ok(Some(EmulatedIndices {
+        data: write_indices(&output, data_type),
+        data_type: data_type,
+        primitives: output_primitives,
+        polygon_mode_emulated: emulate_polygon_mode,
+        provoking_vertex_emulated: emulate_provoking_vertex,
+    }))
+}
+
+/// Returns true if `glPolygonMode` is available.
+#[inline]
+pub fn is_polygon_mode_supported(context: &Context) -> bool {
+    context.get_opengl_version().0 == Api::Gl
+}
+
+/// Returns true if `glProvokingVertex` is available.
+#[inline]
+fn is_provoking_vertex_supported(context: &Context) -> bool {
+    context.get_opengl_version() >= &Version(Api::Gl, 3, 2) ||
+    context.get_extensions().gl_arb_provoking_vertex ||
+    context.get_extensions().gl_ext_provoking_vertex
+}
+
+/// Returns true if the polygon mode applies to this type of primitives.
+#[inline]
+pub fn is_triangles(primitives: PrimitiveType) -> bool {
+    match primitives {
+        PrimitiveType::TrianglesList | PrimitiveType::TrianglesListAdjacency |
+        PrimitiveType::TriangleStrip | PrimitiveType::TriangleStripAdjacency |
+        PrimitiveType::TriangleFan => true,
+        _ => false
+    }
+}
+
+/// Returns false if a draw command must be skipped because it draws no instance, or an error if
+/// it draws more than a single instance, as this can't be emulated.
+fn is_single_instance(instance_count: u32, base_instance: u32, instanced: bool,
+                      error: &DrawError) -> Result<bool, DrawError>
+{
+    match instance_count {
+        0 => Ok(false),
+        1 if base_instance == 0 && !instanced => Ok(true),
+        _ => Err(error.clone()),
+    }
+}
+
+/// Returns the smallest type of indices that can hold all the indices of the draw commands.
+fn smallest_index_type(draws: &[Vec<u32>]) -> IndexType {
+    let max = draws.iter().flat_map(|d| d.iter()).cloned().max().unwrap_or(0);
+    if max <= 65535 { IndexType::U16 } else { IndexType::U32 }
+}
+
+/// Turns a list, strip or loop of lines into a list of lines.
+fn lines_list(indices: &[u32], primitives: PrimitiveType) -> Vec<u32> {
+    match primitives {
+        PrimitiveType::LinesList => indices[.. indices.len() - indices.len() % 2].to_vec(),
+        PrimitiveType::LineStrip | PrimitiveType::LineLoop => {
+            let mut result = Vec::with_capacity(indices.len() * 2);
+            for line in indices.windows(2) {
+                result.push(line[0]);
+                result.push(line[1]);
+            }
+            if primitives == PrimitiveType::LineLoop && indices.len() >= 2 {
+                result.push(indices[indices.len() - 1]);
+                result.push(indices[0]);
+            }
+            result
+        },
+        _ => unreachable!()
+    }
+}
+
+/// Turns a list, strip or fan of triangles into a list of triangles.
+///
+/// If `first_vertex_first` is true, the vertex that OpenGL considers as the first vertex of
+/// each triangle is put first. Otherwise the one that OpenGL considers as the last vertex is
+/// put last.
+fn triangles_list(indices: &[u32], primitives: PrimitiveType, first_vertex_first: bool)
+                  -> Vec<u32>
+{
+    match primitives {
+        PrimitiveType::TrianglesList => indices[.. indices.len() - indices.len() % 3].to_vec(),
+        PrimitiveType::TriangleStrip => {
+            let mut result = Vec::with_capacity(indices.len() * 3);
+            for (n, t) in indices.windows(3).enumerate() {
+                if n % 2 == 0 {
+                    result.extend([t[0], t[1], t[2]].iter().cloned());
+                } else if first_vertex_first {
+                    result.extend([t[0], t[2], t[1]].iter().cloned());
+                } else {
+                    result.extend([t[1], t[0], t[2]].iter().cloned());
+                }
+            }
+            result
+        },
+        PrimitiveType::TriangleFan => {
+            let mut result = Vec::with_capacity(indices.len() * 3);
+            for n in 2 .. indices.len() {
+                if first_vertex_first {
+                    result.extend([indices[n - 1], indices[n], indices[0]].iter().cloned());
+                } else {
+                    result.extend([indices[0], indices[n - 1], indices[n]].iter().cloned());
+                }
+            }
+            result
+        },
+        _ => unreachable!()
+    }
+}
+
+/// Reads raw indices.
+fn read_indices(data: &[u8], data_type: IndexType) -> Vec<u32> {
+    match data_type {
+        IndexType::U8 => data.iter().map(|&i| i as u32).collect(),
+        IndexType::U16 => data.chunks(2).filter(|c| c.len() == 2).map(|c| {
+            let value: u16 = unsafe { mem::transmute([c[0], c[1]]) };
+            value as u32
+        }).collect(),
+        IndexType::U32 => data.chunks(4).filter(|c| c.len() == 4).map(|c| {
+            let value: u32 = unsafe { mem::transmute([c[0], c[1], c[2], c[3]]) };
+            value
+        }).collect(),
+    }
+}
+
+/// Writes raw indices. The indices must fit in `data_type`.
+fn write_indices(indices: &[u32], data_type: IndexType) -> Vec<u8> {
+    let mut result = Vec::with_capacity(indices.len() * data_type.get_size());
+
+    for &index in indices {
+        match data_type {
+            IndexType::U8 => result.push(index as u8),
+            IndexType::U16 => {
+                let bytes: [u8; 2] = unsafe { mem::transmute(index as u16) };
+                result.extend(bytes.iter().cloned());
+            },
+            IndexType::U32 => {
+                let bytes: [u8; 4] = unsafe { mem::transmute(index) };
+                result.extend(bytes.iter().cloned());
+            },
+        }
+    }
+
+    result
+}
@@ -6,4 +6,5 @@ pub use self::read::{read, read_if_supported, Source, Destination};
 mod blit;
 mod clear;
 mod draw;
+mod emulation;
 mod read;
@@ -11,6 +11,7 @@ use buffer::BufferAny;
 use BufferExt;
 use Rect;
 use context::CommandContext;
+use version::{Api, Version};
 use gl;
 
 /// A source for reading pixels.
@@ -66,6 +67,9 @@ pub fn read<'a, S, D>(mut ctxt: &mut CommandContext, source: S, rect: &Rect, des
 
 /// Reads pixels from the source into the destination.
 ///
+/// Returns `Err` without reading anything if the backend can't read pixels in the format of
+/// the destination.
+///
 /// Panicks if the destination is not large enough.
 pub fn read_if_supported<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
                                       dest: D) -> Result<(), ()>
@@ -77,8 +81,6 @@ pub fn read_if_supported<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S,
 
     let pixels_to_read = rect.width * rect.height;
 
-    // FIXME: check if format is supported by ReadPixels
-
     let (format, gltype) = client_format_to_gl_enum(&<T as PixelValue>::get_format());
 
     match source {
@@ -90,6 +92,11 @@ pub fn read_if_supported<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S,
         },
     };
 
+    // the read framebuffer must be binded, as the format supported by OpenGL ES depends on it
+    if !is_format_supported(ctxt, format, gltype) {
+        return Err(());
+    }
+
     unsafe {
         // reading
         match dest {
@@ -139,6 +146,34 @@ pub fn read_if_supported<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S,
     Ok(())
 }
 
+/// Returns true if `glReadPixels` accepts this format and type for the binded read framebuffer.
+fn is_format_supported(ctxt: &CommandContext, format: gl::types::GLenum,
+                       gltype: gl::types::GLenum) -> bool
+{
+    // `RGBA` and `UNSIGNED_BYTE` are always supported
+    if format == gl::RGBA && gltype == gl::UNSIGNED_BYTE {
+        return true;
+    }
+
+    if ctxt.version.0 == Api::GlEs {
+        // OpenGL ES supports one additional combination, chosen by the implementation
+        let (mut read_format, mut read_type) = (0, 0);
+        unsafe {
+            ctxt.gl.GetIntegerv(gl::IMPLEMENTATION_COLOR_READ_FORMAT, &mut read_format);
+            ctxt.gl.GetIntegerv(gl::IMPLEMENTATION_COLOR_READ_TYPE, &mut read_type);
+        }
+
+        return read_format as gl::types::GLenum == format &&
+               read_type as gl::types::GLenum == gltype;
+    }
+
+    if gltype == gl::HALF_FLOAT {
+        return ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_half_float_pixel;
+    }
+
+    true
+}
+
 fn client_format_to_gl_enum(format: &ClientFormat) -> (gl::types::GLenum, gl::types::GLenum) {
     match *format {
         ClientFormat::U8 => (gl::RED, gl::UNSIGNED_BYTE),
@@ -0,0 +1,127 @@
+/*!
+Pipeline state objects.
+
+A `PipelineState` groups a program, the format of the vertices that are going to be drawn with
+it and the draw parameters. All these are checked once when the pipeline state is built,
+instead of at each draw call.
+
+When you draw with `Surface::draw_with_pipeline`, glium remembers which pipeline state was used.
+Drawing again with the same pipeline state on a target of the same dimensions doesn't
+synchronize the states that are controlled by the draw parameters (depth, stencil, blending,
+viewport, etc.), as they are known to be already correct. Only the states that depend on the
+draw call itself, like the queries or the primitives type, are updated.
+
+# Example
+
+```no_run
+# #[macro_use] extern crate glium;
+# fn main() {
+use glium::Surface;
+use glium::pipeline::PipelineState;
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let program: glium::Program = unsafe { ::std::mem::uninitialized() };
+
+#[derive(Copy, Clone)]
+struct Vertex { position: [f32; 2] }
+implement_vertex!(Vertex, position);
+
+let vertex_buffer = glium::VertexBuffer::new(&display, &[
+    Vertex { position: [-0.5, -0.5] },
+    Vertex { position: [ 0.0,  0.5] },
+    Vertex { position: [ 0.5, -0.5] },
+]).unwrap();
+let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+
+let params = glium::DrawParameters {
+    depth_test: glium::DepthTest::IfLess,
+    depth_write: true,
+    .. Default::default()
+};
+
+let pipeline = PipelineState::new(&display, &program, vertex_buffer.get_bindings().clone(),
+                                  params).unwrap();
+
+let mut target = display.draw();
+target.draw_with_pipeline(&vertex_buffer, &indices, &pipeline,
+                          &glium::uniforms::EmptyUniforms).unwrap();
+target.finish().unwrap();
+# }
+```
+
+*/
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+use backend::Facade;
+use draw_parameters::{self, DrawParameters};
+use program::Program;
+use vertex::VertexFormat;
+use vertex_array_object;
+use DrawError;
+
+/// Used to give a unique id to each pipeline state.
+static NEXT_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// A program, a vertex format and draw parameters that have been validated together.
+///
+/// See the documentation of the module.
+pub struct PipelineState<'a> {
+    id: usize,
+    program: &'a Program,
+    vertex_format: VertexFormat,
+    draw_parameters: DrawParameters<'a>,
+}
+
+impl<'a> PipelineState<'a> {
+    /// Builds a new pipeline state.
+    ///
+    /// Returns an error if the draw parameters are not supported by the backend, or if the
+    /// vertex format doesn't provide the attributes of the program with the right types.
+    pub fn new<F>(facade: &F, program: &'a Program, vertex_format: VertexFormat,
+                  draw_parameters: DrawParameters<'a>)
+                  -> Result<PipelineState<'a>, DrawError> where F: Facade
+    {
+        let context = facade.get_context();
+
+        try!(draw_parameters::validate(context, &draw_parameters));
+
+        if let Some(viewport) = draw_parameters.viewport {
+            let max = context.capabilities().max_viewport_dims;
+            if viewport.width > max.0 as u32 || viewport.height > max.1 as u32 {
+                return Err(DrawError::ViewportTooLarge);
+            }
+        }
+
+        try!(vertex_array_object::check_attributes(&[&vertex_format], program));
+
+        Ok(PipelineState {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            program: program,
+            vertex_format: vertex_format,
+            draw_parameters: draw_parameters,
+        })
+    }
+
+    /// Returns the program of this pipeline state.
+    #[inline]
+    pub fn get_program(&self) -> &'a Program {
+        self.program
+    }
+
+    /// Returns the format of the vertices that this pipeline state has been validated with.
+    #[inline]
+    pub fn get_vertex_format(&self) -> &VertexFormat {
+        &self.vertex_format
+    }
+
+    /// Returns the draw parameters of this pipeline state.
+    #[inline]
+    pub fn get_draw_parameters(&self) -> &DrawParameters<'a> {
+        &self.draw_parameters
+    }
+
+    /// Returns the unique id of this pipeline state.
+    #[inline]
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+}
@@ -0,0 +1,263 @@
+/*!
+Measures the time that the GPU spends executing parts of a frame.
+
+A `Profiler` records a timestamp query at the start and at the end of each scope. Scopes can be
+nested, and the result of a frame is a tree of timings.
+
+The results of the queries are only read once they are available, which usually happens a few
+frames later. Reading the results never blocks. If the GPU is so late that the results of a
+frame are still not available after a certain number of frames, this frame is dropped.
+
+# Example
+
+```no_run
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# fn draw_shadows() {} fn draw_scene() {}
+let profiler = glium::profiler::Profiler::new(&display).unwrap();
+
+// in the rendering loop
+profiler.scope("shadows", || draw_shadows());
+profiler.scope("scene", || draw_scene());
+profiler.end_frame();
+
+if let Some(timings) = profiler.get_last_frame_timings() {
+    for scope in timings.scopes.iter() {
+        println!("{}: {}ns", scope.name, scope.nanoseconds);
+    }
+}
+```
+
+*/
+use backend::Facade;
+use context::Context;
+use draw_parameters::{QueryCreationError, TimestampQuery};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Records GPU timings for nested scopes.
+pub struct Profiler {
+    context: Rc<Context>,
+
+    /// Number of frames after which the results of a frame are dropped if they are not
+    /// available.
+    max_latency: usize,
+
+    /// The frame that is being recorded.
+    recording: RefCell<FrameRecording>,
+
+    /// Frames whose results are not available yet, from the oldest to the most recent.
+    pending: RefCell<VecDeque<FrameRecording>>,
+
+    /// Queries that can be reused.
+    free_queries: RefCell<Vec<TimestampQuery>>,
+
+    /// Timings of the most recent frame whose results are available.
+    last_frame: RefCell<Option<FrameTimings>>,
+}
+
+/// Timings of a frame.
+#[derive(Clone, Debug)]
+pub struct FrameTimings {
+    /// The scopes that have been recorded outside of any other scope, in order.
+    pub scopes: Vec<ScopeTiming>,
+}
+
+/// Timing of a scope.
+#[derive(Clone, Debug)]
+pub struct ScopeTiming {
+    /// Name that was given when recording the scope.
+    pub name: String,
+
+    /// Number of nanoseconds that have elapsed on the GPU between the start and the end of
+    /// the scope.
+    pub nanoseconds: u64,
+
+    /// The scopes that have been recorded inside of this scope, in order.
+    pub children: Vec<ScopeTiming>,
+}
+
+struct FrameRecording {
+    scopes: Vec<RecordedScope>,
+    /// Indices within `scopes` of the scopes that haven't ended yet.
+    open: Vec<usize>,
+}
+
+struct RecordedScope {
+    name: String,
+    parent: Option<usize>,
+    start: TimestampQuery,
+    end: Option<TimestampQuery>,
+}
+
+impl Profiler {
+    /// Builds a new profiler that drops the results of a frame if they are not available after
+    /// three frames.
+    ///
+    /// Returns an error if timestamp queries are not supported by the backend.
+    #[inline]
+    pub fn new<F>(facade: &F) -> Result<Profiler, QueryCreationError> where F: Facade {
+        Profiler::with_max_latency(facade, 3)
+    }
+
+    /// Builds a new profiler that drops the results of a frame if they are not available after
+    /// `max_latency` frames.
+    ///
+    /// Returns an error if timestamp queries are not supported by the backend.
+    pub fn with_max_latency<F>(facade: &F, max_latency: usize)
+                               -> Result<Profiler, QueryCreationError> where F: Facade
+    {
+        // checking whether timestamp queries are supported
+        let query = try!(TimestampQuery::new(facade));
+
+        Ok(Profiler {
+            context: facade.get_context().clone(),
+            max_latency: max_latency,
+            recording: RefCell::new(FrameRecording { scopes: Vec::new(), open: Vec::new() }),
+            pending: RefCell::new(VecDeque::new()),
+            free_queries: RefCell::new(vec![query]),
+            last_frame: RefCell::new(None),
+        })
+    }
+
+    /// Records the time that the GPU spends executing the commands submitted by `f`.
+    ///
+    /// Scopes can be nested by calling `scope` from inside `f`.
+    #[inline]
+    pub fn scope<F, R>(&self, name: &str, f: F) -> R where F: FnOnce() -> R {
+        self.begin_scope(name);
+        let result = f();
+        self.end_scope();
+        result
+    }
+
+    /// Starts a scope. It must be ended with `end_scope`.
+    ///
+    /// Prefer `scope` if possible.
+    pub fn begin_scope(&self, name: &str) {
+        let query = self.get_query();
+        query.record();
+
+        let mut recording = self.recording.borrow_mut();
+        let parent = recording.open.last().cloned();
+        let index = recording.scopes.len();
+
+        recording.scopes.push(RecordedScope {
+            name: name.to_string(),
+            parent: parent,
+            start: query,
+            end: None,
+        });
+
+        recording.open.push(index);
+    }
+
+    /// Ends the most recent scope started with `begin_scope`.
+    ///
+    /// # Panic
+    ///
+    /// Panics in debug builds if there is no scope to end. Does nothing in release builds.
+    pub fn end_scope(&self) {
+        let mut recording = self.recording.borrow_mut();
+
+        let index = match recording.open.pop() {
+            Some(index) => index,
+            None => {
+                debug_assert!(false, "Profiler::end_scope called while no scope is open");
+                return;
+            }
+        };
+
+        let query = self.get_query();
+        query.record();
+        recording.scopes[index].end = Some(query);
+    }
+
+    /// Marks the end of the current frame, and retreives the results of the previous frames
+    /// that are available.
+    ///
+    /// # Panic
+    ///
+    /// Panics if a scope hasn't been ended.
+    pub fn end_frame(&self) {
+        let recording = {
+            let mut recording = self.recording.borrow_mut();
+            assert!(recording.open.is_empty(), "A profiler scope hasn't been ended");
+            ::std::mem::replace(&mut *recording,
+                                FrameRecording { scopes: Vec::new(), open: Vec::new() })
+        };
+
+        let mut pending = self.pending.borrow_mut();
+        if !recording.scopes.is_empty() {
+            pending.push_back(recording);
+        }
+
+        // collecting all the frames whose results are available
+        loop {
+            let ready = match pending.front() {
+                Some(frame) => frame.scopes.iter().all(|s| {
+                    s.start.is_ready() && s.end.as_ref().unwrap().is_ready()
+                }),
+                None => break
+            };
+
+            if ready {
+                let frame = pending.pop_front().unwrap();
+                *self.last_frame.borrow_mut() = Some(FrameTimings {
+                    scopes: build_tree(&frame.scopes, None),
+                });
+                self.recycle(frame);
+
+            } else if pending.len() > self.max_latency {
+                let frame = pending.pop_front().unwrap();
+                self.recycle(frame);
+
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Returns the timings of the most recent frame whose results are available.
+    #[inline]
+    pub fn get_last_frame_timings(&self) -> Option<FrameTimings> {
+        self.last_frame.borrow().clone()
+    }
+
+    /// Returns an unused query.
+    fn get_query(&self) -> TimestampQuery {
+        match self.free_queries.borrow_mut().pop() {
+            Some(query) => query,
+            None => {
+                let query = TimestampQuery::new(&self.context);
+                query.ok().expect("Timestamp queries are supported, as this was checked when \
+                                   building the profiler")
+            },
+        }
+    }
+
+    /// Puts the queries of a frame back in the list of unused queries.
+    fn recycle(&self, frame: FrameRecording) {
+        let mut free_queries = self.free_queries.borrow_mut();
+
+        for scope in frame.scopes.into_iter() {
+            free_queries.push(scope.start);
+            free_queries.extend(scope.end.into_iter());
+        }
+    }
+}
+
+/// Builds the timings of the children of `parent`. The queries must be ready.
+fn build_tree(scopes: &[RecordedScope], parent: Option<usize>) -> Vec<ScopeTiming> {
+    scopes.iter().enumerate().filter(|&(_, s)| s.parent == parent).map(|(index, scope)| {
+        let start = scope.start.get();
+        let end = scope.end.as_ref().unwrap().get();
+
+        ScopeTiming {
+            name: scope.name.clone(),
+            nanoseconds: end.saturating_sub(start),
+            children: build_tree(scopes, Some(index)),
+        }
+    }).collect()
+}
@@ -84,6 +84,19 @@ impl ComputeShader {
     pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
         self.raw.get_uniform(name)
     }
+
+    /// Sets the label of the compute shader. The label is included in the errors that happen
+    /// when executing it.
+    #[inline]
+    pub fn set_label(&self, label: Option<String>) {
+        self.raw.set_label(label)
+    }
+
+    /// Returns the label of the compute shader.
+    #[inline]
+    pub fn get_label(&self) -> Option<String> {
+        self.raw.get_label()
+    }
     
     /// Returns an iterator to the list of uniforms.
     ///
@@ -180,6 +193,11 @@ impl ProgramExt for ComputeShader {
         self.raw.get_uniform(name)
     }
 
+    #[inline]
+    fn get_label(&self) -> Option<String> {
+        self.raw.get_label()
+    }
+
     #[inline]
     fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock> {
         self.raw.get_uniform_blocks()
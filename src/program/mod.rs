@@ -38,6 +38,18 @@ pub fn is_binary_supported<C>(ctxt: &C) -> bool where C: CapabilitiesSource {
         || ctxt.get_extensions().gl_arb_get_programy_binary
 }
 
+/// Returns true if the backend supports fragment shaders that read the content of the
+/// framebuffer, either with `inout` outputs or with `gl_LastFragData`.
+///
+/// This is the case with the `GL_EXT_shader_framebuffer_fetch` extension and its variants.
+#[inline]
+pub fn is_framebuffer_fetch_supported<C>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    ctxt.get_extensions().gl_ext_shader_framebuffer_fetch ||
+    ctxt.get_extensions().gl_ext_shader_framebuffer_fetch_non_coherent ||
+    ctxt.get_extensions().gl_arm_shader_framebuffer_fetch ||
+    ctxt.get_extensions().gl_nv_shader_framebuffer_fetch
+}
+
 /// Some shader compilers have race-condition issues, so we lock this mutex
 /// in the GL thread every time we compile a shader or link a program.
 // TODO: replace by a StaticMutex
@@ -69,6 +81,10 @@ pub enum ProgramCreationError {
     /// You have requested point size setting from the shader, but it's not
     /// supported by the backend.
     PointSizeNotSupported,
+
+    /// The fragment shader reads the content of the framebuffer and failed to compile, and the
+    /// backend doesn't support framebuffer fetch.
+    FramebufferFetchNotSupported,
 }
 
 impl fmt::Display for ProgramCreationError {
@@ -89,6 +105,9 @@ impl fmt::Display for ProgramCreationError {
             &ProgramCreationError::PointSizeNotSupported =>
                 formatter.write_str("You requested point size setting, but it's not \
                                      supported by the backend"),
+            &ProgramCreationError::FramebufferFetchNotSupported =>
+                formatter.write_str("The fragment shader reads the content of the framebuffer, \
+                                     but this is not supported by the backend"),
         }
     }
 }
@@ -107,6 +126,8 @@ impl Error for ProgramCreationError {
                                                                      supported by the backend.",
             &ProgramCreationError::PointSizeNotSupported => "Point size is not supported by \
                                                              the backend.",
+            &ProgramCreationError::FramebufferFetchNotSupported => "Framebuffer fetch is not \
+                                                                    supported by the backend.",
         }
     }
 
@@ -123,6 +144,21 @@ pub enum GetBinaryError {
     NotSupported,
 }
 
+impl fmt::Display for GetBinaryError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for GetBinaryError {
+    fn description(&self) -> &str {
+        match self {
+            &GetBinaryError::NotSupported => "The backend doesn't support retrieving the binary \
+                                              of a program",
+        }
+    }
+}
+
 /// Input when creating a program.
 pub enum ProgramCreationInput<'a> {
     /// Use GLSL source code.
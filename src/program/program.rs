@@ -21,7 +21,8 @@ use program::GetBinaryError;
 
 use program::reflection::{Uniform, UniformBlock, OutputPrimitives};
 use program::reflection::{Attribute, TransformFeedbackBuffer};
-use program::shader::build_shader;
+use program::is_framebuffer_fetch_supported;
+use program::shader::{build_shader, uses_framebuffer_fetch};
 
 use program::raw::RawProgram;
 
@@ -32,6 +33,7 @@ pub struct Program {
     raw: RawProgram,
     outputs_srgb: bool,
     uses_point_size: bool,
+    uses_framebuffer_fetch: bool,
 }
 
 impl Program {
@@ -41,7 +43,7 @@ impl Program {
     {
         let input = input.into();
 
-        let (raw, outputs_srgb, uses_point_size) = match input {
+        let (raw, outputs_srgb, uses_point_size, uses_framebuffer_fetch) = match input {
             ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
                                                tessellation_evaluation_shader, geometry_shader,
                                                fragment_shader, transform_feedback_varyings,
@@ -82,19 +84,30 @@ impl Program {
                     return Err(ProgramCreationError::PointSizeNotSupported);
                 }
 
+                let uses_framebuffer_fetch = uses_framebuffer_fetch(fragment_shader);
+
                 let _lock = COMPILER_GLOBAL_LOCK.lock();
 
                 let shaders_store = {
                     let mut shaders_store = Vec::new();
                     for (src, ty) in shaders.into_iter() {
-                        shaders_store.push(try!(build_shader(facade, ty, src)));
+                        shaders_store.push(match build_shader(facade, ty, src) {
+                            // giving a better error than the one of the compiler
+                            Err(ProgramCreationError::CompilationError(_))
+                                if ty == gl::FRAGMENT_SHADER && uses_framebuffer_fetch &&
+                                   !is_framebuffer_fetch_supported(facade) =>
+                            {
+                                return Err(ProgramCreationError::FramebufferFetchNotSupported);
+                            },
+                            shader => try!(shader),
+                        });
                     }
                     shaders_store
                 };
 
                 (try!(RawProgram::from_shaders(facade, &shaders_store, has_geometry_shader,
                                                has_tessellation_shaders, transform_feedback_varyings)),
-                 outputs_srgb, uses_point_size)
+                 outputs_srgb, uses_point_size, uses_framebuffer_fetch)
             },
 
             ProgramCreationInput::Binary { data, outputs_srgb, uses_point_size } => {
@@ -102,7 +115,7 @@ impl Program {
                     return Err(ProgramCreationError::PointSizeNotSupported);
                 }
 
-                (try!(RawProgram::from_binary(facade, data)), outputs_srgb, uses_point_size)
+                (try!(RawProgram::from_binary(facade, data)), outputs_srgb, uses_point_size, false)
             },
         };
 
@@ -110,6 +123,7 @@ impl Program {
             raw: raw,
             outputs_srgb: outputs_srgb,
             uses_point_size: uses_point_size,
+            uses_framebuffer_fetch: uses_framebuffer_fetch,
         })
     }
 
@@ -179,6 +193,44 @@ impl Program {
     pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
         self.raw.get_uniform(name)
     }
+
+    /// Sets whether glium should remember the value of a uniform and avoid uploading it again
+    /// if it didn't change since the previous draw call. This is enabled by default.
+    ///
+    /// Disabling it skips comparing and storing the value of uniforms that change at each draw
+    /// call, like the model matrix of a scene with lots of objects. Returns `false` if the
+    /// uniform doesn't exist.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+    /// program.set_uniform_caching("model", false);
+    /// ```
+    #[inline]
+    pub fn set_uniform_caching(&self, name: &str, enabled: bool) -> bool {
+        self.raw.set_uniform_caching(name, enabled)
+    }
+
+    /// Sets the label of the program. The label is included in the errors that happen when
+    /// drawing with the program, which helps to find which material is wrong.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+    /// program.set_label(Some("terrain".to_owned()));
+    /// ```
+    #[inline]
+    pub fn set_label(&self, label: Option<String>) {
+        self.raw.set_label(label)
+    }
+
+    /// Returns the label of the program.
+    #[inline]
+    pub fn get_label(&self) -> Option<String> {
+        self.raw.get_label()
+    }
     
     /// Returns an iterator to the list of uniforms.
     ///
@@ -291,6 +343,17 @@ impl Program {
     pub fn uses_point_size(&self) -> bool {
       self.uses_point_size
     }
+
+    /// Returns true if the fragment shader reads the content of the framebuffer, either with
+    /// `inout` outputs or with `gl_LastFragData`. See `program::is_framebuffer_fetch_supported`.
+    ///
+    /// Blending is still applied to the outputs of such a program. The blending function
+    /// usually keeps its default value of `AlwaysReplace`, so that the shader does the blending
+    /// itself. This is always false for programs that have been created from a binary.
+    #[inline]
+    pub fn uses_framebuffer_fetch(&self) -> bool {
+        self.uses_framebuffer_fetch
+    }
 }
 
 impl fmt::Debug for Program {
@@ -362,6 +425,11 @@ impl ProgramExt for Program {
         self.raw.get_uniform(name)
     }
 
+    #[inline]
+    fn get_label(&self) -> Option<String> {
+        self.raw.get_label()
+    }
+
     #[inline]
     fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock> {
         self.raw.get_uniform_blocks()
@@ -36,6 +36,7 @@ use program::reflection::{reflect_tess_eval_output_type, reflect_shader_storage_
 use program::shader::Shader;
 
 use uniforms::Uniforms;
+use buffer::Inserter;
 
 use vertex::VertexFormat;
 use vertex_array_object::VertexAttributesSystem;
@@ -53,6 +54,7 @@ pub struct RawProgram {
     ssbos: HashMap<String, UniformBlock>,
     output_primitives: Option<OutputPrimitives>,
     has_tessellation_shaders: bool,
+    label: RefCell<Option<String>>,
 }
 
 impl RawProgram {
@@ -175,6 +177,7 @@ impl RawProgram {
             ssbos: ssbos,
             output_primitives: output_primitives,
             has_tessellation_shaders: has_tessellation_shaders,
+            label: RefCell::new(None),
         })
     }
 
@@ -225,6 +228,7 @@ impl RawProgram {
             ssbos: ssbos,
             output_primitives: None,            // FIXME: 
             has_tessellation_shaders: true,     // FIXME: 
+            label: RefCell::new(None),
         })
     }
 
@@ -314,6 +318,34 @@ impl RawProgram {
     pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
         self.uniforms.get(name)
     }
+
+    /// Sets whether glium should remember the value of a uniform and avoid uploading it again
+    /// if it didn't change since the previous draw call. This is enabled by default.
+    ///
+    /// Disabling it skips comparing and storing the value of uniforms that change at each draw
+    /// call. Returns `false` if the uniform doesn't exist.
+    pub fn set_uniform_caching(&self, name: &str, enabled: bool) -> bool {
+        match self.uniforms.get(name) {
+            Some(uniform) => {
+                self.uniform_values.set_caching(uniform.location, enabled);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Sets the label of the program. The label is included in the errors that happen when
+    /// drawing with the program.
+    #[inline]
+    pub fn set_label(&self, label: Option<String>) {
+        *self.label.borrow_mut() = label;
+    }
+
+    /// Returns the label of the program.
+    #[inline]
+    pub fn get_label(&self) -> Option<String> {
+        self.label.borrow().clone()
+    }
     
     /// Returns an iterator to the list of uniforms.
     ///
@@ -441,6 +473,8 @@ impl RawProgram {
                                       -> Result<(), DrawError>      // TODO: other error?
                                       where U: Uniforms
     {
+        trace_operation!("dispatch_compute");
+
         let mut ctxt = self.context.make_current();
 
         // TODO: return an error instead
@@ -460,9 +494,7 @@ impl RawProgram {
         try!(uniforms.bind_uniforms(&mut ctxt, self, &mut fences));
         ctxt.gl.DispatchCompute(x, y, z);
 
-        for fence in fences {
-            fence.insert(&mut ctxt);
-        }
+        Inserter::insert_all(&mut ctxt, fences);
 
         Ok(())
     }
@@ -495,6 +527,7 @@ impl ProgramExt for RawProgram {
                     Handle::Handle(id) => ctxt.gl.UseProgramObjectARB(id),
                 }
                 ctxt.state.program = program_id;
+                ctxt.record_statistics(|s| s.program_binds += 1);
             }
         }
     }
@@ -526,6 +559,11 @@ impl ProgramExt for RawProgram {
         self.uniforms.get(name)
     }
 
+    #[inline]
+    fn get_label(&self) -> Option<String> {
+        self.label.borrow().clone()
+    }
+
     #[inline]
     fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock> {
         &self.uniform_blocks
@@ -220,3 +220,69 @@ pub fn check_shader_type_compatibility<C>(ctxt: &C, shader_type: gl::types::GLen
 
     true
 }
+
+/// Returns true if the source code of a fragment shader reads the content of the framebuffer,
+/// either with `inout` variables declared at global scope or with one of the `gl_LastFrag*`
+/// built-in variables.
+///
+/// Preprocessor directives are not evaluated, so code that is disabled with `#if` is taken
+/// into account as well.
+pub fn uses_framebuffer_fetch(source_code: &str) -> bool {
+    let mut chars = source_code.chars().chain(Some('\n')).peekable();
+    let mut word = String::new();
+    let mut nesting = 0u32;
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+
+        if (word == "inout" && nesting == 0) || word.starts_with("gl_LastFrag") {
+            return true;
+        }
+        word.clear();
+
+        let next = chars.peek().cloned();
+        match (c, next) {
+            ('{', _) | ('(', _) => nesting += 1,
+            ('}', _) | (')', _) => nesting = nesting.saturating_sub(1),
+
+            // skipping comments
+            ('/', Some('/')) => {
+                while chars.peek().map(|&c| c != '\n').unwrap_or(false) { chars.next(); }
+            },
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                while let Some(c) = chars.next() {
+                    if previous == '*' && c == '/' { break; }
+                    previous = c;
+                }
+            },
+
+            _ => ()
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::uses_framebuffer_fetch;
+
+    #[test]
+    fn framebuffer_fetch_detection() {
+        assert!(uses_framebuffer_fetch("inout vec4 color; void main() { color *= 2.0; }"));
+        assert!(uses_framebuffer_fetch("void main() { gl_FragColor = gl_LastFragData[0]; }"));
+        assert!(uses_framebuffer_fetch("void main() { gl_FragColor = gl_LastFragColorARM; }"));
+    }
+
+    #[test]
+    fn framebuffer_fetch_false_positives() {
+        assert!(!uses_framebuffer_fetch("void f(inout vec4 c) {} void main() {}"));
+        assert!(!uses_framebuffer_fetch("// inout vec4 color;\nvoid main() {}"));
+        assert!(!uses_framebuffer_fetch("/* inout vec4 color; */ void main() {}"));
+    }
+}
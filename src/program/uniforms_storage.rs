@@ -11,7 +11,8 @@ use version::Version;
 use version::Api;
 
 pub struct UniformsStorage {
-    values: RefCell<HashMap<gl::types::GLint, Option<RawUniformValue>>>,
+    /// For each location, whether caching is enabled and the last value if it is.
+    values: RefCell<HashMap<gl::types::GLint, (bool, Option<RawUniformValue>)>>,
     uniform_blocks: RefCell<SmallVec<[Option<gl::types::GLuint>; 4]>>,
     shader_storage_blocks: RefCell<SmallVec<[Option<gl::types::GLuint>; 4]>>,
 }
@@ -27,6 +28,15 @@ impl UniformsStorage {
         }
     }
 
+    /// Sets whether the value of the uniform at `location` should be compared with the
+    /// previous value before calling `glUniform`.
+    ///
+    /// Comparing is useless for uniforms whose value changes at each draw call.
+    pub fn set_caching(&self, location: gl::types::GLint, enabled: bool) {
+        // the value isn't updated while caching is disabled, so it is forgotten
+        self.values.borrow_mut().insert(location, (enabled, None));
+    }
+
     /// Compares `value` with the value stored in this object. If the values differ, updates
     /// the storage and calls `glUniform`.
     pub fn set_uniform_value(&self, ctxt: &mut CommandContext, program: Handle,
@@ -37,10 +47,12 @@ impl UniformsStorage {
         // TODO: don't assume that, instead use DSA if the program is not current
         assert!(ctxt.state.program == program);
 
-        // TODO: more optimized
-        if values.get(&location).is_none() {
-            values.insert(location, None);
-        }
+        // the value of an uncached uniform is written in `uncached` and never matches
+        let mut uncached = None;
+        let target = match values.entry(location).or_insert((true, None)) {
+            &mut (true, ref mut stored) => stored,
+            &mut (false, _) => &mut uncached,
+        };
 
         macro_rules! uniform(
             ($ctxt:expr, $uniform:ident, $uniform_arb:ident, $($params:expr),+) => (
@@ -69,7 +81,7 @@ impl UniformsStorage {
             )
         );
 
-        match (value, values.get_mut(&location).unwrap()) {
+        match (value, target) {
             (&RawUniformValue::SignedInt(a), &mut Some(RawUniformValue::SignedInt(b))) if a == b => (),
             (&RawUniformValue::UnsignedInt(a), &mut Some(RawUniformValue::UnsignedInt(b))) if a == b => (),
             (&RawUniformValue::Float(a), &mut Some(RawUniformValue::Float(b))) if a == b => (),
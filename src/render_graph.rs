@@ -0,0 +1,670 @@
+/*!
+Describes a frame as a list of passes that read and write textures.
+
+Instead of creating the intermediate textures of a frame yourself, you declare them in a
+`RenderGraph`, then add passes that declare which textures they read and write. When the graph
+is executed, glium:
+
+ - Sorts the passes so that a pass is executed after the passes that write the textures that it
+   reads.
+ - Checks that no intermediate texture is read without being written, and that no pass samples
+   a texture that it draws upon.
+ - Skips the passes whose results are never used.
+ - Calls `glMemoryBarrier` when a pass accesses a texture that has been written with image
+   stores.
+ - Takes the intermediate textures from a `TexturePool`, reuses the same texture for several
+   intermediate textures whose lifetimes don't overlap, and invalidates their content when they
+   are no longer needed.
+
+Passes that write the same texture are executed in the order in which they have been added, and
+a pass that reads a texture is executed after all the passes that write it. Passes that don't
+depend on each other also keep the order in which they have been added. If the passes can't be
+ordered, for example because two passes read a texture that the other pass writes, executing the
+graph returns an error.
+
+Passes can only access the textures that they have declared.
+
+# Example
+
+```no_run
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let output: glium::Texture2d = unsafe { ::std::mem::uninitialized() };
+use glium::Surface;
+use glium::render_graph::{RenderGraph, TexturePool};
+use glium::texture::UncompressedFloatFormat;
+
+let mut pool = TexturePool::new();
+
+// in the rendering loop
+let mut graph = RenderGraph::new(&display);
+let scene = graph.create_texture(1024, 768, UncompressedFloatFormat::F16F16F16F16);
+let output_id = graph.import_texture(&output);
+
+graph.add_pass("scene", move |res| {
+    res.get(scene).unwrap().as_surface().clear_color(0.0, 0.0, 0.0, 1.0);
+    // draw the scene
+}).writes(scene);
+
+graph.add_pass("tonemapping", move |res| {
+    let _source = res.get(scene).unwrap();
+    res.get(output_id).unwrap().as_surface().clear_color(0.0, 0.0, 0.0, 1.0);
+    // draw `source` on the output
+}).reads(scene).writes(output_id);
+
+graph.execute(&mut pool).unwrap();
+```
+
+A pass that doesn't write any texture of the graph, for example because it draws on the
+default framebuffer, is always executed. Other passes are only executed if they write a texture
+that has been imported with `import_texture`, or a texture that is read by a pass that is
+executed.
+
+*/
+use backend::Facade;
+use context::Context;
+use ContextExt;
+use gl;
+use texture::{MipmapsOption, Texture2d, TextureCreationError, UncompressedFloatFormat};
+use version::Api;
+use version::Version;
+
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+/// Used to give a unique id to each render graph.
+static NEXT_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Identifies a texture of a `RenderGraph`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceId {
+    /// Id of the graph that the texture belongs to.
+    graph: usize,
+    /// Index of the texture within the graph.
+    index: usize,
+}
+
+/// Description of an intermediate texture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureDescription {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Format of the texture.
+    pub format: UncompressedFloatFormat,
+}
+
+/// Error that can happen when executing a `RenderGraph`.
+#[derive(Clone, Debug)]
+pub enum RenderGraphError {
+    /// A pass reads an intermediate texture that no pass writes.
+    ReadBeforeWrite {
+        /// Name of the pass.
+        pass: String,
+        /// The texture.
+        resource: ResourceId,
+    },
+
+    /// A pass samples a texture that it also draws upon.
+    ///
+    /// Reading and writing the same texture is only allowed with image load/store.
+    FeedbackLoop {
+        /// Name of the pass.
+        pass: String,
+        /// The texture.
+        resource: ResourceId,
+    },
+
+    /// A pass uses a texture that belongs to another graph.
+    UnknownResource {
+        /// Name of the pass.
+        pass: String,
+        /// The texture.
+        resource: ResourceId,
+    },
+
+    /// The passes can't be ordered, because they read textures that are written by each other.
+    Cycle {
+        /// Name of one of the passes of the cycle.
+        pass: String,
+    },
+
+    /// Error while creating an intermediate texture.
+    TextureCreationError(TextureCreationError),
+}
+
+impl fmt::Display for RenderGraphError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &RenderGraphError::ReadBeforeWrite { ref pass, ref resource } => {
+                write!(fmt, "The pass `{}` reads {:?} but no pass writes it", pass, resource)
+            },
+            &RenderGraphError::FeedbackLoop { ref pass, ref resource } => {
+                write!(fmt, "The pass `{}` samples {:?} while also drawing upon it", pass,
+                       resource)
+            },
+            &RenderGraphError::UnknownResource { ref pass, ref resource } => {
+                write!(fmt, "The pass `{}` uses {:?}, which belongs to another graph", pass,
+                       resource)
+            },
+            &RenderGraphError::Cycle { ref pass } => {
+                write!(fmt, "The pass `{}` reads textures that are written by passes that \
+                             depend on it", pass)
+            },
+            &RenderGraphError::TextureCreationError(ref err) => {
+                write!(fmt, "Error while creating an intermediate texture: {}", err)
+            },
+        }
+    }
+}
+
+impl Error for RenderGraphError {
+    fn description(&self) -> &str {
+        match self {
+            &RenderGraphError::ReadBeforeWrite { .. } => "A pass reads an intermediate texture \
+                                                          that no pass writes",
+            &RenderGraphError::FeedbackLoop { .. } => "A pass samples a texture that it also \
+                                                       draws upon",
+            &RenderGraphError::UnknownResource { .. } => "A pass uses a texture that belongs to \
+                                                          another graph",
+            &RenderGraphError::Cycle { .. } => "The passes can't be ordered because they read \
+                                                textures that are written by each other",
+            &RenderGraphError::TextureCreationError(_) => "Error while creating an intermediate \
+                                                           texture",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &RenderGraphError::TextureCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<TextureCreationError> for RenderGraphError {
+    #[inline]
+    fn from(err: TextureCreationError) -> RenderGraphError {
+        RenderGraphError::TextureCreationError(err)
+    }
+}
+
+/// Intermediate textures kept between the executions of render graphs.
+///
+/// You should use the same pool for every frame, so that textures are reused instead of being
+/// created every frame.
+pub struct TexturePool {
+    textures: Vec<(TextureDescription, Texture2d)>,
+}
+
+impl TexturePool {
+    /// Builds an empty pool.
+    #[inline]
+    pub fn new() -> TexturePool {
+        TexturePool {
+            textures: Vec::new(),
+        }
+    }
+
+    /// Returns the number of textures in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Destroys all the textures of the pool.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.textures.clear();
+    }
+
+    /// Takes a texture with the given description from the pool, or creates one.
+    fn acquire(&mut self, context: &Rc<Context>, desc: &TextureDescription)
+               -> Result<Texture2d, TextureCreationError>
+    {
+        if let Some(pos) = self.textures.iter().position(|&(ref d, _)| d == desc) {
+            return Ok(self.textures.remove(pos).1);
+        }
+
+        Texture2d::empty_with_format(context, desc.format, MipmapsOption::NoMipmap,
+                                     desc.width, desc.height)
+    }
+}
+
+impl Default for TexturePool {
+    #[inline]
+    fn default() -> TexturePool {
+        TexturePool::new()
+    }
+}
+
+/// Error that can happen when accessing a texture from within a pass.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResourceAccessError {
+    /// The texture belongs to another graph.
+    UnknownResource,
+
+    /// The pass hasn't declared that it reads or writes the texture.
+    NotDeclared,
+}
+
+impl fmt::Display for ResourceAccessError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for ResourceAccessError {
+    fn description(&self) -> &str {
+        match self {
+            &ResourceAccessError::UnknownResource => "The texture belongs to another graph",
+            &ResourceAccessError::NotDeclared => "The pass hasn't declared that it reads or \
+                                                  writes the texture",
+        }
+    }
+}
+
+/// Gives access to the textures of the graph from within a pass.
+pub struct PassResources<'g> {
+    graph: usize,
+    resources: &'g [Resource<'g>],
+    textures: &'g [Option<Texture2d>],
+    reads: &'g [(ResourceId, bool)],
+    writes: &'g [(ResourceId, bool)],
+}
+
+impl<'g> PassResources<'g> {
+    /// Returns the texture corresponding to `id`.
+    ///
+    /// Returns an error if `id` belongs to another graph, or if the current pass hasn't
+    /// declared that it reads or writes this texture.
+    pub fn get(&self, id: ResourceId) -> Result<&Texture2d, ResourceAccessError> {
+        if id.graph != self.graph || id.index >= self.resources.len() {
+            return Err(ResourceAccessError::UnknownResource);
+        }
+
+        if !self.reads.iter().chain(self.writes.iter()).any(|&(r, _)| r == id) {
+            return Err(ResourceAccessError::NotDeclared);
+        }
+
+        match self.resources[id.index] {
+            Resource::Imported(texture) => Ok(texture),
+            // the intermediate textures used by the current pass are always allocated
+            Resource::Transient(_) => Ok(self.textures[id.index].as_ref().unwrap()),
+        }
+    }
+}
+
+/// A pass of a `RenderGraph`.
+pub struct Pass<'a> {
+    name: String,
+    reads: Vec<(ResourceId, bool)>,
+    writes: Vec<(ResourceId, bool)>,
+    execute: Box<FnMut(&PassResources) + 'a>,
+}
+
+impl<'a> Pass<'a> {
+    /// Declares that the pass samples this texture.
+    #[inline]
+    pub fn reads(&mut self, id: ResourceId) -> &mut Pass<'a> {
+        self.reads.push((id, false));
+        self
+    }
+
+    /// Declares that the pass reads this texture with image loads.
+    #[inline]
+    pub fn reads_image(&mut self, id: ResourceId) -> &mut Pass<'a> {
+        self.reads.push((id, true));
+        self
+    }
+
+    /// Declares that the pass draws on this texture.
+    #[inline]
+    pub fn writes(&mut self, id: ResourceId) -> &mut Pass<'a> {
+        self.writes.push((id, false));
+        self
+    }
+
+    /// Declares that the pass writes this texture with image stores.
+    #[inline]
+    pub fn writes_image(&mut self, id: ResourceId) -> &mut Pass<'a> {
+        self.writes.push((id, true));
+        self
+    }
+}
+
+enum Resource<'a> {
+    Imported(&'a Texture2d),
+    Transient(TextureDescription),
+}
+
+/// A list of passes and the textures that they use.
+pub struct RenderGraph<'a> {
+    id: usize,
+    context: Rc<Context>,
+    resources: Vec<Resource<'a>>,
+    passes: Vec<Pass<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// Builds an empty graph.
+    #[inline]
+    pub fn new<F>(facade: &F) -> RenderGraph<'a> where F: Facade {
+        RenderGraph {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            context: facade.get_context().clone(),
+            resources: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    /// Declares an intermediate texture. Its content is undefined until a pass writes it.
+    pub fn create_texture(&mut self, width: u32, height: u32, format: UncompressedFloatFormat)
+                          -> ResourceId
+    {
+        self.resources.push(Resource::Transient(TextureDescription {
+            width: width,
+            height: height,
+            format: format,
+        }));
+
+        ResourceId { graph: self.id, index: self.resources.len() - 1 }
+    }
+
+    /// Declares a texture that exists outside of the graph. Its content is kept.
+    ///
+    /// The passes that write this texture are always executed.
+    pub fn import_texture(&mut self, texture: &'a Texture2d) -> ResourceId {
+        self.resources.push(Resource::Imported(texture));
+        ResourceId { graph: self.id, index: self.resources.len() - 1 }
+    }
+
+    /// Adds a pass to the graph. Use the returned object to declare the textures that the pass
+    /// reads and writes.
+    pub fn add_pass<F>(&mut self, name: &str, execute: F) -> &mut Pass<'a>
+                       where F: FnMut(&PassResources) + 'a
+    {
+        self.passes.push(Pass {
+            name: name.to_string(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            execute: Box::new(execute),
+        });
+
+        self.passes.last_mut().unwrap()
+    }
+
+    /// Checks and sorts the graph, then executes the passes.
+    ///
+    /// Nothing is executed if an error is returned.
+    pub fn execute(self, pool: &mut TexturePool) -> Result<(), RenderGraphError> {
+        let RenderGraph { id: graph_id, context, resources, mut passes } = self;
+
+        for pass in passes.iter() {
+            for &(id, _) in pass.reads.iter().chain(pass.writes.iter()) {
+                if id.graph != graph_id || id.index >= resources.len() {
+                    return Err(RenderGraphError::UnknownResource {
+                        pass: pass.name.clone(),
+                        resource: id,
+                    });
+                }
+            }
+        }
+
+        let deps = try!(dependencies(&resources, &passes));
+        let order = try!(sort(&passes, &deps));
+
+        // determining which passes need to be executed, starting from the end
+        let mut needed = passes.iter().map(|pass| {
+            pass.writes.is_empty() || pass.writes.iter().any(|&(id, _)| {
+                match resources[id.index] { Resource::Imported(_) => true, _ => false }
+            })
+        }).collect::<Vec<_>>();
+
+        for &pass in order.iter().rev() {
+            if needed[pass] {
+                for &dep in deps[pass].iter() {
+                    needed[dep] = true;
+                }
+            }
+        }
+
+        // the positions within `order` of the first and last passes that use each intermediate
+        // texture
+        let mut lifetimes: Vec<Option<(usize, usize)>> = vec![None; resources.len()];
+        for (position, &index) in order.iter().enumerate().filter(|&(_, &i)| needed[i]) {
+            let pass = &passes[index];
+            for &(id, _) in pass.reads.iter().chain(pass.writes.iter()) {
+                let lifetime = &mut lifetimes[id.index];
+                *lifetime = Some(match *lifetime {
+                    Some((first, _)) => (first, position),
+                    None => (position, position),
+                });
+            }
+        }
+
+        // assigning each intermediate texture to a slot, so that textures whose lifetimes don't
+        // overlap share the same slot
+        let mut slots: Vec<(TextureDescription, Option<Texture2d>)> = Vec::new();
+        let mut slot_of: Vec<Option<usize>> = vec![None; resources.len()];
+        {
+            let mut free_slots: Vec<usize> = Vec::new();
+
+            for position in 0 .. order.len() {
+                for (id, resource) in resources.iter().enumerate() {
+                    if let &Resource::Transient(ref desc) = resource {
+                        match lifetimes[id] {
+                            Some((first, _)) if first == position => (),
+                            _ => continue
+                        };
+
+                        let slot = match free_slots.iter().position(|&s| slots[s].0 == *desc) {
+                            Some(pos) => free_slots.remove(pos),
+                            None => {
+                                slots.push((*desc, None));
+                                slots.len() - 1
+                            }
+                        };
+
+                        slot_of[id] = Some(slot);
+                    }
+                }
+
+                for id in 0 .. resources.len() {
+                    match (lifetimes[id], slot_of[id]) {
+                        (Some((_, last)), Some(slot)) if last == position => free_slots.push(slot),
+                        _ => ()
+                    };
+                }
+            }
+        }
+
+        // allocating all the textures before executing anything
+        for slot in 0 .. slots.len() {
+            match pool.acquire(&context, &slots[slot].0) {
+                Ok(texture) => slots[slot].1 = Some(texture),
+                Err(err) => {
+                    for (desc, texture) in slots.into_iter() {
+                        if let Some(texture) = texture {
+                            pool.textures.push((desc, texture));
+                        }
+                    }
+
+                    return Err(From::from(err));
+                }
+            }
+        }
+
+        let mut textures: Vec<Option<Texture2d>> = (0 .. resources.len()).map(|_| None).collect();
+
+        // true if the texture has been written with image stores since the last barrier
+        let mut needs_barrier = vec![false; resources.len()];
+
+        for (position, &index) in order.iter().enumerate().filter(|&(_, &i)| needed[i]) {
+            let pass = &mut passes[index];
+
+            // taking the textures that are first used by this pass from their slot
+            for id in 0 .. resources.len() {
+                match (lifetimes[id], slot_of[id]) {
+                    (Some((first, _)), Some(slot)) if first == position => {
+                        let texture = slots[slot].1.take().unwrap();
+                        texture.main_level().invalidate();
+                        textures[id] = Some(texture);
+                    },
+                    _ => ()
+                };
+            }
+
+            let barrier = pass.reads.iter().chain(pass.writes.iter())
+                                    .any(|&(id, _)| needs_barrier[id.index]);
+            if barrier {
+                memory_barrier(&context);
+                for b in needs_barrier.iter_mut() { *b = false; }
+            }
+
+            {
+                let Pass { ref reads, ref writes, ref mut execute, .. } = *pass;
+
+                (*execute)(&PassResources {
+                    graph: graph_id,
+                    resources: &resources,
+                    textures: &textures,
+                    reads: reads,
+                    writes: writes,
+                });
+            }
+
+            for &(id, image) in pass.writes.iter() {
+                if image {
+                    needs_barrier[id.index] = true;
+                }
+            }
+
+            // putting back in their slot the textures that are no longer needed
+            for id in 0 .. resources.len() {
+                match (lifetimes[id], slot_of[id]) {
+                    (Some((_, last)), Some(slot)) if last == position => {
+                        slots[slot].1 = textures[id].take();
+                    },
+                    _ => ()
+                };
+            }
+        }
+
+        for (desc, texture) in slots.into_iter() {
+            let texture = texture.unwrap();
+            texture.main_level().invalidate();
+            pool.textures.push((desc, texture));
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the passes and returns, for each pass, the list of passes whose results it uses.
+///
+/// A pass that reads a texture uses the results of all the passes that write it. A pass that
+/// writes a texture uses the results of the passes that write it and that have been added
+/// before, as it may draw over their content.
+fn dependencies(resources: &[Resource], passes: &[Pass])
+                -> Result<Vec<Vec<usize>>, RenderGraphError>
+{
+    // for each resource, the passes that write it in the order in which they have been added
+    let mut writers: Vec<Vec<usize>> = vec![Vec::new(); resources.len()];
+    for (index, pass) in passes.iter().enumerate() {
+        for &(id, _) in pass.writes.iter() {
+            if writers[id.index].last() != Some(&index) {
+                writers[id.index].push(index);
+            }
+        }
+    }
+
+    passes.iter().enumerate().map(|(index, pass)| {
+        let mut pass_deps = Vec::new();
+
+        for &(id, image) in pass.reads.iter() {
+            let imported = match resources[id.index] {
+                Resource::Imported(_) => true,
+                Resource::Transient(_) => false,
+            };
+
+            if !imported && writers[id.index].is_empty() {
+                return Err(RenderGraphError::ReadBeforeWrite {
+                    pass: pass.name.clone(),
+                    resource: id,
+                });
+            }
+
+            if pass.writes.iter().any(|&(w, _)| w == id) {
+                if !image {
+                    return Err(RenderGraphError::FeedbackLoop {
+                        pass: pass.name.clone(),
+                        resource: id,
+                    });
+                }
+
+                // handled below, as the pass is one of the writers
+                continue;
+            }
+
+            pass_deps.extend(writers[id.index].iter().cloned());
+        }
+
+        for &(id, _) in pass.writes.iter() {
+            pass_deps.extend(writers[id.index].iter().cloned().take_while(|&w| w != index));
+        }
+
+        pass_deps.sort();
+        pass_deps.dedup();
+        Ok(pass_deps)
+    }).collect()
+}
+
+/// Returns the indices of the passes in the order in which they must be executed, so that each
+/// pass is executed after the passes whose results it uses.
+///
+/// Among the passes whose dependencies have been executed, the one that has been added first is
+/// executed first.
+fn sort(passes: &[Pass], deps: &[Vec<usize>]) -> Result<Vec<usize>, RenderGraphError> {
+    let mut order = Vec::with_capacity(passes.len());
+    let mut done = vec![false; passes.len()];
+
+    while order.len() < passes.len() {
+        let next = (0 .. passes.len()).find(|&p| {
+            !done[p] && deps[p].iter().all(|&d| done[d])
+        });
+
+        match next {
+            Some(pass) => {
+                done[pass] = true;
+                order.push(pass);
+            },
+            None => {
+                let pass = (0 .. passes.len()).find(|&p| !done[p]).unwrap();
+                return Err(RenderGraphError::Cycle { pass: passes[pass].name.clone() });
+            },
+        }
+    }
+
+    Ok(order)
+}
+
+/// Makes sure that the writes done with image stores are visible to the next commands.
+fn memory_barrier(context: &Context) {
+    let ctxt = context.make_current();
+
+    if ctxt.version >= &Version(Api::Gl, 4, 2) ||
+       ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+       ctxt.extensions.gl_arb_shader_image_load_store
+    {
+        unsafe {
+            ctxt.gl.MemoryBarrier(gl::TEXTURE_FETCH_BARRIER_BIT |
+                                  gl::SHADER_IMAGE_ACCESS_BARRIER_BIT |
+                                  gl::TEXTURE_UPDATE_BARRIER_BIT |
+                                  gl::FRAMEBUFFER_BARRIER_BIT);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+/*!
+Allows threads that don't own the OpenGL context to request the creation of buffers, textures
+and programs.
+
+A `ResourceQueue` can be cloned and sent to other threads. Each request returns a `Promise`.
+The thread that owns the context calls `process` at the point of its choice, for example once
+per frame, which creates the objects, stores them in a `ResourceStore`, and fulfills the
+promises with the `ResourceId` of the objects. If the queue is dropped before processing a
+request, the promise of this request returns `RequestError::Abandoned`.
+
+Glium objects can't be sent between threads, so the objects themselves stay in the
+`ResourceStore`. The ids can be used in the commands of a `command_encoder::CommandQueue`.
+
+# Example
+
+```no_run
+# #[macro_use] extern crate glium;
+# fn main() {
+use std::thread;
+use glium::resource_queue::{ResourceQueue, ResourceStore};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+
+#[derive(Copy, Clone)]
+struct Vertex { position: [f32; 2] }
+implement_vertex!(Vertex, position);
+
+let queue = ResourceQueue::new();
+let mut store = ResourceStore::new();
+
+let loader = {
+    let queue = queue.clone();
+    thread::spawn(move || {
+        let vertices = vec![Vertex { position: [0.0, 0.0] }; 3];
+        queue.create_vertex_buffer(vertices).wait()
+    })
+};
+
+// in the rendering loop
+queue.process(&display, &mut store);
+
+let id = loader.join().unwrap().unwrap();
+let vertex_buffer = store.vertex_buffers.get(id).unwrap();
+# }
+```
+
+*/
+use backend::Facade;
+use context::Context;
+use command_encoder::{ResourceId, Resources};
+use program::{Program, ProgramCreationError};
+use texture::{Texture2d, Texture2dDataSource, TextureCreationError};
+use vertex::{BufferCreationError, Vertex, VertexBuffer, VertexBufferAny};
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// The objects that have been created by a `ResourceQueue`.
+pub struct ResourceStore {
+    /// The vertex buffers.
+    pub vertex_buffers: Resources<VertexBufferAny>,
+
+    /// The textures.
+    pub textures: Resources<Texture2d>,
+
+    /// The programs.
+    pub programs: Resources<Program>,
+}
+
+impl ResourceStore {
+    /// Builds an empty store.
+    #[inline]
+    pub fn new() -> ResourceStore {
+        ResourceStore {
+            vertex_buffers: Resources::new(),
+            textures: Resources::new(),
+            programs: Resources::new(),
+        }
+    }
+}
+
+impl Default for ResourceStore {
+    #[inline]
+    fn default() -> ResourceStore {
+        ResourceStore::new()
+    }
+}
+
+/// Error that a `Promise` can return.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequestError<E> {
+    /// The creation of the object has failed.
+    CreationError(E),
+
+    /// The request has been destroyed without being processed, for example because all the
+    /// handles to the `ResourceQueue` have been dropped.
+    Abandoned,
+}
+
+impl<E> fmt::Display for RequestError<E> where E: fmt::Display {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &RequestError::CreationError(ref err) => write!(fmt, "{}", err),
+            &RequestError::Abandoned => {
+                write!(fmt, "The request has been destroyed without being processed")
+            },
+        }
+    }
+}
+
+impl<E> Error for RequestError<E> where E: Error {
+    fn description(&self) -> &str {
+        match self {
+            &RequestError::CreationError(ref err) => err.description(),
+            &RequestError::Abandoned => "The request has been destroyed without being processed",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &RequestError::CreationError(ref err) => Some(err),
+            &RequestError::Abandoned => None,
+        }
+    }
+}
+
+/// Shared between a promise and its request. The condition variable is notified when the
+/// result is stored.
+type PromiseShared<E> = Arc<(Mutex<Option<Result<ResourceId, RequestError<E>>>>, Condvar)>;
+
+/// The result of a creation request.
+///
+/// The promise is fulfilled when the thread that owns the context calls
+/// `ResourceQueue::process`, or with `RequestError::Abandoned` if the request is destroyed
+/// before being processed.
+pub struct Promise<E> {
+    shared: PromiseShared<E>,
+}
+
+impl<E> Promise<E> {
+    /// Returns true if the promise has been fulfilled.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.shared.0.lock().unwrap().is_some()
+    }
+
+    /// Returns the result if the promise has been fulfilled, or the promise itself otherwise.
+    pub fn try_get(self) -> Result<Result<ResourceId, RequestError<E>>, Promise<E>> {
+        let result = self.shared.0.lock().unwrap().take();
+
+        match result {
+            Some(result) => Ok(result),
+            None => Err(self),
+        }
+    }
+
+    /// Blocks until the promise is fulfilled and returns the result.
+    ///
+    /// Returns `RequestError::Abandoned` if the request is destroyed without being processed,
+    /// which happens when the last handle to the queue is dropped.
+    ///
+    /// This must not be called from the thread that processes the queue, as it would wait
+    /// forever.
+    pub fn wait(self) -> Result<ResourceId, RequestError<E>> {
+        let mut result = self.shared.0.lock().unwrap();
+
+        loop {
+            if let Some(result) = result.take() {
+                return result;
+            }
+
+            result = self.shared.1.wait(result).unwrap();
+        }
+    }
+}
+
+/// Request that has been sent to the queue.
+type Request = Box<FnMut(&Rc<Context>, &mut ResourceStore) + Send>;
+
+/// Request side of a promise. Abandons the promise when dropped if it hasn't been fulfilled.
+struct Fulfiller<E> {
+    shared: PromiseShared<E>,
+    fulfilled: bool,
+}
+
+impl<E> Fulfiller<E> {
+    fn fulfill(&mut self, result: Result<ResourceId, RequestError<E>>) {
+        *self.shared.0.lock().unwrap() = Some(result);
+        self.shared.1.notify_all();
+        self.fulfilled = true;
+    }
+}
+
+impl<E> Drop for Fulfiller<E> {
+    fn drop(&mut self) {
+        if !self.fulfilled {
+            self.fulfill(Err(RequestError::Abandoned));
+        }
+    }
+}
+
+/// Queue of requests for the creation of objects.
+///
+/// Cloning a queue returns a handle to the same queue.
+#[derive(Clone)]
+pub struct ResourceQueue {
+    requests: Arc<Mutex<VecDeque<Request>>>,
+}
+
+impl ResourceQueue {
+    /// Builds an empty queue.
+    #[inline]
+    pub fn new() -> ResourceQueue {
+        ResourceQueue {
+            requests: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Requests the creation of a vertex buffer containing `data`.
+    pub fn create_vertex_buffer<T>(&self, data: Vec<T>) -> Promise<BufferCreationError>
+                                   where T: Vertex + Copy + Send + 'static
+    {
+        self.push(move |context, store| {
+            VertexBuffer::new(context, &data).map(|buffer| {
+                store.vertex_buffers.insert(buffer.into())
+            })
+        })
+    }
+
+    /// Requests the creation of a texture containing `data`.
+    pub fn create_texture2d<T>(&self, data: T) -> Promise<TextureCreationError>
+                               where T: Texture2dDataSource<'static> + Send + 'static
+    {
+        let mut data = Some(data);
+
+        self.push(move |context, store| {
+            Texture2d::new(context, data.take().unwrap()).map(|texture| {
+                store.textures.insert(texture)
+            })
+        })
+    }
+
+    /// Requests the creation of a program from the source code of its shaders.
+    pub fn create_program(&self, vertex_shader: String, fragment_shader: String,
+                          geometry_shader: Option<String>) -> Promise<ProgramCreationError>
+    {
+        self.push(move |context, store| {
+            let geometry_shader = geometry_shader.as_ref().map(|s| &s[..]);
+            Program::from_source(context, &vertex_shader, &fragment_shader,
+                                 geometry_shader).map(|program| {
+                store.programs.insert(program)
+            })
+        })
+    }
+
+    /// Returns the number of requests that haven't been processed yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Processes all the requests that have been sent so far.
+    ///
+    /// Returns the number of requests that have been processed.
+    #[inline]
+    pub fn process<F>(&self, facade: &F, store: &mut ResourceStore) -> usize where F: Facade {
+        self.process_at_most(facade, store, ::std::usize::MAX)
+    }
+
+    /// Processes at most `max` requests, in the order in which they have been sent. This can
+    /// be used to limit the time spent creating objects each frame.
+    ///
+    /// Returns the number of requests that have been processed.
+    pub fn process_at_most<F>(&self, facade: &F, store: &mut ResourceStore, max: usize) -> usize
+                              where F: Facade
+    {
+        let context = facade.get_context();
+
+        // the lock is not kept while creating the objects so that other threads can continue
+        // to send requests
+        let requests = {
+            let mut queue = self.requests.lock().unwrap();
+            let count = if queue.len() < max { queue.len() } else { max };
+            (0 .. count).map(|_| queue.pop_front().unwrap()).collect::<Vec<_>>()
+        };
+
+        let count = requests.len();
+        for mut request in requests.into_iter() {
+            request(context, store);
+        }
+        count
+    }
+
+    /// Adds a request to the queue and returns the corresponding promise.
+    fn push<E, F>(&self, mut creation: F) -> Promise<E>
+                  where F: FnMut(&Rc<Context>, &mut ResourceStore) -> Result<ResourceId, E> +
+                           Send + 'static,
+                        E: Send + 'static
+    {
+        let shared = Arc::new((Mutex::new(None), Condvar::new()));
+
+        let request = {
+            let mut fulfiller = Fulfiller { shared: shared.clone(), fulfilled: false };
+            Box::new(move |context: &Rc<Context>, store: &mut ResourceStore| {
+                let result = creation(context, store).map_err(RequestError::CreationError);
+                fulfiller.fulfill(result);
+            })
+        };
+
+        self.requests.lock().unwrap().push_back(request);
+        Promise { shared: shared }
+    }
+}
+
+impl Default for ResourceQueue {
+    #[inline]
+    fn default() -> ResourceQueue {
+        ResourceQueue::new()
+    }
+}
@@ -0,0 +1,263 @@
+use gl;
+use context::CommandContext;
+use sync::{self, LinearSyncFence};
+use version::Version;
+use version::Api;
+use vertex_array_object::VertexAttributesSystem;
+use GlObject;
+
+use libc;
+use std::collections::VecDeque;
+use std::mem;
+use std::ptr;
+
+/// Number of parts of the buffer that are protected by their own fence when the storage can't
+/// be orphaned.
+const SEGMENTS: usize = 4;
+
+/// A buffer used internally by glium to upload small amounts of data that change between each
+/// draw call, like indices stored in RAM.
+///
+/// Data is written one after the other. When the end of the buffer is reached, its storage is
+/// orphaned with `glBufferData` and writing starts again at the beginning, so that we never
+/// have to wait for the draw commands that are still using the previous content.
+///
+/// If the `no_buffer_orphaning` quirk is enabled, the buffer is instead split in `SEGMENTS`
+/// parts. A fence is inserted each time writing leaves a part, and a part is only written again
+/// once the fence that covers it is signaled.
+pub struct RingBuffer {
+    id: gl::types::GLuint,
+    size: usize,
+    offset: usize,
+    destroyed: bool,
+
+    /// Part that contains the end of the last write.
+    segment: usize,
+
+    /// Fences in the order in which they have been inserted, with the range of parts whose
+    /// previous content is no longer used once they are signaled.
+    fences: VecDeque<(usize, usize, LinearSyncFence)>,
+
+    /// First part that isn't covered by a fence yet.
+    unfenced: usize,
+}
+
+impl RingBuffer {
+    /// Builds a new ring buffer whose storage is `size` bytes.
+    pub fn new(ctxt: &mut CommandContext, size: usize) -> RingBuffer {
+        let id = unsafe {
+            let mut id: gl::types::GLuint = mem::uninitialized();
+
+            if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+               ctxt.version >= &Version(Api::GlEs, 2, 0)
+            {
+                ctxt.gl.GenBuffers(1, &mut id);
+            } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                ctxt.gl.GenBuffersARB(1, &mut id);
+            } else {
+                unreachable!();
+            }
+
+            allocate(ctxt, id, size);
+            id
+        };
+
+        RingBuffer {
+            id: id,
+            size: size,
+            offset: 0,
+            destroyed: false,
+            segment: 0,
+            fences: VecDeque::new(),
+            unfenced: 0,
+        }
+    }
+
+    /// Writes data in the buffer and returns the offset in bytes where it has been written.
+    ///
+    /// The returned offset is always a multiple of `alignment`.
+    pub fn upload(&mut self, ctxt: &mut CommandContext, data: &[u8], alignment: usize) -> usize {
+        let mut offset = (self.offset + alignment - 1) / alignment * alignment;
+
+        unsafe {
+            if ctxt.quirks.no_buffer_orphaning {
+                offset = self.reserve_without_orphaning(ctxt, offset, data.len());
+
+            } else if data.len() > self.size {
+                self.size = data.len().next_power_of_two();
+                allocate(ctxt, self.id, self.size);
+                offset = 0;
+
+            } else if offset + data.len() > self.size {
+                allocate(ctxt, self.id, self.size);
+                offset = 0;
+            }
+
+            bind(ctxt, self.id);
+
+            if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+               ctxt.version >= &Version(Api::GlEs, 2, 0)
+            {
+                ctxt.gl.BufferSubData(gl::ARRAY_BUFFER, offset as gl::types::GLintptr,
+                                      data.len() as gl::types::GLsizeiptr,
+                                      data.as_ptr() as *const libc::c_void);
+            } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                ctxt.gl.BufferSubDataARB(gl::ARRAY_BUFFER, offset as gl::types::GLintptr,
+                                         data.len() as gl::types::GLsizeiptr,
+                                         data.as_ptr() as *const libc::c_void);
+            } else {
+                unreachable!();
+            }
+        }
+
+        self.offset = offset + data.len();
+        offset
+    }
+
+    /// Returns the offset where `len` bytes can be written without orphaning the storage, and
+    /// waits until the draw commands that use the previous content of this range are finished.
+    unsafe fn reserve_without_orphaning(&mut self, ctxt: &mut CommandContext, offset: usize,
+                                        len: usize) -> usize
+    {
+        if len > self.size {
+            // the storage is reallocated, so every command that uses it must be finished
+            self.wait_all(ctxt);
+            self.size = len.next_power_of_two();
+            allocate(ctxt, self.id, self.size);
+            self.segment = 0;
+            self.unfenced = 0;
+            return 0;
+        }
+
+        let segment_size = (self.size + SEGMENTS - 1) / SEGMENTS;
+        let wraps = offset + len > self.size;
+        let offset = if wraps { 0 } else { offset };
+        let first = offset / segment_size;
+        let last = (offset + len).saturating_sub(1).max(offset) / segment_size;
+
+        // the draw commands that use the parts that are left have already been submitted
+        if wraps || first != self.segment {
+            let fence = sync::new_linear_sync_fence(ctxt).ok();
+            match fence {
+                Some(fence) => self.fences.push_back((self.unfenced, self.segment, fence)),
+                None => ctxt.gl.Finish(),
+            }
+            self.unfenced = first;
+        }
+
+        // fences are signaled in order, so waiting for one means that the previous ones are
+        // signaled as well
+        loop {
+            let position = self.fences.iter().position(|&(start, end, _)| {
+                start <= last && first <= end
+            });
+
+            let position = match position {
+                Some(position) => position,
+                None => break,
+            };
+
+            for _ in 0 .. position {
+                let (_, _, fence) = self.fences.pop_front().unwrap();
+                sync::destroy_linear_sync_fence(ctxt, fence);
+            }
+
+            let (_, _, fence) = self.fences.pop_front().unwrap();
+            sync::wait_linear_sync_fence_and_drop(fence, ctxt);
+        }
+
+        self.segment = last;
+        offset
+    }
+
+    /// Waits until all the draw commands that use the buffer are finished.
+    unsafe fn wait_all(&mut self, ctxt: &mut CommandContext) {
+        while let Some((_, _, fence)) = self.fences.pop_front() {
+            sync::destroy_linear_sync_fence(ctxt, fence);
+        }
+
+        match sync::new_linear_sync_fence(ctxt) {
+            Ok(fence) => sync::wait_linear_sync_fence_and_drop(fence, ctxt),
+            Err(_) => ctxt.gl.Finish(),
+        }
+    }
+
+    /// Destroys the buffer.
+    pub fn destroy(mut self, ctxt: &mut CommandContext) {
+        self.destroyed = true;
+
+        while let Some((_, _, fence)) = self.fences.pop_front() {
+            unsafe { sync::destroy_linear_sync_fence(ctxt, fence) };
+        }
+
+        VertexAttributesSystem::purge_buffer(ctxt, self.id);
+
+        if ctxt.state.array_buffer_binding == self.id {
+            ctxt.state.array_buffer_binding = 0;
+        }
+
+        unsafe {
+            if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+               ctxt.version >= &Version(Api::GlEs, 2, 0)
+            {
+                ctxt.gl.DeleteBuffers(1, [self.id].as_ptr());
+            } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                ctxt.gl.DeleteBuffersARB(1, [self.id].as_ptr());
+            } else {
+                unreachable!();
+            }
+        }
+    }
+}
+
+impl GlObject for RingBuffer {
+    type Id = gl::types::GLuint;
+
+    #[inline]
+    fn get_id(&self) -> gl::types::GLuint {
+        self.id
+    }
+}
+
+impl Drop for RingBuffer {
+    #[inline]
+    fn drop(&mut self) {
+        assert!(self.destroyed);
+    }
+}
+
+/// Binds the buffer to `GL_ARRAY_BUFFER`, which doesn't modify the state of the current VAO.
+unsafe fn bind(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    if ctxt.state.array_buffer_binding == id {
+        return;
+    }
+
+    ctxt.state.array_buffer_binding = id;
+
+    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+       ctxt.version >= &Version(Api::GlEs, 2, 0)
+    {
+        ctxt.gl.BindBuffer(gl::ARRAY_BUFFER, id);
+    } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+        ctxt.gl.BindBufferARB(gl::ARRAY_BUFFER, id);
+    } else {
+        unreachable!();
+    }
+}
+
+/// Allocates a new storage for the buffer. The previous content is orphaned.
+unsafe fn allocate(ctxt: &mut CommandContext, id: gl::types::GLuint, size: usize) {
+    bind(ctxt, id);
+
+    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+       ctxt.version >= &Version(Api::GlEs, 2, 0)
+    {
+        ctxt.gl.BufferData(gl::ARRAY_BUFFER, size as gl::types::GLsizeiptr, ptr::null(),
+                           gl::STREAM_DRAW);
+    } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+        ctxt.gl.BufferDataARB(gl::ARRAY_BUFFER, size as gl::types::GLsizeiptr, ptr::null(),
+                              gl::STREAM_DRAW);
+    } else {
+        unreachable!();
+    }
+}
@@ -1,6 +1,6 @@
 use DrawError;
 
-use uniforms::SamplerBehavior;
+use uniforms::{SamplerBehavior, SamplerReductionMode};
 
 use gl;
 use context::CommandContext;
@@ -50,6 +50,22 @@ impl SamplerObject {
 
                 ctxt.gl.SamplerParameterf(sampler, gl::TEXTURE_MAX_ANISOTROPY_EXT, value);
             }
+
+            if let Some(comparison) = behavior.depth_texture_comparison {
+                ctxt.gl.SamplerParameteri(sampler, gl::TEXTURE_COMPARE_MODE,
+                                          gl::COMPARE_REF_TO_TEXTURE as gl::types::GLint);
+                ctxt.gl.SamplerParameteri(sampler, gl::TEXTURE_COMPARE_FUNC,
+                                          comparison.to_glenum() as gl::types::GLint);
+            } else {
+                ctxt.gl.SamplerParameteri(sampler, gl::TEXTURE_COMPARE_MODE,
+                                          gl::NONE as gl::types::GLint);
+            }
+
+            // support was checked by `get_sampler`
+            if behavior.reduction_mode != SamplerReductionMode::WeightedAverage {
+                ctxt.gl.SamplerParameteri(sampler, gl::TEXTURE_REDUCTION_MODE_ARB,
+                                          behavior.reduction_mode.to_glenum() as gl::types::GLint);
+            }
         }
 
         SamplerObject {
@@ -95,6 +111,20 @@ pub fn get_sampler(ctxt: &mut CommandContext, behavior: &SamplerBehavior)
         return Err(DrawError::SamplersNotSupported);
     }
 
+    if behavior.reduction_mode != SamplerReductionMode::WeightedAverage &&
+       !ctxt.extensions.gl_arb_texture_filter_minmax &&
+       !ctxt.extensions.gl_ext_texture_filter_minmax
+    {
+        return Err(DrawError::SamplerReductionModeNotSupported);
+    }
+
+    if !behavior.wrap_function.0.is_supported(&*ctxt) ||
+       !behavior.wrap_function.1.is_supported(&*ctxt) ||
+       !behavior.wrap_function.2.is_supported(&*ctxt)
+    {
+        return Err(DrawError::SamplerWrapFunctionNotSupported);
+    }
+
     // looking for an existing sampler
     match ctxt.samplers.get(behavior) {
         Some(obj) => return Ok(obj.get_id()),
@@ -0,0 +1,356 @@
+/*!
+Takes screenshots of the default framebuffer without stalling the rendering.
+
+Reading the default framebuffer with `read_front_buffer` waits until all the previous commands
+have been executed, then transfers the pixels to the client memory. A `Screenshot` instead
+reads the pixels into a pixel buffer, which doesn't block, and inserts a sync fence after the
+transfer. Each time `Screenshot::poll` is called, the transfers whose fence is signaled are
+copied to the client memory and handed to a worker thread, which flips the rows and converts
+the pixels to the requested format. The final image is then sent through a channel.
+
+The front buffer can't be read with OpenGL ES, in which case `capture` returns an error.
+
+If the backend doesn't support pixel buffers, the pixels are read synchronously when the
+capture is requested. If it doesn't support sync fences, they are copied to the client memory
+during the next call to `poll`. The conversion always happens on the worker thread.
+
+# Example
+
+```no_run
+use glium::Surface;
+use glium::screenshot::{Screenshot, ScreenshotFormat, ScreenshotSource};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let f12_pressed = false;
+
+let mut screenshot = Screenshot::new(&display);
+let mut images = Vec::new();
+
+// in the rendering loop
+let mut target = display.draw();
+target.clear_color(0.0, 0.0, 0.0, 1.0);
+// ...
+
+if f12_pressed {
+    images.push(screenshot.capture(ScreenshotSource::BackBuffer, ScreenshotFormat::Rgb).unwrap());
+}
+
+target.finish().unwrap();
+screenshot.poll().unwrap();
+
+// for example on the thread that saves the images
+for receiver in images.iter() {
+    if let Ok(image) = receiver.try_recv() {
+        assert_eq!(image.data.len(), image.width as usize * image.height as usize * 3);
+    }
+}
+```
+
+*/
+use backend::Facade;
+use context::Context;
+use ops;
+use sync::SyncFence;
+use texture::pixel_buffer::PixelBuffer;
+use version::{Api, Version};
+use CapabilitiesSource;
+use ContextExt;
+use Rect;
+
+use gl;
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// The buffer of the default framebuffer to read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScreenshotSource {
+    /// The image that is currently displayed.
+    FrontBuffer,
+
+    /// The image that is being drawn. The capture must be requested before calling
+    /// `Frame::finish`, as the content of the back buffer is undefined after swapping buffers.
+    BackBuffer,
+}
+
+/// Error that can happen while capturing the default framebuffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScreenshotError {
+    /// Reading the front buffer is not supported by the backend. This is the case with OpenGL ES.
+    FrontBufferNotSupported,
+
+    /// The worker thread that converts the pixels has stopped.
+    WorkerThreadStopped,
+}
+
+impl fmt::Display for ScreenshotError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for ScreenshotError {
+    fn description(&self) -> &str {
+        match self {
+            &ScreenshotError::FrontBufferNotSupported => "Reading the front buffer is not \
+                                                          supported by the backend",
+            &ScreenshotError::WorkerThreadStopped => "The worker thread that converts the pixels \
+                                                      has stopped",
+        }
+    }
+}
+
+/// Layout of the pixels of a `ScreenshotImage`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    /// Four bytes per pixel, in the red, green, blue, alpha order.
+    Rgba,
+
+    /// Three bytes per pixel, in the red, green, blue order. The alpha component is dropped.
+    Rgb,
+
+    /// Four bytes per pixel, in the blue, green, red, alpha order.
+    Bgra,
+}
+
+impl ScreenshotFormat {
+    /// Returns the number of bytes of each pixel.
+    #[inline]
+    pub fn bytes_per_pixel(&self) -> usize {
+        match *self {
+            ScreenshotFormat::Rgba | ScreenshotFormat::Bgra => 4,
+            ScreenshotFormat::Rgb => 3,
+        }
+    }
+}
+
+/// An image produced by a `Screenshot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenshotImage {
+    /// The pixels, with the top-left pixel first and rows from top to bottom.
+    pub data: Vec<u8>,
+
+    /// Width of the image, in pixels.
+    pub width: u32,
+
+    /// Height of the image, in pixels.
+    pub height: u32,
+
+    /// Layout of the pixels in `data`.
+    pub format: ScreenshotFormat,
+}
+
+/// Pixels that have been transferred to the client memory and must be converted.
+struct Job {
+    data: Vec<(u8, u8, u8, u8)>,
+    width: u32,
+    height: u32,
+    format: ScreenshotFormat,
+    sender: Sender<ScreenshotImage>,
+}
+
+/// A capture whose pixels are being transferred to a pixel buffer.
+struct PendingCapture {
+    buffer: PixelBuffer<(u8, u8, u8, u8)>,
+    fence: Option<SyncFence>,
+    width: u32,
+    height: u32,
+    format: ScreenshotFormat,
+    sender: Sender<ScreenshotImage>,
+}
+
+/// Captures the default framebuffer asynchronously.
+///
+/// See the documentation of the module.
+pub struct Screenshot {
+    context: Rc<Context>,
+
+    /// Captures in the order in which they have been requested.
+    pending: VecDeque<PendingCapture>,
+
+    /// Pixel buffers of the finished captures, that can be reused.
+    free: Vec<PixelBuffer<(u8, u8, u8, u8)>>,
+
+    /// Sends the pixels to the worker thread.
+    jobs: Sender<Job>,
+}
+
+impl Screenshot {
+    /// Builds a new `Screenshot` and starts its worker thread.
+    ///
+    /// The worker thread stops when the `Screenshot` is destroyed.
+    pub fn new<F>(facade: &F) -> Screenshot where F: Facade {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+
+        thread::spawn(move || {
+            for job in receiver.iter() {
+                let image = convert(&job.data, job.width, job.height, job.format);
+                // the receiver may have been dropped, in which case the image is discarded
+                let _ = job.sender.send(image);
+            }
+        });
+
+        Screenshot {
+            context: facade.get_context().clone(),
+            pending: VecDeque::new(),
+            free: Vec::new(),
+            jobs: jobs,
+        }
+    }
+
+    /// Starts reading the whole default framebuffer. Returns the receiver on which the
+    /// image will be sent once it has been converted.
+    ///
+    /// The pixels are only handed to the worker thread during the calls to `poll`. If the
+    /// `Screenshot` is destroyed before that, the receiver is disconnected.
+    ///
+    /// Returns `FrontBufferNotSupported` if the front buffer is requested with OpenGL ES.
+    pub fn capture(&mut self, source: ScreenshotSource, format: ScreenshotFormat)
+                   -> Result<Receiver<ScreenshotImage>, ScreenshotError>
+    {
+        let gles = self.context.get_version().0 == Api::GlEs;
+
+        let read_buffer = match source {
+            ScreenshotSource::FrontBuffer if gles => {
+                return Err(ScreenshotError::FrontBufferNotSupported);
+            },
+            ScreenshotSource::FrontBuffer => gl::FRONT_LEFT,
+            ScreenshotSource::BackBuffer if gles => gl::BACK,
+            ScreenshotSource::BackBuffer => gl::BACK_LEFT,
+        };
+        let source = ops::Source::DefaultFramebuffer(read_buffer);
+
+        let (sender, receiver) = mpsc::channel();
+
+        let (width, height) = self.context.get_framebuffer_dimensions();
+        let rect = Rect { left: 0, bottom: 0, width: width, height: height };
+
+        if !is_pixel_pack_supported(&*self.context) {
+            let mut data = Vec::with_capacity(0);
+            ops::read(&mut self.context.make_current(), source, &rect, &mut data);
+            try!(self.send_job(data, width, height, format, sender));
+            return Ok(receiver);
+        }
+
+        let len = width as usize * height as usize;
+        let buffer = match self.free.iter().position(|b| b.len() >= len) {
+            Some(index) => self.free.swap_remove(index),
+            None => PixelBuffer::new_empty(&self.context, len),
+        };
+
+        ops::read(&mut self.context.make_current(), source, &rect, &buffer);
+        let fence = SyncFence::new(&self.context).ok();
+
+        self.pending.push_back(PendingCapture {
+            buffer: buffer,
+            fence: fence,
+            width: width,
+            height: height,
+            format: format,
+            sender: sender,
+        });
+
+        Ok(receiver)
+    }
+
+    /// Hands the captures whose transfer is finished to the worker thread. Doesn't block.
+    ///
+    /// This should be called regularly, for example once per frame. Returns the number of
+    /// captures that have been handed to the worker thread, or `WorkerThreadStopped` if the
+    /// worker thread has stopped.
+    pub fn poll(&mut self) -> Result<usize, ScreenshotError> {
+        let mut count = 0;
+
+        // the transfers are finished in the order in which they have been started
+        while let Some(capture) = self.pending.pop_front() {
+            let signaled = match capture.fence {
+                Some(ref fence) => fence.is_signaled(),
+                None => true,
+            };
+
+            if !signaled {
+                self.pending.push_front(capture);
+                break;
+            }
+
+            let len = capture.width as usize * capture.height as usize;
+            let data = capture.buffer.slice(0 .. len).unwrap().read();
+
+            self.free.push(capture.buffer);
+
+            // if the buffer can't be read, the capture is dropped and its receiver disconnected
+            if let Ok(data) = data {
+                try!(self.send_job(data, capture.width, capture.height, capture.format,
+                                   capture.sender));
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the number of captures whose transfer isn't finished yet.
+    #[inline]
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn send_job(&self, data: Vec<(u8, u8, u8, u8)>, width: u32, height: u32,
+                format: ScreenshotFormat, sender: Sender<ScreenshotImage>)
+                -> Result<(), ScreenshotError>
+    {
+        // sending only fails if the worker thread has panicked
+        self.jobs.send(Job {
+            data: data,
+            width: width,
+            height: height,
+            format: format,
+            sender: sender,
+        }).map_err(|_| ScreenshotError::WorkerThreadStopped)
+    }
+}
+
+/// Returns true if the pixels can be read into a pixel buffer.
+fn is_pixel_pack_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_version() >= &Version(Api::Gl, 2, 1) ||
+    context.get_version() >= &Version(Api::GlEs, 3, 0) ||
+    context.get_extensions().gl_arb_pixel_buffer_object ||
+    context.get_extensions().gl_nv_pixel_buffer_object
+}
+
+/// Flips the rows of pixels read by OpenGL and converts them to the requested format.
+fn convert(data: &[(u8, u8, u8, u8)], width: u32, height: u32, format: ScreenshotFormat)
+           -> ScreenshotImage
+{
+    let mut output = Vec::with_capacity(data.len() * format.bytes_per_pixel());
+
+    // OpenGL stores the rows from bottom to top
+    let rows = if width == 0 { data.chunks(1) } else { data.chunks(width as usize) };
+    for row in rows.rev() {
+        for &(r, g, b, a) in row.iter() {
+            let (first, third) = match format {
+                ScreenshotFormat::Rgba | ScreenshotFormat::Rgb => (r, b),
+                ScreenshotFormat::Bgra => (b, r),
+            };
+
+            output.push(first);
+            output.push(g);
+            output.push(third);
+
+            if format != ScreenshotFormat::Rgb {
+                output.push(a);
+            }
+        }
+    }
+
+    ScreenshotImage {
+        data: output,
+        width: width,
+        height: height,
+        format: format,
+    }
+}
@@ -0,0 +1,296 @@
+/*!
+Depth textures and the states that are needed to render and sample shadow maps.
+
+A `ShadowMap` owns a depth texture, or an array of depth textures when the view frustum of the
+light is split into several cascades. It provides:
+
+ - The framebuffers to draw on, either one per cascade or a single layered framebuffer in which
+   a geometry shader chooses the cascade of each primitive by writing to `gl_Layer`.
+ - The `DrawParameters` to use when rendering the shadow casters, which enable depth testing,
+   offset the depth of the polygons to avoid self-shadowing artifacts, and clamp the depth of the
+   casters that are in front of the near plane of the light.
+ - A sampler that compares the depth of the fragment with the content of the shadow map, so that
+   the shadow map can be passed to a `sampler2DShadow` (or `sampler2DArrayShadow` for cascades)
+   uniform with the `uniform!` macro.
+
+# Example
+
+```no_run
+# #[macro_use] extern crate glium;
+# fn main() {
+use glium::Surface;
+use glium::shadow_map::{ShadowMap, ShadowMapOptions};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let vertex_buffer: glium::vertex::VertexBufferAny = unsafe { ::std::mem::uninitialized() };
+# let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+# let depth_program: glium::Program = unsafe { ::std::mem::uninitialized() };
+# let program: glium::Program = unsafe { ::std::mem::uninitialized() };
+# let light_matrix = [[0.0f32; 4]; 4];
+
+let options = ShadowMapOptions { size: 2048, .. Default::default() };
+let shadow_map = ShadowMap::new(&display, &options).unwrap();
+
+// rendering the shadow casters from the point of view of the light
+let mut surface = shadow_map.cascade_surface(0).unwrap();
+surface.clear_depth(1.0);
+surface.draw(&vertex_buffer, &indices, &depth_program, &uniform! { matrix: light_matrix },
+             &shadow_map.draw_parameters()).unwrap();
+
+// rendering the scene, with `uniform sampler2DShadow shadow_map;` in the fragment shader
+let mut target = display.draw();
+target.draw(&vertex_buffer, &indices, &program,
+            &uniform! { light_matrix: light_matrix, shadow_map: &shadow_map },
+            &Default::default()).unwrap();
+target.finish().unwrap();
+# }
+```
+
+*/
+use backend::Facade;
+use context::Context;
+use draw_parameters::{DepthClamp, DepthTest, DrawParameters, PolygonOffset};
+use framebuffer::{SimpleFrameBuffer, ValidationError};
+use texture::{DepthFormat, DepthTexture2d, DepthTexture2dArray, MipmapsOption};
+use texture::TextureCreationError;
+use uniforms::{AsUniformValue, DepthTextureComparison, MagnifySamplerFilter};
+use uniforms::{MinifySamplerFilter, SamplerBehavior, SamplerWrapFunction, UniformValue};
+use version::{Api, Version};
+use CapabilitiesSource;
+
+use std::rc::Rc;
+
+/// Characteristics of a `ShadowMap`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ShadowMapOptions {
+    /// Width and height of each cascade, in texels.
+    ///
+    /// The default value is `1024`.
+    pub size: u32,
+
+    /// Number of cascades. If it is greater than `1`, the shadow map is a `DepthTexture2dArray`
+    /// with one layer per cascade, otherwise it is a `DepthTexture2d`.
+    ///
+    /// The default value is `1`.
+    pub cascades: u32,
+
+    /// Format of the depth values.
+    ///
+    /// The default value is `DepthFormat::I24`.
+    pub depth_format: DepthFormat,
+
+    /// Offset applied to the depth of the shadow casters when they are rendered.
+    ///
+    /// The default value offsets filled polygons with a factor of `1.1` and `4.0` units.
+    pub polygon_offset: PolygonOffset,
+
+    /// If true, the depth of the shadow casters that are in front of the near plane of the
+    /// light is clamped instead of the casters being clipped. This is ignored if the backend
+    /// doesn't support depth clamping.
+    ///
+    /// The default value is `true`.
+    pub depth_clamp: bool,
+
+    /// Comparison between the depth of the fragment and the content of the shadow map. The
+    /// sampling returns `1.0` when the comparison passes, which means that the fragment is lit.
+    ///
+    /// The default value is `LessOrEqual`.
+    pub comparison: DepthTextureComparison,
+
+    /// If true, the results of the comparisons with the four nearest texels are merged, which
+    /// smoothens the edges of the shadows.
+    ///
+    /// The default value is `true`.
+    pub linear_filtering: bool,
+}
+
+impl Default for ShadowMapOptions {
+    #[inline]
+    fn default() -> ShadowMapOptions {
+        ShadowMapOptions {
+            size: 1024,
+            cascades: 1,
+            depth_format: DepthFormat::I24,
+            polygon_offset: PolygonOffset {
+                factor: 1.1,
+                units: 4.0,
+                fill: true,
+                .. Default::default()
+            },
+            depth_clamp: true,
+            comparison: DepthTextureComparison::LessOrEqual,
+            linear_filtering: true,
+        }
+    }
+}
+
+/// The texture of a shadow map.
+enum ShadowTexture {
+    Single(DepthTexture2d),
+    Cascades(DepthTexture2dArray),
+}
+
+/// A depth texture with its framebuffers, draw parameters and sampler.
+///
+/// See the documentation of the module.
+pub struct ShadowMap {
+    context: Rc<Context>,
+    texture: ShadowTexture,
+    options: ShadowMapOptions,
+    depth_clamp: DepthClamp,
+}
+
+impl ShadowMap {
+    /// Creates the texture of a shadow map.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `options.cascades` is `0`.
+    pub fn new<F>(facade: &F, options: &ShadowMapOptions)
+                  -> Result<ShadowMap, TextureCreationError> where F: Facade
+    {
+        assert!(options.cascades >= 1, "A shadow map must have at least one cascade");
+
+        let (format, size) = (options.depth_format, options.size);
+        let texture = if options.cascades == 1 {
+            let texture = try!(DepthTexture2d::empty_with_format(facade, format,
+                                                                 MipmapsOption::NoMipmap,
+                                                                 size, size));
+            ShadowTexture::Single(texture)
+        } else {
+            let texture = try!(DepthTexture2dArray::empty_with_format(facade, format,
+                                                                      MipmapsOption::NoMipmap,
+                                                                      size, size,
+                                                                      options.cascades));
+            ShadowTexture::Cascades(texture)
+        };
+
+        let depth_clamp = if options.depth_clamp && is_depth_clamp_supported(facade) {
+            DepthClamp::Clamp
+        } else {
+            DepthClamp::NoClamp
+        };
+
+        Ok(ShadowMap {
+            context: facade.get_context().clone(),
+            texture: texture,
+            options: *options,
+            depth_clamp: depth_clamp,
+        })
+    }
+
+    /// Returns the options that the shadow map has been created with.
+    #[inline]
+    pub fn get_options(&self) -> &ShadowMapOptions {
+        &self.options
+    }
+
+    /// Returns the number of cascades.
+    #[inline]
+    pub fn get_cascades_count(&self) -> u32 {
+        self.options.cascades
+    }
+
+    /// Returns the texture if the shadow map has a single cascade.
+    #[inline]
+    pub fn get_texture(&self) -> Option<&DepthTexture2d> {
+        match self.texture {
+            ShadowTexture::Single(ref texture) => Some(texture),
+            ShadowTexture::Cascades(_) => None,
+        }
+    }
+
+    /// Returns the texture if the shadow map has several cascades.
+    #[inline]
+    pub fn get_texture_array(&self) -> Option<&DepthTexture2dArray> {
+        match self.texture {
+            ShadowTexture::Single(_) => None,
+            ShadowTexture::Cascades(ref texture) => Some(texture),
+        }
+    }
+
+    /// Builds a framebuffer that draws on one cascade.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of range.
+    pub fn cascade_surface(&self, index: u32) -> Result<SimpleFrameBuffer, ValidationError> {
+        assert!(index < self.options.cascades, "The cascade index is out of range");
+
+        match self.texture {
+            ShadowTexture::Single(ref texture) => {
+                SimpleFrameBuffer::depth_only(&self.context, texture)
+            },
+            ShadowTexture::Cascades(ref texture) => {
+                let layer = texture.main_level().layer(index).unwrap();
+                SimpleFrameBuffer::depth_only(&self.context, layer)
+            },
+        }
+    }
+
+    /// Builds a layered framebuffer that draws on all the cascades at once. A geometry shader
+    /// must choose the cascade of each primitive by writing to `gl_Layer`.
+    ///
+    /// If the shadow map has a single cascade, this is the same as `cascade_surface(0)`.
+    pub fn layered_surface(&self) -> Result<SimpleFrameBuffer, ValidationError> {
+        match self.texture {
+            ShadowTexture::Single(_) => self.cascade_surface(0),
+            ShadowTexture::Cascades(ref texture) => {
+                SimpleFrameBuffer::depth_only_layered(&self.context, *texture.main_level())
+            },
+        }
+    }
+
+    /// Returns the draw parameters to use when rendering the shadow casters.
+    ///
+    /// The other parameters keep their default value. Culling the faces that are facing the
+    /// light can be added to further reduce the artifacts.
+    pub fn draw_parameters<'a>(&self) -> DrawParameters<'a> {
+        DrawParameters {
+            depth_test: DepthTest::IfLess,
+            depth_write: true,
+            depth_clamp: self.depth_clamp,
+            polygon_offset: self.options.polygon_offset,
+            color_mask: (false, false, false, false),
+            .. Default::default()
+        }
+    }
+
+    /// Returns the sampler that compares the depth of the fragment with the shadow map.
+    pub fn get_sampler_behavior(&self) -> SamplerBehavior {
+        let filter = if self.options.linear_filtering {
+            (MinifySamplerFilter::Linear, MagnifySamplerFilter::Linear)
+        } else {
+            (MinifySamplerFilter::Nearest, MagnifySamplerFilter::Nearest)
+        };
+
+        SamplerBehavior {
+            wrap_function: (SamplerWrapFunction::Clamp, SamplerWrapFunction::Clamp,
+                            SamplerWrapFunction::Clamp),
+            minify_filter: filter.0,
+            magnify_filter: filter.1,
+            depth_texture_comparison: Some(self.options.comparison),
+            .. Default::default()
+        }
+    }
+}
+
+impl<'a> AsUniformValue for &'a ShadowMap {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        let sampler = Some(self.get_sampler_behavior());
+
+        match self.texture {
+            ShadowTexture::Single(ref texture) => UniformValue::DepthTexture2d(texture, sampler),
+            ShadowTexture::Cascades(ref texture) => {
+                UniformValue::DepthTexture2dArray(texture, sampler)
+            },
+        }
+    }
+}
+
+/// Returns true if the backend supports `DepthClamp::Clamp`.
+fn is_depth_clamp_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_version() >= &Version(Api::Gl, 3, 0) ||
+    context.get_extensions().gl_arb_depth_clamp ||
+    context.get_extensions().gl_nv_depth_clamp
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+use std::error::Error;
 use context::CommandContext;
 use version::Api;
 use version::Version;
@@ -14,6 +16,18 @@ use std::thread;
 #[derive(Copy, Clone, Debug)]
 pub struct SyncNotSupportedError;
 
+impl fmt::Display for SyncNotSupportedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for SyncNotSupportedError {
+    fn description(&self) -> &str {
+        "Sync fences are not supported by the backend"
+    }
+}
+
 /// Provides a way to wait for a server-side operation to be finished.
 ///
 /// Creating a `SyncFence` injects an element in the commands queue of the backend.
@@ -41,6 +55,30 @@ impl SyncFence {
         unsafe { new_linear_sync_fence(&mut ctxt) }.map(|f| f.into_sync_fence(facade))
     }
 
+    /// Returns true if the operation has finished on the server. Doesn't block.
+    ///
+    /// Also returns true if the wait has failed, as the fence would then never be signaled.
+    pub fn is_signaled(&self) -> bool {
+        let sync = self.id.unwrap();
+
+        let ctxt = self.context.make_current();
+
+        let result = unsafe {
+            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+            {
+                ctxt.gl.ClientWaitSync(sync, gl::SYNC_FLUSH_COMMANDS_BIT, 0)
+            } else {
+                ctxt.gl.ClientWaitSyncAPPLE(sync, gl::SYNC_FLUSH_COMMANDS_BIT_APPLE, 0)
+            }
+        };
+
+        match result {
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED | gl::WAIT_FAILED => true,
+            _ => false,
+        }
+    }
+
     /// Blocks until the operation has finished on the server.
     pub fn wait(mut self) {
         let sync = self.id.take().unwrap();
@@ -130,6 +168,31 @@ pub unsafe fn wait_linear_sync_fence_and_drop(mut fence: LinearSyncFence,
     delete_fence(ctxt, fence);
 }
 
+/// Waits for this fence without destroying it, from within the commands context.
+#[inline]
+pub unsafe fn wait_linear_sync_fence(fence: &LinearSyncFence, ctxt: &mut CommandContext) {
+    client_wait(ctxt, fence.id.unwrap());
+}
+
+/// Returns true if the fence has been signaled, from within the commands context. Doesn't block.
+pub unsafe fn is_linear_sync_fence_signaled(fence: &LinearSyncFence, ctxt: &mut CommandContext)
+                                            -> bool
+{
+    let result = if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                    ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+    {
+        ctxt.gl.ClientWaitSync(fence.id.unwrap(), gl::SYNC_FLUSH_COMMANDS_BIT, 0)
+    } else {
+        ctxt.gl.ClientWaitSyncAPPLE(fence.id.unwrap(), gl::SYNC_FLUSH_COMMANDS_BIT_APPLE, 0)
+    };
+
+    // a fence whose wait has failed would never be signaled
+    match result {
+        gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED | gl::WAIT_FAILED => true,
+        _ => false,
+    }
+}
+
 /// Destroys a fence, from within the commands context.
 #[inline]
 pub unsafe fn destroy_linear_sync_fence(ctxt: &mut CommandContext, mut fence: LinearSyncFence) {
@@ -160,7 +223,8 @@ unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync) -> gl
     match result {
         val @ gl::ALREADY_SIGNALED | val @ gl::CONDITION_SATISFIED => return val,
         gl::TIMEOUT_EXPIRED => (),
-        gl::WAIT_FAILED => (),
+        // waiting again would fail the same way
+        val @ gl::WAIT_FAILED => return val,
         _ => unreachable!()
     };
 
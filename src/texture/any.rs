@@ -11,11 +11,12 @@ use TextureExt;
 use TextureMipmapExt;
 use version::Api;
 use Rect;
+use debug::ErrorCheckPolicy;
 
 use image_format::{self, TextureFormatRequest, ClientFormatAny};
 use texture::Texture2dDataSink;
-use texture::{MipmapsOption, TextureFormat, TextureCreationError, CubeLayer};
-use texture::{get_format, InternalFormat, GetFormatError};
+use texture::{MipmapsOption, TextureFormat, TextureCreationError, TextureUsage, CubeLayer};
+use texture::{get_format, InternalFormat, GetFormatError, ReadError};
 use texture::pixel::PixelValue;
 use texture::pixel_buffer::PixelBuffer;
 
@@ -69,6 +70,12 @@ pub struct TextureAny {
     levels: u32,
     /// Is automatic mipmap generation allowed for this texture?
     generate_mipmaps: bool,
+
+    /// How the texture has been declared to be used.
+    usage: TextureUsage,
+
+    /// False if the texture has been created outside of glium and must not be destroyed.
+    owned: bool,
 }
 
 /// Builds a new texture.
@@ -78,7 +85,7 @@ pub struct TextureAny {
 /// Panicks if the size of the data doesn't match the texture dimensions.
 pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                              data: Option<(ClientFormatAny, Cow<'a, [P]>)>,
-                             mipmaps: MipmapsOption, ty: Dimensions)
+                             mipmaps: MipmapsOption, usage: TextureUsage, ty: Dimensions)
                              -> Result<TextureAny, TextureCreationError>
                              where P: Send + Clone + 'a, F: Facade
 {
@@ -92,7 +99,11 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
         Dimensions::Texture2dMultisampleArray { width, height, array_size, samples } => (width, Some(height), None, Some(array_size), Some(samples)),
         Dimensions::Texture3d { width, height, depth } => (width, Some(height), Some(depth), None, None),
         Dimensions::Cubemap { dimension } => (dimension, Some(dimension), None, None, None),
-        Dimensions::CubemapArray { dimension, array_size } => (dimension, Some(dimension), None, Some(array_size * 6), None),
+        Dimensions::CubemapArray { dimension, array_size } => {
+            let layers = try!(array_size.checked_mul(6)
+                                        .ok_or(TextureCreationError::DimensionsNotSupported));
+            (dimension, Some(dimension), None, Some(layers), None)
+        },
     };
 
     let (is_client_compressed, data_bufsize) = match data {
@@ -104,9 +115,8 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
     };
 
     if let Some((_, ref data)) = data {
-        if data.len() * mem::size_of::<P>() != data_bufsize
-        {
-            panic!("Texture data size mismatch");
+        if data.len() * mem::size_of::<P>() != data_bufsize {
+            return Err(TextureCreationError::DataSizeMismatch);
         }
     }
 
@@ -138,12 +148,116 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
         }
     }
 
+    // checking the dimensions against the limits of the backend
+    {
+        let capabilities = facade.get_context().get_capabilities();
+
+        let max_size = match ty {
+            Dimensions::Texture3d { .. } => capabilities.max_3d_texture_size,
+            Dimensions::Cubemap { .. } | Dimensions::CubemapArray { .. } => {
+                capabilities.max_cube_map_texture_size
+            },
+            _ => Some(capabilities.max_texture_size),
+        };
+
+        let max_size = match max_size {
+            Some(s) => s as u32,
+            None => return Err(TextureCreationError::TypeNotSupported),
+        };
+
+        if width > max_size || height.unwrap_or(0) > max_size || depth.unwrap_or(0) > max_size {
+            return Err(TextureCreationError::DimensionsNotSupported);
+        }
+
+        if let Some(array_size) = array_size {
+            match capabilities.max_array_texture_layers {
+                Some(max) if array_size <= max as u32 => (),
+                Some(_) => return Err(TextureCreationError::DimensionsNotSupported),
+                None => return Err(TextureCreationError::TypeNotSupported),
+            }
+        }
+    }
+
+    // checking multisampling
+    if samples.is_some() {
+        let context = facade.get_context();
+        let version = context.get_version();
+        let extension = context.get_extensions().gl_arb_texture_multisample;
+
+        // OpenGL ES 3.1 only supports multisample textures without layers
+        let supported = match ty {
+            Dimensions::Texture2dMultisampleArray { .. } => {
+                version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 2) ||
+                extension
+            },
+            _ => {
+                version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 1) ||
+                extension
+            },
+        };
+
+        if !supported {
+            return Err(TextureCreationError::TypeNotSupported);
+        }
+    }
+
     let generate_mipmaps = mipmaps.should_generate();
     let texture_levels = mipmaps.num_levels(width, height, depth) as gl::types::GLsizei;
 
     let teximg_internal_format = try!(image_format::format_request_to_glenum(facade.get_context(), data.as_ref().map(|&(c, _)| c), format, image_format::RequestType::TexImage));
     let storage_internal_format = image_format::format_request_to_glenum(facade.get_context(), data.as_ref().map(|&(c, _)| c), format, image_format::RequestType::TexStorage).ok();
 
+    // choosing between immutable and mutable storage depending on the usage
+    let use_tex_storage = {
+        let context = facade.get_context();
+        let tex_storage_supported = context.get_version() >= &Version(Api::Gl, 4, 2) ||
+                                    context.get_extensions().gl_arb_texture_storage;
+
+        match usage {
+            TextureUsage::StreamingUpload => false,
+            TextureUsage::StorageImage => {
+                storage_internal_format.is_some() &&
+                (tex_storage_supported || context.get_version() >= &Version(Api::GlEs, 3, 0))
+            },
+            _ => storage_internal_format.is_some() && tex_storage_supported,
+        }
+    };
+
+    // OpenGL ES only allows immutable storage for multisample textures
+    let use_tex_storage = if samples.is_some() &&
+                             facade.get_context().get_version() >= &Version(Api::GlEs, 3, 1)
+    {
+        if storage_internal_format.is_none() {
+            return Err(TextureCreationError::FormatNotSupported);
+        }
+
+        true
+    } else {
+        use_tex_storage
+    };
+
+    // checking that the usage is compatible with the texture
+    match usage {
+        TextureUsage::RenderTarget => {
+            if is_client_compressed || format_is_compressed(format) {
+                return Err(TextureCreationError::UsageNotSupported);
+            }
+        },
+        TextureUsage::StorageImage => {
+            let context = facade.get_context();
+            let image_load_store = context.get_version() >= &Version(Api::Gl, 4, 2) ||
+                                   context.get_version() >= &Version(Api::GlEs, 3, 1) ||
+                                   context.get_extensions().gl_arb_shader_image_load_store;
+
+            if !image_load_store || !use_tex_storage || is_client_compressed ||
+               format_is_compressed(format)
+            {
+                return Err(TextureCreationError::UsageNotSupported);
+            }
+        },
+        _ => (),
+    }
+
     let (client_format, client_type) = match (&data, format) {
         (&Some((client_format, _)), f) => try!(image_format::client_format_to_glenum(facade.get_context(), client_format, f, false)),
         (&None, TextureFormatRequest::AnyDepth) => (gl::DEPTH_COMPONENT, gl::FLOAT),
@@ -155,6 +269,10 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
 
     let mut ctxt = facade.get_context().make_current();
 
+    if data.is_some() {
+        ctxt.record_statistics(|s| s.texture_upload_bytes += data_bufsize as u64);
+    }
+
     let id = unsafe {
         let has_mipmaps = texture_levels > 1;
         let data = data;
@@ -234,7 +352,7 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                 a => a
             };
 
-            if storage_internal_format.is_some() && (ctxt.version >= &Version(Api::Gl, 4, 2) || ctxt.extensions.gl_arb_texture_storage) {
+            if use_tex_storage {
                 ctxt.gl.TexStorage3D(bind_point, texture_levels,
                                      storage_internal_format.unwrap() as gl::types::GLenum,
                                      width, height, depth);
@@ -276,7 +394,7 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                 a => a
             };
 
-            if storage_internal_format.is_some() && (ctxt.version >= &Version(Api::Gl, 4, 2) || ctxt.extensions.gl_arb_texture_storage) {
+            if use_tex_storage {
                 ctxt.gl.TexStorage2D(bind_point, texture_levels,
                                      storage_internal_format.unwrap() as gl::types::GLenum,
                                      width, height);
@@ -315,7 +433,7 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                 a => a
             };
 
-            if storage_internal_format.is_some() && (ctxt.version >= &Version(Api::Gl, 4, 2) || ctxt.extensions.gl_arb_texture_storage) {
+            if use_tex_storage {
                 ctxt.gl.TexStorage2DMultisample(gl::TEXTURE_2D_MULTISAMPLE,
                                                 samples.unwrap() as gl::types::GLsizei,
                                                 storage_internal_format.unwrap() as gl::types::GLenum,
@@ -344,7 +462,7 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                 a => a
             };
 
-            if storage_internal_format.is_some() && (ctxt.version >= &Version(Api::Gl, 4, 2) || ctxt.extensions.gl_arb_texture_storage) {
+            if use_tex_storage {
                 ctxt.gl.TexStorage3DMultisample(gl::TEXTURE_2D_MULTISAMPLE_ARRAY,
                                                 samples.unwrap() as gl::types::GLsizei,
                                                 storage_internal_format.unwrap() as gl::types::GLenum,
@@ -370,7 +488,7 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                 a => a
             };
 
-            if storage_internal_format.is_some() && (ctxt.version >= &Version(Api::Gl, 4, 2) || ctxt.extensions.gl_arb_texture_storage) {
+            if use_tex_storage {
                 ctxt.gl.TexStorage1D(bind_point, texture_levels,
                                      storage_internal_format.unwrap() as gl::types::GLenum,
                                      width);
@@ -400,8 +518,36 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
             unreachable!();
         }
 
-        // only generate mipmaps for color textures
-        if generate_mipmaps {
+        // drivers report a lack of memory with `GL_OUT_OF_MEMORY`, in which case the texture
+        // has no storage ; querying the storage can force a synchronization with the driver, so
+        // this is only done when OpenGL errors are checked
+        if ctxt.error_check_policy != ErrorCheckPolicy::Never &&
+           (ctxt.version >= &Version(Api::Gl, 1, 0) || ctxt.version >= &Version(Api::GlEs, 3, 1))
+        {
+            let target = if bind_point == gl::TEXTURE_CUBE_MAP {
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X
+            } else {
+                bind_point
+            };
+
+            let mut obtained_width = 0;
+            ctxt.gl.GetTexLevelParameteriv(target, 0, gl::TEXTURE_WIDTH, &mut obtained_width);
+
+            if obtained_width == 0 {
+                for tex_unit in ctxt.state.texture_units.iter_mut() {
+                    if tex_unit.texture == id {
+                        tex_unit.texture = 0;
+                    }
+                }
+
+                ctxt.gl.DeleteTextures(1, [id].as_ptr());
+                return Err(TextureCreationError::OutOfMemory);
+            }
+        }
+
+        // only generate mipmaps for color textures, and not for render targets since their
+        // content is going to be overwritten
+        if generate_mipmaps && usage != TextureUsage::RenderTarget {
             if ctxt.version >= &Version(Api::Gl, 3, 0) ||
                ctxt.version >= &Version(Api::GlEs, 2, 0)
             {
@@ -424,9 +570,45 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
         ty: ty,
         levels: texture_levels as u32,
         generate_mipmaps: generate_mipmaps,
+        usage: usage,
+        owned: true,
     })
 }
 
+/// Returns true if the requested format is compressed.
+fn format_is_compressed(format: TextureFormatRequest) -> bool {
+    match format {
+        TextureFormatRequest::AnyCompressed | TextureFormatRequest::AnyCompressedSrgb => true,
+        TextureFormatRequest::Specific(TextureFormat::CompressedFormat(_)) => true,
+        TextureFormatRequest::Specific(TextureFormat::CompressedSrgbFormat(_)) => true,
+        _ => false,
+    }
+}
+
+/// Builds a texture from the id of an existing OpenGL texture.
+///
+/// If `owned` is false, the OpenGL texture isn't destroyed when the object is dropped.
+pub unsafe fn from_id<F>(facade: &F, format: TextureFormatRequest, id: gl::types::GLuint,
+                         owned: bool, mipmaps: MipmapsOption, ty: Dimensions) -> TextureAny
+                         where F: Facade
+{
+    let mut texture = TextureAny {
+        context: facade.get_context().clone(),
+        id: id,
+        requested_format: format,
+        actual_format: Cell::new(None),
+        ty: ty,
+        levels: 1,
+        generate_mipmaps: mipmaps.should_generate(),
+        usage: TextureUsage::General,
+        owned: owned,
+    };
+
+    texture.levels = mipmaps.num_levels(texture.get_width(), texture.get_height(),
+                                        texture.get_depth());
+    texture
+}
+
 impl TextureAny {
     /// Returns the width of the texture.
     #[inline]
@@ -546,6 +728,12 @@ impl TextureAny {
         self.levels
     }
 
+    /// Returns the usage that the texture has been created with.
+    #[inline]
+    pub fn get_usage(&self) -> TextureUsage {
+        self.usage
+    }
+
     /// Returns a structure that represents the main mipmap level of the texture.
     #[inline]
     pub fn main_level(&self) -> TextureAnyMipmap {
@@ -570,6 +758,72 @@ impl TextureAny {
             depth: self.get_depth().map(|depth| cmp::max(1, depth / pow)),
         })
     }
+
+    /// Reads a rectangle of a mipmap level of the texture to RAM.
+    ///
+    /// Only the content of `rect` is transferred, which is much cheaper than reading the whole
+    /// texture when it is large. The first layer of arrays and the positive X face of cubemaps
+    /// are read.
+    ///
+    /// Uses `glGetTextureSubImage` if the backend supports it, and otherwise attaches the
+    /// mipmap level to a framebuffer and reads it with `glReadPixels`.
+    ///
+    /// Returns `None` if the level is out of range.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the rect is out of range.
+    pub fn read_rect<T>(&self, rect: Rect, level: u32) -> Option<T>
+                        where T: Texture2dDataSink<(u8, u8, u8, u8)>
+    {
+        let mipmap = match self.mipmap(level) {
+            Some(m) => m,
+            None => return None,
+        };
+
+        assert!(rect.left + rect.width <= mipmap.width);
+        assert!(rect.bottom + rect.height <= mipmap.height.unwrap_or(1));
+
+        let len = rect.width as usize * rect.height as usize;
+        let mut data: Vec<(u8, u8, u8, u8)> = Vec::with_capacity(len);
+
+        let mut ctxt = self.context.make_current();
+
+        if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_get_texture_sub_image {
+            unsafe {
+                BufferAny::unbind_pixel_pack(&mut ctxt);
+
+                // the rows of `(u8, u8, u8, u8)` pixels are always aligned on 4 bytes
+                if ctxt.state.pixel_store_pack_alignment > 4 {
+                    ctxt.state.pixel_store_pack_alignment = 4;
+                    ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
+                }
+
+                ctxt.gl.GetTextureSubImage(self.id, level as gl::types::GLint,
+                                           rect.left as gl::types::GLint,
+                                           rect.bottom as gl::types::GLint, 0,
+                                           rect.width as gl::types::GLsizei,
+                                           rect.height as gl::types::GLsizei, 1,
+                                           gl::RGBA, gl::UNSIGNED_BYTE,
+                                           (len * 4) as gl::types::GLsizei,
+                                           data.as_mut_ptr() as *mut _);
+                data.set_len(len);
+            }
+
+        } else {
+            let cube_layer = match self.ty {
+                Dimensions::Cubemap { .. } | Dimensions::CubemapArray { .. } => {
+                    Some(CubeLayer::PositiveX)
+                },
+                _ => None,
+            };
+
+            let image = mipmap.first_layer().into_image(cube_layer).unwrap();
+            ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(image), &rect, &mut data);
+        }
+
+        Some(T::from_raw(Cow::Owned(data), rect.width, rect.height))
+    }
 }
 
 impl TextureExt for TextureAny {
@@ -605,6 +859,7 @@ impl TextureExt for TextureAny {
         if ctxt.state.texture_units[texture_unit as usize].texture != self.id {
             unsafe { ctxt.gl.BindTexture(bind_point, self.id) };
             ctxt.state.texture_units[texture_unit as usize].texture = self.id;
+            ctxt.record_statistics(|s| s.texture_binds += 1);
         }
 
         bind_point
@@ -636,14 +891,26 @@ impl Drop for TextureAny {
         // removing FBOs which contain this texture
         fbo::FramebuffersContainer::purge_texture(&mut ctxt, self.id);
 
-        // resetting the bindings
-        for tex_unit in ctxt.state.texture_units.iter_mut() {
-            if tex_unit.texture == self.id {
-                tex_unit.texture = 0;
-            }
+        if self.owned {
+            unsafe { ctxt.destroy_object(self.id, destroy_texture); }
+        } else {
+            reset_texture_bindings(&mut ctxt, self.id);
         }
+    }
+}
+
+/// Resets the bindings of a texture and destroys it.
+unsafe fn destroy_texture(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    reset_texture_bindings(ctxt, id);
+    ctxt.gl.DeleteTextures(1, [ id ].as_ptr());
+}
 
-        unsafe { ctxt.gl.DeleteTextures(1, [ self.id ].as_ptr()); }
+/// Removes a texture from the texture units.
+fn reset_texture_bindings(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    for tex_unit in ctxt.state.texture_units.iter_mut() {
+        if tex_unit.texture == id {
+            tex_unit.texture = 0;
+        }
     }
 }
 
@@ -796,6 +1063,20 @@ impl<'a> TextureAnyMipmap<'a> {
         })
     }
 
+    /// Invalidates the content of this mipmap. Its data becomes undefined.
+    ///
+    /// This tells the backend that it doesn't need to preserve the content of the texture, for
+    /// example before drawing over all of it. Does nothing if the backend doesn't support it.
+    pub fn invalidate(&self) {
+        let ctxt = self.texture.context.make_current();
+
+        if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.extensions.gl_arb_invalidate_subdata {
+            unsafe {
+                ctxt.gl.InvalidateTexImage(self.texture.id, self.level as gl::types::GLint);
+            }
+        }
+    }
+
     /// Uploads data to the texture from a buffer.
     ///
     /// # Panic
@@ -1029,6 +1310,7 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
                                                                                       .map_err(|_| ()));
 
         let mut ctxt = self.texture.context.make_current();
+        ctxt.record_statistics(|s| s.texture_upload_bytes += data_bufsize as u64);
 
         unsafe {
             if ctxt.state.pixel_store_unpack_alignment != 1 {
@@ -1044,8 +1326,29 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
 
             } else if bind_point == gl::TEXTURE_2D || bind_point == gl::TEXTURE_1D_ARRAY {
                 assert!(z_offset == 0);
-                // FIXME should glTexImage be used here somewhere or glTexSubImage does it just fine?
-                if is_client_compressed {
+
+                // streaming textures have a mutable storage, and respecifying the whole image
+                // allows the driver to orphan the previous one instead of waiting for it
+                let respecify = self.texture.usage == TextureUsage::StreamingUpload &&
+                                !is_client_compressed && x_offset == 0 && y_offset == 0 &&
+                                width == self.width &&
+                                height.unwrap_or(1) == self.height.unwrap_or(1);
+
+                if respecify {
+                    let internal_format = try!(image_format::format_request_to_glenum(
+                                                    &self.texture.context, Some(format),
+                                                    self.texture.requested_format,
+                                                    image_format::RequestType::TexImage)
+                                                    .map_err(|_| ()));
+
+                    ctxt.gl.TexImage2D(bind_point, level as gl::types::GLint,
+                                       internal_format as gl::types::GLint,
+                                       width as gl::types::GLsizei,
+                                       height.unwrap_or(1) as gl::types::GLsizei, 0,
+                                       client_format, client_type,
+                                       data.as_ptr() as *const libc::c_void);
+
+                } else if is_client_compressed {
                     ctxt.gl.CompressedTexSubImage2D(bind_point, level as gl::types::GLint,
                                                     x_offset as gl::types::GLint,
                                                     y_offset as gl::types::GLint,
@@ -1297,6 +1600,35 @@ impl<'a> TextureAnyImage<'a> {
         T::from_raw(Cow::Owned(data), self.width, self.height.unwrap_or(1))
     }
 
+    /// Reads the content of the image with a pixel type other than `(u8, u8, u8, u8)`, for
+    /// example `(f16, f16, f16, f16)` for floating-point textures.
+    ///
+    /// Returns `FormatNotSupported` if the backend can't read pixels of this type. OpenGL ES
+    /// only supports one type in addition to `(u8, u8, u8, u8)`, which depends on the format of
+    /// the texture. Returns `ContextLost` if the context has been lost.
+    ///
+    /// # Panic
+    ///
+    /// Panicks if the rect is out of range.
+    ///
+    pub fn raw_read_if_supported<T, P>(&self, rect: &Rect) -> Result<T, ReadError>
+                                       where T: Texture2dDataSink<P>, P: PixelValue
+    {
+        assert!(rect.left + rect.width <= self.width);
+        assert!(rect.bottom + rect.height <= self.height.unwrap_or(1));
+
+        let mut ctxt = self.texture.context.make_current();
+
+        if ctxt.state.lost_context {
+            return Err(ReadError::ContextLost);
+        }
+
+        let mut data = Vec::new();
+        try!(ops::read_if_supported(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect,
+                                    &mut data).map_err(|_| ReadError::FormatNotSupported));
+        Ok(T::from_raw(Cow::Owned(data), rect.width, rect.height))
+    }
+
     /// Reads the content of the image to a pixel buffer.
     ///
     /// # Panic
@@ -1309,8 +1641,36 @@ impl<'a> TextureAnyImage<'a> {
         assert!(rect.bottom + rect.height <= self.height.unwrap_or(1));
         assert!(dest.len() >= rect.width as usize * rect.height as usize);
 
-        let size = rect.width as usize * rect.height as usize * 4;
         let mut ctxt = self.texture.context.make_current();
         ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect, dest);
     }
+
+    /// Reads the content of the image to a pixel buffer with a pixel type other than
+    /// `(u8, u8, u8, u8)`.
+    ///
+    /// Returns `FormatNotSupported` if the backend can't read pixels of this type, or
+    /// `ContextLost` if the context has been lost. The buffer is then left untouched.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the rect is out of range.
+    /// - Panicks if the buffer is not large enough.
+    ///
+    pub fn raw_read_to_pixel_buffer_if_supported<P>(&self, rect: &Rect, dest: &PixelBuffer<P>)
+                                                    -> Result<(), ReadError>
+                                                    where P: PixelValue
+    {
+        assert!(rect.left + rect.width <= self.width);
+        assert!(rect.bottom + rect.height <= self.height.unwrap_or(1));
+        assert!(dest.len() >= rect.width as usize * rect.height as usize);
+
+        let mut ctxt = self.texture.context.make_current();
+
+        if ctxt.state.lost_context {
+            return Err(ReadError::ContextLost);
+        }
+
+        ops::read_if_supported(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect, dest)
+            .map_err(|_| ReadError::FormatNotSupported)
+    }
 }
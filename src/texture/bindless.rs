@@ -57,6 +57,8 @@ do in the future). Binding the wrong type of texture may lead to undefined value
 the texture.
 
 */
+use std::fmt;
+use std::error::Error;
 use texture::any::TextureAny;
 use TextureExt;
 use GlObject;
@@ -290,6 +292,18 @@ impl<'a> UniformBlock for TextureHandle<'a> {
 #[derive(Debug, Copy, Clone)]
 pub struct BindlessTexturesNotSupportedError;
 
+impl fmt::Display for BindlessTexturesNotSupportedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for BindlessTexturesNotSupportedError {
+    fn description(&self) -> &str {
+        "Bindless textures are not supported by the backend"
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::mem;
@@ -52,6 +52,8 @@ to sample from a buffer texture of type `Unsigned` you need to use a `usamplerBu
 wrong type will result in an error.
 
 */
+use std::fmt;
+use std::error::Error;
 use std::mem;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
@@ -90,6 +92,26 @@ pub enum TextureCreationError {
     TooLarge,
 }
 
+impl fmt::Display for TextureCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for TextureCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &TextureCreationError::NotSupported => "Buffer textures are not supported by the \
+                                                    backend",
+            &TextureCreationError::FormatNotSupported => "The requested format is not supported \
+                                                          in combination with the texture buffer \
+                                                          type",
+            &TextureCreationError::TooLarge => "The size of the buffer exceeds \
+                                                `GL_MAX_TEXTURE_BUFFER_SIZE`",
+        }
+    }
+}
+
 /// Error that can happen while building a buffer texture.
 #[derive(Copy, Clone, Debug)]
 pub enum CreationError {
@@ -100,6 +122,31 @@ pub enum CreationError {
     TextureCreationError(TextureCreationError),
 }
 
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => err.fmt(fmt),
+            &CreationError::TextureCreationError(ref err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::BufferCreationError(_) => "Failed to create the buffer",
+            &CreationError::TextureCreationError(_) => "Failed to create the texture",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            &CreationError::TextureCreationError(ref err) => Some(err),
+        }
+    }
+}
+
 impl From<BufferCreationError> for CreationError {
     #[inline]
     fn from(err: BufferCreationError) -> CreationError {
@@ -506,6 +553,7 @@ impl<'a> TextureExt for BufferTextureRef<'a> {
     #[inline]
     fn bind_to_current(&self, ctxt: &mut CommandContext) -> gl::types::GLenum {
         unsafe { ctxt.gl.BindTexture(gl::TEXTURE_BUFFER, self.texture); }
+        ctxt.record_statistics(|s| s.texture_binds += 1);
         gl::TEXTURE_BUFFER
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+use std::error::Error;
 use context::CommandContext;
 use version::Version;
 use version::Api;
@@ -16,6 +18,21 @@ pub enum GetFormatError {
     NotSupported,
 }
 
+impl fmt::Display for GetFormatError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for GetFormatError {
+    fn description(&self) -> &str {
+        match self {
+            &GetFormatError::NotSupported => "The backend doesn't support retrieving the \
+                                              internal format",
+        }
+    }
+}
+
 /// Internal format of a texture.
 ///
 /// The actual format of a texture is not necessarly one of the predefined ones, so we have
@@ -0,0 +1,169 @@
+/*!
+Conversions between `Texture2d` and the `DynamicImage` type of the `image` library.
+
+This module is only available if the `image` feature is enabled.
+
+# Example
+
+```no_run
+# #[cfg(feature = "image")]
+# fn example() {
+extern crate image;
+use glium::texture::Texture2d;
+use glium::texture::image_io::ImageOptions;
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+
+let image = image::open("texture.png").unwrap();
+let texture = Texture2d::from_dynamic_image(&display, &image, Default::default()).unwrap();
+
+// ...
+
+let screenshot = texture.to_dynamic_image(ImageOptions { srgb: true, .. Default::default() });
+# }
+# fn main() {}
+```
+
+*/
+use std::borrow::Cow;
+
+use image::{self, GenericImage};
+
+use backend::Facade;
+use texture::{ClientFormat, MipmapsOption, RawImage2d, Texture2d, TextureCreationError};
+use texture::UncompressedFloatFormat;
+
+/// Options for the conversions between textures and images.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageOptions {
+    /// If true, the rows are flipped during the conversion.
+    ///
+    /// The `image` library stores the rows from top to bottom, while OpenGL stores them from
+    /// bottom to top. Flipping them keeps the image the right way up when it is sampled with
+    /// texture coordinates whose origin is the bottom-left corner.
+    ///
+    /// The default value is `true`.
+    pub flip_vertically: bool,
+
+    /// If true, the colors of the image are sRGB-encoded.
+    ///
+    /// When loading an image, its colors are converted to linear values and the texture is
+    /// created with 16 bits per component so that no precision is lost in the dark colors.
+    /// When saving a texture, its colors are considered linear and are encoded to sRGB. The
+    /// alpha component is never converted.
+    ///
+    /// If you want OpenGL to do the conversion when sampling, load the image in a
+    /// `SrgbTexture2d` instead.
+    ///
+    /// The default value is `false`.
+    pub srgb: bool,
+}
+
+impl Default for ImageOptions {
+    #[inline]
+    fn default() -> ImageOptions {
+        ImageOptions {
+            flip_vertically: true,
+            srgb: false,
+        }
+    }
+}
+
+impl Texture2d {
+    /// Builds a new texture with the content of an image.
+    ///
+    /// The image is converted to RGBA, and mipmaps are automatically generated.
+    pub fn from_dynamic_image<F>(facade: &F, image: &image::DynamicImage, options: ImageOptions)
+                                 -> Result<Texture2d, TextureCreationError> where F: Facade
+    {
+        let image = image.to_rgba();
+        let (width, height) = image.dimensions();
+        let data = flip_rows(image.into_raw(), width as usize * 4, options.flip_vertically);
+
+        if options.srgb {
+            let data = data.iter().enumerate().map(|(i, &c)| {
+                if i % 4 == 3 { c as u16 * 257 } else { srgb_to_linear(c) }
+            }).collect::<Vec<u16>>();
+
+            let raw = RawImage2d {
+                data: Cow::Owned(data),
+                width: width,
+                height: height,
+                format: ClientFormat::U16U16U16U16,
+            };
+
+            Texture2d::with_format(facade, raw, UncompressedFloatFormat::U16U16U16U16,
+                                   MipmapsOption::AutoGeneratedMipmaps)
+
+        } else {
+            let raw = RawImage2d {
+                data: Cow::Owned(data),
+                width: width,
+                height: height,
+                format: ClientFormat::U8U8U8U8,
+            };
+
+            Texture2d::new(facade, raw)
+        }
+    }
+
+    /// Reads the content of the texture into an RGBA image.
+    ///
+    /// The content is read with 8 bits per component, whatever the format of the texture.
+    pub fn to_dynamic_image(&self, options: ImageOptions) -> image::DynamicImage {
+        let raw: RawImage2d<(u8, u8, u8, u8)> = self.read();
+
+        let mut data = Vec::with_capacity(raw.data.len() * 4);
+        for &(r, g, b, a) in raw.data.iter() {
+            if options.srgb {
+                data.push(linear_to_srgb(r));
+                data.push(linear_to_srgb(g));
+                data.push(linear_to_srgb(b));
+            } else {
+                data.push(r);
+                data.push(g);
+                data.push(b);
+            }
+
+            data.push(a);
+        }
+
+        let data = flip_rows(data, raw.width as usize * 4, options.flip_vertically);
+        let image = image::ImageBuffer::from_raw(raw.width, raw.height, data).unwrap();
+        image::DynamicImage::ImageRgba8(image)
+    }
+}
+
+/// Reverses the order of the rows of `data` if `flip` is true.
+fn flip_rows<T>(data: Vec<T>, row_len: usize, flip: bool) -> Vec<T> where T: Copy {
+    if !flip || row_len == 0 {
+        return data;
+    }
+
+    data.chunks(row_len).rev().flat_map(|row| row.iter().cloned()).collect()
+}
+
+/// Converts an sRGB-encoded component to a linear 16 bits component.
+fn srgb_to_linear(value: u8) -> u16 {
+    let value = value as f32 / 255.0;
+
+    let linear = if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    };
+
+    (linear * 65535.0 + 0.5) as u16
+}
+
+/// Converts a linear component to an sRGB-encoded component.
+fn linear_to_srgb(value: u8) -> u8 {
+    let value = value as f32 / 255.0;
+
+    let srgb = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (srgb * 255.0 + 0.5) as u8
+}
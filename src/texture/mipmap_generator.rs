@@ -0,0 +1,467 @@
+/*!
+Generates the mipmaps of a texture with a downsampling pass, instead of `glGenerateMipmap`.
+
+The quality of the mipmaps that drivers generate varies, and some drivers block until the
+generation is over. A `MipmapGenerator` instead draws each level into a framebuffer whose color
+attachment is the level, and computes its texels from the previous level with a selectable
+filter:
+
+ - `MipmapFilter::Box` averages the texels of the previous level that are covered by each texel,
+   weighted by the covered area. This handles textures whose dimensions are not powers of two.
+ - `MipmapFilter::Kaiser` is a sinc filter windowed with a Kaiser window, which keeps the
+   mipmaps sharper than a box filter.
+
+The texels are blended in linear space if `gamma_correct` is true, which avoids darkening the
+mipmaps of textures that contain sRGB data in a linear format. For `SrgbTexture2d`s, OpenGL
+already converts the texels to linear space when they are read and back to sRGB when they are
+written.
+
+The levels are computed one after the other on the GPU, and the generation doesn't block.
+
+# Example
+
+```no_run
+use glium::texture::mipmap_generator::{GenerationOptions, MipmapFilter, MipmapGenerator};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let texture: glium::texture::Texture2d = unsafe { ::std::mem::uninitialized() };
+
+let generator = MipmapGenerator::new(&display).unwrap();
+
+let options = GenerationOptions {
+    filter: MipmapFilter::Kaiser { alpha: 4.0 },
+    gamma_correct: true,
+};
+
+generator.generate(&texture, &options).unwrap();
+```
+
+*/
+use std::error::Error;
+use std::fmt;
+
+use backend::Facade;
+use framebuffer::{SimpleFrameBuffer, ValidationError};
+use index::{NoIndices, PrimitiveType};
+use program::{Program, ProgramCreationError};
+use texture::{SrgbTexture2d, Texture2d, TextureAny};
+use uniforms::AsUniformValue;
+use vertex::{BufferCreationError, VertexBuffer};
+use version::{Api, Version};
+use CapabilitiesSource;
+use DrawError;
+use Surface;
+use TextureExt;
+
+use gl;
+
+/// Filter used to compute a level from the previous one.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MipmapFilter {
+    /// Averages the texels that are covered by each texel of the new level.
+    Box,
+
+    /// A sinc filter windowed with a Kaiser window, whose radius is two texels of the new level.
+    Kaiser {
+        /// Shape of the window. Higher values reduce ringing but blur more. `4.0` is a good
+        /// default.
+        alpha: f32,
+    },
+}
+
+/// How the mipmaps are generated.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GenerationOptions {
+    /// The filter to use.
+    ///
+    /// The default value is `MipmapFilter::Box`.
+    pub filter: MipmapFilter,
+
+    /// If true, the texels are converted from sRGB to linear space before being filtered, and
+    /// back to sRGB afterwards. Ignored for `SrgbTexture2d`s, which are always filtered in
+    /// linear space.
+    ///
+    /// The default value is `false`.
+    pub gamma_correct: bool,
+}
+
+impl Default for GenerationOptions {
+    #[inline]
+    fn default() -> GenerationOptions {
+        GenerationOptions {
+            filter: MipmapFilter::Box,
+            gamma_correct: false,
+        }
+    }
+}
+
+/// Error that can happen when creating a `MipmapGenerator`.
+#[derive(Clone, Debug)]
+pub enum CreationError {
+    /// Choosing the level that is read is not supported by the backend.
+    NotSupported,
+
+    /// Error while compiling the program.
+    ProgramCreationError(ProgramCreationError),
+
+    /// Error while creating the vertex buffer.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CreationError::ProgramCreationError(ref err) => err.fmt(fmt),
+            &CreationError::BufferCreationError(ref err) => err.fmt(fmt),
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::NotSupported => "Generating mipmaps with a downsampling pass is not \
+                                             supported by the backend",
+            &CreationError::ProgramCreationError(_) => "Error while compiling the program",
+            &CreationError::BufferCreationError(_) => "Error while creating the vertex buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::ProgramCreationError(ref err) => Some(err),
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProgramCreationError> for CreationError {
+    #[inline]
+    fn from(err: ProgramCreationError) -> CreationError {
+        CreationError::ProgramCreationError(err)
+    }
+}
+
+impl From<BufferCreationError> for CreationError {
+    #[inline]
+    fn from(err: BufferCreationError) -> CreationError {
+        CreationError::BufferCreationError(err)
+    }
+}
+
+/// Error that can happen when generating mipmaps.
+#[derive(Clone, Debug)]
+pub enum GenerationError {
+    /// A level of the texture can't be used as a framebuffer attachment.
+    FramebufferError(ValidationError),
+
+    /// Error while drawing a level.
+    DrawError(DrawError),
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &GenerationError::FramebufferError(ref err) => err.fmt(fmt),
+            &GenerationError::DrawError(ref err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl Error for GenerationError {
+    fn description(&self) -> &str {
+        match self {
+            &GenerationError::FramebufferError(_) => "A level of the texture can't be used as a \
+                                                      framebuffer attachment",
+            &GenerationError::DrawError(_) => "Error while drawing a level",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &GenerationError::FramebufferError(ref err) => Some(err),
+            &GenerationError::DrawError(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<ValidationError> for GenerationError {
+    #[inline]
+    fn from(err: ValidationError) -> GenerationError {
+        GenerationError::FramebufferError(err)
+    }
+}
+
+impl From<DrawError> for GenerationError {
+    #[inline]
+    fn from(err: DrawError) -> GenerationError {
+        GenerationError::DrawError(err)
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+/// Generates mipmaps with a downsampling pass.
+///
+/// See the documentation of the module.
+pub struct MipmapGenerator {
+    vertex_buffer: VertexBuffer<Vertex>,
+    program: Program,
+}
+
+impl MipmapGenerator {
+    /// Returns true if the backend supports generating mipmaps with a `MipmapGenerator`.
+    ///
+    /// The level that is read is chosen with `GL_TEXTURE_BASE_LEVEL` and `GL_TEXTURE_MAX_LEVEL`,
+    /// which requires OpenGL 1.2 or OpenGL ES 3.0.
+    #[inline]
+    pub fn is_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+        context.get_version() >= &Version(Api::Gl, 1, 2) ||
+        context.get_version() >= &Version(Api::GlEs, 3, 0)
+    }
+
+    /// Builds the program and the vertex buffer of the generator.
+    pub fn new<F>(facade: &F) -> Result<MipmapGenerator, CreationError> where F: Facade {
+        if !MipmapGenerator::is_supported(facade) {
+            return Err(CreationError::NotSupported);
+        }
+
+        let vertex_buffer = try!(VertexBuffer::new(facade, &[
+            Vertex { position: [-1.0, -1.0] },
+            Vertex { position: [1.0, -1.0] },
+            Vertex { position: [-1.0, 1.0] },
+            Vertex { position: [1.0, 1.0] },
+        ]));
+
+        let program = if facade.get_context().is_glsl_version_supported(&Version(Api::Gl, 1, 10)) {
+            try!(Program::from_source(facade, &format!("#version 110\n{}", VERTEX),
+                                      &format!("#version 110\n{}", FRAGMENT), None))
+        } else {
+            try!(Program::from_source(facade, &format!("#version 100\n{}", VERTEX),
+                                      &format!("#version 100\nprecision highp float;\n{}",
+                                               FRAGMENT), None))
+        };
+
+        Ok(MipmapGenerator {
+            vertex_buffer: vertex_buffer,
+            program: program,
+        })
+    }
+
+    /// Generates all the mipmaps of a texture from its main level.
+    ///
+    /// Does nothing if the texture doesn't have mipmaps.
+    pub fn generate(&self, texture: &Texture2d, options: &GenerationOptions)
+                    -> Result<(), GenerationError>
+    {
+        let context = texture.get_context();
+        self.generate_levels(texture, texture, options.filter, options.gamma_correct, |level| {
+            SimpleFrameBuffer::new(context, texture.mipmap(level).unwrap())
+        })
+    }
+
+    /// Generates all the mipmaps of an sRGB texture from its main level.
+    ///
+    /// Does nothing if the texture doesn't have mipmaps.
+    pub fn generate_srgb(&self, texture: &SrgbTexture2d, options: &GenerationOptions)
+                         -> Result<(), GenerationError>
+    {
+        let context = texture.get_context();
+        self.generate_levels(texture, texture, options.filter, false, |level| {
+            SimpleFrameBuffer::new(context, texture.mipmap(level).unwrap())
+        })
+    }
+
+    fn generate_levels<'a, U, S>(&self, texture: &TextureAny, source: U, filter: MipmapFilter,
+                                 gamma_correct: bool, surface: S) -> Result<(), GenerationError>
+                                 where U: AsUniformValue + Copy,
+                                       S: FnMut(u32) -> Result<SimpleFrameBuffer<'a>,
+                                                               ValidationError>
+    {
+        let (base, max) = unsafe { get_levels_range(texture) };
+        let result = self.draw_levels(texture, source, filter, gamma_correct, surface);
+        unsafe { set_levels_range(texture, base, max) };
+        result
+    }
+
+    fn draw_levels<'a, U, S>(&self, texture: &TextureAny, source: U, filter: MipmapFilter,
+                             gamma_correct: bool, mut surface: S) -> Result<(), GenerationError>
+                             where U: AsUniformValue + Copy,
+                                   S: FnMut(u32) -> Result<SimpleFrameBuffer<'a>, ValidationError>
+    {
+        let (kaiser, kaiser_alpha) = match filter {
+            MipmapFilter::Box => (false, 0.0),
+            MipmapFilter::Kaiser { alpha } => (true, alpha),
+        };
+
+        for level in 1 .. texture.get_mipmap_levels() {
+            // only the previous level can be sampled, so that the level that is written isn't
+            // read at the same time
+            unsafe { set_levels_range(texture, level - 1, level - 1) };
+
+            let mut target = try!(surface(level));
+            let source_size = {
+                let previous = texture.mipmap(level - 1).unwrap();
+                (previous.get_width() as f32, previous.get_height().unwrap_or(1) as f32)
+            };
+            let (width, height) = target.get_dimensions();
+
+            let uniforms = uniform! {
+                source: source,
+                source_size: source_size,
+                ratio: (source_size.0 / width as f32, source_size.1 / height as f32),
+                kaiser: kaiser,
+                kaiser_alpha: kaiser_alpha,
+                gamma_correct: gamma_correct,
+            };
+
+            try!(target.draw(&self.vertex_buffer, &NoIndices(PrimitiveType::TriangleStrip),
+                             &self.program, &uniforms, &Default::default()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the values of `GL_TEXTURE_BASE_LEVEL` and `GL_TEXTURE_MAX_LEVEL`.
+unsafe fn get_levels_range(texture: &TextureAny) -> (u32, u32) {
+    let mut ctxt = texture.get_context().make_current();
+    let bind_point = texture.bind_to_current(&mut ctxt);
+
+    let mut base = 0;
+    ctxt.gl.GetTexParameteriv(bind_point, gl::TEXTURE_BASE_LEVEL, &mut base);
+    let mut max = 1000;
+    ctxt.gl.GetTexParameteriv(bind_point, gl::TEXTURE_MAX_LEVEL, &mut max);
+
+    (base as u32, max as u32)
+}
+
+/// Sets `GL_TEXTURE_BASE_LEVEL` and `GL_TEXTURE_MAX_LEVEL`.
+unsafe fn set_levels_range(texture: &TextureAny, base: u32, max: u32) {
+    let mut ctxt = texture.get_context().make_current();
+    let bind_point = texture.bind_to_current(&mut ctxt);
+    ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_BASE_LEVEL, base as gl::types::GLint);
+    ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MAX_LEVEL, max as gl::types::GLint);
+}
+
+const VERTEX: &'static str = "
+    attribute vec2 position;
+
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+// `source` only gives access to the previous level, and is always sampled at the center of its
+// texels so that the filtering of the texture doesn't matter
+const FRAGMENT: &'static str = "
+    uniform sampler2D source;
+    uniform vec2 source_size;
+    uniform vec2 ratio;
+    uniform bool kaiser;
+    uniform float kaiser_alpha;
+    uniform bool gamma_correct;
+
+    const float PI = 3.14159265;
+    const float KAISER_RADIUS = 2.0;
+
+    vec3 srgb_to_linear(vec3 c) {
+        vec3 low = c / 12.92;
+        vec3 high = pow((c + 0.055) / 1.055, vec3(2.4));
+        return mix(low, high, step(0.04045, c));
+    }
+
+    vec3 linear_to_srgb(vec3 c) {
+        vec3 low = c * 12.92;
+        vec3 high = 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055;
+        return mix(low, high, step(0.0031308, c));
+    }
+
+    vec4 fetch(vec2 texel) {
+        vec2 coords = (clamp(texel, vec2(0.0), source_size - 1.0) + 0.5) / source_size;
+        vec4 value = texture2D(source, coords);
+        if (gamma_correct) {
+            value.rgb = srgb_to_linear(value.rgb);
+        }
+        return value;
+    }
+
+    float bessel_i0(float x) {
+        float sum = 1.0;
+        float term = 1.0;
+        for (int k = 1; k < 12; ++k) {
+            float t = x / (2.0 * float(k));
+            term *= t * t;
+            sum += term;
+        }
+        return sum;
+    }
+
+    // `x` is a distance in texels of the level being written
+    float kaiser_weight(float x) {
+        if (abs(x) >= KAISER_RADIUS) {
+            return 0.0;
+        }
+
+        float sinc = x == 0.0 ? 1.0 : sin(PI * x) / (PI * x);
+        float t = x / KAISER_RADIUS;
+        return sinc * bessel_i0(kaiser_alpha * sqrt(1.0 - t * t)) / bessel_i0(kaiser_alpha);
+    }
+
+    // length of the intersection between the texel `k` and `[low, high)`
+    float box_weight(float k, float low, float high) {
+        return max(0.0, min(k + 1.0, high) - max(k, low));
+    }
+
+    void main() {
+        vec2 dest = floor(gl_FragCoord.xy);
+        vec4 sum = vec4(0.0);
+        float total = 0.0;
+
+        if (kaiser) {
+            vec2 center = (dest + 0.5) * ratio;
+            vec2 first = floor(center);
+
+            // covers the radius of the filter when a level is up to three times smaller
+            for (int y = -6; y <= 6; ++y) {
+                for (int x = -6; x <= 6; ++x) {
+                    vec2 k = first + vec2(float(x), float(y));
+                    vec2 d = (k + 0.5 - center) / ratio;
+                    float w = kaiser_weight(d.x) * kaiser_weight(d.y);
+                    if (w != 0.0) {
+                        sum += w * fetch(k);
+                        total += w;
+                    }
+                }
+            }
+
+        } else {
+            vec2 low = dest * ratio;
+            vec2 high = (dest + 1.0) * ratio;
+            vec2 first = floor(low);
+
+            for (int y = 0; y < 4; ++y) {
+                for (int x = 0; x < 4; ++x) {
+                    vec2 k = first + vec2(float(x), float(y));
+                    float w = box_weight(k.x, low.x, high.x) * box_weight(k.y, low.y, high.y);
+                    if (w > 0.0) {
+                        sum += w * fetch(k);
+                        total += w;
+                    }
+                }
+            }
+        }
+
+        vec4 color = clamp(sum / total, 0.0, 1.0);
+        if (gamma_correct) {
+            color.rgb = linear_to_srgb(color.rgb);
+        }
+        gl_FragColor = color;
+    }
+";
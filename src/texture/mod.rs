@@ -75,6 +75,8 @@ You can use thousands of textures if you want.
 */
 #![allow(unreachable_code)]     // TODO: remove
 
+use std::fmt;
+use std::error::Error;
 use std::borrow::Cow;
 
 #[cfg(feature = "image")]
@@ -99,7 +101,12 @@ pub use self::ty_support::is_cubemap_arrays_supported;
 
 pub mod bindless;
 pub mod buffer_texture;
+#[cfg(feature = "image")]
+pub mod image_io;
+pub mod mipmap_generator;
 pub mod pixel_buffer;
+pub mod streaming;
+pub mod video;
 
 mod any;
 mod get_format;
@@ -145,6 +152,8 @@ pub enum MipmapsOption {
     NoMipmap,
 
     /// Allocates space for all the possible amount of mipmaps given the texture dimensions.
+    ///
+    /// The mipmaps can then be filled with a `mipmap_generator::MipmapGenerator`.
     EmptyMipmaps,
 
     /// Allocates space for the specified amount of mipmaps (excluding the top level) but does not
@@ -218,6 +227,8 @@ pub enum CompressedMipmapsOption {
     NoMipmap,
 
     /// Allocates space for all the possible amount of mipmaps given the texture dimensions.
+    ///
+    /// The mipmaps can then be filled with a `mipmap_generator::MipmapGenerator`.
     EmptyMipmaps,
 
     /// Allocates space for the specified amount of mipmaps (excluding the top level) but does not
@@ -225,6 +236,49 @@ pub enum CompressedMipmapsOption {
     EmptyMipmapsMax(u32),
 }
 
+/// Describes how a texture is going to be used.
+///
+/// Glium uses this hint to choose how the storage of the texture is allocated and updated, and
+/// to report the operations that don't match the usage.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextureUsage {
+    /// The texture can be used in any way. This is the usage of the textures that are built
+    /// without a hint.
+    General,
+
+    /// The texture is only sampled from shaders, and its content is updated with `write`.
+    ///
+    /// The texture can't be attached to a framebuffer.
+    Sampled,
+
+    /// The texture is drawn to by attaching it to a framebuffer.
+    ///
+    /// Compressed formats can't be used, and automatically generated mipmaps aren't generated
+    /// when the texture is created since its content is going to be overwritten.
+    RenderTarget,
+
+    /// The texture is read and written by shaders through image units.
+    ///
+    /// The storage of the texture is always immutable, which the backend must support along
+    /// with image load and store. Compressed formats can't be used.
+    StorageImage,
+
+    /// The whole content of the texture is replaced often, for example every frame.
+    ///
+    /// The storage of the texture is mutable, and a `write` that covers a whole mipmap level of
+    /// a two-dimensional texture respecifies the image instead of modifying it. This lets the
+    /// driver give a new storage to the texture instead of waiting until the draw commands that
+    /// use the previous content are finished.
+    StreamingUpload,
+}
+
+impl Default for TextureUsage {
+    #[inline]
+    fn default() -> TextureUsage {
+        TextureUsage::General
+    }
+}
+
 /// Trait that describes a texture.
 pub trait Texture {
     /// Returns the width in pixels of the texture.
@@ -416,6 +470,18 @@ impl<'a, P: PixelValue + Clone> Texture2dDataSource<'a> for RawImage2d<'a, P> {
     }
 }
 
+impl<'a, P: PixelValue + Clone> Texture2dDataSink<P> for RawImage2d<'a, P> {
+    #[inline]
+    fn from_raw(data: Cow<[P]>, width: u32, height: u32) -> RawImage2d<'a, P> {
+        RawImage2d {
+            data: Cow::Owned(data.into_owned()),
+            width: width,
+            height: height,
+            format: <P as PixelValue>::get_format(),
+        }
+    }
+}
+
 impl<P> Texture2dDataSink<P> for Vec<Vec<P>> where P: Copy + Clone {
     fn from_raw(data: Cow<[P]>, width: u32, height: u32) -> Self {
         data.chunks(width as usize).map(|e| e.to_vec()).collect()
@@ -602,11 +668,48 @@ pub enum TextureCreationError {
     /// The requested format is not supported by the backend.
     FormatNotSupported,
 
-    /// The requested texture dimensions are not supported.
+    /// The requested texture dimensions are not supported, for example because they exceed the
+    /// limits of the backend.
     DimensionsNotSupported,
 
     /// The texture format is not supported by the backend.
     TypeNotSupported,
+
+    /// Not enough memory to create the texture.
+    ///
+    /// This is only detected if OpenGL errors are checked. See `debug::ErrorCheckPolicy`.
+    OutOfMemory,
+
+    /// The requested `TextureUsage` is not supported by the backend, or can't be used with the
+    /// requested format.
+    UsageNotSupported,
+
+    /// The size of the data doesn't match the dimensions of the texture.
+    DataSizeMismatch,
+}
+
+impl fmt::Display for TextureCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for TextureCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &TextureCreationError::FormatNotSupported => "The requested format is not supported \
+                                                          by the backend",
+            &TextureCreationError::DimensionsNotSupported => "The requested texture dimensions \
+                                                              are not supported",
+            &TextureCreationError::TypeNotSupported => "The texture type is not supported by the \
+                                                        backend",
+            &TextureCreationError::OutOfMemory => "Not enough memory to create the texture",
+            &TextureCreationError::UsageNotSupported => "The requested usage is not supported \
+                                                         for this texture",
+            &TextureCreationError::DataSizeMismatch => "The size of the data doesn't match the \
+                                                        dimensions of the texture",
+        }
+    }
 }
 
 impl From<FormatNotSupportedError> for TextureCreationError {
@@ -615,3 +718,29 @@ impl From<FormatNotSupportedError> for TextureCreationError {
         TextureCreationError::FormatNotSupported
     }
 }
+
+/// Error that can happen when reading the content of a texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadError {
+    /// The backend can't read the texture with the requested pixel type.
+    FormatNotSupported,
+
+    /// The context has been lost. Reading from the texture would return garbage data.
+    ContextLost,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for ReadError {
+    fn description(&self) -> &str {
+        match self {
+            &ReadError::FormatNotSupported => "The backend can't read the texture with the \
+                                               requested pixel type",
+            &ReadError::ContextLost => "The context has been lost",
+        }
+    }
+}
@@ -1,5 +1,7 @@
 #[cfg(feature = "image")]
 use image;
+#[cfg(feature = "half")]
+use half;
 
 /// A trait that must be implemented for any type that can represent the value of a pixel.
 pub unsafe trait PixelValue: Copy + Clone + Send + 'static {
@@ -175,6 +177,38 @@ unsafe impl PixelValue for (u32, u32, u32, u32) {
     }
 }
 
+#[cfg(feature = "half")]
+unsafe impl PixelValue for half::f16 {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for (half::f16, half::f16) {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for (half::f16, half::f16, half::f16) {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for (half::f16, half::f16, half::f16, half::f16) {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16F16F16F16
+    }
+}
+
 unsafe impl PixelValue for f32 {
     #[inline]
     fn get_format() -> super::ClientFormat {
@@ -0,0 +1,582 @@
+/*!
+Streams texture data decoded by other threads through a pool of pixel buffers.
+
+The thread that owns the OpenGL context creates a `TextureStreamer` and gives clones of its
+`StreamQueue` to worker threads. A worker asks the queue for a `StagingBuffer`, decodes an
+image in it, then submits it along with the `ResourceId` of the texture to update.
+
+The staging buffers are pixel unpack buffers that are persistently mapped in memory, so the
+workers write the pixels directly in memory that OpenGL can read, without any intermediate
+copy. Streaming is therefore only supported if the backend supports persistent mapping.
+
+Each time `TextureStreamer::process` is called, the textures are updated from the submitted
+buffers with `glTexSubImage`. The transfer between the buffer and the texture is asynchronous.
+A sync fence is inserted after each transfer, and the buffer is only given to a worker again
+once its fence is signaled.
+
+# Example
+
+```no_run
+use std::thread;
+use glium::command_encoder::Resources;
+use glium::texture::streaming::TextureStreamer;
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let texture: glium::texture::Texture2d = unsafe { ::std::mem::uninitialized() };
+
+let mut textures = Resources::new();
+let id = textures.insert(texture);
+
+// 4 buffers that can each contain 256x256 pixels
+let mut streamer = TextureStreamer::<(u8, u8, u8, u8)>::new(&display, 256 * 256, 4).unwrap();
+
+let worker = {
+    let queue = streamer.queue();
+    thread::spawn(move || {
+        let mut staging = queue.staging(256 * 256).unwrap();
+        for pixel in staging.iter_mut() {
+            *pixel = (255, 0, 0, 255);      // decode the image here
+        }
+
+        let rect = glium::Rect { left: 0, bottom: 0, width: 256, height: 256 };
+        queue.submit(staging, id, rect).unwrap();
+    })
+};
+
+worker.join().unwrap();
+
+// on the thread that owns the context, for example once per frame
+streamer.process(&textures).unwrap();
+```
+
+*/
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::slice;
+use std::sync::{Arc, Condvar, Mutex};
+
+use backend::Facade;
+use buffer::{Buffer, BufferCreationError, BufferType, BufferMode};
+use command_encoder::{InvalidResourceId, ResourceId, Resources};
+use sync::SyncFence;
+use texture::{PixelValue, Texture2d};
+use ContextExt;
+use Rect;
+
+/// Error that can happen when creating a `TextureStreamer`.
+#[derive(Copy, Clone, Debug)]
+pub enum CreationError {
+    /// The backend doesn't support persistently-mapped pixel unpack buffers.
+    NotSupported,
+
+    /// Error while creating the pixel buffers.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => err.fmt(fmt),
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::NotSupported => "The backend doesn't support persistently-mapped \
+                                             pixel unpack buffers",
+            &CreationError::BufferCreationError(_) => "Error while creating the pixel buffers",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<BufferCreationError> for CreationError {
+    #[inline]
+    fn from(err: BufferCreationError) -> CreationError {
+        CreationError::BufferCreationError(err)
+    }
+}
+
+/// Error that can happen when submitting or processing an upload.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamError {
+    /// The number of pixels in the staging buffer doesn't match the dimensions of the
+    /// rectangle.
+    DataSizeMismatch,
+
+    /// The rectangle is outside of the main level of the texture.
+    RectOutOfBounds,
+
+    /// The `ResourceId` doesn't correspond to any texture.
+    InvalidResourceId(ResourceId),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for StreamError {
+    fn description(&self) -> &str {
+        match self {
+            &StreamError::DataSizeMismatch => "The number of pixels in the staging buffer \
+                                               doesn't match the dimensions of the rectangle",
+            &StreamError::RectOutOfBounds => "The rectangle is outside of the texture",
+            &StreamError::InvalidResourceId(_) => "The resource id doesn't correspond to any \
+                                                   texture",
+        }
+    }
+}
+
+/// Error returned by `TextureStreamer::process` if some uploads have been discarded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessError {
+    /// Number of uploads that have been started.
+    pub started: usize,
+
+    /// Errors of the uploads that have been discarded, in the order of submission.
+    pub errors: Vec<StreamError>,
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{} upload(s) have been discarded", self.errors.len())
+    }
+}
+
+impl Error for ProcessError {
+    fn description(&self) -> &str {
+        "Some uploads have been discarded"
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        self.errors.first().map(|err| err as &Error)
+    }
+}
+
+impl From<InvalidResourceId> for StreamError {
+    #[inline]
+    fn from(err: InvalidResourceId) -> StreamError {
+        StreamError::InvalidResourceId(err.0)
+    }
+}
+
+/// A pixel buffer of the pool, identified by its index, and its mapping in memory.
+struct Slot<P> {
+    index: usize,
+    data: *mut P,
+}
+
+// the mapping is only accessed by the thread that owns the slot
+unsafe impl<P> Send for Slot<P> where P: Send {}
+
+/// Memory in which a worker thread writes the pixels of an upload.
+///
+/// This is a pixel buffer of the pool that is mapped in memory. Dereferences to the list of
+/// pixels, with the bottom-left pixel first and rows from bottom to top. The buffer goes back
+/// to the pool if it is dropped without being submitted.
+pub struct StagingBuffer<P> where P: PixelValue {
+    shared: Arc<(Mutex<Shared<P>>, Condvar)>,
+    slot: Option<Slot<P>>,
+    len: usize,
+}
+
+impl<P> Deref for StagingBuffer<P> where P: PixelValue {
+    type Target = [P];
+
+    #[inline]
+    fn deref(&self) -> &[P] {
+        let slot = self.slot.as_ref().unwrap();
+        unsafe { slice::from_raw_parts(slot.data, self.len) }
+    }
+}
+
+impl<P> DerefMut for StagingBuffer<P> where P: PixelValue {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [P] {
+        let slot = self.slot.as_ref().unwrap();
+        unsafe { slice::from_raw_parts_mut(slot.data, self.len) }
+    }
+}
+
+impl<P> Drop for StagingBuffer<P> where P: PixelValue {
+    fn drop(&mut self) {
+        let mut shared = self.shared.0.lock().unwrap();
+        shared.outstanding -= 1;
+
+        if let Some(slot) = self.slot.take() {
+            shared.free.push(slot);
+        }
+
+        self.shared.1.notify_all();
+    }
+}
+
+/// An upload that has been submitted but not processed yet.
+struct Upload<P> {
+    slot: Slot<P>,
+    len: usize,
+    texture: ResourceId,
+    rect: Rect,
+}
+
+/// State shared between the streamer and the queues.
+struct Shared<P> {
+    /// Uploads in the order of submission.
+    uploads: VecDeque<Upload<P>>,
+
+    /// Buffers that can be given to the workers.
+    free: Vec<Slot<P>>,
+
+    /// Number of `StagingBuffer`s that exist.
+    outstanding: usize,
+
+    /// True if the `TextureStreamer` has been destroyed.
+    closed: bool,
+}
+
+/// Handle that worker threads use to submit uploads.
+///
+/// Cloning a queue returns a handle to the same queue.
+pub struct StreamQueue<P> where P: PixelValue {
+    shared: Arc<(Mutex<Shared<P>>, Condvar)>,
+    capacity: usize,
+}
+
+impl<P> StreamQueue<P> where P: PixelValue {
+    /// Returns a buffer of the pool that can contain `len` pixels. The content of the buffer
+    /// is unspecified.
+    ///
+    /// If all the buffers are used, blocks until `TextureStreamer::process` makes one of them
+    /// available again. Returns `None` if the `TextureStreamer` has been destroyed.
+    ///
+    /// This must not be called from the thread that processes the queue, as it could wait
+    /// forever.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `len` is larger than the capacity of the buffers of the pool.
+    pub fn staging(&self, len: usize) -> Option<StagingBuffer<P>> {
+        assert!(len <= self.capacity, "The staging buffer is larger than the pixel buffers");
+
+        let mut shared = self.shared.0.lock().unwrap();
+
+        loop {
+            if shared.closed {
+                return None;
+            }
+
+            if let Some(slot) = shared.free.pop() {
+                shared.outstanding += 1;
+                return Some(StagingBuffer { shared: self.shared.clone(), slot: Some(slot),
+                                            len: len });
+            }
+
+            shared = self.shared.1.wait(shared).unwrap();
+        }
+    }
+
+    /// Same as `staging`, but returns `None` instead of blocking if all the buffers are used.
+    pub fn try_staging(&self, len: usize) -> Option<StagingBuffer<P>> {
+        assert!(len <= self.capacity, "The staging buffer is larger than the pixel buffers");
+
+        let mut shared = self.shared.0.lock().unwrap();
+        if shared.closed {
+            return None;
+        }
+
+        shared.free.pop().map(|slot| {
+            shared.outstanding += 1;
+            StagingBuffer { shared: self.shared.clone(), slot: Some(slot), len: len }
+        })
+    }
+
+    /// Submits the content of a staging buffer. It will be written to the `rect` area of the
+    /// main level of `texture` during the next call to `TextureStreamer::process`.
+    ///
+    /// Returns an error if the number of pixels in the staging buffer doesn't match the
+    /// dimensions of `rect`, in which case the buffer goes back to the pool. Whether the
+    /// rectangle fits in the texture is checked by `process`, as only the thread that owns the
+    /// context has access to the texture.
+    pub fn submit(&self, mut staging: StagingBuffer<P>, texture: ResourceId, rect: Rect)
+                  -> Result<(), StreamError>
+    {
+        if staging.len != rect.width as usize * rect.height as usize {
+            return Err(StreamError::DataSizeMismatch);
+        }
+
+        self.shared.0.lock().unwrap().uploads.push_back(Upload {
+            slot: staging.slot.take().unwrap(),
+            len: staging.len,
+            texture: texture,
+            rect: rect,
+        });
+
+        // the staging buffer is only destroyed once the upload is in the queue, so that the
+        // streamer can't be destroyed in between
+        drop(staging);
+        Ok(())
+    }
+
+    /// Returns the number of uploads that haven't been processed yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shared.0.lock().unwrap().uploads.len()
+    }
+}
+
+impl<P> Clone for StreamQueue<P> where P: PixelValue {
+    #[inline]
+    fn clone(&self) -> StreamQueue<P> {
+        StreamQueue {
+            shared: self.shared.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// A pixel buffer of the pool.
+struct PooledBuffer<P> where P: PixelValue {
+    buffer: Buffer<[P]>,
+
+    /// The buffer and its fence while a transfer from this buffer may be in progress.
+    pending: Option<(Slot<P>, Option<SyncFence>)>,
+}
+
+/// Owns the pool of pixel buffers and processes the uploads on the thread that owns the
+/// context.
+///
+/// If workers still have staging buffers when the streamer is destroyed, the pixel buffers of
+/// these staging buffers are kept alive until all of them have been dropped, then destroyed at
+/// the end of the next frame.
+pub struct TextureStreamer<P> where P: PixelValue {
+    queue: StreamQueue<P>,
+    buffers: Vec<PooledBuffer<P>>,
+}
+
+impl<P> TextureStreamer<P> where P: PixelValue {
+    /// Builds a new streamer with `buffers_count` buffers that can each contain `capacity`
+    /// pixels.
+    ///
+    /// Returns an error if the backend doesn't support persistently-mapped pixel unpack
+    /// buffers.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `buffers_count` is 0.
+    pub fn new<F>(facade: &F, capacity: usize, buffers_count: usize)
+                  -> Result<TextureStreamer<P>, CreationError> where F: Facade
+    {
+        assert!(buffers_count >= 1);
+
+        let mut buffers = Vec::with_capacity(buffers_count);
+        let mut free = Vec::with_capacity(buffers_count);
+
+        for index in 0 .. buffers_count {
+            let mut buffer = match Buffer::empty_array(facade, BufferType::PixelUnpackBuffer,
+                                                       capacity, BufferMode::Persistent)
+            {
+                Ok(buffer) => buffer,
+                Err(BufferCreationError::BufferTypeNotSupported) => {
+                    return Err(CreationError::NotSupported);
+                },
+                Err(err) => return Err(err.into()),
+            };
+
+            // the workers need to write in the buffers without the context
+            if !buffer.is_persistent() {
+                return Err(CreationError::NotSupported);
+            }
+
+            // the pointer stays valid as long as the buffer exists
+            let data = buffer.map().as_mut_ptr();
+
+            buffers.push(PooledBuffer { buffer: buffer, pending: None });
+            free.push(Slot { index: index, data: data });
+        }
+
+        Ok(TextureStreamer {
+            queue: StreamQueue {
+                shared: Arc::new((Mutex::new(Shared {
+                    uploads: VecDeque::new(),
+                    free: free,
+                    outstanding: 0,
+                    closed: false,
+                }), Condvar::new())),
+                capacity: capacity,
+            },
+            buffers: buffers,
+        })
+    }
+
+    /// Returns a queue that can be sent to worker threads.
+    #[inline]
+    pub fn queue(&self) -> StreamQueue<P> {
+        self.queue.clone()
+    }
+
+    /// Returns the number of pixel buffers of the pool.
+    #[inline]
+    pub fn buffers_count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Starts the transfer of the submitted uploads, in the order of submission, and gives the
+    /// buffers whose transfer is finished back to the workers.
+    ///
+    /// Returns the number of uploads that have been started. If the `ResourceId` of an upload
+    /// doesn't correspond to any texture or if its rectangle is outside of the texture, the
+    /// upload is discarded and the other uploads are still processed. In that case an error
+    /// containing the number of started uploads is returned.
+    pub fn process(&mut self, textures: &Resources<Texture2d>) -> Result<usize, ProcessError> {
+        self.recycle_buffers();
+
+        let mut count = 0;
+        let mut errors = Vec::new();
+
+        loop {
+            // the lock is not kept while uploading so that the workers can continue to submit
+            let upload = match self.queue.shared.0.lock().unwrap().uploads.pop_front() {
+                Some(upload) => upload,
+                None => break,
+            };
+
+            match self.start_upload(upload, textures) {
+                Ok(()) => count += 1,
+                Err(err) => errors.push(err),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(count)
+        } else {
+            Err(ProcessError { started: count, errors: errors })
+        }
+    }
+
+    /// Gives the buffers whose transfer is finished back to the workers.
+    fn recycle_buffers(&mut self) {
+        let mut shared = self.queue.shared.0.lock().unwrap();
+
+        for pooled in self.buffers.iter_mut() {
+            let finished = match pooled.pending {
+                Some((_, Some(ref fence))) => fence.is_signaled(),
+                Some((_, None)) => true,
+                None => false,
+            };
+
+            if finished {
+                let (slot, _) = pooled.pending.take().unwrap();
+                shared.free.push(slot);
+            }
+        }
+
+        self.queue.shared.1.notify_all();
+    }
+
+    /// Starts the transfer of an upload to its texture.
+    fn start_upload(&mut self, upload: Upload<P>, textures: &Resources<Texture2d>)
+                    -> Result<(), StreamError>
+    {
+        let texture = match textures.get(upload.texture) {
+            Ok(texture) => texture,
+            Err(err) => {
+                self.discard_upload(upload);
+                return Err(err.into());
+            },
+        };
+
+        let level = texture.main_level();
+        let rect = upload.rect;
+
+        if rect.left as u64 + rect.width as u64 > level.get_width() as u64 ||
+           rect.bottom as u64 + rect.height as u64 > level.get_height().unwrap_or(1) as u64
+        {
+            self.discard_upload(upload);
+            return Err(StreamError::RectOutOfBounds);
+        }
+
+        let pooled = &mut self.buffers[upload.slot.index];
+
+        // dropping a write mapping of a persistent buffer flushes the range, which makes the
+        // pixels written by the worker visible to OpenGL
+        pooled.buffer.slice_mut(0 .. upload.len).unwrap().map_write();
+
+        level.raw_upload_from_pixel_buffer(pooled.buffer.slice(0 .. upload.len).unwrap(),
+                                           rect.left .. rect.left + rect.width,
+                                           rect.bottom .. rect.bottom + rect.height, 0 .. 1);
+
+        // the buffer must not be written again before the end of the transfer
+        let fence = match SyncFence::new(pooled.buffer.get_context()) {
+            Ok(fence) => Some(fence),
+            Err(_) => {
+                pooled.buffer.get_context().finish();
+                None
+            },
+        };
+
+        pooled.pending = Some((upload.slot, fence));
+        Ok(())
+    }
+
+    /// Gives the buffer of an upload that hasn't been transferred back to the workers.
+    fn discard_upload(&mut self, upload: Upload<P>) {
+        self.queue.shared.0.lock().unwrap().free.push(upload.slot);
+        self.queue.shared.1.notify_all();
+    }
+}
+
+impl<P> Drop for TextureStreamer<P> where P: PixelValue {
+    fn drop(&mut self) {
+        // the buffers that are neither in the pool nor in the queue nor being transferred are
+        // written by the workers
+        let mut in_use = vec![true; self.buffers.len()];
+
+        {
+            let mut shared = self.queue.shared.0.lock().unwrap();
+            shared.closed = true;
+            self.queue.shared.1.notify_all();
+
+            for slot in shared.free.iter().chain(shared.uploads.iter().map(|u| &u.slot)) {
+                in_use[slot.index] = false;
+            }
+
+            // the uploads that haven't been processed point to buffers that are going to be
+            // destroyed
+            shared.uploads.clear();
+            shared.free.clear();
+        }
+
+        for (index, pooled) in self.buffers.iter().enumerate() {
+            if pooled.pending.is_some() {
+                in_use[index] = false;
+            }
+        }
+
+        let detached = self.buffers.drain(..).enumerate()
+                                   .filter(|&(index, _)| in_use[index])
+                                   .map(|(_, pooled)| pooled.buffer)
+                                   .collect::<Vec<_>>();
+
+        if detached.is_empty() {
+            return;
+        }
+
+        // the staging buffers give their slot back when they are dropped
+        let context = detached[0].get_context().clone();
+        let shared = self.queue.shared.clone();
+        context.keep_alive_until(Box::new(detached),
+                                 Box::new(move || shared.0.lock().unwrap().outstanding == 0));
+    }
+}
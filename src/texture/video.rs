@@ -0,0 +1,337 @@
+/*!
+Imports the surfaces of hardware video decoders as textures, without copying them through the
+CPU.
+
+Two APIs are supported, both on Linux:
+
+ - VDPAU, through the `GL_NV_vdpau_interop` extension. A `VdpauInterop` is created from the
+   `VdpDevice` of the decoder, then output surfaces (usually the result of a `VdpVideoMixer`)
+   or video surfaces are registered. Before sampling the textures, the surfaces must be mapped
+   with `map`. The textures are only accessible through the returned `VdpauMapping`, and the
+   surface is unmapped and handed back to VDPAU when the mapping is dropped.
+
+ - VA-API, through EGL images and the `GL_OES_EGL_image` extension. Export the decoded surface
+   with `vaExportSurfaceHandle` and `VA_SURFACE_ATTRIB_MEM_TYPE_DRM_PRIME_2`, then create one
+   `EGLImage` per layer with `eglCreateImageKHR` and `EGL_LINUX_DMA_BUF_EXT`. Each image can then
+   be imported with `import_egl_image`. For example a NV12 surface gives a `R8` luma image and a
+   `GR88` chroma image, which are imported with the `U8` and `U8U8` formats.
+
+The textures contain YUV data for video surfaces, which must be converted to RGB in the shader
+that samples them.
+
+# Example
+
+```no_run
+use glium::texture::video::VdpauInterop;
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let device: u32 = 0; let get_proc_address: *const u8 = ::std::ptr::null();
+# let output_surface: u32 = 0;
+
+let interop = unsafe { VdpauInterop::new(&display, device, get_proc_address as *const _) }
+                  .unwrap();
+let mut surface = unsafe { interop.register_output_surface(output_surface, 1920, 1080) }
+                      .unwrap();
+
+// after the video mixer has rendered the frame in the output surface
+let mapping = surface.map();
+let texture = mapping.get_texture();
+// sample `texture` in a draw call
+```
+
+*/
+use backend::Facade;
+use context::{CommandContext, Context};
+use texture::{Dimensions, MipmapsOption, Texture2d, UncompressedFloatFormat};
+use CapabilitiesSource;
+use ContextExt;
+
+use gl;
+use libc;
+
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::rc::Rc;
+
+/// Error that can happen when importing a video surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CreationError {
+    /// The backend doesn't support the extension required by this kind of surface.
+    NotSupported,
+
+    /// The implementation refused to import the surface.
+    ImportFailed,
+}
+
+impl fmt::Display for CreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for CreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        use self::CreationError::*;
+        match *self {
+            NotSupported => "The backend doesn't support importing this kind of video surface",
+            ImportFailed => "The implementation refused to import the video surface",
+        }
+    }
+}
+
+/// Returns true if VA-API surfaces exported as EGL images can be imported.
+#[inline]
+pub fn is_egl_image_import_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_extensions().gl_oes_egl_image
+}
+
+/// Returns true if VDPAU surfaces can be imported.
+#[inline]
+pub fn is_vdpau_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_extensions().gl_nv_vdpau_interop
+}
+
+/// Builds a texture whose storage is an `EGLImage`, for example a layer of a VA-API surface.
+///
+/// `format` must be the format that corresponds to the image, and `width` and `height` its
+/// dimensions.
+///
+/// # Safety
+///
+/// `image` must be a valid `EGLImage` of the display of the context. It can be destroyed with
+/// `eglDestroyImageKHR` once the texture has been created. The content of the texture is
+/// undefined while the decoder writes to the surface.
+pub unsafe fn import_egl_image<F>(facade: &F, image: *const libc::c_void,
+                                  format: UncompressedFloatFormat, width: u32, height: u32)
+                                  -> Result<Texture2d, CreationError>
+                                  where F: Facade
+{
+    if !is_egl_image_import_supported(facade) {
+        return Err(CreationError::NotSupported);
+    }
+
+    let id = {
+        let mut ctxt = facade.get_context().make_current();
+
+        let id = gen_texture(&mut ctxt);
+        bind_texture(&mut ctxt, id);
+
+        // the image has no mipmaps
+        ctxt.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        ctxt.gl.EGLImageTargetTexture2DOES(gl::TEXTURE_2D, image);
+
+        if ::get_gl_error(&mut ctxt).is_some() {
+            delete_textures(&mut ctxt, &[id]);
+            return Err(CreationError::ImportFailed);
+        }
+
+        id
+    };
+
+    Ok(Texture2d::from_id(facade, format, id, true, MipmapsOption::NoMipmap,
+                          Dimensions::Texture2d { width: width, height: height }))
+}
+
+/// The VDPAU interop state of a context.
+///
+/// Only one `VdpauInterop` can exist at a time for a given context.
+pub struct VdpauInterop {
+    context: Rc<Context>,
+
+    // the interop state is bound to the context, which isn't thread-safe
+    marker: PhantomData<*mut u8>,
+}
+
+impl VdpauInterop {
+    /// Initializes the interop with the `VdpDevice` used by the decoder and the
+    /// `VdpGetProcAddress` function that has been returned when creating it.
+    ///
+    /// # Safety
+    ///
+    /// The device must be valid and must outlive the `VdpauInterop`. There must not be any other
+    /// `VdpauInterop` for this context.
+    pub unsafe fn new<F>(facade: &F, device: u32, get_proc_address: *const libc::c_void)
+                         -> Result<VdpauInterop, CreationError>
+                         where F: Facade
+    {
+        if !is_vdpau_supported(facade) {
+            return Err(CreationError::NotSupported);
+        }
+
+        let mut ctxt = facade.get_context().make_current();
+
+        // VDPAU handles are passed as pointers
+        ctxt.gl.VDPAUInitNV(device as usize as *const libc::c_void, get_proc_address);
+
+        if ::get_gl_error(&mut ctxt).is_some() {
+            return Err(CreationError::ImportFailed);
+        }
+
+        Ok(VdpauInterop {
+            context: facade.get_context().clone(),
+            marker: PhantomData,
+        })
+    }
+
+    /// Registers a `VdpOutputSurface`, which contains RGBA data.
+    ///
+    /// # Safety
+    ///
+    /// The surface must have been created with the device of the interop, must have the given
+    /// dimensions, and must outlive the returned object.
+    pub unsafe fn register_output_surface(&self, surface: u32, width: u32, height: u32)
+                                          -> Result<VdpauSurface, CreationError>
+    {
+        let formats = [(UncompressedFloatFormat::U8U8U8U8, width, height)];
+        self.register(surface, false, &formats)
+    }
+
+    /// Registers a `VdpVideoSurface` with 4:2:0 chroma subsampling.
+    ///
+    /// The surface is registered as four textures: the luma of the top field, the luma of the
+    /// bottom field, the chroma of the top field and the chroma of the bottom field. Each
+    /// field contains every other line of the frame. The luma textures have one component, and
+    /// the chroma textures have two components (U and V) at half the horizontal resolution.
+    ///
+    /// # Safety
+    ///
+    /// The surface must have been created with the device of the interop, must have the given
+    /// dimensions, and must outlive the returned object.
+    pub unsafe fn register_video_surface(&self, surface: u32, width: u32, height: u32)
+                                         -> Result<VdpauSurface, CreationError>
+    {
+        let luma = (UncompressedFloatFormat::U8, width, height / 2);
+        let chroma = (UncompressedFloatFormat::U8U8, width / 2, height / 4);
+        self.register(surface, true, &[luma, luma, chroma, chroma])
+    }
+
+    unsafe fn register(&self, surface: u32, video: bool,
+                       textures: &[(UncompressedFloatFormat, u32, u32)])
+                       -> Result<VdpauSurface, CreationError>
+    {
+        let (ids, handle) = {
+            let mut ctxt = self.context.make_current();
+
+            let ids = textures.iter().map(|_| gen_texture(&mut ctxt)).collect::<Vec<_>>();
+            let surface = surface as usize as *const libc::c_void;
+
+            let handle = if video {
+                ctxt.gl.VDPAURegisterVideoSurfaceNV(surface, gl::TEXTURE_2D,
+                                                    ids.len() as gl::types::GLsizei,
+                                                    ids.as_ptr())
+            } else {
+                ctxt.gl.VDPAURegisterOutputSurfaceNV(surface, gl::TEXTURE_2D,
+                                                     ids.len() as gl::types::GLsizei,
+                                                     ids.as_ptr())
+            };
+
+            if handle == 0 {
+                ::get_gl_error(&mut ctxt);
+                delete_textures(&mut ctxt, &ids);
+                return Err(CreationError::ImportFailed);
+            }
+
+            ctxt.gl.VDPAUSurfaceAccessNV(handle, gl::READ_ONLY);
+            (ids, handle)
+        };
+
+        let textures = ids.iter().zip(textures.iter()).map(|(&id, &(format, w, h))| {
+            Texture2d::from_id(&self.context, format, id, true, MipmapsOption::NoMipmap,
+                               Dimensions::Texture2d { width: w, height: h })
+        }).collect();
+
+        Ok(VdpauSurface {
+            interop: self,
+            handle: handle,
+            textures: textures,
+        })
+    }
+}
+
+impl Drop for VdpauInterop {
+    #[inline]
+    fn drop(&mut self) {
+        let ctxt = self.context.make_current();
+        unsafe { ctxt.gl.VDPAUFiniNV(); }
+    }
+}
+
+/// A VDPAU surface registered in a `VdpauInterop`.
+pub struct VdpauSurface<'a> {
+    interop: &'a VdpauInterop,
+    handle: gl::types::GLvdpauSurfaceNV,
+    textures: Vec<Texture2d>,
+}
+
+impl<'a> VdpauSurface<'a> {
+    /// Maps the surface so that its textures can be used. The surface must not be used by
+    /// VDPAU until the mapping is dropped.
+    pub fn map(&mut self) -> VdpauMapping {
+        let ctxt = self.interop.context.make_current();
+        unsafe { ctxt.gl.VDPAUMapSurfacesNV(1, &self.handle); }
+        VdpauMapping { surface: self }
+    }
+}
+
+impl<'a> Drop for VdpauSurface<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        // the textures are destroyed after the surface has been unregistered
+        let ctxt = self.interop.context.make_current();
+        unsafe { ctxt.gl.VDPAUUnregisterSurfaceNV(self.handle); }
+    }
+}
+
+/// A mapped VDPAU surface. The surface is unmapped when this object is dropped.
+pub struct VdpauMapping<'s, 'a: 's> {
+    surface: &'s mut VdpauSurface<'a>,
+}
+
+impl<'s, 'a> VdpauMapping<'s, 'a> {
+    /// Returns the texture of an output surface, or the luma of the top field of a video
+    /// surface.
+    #[inline]
+    pub fn get_texture(&self) -> &Texture2d {
+        &self.surface.textures[0]
+    }
+
+    /// Returns the textures of the surface. See `VdpauInterop::register_video_surface` for the
+    /// list of textures of a video surface.
+    #[inline]
+    pub fn get_textures(&self) -> &[Texture2d] {
+        &self.surface.textures
+    }
+}
+
+impl<'s, 'a> Drop for VdpauMapping<'s, 'a> {
+    #[inline]
+    fn drop(&mut self) {
+        let ctxt = self.surface.interop.context.make_current();
+        unsafe { ctxt.gl.VDPAUUnmapSurfacesNV(1, &self.surface.handle); }
+    }
+}
+
+unsafe fn gen_texture(ctxt: &mut CommandContext) -> gl::types::GLuint {
+    let mut id = mem::uninitialized();
+    ctxt.gl.GenTextures(1, &mut id);
+    id
+}
+
+unsafe fn bind_texture(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    ctxt.gl.BindTexture(gl::TEXTURE_2D, id);
+    let act = ctxt.state.active_texture as usize;
+    ctxt.state.texture_units[act].texture = id;
+}
+
+unsafe fn delete_textures(ctxt: &mut CommandContext, ids: &[gl::types::GLuint]) {
+    for tex_unit in ctxt.state.texture_units.iter_mut() {
+        if ids.contains(&tex_unit.texture) {
+            tex_unit.texture = 0;
+        }
+    }
+
+    ctxt.gl.DeleteTextures(ids.len() as gl::types::GLsizei, ids.as_ptr());
+}
@@ -0,0 +1,211 @@
+#![cfg(feature = "gl_trace")]
+/*!
+
+Records the OpenGL calls that glium makes, to attach them to bug reports against drivers.
+
+When the `gl_trace` feature is enabled, the OpenGL bindings of glium record each call with its
+arguments and a timestamp before executing it. glium also records a marker at the start of
+its high-level operations (draws, clears, blits, compute dispatches and buffer swaps), so that
+the calls can be matched with the code that issued them.
+
+The trace can be kept in memory in a ring buffer that only keeps the most recent entries, or
+written to a file as it is recorded. Recording is done per thread, and must be started on the
+thread that owns the context. Nothing is recorded until a trace is started, and the cost of a
+call is then only a thread-local lookup.
+
+# Features
+
+Only available if the 'gl_trace' feature is enabled.
+
+# Example
+
+```no_run
+use glium::trace;
+
+trace::start_ring_buffer(4096);
+trace::marker("loading the level");
+
+// ...
+
+for entry in trace::stop() {
+    println!("{}", entry);
+}
+```
+
+*/
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// An entry of a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Time elapsed between the start of the trace and the entry.
+    pub timestamp: Duration,
+
+    /// What has been recorded.
+    pub content: TraceContent,
+}
+
+/// What a `TraceEntry` contains.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceContent {
+    /// An OpenGL function has been called.
+    Call {
+        /// Name of the function, for example `glDrawArrays`.
+        function: &'static str,
+
+        /// The arguments, separated by commas.
+        arguments: String,
+    },
+
+    /// A marker recorded by glium at the start of an operation, or by the user with `marker`.
+    Marker(String),
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let micros = self.timestamp.as_secs() * 1000000 +
+                     self.timestamp.subsec_nanos() as u64 / 1000;
+
+        match self.content {
+            TraceContent::Call { function, ref arguments } => {
+                write!(fmt, "{:>12}us {}({})", micros, function, arguments)
+            },
+            TraceContent::Marker(ref marker) => write!(fmt, "{:>12}us -- {}", micros, marker),
+        }
+    }
+}
+
+/// Where the entries go.
+enum Sink {
+    RingBuffer { entries: VecDeque<TraceEntry>, capacity: usize },
+    File(BufWriter<File>),
+}
+
+struct Tracer {
+    start: Instant,
+    sink: Sink,
+}
+
+thread_local!(static TRACER: RefCell<Option<Tracer>> = RefCell::new(None));
+
+/// Starts recording in memory. Only the `capacity` most recent entries are kept.
+///
+/// Any trace that was being recorded on this thread is stopped and discarded.
+///
+/// # Panic
+///
+/// Panics if `capacity` is 0.
+pub fn start_ring_buffer(capacity: usize) {
+    assert!(capacity >= 1);
+
+    start(Sink::RingBuffer {
+        entries: VecDeque::with_capacity(capacity),
+        capacity: capacity,
+    });
+}
+
+/// Starts recording to a file, which is created or truncated. Each entry is written on its own
+/// line.
+///
+/// Any trace that was being recorded on this thread is stopped and discarded.
+pub fn start_file<P>(path: P) -> io::Result<()> where P: AsRef<Path> {
+    let file = try!(File::create(path));
+    start(Sink::File(BufWriter::new(file)));
+    Ok(())
+}
+
+/// Stops recording on this thread.
+///
+/// Returns the entries of the ring buffer, from the oldest to the most recent. If the trace
+/// was written to a file, the file is flushed and the returned list is empty.
+pub fn stop() -> Vec<TraceEntry> {
+    let tracer = TRACER.with(|tracer| tracer.borrow_mut().take());
+
+    match tracer.map(|t| t.sink) {
+        Some(Sink::RingBuffer { entries, .. }) => entries.into_iter().collect(),
+        Some(Sink::File(mut file)) => {
+            file.flush().ok();
+            Vec::new()
+        },
+        None => Vec::new(),
+    }
+}
+
+/// Returns true if a trace is being recorded on this thread.
+#[inline]
+pub fn is_enabled() -> bool {
+    TRACER.with(|tracer| tracer.borrow().is_some())
+}
+
+/// Returns a copy of the entries of the ring buffer without stopping the trace.
+pub fn get_entries() -> Vec<TraceEntry> {
+    TRACER.with(|tracer| {
+        match *tracer.borrow() {
+            Some(Tracer { sink: Sink::RingBuffer { ref entries, .. }, .. }) => {
+                entries.iter().cloned().collect()
+            },
+            _ => Vec::new(),
+        }
+    })
+}
+
+/// Records a marker, for example to delimit the parts of a frame.
+#[inline]
+pub fn marker<S>(marker: S) where S: Into<String> {
+    record(TraceContent::Marker(marker.into()));
+}
+
+/// Records a call to an OpenGL function. Called by the bindings.
+#[doc(hidden)]
+#[inline]
+pub fn record_call(function: &'static str, arguments: String) {
+    record(TraceContent::Call { function: function, arguments: arguments });
+}
+
+fn start(sink: Sink) {
+    let previous = TRACER.with(|tracer| {
+        mem::replace(&mut *tracer.borrow_mut(), Some(Tracer {
+            start: Instant::now(),
+            sink: sink,
+        }))
+    });
+
+    if let Some(Tracer { sink: Sink::File(mut file), .. }) = previous {
+        file.flush().ok();
+    }
+}
+
+fn record(content: TraceContent) {
+    TRACER.with(|tracer| {
+        let mut tracer = tracer.borrow_mut();
+        let tracer = match *tracer {
+            Some(ref mut tracer) => tracer,
+            None => return,
+        };
+
+        let entry = TraceEntry {
+            timestamp: tracer.start.elapsed(),
+            content: content,
+        };
+
+        match tracer.sink {
+            Sink::RingBuffer { ref mut entries, capacity } => {
+                if entries.len() >= capacity {
+                    entries.pop_front();
+                }
+                entries.push_back(entry);
+            },
+            Sink::File(ref mut file) => {
+                // the trace is a debugging aid, so a failed write isn't reported
+                writeln!(file, "{}", entry).ok();
+            },
+        }
+    });
+}
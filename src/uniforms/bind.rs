@@ -36,7 +36,11 @@ impl<U> UniformsExt for U where U: Uniforms {
                             -> Result<(), DrawError>
                             where P: ProgramExt
     {
-        let mut texture_bind_points = Bitsfield::new();
+        let mut texture_bind_points = TextureBindPoints {
+            used: Bitsfield::new(),
+            pending: if is_multi_bind_supported(ctxt) { Some(Vec::new()) } else { None },
+            samplers_changed: false,
+        };
         let mut uniform_buffer_bind_points = Bitsfield::new();
         let mut shared_storage_buffer_bind_points = Bitsfield::new();
 
@@ -51,6 +55,8 @@ impl<U> UniformsExt for U where U: Uniforms {
                     visiting_result = Err(DrawError::UniformTypeMismatch {
                         name: name.to_owned(),
                         expected: uniform.ty,
+                        obtained: value.get_type(),
+                        program: program.get_label(),
                     });
                     return;
                 }
@@ -98,6 +104,10 @@ impl<U> UniformsExt for U where U: Uniforms {
             }
         });
 
+        // the texture units visited before an error have already been written in the state
+        // cache, so they must be sent to OpenGL even if we don't draw
+        flush_texture_bindings(ctxt, &texture_bind_points);
+
         visiting_result
     }
 }
@@ -116,6 +126,7 @@ fn bind_uniform_block<'a, P>(ctxt: &mut context::CommandContext, value: &Uniform
                     return Err(DrawError::UniformBlockLayoutMismatch {
                         name: name.to_owned(),
                         err: e,
+                        program: program.get_label(),
                     });
                 }
             }
@@ -133,7 +144,10 @@ fn bind_uniform_block<'a, P>(ctxt: &mut context::CommandContext, value: &Uniform
             Ok(fence)
         },
         _ => {
-            Err(DrawError::UniformValueToBlock { name: name.to_owned() })
+            Err(DrawError::UniformValueToBlock {
+                name: name.to_owned(),
+                program: program.get_label(),
+            })
         }
     }
 }
@@ -152,6 +166,7 @@ fn bind_shared_storage_block<'a, P>(ctxt: &mut context::CommandContext, value: &
                     return Err(DrawError::UniformBlockLayoutMismatch {
                         name: name.to_owned(),
                         err: e,
+                        program: program.get_label(),
                     });
                 }
             }
@@ -169,14 +184,17 @@ fn bind_shared_storage_block<'a, P>(ctxt: &mut context::CommandContext, value: &
             Ok(fence)
         },
         _ => {
-            Err(DrawError::UniformValueToBlock { name: name.to_owned() })
+            Err(DrawError::UniformValueToBlock {
+                name: name.to_owned(),
+                program: program.get_label(),
+            })
         }
     }
 }
 
 fn bind_uniform<P>(ctxt: &mut context::CommandContext,
                    value: &UniformValue, program: &P, location: gl::types::GLint,
-                   texture_bind_points: &mut Bitsfield, name: &str)
+                   texture_bind_points: &mut TextureBindPoints, name: &str)
                    -> Result<(), DrawError> where P: ProgramExt
 {
     assert!(location >= 0);
@@ -185,6 +203,7 @@ fn bind_uniform<P>(ctxt: &mut context::CommandContext,
         UniformValue::Block(_, _) => {
             Err(DrawError::UniformBufferToValue {
                 name: name.to_owned(),
+                program: program.get_label(),
             })
         },
         UniformValue::Bool(val) => {
@@ -478,10 +497,68 @@ fn bind_uniform<P>(ctxt: &mut context::CommandContext,
     }
 }
 
+/// Texture units used while binding the uniforms of a draw call.
+struct TextureBindPoints {
+    /// Texture units that are used by the current draw call.
+    used: Bitsfield,
+
+    /// Texture units whose content has changed in the state cache but not in OpenGL yet.
+    /// `None` if `glBindTextures` is not supported, in which case the units are updated
+    /// immediately.
+    pending: Option<Vec<u16>>,
+
+    /// True if the sampler of one of the pending units has changed.
+    samplers_changed: bool,
+}
+
+/// Returns true if `glBindTextures` and `glBindSamplers` are supported.
+#[inline]
+fn is_multi_bind_supported(ctxt: &context::CommandContext) -> bool {
+    ctxt.version >= &Version(Api::Gl, 4, 4) || ctxt.extensions.gl_arb_multi_bind
+}
+
+/// Sends the texture units that have been modified by `bind_texture_uniform` to OpenGL.
+///
+/// All the units between the lowest and the highest modified unit are binded with a single call
+/// to `glBindTextures` (and `glBindSamplers` if necessary). The content of the units in between
+/// is taken from the state cache, so it stays the same. Units whose content didn't change since
+/// the previous draw call aren't modified, which means that consecutive draws that use the same
+/// textures don't call OpenGL at all.
+fn flush_texture_bindings(ctxt: &mut context::CommandContext, bind_points: &TextureBindPoints) {
+    let pending = match bind_points.pending {
+        Some(ref pending) if !pending.is_empty() => pending,
+        _ => return
+    };
+
+    let first = *pending.iter().min().unwrap() as usize;
+    let last = *pending.iter().max().unwrap() as usize;
+
+    let textures = ctxt.state.texture_units[first .. last + 1].iter()
+                                                              .map(|unit| unit.texture)
+                                                              .collect::<Vec<_>>();
+
+    unsafe {
+        ctxt.gl.BindTextures(first as gl::types::GLuint, textures.len() as gl::types::GLsizei,
+                             textures.as_ptr());
+    }
+    ctxt.record_statistics(|s| s.texture_binds += pending.len() as u64);
+
+    if bind_points.samplers_changed {
+        let samplers = ctxt.state.texture_units[first .. last + 1].iter()
+                                                                  .map(|unit| unit.sampler)
+                                                                  .collect::<Vec<_>>();
+
+        unsafe {
+            ctxt.gl.BindSamplers(first as gl::types::GLuint, samplers.len() as gl::types::GLsizei,
+                                 samplers.as_ptr());
+        }
+    }
+}
+
 fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
                               texture: &T, sampler: Option<SamplerBehavior>,
                               location: gl::types::GLint, program: &P,
-                              texture_bind_points: &mut Bitsfield)
+                              texture_bind_points: &mut TextureBindPoints)
                               -> Result<(), DrawError> where P: ProgramExt, T: TextureExt
 {
     let sampler = if let Some(sampler) = sampler {
@@ -497,8 +574,8 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
         ctxt.state.texture_units
             .iter().enumerate()
             .find(|&(unit, content)| {
-                content.texture == texture.get_texture_id() && (content.sampler == sampler ||
-                                                        !texture_bind_points.is_used(unit as u16))
+                content.texture == texture.get_texture_id() &&
+                    (content.sampler == sampler || !texture_bind_points.used.is_used(unit as u16))
             })
             .map(|(unit, _)| unit as u16)
             .or_else(|| {
@@ -511,11 +588,12 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
                 }
             })
             .unwrap_or_else(|| {
-                texture_bind_points.get_unused().expect("Not enough texture units available")
+                texture_bind_points.used.get_unused()
+                                   .expect("Not enough texture units available")
             });
     assert!((texture_unit as gl::types::GLint) <
             ctxt.capabilities.max_combined_texture_image_units);
-    texture_bind_points.set_used(texture_unit);
+    texture_bind_points.used.set_used(texture_unit);
 
     // updating the program to use the right unit
     program.set_uniform(ctxt, location,
@@ -528,6 +606,24 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
         }
     }
 
+    // with multi-bind, the state is only updated here and sent to OpenGL all at once by
+    // `flush_texture_bindings`
+    if let Some(ref mut pending) = texture_bind_points.pending {
+        let unit_state = &mut ctxt.state.texture_units[texture_unit as usize];
+
+        if unit_state.texture != texture.get_texture_id() || unit_state.sampler != sampler {
+            if unit_state.sampler != sampler {
+                texture_bind_points.samplers_changed = true;
+            }
+
+            unit_state.texture = texture.get_texture_id();
+            unit_state.sampler = sampler;
+            pending.push(texture_unit);
+        }
+
+        return Ok(());
+    }
+
     // TODO: do better
     if ctxt.state.texture_units[texture_unit as usize].texture != texture.get_texture_id() ||
        ctxt.state.texture_units[texture_unit as usize].sampler != sampler
@@ -0,0 +1,203 @@
+use std::mem;
+
+use program::BlockLayout;
+use uniforms::{LayoutMismatchError, UniformBlock, UniformType};
+
+/// Rules that determine the offsets of the members of a uniform block or of a shader storage
+/// block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutRules {
+    /// The `layout(std140)` rules, which can be used for both uniform blocks and shader storage
+    /// blocks.
+    ///
+    /// The alignment of arrays and structs, and the stride of arrays, are rounded up to the
+    /// size of a `vec4`.
+    Std140,
+
+    /// The `layout(std430)` rules, which can only be used for shader storage blocks.
+    ///
+    /// They are the same as `Std140`, except that arrays and structs are not padded to the size
+    /// of a `vec4`. For example the stride of a `float` array is 4 bytes instead of 16.
+    Std430,
+}
+
+impl LayoutRules {
+    /// Returns the layout that a block with the same members as `layout` has when its offsets
+    /// follow these rules, and if it starts at `base_offset`.
+    ///
+    /// The offsets contained in `layout` are ignored. This can be used to build the layout
+    /// that a program expects from the layout of a Rust type returned by
+    /// `UniformBlock::build_layout`.
+    pub fn apply(&self, layout: &BlockLayout, base_offset: usize) -> BlockLayout {
+        self.apply_impl(layout, base_offset).0
+    }
+
+    /// Returns the alignment in bytes of an element with this layout.
+    pub fn alignment(&self, layout: &BlockLayout) -> usize {
+        match layout {
+            &BlockLayout::BasicType { ty, .. } => self.basic_type_alignment(ty),
+
+            &BlockLayout::Array { ref content, .. } |
+            &BlockLayout::DynamicSizedArray { ref content } => {
+                self.round_alignment(self.alignment(content))
+            },
+
+            &BlockLayout::Struct { ref members } => {
+                let max = members.iter().map(|&(_, ref m)| self.alignment(m)).max().unwrap_or(1);
+                self.round_alignment(max)
+            },
+        }
+    }
+
+    /// Returns the number of bytes between two elements of an array whose content has this
+    /// layout.
+    pub fn array_stride(&self, content: &BlockLayout) -> usize {
+        let (_, size) = self.apply_impl(content, 0);
+        round_up(size, self.round_alignment(self.alignment(content)))
+    }
+
+    /// Returns the layout and the size in bytes.
+    fn apply_impl(&self, layout: &BlockLayout, offset: usize) -> (BlockLayout, usize) {
+        match layout {
+            &BlockLayout::BasicType { ty, .. } => {
+                let layout = BlockLayout::BasicType { ty: ty, offset_in_buffer: offset };
+                (layout, self.basic_type_size(ty))
+            },
+
+            &BlockLayout::Array { ref content, length } => {
+                let stride = self.array_stride(content);
+                let layout = BlockLayout::Array {
+                    content: Box::new(self.apply_impl(content, offset).0),
+                    length: length,
+                };
+                (layout, stride * length)
+            },
+
+            &BlockLayout::DynamicSizedArray { ref content } => {
+                let layout = BlockLayout::DynamicSizedArray {
+                    content: Box::new(self.apply_impl(content, offset).0),
+                };
+                (layout, 0)
+            },
+
+            &BlockLayout::Struct { ref members } => {
+                let mut cursor = offset;
+
+                let members = members.iter().map(|&(ref name, ref member)| {
+                    cursor = round_up(cursor, self.alignment(member));
+                    let (member, size) = self.apply_impl(member, cursor);
+                    cursor += size;
+                    (name.clone(), member)
+                }).collect();
+
+                let size = round_up(cursor - offset, self.alignment(layout));
+                (BlockLayout::Struct { members: members }, size)
+            },
+        }
+    }
+
+    /// Applies the padding of arrays and structs.
+    fn round_alignment(&self, alignment: usize) -> usize {
+        match *self {
+            LayoutRules::Std140 => round_up(alignment, 16),
+            LayoutRules::Std430 => alignment,
+        }
+    }
+
+    fn basic_type_alignment(&self, ty: UniformType) -> usize {
+        let (component, rows, columns) = basic_type_shape(ty);
+
+        // a vec3 is aligned like a vec4
+        let column_alignment = component * if rows == 3 { 4 } else { rows };
+
+        if columns == 1 {
+            column_alignment
+        } else {
+            // matrices are stored like arrays of column vectors
+            self.round_alignment(column_alignment)
+        }
+    }
+
+    fn basic_type_size(&self, ty: UniformType) -> usize {
+        let (component, rows, columns) = basic_type_shape(ty);
+
+        if columns == 1 {
+            component * rows
+        } else {
+            self.basic_type_alignment(ty) * columns
+        }
+    }
+}
+
+/// Checks whether the layout of `T` in memory matches the layout of the same members with the
+/// given rules.
+///
+/// This can be used to check a type before any program is built, for example in a test.
+pub fn check_layout<T: ?Sized>(rules: LayoutRules) -> Result<(), LayoutMismatchError>
+                               where T: UniformBlock
+{
+    let expected = rules.apply(&<T as UniformBlock>::build_layout(0), 0);
+    <T as UniformBlock>::matches(&expected, 0)
+}
+
+/// Same as `check_layout`, but also checks that the size of `T` is equal to the stride of an
+/// array of `T` with the given rules. Use this function for the content of arrays, for example
+/// when `T` is the type of the elements of a `buffer Particles { Particle particles[]; }` block.
+pub fn check_array_layout<T>(rules: LayoutRules) -> Result<(), LayoutMismatchError>
+                             where T: UniformBlock
+{
+    try!(check_layout::<T>(rules));
+
+    let stride = rules.array_stride(&<T as UniformBlock>::build_layout(0));
+    if stride != mem::size_of::<T>() {
+        return Err(LayoutMismatchError::OffsetMismatch {
+            expected: stride,
+            obtained: mem::size_of::<T>(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the size in bytes of a component, the number of rows and the number of columns.
+fn basic_type_shape(ty: UniformType) -> (usize, usize, usize) {
+    match ty {
+        UniformType::Float | UniformType::Int | UniformType::UnsignedInt |
+        UniformType::Bool => (4, 1, 1),
+        UniformType::FloatVec2 | UniformType::IntVec2 | UniformType::UnsignedIntVec2 |
+        UniformType::BoolVec2 => (4, 2, 1),
+        UniformType::FloatVec3 | UniformType::IntVec3 | UniformType::UnsignedIntVec3 |
+        UniformType::BoolVec3 => (4, 3, 1),
+        UniformType::FloatVec4 | UniformType::IntVec4 | UniformType::UnsignedIntVec4 |
+        UniformType::BoolVec4 => (4, 4, 1),
+        UniformType::Double => (8, 1, 1),
+        UniformType::DoubleVec2 => (8, 2, 1),
+        UniformType::DoubleVec3 => (8, 3, 1),
+        UniformType::DoubleVec4 => (8, 4, 1),
+        UniformType::FloatMat2 => (4, 2, 2),
+        UniformType::FloatMat3 => (4, 3, 3),
+        UniformType::FloatMat4 => (4, 4, 4),
+        UniformType::FloatMat2x3 => (4, 3, 2),
+        UniformType::FloatMat2x4 => (4, 4, 2),
+        UniformType::FloatMat3x2 => (4, 2, 3),
+        UniformType::FloatMat3x4 => (4, 4, 3),
+        UniformType::FloatMat4x2 => (4, 2, 4),
+        UniformType::FloatMat4x3 => (4, 3, 4),
+        UniformType::DoubleMat2 => (8, 2, 2),
+        UniformType::DoubleMat3 => (8, 3, 3),
+        UniformType::DoubleMat4 => (8, 4, 4),
+        UniformType::DoubleMat2x3 => (8, 3, 2),
+        UniformType::DoubleMat2x4 => (8, 4, 2),
+        UniformType::DoubleMat3x2 => (8, 2, 3),
+        UniformType::DoubleMat3x4 => (8, 4, 3),
+        UniformType::DoubleMat4x2 => (8, 2, 4),
+        UniformType::DoubleMat4x3 => (8, 3, 4),
+        // samplers and images can only be in a block as bindless 64 bits handles
+        _ => (8, 1, 1),
+    }
+}
+
+#[inline]
+fn round_up(value: usize, alignment: usize) -> usize {
+    (value + alignment - 1) / alignment * alignment
+}
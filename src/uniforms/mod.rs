@@ -88,13 +88,51 @@ let uniforms = uniform! {
 # }
 ```
 
+## Layout rules
+
+The offsets of the members of a block depend on the layout declared in GLSL. Uniform blocks
+usually use `layout(std140)`, while shader storage blocks can also use `layout(std430)`, which
+doesn't pad arrays and structs to the size of a `vec4`. When a buffer is bound, glium checks
+that the layout of its content matches the layout reported by the program.
+
+The `check_layout` and `check_array_layout` functions check a type against `LayoutRules`
+without building any program, which is useful to catch padding mistakes early.
+
+```no_run
+#[macro_use]
+extern crate glium;
+# fn main() {
+use glium::uniforms::{check_array_layout, LayoutRules};
+
+#[derive(Copy, Clone)]
+struct Particle {
+    position: [f32; 3],
+    mass: f32,
+    velocity: [f32; 2],
+    _padding: [f32; 2],
+}
+
+implement_uniform_block!(Particle, position, mass, velocity);
+
+// matches `struct Particle { vec3 position; float mass; vec2 velocity; }` in an std140 or
+// std430 block
+check_array_layout::<Particle>(LayoutRules::Std430).unwrap();
+check_array_layout::<Particle>(LayoutRules::Std140).unwrap();
+# }
+```
+
 */
 pub use self::buffer::UniformBuffer;
+pub use self::layout::{LayoutRules, check_layout, check_array_layout};
 pub use self::sampler::{SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter};
-pub use self::sampler::{Sampler, SamplerBehavior};
+pub use self::sampler::{Sampler, SamplerBehavior, DepthTextureComparison};
+pub use self::sampler::SamplerReductionMode;
 pub use self::uniforms::{EmptyUniforms, UniformsStorage};
 pub use self::value::{UniformValue, UniformType};
 
+use std::error::Error;
+use std::fmt;
+
 use buffer::Content as BufferContent;
 use buffer::Buffer;
 use program;
@@ -102,6 +140,7 @@ use program::BlockLayout;
 
 mod bind;
 mod buffer;
+mod layout;
 mod sampler;
 mod uniforms;
 mod value;
@@ -158,6 +197,54 @@ pub enum LayoutMismatchError {
     },
 }
 
+impl fmt::Display for LayoutMismatchError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &LayoutMismatchError::TypeMismatch { ref expected, ref obtained } => {
+                write!(fmt, "Type mismatch: the shader expects {:?}, got {:?}", expected, obtained)
+            },
+            &LayoutMismatchError::LayoutMismatch { ref expected, ref obtained } => {
+                write!(fmt, "Layout mismatch: the shader expects {:?}, got {:?}", expected,
+                       obtained)
+            },
+            &LayoutMismatchError::OffsetMismatch { expected, obtained } => {
+                write!(fmt, "Offset mismatch: the shader expects offset {}, got {}", expected,
+                       obtained)
+            },
+            &LayoutMismatchError::MemberMismatch { ref member, ref err } => {
+                write!(fmt, "Mismatch in the member `{}`: {}", member, err)
+            },
+            &LayoutMismatchError::MissingField { ref name } => {
+                write!(fmt, "The field `{}` is missing", name)
+            },
+        }
+    }
+}
+
+impl Error for LayoutMismatchError {
+    fn description(&self) -> &str {
+        match self {
+            &LayoutMismatchError::TypeMismatch { .. } => "There is a mismatch in the type of one \
+                                                          element",
+            &LayoutMismatchError::LayoutMismatch { .. } => "The expected layout is totally \
+                                                            different from what we have",
+            &LayoutMismatchError::OffsetMismatch { .. } => "The type of data is good, but there \
+                                                            is a misalignment",
+            &LayoutMismatchError::MemberMismatch { .. } => "There is a mismatch in a submember \
+                                                            of this layout",
+            &LayoutMismatchError::MissingField { .. } => "A field is missing in either the \
+                                                          expected of the input data layout",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &LayoutMismatchError::MemberMismatch { ref err, .. } => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
 /// Value that can be used as the value of a uniform.
 ///
 /// This includes buffers and textures for example.
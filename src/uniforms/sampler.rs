@@ -1,6 +1,10 @@
 use ToGlEnum;
+use CapabilitiesSource;
 use gl;
 
+use version::Api;
+use version::Version;
+
 /// Function to use for out-of-bounds samples.
 ///
 /// This is how GL must handle samples that are outside the texture.
@@ -16,7 +20,30 @@ pub enum SamplerWrapFunction {
     Clamp,
 
     /// Same as Mirror, but only for one repetition,
-    MirrorClamp
+    MirrorClamp,
+
+    /// Samples outside of the texture return the border color, which is transparent black.
+    ///
+    /// Using this function triggers a `SamplerWrapFunctionNotSupported` error when drawing
+    /// if the backend doesn't support it, which is the case of OpenGL ES before version 3.2
+    /// without the `GL_EXT_texture_border_clamp` or `GL_OES_texture_border_clamp` extension.
+    ClampToBorder,
+}
+
+impl SamplerWrapFunction {
+    /// Returns true if the backend supports this wrap function.
+    #[inline]
+    pub fn is_supported<C>(&self, context: &C) -> bool where C: CapabilitiesSource {
+        match *self {
+            SamplerWrapFunction::ClampToBorder => {
+                context.get_version().0 == Api::Gl ||
+                context.get_version() >= &Version(Api::GlEs, 3, 2) ||
+                context.get_extensions().gl_ext_texture_border_clamp ||
+                context.get_extensions().gl_oes_texture_border_clamp
+            },
+            _ => true
+        }
+    }
 }
 
 impl ToGlEnum for SamplerWrapFunction {
@@ -27,6 +54,7 @@ impl ToGlEnum for SamplerWrapFunction {
             SamplerWrapFunction::Mirror => gl::MIRRORED_REPEAT,
             SamplerWrapFunction::Clamp => gl::CLAMP_TO_EDGE,
             SamplerWrapFunction::MirrorClamp => gl::MIRROR_CLAMP_TO_EDGE,
+            SamplerWrapFunction::ClampToBorder => gl::CLAMP_TO_BORDER,
         }
     }
 }
@@ -91,6 +119,92 @@ impl ToGlEnum for MinifySamplerFilter {
     }
 }
 
+/// The function that the GPU will use to compare the depth reference value passed by the shader
+/// with the texel, when sampling a depth texture through a shadow sampler (for example
+/// `sampler2DShadow` in GLSL).
+///
+/// The result of the sampling is `1.0` if the comparison passes and `0.0` otherwise. With linear
+/// filtering, the results of the nearby texels are merged.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DepthTextureComparison {
+    /// The comparison never passes.
+    Never,
+
+    /// Passes if the reference value is less than the texel.
+    Less,
+
+    /// Passes if the reference value is equal to the texel.
+    Equal,
+
+    /// Passes if the reference value is less than or equal to the texel.
+    LessOrEqual,
+
+    /// Passes if the reference value is greater than the texel.
+    Greater,
+
+    /// Passes if the reference value is not equal to the texel.
+    NotEqual,
+
+    /// Passes if the reference value is greater than or equal to the texel.
+    GreaterOrEqual,
+
+    /// The comparison always passes.
+    Always,
+}
+
+impl ToGlEnum for DepthTextureComparison {
+    #[inline]
+    fn to_glenum(&self) -> gl::types::GLenum {
+        match *self {
+            DepthTextureComparison::Never => gl::NEVER,
+            DepthTextureComparison::Less => gl::LESS,
+            DepthTextureComparison::Equal => gl::EQUAL,
+            DepthTextureComparison::LessOrEqual => gl::LEQUAL,
+            DepthTextureComparison::Greater => gl::GREATER,
+            DepthTextureComparison::NotEqual => gl::NOTEQUAL,
+            DepthTextureComparison::GreaterOrEqual => gl::GEQUAL,
+            DepthTextureComparison::Always => gl::ALWAYS,
+        }
+    }
+}
+
+/// How the values of the texels that are loaded by a linear filter are merged together.
+///
+/// The `Min` and `Max` modes allow for example to build a hierarchical depth buffer by sampling
+/// each mipmap from the previous one, as a single linear sample returns the minimum or maximum
+/// depth of the four texels.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum SamplerReductionMode {
+    /// The texels are merged by computing their weighted average. This is the usual filtering.
+    WeightedAverage,
+
+    /// The minimum value of each component of the texels is returned.
+    Min,
+
+    /// The maximum value of each component of the texels is returned.
+    Max,
+}
+
+impl SamplerReductionMode {
+    /// Returns true if the backend supports the `Min` and `Max` reduction modes.
+    #[inline]
+    pub fn is_minmax_supported<C>(context: &C) -> bool where C: CapabilitiesSource {
+        context.get_extensions().gl_arb_texture_filter_minmax ||
+        context.get_extensions().gl_ext_texture_filter_minmax
+    }
+}
+
+impl ToGlEnum for SamplerReductionMode {
+    #[inline]
+    fn to_glenum(&self) -> gl::types::GLenum {
+        match *self {
+            SamplerReductionMode::WeightedAverage => gl::WEIGHTED_AVERAGE_ARB,
+            SamplerReductionMode::Min => gl::MIN,
+            SamplerReductionMode::Max => gl::MAX,
+        }
+    }
+}
+
 /// A sampler.
 pub struct Sampler<'t, T: 't>(pub &'t T, pub SamplerBehavior);
 
@@ -123,11 +237,24 @@ impl<'t, T: 't> Sampler<'t, T> {
         self.1.max_anisotropy = level;
         self
     }
+
+    /// Changes the comparison function used when sampling a depth texture with a shadow sampler.
+    pub fn depth_texture_comparison(mut self, comparison: Option<DepthTextureComparison>)
+                                    -> Sampler<'t, T>
+    {
+        self.1.depth_texture_comparison = comparison;
+        self
+    }
+
+    /// Changes the way the texels loaded by a linear filter are merged together.
+    pub fn reduction_mode(mut self, mode: SamplerReductionMode) -> Sampler<'t, T> {
+        self.1.reduction_mode = mode;
+        self
+    }
 }
 
 /// Behavior of a sampler.
-// TODO: GL_TEXTURE_BORDER_COLOR, GL_TEXTURE_MIN_LOD, GL_TEXTURE_MAX_LOD, GL_TEXTURE_LOD_BIAS,
-//       GL_TEXTURE_COMPARE_MODE, GL_TEXTURE_COMPARE_FUNC
+// TODO: GL_TEXTURE_BORDER_COLOR, GL_TEXTURE_MIN_LOD, GL_TEXTURE_MAX_LOD, GL_TEXTURE_LOD_BIAS
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct SamplerBehavior {
     /// Functions to use for the X, Y, and Z coordinates.
@@ -149,6 +276,16 @@ pub struct SamplerBehavior {
     /// If you set the value to a value higher than what the hardware supports, it will
     /// be clamped.
     pub max_anisotropy: u16,
+
+    /// If `Some`, the texture must be a depth texture and must be sampled with a shadow sampler.
+    /// The value returned by the shader is then the result of the comparison between the
+    /// reference value and the texel. The default value is `None`.
+    pub depth_texture_comparison: Option<DepthTextureComparison>,
+
+    /// How the texels that are loaded by the linear filters are merged together. Using a value
+    /// other than `WeightedAverage` triggers a `SamplerReductionModeNotSupported` error when
+    /// drawing if the backend doesn't support it. The default value is `WeightedAverage`.
+    pub reduction_mode: SamplerReductionMode,
 }
 
 impl Default for SamplerBehavior {
@@ -163,6 +300,8 @@ impl Default for SamplerBehavior {
             minify_filter: MinifySamplerFilter::LinearMipmapLinear,
             magnify_filter: MagnifySamplerFilter::Linear,
             max_anisotropy: 1,
+            depth_texture_comparison: None,
+            reduction_mode: SamplerReductionMode::WeightedAverage,
         }
     }
 }
@@ -273,6 +273,7 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::IntegralTexture1d(_, _), UniformType::ISampler1d) => true,
             (&UniformValue::UnsignedTexture1d(_, _), UniformType::USampler1d) => true,
             (&UniformValue::DepthTexture1d(_, _), UniformType::Sampler1d) => true,
+            (&UniformValue::DepthTexture1d(_, _), UniformType::Sampler1dShadow) => true,
             (&UniformValue::Texture2d(_, _), UniformType::Sampler2d) => true,
             (&UniformValue::CompressedTexture2d(_, _), UniformType::Sampler2d) => true,
             (&UniformValue::SrgbTexture2d(_, _), UniformType::Sampler2d) => true,
@@ -280,6 +281,7 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::IntegralTexture2d(_, _), UniformType::ISampler2d) => true,
             (&UniformValue::UnsignedTexture2d(_, _), UniformType::USampler2d) => true,
             (&UniformValue::DepthTexture2d(_, _), UniformType::Sampler2d) => true,
+            (&UniformValue::DepthTexture2d(_, _), UniformType::Sampler2dShadow) => true,
             (&UniformValue::Texture3d(_, _), UniformType::Sampler3d) => true,
             (&UniformValue::CompressedTexture3d(_, _), UniformType::Sampler3d) => true,
             (&UniformValue::SrgbTexture3d(_, _), UniformType::Sampler3d) => true,
@@ -294,6 +296,7 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::IntegralTexture1dArray(_, _), UniformType::ISampler1dArray) => true,
             (&UniformValue::UnsignedTexture1dArray(_, _), UniformType::USampler1dArray) => true,
             (&UniformValue::DepthTexture1dArray(_, _), UniformType::Sampler1dArray) => true,
+            (&UniformValue::DepthTexture1dArray(_, _), UniformType::Sampler1dArrayShadow) => true,
             (&UniformValue::Texture2dArray(_, _), UniformType::Sampler2dArray) => true,
             (&UniformValue::CompressedTexture2dArray(_, _), UniformType::Sampler2dArray) => true,
             (&UniformValue::SrgbTexture2dArray(_, _), UniformType::Sampler2dArray) => true,
@@ -301,6 +304,7 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::IntegralTexture2dArray(_, _), UniformType::ISampler2dArray) => true,
             (&UniformValue::UnsignedTexture2dArray(_, _), UniformType::USampler2dArray) => true,
             (&UniformValue::DepthTexture2dArray(_, _), UniformType::Sampler2dArray) => true,
+            (&UniformValue::DepthTexture2dArray(_, _), UniformType::Sampler2dArrayShadow) => true,
             (&UniformValue::Cubemap(_, _), UniformType::SamplerCube) => true,
             (&UniformValue::CompressedCubemap(_, _), UniformType::SamplerCube) => true,
             (&UniformValue::SrgbCubemap(_, _), UniformType::SamplerCube) => true,
@@ -308,6 +312,7 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::IntegralCubemap(_, _), UniformType::ISamplerCube) => true,
             (&UniformValue::UnsignedCubemap(_, _), UniformType::USamplerCube) => true,
             (&UniformValue::DepthCubemap(_, _), UniformType::SamplerCube) => true,
+            (&UniformValue::DepthCubemap(_, _), UniformType::SamplerCubeShadow) => true,
             (&UniformValue::CubemapArray(_, _), UniformType::SamplerCubeArray) => true,
             (&UniformValue::CompressedCubemapArray(_, _), UniformType::SamplerCubeArray) => true,
             (&UniformValue::SrgbCubemapArray(_, _), UniformType::SamplerCubeArray) => true,
@@ -315,6 +320,7 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::IntegralCubemapArray(_, _), UniformType::ISamplerCubeArray) => true,
             (&UniformValue::UnsignedCubemapArray(_, _), UniformType::USamplerCubeArray) => true,
             (&UniformValue::DepthCubemapArray(_, _), UniformType::SamplerCubeArray) => true,
+            (&UniformValue::DepthCubemapArray(_, _), UniformType::SamplerCubeArrayShadow) => true,
             (&UniformValue::BufferTexture(tex), UniformType::SamplerBuffer) => {
                 tex.get_texture_type() == texture::buffer_texture::BufferTextureType::Float
             },
@@ -327,6 +333,100 @@ impl<'a> UniformValue<'a> {
             _ => false,
         }
     }
+
+    /// Returns the GLSL type that corresponds to this value, or `None` if this value can't be
+    /// used with a single uniform.
+    pub fn get_type(&self) -> Option<UniformType> {
+        match self {
+            &UniformValue::Bool(_) => Some(UniformType::Bool),
+            &UniformValue::SignedInt(_) => Some(UniformType::Int),
+            &UniformValue::UnsignedInt(_) => Some(UniformType::UnsignedInt),
+            &UniformValue::Float(_) => Some(UniformType::Float),
+            &UniformValue::Mat2(_) => Some(UniformType::FloatMat2),
+            &UniformValue::Mat3(_) => Some(UniformType::FloatMat3),
+            &UniformValue::Mat4(_) => Some(UniformType::FloatMat4),
+            &UniformValue::Vec2(_) => Some(UniformType::FloatVec2),
+            &UniformValue::Vec3(_) => Some(UniformType::FloatVec3),
+            &UniformValue::Vec4(_) => Some(UniformType::FloatVec4),
+            &UniformValue::IntVec2(_) => Some(UniformType::IntVec2),
+            &UniformValue::IntVec3(_) => Some(UniformType::IntVec3),
+            &UniformValue::IntVec4(_) => Some(UniformType::IntVec4),
+            &UniformValue::UnsignedIntVec2(_) => Some(UniformType::UnsignedIntVec2),
+            &UniformValue::UnsignedIntVec3(_) => Some(UniformType::UnsignedIntVec3),
+            &UniformValue::UnsignedIntVec4(_) => Some(UniformType::UnsignedIntVec4),
+            &UniformValue::BoolVec2(_) => Some(UniformType::BoolVec2),
+            &UniformValue::BoolVec3(_) => Some(UniformType::BoolVec3),
+            &UniformValue::BoolVec4(_) => Some(UniformType::BoolVec4),
+            &UniformValue::Double(_) => Some(UniformType::Double),
+            &UniformValue::DoubleMat2(_) => Some(UniformType::DoubleMat2),
+            &UniformValue::DoubleMat3(_) => Some(UniformType::DoubleMat3),
+            &UniformValue::DoubleMat4(_) => Some(UniformType::DoubleMat4),
+            &UniformValue::DoubleVec2(_) => Some(UniformType::DoubleVec2),
+            &UniformValue::DoubleVec3(_) => Some(UniformType::DoubleVec3),
+            &UniformValue::DoubleVec4(_) => Some(UniformType::DoubleVec4),
+            &UniformValue::Texture1d(_, _) => Some(UniformType::Sampler1d),
+            &UniformValue::CompressedTexture1d(_, _) => Some(UniformType::Sampler1d),
+            &UniformValue::SrgbTexture1d(_, _) => Some(UniformType::Sampler1d),
+            &UniformValue::CompressedSrgbTexture1d(_, _) => Some(UniformType::Sampler1d),
+            &UniformValue::IntegralTexture1d(_, _) => Some(UniformType::ISampler1d),
+            &UniformValue::UnsignedTexture1d(_, _) => Some(UniformType::USampler1d),
+            &UniformValue::DepthTexture1d(_, _) => Some(UniformType::Sampler1d),
+            &UniformValue::Texture2d(_, _) => Some(UniformType::Sampler2d),
+            &UniformValue::CompressedTexture2d(_, _) => Some(UniformType::Sampler2d),
+            &UniformValue::SrgbTexture2d(_, _) => Some(UniformType::Sampler2d),
+            &UniformValue::CompressedSrgbTexture2d(_, _) => Some(UniformType::Sampler2d),
+            &UniformValue::IntegralTexture2d(_, _) => Some(UniformType::ISampler2d),
+            &UniformValue::UnsignedTexture2d(_, _) => Some(UniformType::USampler2d),
+            &UniformValue::DepthTexture2d(_, _) => Some(UniformType::Sampler2d),
+            &UniformValue::Texture3d(_, _) => Some(UniformType::Sampler3d),
+            &UniformValue::CompressedTexture3d(_, _) => Some(UniformType::Sampler3d),
+            &UniformValue::SrgbTexture3d(_, _) => Some(UniformType::Sampler3d),
+            &UniformValue::CompressedSrgbTexture3d(_, _) => Some(UniformType::Sampler3d),
+            &UniformValue::IntegralTexture3d(_, _) => Some(UniformType::ISampler3d),
+            &UniformValue::UnsignedTexture3d(_, _) => Some(UniformType::USampler3d),
+            &UniformValue::DepthTexture3d(_, _) => Some(UniformType::Sampler3d),
+            &UniformValue::Texture1dArray(_, _) => Some(UniformType::Sampler1dArray),
+            &UniformValue::CompressedTexture1dArray(_, _) => Some(UniformType::Sampler1dArray),
+            &UniformValue::SrgbTexture1dArray(_, _) => Some(UniformType::Sampler1dArray),
+            &UniformValue::CompressedSrgbTexture1dArray(_, _) => Some(UniformType::Sampler1dArray),
+            &UniformValue::IntegralTexture1dArray(_, _) => Some(UniformType::ISampler1dArray),
+            &UniformValue::UnsignedTexture1dArray(_, _) => Some(UniformType::USampler1dArray),
+            &UniformValue::DepthTexture1dArray(_, _) => Some(UniformType::Sampler1dArray),
+            &UniformValue::Texture2dArray(_, _) => Some(UniformType::Sampler2dArray),
+            &UniformValue::CompressedTexture2dArray(_, _) => Some(UniformType::Sampler2dArray),
+            &UniformValue::SrgbTexture2dArray(_, _) => Some(UniformType::Sampler2dArray),
+            &UniformValue::CompressedSrgbTexture2dArray(_, _) => Some(UniformType::Sampler2dArray),
+            &UniformValue::IntegralTexture2dArray(_, _) => Some(UniformType::ISampler2dArray),
+            &UniformValue::UnsignedTexture2dArray(_, _) => Some(UniformType::USampler2dArray),
+            &UniformValue::DepthTexture2dArray(_, _) => Some(UniformType::Sampler2dArray),
+            &UniformValue::Cubemap(_, _) => Some(UniformType::SamplerCube),
+            &UniformValue::CompressedCubemap(_, _) => Some(UniformType::SamplerCube),
+            &UniformValue::SrgbCubemap(_, _) => Some(UniformType::SamplerCube),
+            &UniformValue::CompressedSrgbCubemap(_, _) => Some(UniformType::SamplerCube),
+            &UniformValue::IntegralCubemap(_, _) => Some(UniformType::ISamplerCube),
+            &UniformValue::UnsignedCubemap(_, _) => Some(UniformType::USamplerCube),
+            &UniformValue::DepthCubemap(_, _) => Some(UniformType::SamplerCube),
+            &UniformValue::CubemapArray(_, _) => Some(UniformType::SamplerCubeArray),
+            &UniformValue::CompressedCubemapArray(_, _) => Some(UniformType::SamplerCubeArray),
+            &UniformValue::SrgbCubemapArray(_, _) => Some(UniformType::SamplerCubeArray),
+            &UniformValue::CompressedSrgbCubemapArray(_, _) => Some(UniformType::SamplerCubeArray),
+            &UniformValue::IntegralCubemapArray(_, _) => Some(UniformType::ISamplerCubeArray),
+            &UniformValue::UnsignedCubemapArray(_, _) => Some(UniformType::USamplerCubeArray),
+            &UniformValue::DepthCubemapArray(_, _) => Some(UniformType::SamplerCubeArray),
+            &UniformValue::BufferTexture(tex) => {
+                Some(match tex.get_texture_type() {
+                    texture::buffer_texture::BufferTextureType::Float => UniformType::SamplerBuffer,
+                    texture::buffer_texture::BufferTextureType::Integral => {
+                        UniformType::ISamplerBuffer
+                    },
+                    texture::buffer_texture::BufferTextureType::Unsigned => {
+                        UniformType::USamplerBuffer
+                    },
+                })
+            },
+            _ => None,
+        }
+    }
 }
 
 macro_rules! impl_uniform_block_basic {
@@ -0,0 +1,378 @@
+/*!
+Spreads the uploads of textures and buffers across frames.
+
+Uploading a lot of data at once, for example when streaming the content of a level, makes the
+frame during which it happens take much longer than the others. An `UploadScheduler` accepts
+upload jobs with a priority, and each call to `process` only performs the transfers that fit
+in a budget of bytes and of time. Large jobs are split in rows of pixels or in ranges of
+elements, so that a single job can be spread across several frames too.
+
+The jobs designate their destination with a `ResourceId`, like the commands of the
+`command_encoder` module. Jobs with a higher priority are processed first, and jobs with the
+same priority are processed in the order in which they have been added.
+
+# Example
+
+```no_run
+use std::time::Duration;
+use glium::command_encoder::Resources;
+use glium::upload_scheduler::UploadScheduler;
+# let texture: glium::texture::Texture2d = unsafe { ::std::mem::uninitialized() };
+# let buffer: glium::buffer::BufferAny = unsafe { ::std::mem::uninitialized() };
+
+let mut textures = Resources::new();
+let texture = textures.insert(texture);
+
+let mut buffers = Resources::new();
+let buffer = buffers.insert(buffer);
+
+// at most 1 MiB and 2 milliseconds per frame
+let mut scheduler = UploadScheduler::new();
+scheduler.set_bytes_budget(Some(1024 * 1024));
+scheduler.set_time_budget(Some(Duration::from_millis(2)));
+
+let pixels = vec![(0u8, 0u8, 0u8, 255u8); 1024 * 1024];
+let rect = glium::Rect { left: 0, bottom: 0, width: 1024, height: 1024 };
+let upload = scheduler.upload_texture2d(0, texture, rect, pixels);
+scheduler.upload_buffer(10, buffer, 0, vec![0.0f32; 4096]);
+
+// in the rendering loop
+scheduler.process(&textures, &buffers).unwrap();
+
+if !scheduler.is_pending(upload) {
+    // the texture can be used
+}
+```
+
+*/
+use buffer::BufferAny;
+use command_encoder::{InvalidResourceId, Resources, ResourceId};
+use texture::{PixelValue, RawImage2d, Texture2d};
+use Rect;
+
+use std::borrow::Cow;
+use std::mem;
+use std::time::{Duration, Instant};
+
+/// When a time budget is set, the jobs are split in chunks of at most this number of bytes so
+/// that the time can be checked regularly.
+const TIME_CHECK_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Identifier of a job that has been added to an `UploadScheduler`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UploadId(u64);
+
+/// The data of a job and its destination.
+trait UploadData {
+    /// Returns the number of bytes that haven't been uploaded yet.
+    fn remaining_bytes(&self) -> usize;
+
+    /// Returns the number of bytes of the smallest part that can be uploaded, which is a row of
+    /// pixels or an element.
+    fn unit_bytes(&self) -> usize;
+
+    /// Uploads at most `max_units` rows or elements. Returns the number of bytes uploaded.
+    fn upload(&mut self, textures: &Resources<Texture2d>, buffers: &Resources<BufferAny>,
+              max_units: usize) -> Result<usize, InvalidResourceId>;
+}
+
+/// Upload of pixels to the main level of a texture.
+struct TextureUpload<P> {
+    texture: ResourceId,
+    rect: Rect,
+    data: Vec<P>,
+
+    /// Number of rows that have already been uploaded, starting from the bottom.
+    uploaded_rows: u32,
+}
+
+impl<P> UploadData for TextureUpload<P> where P: PixelValue {
+    #[inline]
+    fn remaining_bytes(&self) -> usize {
+        (self.rect.height - self.uploaded_rows) as usize * self.unit_bytes()
+    }
+
+    #[inline]
+    fn unit_bytes(&self) -> usize {
+        self.rect.width as usize * mem::size_of::<P>()
+    }
+
+    fn upload(&mut self, textures: &Resources<Texture2d>, _: &Resources<BufferAny>,
+              max_units: usize) -> Result<usize, InvalidResourceId>
+    {
+        let texture = try!(textures.get(self.texture));
+
+        let remaining = (self.rect.height - self.uploaded_rows) as usize;
+        let rows = if max_units < remaining { max_units as u32 } else { remaining as u32 };
+
+        let width = self.rect.width;
+        let start = self.uploaded_rows as usize * width as usize;
+        let end = start + rows as usize * width as usize;
+
+        let rect = Rect {
+            left: self.rect.left,
+            bottom: self.rect.bottom + self.uploaded_rows,
+            width: width,
+            height: rows,
+        };
+
+        texture.write(rect, RawImage2d {
+            data: Cow::Borrowed(&self.data[start .. end]),
+            width: width,
+            height: rows,
+            format: <P as PixelValue>::get_format(),
+        });
+
+        self.uploaded_rows += rows;
+        Ok(rows as usize * self.unit_bytes())
+    }
+}
+
+/// Upload of elements to a buffer.
+struct BufferUpload<T> {
+    buffer: ResourceId,
+    offset_bytes: usize,
+    data: Vec<T>,
+
+    /// Number of elements that have already been uploaded.
+    uploaded: usize,
+}
+
+impl<T> UploadData for BufferUpload<T> where T: Copy + 'static {
+    #[inline]
+    fn remaining_bytes(&self) -> usize {
+        (self.data.len() - self.uploaded) * self.unit_bytes()
+    }
+
+    #[inline]
+    fn unit_bytes(&self) -> usize {
+        mem::size_of::<T>()
+    }
+
+    fn upload(&mut self, _: &Resources<Texture2d>, buffers: &Resources<BufferAny>,
+              max_units: usize) -> Result<usize, InvalidResourceId>
+    {
+        let buffer = try!(buffers.get(self.buffer));
+
+        let remaining = self.data.len() - self.uploaded;
+        let count = if max_units < remaining { max_units } else { remaining };
+
+        let offset = self.offset_bytes + self.uploaded * self.unit_bytes();
+        buffer.write_at(offset, &self.data[self.uploaded .. self.uploaded + count]);
+
+        self.uploaded += count;
+        Ok(count * self.unit_bytes())
+    }
+}
+
+/// A job waiting in the scheduler.
+struct Job {
+    id: UploadId,
+    priority: u32,
+    data: Box<UploadData>,
+}
+
+/// Accepts upload jobs and performs them over several frames.
+pub struct UploadScheduler {
+    /// Jobs sorted by decreasing priority, then by order of addition.
+    jobs: Vec<Job>,
+    next_id: u64,
+    bytes_budget: Option<usize>,
+    time_budget: Option<Duration>,
+}
+
+impl UploadScheduler {
+    /// Builds a new scheduler without any budget, which performs all the jobs during the next
+    /// call to `process`.
+    #[inline]
+    pub fn new() -> UploadScheduler {
+        UploadScheduler {
+            jobs: Vec::new(),
+            next_id: 0,
+            bytes_budget: None,
+            time_budget: None,
+        }
+    }
+
+    /// Sets the maximum number of bytes that `process` uploads, or `None` for no limit.
+    ///
+    /// A call to `process` always uploads at least one row of pixels or one element, even if it
+    /// is larger than the budget.
+    #[inline]
+    pub fn set_bytes_budget(&mut self, bytes: Option<usize>) {
+        self.bytes_budget = bytes;
+    }
+
+    /// Returns the maximum number of bytes that `process` uploads.
+    #[inline]
+    pub fn get_bytes_budget(&self) -> Option<usize> {
+        self.bytes_budget
+    }
+
+    /// Sets the maximum time that `process` spends uploading, or `None` for no limit.
+    ///
+    /// This only measures the time spent submitting the transfers to the driver, which may
+    /// perform the actual copies later. The time is checked between chunks of a few hundred
+    /// kilobytes, so the budget can be slightly exceeded.
+    #[inline]
+    pub fn set_time_budget(&mut self, time: Option<Duration>) {
+        self.time_budget = time;
+    }
+
+    /// Returns the maximum time that `process` spends uploading.
+    #[inline]
+    pub fn get_time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+
+    /// Adds a job that writes `data` to the `rect` area of the main level of a texture. Jobs
+    /// with a higher `priority` are performed first.
+    ///
+    /// The pixels start with the bottom-left pixel, and the rows go from bottom to top.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the number of pixels doesn't match the dimensions of `rect`.
+    pub fn upload_texture2d<P>(&mut self, priority: u32, texture: ResourceId, rect: Rect,
+                               data: Vec<P>) -> UploadId
+                               where P: PixelValue
+    {
+        assert_eq!(data.len(), rect.width as usize * rect.height as usize);
+
+        self.push(priority, Box::new(TextureUpload {
+            texture: texture,
+            rect: rect,
+            data: data,
+            uploaded_rows: 0,
+        }))
+    }
+
+    /// Adds a job that writes `data` to a buffer, starting at `offset_bytes` bytes from the
+    /// start of the buffer. Jobs with a higher `priority` are performed first.
+    ///
+    /// If the data doesn't fit in the buffer, `process` will panic.
+    pub fn upload_buffer<T>(&mut self, priority: u32, buffer: ResourceId, offset_bytes: usize,
+                            data: Vec<T>) -> UploadId
+                            where T: Copy + 'static
+    {
+        self.push(priority, Box::new(BufferUpload {
+            buffer: buffer,
+            offset_bytes: offset_bytes,
+            data: data,
+            uploaded: 0,
+        }))
+    }
+
+    /// Returns true if a job hasn't been entirely performed yet.
+    #[inline]
+    pub fn is_pending(&self, id: UploadId) -> bool {
+        self.jobs.iter().any(|job| job.id == id)
+    }
+
+    /// Removes a job from the scheduler. The parts of the job that have already been uploaded
+    /// stay in the destination.
+    ///
+    /// Returns false if the job wasn't pending.
+    pub fn cancel(&mut self, id: UploadId) -> bool {
+        match self.jobs.iter().position(|job| job.id == id) {
+            Some(index) => {
+                self.jobs.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns the number of jobs that haven't been entirely performed yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Returns the number of bytes that haven't been uploaded yet.
+    pub fn get_pending_bytes(&self) -> usize {
+        self.jobs.iter().map(|job| job.data.remaining_bytes()).fold(0, |a, b| a + b)
+    }
+
+    /// Performs the jobs, by order of priority, until the budget of this call is exhausted.
+    /// This is usually called once per frame.
+    ///
+    /// Returns the number of bytes that have been uploaded. If the `ResourceId` of a job
+    /// doesn't correspond to any object of `textures` or `buffers` respectively, for example
+    /// because it was returned by another `Resources`, the job is discarded and an error is
+    /// returned.
+    pub fn process(&mut self, textures: &Resources<Texture2d>, buffers: &Resources<BufferAny>)
+                   -> Result<usize, InvalidResourceId>
+    {
+        let start = Instant::now();
+        let mut uploaded = 0;
+
+        while !self.jobs.is_empty() {
+            if let Some(budget) = self.time_budget {
+                if uploaded != 0 && start.elapsed() >= budget {
+                    break;
+                }
+            }
+
+            let unit = self.jobs[0].data.unit_bytes();
+
+            let mut max_units = match self.bytes_budget {
+                Some(budget) => {
+                    let left = budget.saturating_sub(uploaded);
+                    if left >= unit {
+                        left / unit
+                    } else if uploaded == 0 {
+                        1
+                    } else {
+                        break;
+                    }
+                },
+                None => ::std::usize::MAX,
+            };
+
+            if self.time_budget.is_some() {
+                let chunk = TIME_CHECK_CHUNK_BYTES / unit;
+                if chunk < max_units {
+                    max_units = if chunk == 0 { 1 } else { chunk };
+                }
+            }
+
+            match self.jobs[0].data.upload(textures, buffers, max_units) {
+                Ok(bytes) => uploaded += bytes,
+                Err(err) => {
+                    self.jobs.remove(0);
+                    return Err(err);
+                },
+            };
+
+            if self.jobs[0].data.remaining_bytes() == 0 {
+                self.jobs.remove(0);
+            }
+        }
+
+        Ok(uploaded)
+    }
+
+    /// Inserts a job after the jobs with the same or a higher priority.
+    fn push(&mut self, priority: u32, data: Box<UploadData>) -> UploadId {
+        let id = UploadId(self.next_id);
+        self.next_id += 1;
+
+        // empty jobs are already finished
+        if data.remaining_bytes() == 0 {
+            return id;
+        }
+
+        let index = self.jobs.iter().position(|job| job.priority < priority)
+                                    .unwrap_or(self.jobs.len());
+        self.jobs.insert(index, Job { id: id, priority: priority, data: data });
+        id
+    }
+}
+
+impl Default for UploadScheduler {
+    #[inline]
+    fn default() -> UploadScheduler {
+        UploadScheduler::new()
+    }
+}
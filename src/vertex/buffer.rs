@@ -1,10 +1,13 @@
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
+use std::mem;
 use std::ops::{Range, Deref, DerefMut};
+use std::slice;
 
 use buffer::{Buffer, BufferSlice, BufferAny, BufferType, BufferMode, BufferCreationError};
-use vertex::{Vertex, VerticesSource, IntoVerticesSource, PerInstance};
-use vertex::format::VertexFormat;
+use vertex::{Attribute, Vertex, VerticesSource, IntoVerticesSource, PerInstance};
+use vertex::format::{AttributeType, VertexFormat};
 
 use backend::Facade;
 use version::{Api, Version};
@@ -199,6 +202,44 @@ impl<T> VertexBuffer<T> where T: Vertex {
     }
 }
 
+impl<T> VertexBuffer<T> where T: Attribute + Copy {
+    /// Builds a new vertex buffer where each element is a single attribute named `name`.
+    ///
+    /// This is useful if your data is stored as a structure of arrays. You can then build one
+    /// vertex buffer per attribute and draw them all at once by passing a tuple
+    /// of vertex buffers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// let positions: Vec<[f32; 3]> = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+    /// let normals: Vec<[f32; 3]> = vec![[0.0, 0.0, 1.0], [0.0, 0.0, 1.0], [0.0, 0.0, 1.0]];
+    ///
+    /// let positions = glium::VertexBuffer::from_attribute(&display, "position", &positions)
+    ///                                     .unwrap();
+    /// let normals = glium::VertexBuffer::from_attribute(&display, "normal", &normals).unwrap();
+    ///
+    /// // `(&positions, &normals)` can now be used as a source of vertices
+    /// ```
+    ///
+    pub fn from_attribute<F, S>(facade: &F, name: S, data: &[T])
+                                -> Result<VertexBuffer<T>, CreationError>
+                                where F: Facade, S: Into<Cow<'static, str>>
+    {
+        if !T::is_supported(facade) {
+            return Err(CreationError::FormatNotSupported);
+        }
+
+        let buffer = try!(Buffer::new(facade, data, BufferType::ArrayBuffer, BufferMode::Default));
+
+        Ok(VertexBuffer {
+            buffer: buffer,
+            bindings: Cow::Owned(vec![(name.into(), 0, T::get_type(), false)]),
+        })
+    }
+}
+
 impl<T> VertexBuffer<T> where T: Copy {
     /// Builds a new vertex buffer from an indeterminate data type and bindings.
     ///
@@ -212,10 +253,10 @@ impl<T> VertexBuffer<T> where T: Copy {
     ///
     /// let bindings = Cow::Owned(vec![(
     ///         Cow::Borrowed("position"), 0,
-    ///         glium::vertex::AttributeType::F32F32,
+    ///         glium::vertex::AttributeType::F32F32, false,
     ///     ), (
     ///         Cow::Borrowed("color"), 2 * ::std::mem::size_of::<f32>(),
-    ///         glium::vertex::AttributeType::F32,
+    ///         glium::vertex::AttributeType::F32, false,
     ///     ),
     /// ]);
     ///
@@ -237,7 +278,9 @@ impl<T> VertexBuffer<T> where T: Copy {
                              -> Result<VertexBuffer<T>, CreationError>
                              where F: Facade
     {
-        // FIXME: check that the format is supported
+        if !bindings.iter().all(|&(_, _, ty, _)| ty.is_supported(facade)) {
+            return Err(CreationError::FormatNotSupported);
+        }
 
         Ok(VertexBuffer {
             buffer: try!(Buffer::new(facade, data, BufferType::ArrayBuffer,
@@ -253,7 +296,9 @@ impl<T> VertexBuffer<T> where T: Copy {
                                      -> Result<VertexBuffer<T>, CreationError>
                                      where F: Facade
     {
-        // FIXME: check that the format is supported
+        if !bindings.iter().all(|&(_, _, ty, _)| ty.is_supported(facade)) {
+            return Err(CreationError::FormatNotSupported);
+        }
 
         Ok(VertexBuffer {
             buffer: try!(Buffer::new(facade, data, BufferType::ArrayBuffer,
@@ -457,3 +502,204 @@ impl<'a> IntoVerticesSource<'a> for &'a VertexBufferAny {
 /// Instancing is not supported by the backend.
 #[derive(Debug, Copy, Clone)]
 pub struct InstancingNotSupported;
+
+impl fmt::Display for InstancingNotSupported {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for InstancingNotSupported {
+    fn description(&self) -> &str {
+        "Instancing is not supported by the backend"
+    }
+}
+
+/// Error that can happen when building a vertex buffer with a `VertexBufferBuilder`.
+#[derive(Copy, Clone, Debug)]
+pub enum BuilderError {
+    /// No attribute has been added to the builder.
+    NoAttribute,
+
+    /// The attributes don't all have the same number of elements.
+    LengthMismatch,
+
+    /// Error while creating the vertex buffer.
+    CreationError(CreationError),
+}
+
+impl From<CreationError> for BuilderError {
+    #[inline]
+    fn from(err: CreationError) -> BuilderError {
+        BuilderError::CreationError(err)
+    }
+}
+
+impl From<BufferCreationError> for BuilderError {
+    #[inline]
+    fn from(err: BufferCreationError) -> BuilderError {
+        BuilderError::CreationError(CreationError::BufferCreationError(err))
+    }
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &BuilderError::CreationError(error) => error.fmt(formatter),
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for BuilderError {
+    fn description(&self) -> &str {
+        match self {
+            &BuilderError::NoAttribute => "No attribute has been added to the builder",
+            &BuilderError::LengthMismatch => "The attributes don't all have the same number \
+                                              of elements",
+            &BuilderError::CreationError(..) => "Error while creating the vertex buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &BuilderError::CreationError(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Builds an interleaved vertex buffer from separate lists of attributes.
+///
+/// This is useful if your data is stored as a structure of arrays (for example when loading a
+/// model from a file), as you don't need to define a `Vertex` struct and fill it yourself.
+///
+/// Each attribute is aligned on four bytes.
+///
+/// # Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+/// let positions: Vec<[f32; 3]> = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+/// let tex_coords: Vec<[f32; 2]> = vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+///
+/// let vertex_buffer = glium::vertex::VertexBufferBuilder::new()
+///                         .attribute("position", &positions)
+///                         .attribute("tex_coords", &tex_coords)
+///                         .build(&display).unwrap();
+/// ```
+///
+pub struct VertexBufferBuilder<'a> {
+    // name, type, normalize, raw data, size of each element in the data
+    attributes: Vec<(Cow<'static, str>, AttributeType, bool, &'a [u8], usize)>,
+}
+
+impl<'a> VertexBufferBuilder<'a> {
+    /// Builds a new empty builder.
+    #[inline]
+    pub fn new() -> VertexBufferBuilder<'a> {
+        VertexBufferBuilder {
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Adds an attribute to the vertex buffer.
+    #[inline]
+    pub fn attribute<S, T>(self, name: S, data: &'a [T]) -> VertexBufferBuilder<'a>
+                           where S: Into<Cow<'static, str>>, T: Attribute + Copy
+    {
+        self.attribute_impl(name, data, false)
+    }
+
+    /// Adds an attribute to the vertex buffer. Its integer values will be normalized to floats
+    /// between `0.0` and `1.0` (or `-1.0` and `1.0` for signed types).
+    #[inline]
+    pub fn normalized_attribute<S, T>(self, name: S, data: &'a [T]) -> VertexBufferBuilder<'a>
+                                      where S: Into<Cow<'static, str>>, T: Attribute + Copy
+    {
+        self.attribute_impl(name, data, true)
+    }
+
+    fn attribute_impl<S, T>(mut self, name: S, data: &'a [T], normalize: bool)
+                            -> VertexBufferBuilder<'a>
+                            where S: Into<Cow<'static, str>>, T: Attribute + Copy
+    {
+        let elem_size = mem::size_of::<T>();
+        let raw = unsafe {
+            slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * elem_size)
+        };
+
+        self.attributes.push((name.into(), T::get_type(), normalize, raw, elem_size));
+        self
+    }
+
+    /// Builds the vertex buffer.
+    #[inline]
+    pub fn build<F>(self, facade: &F) -> Result<VertexBufferAny, BuilderError> where F: Facade {
+        self.build_impl(facade, BufferMode::Default)
+    }
+
+    /// Builds the vertex buffer.
+    ///
+    /// This function will create a buffer that is intended to be modified frequently.
+    #[inline]
+    pub fn build_dynamic<F>(self, facade: &F) -> Result<VertexBufferAny, BuilderError>
+                            where F: Facade
+    {
+        self.build_impl(facade, BufferMode::Dynamic)
+    }
+
+    fn build_impl<F>(self, facade: &F, mode: BufferMode) -> Result<VertexBufferAny, BuilderError>
+                     where F: Facade
+    {
+        if self.attributes.is_empty() {
+            return Err(BuilderError::NoAttribute);
+        }
+
+        let len = self.attributes[0].3.len() / self.attributes[0].4;
+
+        // computing the offset of each attribute
+        let mut offsets = Vec::with_capacity(self.attributes.len());
+        let mut stride = 0;
+        for &(_, ty, _, data, elem_size) in self.attributes.iter() {
+            if data.len() / elem_size != len {
+                return Err(BuilderError::LengthMismatch);
+            }
+
+            if !ty.is_supported(facade) {
+                return Err(CreationError::FormatNotSupported.into());
+            }
+
+            offsets.push(stride);
+            stride += (elem_size + 3) / 4 * 4;
+        }
+
+        // interleaving the data
+        let mut interleaved = vec![0u8; len * stride];
+        for (&(_, _, _, data, elem_size), &offset) in self.attributes.iter().zip(offsets.iter()) {
+            for (vertex, elem) in interleaved.chunks_mut(stride).zip(data.chunks(elem_size)) {
+                for (dest, src) in vertex[offset .. offset + elem_size].iter_mut().zip(elem.iter()) {
+                    *dest = *src;
+                }
+            }
+        }
+
+        let buffer: Buffer<[u8]> = try!(Buffer::new(facade, &interleaved[..],
+                                                    BufferType::ArrayBuffer, mode));
+        let mut buffer: BufferAny = buffer.into();
+
+        // each vertex is made of `stride` bytes, which match the bindings built below
+        unsafe { buffer.set_elements_size(stride); }
+
+        let bindings = self.attributes.into_iter().zip(offsets.into_iter())
+                           .map(|((name, ty, normalize, _, _), offset)| {
+                               (name, offset, ty, normalize)
+                           })
+                           .collect::<Vec<_>>();
+
+        Ok(VertexBufferAny {
+            buffer: buffer,
+            bindings: Cow::Owned(bindings),
+        })
+    }
+}
@@ -10,9 +10,11 @@ use CapabilitiesSource;
 use cgmath;
 #[cfg(feature = "nalgebra")]
 use nalgebra;
+#[cfg(feature = "half")]
+use half;
 
 #[allow(missing_docs)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AttributeType {
     I8,
     I8I8,
@@ -147,6 +149,11 @@ pub enum AttributeType {
     ///
     /// Corresponds to `GL_FIXED`.
     FixedFloatI16U16,
+    /// Four unsigned bytes stored in the blue, green, red, alpha order. Each value is normalized
+    /// to a float between `0.0` and `1.0` and the shader receives them in the RGBA order.
+    ///
+    /// Corresponds to a size of `GL_BGRA` with `GL_UNSIGNED_BYTE`.
+    U8U8U8U8Bgra,
 }
 
 impl AttributeType {
@@ -233,6 +240,12 @@ impl AttributeType {
                 caps.get_version() >= &Version(Api::Gl, 4, 0) ||
                 caps.get_extensions().gl_arb_vertex_type_10f_11f_11f_rev
             },
+
+            &AttributeType::U8U8U8U8Bgra => {
+                caps.get_version() >= &Version(Api::Gl, 3, 2) ||
+                caps.get_extensions().gl_arb_vertex_array_bgra ||
+                caps.get_extensions().gl_ext_vertex_array_bgra
+            },
         }
     }
 
@@ -316,6 +329,7 @@ impl AttributeType {
             AttributeType::U10U10U10U2 => 4,
             AttributeType::F10F11F11UnsignedIntReversed => 4,
             AttributeType::FixedFloatI16U16 => 4,
+            AttributeType::U8U8U8U8Bgra => 4 * mem::size_of::<u8>(),
         }
     }
 
@@ -399,6 +413,7 @@ impl AttributeType {
             AttributeType::U10U10U10U2 => 4,
             AttributeType::F10F11F11UnsignedIntReversed => 3,
             AttributeType::FixedFloatI16U16 => 1,
+            AttributeType::U8U8U8U8Bgra => 4,
         }
     }
 }
@@ -406,8 +421,14 @@ impl AttributeType {
 /// Describes the layout of each vertex in a vertex buffer.
 ///
 /// The first element is the name of the binding, the second element is the offset
-/// from the start of each vertex to this element, and the third element is the type.
-pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType)]>;
+/// from the start of each vertex to this element, the third element is the type, and the
+/// fourth element indicates whether integer values should be normalized.
+///
+/// If the fourth element is `true`, unsigned integers are turned into floats between `0.0` and
+/// `1.0` and signed integers into floats between `-1.0` and `1.0` before being passed to the
+/// shader. Has no effect on floating-point attributes or on attributes whose type in the shader
+/// is an integer.
+pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType, bool)]>;
 
 unsafe impl Attribute for i8 {
     #[inline]
@@ -507,6 +528,31 @@ unsafe impl Attribute for [u8; 4] {
     }
 }
 
+/// A color whose components are stored in the blue, green, red, alpha order.
+///
+/// This is the layout used by Direct3D's `D3DCOLOR`. The components are normalized by the
+/// GPU and the vertex shader receives a `vec4` in the usual RGBA order, so there is no need
+/// to swizzle the data on the CPU.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Bgra {
+    /// The blue component.
+    pub b: u8,
+    /// The green component.
+    pub g: u8,
+    /// The red component.
+    pub r: u8,
+    /// The alpha component.
+    pub a: u8,
+}
+
+unsafe impl Attribute for Bgra {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::U8U8U8U8Bgra
+    }
+}
+
 unsafe impl Attribute for i16 {
     #[inline]
     fn get_type() -> AttributeType {
@@ -801,6 +847,86 @@ unsafe impl Attribute for [u64; 4] {
     }
 }
 
+#[cfg(feature = "half")]
+unsafe impl Attribute for half::f16 {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for (half::f16, half::f16) {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [half::f16; 2] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for (half::f16, half::f16, half::f16) {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [half::f16; 3] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for (half::f16, half::f16, half::f16, half::f16) {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [half::f16; 4] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [[half::f16; 2]; 2] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16x2x2
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [[half::f16; 3]; 3] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16x3x3
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [[half::f16; 4]; 4] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16x4x4
+    }
+}
+
 unsafe impl Attribute for f32 {
     #[inline]
     fn get_type() -> AttributeType {
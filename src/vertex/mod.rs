@@ -120,6 +120,28 @@ be the same, or a `DrawError::VerticesSourcesLengthMismatch` will be produced.
 In all situation, the length of all per-instance sources must match, or
 `DrawError::InstancesCountMismatch` will be retured.
 
+## Matrices
+
+Attributes of type `[[f32; 2]; 2]`, `[[f32; 3]; 3]` and `[[f32; 4]; 4]` (and their `f64`
+equivalents) correspond to a `mat2`, `mat3` and `mat4` in the vertex shader. Each inner array
+is a column of the matrix, and each column is binded to its own consecutive attribute location.
+This is especially useful for instancing, as it allows you to store a transformation matrix
+per instance:
+
+```
+# #[macro_use]
+# extern crate glium;
+# fn main() {
+#[derive(Copy, Clone)]
+struct Instance {
+    // in the shader: `in mat4 world_matrix;`
+    world_matrix: [[f32; 4]; 4],
+}
+
+implement_vertex!(Instance, world_matrix);
+# }
+```
+
 # Transform feedback
 
 Transform feedback allows you to write in a buffer the list of primitives that are generated by
@@ -136,9 +158,9 @@ use std::iter::Chain;
 use std::option::IntoIter;
 
 pub use self::buffer::{VertexBuffer, VertexBufferAny};
-pub use self::buffer::VertexBufferSlice;
+pub use self::buffer::{VertexBufferSlice, VertexBufferBuilder, BuilderError};
 pub use self::buffer::CreationError as BufferCreationError;
-pub use self::format::{AttributeType, VertexFormat};
+pub use self::format::{AttributeType, Bgra, VertexFormat};
 pub use self::transform_feedback::{is_transform_feedback_supported, TransformFeedbackSession};
 
 use buffer::BufferAnySlice;
@@ -308,7 +330,7 @@ pub trait Vertex: Copy + Sized {
     fn is_supported<C>(caps: &C) -> bool where C: CapabilitiesSource {
         let format = Self::build_bindings();
 
-        for &(_, _, ref ty) in format.iter() {
+        for &(_, _, ref ty, _) in format.iter() {
             if !ty.is_supported(caps) {
                 return false;
             }
@@ -1,3 +1,5 @@
+use std::fmt;
+use std::error::Error;
 use std::mem;
 
 use version::Api;
@@ -103,6 +105,24 @@ pub enum TransformFeedbackSessionCreationError {
     WrongVertexFormat,
 }
 
+impl fmt::Display for TransformFeedbackSessionCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl Error for TransformFeedbackSessionCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &TransformFeedbackSessionCreationError::NotSupported => "Transform feedback is not \
+                                                                     supported by the backend",
+            &TransformFeedbackSessionCreationError::WrongVertexFormat => {
+                "The format of the output doesn't match what the program outputs"
+            },
+        }
+    }
+}
+
 /// Returns true if transform feedback is supported by the OpenGL implementation.
 #[inline]
 pub fn is_transform_feedback_supported<F>(facade: &F) -> bool where F: Facade {
@@ -5,6 +5,7 @@ use std::mem;
 
 use smallvec::SmallVec;
 
+use DrawError;
 use Handle;
 use buffer::BufferAnySlice;
 use program::Program;
@@ -23,13 +24,18 @@ pub struct VertexAttributesSystem {
     // we maintain a list of VAOs for each vertexbuffer-indexbuffer-program association
     // the key is a (buffers-list-with-offset, program) ; the buffers list must be sorted
     vaos: RefCell<HashMap<(Vec<(gl::types::GLuint, usize)>, Handle), VertexArrayObject>>,
+
+    // if `ARB_vertex_attrib_binding` is supported, we instead maintain a list of VAOs for each
+    // list-of-formats-program association ; the buffers are then binded with `glBindVertexBuffer`
+    formats: RefCell<HashMap<(Vec<(VertexFormat, Option<u32>)>, Handle), VertexArrayObject>>,
 }
 
 /// Object allowing one to bind vertex attributes to the current context.
 pub struct Binder<'a, 'b, 'c: 'b> {
     context: &'b mut CommandContext<'c>,
     program: &'a Program,
-    element_array_buffer: Option<BufferAnySlice<'a>>,
+    // ID of the buffer to bind to `GL_ELEMENT_ARRAY_BUFFER`, or 0
+    element_array_buffer: gl::types::GLuint,
     vertex_buffers: SmallVec<[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>); 2]>,
     base_vertex: bool,
 }
@@ -40,6 +46,7 @@ impl VertexAttributesSystem {
     pub fn new() -> VertexAttributesSystem {
         VertexAttributesSystem {
             vaos: RefCell::new(HashMap::new()),
+            formats: RefCell::new(HashMap::new()),
         }
     }
 
@@ -56,6 +63,20 @@ impl VertexAttributesSystem {
             indices.prepare_for_element_array(ctxt);
         }
 
+        VertexAttributesSystem::start_with_raw_indices(ctxt, program,
+                                                       indices.map(|b| b.get_buffer_id())
+                                                              .unwrap_or(0),
+                                                       base_vertex)
+    }
+
+    /// Same as `start`, but the index buffer is the ID of a buffer that is managed by glium
+    /// itself, or 0 if there is no index buffer.
+    #[inline]
+    pub fn start_with_raw_indices<'a, 'b, 'c: 'b>(ctxt: &'b mut CommandContext<'c>,
+                                                  program: &'a Program,
+                                                  indices: gl::types::GLuint, base_vertex: bool)
+                                                  -> Binder<'a, 'b, 'c>
+    {
         Binder {
             context: ctxt,
             program: program,
@@ -71,14 +92,28 @@ impl VertexAttributesSystem {
     pub fn purge_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
         VertexAttributesSystem::purge_if(ctxt, |&(ref buffers, _)| {
             buffers.iter().find(|&&(b, _)| b == id).is_some()
-        })
+        });
+
+        // the VAOs that only depend on the formats are kept, but they must detach the buffer,
+        // otherwise its storage would be kept alive by them
+        let formats = ctxt.vertex_array_objects.formats.borrow();
+        for (_, vao) in formats.iter() {
+            vao.forget_buffer(ctxt, id);
+        }
     }
 
     /// This function *must* be called whenever you destroy a program so that the system can
     /// purge its VAOs cache.
     #[inline]
     pub fn purge_program(ctxt: &mut CommandContext, program: Handle) {
-        VertexAttributesSystem::purge_if(ctxt, |&(_, p)| p == program)
+        VertexAttributesSystem::purge_if(ctxt, |&(_, p)| p == program);
+
+        let mut formats = ctxt.vertex_array_objects.formats.borrow_mut();
+
+        let keys = formats.keys().filter(|&&(_, p)| p == program).cloned().collect::<Vec<_>>();
+        for key in keys {
+            formats.remove(&key).unwrap().destroy(ctxt);
+        }
     }
 
     /// Purges the VAOs cache.
@@ -89,6 +124,13 @@ impl VertexAttributesSystem {
         for (_, vao) in vaos {
             vao.destroy(ctxt);
         }
+
+        let formats = mem::replace(&mut *ctxt.vertex_array_objects.formats.borrow_mut(),
+                                   HashMap::new());
+
+        for (_, vao) in formats {
+            vao.destroy(ctxt);
+        }
     }
 
     /// Purges the VAOs cache. Contrary to `purge_all`, this function expects the system to be
@@ -100,6 +142,13 @@ impl VertexAttributesSystem {
         for (_, vao) in vaos {
             vao.destroy(ctxt);
         }
+
+        let formats = mem::replace(&mut *ctxt.vertex_array_objects.formats.borrow_mut(),
+                                   HashMap::with_capacity(0));
+
+        for (_, vao) in formats {
+            vao.destroy(ctxt);
+        }
     }
 
     /// Tells the VAOs system that the currently binded element array buffer will change.
@@ -112,6 +161,15 @@ impl VertexAttributesSystem {
                 return;
             }
         }
+
+        let formats = ctxt.vertex_array_objects.formats.borrow_mut();
+
+        for (_, vao) in formats.iter() {
+            if vao.id == ctxt.state.vertex_array {
+                vao.element_array_buffer_hijacked.set(true);
+                return;
+            }
+        }
     }
 
     /// Purges VAOs that match a certain condition.
@@ -159,7 +217,9 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
     /// Finish binding the vertex attributes.
     ///
     /// If `base_vertex` was set to true, returns the base vertex to use when drawing.
-    pub fn bind(mut self) -> Option<gl::types::GLint> {
+    ///
+    /// Returns an error if the vertex sources don't match the attributes of the program.
+    pub fn bind(mut self) -> Result<Option<gl::types::GLint>, DrawError> {
         let ctxt = self.context;
 
         if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
@@ -185,10 +245,45 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
                 }
             }
 
+            // if the format of the attributes can be separated from the buffers, we only need
+            // one VAO per list of formats
+            if ctxt.version >= &Version(Api::Gl, 4, 3) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+               ctxt.extensions.gl_arb_vertex_attrib_binding
+            {
+                let formats_list: Vec<_> = self.vertex_buffers.iter()
+                                                              .map(|&(_, ref f, _, _, d)| {
+                                                                  (f.clone(), d)
+                                                              })
+                                                              .collect();
+                let key = (formats_list, self.program.get_id());
+
+                // trying to find an existing VAO in the cache
+                if let Some(value) = ctxt.vertex_array_objects.formats.borrow().get(&key) {
+                    value.bind(ctxt);
+                    value.bind_vertex_buffers(ctxt, &self.vertex_buffers,
+                                              self.element_array_buffer);
+                    return Ok(base_vertex.map(|v| v as gl::types::GLint));
+                }
+
+                // if not found, building a new one
+                try!(check_attributes(&vertex_formats(&self.vertex_buffers), self.program));
+                let new_vao = unsafe {
+                    VertexArrayObject::with_separate_format(ctxt, &self.vertex_buffers,
+                                                            self.program)
+                };
+
+                new_vao.bind(ctxt);
+                new_vao.bind_vertex_buffers(ctxt, &self.vertex_buffers, self.element_array_buffer);
+                ctxt.vertex_array_objects.formats.borrow_mut().insert(key, new_vao);
+
+                return Ok(base_vertex.map(|v| v as gl::types::GLint));
+            }
+
             let mut buffers_list: Vec<_> = self.vertex_buffers.iter()
                                                               .map(|&(v, _, o, s, _)| (v, o))
                                                               .collect();
-            buffers_list.push((self.element_array_buffer.map(|b| b.get_buffer_id()).unwrap_or(0), 0));
+            buffers_list.push((self.element_array_buffer, 0));
             buffers_list.sort();
 
             let program_id = self.program.get_id();
@@ -198,10 +293,11 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
                                      .get(&(buffers_list.clone(), program_id))
             {
                 value.bind(ctxt);
-                return base_vertex.map(|v| v as gl::types::GLint);
+                return Ok(base_vertex.map(|v| v as gl::types::GLint));
             }
 
             // if not found, building a new one
+            try!(check_attributes(&vertex_formats(&self.vertex_buffers), self.program));
             let new_vao = unsafe {
                 VertexArrayObject::new(ctxt, &self.vertex_buffers,
                                        self.element_array_buffer, self.program)
@@ -210,16 +306,18 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
             new_vao.bind(ctxt);
             ctxt.vertex_array_objects.vaos.borrow_mut().insert((buffers_list, program_id), new_vao);
 
-            base_vertex.map(|v| v as gl::types::GLint)
+            Ok(base_vertex.map(|v| v as gl::types::GLint))
 
         } else {
             // VAOs are not supported
 
+            try!(check_attributes(&vertex_formats(&self.vertex_buffers), self.program));
+
             // just in case
             bind_vao(ctxt, 0);
 
-            if let Some(element_array_buffer) = self.element_array_buffer {
-                element_array_buffer.bind_to_element_array(ctxt);
+            if self.element_array_buffer != 0 {
+                unsafe { bind_element_array_buffer(ctxt, self.element_array_buffer) };
             }
 
             for (vertex_buffer, bindings, offset, stride, divisor) in self.vertex_buffers.into_iter() {
@@ -232,9 +330,9 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
             // TODO: it is unlikely that a backend supports base vertex but not VAOs, so we just
             //       ignore this case ; however it would ideally be better to handle it
             if self.base_vertex {
-                Some(0)
+                Ok(Some(0))
             } else {
-                None
+                Ok(None)
             }
         }
     }
@@ -244,77 +342,33 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
 struct VertexArrayObject {
     id: gl::types::GLuint,
     destroyed: bool,
-    element_array_buffer: gl::types::GLuint,
+    element_array_buffer: Cell<gl::types::GLuint>,
     element_array_buffer_hijacked: Cell<bool>,
+
+    // for VAOs created with `with_separate_format`, the buffer, offset and stride currently
+    // binded to each binding index ; empty otherwise
+    vertex_buffers: RefCell<SmallVec<[(gl::types::GLuint, usize, usize); 2]>>,
 }
 
 impl VertexArrayObject {
     /// Builds a new `VertexArrayObject`.
     ///
     /// The vertex buffer, index buffer and program must not outlive the
-    /// VAO, and the VB & program attributes must not change.
+    /// VAO, and the VB & program attributes must not change. The attributes must have been
+    /// checked with `check_attributes`.
     unsafe fn new(mut ctxt: &mut CommandContext,
                   vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)],
-                  index_buffer: Option<BufferAnySlice>, program: &Program) -> VertexArrayObject
+                  index_buffer: gl::types::GLuint, program: &Program) -> VertexArrayObject
     {
-        // checking the attributes types
-        for &(_, ref bindings, _, _, _) in vertex_buffers {
-            for &(ref name, _, ty) in bindings.iter() {
-                let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
-                    Some(a) => a,
-                    None => continue
-                };
-
-                if ty.get_num_components() != attribute.ty.get_num_components() ||
-                    attribute.size != 1
-                {
-                    panic!("The program attribute `{}` does not match the vertex format. \
-                            Program expected {:?}, got {:?}.", name, attribute.ty, ty);
-                }
-            }
-        }
-
-        // checking for missing attributes
-        for (&ref name, _) in program.attributes() {
-            let mut found = false;
-            for &(_, ref bindings, _, _, _) in vertex_buffers {
-                if bindings.iter().find(|&&(ref n, _, _)| n == name).is_some() {
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                panic!("The program attribute `{}` is missing in the vertex bindings", name);
-            }
-        };
-
-        // TODO: check for collisions between the vertices sources
-
-        // building the VAO
-        let id = {
-            let mut id = mem::uninitialized();
-            if ctxt.version >= &Version(Api::Gl, 3, 0) ||
-                ctxt.version >= &Version(Api::GlEs, 3, 0) ||
-                ctxt.extensions.gl_arb_vertex_array_object
-            {
-                ctxt.gl.GenVertexArrays(1, &mut id);
-            } else if ctxt.extensions.gl_oes_vertex_array_object {
-                ctxt.gl.GenVertexArraysOES(1, &mut id);
-            } else if ctxt.extensions.gl_apple_vertex_array_object {
-                ctxt.gl.GenVertexArraysAPPLE(1, &mut id);
-            } else {
-                unreachable!();
-            };
-            id
-        };
+        let id = gen_vao(ctxt);
 
         // we don't use DSA as we're going to make multiple calls for this VAO
         // and we're likely going to use the VAO right after it's been created
         bind_vao(&mut ctxt, id);
 
         // binding index buffer
-        if let Some(index_buffer) = index_buffer {
-            index_buffer.bind_to_element_array(&mut ctxt);
+        if index_buffer != 0 {
+            bind_element_array_buffer(&mut ctxt, index_buffer);
         }
 
         for &(vertex_buffer, ref bindings, offset, stride, divisor) in vertex_buffers {
@@ -324,8 +378,175 @@ impl VertexArrayObject {
         VertexArrayObject {
             id: id,
             destroyed: false,
-            element_array_buffer: index_buffer.map(|b| b.get_buffer_id()).unwrap_or(0),
+            element_array_buffer: Cell::new(index_buffer),
+            element_array_buffer_hijacked: Cell::new(false),
+            vertex_buffers: RefCell::new(SmallVec::new()),
+        }
+    }
+
+    /// Builds a new `VertexArrayObject` that only depends on the formats of the vertex sources
+    /// and on the program.
+    ///
+    /// The buffers are not binded. You must call `bind_vertex_buffers` after `bind`.
+    ///
+    /// The backend must support `ARB_vertex_attrib_binding`.
+    unsafe fn with_separate_format(mut ctxt: &mut CommandContext,
+                                   vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize,
+                                                      usize, Option<u32>)],
+                                   program: &Program) -> VertexArrayObject
+    {
+        // with DSA, the VAO is modified without being binded
+        let dsa = is_dsa_supported(ctxt);
+
+        let id = if dsa {
+            let mut id = mem::uninitialized();
+            ctxt.gl.CreateVertexArrays(1, &mut id);
+            id
+        } else {
+            let id = gen_vao(ctxt);
+            bind_vao(&mut ctxt, id);
+            id
+        };
+
+        let mut bindings_cache = SmallVec::new();
+
+        for (binding_index, &(_, ref bindings, _, _, divisor)) in vertex_buffers.iter().enumerate() {
+            bind_attribute_format(ctxt, if dsa { Some(id) } else { None }, program,
+                                  binding_index as gl::types::GLuint, bindings, divisor);
+            bindings_cache.push((0, 0, 0));
+        }
+
+        VertexArrayObject {
+            id: id,
+            destroyed: false,
+            element_array_buffer: Cell::new(0),
             element_array_buffer_hijacked: Cell::new(false),
+            vertex_buffers: RefCell::new(bindings_cache),
+        }
+    }
+
+    /// Binds the vertex buffers and the index buffer to this VAO, which must have been created
+    /// with `with_separate_format`.
+    ///
+    /// The VAO must be the current VAO, unless the backend supports DSA.
+    fn bind_vertex_buffers(&self, ctxt: &mut CommandContext,
+                           vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize,
+                                              Option<u32>)],
+                           index_buffer: gl::types::GLuint)
+    {
+        let dsa = is_dsa_supported(ctxt);
+        debug_assert!(dsa || ctxt.state.vertex_array == self.id);
+
+        let multi_bind = ctxt.version >= &Version(Api::Gl, 4, 4) ||
+                         ctxt.extensions.gl_arb_multi_bind;
+
+        let mut current = self.vertex_buffers.borrow_mut();
+
+        // range of binding indices that have been modified, when using multi-bind
+        let mut modified: Option<(usize, usize)> = None;
+
+        for (binding_index, (&(id, _, offset, stride, _), current)) in
+            vertex_buffers.iter().zip(current.iter_mut()).enumerate()
+        {
+            if *current != (id, offset, stride) {
+                if multi_bind {
+                    modified = Some(match modified {
+                        Some((first, _)) => (first, binding_index),
+                        None => (binding_index, binding_index),
+                    });
+                    *current = (id, offset, stride);
+                    continue;
+                }
+
+                unsafe {
+                    if dsa {
+                        ctxt.gl.VertexArrayVertexBuffer(self.id,
+                                                        binding_index as gl::types::GLuint, id,
+                                                        offset as gl::types::GLintptr,
+                                                        stride as gl::types::GLsizei);
+                    } else {
+                        ctxt.gl.BindVertexBuffer(binding_index as gl::types::GLuint, id,
+                                                 offset as gl::types::GLintptr,
+                                                 stride as gl::types::GLsizei);
+                    }
+                }
+                *current = (id, offset, stride);
+            }
+        }
+
+        // all the modified bindings are sent with one call, the bindings in between are
+        // rebinded with the same content
+        if let Some((first, last)) = modified {
+            let range = &current[first .. last + 1];
+            let buffers = range.iter().map(|b| b.0).collect::<Vec<_>>();
+            let offsets = range.iter().map(|b| b.1 as gl::types::GLintptr).collect::<Vec<_>>();
+            let strides = range.iter().map(|b| b.2 as gl::types::GLsizei).collect::<Vec<_>>();
+
+            unsafe {
+                if dsa {
+                    ctxt.gl.VertexArrayVertexBuffers(self.id, first as gl::types::GLuint,
+                                                     range.len() as gl::types::GLsizei,
+                                                     buffers.as_ptr(), offsets.as_ptr(),
+                                                     strides.as_ptr());
+                } else {
+                    ctxt.gl.BindVertexBuffers(first as gl::types::GLuint,
+                                              range.len() as gl::types::GLsizei,
+                                              buffers.as_ptr(), offsets.as_ptr(),
+                                              strides.as_ptr());
+                }
+            }
+        }
+
+        if self.element_array_buffer.get() != index_buffer {
+            unsafe {
+                if dsa {
+                    ctxt.gl.VertexArrayElementBuffer(self.id, index_buffer);
+                } else {
+                    bind_element_array_buffer(ctxt, index_buffer);
+                }
+            }
+            self.element_array_buffer.set(index_buffer);
+        }
+    }
+
+    /// Detaches this buffer from this VAO, which must have been created with
+    /// `with_separate_format`.
+    ///
+    /// Must be called when a buffer is destroyed. OpenGL only detaches a deleted buffer from the
+    /// current VAO, and the ID may later be reused.
+    fn forget_buffer(&self, ctxt: &mut CommandContext, id: gl::types::GLuint) {
+        let dsa = is_dsa_supported(ctxt);
+
+        for (binding_index, binding) in self.vertex_buffers.borrow_mut().iter_mut().enumerate() {
+            if binding.0 != id {
+                continue;
+            }
+
+            unsafe {
+                if dsa {
+                    ctxt.gl.VertexArrayVertexBuffer(self.id, binding_index as gl::types::GLuint,
+                                                    0, 0, 0);
+                } else {
+                    bind_vao(ctxt, self.id);
+                    ctxt.gl.BindVertexBuffer(binding_index as gl::types::GLuint, 0, 0, 0);
+                }
+            }
+
+            *binding = (0, 0, 0);
+        }
+
+        if self.element_array_buffer.get() == id {
+            unsafe {
+                if dsa {
+                    ctxt.gl.VertexArrayElementBuffer(self.id, 0);
+                } else {
+                    bind_vao(ctxt, self.id);
+                    bind_element_array_buffer(ctxt, 0);
+                }
+            }
+
+            self.element_array_buffer.set(0);
+            self.element_array_buffer_hijacked.set(false);
         }
     }
 
@@ -335,17 +556,7 @@ impl VertexArrayObject {
             bind_vao(ctxt, self.id);
 
             if self.element_array_buffer_hijacked.get() {
-                // TODO: use a proper function
-                if ctxt.version >= &Version(Api::Gl, 1, 5) ||
-                    ctxt.version >= &Version(Api::GlEs, 2, 0)
-                {
-                    ctxt.gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.element_array_buffer);
-                } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-                    ctxt.gl.BindBufferARB(gl::ELEMENT_ARRAY_BUFFER_ARB, self.element_array_buffer);
-                } else {
-                    unreachable!();
-                }
-
+                bind_element_array_buffer(ctxt, self.element_array_buffer.get());
                 self.element_array_buffer_hijacked.set(false);
             }
         }
@@ -393,6 +604,89 @@ impl GlObject for VertexArrayObject {
     }
 }
 
+/// Returns the formats of a list of vertex sources.
+#[inline]
+fn vertex_formats(vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize,
+                                     Option<u32>)])
+                  -> SmallVec<[&VertexFormat; 2]>
+{
+    vertex_buffers.iter().map(|&(_, ref format, _, _, _)| format).collect()
+}
+
+/// Checks that the formats of the vertex sources match the attributes of the program.
+///
+/// Returns an error if an attribute of the program is missing or doesn't match the
+/// vertex format.
+pub fn check_attributes(vertex_formats: &[&VertexFormat], program: &Program)
+                        -> Result<(), DrawError>
+{
+    // checking the attributes types
+    for bindings in vertex_formats {
+        for &(ref name, _, ty, _) in bindings.iter() {
+            let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+                Some(a) => a,
+                None => continue
+            };
+
+            if ty.get_num_components() != attribute.ty.get_num_components() ||
+                attribute.size != 1
+            {
+                return Err(DrawError::AttributeTypeMismatch {
+                    name: name.clone().into_owned(),
+                    expected: attribute.ty,
+                    obtained: ty,
+                    program: program.get_label(),
+                });
+            }
+        }
+    }
+
+    // checking for missing attributes
+    for (&ref name, _) in program.attributes() {
+        let mut found = false;
+        for bindings in vertex_formats {
+            if bindings.iter().find(|&&(ref n, _, _, _)| n == name).is_some() {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return Err(DrawError::AttributeMissing {
+                name: name.clone(),
+                program: program.get_label(),
+            });
+        }
+    };
+
+    // TODO: check for collisions between the vertices sources
+
+    Ok(())
+}
+
+/// Generates a new vertex array object.
+///
+/// ## Panic
+///
+/// Panics if the backend doesn't support vertex array objects.
+unsafe fn gen_vao(ctxt: &mut CommandContext) -> gl::types::GLuint {
+    let mut id = mem::uninitialized();
+
+    if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+        ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+        ctxt.extensions.gl_arb_vertex_array_object
+    {
+        ctxt.gl.GenVertexArrays(1, &mut id);
+    } else if ctxt.extensions.gl_oes_vertex_array_object {
+        ctxt.gl.GenVertexArraysOES(1, &mut id);
+    } else if ctxt.extensions.gl_apple_vertex_array_object {
+        ctxt.gl.GenVertexArraysAPPLE(1, &mut id);
+    } else {
+        unreachable!();
+    }
+
+    id
+}
+
 fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types::GLint, gl::types::GLint) {
     match ty {
         AttributeType::I8 => (gl::BYTE, 1, 1),
@@ -472,6 +766,7 @@ fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types
         AttributeType::U10U10U10U2 => (gl::UNSIGNED_INT_10_10_10_2_OES, 1, 1),
         AttributeType::F10F11F11UnsignedIntReversed => (gl::UNSIGNED_INT_10F_11F_11F_REV, 1, 1),
         AttributeType::FixedFloatI16U16 => (gl::FIXED, 1, 1),
+        AttributeType::U8U8U8U8Bgra => (gl::UNSIGNED_BYTE, gl::BGRA as gl::types::GLint, 1),
     }
 }
 
@@ -499,6 +794,19 @@ fn bind_vao(ctxt: &mut CommandContext, vao_id: gl::types::GLuint) {
     }
 }
 
+/// Binds a buffer to `GL_ELEMENT_ARRAY_BUFFER`, which modifies the state of the current VAO.
+unsafe fn bind_element_array_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+        ctxt.version >= &Version(Api::GlEs, 2, 0)
+    {
+        ctxt.gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, id);
+    } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+        ctxt.gl.BindBufferARB(gl::ELEMENT_ARRAY_BUFFER_ARB, id);
+    } else {
+        unreachable!();
+    }
+}
+
 /// Binds an individual attribute to the current VAO.
 unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
                          vertex_buffer: gl::types::GLuint, bindings: &VertexFormat,
@@ -520,7 +828,7 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
     }
 
     // binding attributes
-    for &(ref name, offset, ty) in bindings.iter() {
+    for &(ref name, offset, ty, normalize) in bindings.iter() {
         let (data_type, elements_count, instances_count) = vertex_binding_type_to_gl(ty);
 
         let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
@@ -529,6 +837,9 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
         };
 
         if attribute.location != -1 {
+            // matrices use one location per column ; this is the size in bytes of each column
+            let column_size = ty.get_size_bytes() / instances_count as usize;
+
             let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute.ty);
             match attribute_ty {
                 gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
@@ -539,11 +850,19 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
                                                  (buffer_offset + offset) as *const libc::c_void),
 
                 gl::FLOAT => {
+                    // `GL_BGRA` attributes must always be normalized
+                    let normalized = if normalize || ty == AttributeType::U8U8U8U8Bgra {
+                        gl::TRUE
+                    } else {
+                        gl::FALSE
+                    };
+
                     for i in 0..instances_count {
                         ctxt.gl.VertexAttribPointer((attribute.location + i) as u32,
-                                                    elements_count as gl::types::GLint, data_type, 0,
+                                                    elements_count as gl::types::GLint, data_type,
+                                                    normalized,
                                                     stride as i32,
-                                                    (buffer_offset + offset + (i * elements_count * 4) as usize) as *const libc::c_void)
+                                                    (buffer_offset + offset + i as usize * column_size) as *const libc::c_void)
                     }
                 },
 
@@ -552,7 +871,7 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
                         ctxt.gl.VertexAttribLPointer((attribute.location + i) as u32,
                                                      elements_count as gl::types::GLint, data_type,
                                                      stride as i32,
-                                                     (buffer_offset + offset + (i * elements_count * 8) as usize) as *const libc::c_void)
+                                                     (buffer_offset + offset + i as usize * column_size) as *const libc::c_void)
                     }
                 },
 
@@ -568,3 +887,99 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
         }
     }
 }
+
+/// Specifies the format of the attributes of a vertex source and associates them to a binding
+/// index of a VAO.
+///
+/// If `vao` is `None`, the current VAO is modified. Otherwise the given VAO is modified with
+/// DSA, which must be supported by the backend.
+///
+/// The backend must support `ARB_vertex_attrib_binding`.
+unsafe fn bind_attribute_format(ctxt: &mut CommandContext, vao: Option<gl::types::GLuint>,
+                                program: &Program, binding_index: gl::types::GLuint,
+                                bindings: &VertexFormat, divisor: Option<u32>)
+{
+    for &(ref name, offset, ty, normalize) in bindings.iter() {
+        let (data_type, elements_count, instances_count) = vertex_binding_type_to_gl(ty);
+
+        let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+            Some(a) => a,
+            None => continue
+        };
+
+        if attribute.location == -1 {
+            continue;
+        }
+
+        // matrices use one location per column ; this is the size in bytes of each column
+        let column_size = ty.get_size_bytes() / instances_count as usize;
+
+        let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute.ty);
+
+        for i in 0..instances_count {
+            let location = (attribute.location + i) as gl::types::GLuint;
+            let offset = (offset + i as usize * column_size) as gl::types::GLuint;
+
+            match (attribute_ty, vao) {
+                (gl::BYTE, None) | (gl::UNSIGNED_BYTE, None) | (gl::SHORT, None) |
+                (gl::UNSIGNED_SHORT, None) | (gl::INT, None) | (gl::UNSIGNED_INT, None) =>
+                    ctxt.gl.VertexAttribIFormat(location, elements_count, data_type, offset),
+
+                (gl::BYTE, Some(vao)) | (gl::UNSIGNED_BYTE, Some(vao)) |
+                (gl::SHORT, Some(vao)) | (gl::UNSIGNED_SHORT, Some(vao)) |
+                (gl::INT, Some(vao)) | (gl::UNSIGNED_INT, Some(vao)) =>
+                    ctxt.gl.VertexArrayAttribIFormat(vao, location, elements_count, data_type,
+                                                     offset),
+
+                (gl::FLOAT, vao) => {
+                    // `GL_BGRA` attributes must always be normalized
+                    let normalized = if normalize || ty == AttributeType::U8U8U8U8Bgra {
+                        gl::TRUE
+                    } else {
+                        gl::FALSE
+                    };
+
+                    if let Some(vao) = vao {
+                        ctxt.gl.VertexArrayAttribFormat(vao, location, elements_count, data_type,
+                                                        normalized, offset);
+                    } else {
+                        ctxt.gl.VertexAttribFormat(location, elements_count, data_type,
+                                                   normalized, offset);
+                    }
+                },
+
+                (gl::DOUBLE, None) | (gl::INT64_NV, None) | (gl::UNSIGNED_INT64_NV, None) =>
+                    ctxt.gl.VertexAttribLFormat(location, elements_count, data_type, offset),
+
+                (gl::DOUBLE, Some(vao)) | (gl::INT64_NV, Some(vao)) |
+                (gl::UNSIGNED_INT64_NV, Some(vao)) =>
+                    ctxt.gl.VertexArrayAttribLFormat(vao, location, elements_count, data_type,
+                                                     offset),
+
+                _ => unreachable!()
+            }
+
+            if let Some(vao) = vao {
+                ctxt.gl.VertexArrayAttribBinding(vao, location, binding_index);
+                ctxt.gl.EnableVertexArrayAttrib(vao, location);
+            } else {
+                ctxt.gl.VertexAttribBinding(location, binding_index);
+                ctxt.gl.EnableVertexAttribArray(location);
+            }
+        }
+    }
+
+    if let Some(divisor) = divisor {
+        if let Some(vao) = vao {
+            ctxt.gl.VertexArrayBindingDivisor(vao, binding_index, divisor);
+        } else {
+            ctxt.gl.VertexBindingDivisor(binding_index, divisor);
+        }
+    }
+}
+
+/// Returns true if VAOs can be created and modified with DSA.
+#[inline]
+fn is_dsa_supported(ctxt: &CommandContext) -> bool {
+    ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access
+}
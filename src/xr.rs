@@ -0,0 +1,149 @@
+#![cfg(feature = "xr")]
+/*!
+
+Renders to the swapchains of OpenXR and OpenVR runtimes.
+
+VR runtimes allocate the images that are shown in the headset themselves, and give their OpenGL
+texture ids to the application. A `Swapchain` wraps these ids as `Texture2d`s that glium never
+destroys, so that they can be drawn on like any other texture.
+
+Before an image is handed back to the compositor (with `xrReleaseSwapchainImage` or
+`IVRCompositor::Submit`), the commands that draw on it must have been submitted to the GPU.
+`Swapchain::release_image` takes care of this, and lets the runtime call OpenGL functions
+without breaking glium's state cache.
+
+# Features
+
+Only available if the 'xr' feature is enabled. This module doesn't depend on any OpenXR or
+OpenVR binding.
+
+# Example
+
+```no_run
+# use glium::Surface;
+use glium::xr::{CompositorSync, Swapchain};
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let ids: Vec<u32> = vec![];
+# fn acquire_image() -> usize { 0 } fn release_image() {}
+
+// `ids` are the texture ids enumerated with `xrEnumerateSwapchainImages`
+let swapchain = unsafe {
+    Swapchain::new(&display, &ids, glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                   1440, 1600)
+};
+
+// in the rendering loop
+let index = acquire_image();
+swapchain.as_surface(index).clear_color(0.0, 0.0, 0.0, 1.0);
+swapchain.release_image(index, CompositorSync::Flush, || release_image());
+```
+
+*/
+use backend::Facade;
+use context::Context;
+use framebuffer::SimpleFrameBuffer;
+use sync::SyncFence;
+use texture::{Dimensions, MipmapsOption, Texture2d, UncompressedFloatFormat};
+
+use gl;
+
+use std::rc::Rc;
+
+/// How the commands that draw on an image are synchronized with the compositor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompositorSync {
+    /// Flushes the commands, so that they are executed before the commands that the compositor
+    /// submits from the same context or from a context that waits for this one.
+    ///
+    /// This is what OpenXR requires before calling `xrReleaseSwapchainImage`.
+    Flush,
+
+    /// Inserts a fence and waits until the GPU has reached it. This is needed when the
+    /// compositor reads the image without synchronizing with the OpenGL context, for example
+    /// from another graphics API.
+    ///
+    /// If the backend doesn't support fences, waits with `glFinish` instead.
+    Wait,
+}
+
+/// The images of a swapchain that belongs to a VR runtime.
+pub struct Swapchain {
+    context: Rc<Context>,
+    images: Vec<Texture2d>,
+}
+
+impl Swapchain {
+    /// Wraps the texture ids of the images of a swapchain.
+    ///
+    /// For an sRGB swapchain, pass the linear format with the same layout. The conversion
+    /// depends on the actual format of the textures.
+    ///
+    /// # Safety
+    ///
+    /// The ids must be two-dimensional textures of this context, or of a context that shares
+    /// its objects, with the given format and dimensions. The runtime must not destroy them
+    /// before the `Swapchain` is dropped.
+    pub unsafe fn new<F>(facade: &F, ids: &[gl::types::GLuint], format: UncompressedFloatFormat,
+                         width: u32, height: u32) -> Swapchain
+                         where F: Facade
+    {
+        let images = ids.iter().map(|&id| {
+            Texture2d::from_id(facade, format, id, false, MipmapsOption::NoMipmap,
+                               Dimensions::Texture2d { width: width, height: height })
+        }).collect();
+
+        Swapchain {
+            context: facade.get_context().clone(),
+            images: images,
+        }
+    }
+
+    /// Returns the number of images in the swapchain.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Returns the image with the given index, as returned by the runtime when acquiring it.
+    #[inline]
+    pub fn get_image(&self, index: usize) -> Option<&Texture2d> {
+        self.images.get(index)
+    }
+
+    /// Starts drawing on an image.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of range.
+    #[inline]
+    pub fn as_surface(&self, index: usize) -> SimpleFrameBuffer {
+        self.images[index].as_surface()
+    }
+
+    /// Submits the commands that draw on an image, then calls `release` to hand the image to
+    /// the compositor.
+    ///
+    /// `release` is allowed to call OpenGL functions and to modify the OpenGL state, which some
+    /// runtimes do. glium restores the state that it expects afterwards.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of range.
+    pub fn release_image<T, R>(&self, index: usize, sync: CompositorSync, release: R) -> T
+                               where R: FnOnce() -> T
+    {
+        assert!(index < self.images.len(), "The image index is out of range");
+
+        match sync {
+            CompositorSync::Flush => self.context.flush(),
+            CompositorSync::Wait => {
+                match SyncFence::new(&self.context) {
+                    Ok(fence) => fence.wait(),
+                    Err(_) => self.context.finish(),
+                }
+            },
+        }
+
+        unsafe { self.context.with_foreign_gl(release) }
+    }
+}
@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate glium;
+#[cfg(feature = "half")]
+extern crate half;
 
 use glium::Surface;
 use glium::index::PrimitiveType;
@@ -7,7 +9,6 @@ use glium::index::PrimitiveType;
 mod support;
 
 #[test]
-#[should_panic(expected = "The program attribute `field1` does not match the vertex format")]
 fn attribute_types_mismatch() {
     let display = support::build_display();
 
@@ -46,15 +47,18 @@ fn attribute_types_mismatch() {
 
     // drawing a frame
     let mut target = display.draw();
-    target.draw(&vertex_buffer, &index_buffer, &program, &glium::uniforms::EmptyUniforms,
-                &Default::default()).unwrap();
+    match target.draw(&vertex_buffer, &index_buffer, &program, &glium::uniforms::EmptyUniforms,
+                      &Default::default())
+    {
+        Err(glium::DrawError::AttributeTypeMismatch { ref name, .. }) if name == "field1" => (),
+        a => panic!("{:?}", a)
+    };
     target.finish().unwrap();
 
     display.assert_no_error(None);
 }
 
 #[test]
-#[should_panic(expected = "The program attribute `field2` is missing in the vertex bindings")]
 fn missing_attribute() {
     let display = support::build_display();
 
@@ -93,13 +97,245 @@ fn missing_attribute() {
 
     // drawing a frame
     let mut target = display.draw();
-    target.draw(&vertex_buffer, &index_buffer, &program, &glium::uniforms::EmptyUniforms,
-                &Default::default()).unwrap();
+    match target.draw(&vertex_buffer, &index_buffer, &program, &glium::uniforms::EmptyUniforms,
+                      &Default::default())
+    {
+        Err(glium::DrawError::AttributeMissing { ref name, .. }) if name == "field2" => (),
+        a => panic!("{:?}", a)
+    };
     target.finish().unwrap();
 
     display.assert_no_error(None);
 }
 
+#[test]
+fn attribute_bgra() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: glium::vertex::Bgra,
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let color = glium::vertex::Bgra { b: 0, g: 0, r: 255, a: 255 };
+
+    let vertex_buffer = match glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0], color: color },
+        Vertex { position: [ 1.0,  1.0], color: color },
+        Vertex { position: [-1.0, -1.0], color: color },
+        Vertex { position: [ 1.0, -1.0], color: color },
+    ]) {
+        Err(glium::vertex::BufferCreationError::FormatNotSupported) => return,
+        b => b.unwrap()
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(feature = "half")]
+fn attribute_f16() {
+    use half::f16;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f16; 2],
+        color: [f16; 4],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let one = f16::from_f32(1.0);
+    let minus_one = f16::from_f32(-1.0);
+    let zero = f16::from_f32(0.0);
+    let color = [one, zero, zero, one];
+
+    let vertex_buffer = match glium::VertexBuffer::new(&display, &[
+        Vertex { position: [minus_one, one], color: color },
+        Vertex { position: [one, one], color: color },
+        Vertex { position: [minus_one, minus_one], color: color },
+        Vertex { position: [one, minus_one], color: color },
+    ]) {
+        Err(glium::vertex::BufferCreationError::FormatNotSupported) => return,
+        b => b.unwrap()
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn attribute_normalized() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [u8; 4],
+    }
+
+    implement_vertex!(Vertex, position, color normalize(true));
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0], color: [255, 0, 0, 255] },
+        Vertex { position: [ 1.0,  1.0], color: [255, 0, 0, 255] },
+        Vertex { position: [-1.0, -1.0], color: [255, 0, 0, 255] },
+        Vertex { position: [ 1.0, -1.0], color: [255, 0, 0, 255] },
+    ]).unwrap();
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec4 color;
+
+                varying lowp vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
 macro_rules! attribute_test(
     ($name:ident, $attr_ty:ty, $glsl_ty:expr, $value:expr, $gl_pos:expr) => (
         #[test]
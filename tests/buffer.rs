@@ -977,3 +977,54 @@ fn copy_to_slice() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn write_coalescing() {
+    let display = support::build_display();
+
+    let buf = glium::buffer::BufferView::<[u8]>::new(&display, &[0; 8],
+                                                     glium::buffer::BufferType::ArrayBuffer,
+                                                     BufferMode::Dynamic).unwrap();
+
+    buf.set_write_coalescing(true);
+    assert!(buf.uses_write_coalescing());
+
+    buf.slice(0 .. 2).unwrap().write(&[1, 2]);
+    buf.slice(6 .. 8).unwrap().write(&[7, 8]);
+    buf.slice(2 .. 4).unwrap().write(&[3, 4]);
+    buf.slice(1 .. 3).unwrap().write(&[5, 6]);
+
+    let result = match buf.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(result, [1, 5, 6, 4, 0, 0, 7, 8]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn write_coalescing_disabled_flushes() {
+    let display = support::build_display();
+
+    let buf = glium::buffer::BufferView::<[u8]>::new(&display, &[0; 4],
+                                                     glium::buffer::BufferType::ArrayBuffer,
+                                                     BufferMode::Immutable).unwrap();
+
+    buf.set_write_coalescing(true);
+    buf.slice(1 .. 3).unwrap().write(&[1, 2]);
+    buf.set_write_coalescing(false);
+    buf.slice(3 .. 4).unwrap().write(&[3]);
+
+    let result = match buf.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(result, [0, 1, 2, 3]);
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,76 @@
+extern crate glium;
+
+use glium::command_encoder::{CommandQueue, InvalidResourceId, Resources};
+use std::thread;
+
+#[test]
+fn execution_order() {
+    let queue = CommandQueue::new();
+
+    let threads = (0 .. 4u64).map(|n| {
+        let queue = queue.clone();
+        thread::spawn(move || {
+            let mut encoder = queue.encoder(3 - n);
+            encoder.push((3 - n) * 10);
+            encoder.push((3 - n) * 10 + 1);
+            queue.submit(encoder);
+        })
+    }).collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let mut executed = Vec::new();
+    queue.execute(|command| -> Result<(), ()> {
+        executed.push(command);
+        Ok(())
+    }).unwrap();
+
+    assert_eq!(executed, vec![0, 1, 10, 11, 20, 21, 30, 31]);
+
+    // the queue is now empty
+    queue.execute(|_| -> Result<(), ()> { panic!() }).unwrap();
+}
+
+#[test]
+fn invalid_resource() {
+    let mut resources = Resources::new();
+    let a = resources.insert("a");
+    let b = resources.insert("b");
+
+    assert_eq!(resources.remove(a), Ok("a"));
+
+    let queue = CommandQueue::new();
+    let mut encoder = queue.encoder(0);
+    encoder.push(b);
+    encoder.push(a);
+    encoder.push(b);
+    queue.submit(encoder);
+
+    let mut executed = Vec::new();
+    let result = queue.execute(|id| -> Result<(), InvalidResourceId> {
+        executed.push(*try!(resources.get(id)));
+        Ok(())
+    });
+
+    assert_eq!(result, Err(InvalidResourceId(a)));
+    assert_eq!(executed, vec!["b"]);
+}
+
+#[test]
+fn resource_of_another_resources() {
+    let mut first = Resources::new();
+    let mut second = Resources::new();
+    let a = first.insert("a");
+    let b = second.insert("b");
+
+    // both ids have the same index in their `Resources`
+    assert_eq!(first.get(b), Err(InvalidResourceId(b)));
+    assert_eq!(second.get(a), Err(InvalidResourceId(a)));
+    assert!(!first.contains(b));
+    assert_eq!(first.remove(b), Err(InvalidResourceId(b)));
+
+    assert!(first.contains(a));
+    assert_eq!(first.get(a), Ok(&"a"));
+}
@@ -0,0 +1,95 @@
+#[macro_use]
+extern crate glium;
+
+use glium::buffer::{Buffer, BufferMode, BufferType};
+use glium::culling::{CullingBuffers, CullingError, GpuCuller, ObjectBounds};
+use glium::index::{DrawCommandIndices, DrawCommandsIndicesBuffer};
+
+mod support;
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn command(first_index: u32) -> DrawCommandIndices {
+    DrawCommandIndices {
+        count: 6,
+        instance_count: 1,
+        first_index: first_index,
+        base_vertex: 0,
+        base_instance: 0,
+    }
+}
+
+#[test]
+fn frustum_culling() {
+    let display = support::build_display();
+
+    let culler = match GpuCuller::new(&display) {
+        Ok(c) => c,
+        Err(_) => return
+    };
+
+    let bounds = [
+        ObjectBounds { center: [10.0, 0.0, 0.0], radius: 1.0 },
+        ObjectBounds { center: [0.0, 0.0, 0.0], radius: 0.5 },
+        ObjectBounds { center: [1.2, 0.0, 0.0], radius: 0.5 },
+    ];
+
+    let commands = [command(0), command(6), command(12)];
+
+    let objects = Buffer::new(&display, &bounds[..], BufferType::ShaderStorageBuffer,
+                              BufferMode::Default).unwrap();
+    let commands = Buffer::new(&display, &commands[..], BufferType::ShaderStorageBuffer,
+                               BufferMode::Default).unwrap();
+    let output = DrawCommandsIndicesBuffer::empty(&display, 3).unwrap();
+
+    culler.cull(CullingBuffers { objects: &objects, commands: &commands, output: &output },
+                IDENTITY).unwrap();
+
+    assert_eq!(culler.read_visible_count().unwrap(), 2);
+
+    let result = output.read().unwrap();
+
+    let mut visible = vec![result[0].first_index, result[1].first_index];
+    visible.sort();
+    assert_eq!(visible, vec![6, 12]);
+    assert_eq!(result[0].instance_count, 1);
+    assert_eq!(result[1].instance_count, 1);
+
+    assert_eq!(result[2].first_index, 0);
+    assert_eq!(result[2].instance_count, 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn length_mismatch() {
+    let display = support::build_display();
+
+    let culler = match GpuCuller::new(&display) {
+        Ok(c) => c,
+        Err(_) => return
+    };
+
+    let bounds = [ObjectBounds { center: [0.0, 0.0, 0.0], radius: 1.0 }];
+    let commands = [command(0), command(6)];
+
+    let objects = Buffer::new(&display, &bounds[..], BufferType::ShaderStorageBuffer,
+                              BufferMode::Default).unwrap();
+    let commands = Buffer::new(&display, &commands[..], BufferType::ShaderStorageBuffer,
+                               BufferMode::Default).unwrap();
+    let output = DrawCommandsIndicesBuffer::empty(&display, 2).unwrap();
+
+    match culler.cull(CullingBuffers { objects: &objects, commands: &commands, output: &output },
+                      IDENTITY)
+    {
+        Err(CullingError::LengthMismatch) => (),
+        a => panic!("{:?}", a)
+    };
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,55 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::debug_overlay::{Corner, DebugOverlay, OverlayOptions, OverlaySource};
+
+mod support;
+
+#[test]
+fn overlay_color_bottom_left() {
+    let display = support::build_display();
+    let overlay = DebugOverlay::new(&display).unwrap();
+
+    let source = glium::Texture2d::new(&display, vec![vec![(0u8, 255u8, 0u8, 255u8); 4]; 4])
+                                       .unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 1.0);
+    overlay.draw(&mut texture.as_surface(), OverlaySource::Color(&source),
+                 &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[10][10], (0, 255, 0, 255));
+    assert_eq!(data[10][1000], (0, 0, 0, 255));
+    assert_eq!(data[1000][10], (0, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn overlay_depth_top_right() {
+    let display = support::build_display();
+    let overlay = DebugOverlay::new(&display).unwrap();
+
+    let source = match glium::texture::DepthTexture2d::new(&display, vec![vec![0.5f32; 4]; 4]) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 1.0);
+
+    let options = OverlayOptions { corner: Corner::TopRight, size: 0.5, .. Default::default() };
+    overlay.draw(&mut texture.as_surface(),
+                 OverlaySource::Depth { texture: &source, linearize: None }, &options).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    let (r, g, b, _) = data[1000][1000];
+    assert!(r >= 126 && r <= 129);
+    assert_eq!(r, g);
+    assert_eq!(r, b);
+    assert_eq!(data[10][10], (0, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
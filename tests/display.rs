@@ -149,3 +149,458 @@ fn is_context_lost() {
     display.is_context_lost();
     display.assert_no_error(None);
 }
+
+#[test]
+fn context_lost_callback_not_called() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let display = support::build_display();
+
+    let called = Rc::new(Cell::new(false));
+    {
+        let called = called.clone();
+        display.on_context_lost(move || called.set(true));
+    }
+
+    assert!(!display.is_context_lost());
+    assert!(!called.get());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn debug_callback() {
+    use glium::debug::Severity;
+
+    let display = support::build_display();
+
+    if display.set_debug_callback(|_, _, _, _, _| ()).is_err() {
+        return;
+    }
+
+    display.set_debug_messages_enabled(None, None, Some(Severity::Notification), false).unwrap();
+    display.remove_debug_callback();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn error_check_policy() {
+    use glium::debug::ErrorCheckPolicy;
+
+    let display = support::build_display();
+
+    let previous = display.get_error_check_policy();
+    display.set_error_check_policy(ErrorCheckPolicy::EveryCall);
+    assert_eq!(display.get_error_check_policy(), ErrorCheckPolicy::EveryCall);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    display.set_error_check_policy(previous);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn error_check_policy_reports_errors() {
+    use glium::debug::{ErrorCheckPolicy, MessageType, Source};
+    use glium::backend::StateCategories;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let display = support::build_display();
+
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let errors2 = errors.clone();
+    let result = display.set_debug_callback(move |source, ty, _, id, message| {
+        if source == Source::Api && ty == MessageType::Error {
+            errors2.borrow_mut().push((id, message.to_string()));
+        }
+    });
+
+    if result.is_err() {
+        return;
+    }
+
+    let previous = display.get_error_check_policy();
+    display.set_error_check_policy(ErrorCheckPolicy::EveryCall);
+
+    // `0xdead` is not a valid bind point
+    unsafe {
+        display.exec_with_raw_gl(StateCategories::none(), |gl| gl.BindBuffer(0xdead, 0));
+    }
+
+    display.set_error_check_policy(previous);
+    display.remove_debug_callback();
+
+    // the implementation can also send its own message about the error
+    let errors = errors.borrow();
+    assert!(errors.iter().any(|&(id, ref message)| {
+        id == glium::gl::INVALID_ENUM && message.contains("glBindBuffer")
+    }));
+
+    // the error is still pending, see `error_check_policy_every_call_assert_no_error`
+}
+
+#[test]
+#[should_panic]
+fn error_check_policy_every_call_assert_no_error() {
+    use glium::debug::ErrorCheckPolicy;
+    use glium::backend::StateCategories;
+
+    let display = support::build_display();
+    display.set_error_check_policy(ErrorCheckPolicy::EveryCall);
+
+    // `0xdead` is not a valid bind point
+    unsafe {
+        display.exec_with_raw_gl(StateCategories::none(), |gl| gl.BindBuffer(0xdead, 0));
+    }
+
+    // the wrapper of `glBindBuffer` has already called `glGetError`
+    display.assert_no_error(None);
+}
+
+#[test]
+fn set_swap_interval() {
+    let display = support::build_display();
+
+    // the result depends on the platform and on whether the display is headless
+    let _ = display.set_swap_interval(glium::SwapInterval::Sync(1));
+    let _ = display.set_swap_interval(glium::SwapInterval::Immediate);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn finish_with_damage() {
+    let display = support::build_display();
+
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    frame.finish_with_damage(&[glium::Rect { left: 0, bottom: 0, width: 16, height: 16 }])
+         .unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_latency_limit() {
+    let display = support::build_display();
+
+    display.set_frame_latency_limit(Some(2));
+    assert_eq!(display.get_frame_latency_limit(), Some(2));
+
+    for _ in 0 .. 4 {
+        let mut frame = display.draw();
+        frame.clear_color(0.0, 0.0, 0.0, 0.0);
+        frame.finish().unwrap();
+    }
+
+    display.set_frame_latency_limit(None);
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn frame_latency_limit_zero() {
+    let display = support::build_display();
+    display.set_frame_latency_limit(Some(0));
+}
+
+#[test]
+fn draw_on_surface() {
+    let display = support::build_display();
+    let surface = match support::build_surface(&display) {
+        Some(s) => s,
+        None => return
+    };
+
+    let texture = support::build_unicolor_texture2d(&display, 1.0, 0.0, 0.0);
+
+    let mut frame = display.draw_on_surface(&surface);
+    frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().fill(&frame, glium::uniforms::MagnifySamplerFilter::Nearest);
+    frame.finish().unwrap();
+
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    frame.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_on_surface_with_damage_and_latency_limit() {
+    let display = support::build_display();
+    let surface = match support::build_surface(&display) {
+        Some(s) => s,
+        None => return
+    };
+
+    display.set_frame_latency_limit(Some(1));
+
+    for _ in 0 .. 3 {
+        let mut frame = display.draw_on_surface(&surface);
+        frame.clear_color(0.0, 0.0, 0.0, 0.0);
+        frame.finish_with_damage(&[glium::Rect { left: 0, bottom: 0, width: 16, height: 16 }])
+             .unwrap();
+    }
+
+    display.set_frame_latency_limit(None);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn deferred_destruction() {
+    let display = support::build_display();
+
+    assert!(!display.is_deferred_destruction_enabled());
+    display.set_deferred_destruction_enabled(true);
+    assert!(display.is_deferred_destruction_enabled());
+
+    {
+        let buffer = glium::buffer::Buffer::new(&display, &[1.0f32, 2.0, 3.0][..],
+                                                glium::buffer::BufferType::ArrayBuffer,
+                                                glium::buffer::BufferMode::Default).unwrap();
+        let texture = glium::texture::Texture2d::empty(&display, 16, 16).unwrap();
+        let mut frame = display.draw();
+        frame.clear_color(0.0, 0.0, 0.0, 0.0);
+        drop(buffer);
+        drop(texture);
+        frame.finish().unwrap();
+    }
+
+    display.finish();
+
+    for _ in 0 .. 2 {
+        display.draw().finish().unwrap();
+    }
+
+    assert_eq!(display.get_deferred_destructions_count(), 0);
+
+    display.set_deferred_destruction_enabled(false);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn with_foreign_gl() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    let value = unsafe { display.with_foreign_gl(|| 12) };
+    assert_eq!(value, 12);
+
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn exec_with_raw_gl() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    let touched = glium::backend::StateCategories {
+        blending: true,
+        .. glium::backend::StateCategories::none()
+    };
+
+    // if the blending state isn't restored, nothing is written
+    unsafe {
+        display.exec_with_raw_gl(touched, |gl| {
+            gl.Enable(glium::gl::BLEND);
+            gl.BlendFunc(glium::gl::ZERO, glium::gl::ZERO);
+        });
+    }
+
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn check_state_cache() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+    assert!(display.check_state_cache().is_empty());
+
+    // modifying the state without telling glium
+    unsafe {
+        display.exec_with_raw_gl(glium::backend::StateCategories::none(), |gl| {
+            gl.Enable(glium::gl::BLEND);
+        });
+    }
+
+    let mismatches = display.check_state_cache();
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].name, "GL_BLEND");
+
+    unsafe {
+        display.exec_with_raw_gl(glium::backend::StateCategories::all(), |_| ());
+    }
+    assert!(display.check_state_cache().is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn state_check_policy_reports_mismatches() {
+    use glium::debug::{MessageType, Source, StateCheckPolicy};
+    use glium::backend::StateCategories;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let display = support::build_display();
+
+    let messages = Rc::new(RefCell::new(Vec::new()));
+    let messages2 = messages.clone();
+    let result = display.set_debug_callback(move |source, ty, _, _, message| {
+        if source == Source::Application && ty == MessageType::Error {
+            messages2.borrow_mut().push(message.to_string());
+        }
+    });
+
+    if result.is_err() {
+        return;
+    }
+
+    let previous = display.get_state_check_policy();
+    display.set_state_check_policy(StateCheckPolicy::EverySwap);
+
+    // modifying the state without telling glium
+    unsafe {
+        display.exec_with_raw_gl(StateCategories::none(), |gl| {
+            gl.Enable(glium::gl::BLEND);
+        });
+    }
+
+    // the mismatch is reported instead of panicking
+    display.draw().finish().unwrap();
+
+    display.set_state_check_policy(previous);
+    display.remove_debug_callback();
+
+    unsafe {
+        display.exec_with_raw_gl(StateCategories::all(), |_| ());
+    }
+
+    let messages = messages.borrow();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("GL_BLEND"));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_statistics() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let frame = display.draw();
+    assert!(frame.statistics().is_none());
+    frame.finish().unwrap();
+
+    display.set_statistics_enabled(true);
+
+    let mut frame = display.draw();
+    assert_eq!(frame.statistics().unwrap(), glium::debug::FrameStatistics::default());
+
+    frame.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+               &Default::default()).unwrap();
+    frame.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+               &Default::default()).unwrap();
+
+    let statistics = frame.statistics().unwrap();
+    assert_eq!(statistics.draw_calls, 2);
+    assert!(statistics.program_binds <= 1);
+    assert_eq!(statistics.texture_upload_bytes, 0);
+    frame.finish().unwrap();
+
+    // the counters are reset at each frame
+    let frame = display.draw();
+    assert_eq!(frame.statistics().unwrap().draw_calls, 0);
+    frame.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn context_attributes() {
+    let display = support::build_display();
+    let version = *display.get_opengl_version();
+
+    if version.0 == glium::Api::GlEs || version < glium::Version(glium::Api::Gl, 3, 2) {
+        assert_eq!(display.get_opengl_profile(), None);
+    }
+
+    if version < glium::Version(glium::Api::Gl, 3, 0) {
+        assert!(!display.is_forward_compatible());
+    }
+
+    assert!(display.get_default_framebuffer_samples() != Some(0));
+    display.is_debug();
+    display.is_default_framebuffer_srgb();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn default_framebuffer_properties() {
+    let display = support::build_display();
+    let properties = display.get_default_framebuffer_properties();
+
+    assert_eq!(properties.srgb, display.is_default_framebuffer_srgb());
+    assert_eq!(properties.samples, display.get_default_framebuffer_samples());
+
+    let request: glium::backend::glutin_backend::FramebufferRequest = Default::default();
+    assert!(request.is_satisfied_by(&properties));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn native_fences_not_supported() {
+    use glium::native_fence::{NativeFenceError, NativeFences};
+
+    let display = support::build_display();
+
+    // the extensions are checked before any function is loaded
+    let result = unsafe {
+        NativeFences::new(&display, std::ptr::null(), "EGL_KHR_fence_sync EGL_KHR_wait_sync",
+                          |_| panic!())
+    };
+
+    match result {
+        Err(NativeFenceError::NotSupported) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
@@ -910,6 +910,126 @@ fn provoking_vertex_first() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn provoking_vertex_first_emulated() {
+    let display = support::build_display();
+    display.set_emulation_options(glium::draw_parameters::EmulationOptions {
+        provoking_vertex: true,
+        .. Default::default()
+    });
+
+    let vertex_buffer = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            value: (f32, f32),
+        }
+
+        implement_vertex!(Vertex, value);
+
+        glium::VertexBuffer::new(&display, &[
+            Vertex { value: (-1.0, 1.0) },
+            Vertex { value: (1.0, -1.0) },
+            Vertex { value: (-1.0, -1.0) },
+        ]).unwrap()
+    };
+
+    let program = glium::Program::from_source(&display, "
+            #version 100
+
+            attribute lowp vec2 value;
+            varying lowp flat float v_value;
+
+            void main() {
+                v_value = value.y;
+                gl_Position = vec4(value, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 100
+
+            varying lowp flat float v_value;
+
+            void main() {
+                gl_FragColor = vec4(1.0, (v_value + 1.0) * 0.5, 0.0, 1.0);
+            }
+        ", None);
+    let program = match program {
+        Err(_) => return,
+        Ok(p) => p
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer,
+        &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList), &program,
+        &glium::uniforms::EmptyUniforms,
+        &glium::DrawParameters {
+            provoking_vertex: glium::draw_parameters::ProvokingVertex::FirstVertex,
+            .. Default::default()
+        }).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+
+    // only the bottom-left half of the screen is filled
+    for row in data.iter().take(256) {
+        for pixel in row.iter().take(256) {
+            assert_eq!(pixel, &(255, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn polygon_mode_line_emulated() {
+    let display = support::build_display();
+    display.set_emulation_options(glium::draw_parameters::EmulationOptions {
+        polygon_mode: true,
+        .. Default::default()
+    });
+
+    let (vb, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb,
+        &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip), &program,
+        &glium::uniforms::EmptyUniforms,
+        &glium::DrawParameters {
+            polygon_mode: glium::PolygonMode::Line,
+            .. Default::default()
+        }).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn polygon_mode_line_emulated_index_buffer() {
+    let display = support::build_display();
+    display.set_emulation_options(glium::draw_parameters::EmulationOptions {
+        polygon_mode: true,
+        .. Default::default()
+    });
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    let result = texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+        &glium::DrawParameters {
+            polygon_mode: glium::PolygonMode::Line,
+            .. Default::default()
+        });
+
+    // OpenGL ES 2 can't read the index buffer back
+    match result {
+        Err(glium::DrawError::PolygonModeNotSupported) => return,
+        r => r.unwrap()
+    };
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn depth_clamp_all() {
     let display = support::build_display();
@@ -1267,3 +1387,75 @@ fn primitive_bounding_box() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn polygon_offset() {
+    let display = support::build_display();
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+    let depth = glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                           glium::texture::DepthFormat::I24,
+                                                           texture.get_width(),
+                                                           texture.get_height().unwrap()).unwrap();
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                                  &texture,
+                                                                                  &depth).unwrap();
+
+    // the quad is at the same depth as the content of the depth buffer, and only passes the
+    // depth test thanks to the offset
+    let params = glium::DrawParameters {
+        depth_test: glium::DepthTest::IfLess,
+        polygon_offset: glium::draw_parameters::PolygonOffset {
+            units: -16.0,
+            fill: true,
+            .. Default::default()
+        },
+        .. Default::default()
+    };
+
+    framebuffer.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 0.5);
+    framebuffer.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pixel_local_storage() {
+    let display = support::build_display();
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture).unwrap();
+
+    if glium::framebuffer::is_pixel_local_storage2_supported(&display) {
+        framebuffer.set_pixel_local_storage_size(16).unwrap();
+    }
+
+    let params = glium::DrawParameters {
+        pixel_local_storage: true,
+        .. Default::default()
+    };
+
+    match framebuffer.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params) {
+        Ok(_) => assert!(glium::framebuffer::is_pixel_local_storage_supported(&display)),
+        Err(glium::DrawError::PixelLocalStorageNotSupported) => {
+            assert!(!glium::framebuffer::is_pixel_local_storage_supported(&display));
+            return;
+        },
+        Err(e) => panic!("{:?}", e),
+    }
+
+    // reading the texture discards the pixel local storage
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "egl_headless")]
+
+extern crate glium;
+
+use glium::{GlObject, GliumCreationError, Surface};
+use glium::texture::{Dimensions, MipmapsOption, UncompressedFloatFormat};
+use glium::backend::egl_headless::{CreationError, EglHeadlessFacade};
+
+/// Builds a facade, or returns `None` if EGL or surfaceless contexts aren't available on this
+/// machine.
+fn build_facade() -> Option<EglHeadlessFacade> {
+    match EglHeadlessFacade::new(64, 64) {
+        Ok(facade) => Some(facade),
+        Err(GliumCreationError::BackendCreationError(CreationError::NoDisplay)) |
+        Err(GliumCreationError::BackendCreationError(CreationError::InitializationFailed)) |
+        Err(GliumCreationError::BackendCreationError(CreationError::SurfacelessNotSupported)) |
+        Err(GliumCreationError::BackendCreationError(CreationError::NoAvailableConfig)) => None,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn draw_to_texture() {
+    let facade = match build_facade() {
+        Some(f) => f,
+        None => return,
+    };
+
+    assert_eq!(facade.get_framebuffer_dimensions(), (64, 64));
+
+    let texture = glium::texture::Texture2d::empty(&facade, 64, 64).unwrap();
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    facade.assert_no_error(None);
+}
+
+#[test]
+fn shared_backend() {
+    let facade = match build_facade() {
+        Some(f) => f,
+        None => return,
+    };
+
+    let texture = glium::texture::Texture2d::empty(&facade, 16, 16).unwrap();
+    texture.as_surface().clear_color(1.0, 0.0, 0.0, 1.0);
+    facade.finish();
+
+    let backend = facade.build_shared_backend(16, 16).unwrap();
+    let shared = EglHeadlessFacade::from_backend(backend).unwrap();
+
+    // the texture can be read through the other context
+    let texture = unsafe {
+        glium::texture::Texture2d::from_id(&shared, UncompressedFloatFormat::U8U8U8U8,
+                                           texture.get_id(), false, MipmapsOption::NoMipmap,
+                                           Dimensions::Texture2d { width: 16, height: 16 })
+    };
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    facade.assert_no_error(None);
+    shared.assert_no_error(None);
+}
@@ -439,3 +439,39 @@ fn empty_framebuffer_simple_draw() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn render_buffer_too_large() {
+    let display = support::build_display();
+
+    let buffer = glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                            glium::texture::DepthFormat::I24,
+                                                            1 << 30, 1);
+
+    match buffer {
+        Err(glium::framebuffer::RenderBufferCreationError::DimensionsNotSupported) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pixel_local_storage_clear() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture).unwrap();
+
+    if !glium::framebuffer::is_pixel_local_storage2_supported(&display) {
+        assert!(framebuffer.set_pixel_local_storage_size(16).is_err());
+        assert!(framebuffer.clear_pixel_local_storage(0, &[0, 0, 0, 0]).is_err());
+        return;
+    }
+
+    framebuffer.set_pixel_local_storage_size(16).unwrap();
+    framebuffer.clear_pixel_local_storage(0, &[0, 0, 0, 0]).unwrap();
+
+    display.assert_no_error(None);
+}
@@ -329,6 +329,95 @@ fn indexbuffer_slice_draw() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn indexbufferany_slice_draw() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 3, 2, 0, 1, 3]).unwrap();
+    let indices: glium::index::IndexBufferAny = indices.into();
+
+    assert!(indices.slice(5 .. 8).is_none());
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, indices.slice(3 .. 6).unwrap(), &program,
+                &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+    assert_eq!(data.last().unwrap().last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn client_indices_draw() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+
+    // drawing multiple times in order to make sure that the data is correctly uploaded
+    for _ in 0 .. 3 {
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vb,
+                    glium::index::ClientIndices(PrimitiveType::TrianglesList, &[0u16, 3, 2]),
+                    &program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], (255, 0, 0, 255));
+        assert_eq!(data.last().unwrap().last().unwrap(), &(0, 0, 0, 0));
+
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vb,
+                    glium::index::ClientIndices(PrimitiveType::TrianglesList, &[0u16, 1, 3]),
+                    &program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], (0, 0, 0, 0));
+        assert_eq!(data.last().unwrap().last().unwrap(), &(255, 0, 0, 255));
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_command_builders_validation() {
+    use glium::index::{DrawCommandNoIndicesBuilder, DrawCommandIndicesBuilder, DrawCommandError};
+
+    assert!(DrawCommandNoIndicesBuilder::new(4).build().is_ok());
+    assert_eq!(DrawCommandNoIndicesBuilder::new(4).vertices(2 .. 5).build().err(),
+               Some(DrawCommandError::ElementsOutOfRange));
+    assert_eq!(DrawCommandNoIndicesBuilder::new(4).instances(0 .. 3).instances_count(2)
+                                                  .build().err(),
+               Some(DrawCommandError::InstancesOutOfRange));
+
+    assert!(DrawCommandIndicesBuilder::new(6).indices(3 .. 6).build().is_ok());
+    assert_eq!(DrawCommandIndicesBuilder::new(6).indices(3 .. 7).build().err(),
+               Some(DrawCommandError::ElementsOutOfRange));
+
+    let command = glium::index::DrawCommandNoIndices {
+        count: 4,
+        instance_count: 1,
+        first_index: 1,
+        base_instance: 0,
+    };
+    assert_eq!(command.validate(4, None), Err(DrawCommandError::ElementsOutOfRange));
+    assert_eq!(command.validate(5, None), Ok(()));
+}
+
 #[test]
 fn multidraw_array() {
     let display = support::build_display();
@@ -0,0 +1,100 @@
+extern crate glium;
+
+use glium::Rect;
+use glium::texture::{MipmapsOption, Texture2d};
+use glium::texture::mipmap_generator::{GenerationOptions, MipmapFilter, MipmapGenerator};
+
+mod support;
+
+fn read_level(texture: &Texture2d, level: u32) -> Vec<Vec<(u8, u8, u8, u8)>> {
+    let mipmap = texture.mipmap(level).unwrap();
+    let rect = Rect {
+        left: 0,
+        bottom: 0,
+        width: mipmap.get_width(),
+        height: mipmap.get_height().unwrap(),
+    };
+
+    mipmap.first_layer().into_image(None).unwrap().raw_read(&rect)
+}
+
+fn build_checkerboard(display: &glium::Display) -> Texture2d {
+    let data = (0 .. 8).map(|y| {
+        (0 .. 8).map(|x| {
+            if (x + y) % 2 == 0 { (255u8, 255u8, 255u8, 255u8) } else { (0, 0, 0, 255) }
+        }).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+
+    Texture2d::with_mipmaps(display, data, MipmapsOption::EmptyMipmaps).unwrap()
+}
+
+#[test]
+fn box_filter() {
+    let display = support::build_display();
+    let generator = match MipmapGenerator::new(&display) {
+        Ok(g) => g,
+        Err(_) => return
+    };
+
+    let texture = build_checkerboard(&display);
+    generator.generate(&texture, &Default::default()).unwrap();
+
+    for level in 1 .. texture.get_mipmap_levels() {
+        for row in read_level(&texture, level).iter() {
+            for &(r, g, b, a) in row.iter() {
+                assert!(r >= 126 && r <= 129);
+                assert_eq!(r, g);
+                assert_eq!(r, b);
+                assert_eq!(a, 255);
+            }
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn gamma_correct() {
+    let display = support::build_display();
+    let generator = match MipmapGenerator::new(&display) {
+        Ok(g) => g,
+        Err(_) => return
+    };
+
+    let texture = build_checkerboard(&display);
+    let options = GenerationOptions { gamma_correct: true, .. Default::default() };
+    generator.generate(&texture, &options).unwrap();
+
+    // the average of black and white in linear space is brighter once converted back
+    let (r, _, _, _) = read_level(&texture, 1)[0][0];
+    assert!(r >= 186 && r <= 189);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn kaiser_filter() {
+    let display = support::build_display();
+    let generator = match MipmapGenerator::new(&display) {
+        Ok(g) => g,
+        Err(_) => return
+    };
+
+    let data = vec![vec![(0u8, 255u8, 0u8, 255u8); 16]; 16];
+    let texture = Texture2d::with_mipmaps(&display, data, MipmapsOption::EmptyMipmaps).unwrap();
+
+    let options = GenerationOptions { filter: MipmapFilter::Kaiser { alpha: 4.0 },
+                                      .. Default::default() };
+    generator.generate(&texture, &options).unwrap();
+
+    // a uniform texture stays uniform
+    for level in 1 .. texture.get_mipmap_levels() {
+        for row in read_level(&texture, level).iter() {
+            for pixel in row.iter() {
+                assert_eq!(pixel, &(0, 255, 0, 255));
+            }
+        }
+    }
+
+    display.assert_no_error(None);
+}
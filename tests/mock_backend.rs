@@ -0,0 +1,232 @@
+#![cfg(feature = "mock_backend")]
+
+#[macro_use]
+extern crate glium;
+
+use glium::{Api, CapabilitiesSource, Surface, Version};
+use glium::backend::mock::MockBackendBuilder;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+
+#[test]
+fn version_and_extensions() {
+    let display = MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+                        .with_extensions(&["GL_ARB_direct_state_access"])
+                        .with_integer(glium::gl::MAX_TEXTURE_SIZE, 1024)
+                        .build_glium()
+                        .unwrap();
+
+    assert_eq!(*display.get_opengl_version(), Version(Api::Gl, 3, 3));
+    assert!(display.get_extensions().gl_arb_direct_state_access);
+    assert!(!display.get_extensions().gl_arb_buffer_storage);
+    assert_eq!(display.get_capabilities().max_texture_size, 1024);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn invalid_strings() {
+    use glium::GliumCreationError;
+    use glium::backend::mock::MockCreationError;
+
+    let result = MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+                        .with_vendor("gli\0um")
+                        .build_glium();
+
+    match result {
+        Err(GliumCreationError::BackendCreationError(MockCreationError::NulCharacter(_))) => (),
+        _ => panic!()
+    };
+
+    let result = MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+                        .with_extensions(&["GL_ARB_buffer_storage GL_ARB_direct_state_access"])
+                        .build_backend();
+
+    match result {
+        Err(MockCreationError::InvalidExtensionName(_)) => (),
+        _ => panic!()
+    };
+}
+
+#[test]
+fn fallback_selection() {
+    let dsa = MockBackendBuilder::new(Version(Api::Gl, 4, 5)).build_glium().unwrap();
+    let legacy = MockBackendBuilder::new(Version(Api::Gl, 2, 1)).build_glium().unwrap();
+
+    for display in [&dsa, &legacy].iter() {
+        display.clear_calls();
+        Buffer::new(*display, &[0u8; 16][..], BufferType::ArrayBuffer,
+                    BufferMode::Default).unwrap();
+    }
+
+    assert!(dsa.get_calls().iter().any(|call| call.function == "glCreateBuffers"));
+    assert!(!dsa.get_calls().iter().any(|call| call.function == "glGenBuffers"));
+    assert!(legacy.get_calls().iter().any(|call| call.function == "glGenBuffers"));
+}
+
+#[test]
+fn buffer_content() {
+    let display = MockBackendBuilder::new(Version(Api::Gl, 3, 3)).build_glium().unwrap();
+
+    let buffer = Buffer::new(&display, &[1u32, 2, 3, 4][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+    buffer.slice(1 .. 3).unwrap().write(&[5, 6][..]);
+
+    assert_eq!(buffer.read().unwrap(), vec![1, 5, 6, 4]);
+}
+
+#[test]
+fn draw_calls_recorded() {
+    let display = MockBackendBuilder::new(Version(Api::Gl, 3, 3)).build_glium().unwrap();
+
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 0.0, 0.0, 1.0);
+    frame.finish().unwrap();
+
+    assert!(display.get_calls().iter().any(|call| call.function == "glClear"));
+}
+
+#[test]
+fn multiple_values_integers() {
+    let display = MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+                        .with_integer(glium::gl::MAX_VIEWPORT_DIMS, 8192)
+                        .build_glium()
+                        .unwrap();
+
+    assert_eq!(display.get_capabilities().max_viewport_dims, (8192, 8192));
+}
+
+#[test]
+fn driver_quirks() {
+    let display = MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+                        .with_renderer("AMD Radeon HD 7700 Series")
+                        .with_extensions(&["GL_ARB_direct_state_access"])
+                        .build_glium()
+                        .unwrap();
+
+    assert!(display.get_quirks().limit_texture_units);
+    assert_eq!(display.get_capabilities().max_combined_texture_image_units, 32);
+    assert!(display.get_extensions().gl_arb_direct_state_access);
+}
+
+#[test]
+fn driver_quirks_buffer_orphaning() {
+    let display = MockBackendBuilder::new(Version(Api::GlEs, 3, 0))
+                        .with_vendor("ARM")
+                        .with_renderer("Mali-T760")
+                        .build_glium()
+                        .unwrap();
+
+    assert!(display.get_quirks().no_buffer_orphaning);
+
+    let buffer = Buffer::new(&display, &[0u8; 64][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+    display.clear_calls();
+    buffer.invalidate();
+
+    assert!(!display.get_calls().iter().any(|call| call.function == "glBufferData"));
+}
+
+#[test]
+fn robust_buffer_access() {
+    let robust_flag = glium::gl::CONTEXT_FLAG_ROBUST_ACCESS_BIT as i32;
+
+    let display = MockBackendBuilder::new(Version(Api::Gl, 4, 5))
+                        .with_integer(glium::gl::CONTEXT_FLAGS, robust_flag)
+                        .build_glium()
+                        .unwrap();
+    assert!(display.is_robust());
+    assert!(display.has_robust_buffer_access_behavior());
+
+    // the behavior is only defined in robust contexts
+    let display = MockBackendBuilder::new(Version(Api::Gl, 4, 5)).build_glium().unwrap();
+    assert!(!display.is_robust());
+    assert!(!display.has_robust_buffer_access_behavior());
+
+    let display = MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+                        .with_extensions(&["GL_ARB_robustness"])
+                        .with_integer(glium::gl::CONTEXT_FLAGS, robust_flag)
+                        .build_glium()
+                        .unwrap();
+    assert!(display.is_robust());
+    assert!(!display.has_robust_buffer_access_behavior());
+}
+
+#[test]
+fn disabled_extensions() {
+    use glium::GliumCreationError;
+    use glium::backend::Context;
+
+    let builder = || {
+        MockBackendBuilder::new(Version(Api::Gl, 3, 3))
+            .with_extensions(&["GL_ARB_direct_state_access", "GL_ARB_buffer_storage"])
+            .build_backend()
+            .unwrap()
+    };
+
+    let context = unsafe {
+        Context::new_with_disabled_extensions::<_, ()>(builder(), false,
+                                                        &["GL_ARB_buffer_storage"])
+    }.unwrap();
+
+    assert!(!context.get_extensions().gl_arb_buffer_storage);
+    assert!(context.get_extensions().gl_arb_direct_state_access);
+
+    let result = unsafe {
+        Context::new_with_disabled_extensions::<_, ()>(builder(), false,
+                                                        &["GL_ARB_bufer_storage"])
+    };
+
+    match result {
+        Err(GliumCreationError::UnknownExtension(ref name)) if name == "GL_ARB_bufer_storage" => (),
+        _ => panic!()
+    };
+}
+
+#[test]
+fn destroyed_buffer_detached_from_vaos() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let display = MockBackendBuilder::new(Version(Api::Gl, 4, 5)).build_glium().unwrap();
+
+    let vertex_shader = "
+        #version 110
+
+        attribute vec2 position;
+
+        void main() {
+            gl_Position = vec4(position, 0.0, 1.0);
+        }
+    ";
+
+    let fragment_shader = "
+        #version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0);
+        }
+    ";
+
+    let program = glium::Program::from_source(&display, vertex_shader, fragment_shader,
+                                              None).unwrap();
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [0.0, 0.0] }, Vertex { position: [1.0, 0.0] },
+        Vertex { position: [0.0, 1.0] },
+    ]).unwrap();
+
+    let mut frame = display.draw();
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    frame.draw(&vertex_buffer, &indices, &program, &glium::uniforms::EmptyUniforms,
+               &Default::default()).unwrap();
+    frame.finish().unwrap();
+
+    // the VAO of the format is kept, but it must not keep the storage of the buffer alive
+    display.clear_calls();
+    drop(vertex_buffer);
+    assert!(display.get_calls().iter().any(|call| call.function == "glVertexArrayVertexBuffer"));
+}
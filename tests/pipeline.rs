@@ -0,0 +1,110 @@
+#[macro_use]
+extern crate glium;
+
+use std::borrow::Cow;
+
+use glium::Surface;
+use glium::pipeline::PipelineState;
+
+mod support;
+
+#[test]
+fn draw_with_pipeline() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let pipeline = PipelineState::new(&display, &program, vb.get_bindings().clone(),
+                                      Default::default()).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw_with_pipeline(&vb, &ib, &pipeline,
+                                            &glium::uniforms::EmptyUniforms).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pipeline_states_resynchronized() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let params = glium::DrawParameters {
+        color_mask: (false, true, true, true),
+        .. Default::default()
+    };
+
+    let pipeline = PipelineState::new(&display, &program, vb.get_bindings().clone(),
+                                      params).unwrap();
+
+    let texture1 = support::build_renderable_texture(&display);
+    let texture2 = support::build_renderable_texture(&display);
+
+    texture1.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture1.as_surface().draw_with_pipeline(&vb, &ib, &pipeline,
+                                             &glium::uniforms::EmptyUniforms).unwrap();
+
+    // both the clear and the draw call below modify the color mask
+    texture2.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture2.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                               &Default::default()).unwrap();
+
+    texture1.as_surface().draw_with_pipeline(&vb, &ib, &pipeline,
+                                             &glium::uniforms::EmptyUniforms).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture1.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 0, 0, 255));
+        }
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture2.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pipeline_wrong_depth_range() {
+    let display = support::build_display();
+    let (vb, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let params = glium::DrawParameters {
+        depth_range: (-0.1, 1.0),
+        .. Default::default()
+    };
+
+    match PipelineState::new(&display, &program, vb.get_bindings().clone(), params) {
+        Err(glium::DrawError::InvalidDepthRange) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!(),
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pipeline_attribute_missing() {
+    let display = support::build_display();
+    let (_, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    match PipelineState::new(&display, &program, Cow::Borrowed(&[]), Default::default()) {
+        Err(glium::DrawError::AttributeMissing { .. }) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!(),
+    };
+
+    display.assert_no_error(None);
+}
@@ -309,3 +309,70 @@ fn query_to_buffer() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn timestamp() {
+    let display = support::build_display();
+
+    let (start, end) = match (glium::draw_parameters::TimestampQuery::new(&display),
+                              glium::draw_parameters::TimestampQuery::new(&display))
+    {
+        (Ok(s), Ok(e)) => (s, e),
+        _ => return
+    };
+
+    assert!(!start.is_ready());
+
+    let texture = support::build_renderable_texture(&display);
+    start.record();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    end.record();
+
+    assert!(end.get() >= start.get());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn profiler_scopes() {
+    let display = support::build_display();
+
+    let profiler = match glium::profiler::Profiler::with_max_latency(&display, 1000) {
+        Err(_) => return,
+        Ok(p) => p
+    };
+
+    let texture = support::build_renderable_texture(&display);
+
+    profiler.scope("outer", || {
+        profiler.scope("inner", || texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0));
+    });
+    profiler.end_frame();
+
+    display.finish();
+    profiler.end_frame();
+
+    let timings = profiler.get_last_frame_timings().unwrap();
+    assert_eq!(timings.scopes.len(), 1);
+    assert_eq!(timings.scopes[0].name, "outer");
+    assert_eq!(timings.scopes[0].children.len(), 1);
+    assert_eq!(timings.scopes[0].children[0].name, "inner");
+    assert!(timings.scopes[0].nanoseconds >= timings.scopes[0].children[0].nanoseconds);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn profiler_end_scope_without_scope() {
+    let display = support::build_display();
+
+    let profiler = match glium::profiler::Profiler::new(&display) {
+        // ignore the test
+        Err(_) => panic!(),
+        Ok(p) => p
+    };
+
+    profiler.end_scope();
+}
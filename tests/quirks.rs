@@ -0,0 +1,69 @@
+extern crate glium;
+
+use glium::backend::Quirks;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+
+mod support;
+
+#[test]
+fn detect() {
+    let quirks = Quirks::detect("ATI Technologies Inc.", "AMD Radeon HD 7700 Series",
+                                "4.5.13399 Compatibility Profile Context");
+    assert!(quirks.limit_texture_units);
+    assert!(!quirks.no_buffer_orphaning);
+
+    let quirks = Quirks::detect("NVIDIA Corporation", "GeForce GTX 980/PCIe/SSE2",
+                                "4.5.0 NVIDIA 364.19");
+    assert_eq!(quirks, Quirks::default());
+}
+
+#[test]
+fn detect_direct_state_access() {
+    let quirks = Quirks::detect("Intel", "Intel(R) HD Graphics 4600",
+                                "4.3.0 - Build 20.19.15.4463");
+    assert!(quirks.avoid_direct_state_access_extensions);
+    assert!(!quirks.no_buffer_orphaning);
+
+    // the Mesa drivers are not affected
+    let quirks = Quirks::detect("Intel Open Source Technology Center",
+                                "Mesa DRI Intel(R) Haswell Desktop",
+                                "4.5 (Core Profile) Mesa 12.0.0");
+    assert!(!quirks.avoid_direct_state_access_extensions);
+}
+
+#[test]
+fn detect_buffer_orphaning() {
+    let quirks = Quirks::detect("ARM", "Mali-T760", "OpenGL ES 3.1 v1.r7p0-03rel0");
+    assert!(quirks.no_buffer_orphaning);
+    assert!(!quirks.avoid_direct_state_access_extensions);
+    assert!(!quirks.limit_texture_units);
+}
+
+#[test]
+fn set_by_name() {
+    let mut quirks = Quirks::default();
+
+    assert!(quirks.set("no_buffer_orphaning", true));
+    assert!(quirks.no_buffer_orphaning);
+    assert!(quirks.set("no_buffer_orphaning", false));
+    assert!(!quirks.no_buffer_orphaning);
+
+    assert!(!quirks.set("not_a_quirk", true));
+    assert_eq!(quirks, Quirks::default());
+}
+
+#[test]
+fn override_at_runtime() {
+    let display = support::build_display();
+
+    let mut quirks = display.get_quirks();
+    quirks.no_buffer_orphaning = true;
+    display.set_quirks(quirks);
+    assert_eq!(display.get_quirks(), quirks);
+
+    let buffer = Buffer::new(&display, &[0u8; 64][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+    buffer.invalidate();
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,244 @@
+extern crate glium;
+
+use glium::Surface;
+use glium::render_graph::{RenderGraph, RenderGraphError, ResourceAccessError, TexturePool};
+use glium::texture::UncompressedFloatFormat;
+
+use std::cell::Cell;
+
+mod support;
+
+#[test]
+fn passes_executed_in_order() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    let counter = Cell::new(0);
+    let counter = &counter;
+    let mut graph = RenderGraph::new(&display);
+    let intermediate = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+    let output_id = graph.import_texture(&output);
+
+    graph.add_pass("first", move |res| {
+        assert_eq!(counter.get(), 0);
+        counter.set(1);
+        res.get(intermediate).unwrap().as_surface().clear_color(1.0, 0.0, 0.0, 1.0);
+    }).writes(intermediate);
+
+    graph.add_pass("second", move |res| {
+        assert_eq!(counter.get(), 1);
+        counter.set(2);
+        let source = res.get(intermediate).unwrap();
+        let target = res.get(output_id).unwrap();
+        source.as_surface().fill(&target.as_surface(),
+                                 glium::uniforms::MagnifySamplerFilter::Nearest);
+    }).reads(intermediate).writes(output_id);
+
+    graph.execute(&mut pool).unwrap();
+    assert_eq!(counter.get(), 2);
+    assert_eq!(pool.len(), 1);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn unused_pass_skipped() {
+    let display = support::build_display();
+    let mut pool = TexturePool::new();
+
+    let mut graph = RenderGraph::new(&display);
+    let unused = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+
+    graph.add_pass("unused", |_| panic!()).writes(unused);
+    graph.execute(&mut pool).unwrap();
+    assert_eq!(pool.len(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn read_before_write() {
+    let display = support::build_display();
+    let mut pool = TexturePool::new();
+
+    let mut graph = RenderGraph::new(&display);
+    let texture = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+
+    graph.add_pass("read", |_| panic!()).reads(texture);
+
+    match graph.execute(&mut pool) {
+        Err(RenderGraphError::ReadBeforeWrite { .. }) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn feedback_loop() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    let mut graph = RenderGraph::new(&display);
+    let output_id = graph.import_texture(&output);
+
+    graph.add_pass("loop", |_| panic!()).reads(output_id).writes(output_id);
+
+    match graph.execute(&mut pool) {
+        Err(RenderGraphError::FeedbackLoop { .. }) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn textures_reused() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    for _ in 0 .. 2 {
+        let mut graph = RenderGraph::new(&display);
+        let a = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+        let b = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+        let output_id = graph.import_texture(&output);
+
+        graph.add_pass("a", move |res| {
+            res.get(a).unwrap().as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        }).writes(a);
+
+        graph.add_pass("b", move |res| {
+            let source = res.get(a).unwrap();
+            let target = res.get(b).unwrap();
+            source.as_surface().fill(&target.as_surface(),
+                                     glium::uniforms::MagnifySamplerFilter::Nearest);
+        }).reads(a).writes(b);
+
+        graph.add_pass("output", move |res| {
+            let source = res.get(b).unwrap();
+            let target = res.get(output_id).unwrap();
+            source.as_surface().fill(&target.as_surface(),
+                                     glium::uniforms::MagnifySamplerFilter::Nearest);
+        }).reads(b).writes(output_id);
+
+        graph.execute(&mut pool).unwrap();
+        assert_eq!(pool.len(), 2);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn passes_sorted() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    let counter = Cell::new(0);
+    let counter = &counter;
+    let mut graph = RenderGraph::new(&display);
+    let intermediate = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+    let output_id = graph.import_texture(&output);
+
+    graph.add_pass("second", move |res| {
+        assert_eq!(counter.get(), 1);
+        counter.set(2);
+        let source = res.get(intermediate).unwrap();
+        let target = res.get(output_id).unwrap();
+        source.as_surface().fill(&target.as_surface(),
+                                 glium::uniforms::MagnifySamplerFilter::Nearest);
+    }).reads(intermediate).writes(output_id);
+
+    graph.add_pass("first", move |res| {
+        assert_eq!(counter.get(), 0);
+        counter.set(1);
+        res.get(intermediate).unwrap().as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+    }).writes(intermediate);
+
+    graph.execute(&mut pool).unwrap();
+    assert_eq!(counter.get(), 2);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn cycle() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    let mut graph = RenderGraph::new(&display);
+    let a = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+    let b = graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+    let output_id = graph.import_texture(&output);
+
+    graph.add_pass("a", |_| panic!()).reads(b).writes(a);
+    graph.add_pass("b", |_| panic!()).reads(a).writes(b);
+    graph.add_pass("output", |_| panic!()).reads(b).writes(output_id);
+
+    match graph.execute(&mut pool) {
+        Err(RenderGraphError::Cycle { .. }) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn undeclared_resource() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let other_output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    let mut other_graph = RenderGraph::new(&display);
+    let other_id = other_graph.import_texture(&other_output);
+
+    let executed = Cell::new(false);
+    let executed = &executed;
+    let mut graph = RenderGraph::new(&display);
+    let output_id = graph.import_texture(&output);
+    let undeclared = graph.import_texture(&other_output);
+
+    graph.add_pass("output", move |res| {
+        assert!(res.get(output_id).is_ok());
+        assert_eq!(res.get(undeclared).err(), Some(ResourceAccessError::NotDeclared));
+        assert_eq!(res.get(other_id).err(), Some(ResourceAccessError::UnknownResource));
+        executed.set(true);
+    }).writes(output_id);
+
+    graph.execute(&mut pool).unwrap();
+    assert!(executed.get());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn resource_of_another_graph() {
+    let display = support::build_display();
+    let output = support::build_renderable_texture(&display);
+    let mut pool = TexturePool::new();
+
+    let mut other_graph = RenderGraph::new(&display);
+    let other_id = other_graph.create_texture(64, 64, UncompressedFloatFormat::U8U8U8U8);
+
+    let mut graph = RenderGraph::new(&display);
+    let output_id = graph.import_texture(&output);
+    graph.add_pass("output", |_| panic!()).reads(other_id).writes(output_id);
+
+    match graph.execute(&mut pool) {
+        Err(RenderGraphError::UnknownResource { .. }) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,82 @@
+#[macro_use]
+extern crate glium;
+
+use glium::resource_queue::{ResourceQueue, ResourceStore};
+use std::thread;
+
+mod support;
+
+#[test]
+fn create_from_other_thread() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let queue = ResourceQueue::new();
+    let mut store = ResourceStore::new();
+
+    let promise = {
+        let queue = queue.clone();
+        thread::spawn(move || {
+            queue.create_vertex_buffer(vec![Vertex { position: [0.0, 0.0] }; 3])
+        }).join().unwrap()
+    };
+
+    assert!(!promise.is_ready());
+    assert_eq!(queue.len(), 1);
+
+    assert_eq!(queue.process(&display, &mut store), 1);
+    assert_eq!(queue.len(), 0);
+
+    let id = match promise.try_get() {
+        Ok(result) => result.unwrap(),
+        Err(_) => panic!("the promise has not been fulfilled")
+    };
+
+    assert_eq!(store.vertex_buffers.get(id).unwrap().len(), 3);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn process_at_most() {
+    let display = support::build_display();
+
+    let queue = ResourceQueue::new();
+    let mut store = ResourceStore::new();
+
+    let texture = queue.create_texture2d(vec![vec![(0u8, 0u8, 0u8, 0u8); 4]; 4]);
+    let program = queue.create_program("invalid".to_string(), "invalid".to_string(), None);
+
+    assert_eq!(queue.process_at_most(&display, &mut store, 1), 1);
+    assert!(texture.is_ready());
+    assert!(!program.is_ready());
+
+    assert_eq!(queue.process_at_most(&display, &mut store, 1), 1);
+    assert!(program.wait().is_err());
+    assert!(texture.wait().is_ok());
+    assert_eq!(store.textures.len(), 1);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn abandoned_request() {
+    use glium::resource_queue::RequestError;
+
+    let promise = {
+        let queue = ResourceQueue::new();
+        queue.create_texture2d(vec![vec![(0u8, 0u8, 0u8, 0u8); 4]; 4])
+    };
+
+    assert!(promise.is_ready());
+    match promise.wait() {
+        Err(RequestError::Abandoned) => (),
+        _ => panic!("the promise has not been abandoned")
+    }
+}
@@ -79,3 +79,119 @@ fn magnify_nearest_filtering() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn reduction_mode_min_max() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2D texture;
+
+                void main() {
+                    gl_FragColor = texture2D(texture, vec2(0.5, 0.5));
+                }
+            ",
+        }).unwrap();
+
+    // the linear filter loads both texels when sampling the center of the texture
+    let texture_data = vec![vec![(0u8, 0, 0), (255, 255, 255)]];
+    let texture = glium::texture::Texture2d::new(&display, texture_data).unwrap();
+    let output = support::build_renderable_texture(&display);
+
+    for &(mode, expected) in [(glium::uniforms::SamplerReductionMode::Min, 0u8),
+                              (glium::uniforms::SamplerReductionMode::Max, 255)].iter()
+    {
+        let uniforms = uniform! {
+            texture: texture.sampled()
+                            .minify_filter(glium::uniforms::MinifySamplerFilter::Linear)
+                            .magnify_filter(glium::uniforms::MagnifySamplerFilter::Linear)
+                            .reduction_mode(mode)
+        };
+
+        output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+        match output.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()) {
+            Ok(_) => (),
+            Err(glium::DrawError::SamplersNotSupported) => return,
+            Err(glium::DrawError::SamplerReductionModeNotSupported) => {
+                assert!(!glium::uniforms::SamplerReductionMode::is_minmax_supported(&display));
+                return;
+            },
+            Err(e) => panic!("{:?}", e)
+        };
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+        assert_eq!(data[0][0], (expected, expected, expected, 255));
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn wrap_clamp_to_border() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2D texture;
+
+                void main() {
+                    gl_FragColor = texture2D(texture, vec2(1.5, 0.5));
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_unicolor_texture2d(&display, 1.0, 1.0, 1.0);
+
+    let uniforms = uniform! {
+        texture: texture.sampled()
+                        .wrap_function(glium::uniforms::SamplerWrapFunction::ClampToBorder)
+                        .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest)
+    };
+
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(1.0, 0.0, 0.0, 1.0);
+
+    match output.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()) {
+        Ok(_) => (),
+        Err(glium::DrawError::SamplersNotSupported) => return,
+        Err(glium::DrawError::SamplerWrapFunctionNotSupported) => {
+            let function = glium::uniforms::SamplerWrapFunction::ClampToBorder;
+            assert!(!function.is_supported(&display));
+            return;
+        },
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
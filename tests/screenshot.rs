@@ -0,0 +1,85 @@
+extern crate glium;
+
+use glium::Surface;
+use glium::screenshot::{Screenshot, ScreenshotFormat, ScreenshotSource};
+
+mod support;
+
+#[test]
+fn front_buffer() {
+    let display = support::build_display();
+    let mut screenshot = Screenshot::new(&display);
+
+    let mut target = display.draw();
+    target.clear_color(1.0, 0.0, 0.0, 1.0);
+    target.finish().unwrap();
+
+    let receiver = match screenshot.capture(ScreenshotSource::FrontBuffer,
+                                            ScreenshotFormat::Rgba)
+    {
+        Ok(r) => r,
+        Err(_) => return
+    };
+
+    while screenshot.pending_count() != 0 {
+        screenshot.poll().unwrap();
+    }
+
+    let image = receiver.recv().unwrap();
+    let (width, height) = display.get_framebuffer_dimensions();
+    assert_eq!((image.width, image.height), (width, height));
+    assert_eq!(image.data.len(), width as usize * height as usize * 4);
+    assert_eq!(&image.data[.. 4], &[255, 0, 0, 255]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn formats() {
+    let display = support::build_display();
+    let mut screenshot = Screenshot::new(&display);
+
+    let mut target = display.draw();
+    target.clear_color(1.0, 0.0, 0.0, 1.0);
+    target.finish().unwrap();
+
+    let rgb = match screenshot.capture(ScreenshotSource::FrontBuffer, ScreenshotFormat::Rgb) {
+        Ok(r) => r,
+        Err(_) => return
+    };
+    let bgra = screenshot.capture(ScreenshotSource::FrontBuffer, ScreenshotFormat::Bgra).unwrap();
+
+    while screenshot.pending_count() != 0 {
+        screenshot.poll().unwrap();
+    }
+
+    let rgb = rgb.recv().unwrap();
+    assert_eq!(rgb.data.len(), rgb.width as usize * rgb.height as usize * 3);
+    assert_eq!(&rgb.data[.. 3], &[255, 0, 0]);
+
+    let bgra = bgra.recv().unwrap();
+    assert_eq!(&bgra.data[.. 4], &[0, 0, 255, 255]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn back_buffer() {
+    let display = support::build_display();
+    let mut screenshot = Screenshot::new(&display);
+
+    let mut target = display.draw();
+    target.clear_color(0.0, 1.0, 0.0, 1.0);
+    let receiver = screenshot.capture(ScreenshotSource::BackBuffer,
+                                      ScreenshotFormat::Rgba).unwrap();
+    target.finish().unwrap();
+
+    while screenshot.pending_count() != 0 {
+        screenshot.poll().unwrap();
+    }
+
+    let image = receiver.recv().unwrap();
+    assert_eq!(&image.data[.. 4], &[0, 255, 0, 255]);
+
+    display.assert_no_error(None);
+}
@@ -859,3 +859,59 @@ fn array_layout_offsets() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn framebuffer_fetch() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 130
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 130
+            #extension GL_EXT_shader_framebuffer_fetch : require
+
+            inout vec4 color;
+
+            void main() {
+                color += vec4(0.5, 0.0, 0.0, 0.0);
+            }
+        ",
+        None);
+
+    let program = match program {
+        Ok(p) => p,
+        Err(glium::ProgramCreationError::FramebufferFetchNotSupported) => {
+            assert!(!glium::program::is_framebuffer_fetch_supported(&display));
+            return;
+        },
+        Err(e) => {
+            assert!(glium::program::is_framebuffer_fetch_supported(&display));
+            panic!("{:?}", e);
+        },
+    };
+
+    assert!(program.uses_framebuffer_fetch());
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 1.0);
+
+    // each draw call adds half of the red component to the content of the framebuffer
+    for _ in 0 .. 2 {
+        texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                                  &Default::default()).unwrap();
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,127 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::shadow_map::{ShadowMap, ShadowMapOptions};
+
+mod support;
+
+#[test]
+fn single_cascade() {
+    let display = support::build_display();
+
+    let options = ShadowMapOptions { size: 64, .. Default::default() };
+    let shadow_map = match ShadowMap::new(&display, &options) {
+        Ok(s) => s,
+        Err(_) => return
+    };
+
+    assert!(shadow_map.get_texture().is_some());
+    assert!(shadow_map.get_texture_array().is_none());
+
+    let mut surface = shadow_map.cascade_surface(0).unwrap();
+    assert_eq!(surface.get_dimensions(), (64, 64));
+    surface.clear_depth(1.0);
+
+    let params = shadow_map.draw_parameters();
+    assert_eq!(params.depth_test, glium::DepthTest::IfLess);
+    assert!(params.depth_write);
+    assert!(params.polygon_offset.fill);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn cascades() {
+    let display = support::build_display();
+
+    let options = ShadowMapOptions { size: 64, cascades: 3, .. Default::default() };
+    let shadow_map = match ShadowMap::new(&display, &options) {
+        Ok(s) => s,
+        Err(_) => return
+    };
+
+    assert_eq!(shadow_map.get_cascades_count(), 3);
+    assert!(shadow_map.get_texture_array().is_some());
+
+    for cascade in 0 .. 3 {
+        shadow_map.cascade_surface(cascade).unwrap().clear_depth(1.0);
+    }
+
+    if glium::framebuffer::is_layered_supported(&display) {
+        shadow_map.layered_surface().unwrap().clear_depth(1.0);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn cascade_out_of_range() {
+    let display = support::build_display();
+
+    let options = ShadowMapOptions { size: 64, .. Default::default() };
+    let shadow_map = match ShadowMap::new(&display, &options) {
+        Ok(s) => s,
+        Err(_) => panic!()
+    };
+
+    shadow_map.cascade_surface(1).ok();
+}
+
+#[test]
+fn comparison_sampling() {
+    let display = support::build_display();
+
+    let options = ShadowMapOptions { size: 16, linear_filtering: false, .. Default::default() };
+    let shadow_map = match ShadowMap::new(&display, &options) {
+        Ok(s) => s,
+        Err(_) => return
+    };
+    shadow_map.cascade_surface(0).unwrap().clear_depth(0.5);
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = match program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2DShadow shadow_map;
+                uniform float reference;
+
+                void main() {
+                    float lit = shadow2D(shadow_map, vec3(0.5, 0.5, reference)).r;
+                    gl_FragColor = vec4(lit, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ) {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+
+    // the fragment is lit if its depth is less than or equal to the content of the shadow map
+    for &(reference, expected) in [(0.25f32, 255u8), (0.75, 0)].iter() {
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vb, &ib, &program,
+                                  &uniform! { shadow_map: &shadow_map, reference: reference },
+                                  &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], (expected, 0, 0, 255));
+    }
+
+    display.assert_no_error(None);
+}
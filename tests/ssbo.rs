@@ -69,3 +69,43 @@ fn basic() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn layout_rules_array_stride() {
+    use glium::uniforms::{check_array_layout, LayoutRules};
+
+    #[derive(Copy, Clone)]
+    struct Data {
+        value: f32,
+    }
+
+    implement_uniform_block!(Data, value);
+
+    // the stride of the array is 4 bytes with std430, but 16 bytes with std140
+    assert!(check_array_layout::<Data>(LayoutRules::Std430).is_ok());
+    assert!(check_array_layout::<Data>(LayoutRules::Std140).is_err());
+}
+
+#[test]
+fn layout_rules_nested_struct() {
+    use glium::uniforms::{check_layout, LayoutRules};
+
+    #[derive(Copy, Clone)]
+    struct Inner {
+        x: f32,
+    }
+
+    implement_uniform_block!(Inner, x);
+
+    #[derive(Copy, Clone)]
+    struct Outer {
+        inner: Inner,
+        y: f32,
+    }
+
+    implement_uniform_block!(Outer, inner, y);
+
+    // `y` is at offset 4 with std430, but at offset 16 with std140
+    assert!(check_layout::<Outer>(LayoutRules::Std430).is_ok());
+    assert!(check_layout::<Outer>(LayoutRules::Std140).is_err());
+}
@@ -10,6 +10,7 @@ use glium::backend::Facade;
 use glium::index::PrimitiveType;
 
 use std::env;
+use std::rc::Rc;
 
 /// Builds a headless display for tests.
 pub fn build_display() -> glium::Display {
@@ -44,6 +45,22 @@ pub fn rebuild_display(display: &glium::Display) {
     }
 }
 
+/// Builds an additional window on which the context of the display can draw.
+///
+/// Returns `None` with headless displays or if the window system doesn't support this.
+pub fn build_surface(display: &glium::Display)
+                     -> Option<Rc<glium::backend::glutin_backend::GlutinSurfaceBackend>>
+{
+    if env::var("GLIUM_HEADLESS_TESTS").is_ok() {
+        return None;
+    }
+
+    let version = parse_version();
+    display.build_surface(glutin::WindowBuilder::new().with_gl_debug_flag(true)
+                                                      .with_visibility(false)
+                                                      .with_gl(version)).ok()
+}
+
 fn parse_version() -> glutin::GlRequest {
     match env::var("GLIUM_GL_VERSION") {
         Ok(version) => {
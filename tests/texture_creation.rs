@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate glium;
+#[cfg(feature = "image")]
+extern crate image;
 
 use glium::Surface;
 use glium::Texture;
@@ -19,6 +21,48 @@ fn empty_texture1d_u8u8u8u8() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn empty_texture2d_too_large() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_format(&display,
+                                                       glium::texture::UncompressedFloatFormat::
+                                                       U8U8U8U8,
+                                                       glium::texture::MipmapsOption::NoMipmap,
+                                                       1 << 30, 1);
+
+    match texture {
+        Err(glium::texture::TextureCreationError::DimensionsNotSupported) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_data_size_mismatch() {
+    use std::borrow::Cow;
+
+    let display = support::build_display();
+
+    // 3 pixels for a 2x2 texture
+    let image = glium::texture::RawImage2d {
+        data: Cow::Owned(vec![(0u8, 0u8, 0u8, 0u8); 3]),
+        width: 2,
+        height: 2,
+        format: glium::texture::ClientFormat::U8U8U8U8,
+    };
+
+    match glium::texture::Texture2d::new(&display, image) {
+        Err(glium::texture::TextureCreationError::DataSizeMismatch) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn get_format_u8u8u8u8() {
     let display = support::build_display();
@@ -385,3 +429,150 @@ fn upload_from_pixel_buffer_inverted() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+#[cfg(feature = "image")]
+fn texture_2d_dynamic_image_roundtrip() {
+    use glium::texture::image_io::ImageOptions;
+
+    let display = support::build_display();
+
+    // a 2x2 image whose top row is red and bottom row is blue
+    let mut image = image::ImageBuffer::new(2, 2);
+    for (_, y, pixel) in image.enumerate_pixels_mut() {
+        *pixel = if y == 0 { image::Rgba([255u8, 0, 0, 255]) }
+                 else { image::Rgba([0, 0, 255, 255]) };
+    }
+    let image = image::DynamicImage::ImageRgba8(image);
+
+    let texture = glium::texture::Texture2d::from_dynamic_image(&display, &image,
+                                                                Default::default()).unwrap();
+
+    // OpenGL stores the rows from bottom to top
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+    assert_eq!(data[1][0], (255, 0, 0, 255));
+
+    let output = texture.to_dynamic_image(Default::default());
+    assert_eq!(output.raw_pixels(), image.raw_pixels());
+
+    let unflipped = texture.to_dynamic_image(ImageOptions {
+        flip_vertically: false,
+        .. Default::default()
+    });
+    assert_eq!(&unflipped.raw_pixels()[.. 4], &[0, 0, 255, 255]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_from_id_not_owned() {
+    use glium::GlObject;
+    use glium::texture::{Dimensions, MipmapsOption, Texture2d, UncompressedFloatFormat};
+
+    let display = support::build_display();
+
+    let texture = Texture2d::empty_with_format(&display, UncompressedFloatFormat::U8U8U8U8,
+                                               MipmapsOption::NoMipmap, 4, 4).unwrap();
+
+    {
+        let wrapper = unsafe {
+            Texture2d::from_id(&display, UncompressedFloatFormat::U8U8U8U8, texture.get_id(),
+                               false, MipmapsOption::NoMipmap,
+                               Dimensions::Texture2d { width: 4, height: 4 })
+        };
+
+        assert_eq!(wrapper.get_width(), 4);
+        assert_eq!(wrapper.get_height(), Some(4));
+        assert_eq!(wrapper.get_mipmap_levels(), 1);
+        wrapper.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+    }
+
+    // the texture must still be alive after the wrapper has been dropped
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn video_surface_import_not_supported() {
+    use glium::texture::UncompressedFloatFormat;
+    use glium::texture::video::{self, CreationError, VdpauInterop};
+
+    let display = support::build_display();
+
+    if !video::is_egl_image_import_supported(&display) {
+        let result = unsafe {
+            video::import_egl_image(&display, std::ptr::null(), UncompressedFloatFormat::U8U8U8U8,
+                                    16, 16)
+        };
+
+        match result {
+            Err(CreationError::NotSupported) => (),
+            _ => panic!()
+        };
+    }
+
+    if !video::is_vdpau_supported(&display) {
+        match unsafe { VdpauInterop::new(&display, 0, std::ptr::null()) } {
+            Err(CreationError::NotSupported) => (),
+            _ => panic!()
+        };
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_usage_sampled() {
+    use glium::framebuffer::{SimpleFrameBuffer, ValidationError};
+    use glium::texture::{MipmapsOption, TextureUsage, UncompressedFloatFormat};
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_usage(&display,
+                                                              UncompressedFloatFormat::U8U8U8U8,
+                                                              MipmapsOption::NoMipmap,
+                                                              TextureUsage::Sampled, 16, 16)
+                                                              .unwrap();
+    assert_eq!(texture.get_usage(), TextureUsage::Sampled);
+
+    match SimpleFrameBuffer::new(&display, &texture) {
+        Err(ValidationError::IncompatibleTextureUsage) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_usage_render_target() {
+    use glium::texture::{CompressedFormat, CompressedMipmapsOption, MipmapsOption};
+    use glium::texture::{TextureCreationError, TextureUsage, UncompressedFloatFormat};
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_usage(&display,
+                                                              UncompressedFloatFormat::U8U8U8U8,
+                                                              MipmapsOption::NoMipmap,
+                                                              TextureUsage::RenderTarget, 16, 16)
+                                                              .unwrap();
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    let compressed = glium::texture::CompressedTexture2d::empty_with_usage(&display,
+                                                        CompressedFormat::RgtcFormatU,
+                                                        CompressedMipmapsOption::NoMipmap,
+                                                        TextureUsage::RenderTarget, 16, 16);
+
+    match compressed {
+        Err(TextureCreationError::UsageNotSupported) => (),
+        Err(TextureCreationError::FormatNotSupported) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate glium;
+#[cfg(feature = "half")]
+extern crate half;
 
 mod support;
 
@@ -134,3 +136,62 @@ read_texture_test!(read_unsignedtexture2darray, UnsignedTexture2dArray, (u8, u8,
 read_texture_test!(read_unsignedtexture3d, UnsignedTexture3d, (u8, u8, u8, u8),
     vec![vec![vec![(0, 1, 2, 3), (4, 5, 6, 7)], vec![(8, 9, 10, 11), (12, 13, 14, 15)]]]);*/
 // TODO: srgb textures
+
+#[test]
+fn texture_2d_read_rect() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(0u8, 1u8, 2u8), (4u8, 8u8, 16u8), (1u8, 2u8, 3u8)],
+        vec![(32u8, 64u8, 128u8), (32u8, 16u8, 4u8), (4u8, 5u8, 6u8)],
+    ]).unwrap();
+
+    let rect = glium::Rect { left: 1, bottom: 0, width: 2, height: 2 };
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_rect(rect, 0).unwrap();
+
+    assert_eq!(read_back.len(), 2);
+    assert_eq!(read_back[0], vec![(4, 8, 16, 255), (1, 2, 3, 255)]);
+    assert_eq!(read_back[1], vec![(32, 16, 4, 255), (4, 5, 6, 255)]);
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 1, height: 1 };
+    assert!(texture.read_rect::<Vec<Vec<(u8, u8, u8, u8)>>>(rect, 8).is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(feature = "half")]
+fn texture_2d_read_f16() {
+    use glium::texture::{MipmapsOption, ReadError, UncompressedFloatFormat};
+    use half::f16;
+
+    let display = support::build_display();
+
+    // we use only powers of two, in order to avoid float rounding errors
+    let pixel = |r: f32, g: f32, b: f32, a: f32| {
+        (f16::from_f32(r), f16::from_f32(g), f16::from_f32(b), f16::from_f32(a))
+    };
+
+    let data = vec![
+        vec![pixel(0.0, 0.5, 1.0, 2.0), pixel(-4.0, 0.25, 8.0, 1.0)],
+        vec![pixel(16.0, -0.5, 0.125, 1.0), pixel(1.0, 2.0, 4.0, 0.5)],
+    ];
+
+    let texture = match glium::texture::Texture2d::with_format(&display, data.clone(),
+                                                         UncompressedFloatFormat::F16F16F16F16,
+                                                         MipmapsOption::NoMipmap)
+    {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+
+    let read_back: Vec<Vec<(f16, f16, f16, f16)>> = match texture.read_if_supported() {
+        Ok(r) => r,
+        Err(ReadError::FormatNotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(read_back, data);
+
+    display.assert_no_error(None);
+}
@@ -130,3 +130,111 @@ fn bindless_texture() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn multiple_textures() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let red = glium::texture::Texture2d::new(&display, vec![vec![(255, 0, 0, 255u8)]]).unwrap();
+    let green = glium::texture::Texture2d::new(&display, vec![vec![(0, 255, 0, 255u8)]]).unwrap();
+    let blue = glium::texture::Texture2d::new(&display, vec![vec![(0, 0, 255, 255u8)]]).unwrap();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D texture1;
+            uniform sampler2D texture2;
+
+            void main() {
+                gl_FragColor = texture2D(texture1, vec2(0.5, 0.5)) +
+                               texture2D(texture2, vec2(0.5, 0.5));
+            }
+        ",
+        None).unwrap();
+
+    let output = support::build_renderable_texture(&display);
+
+    // the second draw shares one of its textures with the first one
+    let draws = [(&blue, (255, 0, 255, 255)), (&green, (255, 255, 0, 255))];
+    for &(texture2, expected) in draws.iter() {
+        output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        output.as_surface().draw(&vb, &ib, &program,
+                                 &uniform!{ texture1: &red, texture2: texture2 },
+                                 &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+        for row in data.iter() {
+            for pixel in row.iter() {
+                assert_eq!(pixel, &expected);
+            }
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_binding_after_uniform_error() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let red = glium::texture::Texture2d::new(&display, vec![vec![(255, 0, 0, 255u8)]]).unwrap();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D tex;
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = texture2D(tex, vec2(0.5, 0.5)) + color;
+            }
+        ",
+        None).unwrap();
+
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    // `tex` is visited before `color`, which has the wrong type
+    match output.as_surface().draw(&vb, &ib, &program,
+                                   &uniform!{ color: 1.0f32, tex: &red },
+                                   &Default::default())
+    {
+        Err(glium::DrawError::UniformTypeMismatch { .. }) => (),
+        a => panic!("{:?}", a)
+    };
+
+    output.as_surface().draw(&vb, &ib, &program,
+                             &uniform!{ color: (0.0, 0.0, 0.0, 0.0f32), tex: &red },
+                             &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
@@ -24,3 +24,153 @@ fn texture_2d_write() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn texture_2d_streaming() {
+    use std::thread;
+    use glium::command_encoder::Resources;
+    use glium::texture::streaming::TextureStreamer;
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty(&display, 2, 2).unwrap();
+    let mut textures = Resources::new();
+    let id = textures.insert(texture);
+
+    let mut streamer = match TextureStreamer::<(u8, u8, u8, u8)>::new(&display, 4, 2) {
+        Ok(s) => s,
+        Err(_) => return
+    };
+
+    let worker = {
+        let queue = streamer.queue();
+        thread::spawn(move || {
+            let mut staging = queue.staging(2).unwrap();
+            staging[0] = (255, 0, 0, 255);
+            staging[1] = (0, 255, 0, 255);
+            let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 1 };
+            queue.submit(staging, id, rect).unwrap();
+
+            let mut staging = queue.staging(2).unwrap();
+            staging[0] = (0, 0, 255, 255);
+            staging[1] = (255, 255, 255, 255);
+            let rect = glium::Rect { left: 0, bottom: 1, width: 2, height: 1 };
+            queue.submit(staging, id, rect).unwrap();
+        })
+    };
+
+    worker.join().unwrap();
+
+    let mut processed = 0;
+    while processed < 2 {
+        processed += streamer.process(&textures).unwrap();
+    }
+    assert_eq!(streamer.buffers_count(), 2);
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = textures.get(id).unwrap().read();
+    assert_eq!(read_back[0][0], (255, 0, 0, 255));
+    assert_eq!(read_back[0][1], (0, 255, 0, 255));
+    assert_eq!(read_back[1][0], (0, 0, 255, 255));
+    assert_eq!(read_back[1][1], (255, 255, 255, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_streaming_errors() {
+    use glium::command_encoder::Resources;
+    use glium::texture::streaming::{StreamError, TextureStreamer};
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty(&display, 2, 2).unwrap();
+    let mut textures = Resources::new();
+    let id = textures.insert(texture);
+
+    let mut streamer = match TextureStreamer::<(u8, u8, u8, u8)>::new(&display, 4, 1) {
+        Ok(s) => s,
+        Err(_) => return
+    };
+
+    let queue = streamer.queue();
+
+    // the buffer goes back to the pool after the error
+    let staging = queue.try_staging(3).unwrap();
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 1 };
+    assert_eq!(queue.submit(staging, id, rect), Err(StreamError::DataSizeMismatch));
+
+    let staging = queue.try_staging(4).unwrap();
+    assert!(queue.try_staging(1).is_none());
+    let rect = glium::Rect { left: 1, bottom: 1, width: 2, height: 2 };
+    queue.submit(staging, id, rect).unwrap();
+    match streamer.process(&textures) {
+        Err(err) => {
+            assert_eq!(err.started, 0);
+            assert_eq!(err.errors, vec![StreamError::RectOutOfBounds]);
+        },
+        Ok(_) => panic!("the upload has not been discarded")
+    };
+
+    assert!(queue.try_staging(4).is_some());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_streaming_drop_with_staging() {
+    use glium::texture::streaming::TextureStreamer;
+    use glium::Surface;
+
+    let display = support::build_display();
+
+    let streamer = match TextureStreamer::<(u8, u8, u8, u8)>::new(&display, 4, 2) {
+        Ok(s) => s,
+        Err(_) => return
+    };
+
+    let queue = streamer.queue();
+    let mut staging = queue.try_staging(4).unwrap();
+
+    // doesn't wait for the staging buffer
+    drop(streamer);
+    assert!(queue.try_staging(4).is_none());
+
+    // the buffer is still alive
+    staging[0] = (255, 0, 0, 255);
+    drop(staging);
+
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    frame.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_write_streaming_usage() {
+    use glium::texture::{MipmapsOption, TextureUsage, UncompressedFloatFormat};
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::with_format_and_usage(&display, vec![
+        vec![(0u8, 0u8, 0u8), (0u8, 0u8, 0u8)],
+        vec![(0u8, 0u8, 0u8), (0u8, 0u8, 0u8)],
+    ], UncompressedFloatFormat::U8U8U8U8, MipmapsOption::NoMipmap,
+       TextureUsage::StreamingUpload).unwrap();
+
+    // the whole texture is replaced, then a single texel
+    texture.write(glium::Rect { bottom: 0, left: 0, width: 2, height: 2 }, vec![
+        vec![(0u8, 1u8, 2u8), (4u8, 8u8, 16u8)],
+        vec![(32u8, 64u8, 128u8), (32u8, 16u8, 4u8)],
+    ]);
+    texture.write(glium::Rect { bottom: 1, left: 1, width: 1, height: 1 },
+                  vec![vec![(128u8, 64u8, 2u8)]]);
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(read_back[0][0], (0, 1, 2, 255));
+    assert_eq!(read_back[0][1], (4, 8, 16, 255));
+    assert_eq!(read_back[1][0], (32, 64, 128, 255));
+    assert_eq!(read_back[1][1], (128, 64, 2, 255));
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "gl_trace")]
+
+extern crate glium;
+
+use glium::Surface;
+use glium::trace::{self, TraceContent};
+
+mod support;
+
+#[test]
+fn calls_and_markers() {
+    let display = support::build_display();
+
+    trace::start_ring_buffer(4096);
+    assert!(trace::is_enabled());
+
+    trace::marker("frame");
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    frame.finish().unwrap();
+
+    let entries = trace::stop();
+    assert!(!trace::is_enabled());
+
+    assert_eq!(entries[0].content, TraceContent::Marker("frame".to_string()));
+
+    assert!(entries.iter().any(|e| e.content == TraceContent::Marker("clear".to_string())));
+    assert!(entries.iter().any(|e| e.content == TraceContent::Marker("swap_buffers".to_string())));
+    assert!(entries.iter().any(|e| match e.content {
+        TraceContent::Call { function, .. } => function == "glClear",
+        _ => false
+    }));
+
+    for window in entries.windows(2) {
+        assert!(window[0].timestamp <= window[1].timestamp);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn ring_buffer_capacity() {
+    trace::start_ring_buffer(2);
+    trace::marker("a");
+    trace::marker("b");
+    trace::marker("c");
+
+    let entries = trace::stop();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].content, TraceContent::Marker("b".to_string()));
+    assert_eq!(entries[1].content, TraceContent::Marker("c".to_string()));
+}
@@ -3,6 +3,7 @@ extern crate glium;
 extern crate rand;
 
 use glium::Surface;
+use std::error::Error;
 
 mod support;
 
@@ -206,12 +207,18 @@ fn block_wrong_type() {
     let mut target = display.draw();
     target.clear_color(0.0, 0.0, 0.0, 0.0);
 
-    match target.draw(&vb, &ib, &program, &uniforms, &Default::default()) {
+    let result = target.draw(&vb, &ib, &program, &uniforms, &Default::default());
+    match result {
         Err(glium::DrawError::UniformBlockLayoutMismatch { ref name, .. })
             if name == &"MyBlock" => (),
-        a => panic!("{:?}", a)
+        ref a => panic!("{:?}", a)
     }
-    
+
+    // the details of the mismatch are available through `Error::cause`
+    let err = result.unwrap_err();
+    assert!(err.cause().is_some());
+    assert!(err.to_string().contains("MyBlock"));
+
     target.finish().unwrap();
 
     display.assert_no_error(None);
@@ -327,6 +334,93 @@ fn persistent_block_race_condition() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn multiple_blocks_same_draw() {
+    let display = support::build_display();
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+            uniform layout(std140);
+
+            uniform RedBlock {
+                float red;
+            };
+
+            uniform GreenBlock {
+                float green;
+            };
+
+            void main() {
+                gl_FragColor = vec4(red, green, 0.0, 1.0);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    #[derive(Copy, Clone)]
+    struct Red {
+        red: f32,
+    }
+
+    implement_uniform_block!(Red, red);
+
+    #[derive(Copy, Clone)]
+    struct Green {
+        green: f32,
+    }
+
+    implement_uniform_block!(Green, green);
+
+    let mut red = match glium::uniforms::UniformBuffer::new(&display, Red { red: 0.0 }) {
+        Err(_) => return,
+        Ok(b) => b
+    };
+
+    let mut green = match glium::uniforms::UniformBuffer::new(&display, Green { green: 0.0 }) {
+        Err(_) => return,
+        Ok(b) => b
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    // both buffers are accessed by the same draw calls, then modified one after the other
+    for _ in (0 .. 10) {
+        red.map().red = 1.0;
+        texture.as_surface().draw(&vb, &ib, &program, &uniform!{
+            RedBlock: &red,
+            GreenBlock: &green,
+        }, &Default::default()).unwrap();
+        green.map().green = 1.0;
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn empty_uniform_buffer() {
     let display = support::build_display();
@@ -172,6 +172,57 @@ fn uniform_wrong_type() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn uniform_wrong_type_diagnostics() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    program.set_label(Some("material".to_owned()));
+
+    let uniforms = glium::uniforms::UniformsStorage::new("color", 1.0f32);
+
+    let mut target = display.draw();
+    let err = target.draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap_err();
+    target.finish().unwrap();
+
+    match err {
+        glium::DrawError::UniformTypeMismatch { ref name, expected, obtained, ref program } => {
+            assert_eq!(name, "color");
+            assert_eq!(expected, glium::uniforms::UniformType::FloatVec4);
+            assert_eq!(obtained, Some(glium::uniforms::UniformType::Float));
+            assert_eq!(program.as_ref().map(|p| &p[..]), Some("material"));
+        },
+        a => panic!("{:?}", a)
+    };
+
+    let message = err.to_string();
+    assert!(message.contains("`color`"));
+    assert!(message.contains("`material`"));
+
+    display.assert_no_error(None);
+}
+
 macro_rules! uniform_test(
     ($name:ident, $glsl_ty:expr, $value:expr) => (
         #[test]
@@ -340,3 +391,47 @@ uniform_test!(uniform_type_boolarr_boolvec3, "bvec3", [true, true, true]);
 uniform_test!(uniform_type_booltup_boolvec3, "bvec3", (false, false, false));
 uniform_test!(uniform_type_boolarr_boolvec4, "bvec4", [true, false, false, true]);
 uniform_test!(uniform_type_booltup_boolvec4, "bvec4", (false, true, true, false));
+
+#[test]
+fn uniform_caching_disabled() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    assert!(program.set_uniform_caching("color", false));
+    assert!(!program.set_uniform_caching("nonexisting", false));
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    for &color in &[[0.0, 1.0, 0.0, 1.0f32], [1.0, 0.0, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0]] {
+        let uniforms = glium::uniforms::UniformsStorage::new("color", color);
+        texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data.last().unwrap().last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,109 @@
+extern crate glium;
+
+use glium::buffer::{Buffer, BufferAny, BufferMode, BufferType};
+use glium::command_encoder::{InvalidResourceId, Resources};
+use glium::texture::Texture2d;
+use glium::upload_scheduler::UploadScheduler;
+
+mod support;
+
+#[test]
+fn bytes_budget() {
+    let display = support::build_display();
+
+    let texture = Texture2d::empty(&display, 4, 4).unwrap();
+    let mut textures = Resources::new();
+    let texture = textures.insert(texture);
+    let buffers: Resources<BufferAny> = Resources::new();
+
+    let mut scheduler = UploadScheduler::new();
+    scheduler.set_bytes_budget(Some(32));
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 4, height: 4 };
+    let upload = scheduler.upload_texture2d(0, texture, rect, vec![(255u8, 0u8, 0u8, 255u8); 16]);
+    assert_eq!(scheduler.get_pending_bytes(), 64);
+
+    // each row is 16 bytes
+    assert_eq!(scheduler.process(&textures, &buffers).unwrap(), 32);
+    assert!(scheduler.is_pending(upload));
+    assert_eq!(scheduler.process(&textures, &buffers).unwrap(), 32);
+    assert!(!scheduler.is_pending(upload));
+    assert_eq!(scheduler.len(), 0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = textures.get(texture).unwrap().read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(*pixel, (255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn priorities() {
+    let display = support::build_display();
+
+    let textures = Resources::new();
+
+    let mut buffers = Resources::new();
+    let low = buffers.insert(Buffer::new(&display, &[0u32; 4][..], BufferType::ArrayBuffer,
+                                         BufferMode::Default).unwrap().into());
+    let high = buffers.insert(Buffer::new(&display, &[0u32; 4][..], BufferType::ArrayBuffer,
+                                          BufferMode::Default).unwrap().into());
+
+    let mut scheduler = UploadScheduler::new();
+    scheduler.set_bytes_budget(Some(16));
+
+    let low = scheduler.upload_buffer(0, low, 0, vec![1u32, 2, 3, 4]);
+    let high = scheduler.upload_buffer(5, high, 8, vec![5u32, 6]);
+
+    assert_eq!(scheduler.process(&textures, &buffers).unwrap(), 16);
+    assert!(!scheduler.is_pending(high));
+    assert!(scheduler.is_pending(low));
+
+    assert_eq!(scheduler.process(&textures, &buffers).unwrap(), 8);
+    assert!(!scheduler.is_pending(low));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn cancel() {
+    let display = support::build_display();
+
+    let mut textures = Resources::new();
+    let texture = textures.insert(Texture2d::empty(&display, 1, 1).unwrap());
+    let buffers = Resources::new();
+
+    let mut scheduler = UploadScheduler::new();
+    let rect = glium::Rect { left: 0, bottom: 0, width: 1, height: 1 };
+    let upload = scheduler.upload_texture2d(0, texture, rect, vec![(0u8, 0u8, 0u8, 0u8)]);
+
+    assert!(scheduler.cancel(upload));
+    assert!(!scheduler.cancel(upload));
+    assert_eq!(scheduler.process(&textures, &buffers).unwrap(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_id_used_as_texture() {
+    let display = support::build_display();
+
+    let mut textures = Resources::new();
+    textures.insert(Texture2d::empty(&display, 1, 1).unwrap());
+
+    let mut buffers: Resources<BufferAny> = Resources::new();
+    let buffer = buffers.insert(Buffer::new(&display, &[0u32; 4][..], BufferType::ArrayBuffer,
+                                            BufferMode::Default).unwrap().into());
+
+    let mut scheduler = UploadScheduler::new();
+    let rect = glium::Rect { left: 0, bottom: 0, width: 1, height: 1 };
+    scheduler.upload_texture2d(0, buffer, rect, vec![(0u8, 0u8, 0u8, 0u8)]);
+
+    assert_eq!(scheduler.process(&textures, &buffers), Err(InvalidResourceId(buffer)));
+    assert_eq!(scheduler.len(), 0);
+
+    display.assert_no_error(None);
+}
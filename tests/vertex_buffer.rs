@@ -88,3 +88,26 @@ fn transform_feedback() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn empty_too_large() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let buffer = glium::VertexBuffer::<Vertex>::empty(&display, std::usize::MAX / 2);
+
+    match buffer {
+        Err(glium::vertex::BufferCreationError::BufferCreationError(
+            glium::buffer::BufferCreationError::OutOfMemory)) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!()
+    };
+
+    display.assert_no_error(None);
+}
@@ -112,6 +112,119 @@ fn multiple_buffers_source() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn multiple_buffers_source_rebind() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let (red, green) = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, color);
+
+        let build_buffer = |color| {
+            glium::VertexBuffer::new(&display,
+                &[
+                    Vertex { color: color },
+                    Vertex { color: color },
+                    Vertex { color: color },
+                    Vertex { color: color },
+                ]
+            ).unwrap()
+        };
+
+        (build_buffer([1.0, 0.0, 0.0]), build_buffer([0.0, 1.0, 0.0]))
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, &red), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+    // only the second buffer changes between the two draws
+    texture.as_surface().draw((&buffer1, &green), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn slice_draw_indices() {
     #[derive(Copy, Clone)]
@@ -659,3 +772,374 @@ fn per_instance_length_mismatch() {
     frame.finish().unwrap();
     display.assert_no_error(None);
 }
+
+#[test]
+fn same_format_different_buffers() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let buffer1 = glium::VertexBuffer::new(&display,
+        &[
+            Vertex { position: [-1.0,  1.0], color: [0.0, 1.0, 0.0] },
+            Vertex { position: [ 1.0,  1.0], color: [0.0, 1.0, 0.0] },
+            Vertex { position: [-1.0, -1.0], color: [0.0, 1.0, 0.0] },
+            Vertex { position: [ 1.0, -1.0], color: [0.0, 1.0, 0.0] },
+        ]
+    ).unwrap();
+
+    let buffer2 = glium::VertexBuffer::new(&display,
+        &[
+            Vertex { position: [-1.0,  1.0], color: [1.0, 0.0, 0.0] },
+            Vertex { position: [ 1.0,  1.0], color: [1.0, 0.0, 0.0] },
+            Vertex { position: [-1.0, -1.0], color: [1.0, 0.0, 0.0] },
+            Vertex { position: [ 1.0, -1.0], color: [1.0, 0.0, 0.0] },
+        ]
+    ).unwrap();
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&buffer1, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+    texture.as_surface().draw(&buffer2, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instancing_matrix() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-0.5,  0.5] },
+                Vertex { position: [ 0.5,  0.5] },
+                Vertex { position: [-0.5, -0.5] },
+                Vertex { position: [ 0.5, -0.5] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Instance {
+            matrix: [[f32; 4]; 4],
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Instance, matrix, color);
+
+        glium::vertex::VertexBuffer::new(&display,
+            &[
+                Instance {
+                    matrix: [
+                        [2.0, 0.0, 0.0, 0.0],
+                        [0.0, 2.0, 0.0, 0.0],
+                        [0.0, 0.0, 2.0, 0.0],
+                        [0.0, 0.0, 0.0, 1.0],
+                    ],
+                    color: [1.0, 0.0, 0.0],
+                },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = match buffer2.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            in vec2 position;
+            in mat4 matrix;
+            in vec3 color;
+
+            out vec3 v_color;
+
+            void main() {
+                gl_Position = matrix * vec4(position, 0.0, 1.0);
+                v_color = color;
+            }
+        ",
+        "
+            #version 330
+            in vec3 v_color;
+
+            void main() {
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, buffer2), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_builder() {
+    let display = support::build_display();
+
+    let positions = [[-1.0, 1.0], [1.0, 1.0], [-1.0, -1.0], [1.0, -1.0f32]];
+    let colors = [[255, 0, 0], [255, 0, 0], [255, 0, 0], [255, 0, 0u8]];
+
+    let vertex_buffer = glium::vertex::VertexBufferBuilder::new()
+                            .attribute("position", &positions)
+                            .normalized_attribute("color", &colors)
+                            .build(&display).unwrap();
+
+    assert_eq!(vertex_buffer.len(), 4);
+    assert_eq!(vertex_buffer.get_elements_size(), 12);
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_builder_length_mismatch() {
+    let display = support::build_display();
+
+    let positions = [[-1.0, 1.0], [1.0, 1.0], [-1.0, -1.0], [1.0, -1.0f32]];
+    let colors = [[1.0, 0.0, 0.0], [1.0, 0.0, 0.0f32]];
+
+    let result = glium::vertex::VertexBufferBuilder::new()
+                     .attribute("position", &positions)
+                     .attribute("color", &colors)
+                     .build(&display);
+
+    match result {
+        Err(glium::vertex::BuilderError::LengthMismatch) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_from_attribute() {
+    let display = support::build_display();
+
+    let positions = glium::VertexBuffer::from_attribute(&display, "position",
+        &[[-1.0, 1.0], [1.0, 1.0], [-1.0, -1.0], [1.0, -1.0f32]]).unwrap();
+    let colors = glium::VertexBuffer::from_attribute(&display, "color",
+        &[[1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 0.0f32]]).unwrap();
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&positions, &colors), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}